@@ -0,0 +1,177 @@
+/**
+ * Bun binding of the `mockito-ffi` C ABI.
+ *
+ * The library path defaults to the `MOCKITO_LIB` environment variable, or the
+ * release build of the repository.
+ *
+ * @example
+ * ```ts
+ * import { MocksController } from '@mockito/ffi-binding/bun';
+ *
+ * using controller = MocksController.loadProject('./mocks');
+ * controller.respond({ url: '/api/users/1', method: 'GET', transport: 'HTTP' });
+ * ```
+ */
+
+import { CString, dlopen, FFIType, type Pointer } from 'bun:ffi';
+import { type ActiveRoute, cString, libraryFileName, MockitoError, type Request, type Variant } from './types.ts';
+
+export * from './types.ts';
+
+const SYMBOLS = {
+  mockito_version: { args: [], returns: FFIType.ptr },
+  mockito_last_error: { args: [], returns: FFIType.ptr },
+  mockito_string_free: { args: [FFIType.ptr], returns: FFIType.void },
+  mockito_controller_load: { args: [FFIType.ptr, FFIType.ptr, FFIType.ptr, FFIType.ptr], returns: FFIType.ptr },
+  mockito_controller_load_project: { args: [FFIType.ptr], returns: FFIType.ptr },
+  mockito_controller_free: { args: [FFIType.ptr], returns: FFIType.void },
+  mockito_controller_use_collection: { args: [FFIType.ptr, FFIType.ptr], returns: FFIType.i32 },
+  mockito_controller_use_routes: { args: [FFIType.ptr, FFIType.ptr], returns: FFIType.i32 },
+  mockito_controller_reset_routes: { args: [FFIType.ptr], returns: FFIType.i32 },
+  mockito_controller_find_route: { args: [FFIType.ptr, FFIType.ptr], returns: FFIType.ptr },
+  mockito_controller_respond: { args: [FFIType.ptr, FFIType.ptr], returns: FFIType.ptr },
+} as const;
+
+type Library = ReturnType<typeof dlopen<typeof SYMBOLS>>['symbols'];
+
+let library: Library | undefined;
+
+/**
+ * Open the library, once per process.
+ *
+ * @param path - Library path, only used by the first call
+ */
+export function open(path?: string): Library {
+  library ??= dlopen(path ?? process.env['MOCKITO_LIB'] ?? defaultLibraryPath(), SYMBOLS).symbols;
+  return library;
+}
+
+function defaultLibraryPath(): string {
+  return new URL(`../../target/release/${libraryFileName(process.platform)}`, import.meta.url).pathname;
+}
+
+function lastError(lib: Library): MockitoError {
+  const message = lib.mockito_last_error();
+  return new MockitoError(message ? new CString(message).toString() : 'Unknown error');
+}
+
+/** Read and free a returned JSON string */
+function takeJson<T>(lib: Library, pointer: Pointer | null): T {
+  if (pointer === null) {
+    throw lastError(lib);
+  }
+  try {
+    return JSON.parse(new CString(pointer).toString()) as T;
+  } finally {
+    lib.mockito_string_free(pointer);
+  }
+}
+
+function check(lib: Library, status: number): void {
+  if (status !== 0) {
+    throw lastError(lib);
+  }
+}
+
+/** Library version */
+export function version(): string {
+  return new CString(open().mockito_version()!).toString();
+}
+
+/** Controller of active routes, matching requests like the mock server */
+export class MocksController implements Disposable {
+  #lib: Library;
+  #handle: Pointer | null;
+
+  private constructor(lib: Library, handle: Pointer) {
+    this.#lib = lib;
+    this.#handle = handle;
+  }
+
+  /**
+   * Load a controller from collection, route and optional group files
+   *
+   * @param collectionsPath - Path or glob pattern to collections file(s)
+   * @param routesPath - Path or glob pattern to routes file(s)
+   * @param defaultCollection - Optional default collection ID
+   * @param groupsPath - Optional path to route groups file
+   */
+  static load(
+    collectionsPath: string,
+    routesPath: string,
+    defaultCollection?: string,
+    groupsPath?: string
+  ): MocksController {
+    const lib = open();
+    const handle = lib.mockito_controller_load(
+      cString(collectionsPath),
+      cString(routesPath),
+      groupsPath === undefined ? null : cString(groupsPath),
+      defaultCollection === undefined ? null : cString(defaultCollection)
+    );
+    if (handle === null) {
+      throw lastError(lib);
+    }
+    return new MocksController(lib, handle);
+  }
+
+  /**
+   * Load a controller from a project directory or combined config file
+   *
+   * @param path - Project root directory or combined config file
+   */
+  static loadProject(path: string): MocksController {
+    const lib = open();
+    const handle = lib.mockito_controller_load_project(cString(path));
+    if (handle === null) {
+      throw lastError(lib);
+    }
+    return new MocksController(lib, handle);
+  }
+
+  /** Switch to a collection */
+  useCollection(collectionId: string): void {
+    check(this.#lib, this.#lib.mockito_controller_use_collection(this.#live(), cString(collectionId)));
+  }
+
+  /** Activate routes by `route:preset:variant` references on top of the collection */
+  useRoutes(routes: string[]): void {
+    check(this.#lib, this.#lib.mockito_controller_use_routes(this.#live(), cString(JSON.stringify(routes))));
+  }
+
+  /** Drop route overrides and go back to the routes of the collection */
+  resetRoutes(): void {
+    check(this.#lib, this.#lib.mockito_controller_reset_routes(this.#live()));
+  }
+
+  /** Find the active route matching a request, or `null` */
+  findRoute(request: Request): ActiveRoute | null {
+    const json = cString(JSON.stringify(request));
+    return takeJson(this.#lib, this.#lib.mockito_controller_find_route(this.#live(), json));
+  }
+
+  /** Find the active route matching a request and render its variant, or `null` */
+  respond(request: Request): Variant | null {
+    const json = cString(JSON.stringify(request));
+    return takeJson(this.#lib, this.#lib.mockito_controller_respond(this.#live(), json));
+  }
+
+  /** Release the controller, it can't be used afterwards */
+  close(): void {
+    if (this.#handle !== null) {
+      this.#lib.mockito_controller_free(this.#handle);
+      this.#handle = null;
+    }
+  }
+
+  [Symbol.dispose](): void {
+    this.close();
+  }
+
+  #live(): Pointer {
+    if (this.#handle === null) {
+      throw new MockitoError('Controller is closed');
+    }
+    return this.#handle;
+  }
+}
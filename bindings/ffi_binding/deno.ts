@@ -0,0 +1,179 @@
+/**
+ * Deno binding of the `mockito-ffi` C ABI.
+ *
+ * Requires `--allow-ffi`. The library path defaults to the `MOCKITO_LIB`
+ * environment variable, or the release build of the repository.
+ *
+ * @example
+ * ```ts
+ * import { MocksController } from '@mockito/ffi-binding/deno';
+ *
+ * using controller = MocksController.loadProject('./mocks');
+ * controller.respond({ url: '/api/users/1', method: 'GET', transport: 'HTTP' });
+ * ```
+ */
+
+import { type ActiveRoute, cString, libraryFileName, MockitoError, type Request, type Variant } from './types.ts';
+
+export * from './types.ts';
+
+const SYMBOLS = {
+  mockito_version: { parameters: [], result: 'pointer' },
+  mockito_last_error: { parameters: [], result: 'pointer' },
+  mockito_string_free: { parameters: ['pointer'], result: 'void' },
+  mockito_controller_load: { parameters: ['buffer', 'buffer', 'buffer', 'buffer'], result: 'pointer' },
+  mockito_controller_load_project: { parameters: ['buffer'], result: 'pointer' },
+  mockito_controller_free: { parameters: ['pointer'], result: 'void' },
+  mockito_controller_use_collection: { parameters: ['pointer', 'buffer'], result: 'i32' },
+  mockito_controller_use_routes: { parameters: ['pointer', 'buffer'], result: 'i32' },
+  mockito_controller_reset_routes: { parameters: ['pointer'], result: 'i32' },
+  mockito_controller_find_route: { parameters: ['pointer', 'buffer'], result: 'pointer' },
+  mockito_controller_respond: { parameters: ['pointer', 'buffer'], result: 'pointer' },
+} as const;
+
+type Library = Deno.DynamicLibrary<typeof SYMBOLS>['symbols'];
+
+let library: Library | undefined;
+
+/**
+ * Open the library, once per process.
+ *
+ * @param path - Library path, only used by the first call
+ */
+export function open(path?: string): Library {
+  library ??= Deno.dlopen(
+    path ?? Deno.env.get('MOCKITO_LIB') ?? defaultLibraryPath(),
+    SYMBOLS
+  ).symbols;
+  return library;
+}
+
+function defaultLibraryPath(): string {
+  return new URL(`../../target/release/${libraryFileName(Deno.build.os)}`, import.meta.url).pathname;
+}
+
+function lastError(lib: Library): MockitoError {
+  const message = lib.mockito_last_error();
+  return new MockitoError(message ? Deno.UnsafePointerView.getCString(message) : 'Unknown error');
+}
+
+/** Read and free a returned JSON string */
+function takeJson<T>(lib: Library, pointer: Deno.PointerValue): T {
+  if (pointer === null) {
+    throw lastError(lib);
+  }
+  try {
+    return JSON.parse(Deno.UnsafePointerView.getCString(pointer)) as T;
+  } finally {
+    lib.mockito_string_free(pointer);
+  }
+}
+
+function check(lib: Library, status: number): void {
+  if (status !== 0) {
+    throw lastError(lib);
+  }
+}
+
+/** Library version */
+export function version(): string {
+  return Deno.UnsafePointerView.getCString(open().mockito_version()!);
+}
+
+/** Controller of active routes, matching requests like the mock server */
+export class MocksController implements Disposable {
+  #lib: Library;
+  #handle: Deno.PointerValue;
+
+  private constructor(lib: Library, handle: Deno.PointerValue) {
+    this.#lib = lib;
+    this.#handle = handle;
+  }
+
+  /**
+   * Load a controller from collection, route and optional group files
+   *
+   * @param collectionsPath - Path or glob pattern to collections file(s)
+   * @param routesPath - Path or glob pattern to routes file(s)
+   * @param defaultCollection - Optional default collection ID
+   * @param groupsPath - Optional path to route groups file
+   */
+  static load(
+    collectionsPath: string,
+    routesPath: string,
+    defaultCollection?: string,
+    groupsPath?: string
+  ): MocksController {
+    const lib = open();
+    const handle = lib.mockito_controller_load(
+      cString(collectionsPath),
+      cString(routesPath),
+      groupsPath === undefined ? null : cString(groupsPath),
+      defaultCollection === undefined ? null : cString(defaultCollection)
+    );
+    if (handle === null) {
+      throw lastError(lib);
+    }
+    return new MocksController(lib, handle);
+  }
+
+  /**
+   * Load a controller from a project directory or combined config file
+   *
+   * @param path - Project root directory or combined config file
+   */
+  static loadProject(path: string): MocksController {
+    const lib = open();
+    const handle = lib.mockito_controller_load_project(cString(path));
+    if (handle === null) {
+      throw lastError(lib);
+    }
+    return new MocksController(lib, handle);
+  }
+
+  /** Switch to a collection */
+  useCollection(collectionId: string): void {
+    check(this.#lib, this.#lib.mockito_controller_use_collection(this.#live(), cString(collectionId)));
+  }
+
+  /** Activate routes by `route:preset:variant` references on top of the collection */
+  useRoutes(routes: string[]): void {
+    check(this.#lib, this.#lib.mockito_controller_use_routes(this.#live(), cString(JSON.stringify(routes))));
+  }
+
+  /** Drop route overrides and go back to the routes of the collection */
+  resetRoutes(): void {
+    check(this.#lib, this.#lib.mockito_controller_reset_routes(this.#live()));
+  }
+
+  /** Find the active route matching a request, or `null` */
+  findRoute(request: Request): ActiveRoute | null {
+    const json = cString(JSON.stringify(request));
+    return takeJson(this.#lib, this.#lib.mockito_controller_find_route(this.#live(), json));
+  }
+
+  /** Find the active route matching a request and render its variant, or `null` */
+  respond(request: Request): Variant | null {
+    const json = cString(JSON.stringify(request));
+    return takeJson(this.#lib, this.#lib.mockito_controller_respond(this.#live(), json));
+  }
+
+  /** Release the controller, it can't be used afterwards */
+  close(): void {
+    if (this.#handle !== null) {
+      this.#lib.mockito_controller_free(this.#handle);
+      this.#handle = null;
+    }
+  }
+
+  [Symbol.dispose](): void {
+    this.close();
+  }
+
+  #live(): Deno.PointerValue {
+    if (this.#handle === null) {
+      throw new MockitoError('Controller is closed');
+    }
+    return this.#handle;
+  }
+}
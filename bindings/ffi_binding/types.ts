@@ -0,0 +1,63 @@
+/**
+ * Types shared by the Bun and Deno bindings of the `mockito-ffi` C ABI.
+ */
+
+export type HttpMethod = 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE' | 'HEAD' | 'OPTIONS';
+
+export type Transport = 'HTTP' | 'WEBSOCKET';
+
+/** Request matched against active routes */
+export interface Request {
+  /** Request URL (path + query string) */
+  url: string;
+  /** HTTP method (required for HTTP routes) */
+  method?: HttpMethod;
+  transport: Transport;
+  headers?: Record<string, string>;
+  /** Query parameters (parsed from URL if not provided) */
+  query?: Record<string, string>;
+  payload?: unknown;
+}
+
+/** Response variant, as written in route files */
+export interface Variant {
+  id: string;
+  status?: number;
+  headers?: Record<string, string>;
+  body?: unknown;
+  template?: 'handlebars' | 'liquid' | 'interpolation';
+}
+
+/** Route with selected preset and variant */
+export interface ActiveRoute {
+  route: { id: string; url: string; transport: Transport; method?: HttpMethod; [field: string]: unknown };
+  preset: { id: string; [field: string]: unknown };
+  variant: Variant;
+}
+
+/** Error thrown when a call into the library fails */
+export class MockitoError extends Error {
+  override name = 'MockitoError';
+}
+
+/**
+ * File name of the library built by `cargo build -p mockito-ffi`.
+ *
+ * @param os - Operating system as reported by the runtime, e.g. `linux`, `darwin` or `windows`
+ */
+export function libraryFileName(os: string): string {
+  switch (os) {
+    case 'darwin':
+      return 'libmockito.dylib';
+    case 'windows':
+    case 'win32':
+      return 'mockito.dll';
+    default:
+      return 'libmockito.so';
+  }
+}
+
+/** Encode a string argument as a NUL-terminated UTF-8 buffer */
+export function cString(value: string): Uint8Array {
+  return new TextEncoder().encode(`${value}\0`);
+}
@@ -1,25 +1,111 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/** Subscription to controller events, see `MocksController.onChange()`, `on()` and `onLive()` */
+export declare class ChangeSubscription {
+  /** Stop receiving events. Returns `false` if already unsubscribed. */
+  unsubscribe(): boolean
+}
+
+/** Opaque captured controller state, see `MocksController.snapshot()` */
+export declare class ControllerSnapshot {
+  /** Active collection ID at capture time */
+  get collection(): string | null
+}
+
 export declare class MocksController {
   /**
    * Create a new controller manager
    *
    * @param collectionsPath - Path or glob pattern to collections file(s)
+   * @param routesPath - Path or glob pattern to routes file(s), comma-separated patterns prefixed with `!` exclude files
+   * @param defaultCollection - Optional default collection ID
+   * @param groupsPath - Optional path to route groups file
+   */
+  constructor(collectionsPath: string, routesPath: string, defaultCollection?: string | undefined | null, groupsPath?: string | undefined | null)
+  /**
+   * Create a controller from a project directory or combined config file
+   *
+   * Discovers `routes/**`, `fixtures/**`, `collections/**` and `groups/**` files under a directory
+   * and activates the default collection from the `mockito.config.*` settings file.
+   *
+   * @param path - Project root directory or combined config file
+   * @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+   */
+  static fromProject(path: string, duplicatePolicy?: DuplicatePolicy | undefined | null): MocksController
+  /**
+   * Create a controller serving the routes of a mocks manager
+   *
+   * The controller works on a copy, so later changes to the manager don't affect it.
+   * Together with `MocksManager.create()` this defines mocks inline without config files.
+   *
+   * @param manager - Mocks manager with routes and collections
+   * @param defaultCollection - Optional default collection ID
+   */
+  static fromManager(manager: MocksManager, defaultCollection?: string | undefined | null): MocksController
+  /**
+   * Load a controller on a background thread
+   *
+   * Same as the constructor, without blocking the event loop while files are parsed.
+   *
+   * @param collectionsPath - Path or glob pattern to collections file(s)
    * @param routesPath - Path or glob pattern to routes file(s)
    * @param defaultCollection - Optional default collection ID
+   * @param groupsPath - Optional path to route groups file
+   */
+  static load(collectionsPath: string, routesPath: string, defaultCollection?: string | undefined | null, groupsPath?: string | undefined | null): Promise<MocksController>
+  /**
+   * Load a controller from a project directory or combined config file on a background thread
+   *
+   * Same as `fromProject`, without blocking the event loop while files are parsed.
+   *
+   * @param path - Project root directory or combined config file
+   * @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+   */
+  static loadProject(path: string, duplicatePolicy?: DuplicatePolicy | undefined | null): Promise<MocksController>
+  /**
+   * Share the controller with worker threads.
+   *
+   * Pass the returned handle to a worker (e.g. via `workerData`) and call
+   * `MocksController.fromShared()` there. All handles use the same loaded routes
+   * and state, so route switches in one thread are seen by all. Route handlers
+   * and `on()` subscriptions stay with the handle they were registered on.
+   *
+   * The handle is valid while any controller using it is alive.
+   *
+   * @returns Handle of the shared controller
+   */
+  share(): number
+  /**
+   * Use a controller shared by `share()`, e.g. from a worker thread.
+   *
+   * @param handle - Handle returned by `share()`
+   * @throws Error if no controller with the handle is alive
+   */
+  static fromShared(handle: number): MocksController
+  /**
+   * Reload routes and collections from files.
+   *
+   * Re-resolves the current collection and re-applies route overrides that are
+   * still compatible with the new data. If loading or resolution fails, the
+   * controller keeps its previous state.
+   *
+   * @param collectionsPath - Path or glob pattern to collections file(s)
+   * @param routesPath - Path or glob pattern to routes file(s), comma-separated patterns prefixed with `!` exclude files
+   * @param groupsPath - Optional path to route groups file
    */
-  constructor(collectionsPath: string, routesPath: string, defaultCollection?: string | undefined | null)
+  reload(collectionsPath: string, routesPath: string, groupsPath?: string | undefined | null): void
   /** Apply a collection by ID */
   useCollection(collectionId: string): void
   /**
    * Apply specific HTTP routes without changing the entire collection.
    *
    * This method allows dynamic route switching by:
-   * - Resolving provided route references (`route:preset:variant`)
+   * - Resolving provided route references (`route:preset:variant`),
+   *   expanding wildcard references like `payments-*:default:timeout`
    * - Merging them with existing active routes
    * - Overriding routes with the same route ID
    *
-   * @param routes - Array of route reference strings in format `route_id:preset_id:variant_id`
+   * @param routes - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
    * @throws Error if route, preset, or variant not found
    * @throws Error if route is a WebSocket route (use useSocket instead)
    */
@@ -28,26 +114,350 @@ export declare class MocksController {
    * Apply specific WebSocket routes without changing the entire collection.
    *
    * This method allows dynamic WebSocket route switching by:
-   * - Resolving provided route references (`route:preset:variant`)
+   * - Resolving provided route references (`route:preset:variant`),
+   *   expanding wildcard references like `ws-*:default:error`
    * - Merging them with existing active routes
    * - Overriding routes with the same route ID
    *
-   * @param routes - Array of route reference strings in format `route_id:preset_id:variant_id`
+   * @param routes - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
    * @throws Error if route, preset, or variant not found
    * @throws Error if route is not a WebSocket route (use useRoutes instead)
    */
   useSocket(routes: Array<string>): void
+  /**
+   * Apply a preset and variant to every route tagged with `tag`.
+   *
+   * @param tag - Route tag (e.g. "payments")
+   * @param presetId - Preset to activate on each tagged route
+   * @param variantId - Variant to activate on each tagged route
+   * @throws Error if no route has the tag or a tagged route lacks the preset/variant
+   */
+  useTag(tag: string, presetId: string, variantId: string): void
+  /**
+   * Deactivate every active route tagged with `tag`.
+   *
+   * @param tag - Route tag (e.g. "payments")
+   * @throws Error if no route has the tag
+   */
+  disableTag(tag: string): void
+  /**
+   * Disable routes by ID so they are skipped during matching.
+   *
+   * Routes stay disabled across collection switches until enabled again
+   * or `resetRoutes` is called.
+   *
+   * @param routeIds - IDs of routes to disable
+   * @throws Error if any route is not found
+   */
+  disableRoutes(routeIds: Array<string>): void
+  /**
+   * Enable routes previously disabled via `disableRoutes`.
+   *
+   * @param routeIds - IDs of routes to enable
+   * @throws Error if any route is not found
+   */
+  enableRoutes(routeIds: Array<string>): void
+  /** Get IDs of disabled routes, sorted */
+  getDisabledRoutes(): Array<string>
   /**
    * Reset routes to collection defaults or clear all routes.
    *
    * If a collection is selected, restores routes to the collection's initial state.
    * If no collection is selected, clears all routes (empty state).
+   * Disabled routes are enabled again in both cases.
    */
   resetRoutes(): void
+  /**
+   * Cache the route matched by repeated identical requests.
+   *
+   * Overrides the `match_cache` setting. Cached matches are dropped on every
+   * route change. Routes matching by expression disable the cache.
+   *
+   * @param capacity - Number of distinct requests to cache, `null` or 0 disables caching
+   */
+  setMatchCache(capacity?: number | undefined | null): void
+  /**
+   * Report memory used by loaded definitions and cached fixture files.
+   *
+   * Definition sizes are estimated from their serialized size.
+   */
+  memoryUsage(): MemoryUsage
+  /**
+   * Limit the memory kept for fixture file contents.
+   *
+   * Overrides the `fixture_cache` setting. Least recently served fixtures are
+   * evicted beyond the limit and read from disk again when next served.
+   *
+   * @param bytes - Size limit, 0 reads fixtures on every use
+   * @throws Error if the limit is negative
+   */
+  setFixtureCacheLimit(bytes: number): void
+  /**
+   * Create an isolated child controller.
+   *
+   * The child shares loaded routes and collections with this controller and starts
+   * from its current state, but overrides applied to either don't affect the other.
+   * Useful for giving each parallel test worker its own controller.
+   */
+  fork(): MocksController
+  /**
+   * Capture the current controller state (active collection, route overrides and
+   * request history).
+   *
+   * Use with `restore` to roll back changes and requests made during a test.
+   * Coverage hit counts are cumulative and the audit log is append-only, so
+   * neither is captured.
+   */
+  snapshot(): ControllerSnapshot
+  /**
+   * Restore controller state from a snapshot.
+   *
+   * A snapshot can be restored multiple times.
+   */
+  restore(snapshot: ControllerSnapshot): void
   /** Get current collection ID */
   get currentCollection(): string | null
+  /** Get project settings (empty for controllers not created with `fromProject`) */
+  get settings(): Settings
   /** Get all active routes (HTTP + WS) */
   getActiveRoutes(): Array<ActiveRoute>
+  /**
+   * Find the active route matching a request.
+   *
+   * Lookup doesn't lock the controller, so it never waits for concurrent
+   * route switching and always sees a consistent set of routes.
+   *
+   * @returns Matching route or `null` if no route matches
+   */
+  findRoute(request: Request): ActiveRoute | null
+  /**
+   * Find the active route matching a request and render its variant.
+   *
+   * Templates in variant headers and body are rendered against the request,
+   * with the project `template` engine if the variant has none. Routes with a
+   * handler registered by `onRoute()` are answered by the handler instead.
+   * Bodies of `file:` fixtures and inline `file:data:` bodies are loaded into `bodyBuffer`.
+   * The request is recorded in the history even if answering it fails.
+   *
+   * @returns Rendered variant or `null` if no route matches
+   * @throws Error if a template fails to render, a fixture can't be read or a handler throws
+   */
+  respond(request: Request): Variant | null
+  /**
+   * Build the 404 response of a request that matched no route.
+   *
+   * The body holds an `error` message. With the `debug` setting enabled, it
+   * also lists the routes closest to matching the request in `near_matches`,
+   * with the condition each of them failed.
+   */
+  notFound(request: Request): Variant
+  /**
+   * Get requests looked up by `respond()` or `findRoute()`, oldest first.
+   *
+   * Only the latest requests are kept, see the `history` setting. Handles of
+   * `share()` record into the same history, forks into their own.
+   */
+  getRequests(): Array<RecordedRequest>
+  /**
+   * Get recorded requests that matched a route, oldest first.
+   *
+   * @param routeId - Route ID
+   */
+  getRequestsFor(routeId: string): Array<RecordedRequest>
+  /** Remove all requests from the request history, with their serve times. */
+  clearHistory(): void
+  /**
+   * Verify the recorded requests that matched a route.
+   *
+   * The verification covers requests recorded up to this call.
+   *
+   * @param routeId - Route ID
+   */
+  verify(routeId: string): Verification
+  /**
+   * Check that every recorded request matched a route.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing the unmatched `requests`
+   */
+  verifyNoUnmatched(): void
+  /**
+   * Get requests that matched no route, grouped by transport, method and URL path.
+   *
+   * Unmatched requests are captured even if the request history is disabled,
+   * and removed by `clearHistory()`.
+   */
+  getUnmatched(): Array<UnmatchedRequest>
+  /**
+   * Render route skeletons answering the requests that matched no route.
+   *
+   * YAML reports hold one route document per request, commented with the
+   * request and its count, ready to paste into a routes file. JSON reports
+   * hold an array of routes.
+   *
+   * @param format - Format of the report, YAML if not set
+   */
+  unmatchedReport(format?: ExportFormat | undefined | null): string
+  /**
+   * Render the request history with the served responses as a HAR log, in JSON.
+   *
+   * The log opens in browser devtools and HAR viewers. Entries carry the
+   * matched `_routeId`, `_presetId` and `_variantId`; requests that matched
+   * no route are exported with a 404 response.
+   *
+   * ```js
+   * fs.writeFileSync('traffic.har', controller.exportHar());
+   * ```
+   */
+  exportHar(): string
+  /**
+   * Get serve time percentiles of the routes that answered requests.
+   *
+   * Serve times cover route lookup, response rendering and the injected
+   * `delay`, so a perf test can tell mock latency from client-side time.
+   * Unlike the request history they aggregate every request since the last
+   * `clearHistory()`.
+   */
+  latencyStats(): Array<RouteLatency>
+  /**
+   * Report the routes, presets and variants that answered requests so far.
+   *
+   * Hit counts are kept by `clearHistory()` and reloads, so they cover a
+   * whole test run:
+   *
+   * ```js
+   * afterAll(() => expect(controller.coverageReport().untouched).toEqual([]));
+   * ```
+   */
+  coverageReport(): CoverageReport
+  /** Reset the hit counts of `coverageReport()`. */
+  resetCoverage(): void
+  /**
+   * Change the number of requests kept in the request history.
+   *
+   * Overrides the `history` setting. The oldest requests beyond the new
+   * capacity are dropped.
+   *
+   * @param capacity - Number of kept requests, `null` restores the default of 1000 and 0 disables recording
+   */
+  setHistoryCapacity(capacity?: number | undefined | null): void
+  /**
+   * Answer requests to a route with a JS handler instead of its static variant.
+   *
+   * `respond()` calls the handler synchronously with the request context
+   * (`method`, `url`, `params`, `query`, `headers`, `payload`) whenever the route
+   * matches, whichever preset and variant are active. The returned response
+   * replaces the variant, keeping its ID; returning `undefined` falls back to
+   * the variant. Registering again replaces the previous handler.
+   *
+   * @param routeId - Route ID
+   * @param handler - Function receiving the request context and returning a `RouteResponse`
+   */
+  onRoute(routeId: string, handler: (request: any) => RouteResponse | undefined): void
+  /**
+   * Remove the handler of a route registered by `onRoute()`.
+   *
+   * @returns `false` if the route had no handler
+   */
+  offRoute(routeId: string): boolean
+  /**
+   * Subscribe to controller state changes.
+   *
+   * The callback is invoked asynchronously on the event loop after each change
+   * (collection switches, route overrides, resets, reloads, ...), so it may call
+   * controller methods itself. Subscriptions don't keep the process alive.
+   *
+   * @param callback - Function receiving `ControllerEvent`s
+   */
+  onChange(callback: ((arg: ControllerEvent) => void)): ChangeSubscription
+  /**
+   * Subscribe to an event.
+   *
+   * - `request`: a request looked up by `respond()` or `findRoute()` matched a route
+   * - `unmatched`: a looked up request matched no route
+   * - `collectionChanged`: another collection was activated
+   * - `reloaded`: routes and collections were reloaded
+   *
+   * Like `onChange()`, callbacks are invoked asynchronously on the event loop
+   * and subscriptions don't keep the process alive.
+   *
+   * @param event - Event name
+   * @param callback - Function receiving a `RequestEvent` (`request`, `unmatched`) or `ControllerEvent`
+   */
+  on(event: 'request' | 'unmatched', callback: (event: RequestEvent) => void): ChangeSubscription
+  on(event: 'collectionChanged' | 'reloaded', callback: (event: ControllerEvent) => void): ChangeSubscription
+  /**
+   * Stream every request looked up from now on as a JSON live event.
+   *
+   * Meant for an admin WebSocket at `/__admin/live` feeding a dashboard with
+   * the traffic hitting the mock: each frame holds the request, the matched
+   * route, preset and variant, the response status and the serve time.
+   * Events are streamed regardless of the history capacity, and frames are
+   * dropped if the callback falls behind. Subscriptions don't keep the
+   * process alive.
+   *
+   * ```js
+   * controller.onLive((frame) => socket.send(frame));
+   * ```
+   *
+   * @param callback - Function receiving JSON text frames
+   */
+  onLive(callback: ((arg: string) => void)): ChangeSubscription
+  /**
+   * Label state changes applied from now on in the audit log.
+   *
+   * Set it to the name of the running test, so changes leaking into later
+   * tests can be traced back to it.
+   *
+   * ```js
+   * beforeEach(() => controller.setAuditLabel(expect.getState().currentTestName));
+   * ```
+   *
+   * @param label - Label of later changes, `null` removes it
+   */
+  setAuditLabel(label?: string | undefined | null): void
+  /**
+   * Get the state changes applied so far, oldest first.
+   *
+   * Every change delivered to `onChange()` callbacks is recorded with its
+   * time and the label set by `setAuditLabel()`. Handles of `share()` share
+   * the log and it's kept on reload, forks start with an empty one.
+   */
+  getAuditLog(): Array<AuditEntry>
+  /** Remove all state changes from the audit log, keeping the label. */
+  clearAuditLog(): void
+  /**
+   * Wait for the next request looked up by `respond()` or `findRoute()`.
+   *
+   * Lets tests await a request made by the code under test, e.g. the next call to
+   * a route, instead of polling. Use `on('request')` to receive every request.
+   *
+   * @param routeId - Only resolve for a request matching this route (default: any request, matched or not)
+   * @param timeoutMs - Reject if no request arrives in time (default: 5000)
+   * @throws Error with code `TIMEOUT` if no request arrives in time
+   */
+  nextRequest(routeId?: string | undefined | null, timeoutMs?: number | undefined | null): Promise<RequestEvent>
+  /**
+   * Find all active routes matching a request, in priority order.
+   *
+   * The first route is the one returned by `findRoute`, the rest are shadowed by it.
+   * Like `findRoute`, lookup doesn't lock the controller.
+   */
+  findRoutes(request: Request): Array<ActiveRoute>
+  /**
+   * Report preset expressions that are invalid or fail to evaluate for a request.
+   *
+   * Routes with a failing expression never match, so this explains why `findRoute`
+   * skipped them.
+   */
+  diagnose(request: Request): Array<ExpressionDiagnostic>
+  /**
+   * Explain how every active route matches a request.
+   *
+   * Reports the first condition each route fails, with expected and actual
+   * values, in priority order. Routes failing at a later `MatchStep` are closer
+   * matches, so test failures can point at the route a request almost hit.
+   */
+  explain(request: Request): Array<RouteExplanation>
 }
 
 /** Mocks Manager class */
@@ -56,12 +466,182 @@ export declare class MocksManager {
    * Create a new mocks manager
    *
    * @param collectionsPath - Path or glob pattern to collections file(s)
+   * @param routesPath - Path or glob pattern to routes file(s), comma-separated patterns prefixed with `!` exclude files
+   * @param groupsPath - Optional path to route groups file
+   */
+  constructor(collectionsPath: string, routesPath: string, groupsPath?: string | undefined | null)
+  /**
+   * Create a mocks manager from a project directory or combined config file
+   *
+   * Discovers `routes/**`, `fixtures/**`, `collections/**` and `groups/**` files under a directory.
+   *
+   * @param path - Project root directory or combined config file
+   * @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+   */
+  static fromProject(path: string, duplicatePolicy?: DuplicatePolicy | undefined | null): MocksManager
+  /**
+   * Load a mocks manager on a background thread
+   *
+   * Same as the constructor, without blocking the event loop while files are parsed.
+   *
+   * @param collectionsPath - Path or glob pattern to collections file(s)
    * @param routesPath - Path or glob pattern to routes file(s)
-   * @param basePath - Optional base directory for resolving relative paths
+   * @param groupsPath - Optional path to route groups file
+   */
+  static load(collectionsPath: string, routesPath: string, groupsPath?: string | undefined | null): Promise<MocksManager>
+  /**
+   * Load a mocks manager from a project directory or combined config file on a background thread
+   *
+   * Same as `fromProject`, without blocking the event loop while files are parsed.
+   *
+   * @param path - Project root directory or combined config file
+   * @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+   */
+  static loadProject(path: string, duplicatePolicy?: DuplicatePolicy | undefined | null): Promise<MocksManager>
+  /**
+   * Create an empty mocks manager
+   *
+   * Routes and collections are added with `addRoute()` and `addCollection()`,
+   * so mocks can be defined inline without config files.
+   *
+   * @param duplicatePolicy - Handling of IDs added several times (default: Override)
+   */
+  static create(duplicatePolicy?: DuplicatePolicy | undefined | null): MocksManager
+  /**
+   * Add a route
+   *
+   * An existing route with the same ID is handled by the duplicate policy.
+   * Run `validate()` to check added routes like loaded ones.
+   *
+   * @throws Error if the ID is taken and the duplicate policy is `Error`
+   */
+  addRoute(route: Route): void
+  /**
+   * Add a collection
+   *
+   * An existing collection with the same ID is handled by the duplicate policy.
+   *
+   * @throws Error if the ID is taken and the duplicate policy is `Error`
    */
-  constructor(collectionsPath: string, routesPath: string)
+  addCollection(collection: Collection): void
   /** Resolve collection with inheritance and return active routes */
   resolveCollection(collectionId: string): Array<ActiveRoute>
+  /** List all collections, sorted by collection ID */
+  listCollections(): Array<Collection>
+  /** List all routes, sorted by route ID */
+  listRoutes(): Array<Route>
+  /** Get a route by ID, or `null` if not found */
+  getRoute(routeId: string): Route | null
+  /** Get a route group by ID, or `null` if not found */
+  getGroup(groupId: string): Group | null
+  /**
+   * Get the inheritance chain of a collection.
+   *
+   * Returns collection IDs ordered from the root ancestor down to the given collection.
+   *
+   * @throws Error if a collection in the chain is not found or the chain is circular
+   */
+  collectionTree(collectionId: string): Array<string>
+  /**
+   * Write all routes, collections and groups to config files in a directory.
+   *
+   * Files are written as `routes/<id>`, `collections/<id>` and `groups/<id>`,
+   * so the directory can be loaded back with `MocksManager.fromProject`.
+   *
+   * @param dir - Output directory
+   * @param format - Format of written files
+   * @returns Paths of written files
+   */
+  export(dir: string, format: ExportFormat): Array<string>
+  /**
+   * Find likely mistakes in definitions that resolve fine.
+   *
+   * Reports shadowed presets, routes not referenced by any collection,
+   * redirects without a `Location` header and expressions that don't compile.
+   * Warnings of managers loaded from a project name the file of the definition.
+   */
+  lint(): Array<LintWarning>
+  /**
+   * Validate all collections and routes in one pass.
+   *
+   * Managers loaded from a project check templates with the engine and partials
+   * of its settings, and issues name the file of the definition.
+   * Returns an empty array if no issues were found.
+   */
+  validate(): Array<ValidationIssue>
+  /**
+   * Watch the files the manager was loaded from and apply their changes.
+   *
+   * Changes of project route, collection and group files replace only the
+   * definitions of the changed files, other changes reload all files. Files that
+   * fail to load keep their previous definitions. The callback receives a
+   * `ManagerWatchEvent` on the event loop after each batch of changes, e.g. to
+   * reload a controller with `MocksController.fromManager()`. Subscriptions don't
+   * keep the process alive.
+   *
+   * @param callback - Function receiving `ManagerWatchEvent`s
+   * @param intervalMs - Polling interval in milliseconds (default: 500)
+   * @throws Error if the manager wasn't loaded from files or they fail to load
+   */
+  watch(callback: ((arg: ManagerWatchEvent) => void), intervalMs?: number | undefined | null): WatchSubscription
+}
+
+/**
+ * Check of the requests recorded for a route, see `MocksController.verify()`
+ *
+ * Conditions return a new verification, so a base verification can be reused:
+ *
+ * ```js
+ * const users = controller.verify('users');
+ * users.times(2);
+ * users.withQuery({ page: '2' }).once();
+ * ```
+ */
+export declare class Verification {
+  /** Only count requests with the given headers, compared case-insensitively by name */
+  withHeaders(headers: Record<string, string>): Verification
+  /** Only count requests with the given query parameters */
+  withQuery(query: Record<string, string>): Verification
+  /** Only count requests with the given payload, a subset of it for objects */
+  withPayload(payload: any): Verification
+  /** Number of recorded requests satisfying the conditions */
+  count(): number
+  /**
+   * Check that the route was requested exactly `count` times.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+   */
+  times(count: number): void
+  /**
+   * Check that the route was requested at least `count` times.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+   */
+  atLeast(count: number): void
+  /**
+   * Check that the route was requested at most `count` times.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+   */
+  atMost(count: number): void
+  /**
+   * Check that the route was requested exactly once.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+   */
+  once(): void
+  /**
+   * Check that the route was never requested.
+   *
+   * @throws Error with code `VERIFICATION_FAILED` listing the requests of the route
+   */
+  never(): void
+}
+
+/** Subscription to config file changes, see `watchFiles()` and `MocksManager.watch()` */
+export declare class WatchSubscription {
+  /** Stop watching. Calling it more than once has no effect. */
+  unsubscribe(): void
 }
 
 export interface ActiveRoute {
@@ -70,11 +650,179 @@ export interface ActiveRoute {
   variant: Variant
 }
 
+/** State change recorded in the audit log, see `MocksController.getAuditLog()` */
+export interface AuditEntry {
+  /** Time the change was applied, in milliseconds since the Unix epoch */
+  timestamp: number
+  /** Label set by `setAuditLabel()` when the change was applied */
+  label?: string
+  event: ControllerEvent
+}
+
+/** Body size limits in bytes, unlimited if not set */
+export interface BodyLimits {
+  /** Maximum size of request bodies */
+  request?: number
+  /** Maximum size of response bodies */
+  response?: number
+}
+
+/** Kind of file change */
+export declare const enum ChangeKind {
+  Added = 0,
+  Modified = 1,
+  Removed = 2
+}
+
 /** Collection of routes */
 export interface Collection {
   id: string
   from?: string
   routes: Array<string>
+  /** Variables substituted into route URLs, response headers and bodies via `{{vars.name}}` */
+  vars?: Record<string, any>
+}
+
+/** Kind of config file described by a JSON Schema */
+export declare const enum ConfigFileKind {
+  Routes = 0,
+  Collections = 1,
+  Groups = 2,
+  Settings = 3,
+  Project = 4
+}
+
+/** Controller state change delivered to `MocksController.onChange()` callbacks */
+export interface ControllerEvent {
+  kind: ControllerEventKind
+  /** Activated collection ID (`CollectionChanged`) */
+  collectionId?: string
+  /** Affected route IDs (`RoutesOverridden`, `RoutesDisabled`, `RoutesEnabled`) */
+  routeIds?: Array<string>
+  /** Affected tag (`TagDisabled`) */
+  tag?: string
+}
+
+/** Kind of controller state change */
+export declare const enum ControllerEventKind {
+  CollectionChanged = 0,
+  RoutesOverridden = 1,
+  TagDisabled = 2,
+  RoutesDisabled = 3,
+  RoutesEnabled = 4,
+  RoutesReset = 5,
+  Reloaded = 6,
+  Restored = 7
+}
+
+/** Event subscribed to by `MocksController.on()` */
+export type ControllerEventName = 'request' | 'unmatched' | 'collectionChanged' | 'reloaded'
+
+/** Routes, presets and variants that answered requests, see `MocksController.coverageReport()` */
+export interface CoverageReport {
+  /** Number of variants that answered a request */
+  covered: number
+  /** Number of defined variants */
+  total: number
+  /**
+   * Never matched `route`, `route:preset` and `route:preset:variant` references,
+   * most general first
+   */
+  untouched: Array<string>
+  /** Hit counts of all routes, sorted by ID */
+  routes: Array<RouteCoverage>
+}
+
+/** CORS settings */
+export interface CorsSettings {
+  /** Allowed origins ("*" allows any origin) */
+  origins: Array<string>
+  /** Allowed request headers, any header is allowed if empty */
+  headers: Array<string>
+  /** Allow credentials (cookies, authorization headers) */
+  credentials: boolean
+}
+
+/** How definitions reusing an already loaded ID are handled */
+export declare const enum DuplicatePolicy {
+  /** Replace the loaded definition */
+  Override = 0,
+  /** Fail loading */
+  Error = 1,
+  /** Unite presets and variants of routes, replace other definitions */
+  Merge = 2
+}
+
+/** Kind of a thrown error, set as its `code` */
+export type ErrorCode =
+  'COLLECTION_NOT_FOUND' |
+  'ROUTE_NOT_FOUND' |
+  'PRESET_NOT_FOUND' |
+  'VARIANT_NOT_FOUND' |
+  'GROUP_NOT_FOUND' |
+  'TAG_NOT_FOUND' |
+  'INVALID_ROUTE_REFERENCE' |
+  'CIRCULAR_DEPENDENCY' |
+  'TRANSPORT_MISMATCH' |
+  'DUPLICATE_ID' |
+  'CONFIG_PARSE' |
+  'CONFIG_SCHEMA' |
+  'CONFIG_INVALID' |
+  'IO' |
+  'TEMPLATE' |
+  'EXPRESSION_INVALID' |
+  'EXPRESSION_EVALUATION' |
+  'SHARED_CONTROLLER_NOT_FOUND' |
+  'TIMEOUT' |
+  'NOT_WATCHABLE' |
+  'INVALID_ARGUMENT' |
+  'VERIFICATION_FAILED' |
+  'TRACING_INSTALLED'
+
+/** Limits of expression evaluation and template rendering, unlimited if not set */
+export interface EvaluationLimits {
+  /** Maximum nesting depth of arrays and objects in evaluated data */
+  maxDepth?: number
+  /** Maximum size in bytes of evaluated data and rendered templates */
+  maxSize?: number
+  /**
+   * Maximum duration in milliseconds of an evaluation. CEL expressions and
+   * variants are stopped at the deadline, JMESPath and JSONPath expressions
+   * and single templates are only checked once they return.
+   */
+  timeoutMs?: number
+}
+
+/** Format of exported config files */
+export declare const enum ExportFormat {
+  Yaml = 0,
+  Json = 1
+}
+
+/** Preset expression that failed while matching a request, see `MocksController.diagnose()` */
+export interface ExpressionDiagnostic {
+  routeId: string
+  presetId: string
+  /** Matched request field: `headers`, `query` or `payload` */
+  field: string
+  message: string
+}
+
+/** GraphQL operation answered by a route */
+export interface GraphqlOperation {
+  /** Operation type, any if not set */
+  type?: OperationType
+  /** Operation name, any (including anonymous operations) if not set */
+  name?: string
+}
+
+/** Group of routes sharing a URL prefix and response headers */
+export interface Group {
+  id: string
+  /** URL prefix prepended to URLs of member routes (e.g. "/api/v2") */
+  baseUrl?: string
+  /** Response headers shared by member routes (variant headers take precedence) */
+  headers?: Record<string, string>
 }
 
 /** HTTP method for route matching */
@@ -88,17 +836,239 @@ export declare const enum HttpMethod {
   Options = 6
 }
 
-/** Request matching preset */
+/** Key signing JWTs, see `jwtSign()` */
+export interface JwtOptions {
+  /** Signing algorithm, e.g. `HS256`, `RS256`, `ES256` or `EdDSA` (default `RS256`) */
+  algorithm?: string
+  /**
+   * HMAC secret for `HS*`, PEM PKCS#8 private key otherwise (built-in
+   * development key if not set)
+   */
+  key?: string
+  /** Key ID, the JWK thumbprint of custom asymmetric keys if not set */
+  kid?: string
+  /** Token lifetime in seconds, used for `exp` (default 3600) */
+  expiresIn?: number
+}
+
+/** Summary of serve times, in milliseconds */
+export interface LatencyStats {
+  /** Number of requests */
+  count: number
+  min: number
+  max: number
+  mean: number
+  /** Median */
+  p50: number
+  p95: number
+  p99: number
+}
+
+/** Kind of likely mistake found by `MocksManager.lint()` */
+export declare const enum LintRule {
+  ShadowedPreset = 0,
+  UnreferencedRoute = 1,
+  RedirectWithoutLocation = 2,
+  InvalidExpression = 3
+}
+
+/** Likely mistake found by the linter */
+export interface LintWarning {
+  rule: LintRule
+  /** Location of the problem (e.g. `routes.users.presets[0].variants[1]`) */
+  path: string
+  message: string
+  /** File the definition was loaded from, if known */
+  file?: string
+}
+
+/** Change of the files a manager was loaded from, see `MocksManager.watch()` */
+/** Log record of the core library, see `setLogger()` */
+export interface LogRecord {
+  /** Time of the record, in milliseconds since the Unix epoch */
+  timestamp: number
+  /** Level: `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE` */
+  level: string
+  /** Module emitting the record, e.g. `mockito_core::config::project` */
+  target: string
+  message?: string
+  /** Fields of the record and its enclosing spans, e.g. `routeId` and `error` */
+  fields: any
+}
+
+export interface ManagerWatchEvent {
+  /** Changed files */
+  changes: Array<WatchEvent>
+  /**
+   * Whether all files were reloaded, instead of replacing only the definitions
+   * of changed files
+   */
+  reloaded: boolean
+  /** Error applying the changes, definitions of files that failed to load are kept */
+  error?: string
+}
+
+/** First condition of a route a request failed */
+export interface MatchFailure {
+  step: MatchStep
+  /**
+   * First path parameter, header, query parameter or payload field the request
+   * doesn't satisfy, with nested payload fields joined by dots
+   */
+  field?: string
+  /** Value the route expects, as defined in the route or preset */
+  expected: any
+  /** Value of the request */
+  actual: any
+  /** Error of the preset expression, if the condition failed with one */
+  error?: string
+}
+
+/**
+ * Condition of a route checked against a request, in matching order.
+ *
+ * The later the first failed condition, the closer a route is to matching.
+ */
+export declare const enum MatchStep {
+  Enabled = 0,
+  Transport = 1,
+  Method = 2,
+  Url = 3,
+  /** GraphQL operation of the route */
+  Operation = 4,
+  /** TCP message of the route */
+  Message = 5,
+  Params = 6,
+  Headers = 7,
+  Query = 8,
+  Payload = 9
+}
+
+/** Memory used by a controller in bytes, see `MocksController.memoryUsage()` */
+export interface MemoryUsage {
+  /** Estimated size of loaded routes, collections and groups, shared by forks */
+  definitions: number
+  /** Size of fixture file contents kept in memory, shared by handles of `share()` */
+  fixtures: number
+  /** Size limit of fixture file contents kept in memory */
+  fixtureLimit: number
+}
+
+/** Type of a GraphQL operation */
+export declare const enum OperationType {
+  Query = 0,
+  Mutation = 1,
+  Subscription = 2
+}
+
+/**
+ * Request matching preset
+ *
+ * Fields matched by a map or value and by an expression come in pairs, like
+ * `headers` and `headersExpression`. The expression takes precedence if both are set.
+ */
 export interface Preset {
   id: string
   variants: Array<Variant>
-  /** Headers to match (can be an object or expression string like "${headers.myheader == 1}") */
-  headers?: any
-  /** Query parameters to match (can be an object or expression string like "${query.page == '1'}") */
-  query?: any
+  /** Headers to match */
+  headers?: Record<string, string>
+  /** Expression matching headers, like `headers.myheader == '1'` */
+  headersExpression?: string
+  /** Query parameters to match */
+  query?: Record<string, string>
+  /** Expression matching query parameters, like `query.page == '1'` */
+  queryExpression?: string
   params?: Record<string, string>
-  /** Payload to match (can be any JSON value or expression string like "${payload.items[0].id == 5}") */
+  /** Payload to match, any JSON value */
+  payload?: any
+  /** Expression matching the payload, like `payload.items[0].id == 5` */
+  payloadExpression?: string
+}
+
+/** Hit counts of a preset and its variants */
+export interface PresetCoverage {
+  presetId: string
+  /** Number of requests the preset answered */
+  hits: number
+  variants: Array<VariantCoverage>
+}
+
+/** Protobuf message types used by a route */
+export interface ProtoBinding {
+  /** Compiled descriptor set (`.desc`) or `.proto` file */
+  descriptor: string
+  /** gRPC method as "package.Service/Method" */
+  method?: string
+  /** Fully qualified request message type, overrides the method input */
+  request?: string
+  /** Fully qualified response message type, overrides the method output */
+  response?: string
+}
+
+/** Request recorded in the request history, see `MocksController.getRequests()` */
+export interface RecordedRequest {
+  /** Time the request was recorded, in milliseconds since the Unix epoch */
+  timestamp: number
+  url: string
+  method?: HttpMethod
+  transport: Transport
+  headers?: Record<string, string>
+  query?: Record<string, string>
+  payload?: any
+  /** Matched route ID, `null` if no route matched */
+  routeId?: string
+  /** Active preset ID of the matched route */
+  presetId?: string
+  /** Active variant ID of the matched route */
+  variantId?: string
+  /** Time in milliseconds spent looking up the route and rendering the response */
+  latency: number
+  /** Delay in milliseconds injected before sending the response, see the `delay` setting */
+  delay: number
+  /** Routes closest to matching the request if no route matched, closest first */
+  nearMatches: Array<RouteExplanation>
+  /**
+   * Response rendered by `respond()`, `null` for `findRoute()` lookups and
+   * requests that matched no route
+   */
+  response?: Variant
+}
+
+/** Request to match against active routes */
+export interface Request {
+  /** Request URL (path + query string, or listen address for TCP) */
+  url: string
+  /** HTTP method (required for HTTP routes) */
+  method?: HttpMethod
+  transport: Transport
+  headers?: Record<string, string>
+  /** Query parameters (parsed from URL if not provided) */
+  query?: Record<string, string>
   payload?: any
+  /**
+   * Raw body, used if `payload` isn't set.
+   *
+   * JSON bodies are matched as their value and other UTF-8 bodies as a string.
+   * Binary bodies are matched as a base64 `data:` URI with the `Content-Type` header.
+   * TCP messages are matched as a string, or a base64 `data:` URI if binary.
+   */
+  payloadBuffer?: Uint8Array
+}
+
+/**
+ * Request looked up by `MocksController.respond()` or `findRoute()`, delivered to
+ * `on('request')` and `on('unmatched')` callbacks
+ */
+export interface RequestEvent {
+  url: string
+  method?: HttpMethod
+  transport: Transport
+  /** Matched route ID (`request`) */
+  routeId?: string
+  /** Active preset ID of the matched route (`request`) */
+  presetId?: string
+  /** Active variant ID of the matched route (`request`) */
+  variantId?: string
 }
 
 /** Route definition */
@@ -108,12 +1078,205 @@ export interface Route {
   transport: Transport
   method?: HttpMethod
   presets: Array<Preset>
+  /** Tags grouping this route into functional areas (e.g. "payments") */
+  tags?: Array<string>
+  /** ID of the group providing URL prefix and shared response headers */
+  group?: string
+  /** Protobuf message types of requests and responses */
+  proto?: ProtoBinding
+  /** GraphQL operation answered by the route, for routes of a GraphQL endpoint */
+  graphql?: GraphqlOperation
+  /** Client messages answered by the route, for TCP routes */
+  tcp?: TcpMatch
+}
+
+/** Hit counts of a route and its presets */
+export interface RouteCoverage {
+  routeId: string
+  /** Number of requests the route answered */
+  hits: number
+  presets: Array<PresetCoverage>
+}
+
+/** Outcome of matching a request against one active route, see `MocksController.explain()` */
+export interface RouteExplanation {
+  routeId: string
+  presetId: string
+  /** Why the route doesn't match, absent if it does */
+  failure?: MatchFailure
+}
+
+/** Serve time percentiles of a route, see `MocksController.latencyStats()` */
+export interface RouteLatency {
+  routeId: string
+  /** Serve times of all variants of the route */
+  stats: LatencyStats
+  /** Variants that answered requests, sorted by preset and variant ID */
+  variants: Array<VariantLatency>
+}
+
+/** Response returned by a route handler, see `MocksController.onRoute()` */
+export interface RouteResponse {
+  status?: number
+  headers?: Record<string, string>
+  body?: any
+  /** Binary body, takes precedence over `body` */
+  bodyBuffer?: Buffer
+}
+
+/** Project settings from the `mockito.config.*` file */
+export interface Settings {
+  /** Port the mock server listens on */
+  port?: number
+  /** Collection activated when the controller starts */
+  defaultCollection?: string
+  /** Delay in milliseconds applied to responses without their own delay */
+  delay?: number
+  /** CORS headers added to responses, CORS is disabled if not set */
+  cors?: CorsSettings
+  /** Reloading of config files on changes */
+  watch?: WatchSettings
+  /** Size limits of request and response bodies */
+  bodyLimits?: BodyLimits
+  /** Template engine of variants without their own, Handlebars if not set */
+  template?: TemplateEngine
+  /** Limits of expression evaluation and template rendering */
+  limits?: EvaluationLimits
+  /** Template fragments included from variants with `{{> name}}` */
+  partials?: Record<string, string>
+  /**
+   * Number of distinct requests whose matched route is cached, caching is
+   * disabled if not set
+   */
+  matchCache?: number
+  /** Size limit in bytes of fixture file contents kept in memory, 64 MiB if not set */
+  fixtureCache?: number
+  /**
+   * Number of looked up requests kept in the request history, 1000 if not set
+   * and recording is disabled if 0
+   */
+  history?: number
+  /**
+   * Include debugging details in generated responses, like the routes closest
+   * to an unmatched request in 404 bodies
+   */
+  debug?: boolean
+}
+
+/** Validation issue severity */
+export declare const enum Severity {
+  Error = 0,
+  Warning = 1
+}
+
+/** Chunk of an event stream, see `sseFrames()` */
+export interface SseFrame {
+  /** Time to wait before sending the frame, in milliseconds */
+  delayMs: number
+  /** Frame in the `text/event-stream` format */
+  text: string
+}
+
+/** Chunk of bytes sent on a TCP connection, see `tcpReplies()` */
+export interface TcpFrame {
+  /** Time to wait before sending the bytes, in milliseconds */
+  delayMs: number
+  data: Buffer
+}
+
+/** Client messages answered by a TCP route */
+export interface TcpMatch {
+  /** Text the message starts with, compared byte by byte */
+  prefix?: string
+  /**
+   * Regex searched in the message bytes, `(?-u)` allows matching non-UTF-8
+   * bytes like `\xff`
+   */
+  pattern?: string
+}
+
+/** Replies of a TCP route variant, see `tcpReplies()` */
+export interface TcpReplies {
+  frames: Array<TcpFrame>
+  /** Whether to close the connection after sending the frames */
+  close: boolean
+}
+
+/** Engine rendering templates in variant headers and body */
+export declare const enum TemplateEngine {
+  Handlebars = 0,
+  Liquid = 1,
+  /** `${expression}` placeholders */
+  Interpolation = 2
+}
+
+/** Event reported by the core library, see `initTracing()` */
+export interface TraceEvent {
+  /** Time of the event, in milliseconds since the Unix epoch */
+  timestamp: number
+  /** Level: `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE` */
+  level: string
+  /** Module emitting the event */
+  target: string
+  message?: string
+  /** Fields of the event besides the message */
+  fields: any
+  /** Enclosing spans, outermost first */
+  spans: Array<TraceSpan>
+}
+
+/** Span enclosing a trace event */
+export interface TraceSpan {
+  /** Span name, e.g. `match_request` */
+  name: string
+  /** Fields recorded on the span, e.g. `url` and `routeId` */
+  fields: any
 }
 
 /** Transport type for route matching */
 export declare const enum Transport {
   Http = 0,
-  WebSocket = 1
+  WebSocket = 1,
+  /** Server-Sent Events, streaming the event scripts of variant bodies */
+  Sse = 2,
+  /**
+   * Raw TCP, answering messages sent to the URL address with the reply
+   * scripts of variant bodies
+   */
+  Tcp = 3
+}
+
+/** Request that matched no route, see `MocksController.getUnmatched()` */
+export interface UnmatchedRequest {
+  /** URL of the latest request with the same transport, method and path */
+  url: string
+  method?: HttpMethod
+  transport: Transport
+  /** Number of requests with the same transport, method and path */
+  count: number
+  /** Time the first of the requests was seen, in milliseconds since the Unix epoch */
+  firstSeen: number
+  /** Time the latest of the requests was seen, in milliseconds since the Unix epoch */
+  lastSeen: number
+  /** Route skeleton matching the request */
+  suggestion: Route
+}
+
+/** Result of matching a URL against a route pattern */
+export interface UrlMatch {
+  matched: boolean
+  /** Path parameters captured by `{name}` segments */
+  params: Record<string, string>
+}
+
+/** Problem found during validation */
+export interface ValidationIssue {
+  severity: Severity
+  /** Location of the problem (e.g. `collections.base.routes[1]`) */
+  path: string
+  message: string
+  /** File the definition was loaded from, if known */
+  file?: string
 }
 
 /** Response variant */
@@ -122,7 +1285,230 @@ export interface Variant {
   status?: number
   headers?: Record<string, string>
   body?: any
+  /**
+   * Binary body, takes precedence over `body`.
+   *
+   * Set for inline `file:data:` bodies, and by `MocksController.respond()` for
+   * `file:` fixtures. Stored as a `file:data:` body with the `Content-Type` header.
+   */
+  bodyBuffer?: Buffer
+  /** Template engine of headers and body, the project default if not set */
+  template?: TemplateEngine
+}
+
+/** Hit count of a variant */
+export interface VariantCoverage {
+  variantId: string
+  /** Number of requests the variant answered */
+  hits: number
+}
+
+/** Serve time percentiles of a variant */
+export interface VariantLatency {
+  presetId: string
+  variantId: string
+  stats: LatencyStats
+}
+
+/** Change of a watched config file */
+export interface WatchEvent {
+  path: string
+  kind: ChangeKind
+  /** Parse error of an added or modified file, not set if it parsed successfully */
+  parseError?: string
+}
+
+/** Config file watching settings */
+export interface WatchSettings {
+  /** Reload config files on changes */
+  enabled: boolean
+  /** Polling interval in milliseconds */
+  intervalMs?: number
 }
 
+/**
+ * Generate the JSON Schema (draft-07) of a config file kind.
+ *
+ * Useful for editor autocomplete and validation of fixture files.
+ *
+ * @param kind - Kind of config file
+ * @returns JSON Schema object
+ */
+export declare function configJsonSchema(kind: ConfigFileKind): any
+
+/**
+ * Match request headers like a preset `headers`, ignoring the case of names.
+ *
+ * @param expected - Headers or `${...}` expression
+ * @param headers - Headers of the request
+ * @param request - Request context evaluated by expressions (default: `{ headers }`)
+ * @throws Error if `expected` is malformed or the expression fails
+ */
+export declare function headersMatches(expected: any, headers: Record<string, string>, request?: any | undefined | null): boolean
+
+/**
+ * Forward matching, resolution and rendering events of the core library to a callback.
+ *
+ * Each event carries the fields of its enclosing spans, so events of a lookup
+ * hold the request and the matched route, preset and variant. The callback is
+ * invoked asynchronously on the event loop and doesn't keep the process alive.
+ * Calling it again replaces the callback.
+ *
+ * @param callback - Function receiving `TraceEvent`s
+ * @param filter - Comma-separated `target=level` directives or a bare level (default: `mockito_core=debug`)
+ * @throws Error with code `INVALID_ARGUMENT` if the filter is invalid, or
+ * `TRACING_INSTALLED` if another subscriber is installed in the process
+ */
+export declare function initTracing(callback: ((arg: TraceEvent) => void), filter?: string | undefined | null): void
+
+/**
+ * Build the JWKS (`{"keys": [...]}`) verifying tokens of `jwtSign()`.
+ *
+ * Without keys, holds the development keys of all asymmetric algorithms. HMAC
+ * keys have no public key and are skipped.
+ *
+ * @param keys - Keys whose public keys to publish
+ * @throws Error if an algorithm is unknown or a key invalid
+ */
+export declare function jwks(keys?: Array<JwtOptions> | undefined | null): any
+
+/**
+ * Mint a signed JWT, e.g. in a route handler.
+ *
+ * `iat` and `exp` are added unless set in `claims`. Without `key`, tokens are
+ * signed with built-in development keys, published by `jwks()`.
+ *
+ * @param claims - Token claims
+ * @param options - Signing key and lifetime
+ * @throws Error if the algorithm is unknown or the key invalid
+ */
+export declare function jwtSign(claims?: Record<string, any> | undefined | null, options?: JwtOptions | undefined | null): string
+
+/**
+ * Match a request payload like a preset `payload`.
+ *
+ * Objects match if the payload contains all expected fields, other values must be equal.
+ *
+ * @param expected - Expected payload or `${...}` expression
+ * @param payload - Payload of the request
+ * @param request - Request context evaluated by expressions (default: `{ payload }`)
+ * @throws Error if the expression fails
+ */
+export declare function payloadMatches(expected: any, payload: any, request?: any | undefined | null): boolean
+
+/**
+ * Match query parameters like a preset `query`.
+ *
+ * @param expected - Query parameters or `${...}` expression
+ * @param query - Query parameters of the request
+ * @param request - Request context evaluated by expressions (default: `{ query }`)
+ * @throws Error if `expected` is malformed or the expression fails
+ */
+export declare function queryMatches(expected: any, query: Record<string, string>, request?: any | undefined | null): boolean
+
+/**
+ * Register a JS function callable from JMESPath and CEL expressions and `${}`
+ * template placeholders.
+ *
+ * Expressions call the function synchronously with the evaluated arguments, so
+ * it must not return a promise. Functions are only callable on the JS thread
+ * that registered them. Built-in functions take precedence.
+ *
+ * @param name - Function name used in expressions
+ * @param func - Function receiving the arguments and returning a JSON value
+ * @throws Error if the function throws while evaluating an expression
+ */
+export declare function registerExpressionFunction(name: string, func: (...args: any[]) => any): void
+
+/**
+ * Forward log records of the core library to a JS logger.
+ *
+ * By default only warnings and errors are forwarded, e.g. preset expressions
+ * that don't compile, validation issues of loaded projects and remote files
+ * served from the cache. The callback is invoked asynchronously on the event
+ * loop and doesn't keep the process alive.
+ *
+ * ```js
+ * setLogger((record) => console[record.level.toLowerCase()]?.(record.message, record.fields));
+ * ```
+ *
+ * @param logger - Function receiving `LogRecord`s, `null` stops forwarding
+ * @param filter - Comma-separated `target=level` directives or a bare level (default: `mockito_core=warn`)
+ * @throws Error with code `INVALID_ARGUMENT` if the filter is invalid, or
+ * `TRACING_INSTALLED` if another subscriber is installed in the process
+ */
+export declare function setLogger(logger?: ((arg: LogRecord) => void) | undefined | null, filter?: string | undefined | null): void
+
+/**
+ * Get the frames streamed by a variant of an SSE route.
+ *
+ * The variant body holds the event script (`retry` and `events`). Events up to
+ * the one with `lastEventId` are skipped, so reconnecting clients resume
+ * where they left off. Answer with the `text/event-stream` content type.
+ *
+ * @param variant - Variant rendered by `MocksController.respond()`
+ * @param lastEventId - `Last-Event-ID` header of the request
+ * @throws Error if the variant body isn't an event script
+ */
+export declare function sseFrames(variant: Variant, lastEventId?: string | undefined | null): Array<SseFrame>
+
+/**
+ * Get the replies sent by a variant of a TCP route.
+ *
+ * The variant body holds the reply script (`replies` and `close`), or a single
+ * reply as a string. Replies holding a `data:` URI are decoded to bytes.
+ *
+ * The core `TcpServer` answering TCP routes isn't exposed to Node: a `net`
+ * server passes messages to `MocksController.respond()` and writes these
+ * replies.
+ *
+ * @param variant - Variant rendered by `MocksController.respond()`
+ * @throws Error if the variant body isn't a reply script
+ */
+export declare function tcpReplies(variant: Variant): TcpReplies
+
+/**
+ * Evaluate an expression against a sample request.
+ *
+ * Lets fixture authors try expressions on captured requests without running tests.
+ *
+ * @param expression - Expression, optionally wrapped in `${}`
+ * @param request - Sample request context (`method`, `url`, `params`, `query`, `headers`, `payload`)
+ * @returns Evaluation result
+ * @throws Error if the expression is invalid or fails on the sample
+ */
+export declare function testExpression(expression: string, request: any): any
+
+/**
+ * Unregister a JS function registered with `registerExpressionFunction()`.
+ *
+ * @param name - Function name
+ * @returns `false` if no function with the name was registered on this thread
+ */
+export declare function unregisterExpressionFunction(name: string): boolean
+
+/**
+ * Match a URL against a route URL pattern.
+ *
+ * Trailing slashes and the query string are ignored.
+ *
+ * @param pattern - Route URL pattern like `/api/users/{id}`
+ * @param url - Request URL
+ */
+export declare function urlMatches(pattern: string, url: string): UrlMatch
+
 /** Library version */
 export declare function version(): string
+
+/**
+ * Watch config files matched by glob patterns for changes.
+ *
+ * The callback receives batches of changes (added/modified/removed files with
+ * parse results) on the event loop. Subscriptions don't keep the process alive.
+ *
+ * @param patterns - Glob patterns of watched files, `!` prefixed ones exclude files
+ * @param callback - Function receiving arrays of `WatchEvent`s
+ * @param intervalMs - Polling interval in milliseconds (default: 500)
+ * @throws Error if any glob pattern is invalid
+ */
+export declare function watchFiles(patterns: Array<string>, callback: ((arg: Array<WatchEvent>) => void), intervalMs?: number | undefined | null): WatchSubscription
@@ -0,0 +1,33 @@
+//! Benchmarks of query string parsing.
+//!
+//! Run with `cargo bench -p mockito-core --bench query`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mockito_core::matching::parse_query_string;
+use std::hint::black_box;
+
+fn bench_parse_query_string(c: &mut Criterion) {
+    let cases = [
+        (
+            "plain",
+            "page=1&limit=10&sort=name&order=asc&fields=id,name,email",
+        ),
+        (
+            "encoded",
+            "q=hello%20world&filter=name%3Dadmin&redirect=%2Fapi%2Fusers%3Fpage%3D2",
+        ),
+        (
+            "repeated",
+            "tag=a&tag=b&tag=c&tag=d&tag=e&tag=f&tag=g&tag=h",
+        ),
+    ];
+
+    let mut group = c.benchmark_group("parse_query_string");
+    for (name, query) in cases {
+        group.bench_function(name, |b| b.iter(|| parse_query_string(black_box(query))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_query_string);
+criterion_main!(benches);
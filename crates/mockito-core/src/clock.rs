@@ -0,0 +1,61 @@
+//! Clock working on `wasm32-unknown-unknown`, where `Instant::now()` and
+//! `SystemTime::now()` of the standard library panic.
+//!
+//! Elsewhere, [`Instant`] is [`std::time::Instant`] and [`now`] is
+//! [`SystemTime::now`]. On wasm32, both read `Date.now()`, so instants have
+//! millisecond resolution and follow changes of the system clock.
+
+use std::time::SystemTime;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::Instant;
+
+/// Current system time.
+pub fn now() -> SystemTime {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        SystemTime::now()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        std::time::UNIX_EPOCH + wasm::since_epoch()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use std::ops::Add;
+    use std::time::Duration;
+
+    /// Time elapsed since the Unix epoch, read from `Date.now()`.
+    pub(super) fn since_epoch() -> Duration {
+        Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+    }
+
+    /// Point in time, for measuring durations
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        /// Current point in time.
+        pub fn now() -> Self {
+            Instant(since_epoch())
+        }
+
+        /// Time elapsed since this instant, zero if the clock went back.
+        pub fn elapsed(&self) -> Duration {
+            since_epoch().saturating_sub(self.0)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, duration: Duration) -> Instant {
+            Instant(self.0 + duration)
+        }
+    }
+}
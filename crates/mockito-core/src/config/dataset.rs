@@ -0,0 +1,249 @@
+//! Tabular dataset fixtures in variants.
+//!
+//! A variant may reference a CSV or JSON dataset instead of inlining its body:
+//!
+//! ```yaml
+//! variants:
+//!   - id: list
+//!     dataset: ./users.csv
+//! ```
+//!
+//! Datasets are parsed into arrays when the config file is loaded and replace
+//! the `dataset` key with a `body`. CSV files need a header row, each following
+//! row becomes an object with values inferred as numbers, booleans, `null`
+//! (empty cells) or strings. Other files are parsed by their extension and must
+//! hold an array. CSV files need the `csv` feature.
+
+use crate::config::error::ConfigError;
+use crate::config::parser::parse_config;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Key referencing a dataset file
+pub const DATASET_KEY: &str = "dataset";
+
+/// Key holding the body in variants
+const BODY_KEY: &str = "body";
+
+/// Replace `dataset` references with `body` arrays loaded from the files.
+///
+/// Relative paths are resolved against `base_dir`, the directory of the config
+/// file declaring the variant.
+///
+/// Returns error if a dataset can't be loaded or a variant has both a `dataset`
+/// and a `body`.
+pub fn resolve_datasets(value: Value, base_dir: &Path) -> Result<Value, ConfigError> {
+    match value {
+        Value::Object(mut obj) => {
+            let dataset = match obj.remove(DATASET_KEY) {
+                Some(Value::String(path)) => Some(path),
+                Some(other) => {
+                    return Err(ConfigError::Dataset {
+                        path: other.to_string(),
+                        message: format!("{} must be a file path", DATASET_KEY),
+                    })
+                }
+                None => None,
+            };
+
+            let mut resolved = obj
+                .into_iter()
+                .map(|(key, value)| Ok((key, resolve_datasets(value, base_dir)?)))
+                .collect::<Result<Map<_, _>, ConfigError>>()?;
+
+            if let Some(path) = dataset {
+                if resolved.contains_key(BODY_KEY) {
+                    return Err(ConfigError::Dataset {
+                        path,
+                        message: format!("{} can't be combined with {}", DATASET_KEY, BODY_KEY),
+                    });
+                }
+                let rows = load_dataset(&base_dir.join(path))?;
+                resolved.insert(BODY_KEY.to_owned(), rows);
+            }
+            Ok(Value::Object(resolved))
+        }
+        Value::Array(arr) => arr
+            .into_iter()
+            .map(|item| resolve_datasets(item, base_dir))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        other => Ok(other),
+    }
+}
+
+/// Load a dataset file into an array.
+pub fn load_dataset(path: &Path) -> Result<Value, ConfigError> {
+    let display_path = path.to_string_lossy().into_owned();
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::Io {
+        source: e,
+        path: display_path.clone(),
+    })?;
+
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        #[cfg(feature = "csv")]
+        return parse_csv(&content).map_err(|e| ConfigError::Dataset {
+            path: display_path,
+            message: e.to_string(),
+        });
+        #[cfg(not(feature = "csv"))]
+        return Err(ConfigError::FeatureDisabled {
+            what: format!("CSV dataset {}", display_path),
+            feature: "csv",
+        });
+    }
+
+    match parse_config(&content, &display_path)? {
+        rows @ Value::Array(_) => Ok(rows),
+        _ => Err(ConfigError::Dataset {
+            path: display_path,
+            message: "dataset must be an array".to_owned(),
+        }),
+    }
+}
+
+/// Parse CSV content with a header row into an array of objects.
+#[cfg(feature = "csv")]
+fn parse_csv(content: &str) -> Result<Value, csv::Error> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(header, cell)| (header.to_owned(), infer_value(cell)))
+                .collect::<Map<_, _>>()
+                .into())
+        })
+        .collect::<Result<_, _>>()
+        .map(Value::Array)
+}
+
+/// Infer the JSON type of a CSV cell.
+#[cfg(feature = "csv")]
+fn infer_value(cell: &str) -> Value {
+    if cell.is_empty() {
+        return Value::Null;
+    }
+    if let Ok(b) = cell.parse::<bool>() {
+        return Value::Bool(b);
+    }
+    if let Ok(i) = cell.parse::<i64>() {
+        return Value::Number(i.into());
+    }
+    match cell
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Some(n) => Value::Number(n),
+        None => Value::String(cell.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_dir::TestDir;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[cfg(feature = "csv")]
+    #[rstest]
+    #[case("42", json!(42))]
+    #[case("-1.5", json!(-1.5))]
+    #[case("true", json!(true))]
+    #[case("", json!(null))]
+    #[case("alice", json!("alice"))]
+    #[case("NaN", json!("NaN"))]
+    fn test_infer_value(#[case] cell: &str, #[case] expected: Value) {
+        assert_eq!(infer_value(cell), expected);
+    }
+
+    #[cfg(feature = "csv")]
+    #[rstest]
+    fn test_resolve_datasets() {
+        let dir = TestDir::with_files(
+            "dataset_resolve",
+            &[
+                (
+                    "users.csv",
+                    "id,name,active\n1,Alice,true\n2,\"Smith, Bob\",\n",
+                ),
+                ("orders.json", r#"[{"id": 7}]"#),
+            ],
+        );
+        let value = json!({
+            "variants": [
+                {"id": "users", "dataset": "users.csv"},
+                {"id": "orders", "status": 200, "dataset": "./orders.json"},
+                {"id": "inline", "body": []}
+            ]
+        });
+
+        let resolved = resolve_datasets(value, &dir).unwrap();
+        assert_eq!(
+            resolved["variants"],
+            json!([
+                {"id": "users", "body": [
+                    {"id": 1, "name": "Alice", "active": true},
+                    {"id": 2, "name": "Smith, Bob", "active": null}
+                ]},
+                {"id": "orders", "status": 200, "body": [{"id": 7}]},
+                {"id": "inline", "body": []}
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case(json!({"id": "v", "dataset": "users.csv", "body": []}), "can't be combined")]
+    #[case(json!({"id": "v", "dataset": "object.json"}), "must be an array")]
+    #[case(json!({"id": "v", "dataset": 1}), "must be a file path")]
+    #[cfg_attr(feature = "csv", case(json!({"id": "v", "dataset": "ragged.csv"}), "fields"))]
+    fn test_resolve_datasets_invalid(#[case] value: Value, #[case] expected: &str) {
+        let name = format!(
+            "mockito_dataset_invalid_{}",
+            expected.replace([' ', '\''], "_")
+        );
+        let dir = TestDir::with_files(
+            &name,
+            &[
+                ("users.csv", "id\n1\n"),
+                ("object.json", "{}"),
+                ("ragged.csv", "id,name\n1\n"),
+            ],
+        );
+
+        let err = resolve_datasets(value, &dir).unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::Dataset { message, .. } if message.contains(expected)),
+            "{err}"
+        );
+    }
+
+    #[rstest]
+    fn test_resolve_datasets_missing_file() {
+        let dir = TestDir::with_files("dataset_missing", &[]);
+        let err = resolve_datasets(json!({"dataset": "missing.csv"}), &dir).unwrap_err();
+        assert!(matches!(err, ConfigError::Io { path, .. } if path.ends_with("missing.csv")));
+    }
+
+    #[cfg(not(feature = "csv"))]
+    #[rstest]
+    fn test_resolve_datasets_csv_disabled() {
+        let dir = TestDir::with_files("dataset_csv_disabled", &[("users.csv", "id\n1\n")]);
+        let err = resolve_datasets(json!({"dataset": "users.csv"}), &dir).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::FeatureDisabled { feature: "csv", .. }
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Environment variable substitution in config files.
+//!
+//! Config content may reference environment variables using `${ENV:NAME}` placeholders,
+//! optionally with a default value used when the variable is not set: `${ENV:PORT:-3000}`.
+//!
+//! A placeholder making up a whole value takes the type of the substituted text read
+//! as a JSON scalar, so `port: ${ENV:PORT:-3000}` is a number and `${ENV:DEBUG}` set
+//! to `true` a boolean. Other text substituted is kept as a string.
+
+use crate::config::error::ConfigError;
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::sync::LazyLock;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$\{ENV:([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").expect("valid regex")
+});
+
+/// Substitute environment variable placeholders using a custom variable lookup.
+pub fn substitute_env_with<F>(content: &str, lookup: F) -> Result<String, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut missing = None;
+    let result = PLACEHOLDER.replace_all(content, |caps: &Captures| {
+        match (lookup(&caps[1]), caps.get(2)) {
+            (Some(value), _) => value,
+            (None, Some(default)) => default.as_str().to_string(),
+            (None, None) => {
+                missing.get_or_insert_with(|| caps[1].to_string());
+                String::new()
+            }
+        }
+    });
+
+    match missing {
+        Some(name) => Err(ConfigError::MissingEnvVar(name)),
+        None => Ok(result.into_owned()),
+    }
+}
+
+/// Substitute environment variable placeholders in the strings of a parsed value,
+/// including object keys.
+///
+/// Used for content parsed before substitution, where placeholders can only
+/// appear inside strings. Strings made of a single placeholder become numbers or
+/// booleans if the substituted text is one.
+///
+/// Returns error if a variable is not set and the placeholder has no default.
+pub fn substitute_env_in_value(value: Value) -> Result<Value, ConfigError> {
+    substitute_value_with(value, &|name: &str| std::env::var(name).ok())
+}
+
+fn substitute_value_with<F>(value: Value, lookup: &F) -> Result<Value, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let substitute = |s: String| {
+        if s.contains("${ENV:") {
+            substitute_env_with(&s, lookup)
+        } else {
+            Ok(s)
+        }
+    };
+    Ok(match value {
+        Value::String(s) if is_placeholder(&s) => typed(substitute(s)?),
+        Value::String(s) => Value::String(substitute(s)?),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| substitute_value_with(item, lookup))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| Ok((substitute(key)?, substitute_value_with(value, lookup)?)))
+                .collect::<Result<_, ConfigError>>()?,
+        ),
+        other => other,
+    })
+}
+
+/// Check if a string is a single placeholder.
+fn is_placeholder(s: &str) -> bool {
+    PLACEHOLDER
+        .find(s)
+        .is_some_and(|m| m.start() == 0 && m.end() == s.len())
+}
+
+/// Read substituted text as a number or boolean, keeping other text a string.
+fn typed(s: String) -> Value {
+    match serde_json::from_str(&s) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_))) => value,
+        _ => Value::String(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "API_HOST" => Some("api.local".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[rstest]
+    #[case("url: https://${ENV:API_HOST}/users", "url: https://api.local/users")]
+    #[case("port: ${ENV:PORT:-3000}", "port: 3000")]
+    #[case("host: ${ENV:API_HOST:-localhost}", "host: api.local")]
+    #[case("token: '${ENV:EMPTY:-unused}'", "token: ''")]
+    #[case("token: '${ENV:TOKEN:-}'", "token: ''")]
+    #[case("plain: ${HOME} {{vars.name}}", "plain: ${HOME} {{vars.name}}")]
+    fn test_substitute_env(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(substitute_env_with(input, lookup).unwrap(), expected);
+    }
+
+    #[rstest]
+    fn test_substitute_env_in_value() {
+        let value = serde_json::json!({
+            "url": "https://${ENV:API_HOST}/users",
+            "${ENV:API_HOST}": [{"port": "${ENV:PORT:-3000}", "n": 1}],
+            "host": "${ENV:API_HOST}",
+            "debug": "${ENV:DEBUG:-true}",
+            "version": "v${ENV:VERSION:-2}",
+            "token": "${ENV:EMPTY:-unused}",
+        });
+        assert_eq!(
+            substitute_value_with(value, &lookup).unwrap(),
+            serde_json::json!({
+                "url": "https://api.local/users",
+                "api.local": [{"port": 3000, "n": 1}],
+                "host": "api.local",
+                "debug": true,
+                "version": "v2",
+                "token": "",
+            })
+        );
+
+        let error = substitute_value_with(serde_json::json!(["${ENV:MISSING}"]), &lookup);
+        assert!(matches!(error, Err(ConfigError::MissingEnvVar(_))));
+    }
+
+    #[rstest]
+    fn test_substitute_env_missing_variable() {
+        let error = substitute_env_with("a: ${ENV:FIRST}\nb: ${ENV:SECOND}", lookup).unwrap_err();
+        assert!(matches!(error, ConfigError::MissingEnvVar(ref name) if name == "FIRST"));
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for configuration parsing.
 
+use crate::mocks::manager::DuplicateIdError;
 use thiserror::Error;
 
 /// Configuration parsing error
@@ -11,9 +12,81 @@ pub enum ConfigError {
     /// YAML parsing error
     #[error("YAML parsing error: {0}")]
     Yaml(#[from] serde_yaml::Error),
+    /// JSON5 parsing error
+    #[cfg(feature = "json5")]
+    #[error("JSON5 parsing error: {0}")]
+    Json5(#[from] json5::Error),
+    /// TOML parsing error
+    #[cfg(feature = "toml")]
+    #[error("TOML parsing error: {0}")]
+    Toml(#[from] toml::de::Error),
     /// Unknown file type
     #[error("Unknown file type: {0}")]
     UnknownFileType(String),
+    /// Config value doesn't match the expected structure
+    #[error("{}", format_schema_error(file, field, *line, *column, message))]
+    Schema {
+        /// File the value was loaded from
+        file: String,
+        /// Path of the failing field (e.g. `presets[0].variants[1].status`)
+        field: String,
+        /// Line in the file (1-based), if the field could be located
+        line: Option<usize>,
+        /// Column in the file (1-based), if the field could be located
+        column: Option<usize>,
+        /// Description of the problem
+        message: String,
+    },
+    /// Environment variable referenced without default is not set
+    #[error("Environment variable not set: {0}")]
+    MissingEnvVar(String),
+    /// Config files include each other in a cycle
+    #[error("Include cycle detected: {0}")]
+    IncludeCycle(String),
+    /// Malformed include directive or missing fragment
+    #[error("Invalid include: {0}")]
+    InvalidInclude(String),
+    /// Remote config file couldn't be fetched
+    #[error("Failed to fetch {url}: {message}")]
+    Remote {
+        /// URL of the remote file
+        url: String,
+        /// Description of the failure
+        message: String,
+    },
+    /// OpenAPI document can't be imported
+    #[error("Invalid OpenAPI document: {0}")]
+    OpenApi(String),
+    /// Protobuf descriptor can't be loaded or doesn't define a referenced type
+    #[error("Invalid protobuf descriptor {path}: {message}")]
+    Proto {
+        /// Path of the descriptor file
+        path: String,
+        /// Description of the problem
+        message: String,
+    },
+    /// Inline `data:` fixture is malformed
+    #[error("Invalid inline fixture: {0}")]
+    InlineFixture(String),
+    /// Dataset file can't be parsed or is referenced incorrectly
+    #[error("Invalid dataset {path}: {message}")]
+    Dataset {
+        /// Path of the dataset file
+        path: String,
+        /// Description of the problem
+        message: String,
+    },
+    /// Loaded definition reuses an existing ID
+    #[error("{0}")]
+    Duplicate(#[from] DuplicateIdError),
+    /// Config uses a feature of the crate that isn't enabled
+    #[error("{what} requires the `{feature}` feature")]
+    FeatureDisabled {
+        /// What the config uses, e.g. `TOML file routes.toml`
+        what: String,
+        /// Name of the disabled crate feature
+        feature: &'static str,
+    },
     /// Glob pattern error
     #[error("Glob pattern error: {0}")]
     GlobPattern(String),
@@ -26,6 +99,21 @@ pub enum ConfigError {
     },
 }
 
+fn format_schema_error(
+    file: &str,
+    field: &str,
+    line: Option<usize>,
+    column: Option<usize>,
+    message: &str,
+) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => {
+            format!("{}:{}:{}: {}: {}", file, line, column, field, message)
+        }
+        _ => format!("{}: {}: {}", file, field, message),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,6 +135,24 @@ mod tests {
         assert!(display.contains("YAML parsing error"));
     }
 
+    #[cfg(feature = "json5")]
+    #[rstest]
+    fn test_config_error_json5_display() {
+        let json5_err = json5::from_str::<serde_json::Value>("{invalid").unwrap_err();
+        let error = ConfigError::from(json5_err);
+        let display = format!("{}", error);
+        assert!(display.contains("JSON5 parsing error"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[rstest]
+    fn test_config_error_toml_display() {
+        let toml_err = toml::from_str::<serde_json::Value>("invalid = [").unwrap_err();
+        let error = ConfigError::from(toml_err);
+        let display = format!("{}", error);
+        assert!(display.contains("TOML parsing error"));
+    }
+
     #[rstest]
     #[case("test.txt")]
     #[case("unknown.extension")]
@@ -72,6 +178,62 @@ mod tests {
         assert!(matches!(error, ConfigError::Yaml(_)));
     }
 
+    #[rstest]
+    #[case(
+        Some(8),
+        Some(17),
+        "routes/users.yaml:8:17: presets[0].variants[0].status: invalid type"
+    )]
+    #[case(
+        None,
+        None,
+        "routes/users.yaml: presets[0].variants[0].status: invalid type"
+    )]
+    fn test_config_error_schema_display(
+        #[case] line: Option<usize>,
+        #[case] column: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let error = ConfigError::Schema {
+            file: "routes/users.yaml".to_string(),
+            field: "presets[0].variants[0].status".to_string(),
+            line,
+            column,
+            message: "invalid type".to_string(),
+        };
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[rstest]
+    fn test_config_error_missing_env_var_display() {
+        let error = ConfigError::MissingEnvVar("API_TOKEN".to_string());
+        assert_eq!(error.to_string(), "Environment variable not set: API_TOKEN");
+    }
+
+    #[rstest]
+    fn test_config_error_include_display() {
+        let error = ConfigError::IncludeCycle("a.yaml -> b.yaml -> a.yaml".to_string());
+        assert_eq!(
+            error.to_string(),
+            "Include cycle detected: a.yaml -> b.yaml -> a.yaml"
+        );
+
+        let error = ConfigError::InvalidInclude("Fragment not found: a.yaml#/x".to_string());
+        assert!(error.to_string().contains("Invalid include"));
+    }
+
+    #[rstest]
+    fn test_config_error_remote_display() {
+        let error = ConfigError::Remote {
+            url: "https://example.com/routes.yaml".to_string(),
+            message: "http status: 404".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to fetch https://example.com/routes.yaml: http status: 404"
+        );
+    }
+
     #[rstest]
     fn test_config_error_glob_pattern_display() {
         let error = ConfigError::GlobPattern("Invalid glob pattern: *[".to_string());
@@ -0,0 +1,245 @@
+//! Export of stored definitions back to config files.
+//!
+//! Files are written in the project directory layout, so the output can be
+//! loaded back with [`load_project`](crate::config::project::load_project):
+//!
+//! ```text
+//! routes/<route id>.yaml
+//! collections/<collection id>.yaml
+//! groups/<group id>.yaml
+//! ```
+
+use crate::config::error::ConfigError;
+use crate::mocks::manager::MocksManager;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Format of exported config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Yaml,
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension used for exported files.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Serialize a value in this format.
+    pub(crate) fn serialize<T: Serialize>(&self, value: &T) -> Result<String, ConfigError> {
+        match self {
+            ExportFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+        }
+    }
+}
+
+/// Write all routes, collections and groups of a manager to a directory.
+///
+/// Each definition is written to its own file named after its ID, with characters
+/// not allowed in file names replaced. Existing files with the same names are
+/// overwritten, other files are left untouched.
+///
+/// Returns the paths of written files.
+pub fn export(
+    manager: &MocksManager,
+    dir: &str,
+    format: ExportFormat,
+) -> Result<Vec<String>, ConfigError> {
+    let root = Path::new(dir);
+    let mut written = Vec::new();
+
+    let routes = manager.list_routes();
+    let routes = routes.iter().map(|r| (&*r.id, *r));
+    write_all(&root.join("routes"), routes, format, &mut written)?;
+
+    let collections = manager.list_collections();
+    let collections = collections.iter().map(|c| (&*c.id, *c));
+    write_all(&root.join("collections"), collections, format, &mut written)?;
+
+    let groups = manager.list_groups();
+    let groups = groups.iter().map(|g| (g.id.as_str(), *g));
+    write_all(&root.join("groups"), groups, format, &mut written)?;
+
+    Ok(written)
+}
+
+/// Write each item to a separate file in a directory.
+fn write_all<'a, T: Serialize + 'a>(
+    dir: &Path,
+    items: impl Iterator<Item = (&'a str, &'a T)>,
+    format: ExportFormat,
+    written: &mut Vec<String>,
+) -> Result<(), ConfigError> {
+    let mut names = HashSet::new();
+    for (id, item) in items {
+        if names.is_empty() {
+            fs::create_dir_all(dir).map_err(|e| ConfigError::Io {
+                source: e,
+                path: dir.to_string_lossy().into_owned(),
+            })?;
+        }
+
+        let name = unique_name(&file_stem(id), &mut names);
+        let path = dir.join(format!("{}.{}", name, format.extension()));
+        let display_path = path.to_string_lossy().into_owned();
+        fs::write(&path, format.serialize(item)?).map_err(|e| ConfigError::Io {
+            source: e,
+            path: display_path.clone(),
+        })?;
+        written.push(display_path);
+    }
+    Ok(())
+}
+
+/// File name stem for an ID, with unsafe characters replaced by `_`.
+fn file_stem(id: &str) -> String {
+    let stem: String = id
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    match stem.trim_start_matches('.') {
+        "" => "_".to_string(),
+        _ => stem,
+    }
+}
+
+/// Make a name unique among already used ones by appending a counter.
+fn unique_name(stem: &str, used: &mut HashSet<String>) -> String {
+    let mut name = stem.to_string();
+    let mut counter = 2;
+    // File systems may be case-insensitive
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{}-{}", stem, counter);
+        counter += 1;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::project::load_project;
+    use crate::config::test_dir::TestDir;
+    use crate::types::collection::Collection;
+    use crate::types::group::Group;
+    use crate::types::preset::Preset;
+    use crate::types::route::{HttpMethod, Route, Transport};
+    use crate::types::variant::Variant;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn create_test_manager() -> MocksManager {
+        let mut manager = MocksManager::new();
+        for id in ["users", "users/list"] {
+            manager
+                .add_route(Route {
+                    id: id.into(),
+                    url: "/api/users".to_string(),
+                    transport: Transport::Http,
+                    method: Some(HttpMethod::Get),
+                    presets: vec![Preset {
+                        id: "default".into(),
+                        params: None,
+                        query: None,
+                        headers: None,
+                        payload: None,
+                        variants: vec![Variant {
+                            id: "ok".into(),
+                            status: Some(200),
+                            headers: None,
+                            body: Some(json!([{"id": 1}])),
+                            template: None,
+                        }],
+                    }],
+                    tags: vec!["users".to_string()],
+                    group: Some("api".to_string()),
+                    proto: None,
+                    graphql: None,
+                    tcp: None,
+                })
+                .unwrap();
+        }
+        manager
+            .add_collection(Collection {
+                id: "base".into(),
+                from: None,
+                vars: None,
+                routes: vec!["users:default:ok".into()],
+            })
+            .unwrap();
+        manager
+            .add_group(Group {
+                id: "api".to_string(),
+                base_url: Some("/v1".to_string()),
+                headers: None,
+            })
+            .unwrap();
+        manager
+    }
+
+    #[rstest]
+    #[case(ExportFormat::Yaml)]
+    #[case(ExportFormat::Json)]
+    fn test_export_round_trip(#[case] format: ExportFormat) {
+        let dir = TestDir::new("export");
+        let manager = create_test_manager();
+
+        let written = export(&manager, dir.to_str().unwrap(), format).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| {
+                Path::new(p)
+                    .strip_prefix(&dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        let ext = format.extension();
+        assert_eq!(
+            names,
+            vec![
+                format!("routes/users.{ext}"),
+                format!("routes/users_list.{ext}"),
+                format!("collections/base.{ext}"),
+                format!("groups/api.{ext}"),
+            ]
+        );
+
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(project.manager.list_routes(), manager.list_routes());
+        assert_eq!(
+            project.manager.list_collections(),
+            manager.list_collections()
+        );
+        assert_eq!(project.manager.get_group("api"), manager.get_group("api"));
+    }
+
+    #[rstest]
+    #[case("users", "users")]
+    #[case("users/list:v2", "users_list_v2")]
+    #[case("..", "_")]
+    #[case(".hidden", ".hidden")]
+    fn test_file_stem(#[case] id: &str, #[case] expected: &str) {
+        assert_eq!(file_stem(id), expected);
+    }
+
+    #[rstest]
+    fn test_unique_name() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name("users", &mut used), "users");
+        assert_eq!(unique_name("Users", &mut used), "Users-2");
+        assert_eq!(unique_name("users", &mut used), "users-3");
+    }
+}
@@ -0,0 +1,499 @@
+//! `file:` fixture references in variant bodies.
+//!
+//! A variant may reference a fixture file instead of inlining the body:
+//!
+//! ```yaml
+//! variants:
+//!   - id: success
+//!     body: file:./fixtures/users.json
+//! ```
+//!
+//! Relative paths are resolved against the directory of the config file declaring
+//! the variant, and replaced with the canonical path when the file is loaded, so
+//! fixtures work regardless of the process working directory.
+//!
+//! Small binary fixtures can be inlined as a `data:` URI instead, keeping a route
+//! file self-contained:
+//!
+//! ```yaml
+//! variants:
+//!   - id: avatar
+//!     body: file:data:image/png;base64,iVBORw0KGgo=
+//! ```
+//!
+//! Loading config only records fixture paths. Files are read when a variant is
+//! first served, and kept in a [`FixtureCache`] bounded by size.
+
+use crate::config::error::ConfigError;
+use base64::Engine;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+/// Prefix of fixture references in variant bodies
+pub const FILE_PREFIX: &str = "file:";
+
+/// Prefix of inline fixture content
+pub const DATA_PREFIX: &str = "data:";
+
+/// Media type of `data:` URIs without one
+const DEFAULT_MEDIA_TYPE: &str = "text/plain";
+
+/// Media type of binary content of unknown type
+pub const BINARY_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// Key holding the body in variants
+const BODY_KEY: &str = "body";
+
+/// Default size limit of cached fixture content
+pub const DEFAULT_FIXTURE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Fixture content inlined in a variant body as a `data:` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineFixture {
+    /// Media type of the content (e.g. `image/png`)
+    pub media_type: String,
+    /// Decoded content
+    pub data: Vec<u8>,
+}
+
+/// Get the fixture reference of a variant body without the `file:` prefix, if any.
+fn fixture_reference(body: &Value) -> Option<&str> {
+    match body {
+        Value::String(s) => s.strip_prefix(FILE_PREFIX),
+        _ => None,
+    }
+}
+
+/// Get the fixture path referenced by a variant body, if any.
+///
+/// Inline `data:` fixtures have no path, see [`inline_fixture`].
+pub fn fixture_path(body: &Value) -> Option<&Path> {
+    fixture_reference(body)
+        .filter(|reference| !reference.starts_with(DATA_PREFIX))
+        .map(Path::new)
+}
+
+/// Get the `data:` URI inlined in a variant body, if any.
+pub fn data_uri(body: &Value) -> Option<&str> {
+    fixture_reference(body).filter(|reference| reference.starts_with(DATA_PREFIX))
+}
+
+/// Decode the inline fixture of a variant body, if any.
+///
+/// Returns `None` for other bodies and malformed `data:` URIs, which are rejected
+/// when config files are loaded.
+pub fn inline_fixture(body: &Value) -> Option<InlineFixture> {
+    data_uri(body).and_then(|uri| parse_data_uri(uri).ok())
+}
+
+/// Parse a `data:[<media type>][;base64],<data>` URI.
+///
+/// Content without `;base64` is percent-encoded text.
+pub fn parse_data_uri(uri: &str) -> Result<InlineFixture, ConfigError> {
+    let invalid = |message: &str| ConfigError::InlineFixture(message.to_string());
+    let (header, content) = uri
+        .strip_prefix(DATA_PREFIX)
+        .and_then(|rest| rest.split_once(','))
+        .ok_or_else(|| invalid("expected `data:[<media type>][;base64],<data>`"))?;
+
+    let (media_type, is_base64) = match header.strip_suffix(";base64") {
+        Some(media_type) => (media_type, true),
+        None => (header, false),
+    };
+    let data = if is_base64 {
+        base64::engine::general_purpose::STANDARD
+            .decode(content.trim())
+            .map_err(|e| invalid(&e.to_string()))?
+    } else {
+        urlencoding::decode_binary(content.as_bytes()).into_owned()
+    };
+
+    Ok(InlineFixture {
+        media_type: match media_type {
+            "" => DEFAULT_MEDIA_TYPE.to_string(),
+            media_type => media_type.to_string(),
+        },
+        data,
+    })
+}
+
+/// Encode binary content as a base64 `data:` URI.
+pub fn encode_data_uri(media_type: &str, data: &[u8]) -> String {
+    format!(
+        "{}{};base64,{}",
+        DATA_PREFIX,
+        media_type,
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+/// Build a variant body inlining binary content as a `file:data:` fixture.
+pub fn inline_fixture_body(media_type: &str, data: &[u8]) -> Value {
+    Value::String(format!(
+        "{}{}",
+        FILE_PREFIX,
+        encode_data_uri(media_type, data)
+    ))
+}
+
+/// Replace fixture references in `body` fields with canonical paths.
+///
+/// Every `body` field is treated as a variant body, so variants moved to other
+/// files with `$include` are resolved against their own file. Inline `data:`
+/// fixtures are validated and kept as is.
+///
+/// Returns error if a referenced fixture file doesn't exist or an inline fixture
+/// is malformed.
+pub fn resolve_fixture_paths(value: Value, base_dir: &Path) -> Result<Value, ConfigError> {
+    match value {
+        Value::Object(obj) => obj
+            .into_iter()
+            .map(|(key, value)| {
+                if key == BODY_KEY {
+                    if let Some(uri) = data_uri(&value) {
+                        parse_data_uri(uri)?;
+                        return Ok((key, value));
+                    }
+                }
+                let value = match fixture_path(&value) {
+                    Some(path) if key == BODY_KEY => resolve_fixture(path, base_dir)?,
+                    _ => resolve_fixture_paths(value, base_dir)?,
+                };
+                Ok((key, value))
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        Value::Array(arr) => arr
+            .into_iter()
+            .map(|item| resolve_fixture_paths(item, base_dir))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        other => Ok(other),
+    }
+}
+
+/// Build a fixture reference with the canonical path of a fixture file.
+fn resolve_fixture(path: &Path, base_dir: &Path) -> Result<Value, ConfigError> {
+    let path = base_dir.join(path);
+    let canonical = fs::canonicalize(&path).map_err(|e| ConfigError::Io {
+        source: e,
+        path: path.to_string_lossy().into_owned(),
+    })?;
+    Ok(Value::String(format!(
+        "{}{}",
+        FILE_PREFIX,
+        canonical.to_string_lossy()
+    )))
+}
+
+/// Contents of fixture files, read on first use and evicted least recently used first.
+///
+/// Files are checked for modification on every read, so edited fixtures are
+/// served without reloading config. Files larger than the cache are read on every
+/// use without being cached.
+#[derive(Debug)]
+pub struct FixtureCache {
+    /// Size limit of cached content in bytes
+    capacity: AtomicUsize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<PathBuf, CachedFixture>,
+    /// Size of cached content in bytes
+    size: usize,
+    /// Incremented on every read, orders entries by last use
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CachedFixture {
+    data: Arc<[u8]>,
+    modified: Option<SystemTime>,
+    last_used: u64,
+}
+
+impl FixtureCache {
+    /// Create a cache holding up to `capacity` bytes of fixture content.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Read a fixture file, from the cache if it's unchanged since it was cached.
+    ///
+    /// Returns error if the file can't be read.
+    pub fn read(&self, path: &Path) -> Result<Arc<[u8]>, ConfigError> {
+        let io_error = |source| ConfigError::Io {
+            source,
+            path: path.display().to_string(),
+        };
+        let metadata = fs::metadata(path).map_err(io_error)?;
+        let modified = metadata.modified().ok();
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.clock += 1;
+        let clock = state.clock;
+        if let Some(entry) = state.entries.get_mut(path) {
+            let unchanged = entry.modified.is_some()
+                && entry.modified == modified
+                && entry.data.len() as u64 == metadata.len();
+            if unchanged {
+                entry.last_used = clock;
+                return Ok(Arc::clone(&entry.data));
+            }
+        }
+        if let Some(stale) = state.entries.remove(path) {
+            state.size -= stale.data.len();
+        }
+        drop(state);
+
+        let data: Arc<[u8]> = fs::read(path).map_err(io_error)?.into();
+        let capacity = self.capacity();
+        if data.len() <= capacity {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.insert(
+                path.to_path_buf(),
+                CachedFixture {
+                    data: Arc::clone(&data),
+                    modified,
+                    last_used: clock,
+                },
+            );
+            state.evict(capacity);
+        }
+        Ok(data)
+    }
+
+    /// Size limit of cached content in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Change the size limit, evicting least recently used content beyond it.
+    ///
+    /// A limit of 0 disables caching, so fixtures are read on every use.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .evict(capacity);
+    }
+
+    /// Size of cached content in bytes.
+    pub fn size(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .size
+    }
+
+    /// Remove all cached content.
+    pub fn clear(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = CacheState::default();
+    }
+}
+
+impl Default for FixtureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_FIXTURE_CACHE_BYTES)
+    }
+}
+
+impl CacheState {
+    fn insert(&mut self, path: PathBuf, entry: CachedFixture) {
+        self.size += entry.data.len();
+        if let Some(replaced) = self.entries.insert(path, entry) {
+            self.size -= replaced.data.len();
+        }
+    }
+
+    /// Evict least recently used entries until the size is within `capacity`.
+    fn evict(&mut self, capacity: usize) {
+        while self.size > capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.size -= evicted.data.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_dir::TestDir;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn create_test_dir(name: &str) -> TestDir {
+        TestDir::with_files(name, &[("fixtures/users.json", "[]")])
+    }
+
+    #[rstest]
+    #[case(json!("file:fixtures/users.json"), Some("fixtures/users.json"))]
+    #[case(json!("file:data:,hello"), None)]
+    #[case(json!("plain text"), None)]
+    #[case(json!({"file": "x"}), None)]
+    fn test_fixture_path(#[case] body: Value, #[case] expected: Option<&str>) {
+        assert_eq!(fixture_path(&body), expected.map(Path::new));
+    }
+
+    #[rstest]
+    #[case("data:image/png;base64,iVBORw==", "image/png", b"\x89PNG".as_slice())]
+    #[case("data:;base64,aGk=", "text/plain", b"hi".as_slice())]
+    #[case("data:text/csv,id%2Cname%0A1", "text/csv", b"id,name\n1".as_slice())]
+    #[case("data:,", "text/plain", b"".as_slice())]
+    fn test_parse_data_uri(#[case] uri: &str, #[case] media_type: &str, #[case] data: &[u8]) {
+        let fixture = parse_data_uri(uri).unwrap();
+        assert_eq!(fixture.media_type, media_type);
+        assert_eq!(fixture.data, data);
+    }
+
+    #[rstest]
+    fn test_inline_fixture_body() {
+        let body = inline_fixture_body("image/png", b"\x89PNG");
+        assert_eq!(body, json!("file:data:image/png;base64,iVBORw=="));
+        assert_eq!(
+            inline_fixture(&body),
+            Some(InlineFixture {
+                media_type: "image/png".to_string(),
+                data: b"\x89PNG".to_vec(),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("data:image/png;base64")]
+    #[case("data:image/png;base64,not base64!")]
+    #[case("image/png;base64,aGk=")]
+    fn test_parse_data_uri_invalid(#[case] uri: &str) {
+        assert!(matches!(
+            parse_data_uri(uri),
+            Err(ConfigError::InlineFixture(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_resolve_fixture_paths() {
+        let dir = create_test_dir("fixtures_resolve");
+        let value = json!({
+            "presets": [{
+                "id": "p",
+                "variants": [
+                    {"id": "file", "body": "file:./fixtures/users.json"},
+                    {"id": "inline", "body": {"users": []}}
+                ]
+            }],
+            "payload": "file:./missing.json"
+        });
+
+        let resolved = resolve_fixture_paths(value, &dir).unwrap();
+        let canonical = fs::canonicalize(dir.join("fixtures/users.json")).unwrap();
+        assert_eq!(
+            resolved["presets"][0]["variants"][0]["body"],
+            json!(format!("file:{}", canonical.to_string_lossy()))
+        );
+        assert_eq!(
+            resolved["presets"][0]["variants"][1]["body"],
+            json!({"users": []})
+        );
+        // Only bodies are resolved
+        assert_eq!(resolved["payload"], "file:./missing.json");
+    }
+
+    #[rstest]
+    fn test_resolve_fixture_paths_inline() {
+        let dir = create_test_dir("fixtures_inline");
+        let value = json!({"id": "v", "body": "file:data:text/plain;base64,aGk="});
+        let resolved = resolve_fixture_paths(value.clone(), &dir).unwrap();
+        assert_eq!(resolved, value);
+
+        let value = json!({"id": "v", "body": "file:data:text/plain;base64,%%%"});
+        let err = resolve_fixture_paths(value, &dir).unwrap_err();
+        assert!(matches!(err, ConfigError::InlineFixture(_)));
+    }
+
+    #[rstest]
+    fn test_resolve_fixture_paths_missing_file() {
+        let dir = create_test_dir("fixtures_missing");
+        let value = json!({"id": "v", "body": "file:missing.json"});
+
+        let err = resolve_fixture_paths(value, &dir).unwrap_err();
+        assert!(matches!(err, ConfigError::Io { path, .. } if path.ends_with("missing.json")));
+    }
+
+    #[rstest]
+    fn test_fixture_cache_reads_once() {
+        let dir = create_test_dir("fixtures_cache");
+        let path = dir.join("fixtures/users.json");
+        let cache = FixtureCache::default();
+
+        let first = cache.read(&path).unwrap();
+        let second = cache.read(&path).unwrap();
+        assert_eq!(&*first, b"[]");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.size(), 2);
+
+        // Modified files are read again
+        fs::write(&path, r#"[{"id": 1}]"#).unwrap();
+        assert_eq!(&*cache.read(&path).unwrap(), br#"[{"id": 1}]"#);
+        assert_eq!(cache.size(), 11);
+
+        cache.clear();
+        assert_eq!(cache.size(), 0);
+    }
+
+    #[rstest]
+    fn test_fixture_cache_evicts_least_recently_used() {
+        let dir = create_test_dir("fixtures_cache_evict");
+        let file = |name: &str, size: usize| {
+            let path = dir.join("fixtures").join(name);
+            fs::write(&path, "x".repeat(size)).unwrap();
+            path
+        };
+        let (a, b, c, large) = (file("a", 4), file("b", 4), file("c", 4), file("large", 20));
+        let cache = FixtureCache::new(10);
+
+        let cached_a = cache.read(&a).unwrap();
+        let cached_b = cache.read(&b).unwrap();
+        cache.read(&a).unwrap();
+        cache.read(&c).unwrap();
+
+        // `b` was used least recently
+        assert!(Arc::ptr_eq(&cached_a, &cache.read(&a).unwrap()));
+        assert!(!Arc::ptr_eq(&cached_b, &cache.read(&b).unwrap()));
+        assert_eq!(cache.size(), 8);
+
+        // Files larger than the cache aren't cached
+        assert_eq!(cache.read(&large).unwrap().len(), 20);
+        assert_eq!(cache.size(), 8);
+
+        // Lowering the limit evicts down to it
+        cache.set_capacity(4);
+        assert_eq!((cache.capacity(), cache.size()), (4, 4));
+        cache.set_capacity(0);
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.read(&a).unwrap().len(), 4);
+        assert_eq!(cache.size(), 0);
+
+        let err = cache.read(&dir.join("missing.json")).unwrap_err();
+        assert!(matches!(err, ConfigError::Io { path, .. } if path.ends_with("missing.json")));
+    }
+}
@@ -0,0 +1,334 @@
+//! `$include` directives across config files.
+//!
+//! Any object in a config file may be replaced with the content of another file:
+//!
+//! ```yaml
+//! presets:
+//!   - $include: ./shared/auth-preset.yaml
+//!   - id: custom
+//!     headers:
+//!       $include: ./shared/headers.yaml#/default
+//! ```
+//!
+//! Paths are relative to the including file. An optional `#/pointer` fragment
+//! (JSON Pointer) selects a part of the included file. Other keys next to
+//! `$include` are merged over the included object.
+//!
+//! Large JSON files holding an array can be loaded item by item with
+//! [`load_array_items`], without reading the whole file into memory.
+
+use crate::config::dataset::resolve_datasets;
+use crate::config::env::substitute_env_in_value;
+use crate::config::error::ConfigError;
+use crate::config::fixtures::resolve_fixture_paths;
+use crate::config::parser::parse_config;
+use crate::config::proto::resolve_descriptor_paths;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Key of the include directive
+pub const INCLUDE_KEY: &str = "$include";
+
+/// Load a config file into a JSON value, resolving `$include` directives.
+///
+/// Returns error if an included file can't be loaded, a fragment doesn't exist
+/// or files include each other in a cycle.
+pub fn load_value(path: &str) -> Result<Value, ConfigError> {
+    load_file(Path::new(path), &mut Vec::new())
+}
+
+/// Load a file, resolving includes with `stack` holding the files being loaded.
+fn load_file(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Value, ConfigError> {
+    let display_path = path.to_string_lossy().into_owned();
+    let io_error = |e| ConfigError::Io {
+        source: e,
+        path: display_path.clone(),
+    };
+    let canonical = fs::canonicalize(path).map_err(io_error)?;
+
+    if stack.contains(&canonical) {
+        let mut cycle: Vec<String> = stack
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        cycle.push(canonical.to_string_lossy().into_owned());
+        return Err(ConfigError::IncludeCycle(cycle.join(" -> ")));
+    }
+
+    let content = fs::read_to_string(path).map_err(io_error)?;
+    let value: Value = parse_config(&content, &display_path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    stack.push(canonical);
+    let resolved = resolve_value(value, base_dir, stack);
+    stack.pop();
+    resolved
+}
+
+/// Resolve file references and includes in a value loaded from a file in `base_dir`.
+fn resolve_value(
+    value: Value,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    let value = resolve_fixture_paths(value, base_dir)?;
+    let value = resolve_descriptor_paths(value, base_dir)?;
+    let value = resolve_datasets(value, base_dir)?;
+    resolve_includes(value, base_dir, stack)
+}
+
+/// Load the items of a JSON file holding an array, one at a time.
+///
+/// The file is read through a buffer and each item is resolved like in
+/// [`load_value`] and passed to `f` with its index, so only the converted items
+/// are kept in memory. `${ENV:...}` placeholders are substituted in strings.
+///
+/// Returns `None` if the file doesn't hold an array, without parsing it.
+pub fn load_array_items<T, F>(path: &str, mut f: F) -> Result<Option<Vec<T>>, ConfigError>
+where
+    F: FnMut(Value, usize) -> Result<T, ConfigError>,
+{
+    let io_error = |e| ConfigError::Io {
+        source: e,
+        path: path.to_string(),
+    };
+    let canonical = fs::canonicalize(path).map_err(io_error)?;
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    if !starts_with_array(&mut reader).map_err(io_error)? {
+        return Ok(None);
+    }
+
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let mut stack = vec![canonical];
+    let mut items = Vec::new();
+    let mut failure = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = deserializer.deserialize_seq(ItemVisitor(|value| {
+        let item = substitute_env_in_value(value)
+            .and_then(|value| resolve_value(value, base_dir, &mut stack))
+            .and_then(|value| f(value, items.len()));
+        match item {
+            Ok(item) => items.push(item),
+            Err(e) => failure = Some(e),
+        }
+        failure.is_none()
+    }));
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    result?;
+    deserializer.end()?;
+    Ok(Some(items))
+}
+
+/// Check if the content of a reader starts with an array, skipping whitespace.
+fn starts_with_array(reader: &mut impl BufRead) -> io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&first) = buf.first() else {
+            return Ok(false);
+        };
+        if !first.is_ascii_whitespace() {
+            return Ok(first == b'[');
+        }
+        let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        reader.consume(whitespace);
+    }
+}
+
+/// Visitor passing array items to a callback, which returns `false` to stop.
+struct ItemVisitor<F>(F);
+
+impl<'de, F: FnMut(Value) -> bool> Visitor<'de> for ItemVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<Value>()? {
+            if !(self.0)(item) {
+                return Err(de::Error::custom("stopped at a failing item"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Recursively replace include directives in a value.
+fn resolve_includes(
+    value: Value,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    match value {
+        Value::Object(mut obj) => {
+            let include = match obj.remove(INCLUDE_KEY) {
+                Some(Value::String(include)) => Some(include),
+                Some(other) => {
+                    return Err(ConfigError::InvalidInclude(format!(
+                        "{} must be a string, got {}",
+                        INCLUDE_KEY, other
+                    )))
+                }
+                None => None,
+            };
+
+            let mut resolved = serde_json::Map::with_capacity(obj.len());
+            for (key, value) in obj {
+                resolved.insert(key, resolve_includes(value, base_dir, stack)?);
+            }
+
+            match include {
+                Some(include) => {
+                    let included = load_include(&include, base_dir, stack)?;
+                    merge_siblings(included, resolved, &include)
+                }
+                None => Ok(Value::Object(resolved)),
+            }
+        }
+        Value::Array(arr) => arr
+            .into_iter()
+            .map(|item| resolve_includes(item, base_dir, stack))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        other => Ok(other),
+    }
+}
+
+/// Load an include target (`path` or `path#/pointer`).
+fn load_include(
+    include: &str,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    let (file, fragment) = match include.split_once('#') {
+        Some((file, fragment)) => (file, Some(fragment)),
+        None => (include, None),
+    };
+
+    let value = load_file(&base_dir.join(file), stack)?;
+    match fragment {
+        Some(pointer) => value
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| ConfigError::InvalidInclude(format!("Fragment not found: {}", include))),
+        None => Ok(value),
+    }
+}
+
+/// Merge keys defined next to `$include` over the included value.
+fn merge_siblings(
+    included: Value,
+    siblings: serde_json::Map<String, Value>,
+    include: &str,
+) -> Result<Value, ConfigError> {
+    if siblings.is_empty() {
+        return Ok(included);
+    }
+    match included {
+        Value::Object(mut obj) => {
+            obj.extend(siblings);
+            Ok(Value::Object(obj))
+        }
+        _ => Err(ConfigError::InvalidInclude(format!(
+            "{} is not an object and can't be merged with sibling keys",
+            include
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_dir::TestDir;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    fn test_load_value_resolves_includes() {
+        let dir = TestDir::with_files(
+            "include_resolve",
+            &[
+                (
+                    "route.yaml",
+                    "id: users\npresets:\n  - $include: ./shared/preset.json\n    id: renamed\n  - id: other\n    headers:\n      $include: shared/headers.yaml#/default",
+                ),
+                ("shared/preset.json", r#"{"id": "auth", "variants": []}"#),
+                ("shared/headers.yaml", "default:\n  authorization: token"),
+            ],
+        );
+
+        let value = load_value(dir.join("route.yaml").to_str().unwrap()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "id": "users",
+                "presets": [
+                    {"id": "renamed", "variants": []},
+                    {"id": "other", "headers": {"authorization": "token"}}
+                ]
+            })
+        );
+    }
+
+    #[rstest]
+    fn test_load_value_resolves_fixtures_per_file() {
+        let dir = TestDir::with_files(
+            "include_fixtures",
+            &[
+                (
+                    "route.yaml",
+                    "variants:\n  - id: a\n    body: file:data/a.json\n  - $include: shared/variant.yaml",
+                ),
+                ("data/a.json", "{}"),
+                ("shared/variant.yaml", "id: b\nbody: file:../data/a.json"),
+            ],
+        );
+        let value = load_value(dir.join("route.yaml").to_str().unwrap()).unwrap();
+        let fixture = fs::canonicalize(dir.join("data/a.json")).unwrap();
+        let expected = json!(format!("file:{}", fixture.to_string_lossy()));
+        assert_eq!(value["variants"][0]["body"], expected);
+        assert_eq!(value["variants"][1]["body"], expected);
+    }
+
+    #[rstest]
+    fn test_load_value_detects_cycles() {
+        let dir = TestDir::with_files(
+            "include_cycle",
+            &[
+                ("a.yaml", "nested:\n  $include: b.yaml"),
+                ("b.yaml", "nested:\n  $include: a.yaml"),
+            ],
+        );
+
+        let error = load_value(dir.join("a.yaml").to_str().unwrap()).unwrap_err();
+        match error {
+            ConfigError::IncludeCycle(cycle) => {
+                assert_eq!(cycle.matches("a.yaml").count(), 2);
+                assert!(cycle.contains("b.yaml"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[rstest]
+    #[case(r#"{"$include": "shared.json#/missing"}"#, "Fragment not found")]
+    #[case(r#"{"$include": 1}"#, "must be a string")]
+    #[case(r#"{"$include": "shared.json#/list", "id": "x"}"#, "can't be merged")]
+    fn test_load_value_invalid_include(#[case] content: &str, #[case] message: &str) {
+        let dir = TestDir::with_files(
+            "include_invalid",
+            &[("main.json", content), ("shared.json", r#"{"list": [1]}"#)],
+        );
+
+        let error = load_value(dir.join("main.json").to_str().unwrap()).unwrap_err();
+        assert!(matches!(error, ConfigError::InvalidInclude(_)));
+        assert!(error.to_string().contains(message));
+    }
+}
@@ -0,0 +1,155 @@
+//! JSON Schemas of config files.
+//!
+//! Schemas are generated from the config types, so editors and external validators
+//! can offer autocomplete and validation for fixture files, e.g. with the YAML
+//! language server:
+//!
+//! ```yaml
+//! # yaml-language-server: $schema=./schemas/routes.schema.json
+//! - id: users
+//!   url: /api/users
+//! ```
+
+use crate::types::{collection::Collection, group::Group, route::Route, settings::Settings};
+use crate::vars::Vars;
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+
+/// Kind of config file described by a schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFileKind {
+    /// Route files (`routes/**`)
+    Routes,
+    /// Collection files (`collections/**`)
+    Collections,
+    /// Route group files (`groups/**`)
+    Groups,
+    /// Settings file (`mockito.config.*`)
+    Settings,
+    /// Combined project file with all sections
+    Project,
+}
+
+/// Generate the JSON Schema (draft-07) of a config file kind.
+///
+/// Definition files accept a single item, an array of items, or both wrapped
+/// with a file-level `vars` block.
+pub fn json_schema(kind: ConfigFileKind) -> Value {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let (title, schema) = match kind {
+        ConfigFileKind::Routes => (
+            "Mockito routes",
+            definitions_schema::<Route>(&mut generator, "routes"),
+        ),
+        ConfigFileKind::Collections => (
+            "Mockito collections",
+            definitions_schema::<Collection>(&mut generator, "collections"),
+        ),
+        ConfigFileKind::Groups => (
+            "Mockito route groups",
+            definitions_schema::<Group>(&mut generator, "groups"),
+        ),
+        ConfigFileKind::Settings => (
+            "Mockito settings",
+            generator.subschema_for::<Settings>().to_value(),
+        ),
+        ConfigFileKind::Project => ("Mockito project", project_schema(&mut generator)),
+    };
+
+    let mut root = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": title,
+        "allOf": [schema],
+    });
+    let definitions = generator.take_definitions(true);
+    if !definitions.is_empty() {
+        root["definitions"] = Value::Object(definitions);
+    }
+    root
+}
+
+/// Schema of a file holding one or many definitions, optionally with `vars`.
+fn definitions_schema<T: JsonSchema>(generator: &mut SchemaGenerator, items_key: &str) -> Value {
+    let item = generator.subschema_for::<T>().to_value();
+    let array = json!({"type": "array", "items": item});
+    let vars = generator.subschema_for::<Vars>().to_value();
+    json!({
+        "anyOf": [
+            item,
+            array,
+            {
+                "type": "object",
+                "properties": {
+                    "vars": vars,
+                    items_key: {"anyOf": [item, array]},
+                },
+                "required": [items_key],
+                "additionalProperties": false,
+            },
+        ]
+    })
+}
+
+/// Schema of a combined project file.
+fn project_schema(generator: &mut SchemaGenerator) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "vars": generator.subschema_for::<Vars>().to_value(),
+            "settings": generator.subschema_for::<Settings>().to_value(),
+            "routes": generator.subschema_for::<Vec<Route>>().to_value(),
+            "collections": generator.subschema_for::<Vec<Collection>>().to_value(),
+            "groups": generator.subschema_for::<Vec<Group>>().to_value(),
+        },
+        "additionalProperties": false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(ConfigFileKind::Routes, "Route")]
+    #[case(ConfigFileKind::Collections, "Collection")]
+    #[case(ConfigFileKind::Groups, "Group")]
+    #[case(ConfigFileKind::Settings, "Settings")]
+    #[case(ConfigFileKind::Project, "Route")]
+    fn test_json_schema_definitions(#[case] kind: ConfigFileKind, #[case] definition: &str) {
+        let schema = json_schema(kind);
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert!(schema["definitions"].get(definition).is_some());
+    }
+
+    #[rstest]
+    fn test_json_schema_route() {
+        let schema = json_schema(ConfigFileKind::Routes);
+        let route = &schema["definitions"]["Route"];
+        assert_eq!(route["additionalProperties"], false);
+        assert_eq!(
+            route["required"],
+            json!(["id", "url", "transport", "presets"])
+        );
+        assert_eq!(
+            schema["definitions"]["Transport"]["enum"],
+            json!(["HTTP", "WEBSOCKET", "SSE", "TCP"])
+        );
+
+        let variant = &schema["definitions"]["Variant"];
+        assert_eq!(variant["properties"]["status"]["minimum"], 100);
+        assert_eq!(variant["properties"]["status"]["maximum"], 599);
+
+        let query = &schema["definitions"]["QueryOrExpression"];
+        assert_eq!(query["anyOf"][1]["pattern"], "^\\$\\{.*\\}$");
+    }
+
+    #[rstest]
+    fn test_json_schema_file_wrapper() {
+        let schema = json_schema(ConfigFileKind::Collections);
+        let wrapper = &schema["allOf"][0]["anyOf"][2];
+        assert_eq!(wrapper["required"], json!(["collections"]));
+        assert!(wrapper["properties"].get("vars").is_some());
+    }
+}
@@ -0,0 +1,507 @@
+//! Linting of loaded config for likely mistakes.
+//!
+//! Unlike [`validation`](crate::mocks::validation), lint warnings point at
+//! definitions that resolve fine but probably don't behave as intended.
+
+use crate::expression;
+use crate::matching::{hashmap_intersects, object_intersects, url_matches};
+use crate::mocks::manager::{ActiveRoute, DefinitionKind, MocksManager};
+use crate::types::preset::{HeadersOrExpression, PayloadOrExpression, QueryOrExpression};
+use crate::types::route::{Route, RouteReference};
+use glob::Pattern;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Kind of likely mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    /// Active route never matches because an earlier one matches all its requests
+    ShadowedPreset,
+    /// Route isn't referenced by any collection
+    UnreferencedRoute,
+    /// Variant with a redirect status has no `Location` header
+    RedirectWithoutLocation,
+    /// Preset expression doesn't compile
+    InvalidExpression,
+}
+
+impl LintRule {
+    /// Rule name used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::ShadowedPreset => "shadowed-preset",
+            LintRule::UnreferencedRoute => "unreferenced-route",
+            LintRule::RedirectWithoutLocation => "redirect-without-location",
+            LintRule::InvalidExpression => "invalid-expression",
+        }
+    }
+}
+
+/// Likely mistake found by the linter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// Rule that produced the warning
+    pub rule: LintRule,
+    /// Location of the problem (e.g. `routes.users.presets[0].variants[1]`)
+    pub path: String,
+    /// Human-readable description
+    pub message: String,
+    /// File the definition was loaded from, if known
+    pub file: Option<String>,
+}
+
+impl LintWarning {
+    fn new(rule: LintRule, path: String, message: String) -> Self {
+        Self {
+            rule,
+            path,
+            message,
+            file: None,
+        }
+    }
+}
+
+impl std::fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] ", self.rule.name())?;
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Lint all routes and collections stored in the manager.
+///
+/// Warnings are ordered by routes first, then collections, both sorted by ID.
+pub fn lint(manager: &MocksManager) -> Vec<LintWarning> {
+    lint_with(manager, |_, _| None)
+}
+
+/// Lint all routes and collections stored in the manager, attaching files.
+///
+/// `source` returns the file a definition was loaded from, if known.
+pub fn lint_with(
+    manager: &MocksManager,
+    source: impl Fn(DefinitionKind, &str) -> Option<String>,
+) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    let referenced = referenced_routes(manager);
+
+    for route in manager.list_routes() {
+        let start = warnings.len();
+        let route_path = format!("routes.{}", route.id);
+        if !referenced.contains(&*route.id) {
+            warnings.push(LintWarning::new(
+                LintRule::UnreferencedRoute,
+                route_path.clone(),
+                "Route is not referenced by any collection".to_string(),
+            ));
+        }
+        lint_presets(manager, route, &route_path, &mut warnings);
+        attach_file(
+            &mut warnings[start..],
+            source(DefinitionKind::Route, &route.id),
+        );
+    }
+
+    for collection in manager.list_collections() {
+        let start = warnings.len();
+        // Unresolvable collections are reported by validation
+        if let Ok(active_routes) = manager.resolve_collection(&collection.id) {
+            lint_shadowing(&collection.id, &active_routes, &mut warnings);
+        }
+        attach_file(
+            &mut warnings[start..],
+            source(DefinitionKind::Collection, &collection.id),
+        );
+    }
+
+    warnings
+}
+
+/// Set the file of warnings found for a definition.
+fn attach_file(warnings: &mut [LintWarning], file: Option<String>) {
+    for warning in warnings {
+        warning.file = file.clone();
+    }
+}
+
+/// IDs of routes referenced by any collection, including wildcard references.
+fn referenced_routes(manager: &MocksManager) -> HashSet<&str> {
+    let routes = manager.list_routes();
+    let mut referenced = HashSet::new();
+    for collection in manager.list_collections() {
+        for reference in &collection.routes {
+            let Some(reference) = RouteReference::parse(reference) else {
+                continue;
+            };
+            match Pattern::new(&reference.route_id) {
+                Ok(pattern) if reference.is_wildcard() => referenced.extend(
+                    routes
+                        .iter()
+                        .filter(|r| pattern.matches(&r.id))
+                        .map(|r| &*r.id),
+                ),
+                _ => {
+                    if let Some(route) = manager.get_route(&reference.route_id) {
+                        referenced.insert(&*route.id);
+                    }
+                }
+            }
+        }
+    }
+    referenced
+}
+
+/// Check preset expressions and redirect variants of a route.
+fn lint_presets(
+    manager: &MocksManager,
+    route: &Route,
+    route_path: &str,
+    warnings: &mut Vec<LintWarning>,
+) {
+    let group_headers = route
+        .group
+        .as_deref()
+        .and_then(|id| manager.get_group(id))
+        .and_then(|group| group.headers.as_ref());
+
+    for (i, preset) in route.presets.iter().enumerate() {
+        let preset_path = format!("{}.presets[{}]", route_path, i);
+
+        for (field, expression) in preset.expressions() {
+            if let Err(e) = expression::compile(expression) {
+                warnings.push(LintWarning::new(
+                    LintRule::InvalidExpression,
+                    format!("{}.{}", preset_path, field),
+                    e.to_string(),
+                ));
+            }
+        }
+
+        for (j, variant) in preset.variants.iter().enumerate() {
+            let is_redirect =
+                matches!(variant.status, Some(300..=399) if variant.status != Some(304));
+            let has_location = [variant.headers.as_ref(), group_headers]
+                .into_iter()
+                .flatten()
+                .any(|headers| has_header(headers, "location"));
+            if is_redirect && !has_location {
+                warnings.push(LintWarning::new(
+                    LintRule::RedirectWithoutLocation,
+                    format!("{}.variants[{}]", preset_path, j),
+                    format!(
+                        "Variant '{}' has redirect status {} but no Location header",
+                        variant.id,
+                        variant.status.unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Report active routes matching only requests an earlier active route matches.
+fn lint_shadowing(
+    collection_id: &str,
+    active_routes: &[ActiveRoute],
+    warnings: &mut Vec<LintWarning>,
+) {
+    for (j, shadowed) in active_routes.iter().enumerate() {
+        let shadowing = active_routes[..j]
+            .iter()
+            .find(|earlier| shadows(earlier, shadowed));
+        if let Some(earlier) = shadowing {
+            warnings.push(LintWarning::new(
+                LintRule::ShadowedPreset,
+                format!("collections.{}", collection_id),
+                format!(
+                    "Route '{}' (preset '{}') never matches, all its requests match '{}' (preset '{}') first",
+                    shadowed.route.id, shadowed.preset().id, earlier.route.id, earlier.preset().id
+                ),
+            ));
+        }
+    }
+}
+
+/// Check if every request matching `later` also matches `earlier`.
+///
+/// Expressions are only considered broader when both presets use the same one.
+fn shadows(earlier: &ActiveRoute, later: &ActiveRoute) -> bool {
+    let (e, l) = (&earlier.route, &later.route);
+    if e.transport != l.transport {
+        return false;
+    }
+    if e.method.is_some() && e.method != l.method {
+        return false;
+    }
+    // Parameter segments of the later URL are matched as literal segments
+    if !url_matches(&e.url, &l.url).matched {
+        return false;
+    }
+
+    let (e, l) = (earlier.preset(), later.preset());
+    let params_broader = match &e.params {
+        None => true,
+        Some(params) if params.is_empty() => true,
+        // Parameter names only line up for identical URL patterns
+        Some(_) => {
+            earlier.route.url == later.route.url
+                && hashmap_intersects(e.params.as_ref(), l.params.as_ref())
+        }
+    };
+    let query_broader = match (&e.query, &l.query) {
+        (None, _) => true,
+        (Some(QueryOrExpression::Map(em)), Some(QueryOrExpression::Map(lm))) => {
+            hashmap_intersects(Some(em), Some(lm))
+        }
+        (Some(QueryOrExpression::Map(em)), None) => hashmap_intersects(Some(em), None),
+        (Some(eq), Some(lq)) => eq == lq,
+        _ => false,
+    };
+    let headers_broader = match (&e.headers, &l.headers) {
+        (None, _) => true,
+        (Some(HeadersOrExpression::Map(em)), Some(HeadersOrExpression::Map(lm))) => {
+            hashmap_intersects(Some(em), Some(lm))
+        }
+        (Some(HeadersOrExpression::Map(em)), None) => hashmap_intersects(Some(em), None),
+        (Some(eh), Some(lh)) => eh == lh,
+        _ => false,
+    };
+    let payload_broader = match (&e.payload, &l.payload) {
+        (None, _) => true,
+        (Some(PayloadOrExpression::Value(ev)), Some(PayloadOrExpression::Value(lv))) => {
+            object_intersects(Some(lv), Some(ev))
+        }
+        (Some(PayloadOrExpression::Value(ev)), None) => object_intersects(None, Some(ev)),
+        (Some(ep), Some(lp)) => ep == lp,
+        _ => false,
+    };
+
+    params_broader && query_broader && headers_broader && payload_broader
+}
+
+/// Check if a header map contains a header, ignoring name case.
+fn has_header(headers: &HashMap<Arc<str>, String>, name: &str) -> bool {
+    headers.keys().any(|key| key.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::collection::Collection;
+    use crate::types::preset::Preset;
+    use crate::types::route::{HttpMethod, Transport};
+    use crate::types::variant::Variant;
+    use rstest::rstest;
+
+    fn create_preset(id: &str, query: Option<&[(&str, &str)]>, status: u16) -> Preset {
+        Preset {
+            id: id.into(),
+            params: None,
+            query: query.map(|q| {
+                QueryOrExpression::Map(
+                    q.iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                )
+            }),
+            headers: None,
+            payload: None,
+            variants: vec![Variant {
+                id: "v".into(),
+                status: Some(status),
+                headers: None,
+                body: None,
+                template: None,
+            }],
+        }
+    }
+
+    fn create_route(id: &str, url: &str, presets: Vec<Preset>) -> Route {
+        Route {
+            id: id.into(),
+            url: url.to_string(),
+            transport: Transport::Http,
+            method: Some(HttpMethod::Get),
+            presets,
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
+        }
+    }
+
+    fn create_manager(routes: Vec<Route>, references: &[&str]) -> MocksManager {
+        let mut manager = MocksManager::new();
+        manager.add_routes(routes).unwrap();
+        manager
+            .add_collection(Collection {
+                id: "base".into(),
+                from: None,
+                vars: None,
+                routes: references.iter().map(|r| (*r).into()).collect(),
+            })
+            .unwrap();
+        manager
+    }
+
+    #[rstest]
+    fn test_lint_clean() {
+        let manager = create_manager(
+            vec![create_route(
+                "users",
+                "/users",
+                vec![create_preset("p", None, 200)],
+            )],
+            &["users"],
+        );
+        assert!(lint(&manager).is_empty());
+    }
+
+    #[rstest]
+    fn test_lint_unreferenced_routes() {
+        let manager = create_manager(
+            vec![
+                create_route("users-list", "/users", vec![create_preset("p", None, 200)]),
+                create_route(
+                    "users-get",
+                    "/users/{id}",
+                    vec![create_preset("p", None, 200)],
+                ),
+                create_route("orders", "/orders", vec![create_preset("p", None, 200)]),
+            ],
+            &["users-*", "missing"],
+        );
+
+        let warnings = lint(&manager);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].rule, LintRule::UnreferencedRoute);
+        assert_eq!(warnings[0].path, "routes.orders");
+    }
+
+    #[rstest]
+    #[case("/users/{id}", None, Some(&[("page", "1")][..]), true)]
+    #[case("/users/me", None, None, true)]
+    #[case("/users/{id}", Some(&[("page", "1")][..]), Some(&[("page", "1"), ("size", "10")][..]), true)]
+    #[case("/users/{id}", Some(&[("page", "2")][..]), Some(&[("page", "1")][..]), false)]
+    #[case("/users/{id}", Some(&[("page", "1")][..]), None, false)]
+    #[case("/orders", None, None, false)]
+    fn test_lint_shadowed_presets(
+        #[case] later_url: &str,
+        #[case] earlier_query: Option<&[(&str, &str)]>,
+        #[case] later_query: Option<&[(&str, &str)]>,
+        #[case] shadowed: bool,
+    ) {
+        let manager = create_manager(
+            vec![
+                create_route(
+                    "a",
+                    "/users/{id}",
+                    vec![create_preset("broad", earlier_query, 200)],
+                ),
+                create_route(
+                    "b",
+                    later_url,
+                    vec![create_preset("narrow", later_query, 200)],
+                ),
+            ],
+            &["a", "b"],
+        );
+
+        let warnings = lint(&manager);
+        assert_eq!(warnings.len(), usize::from(shadowed));
+        if shadowed {
+            assert_eq!(warnings[0].rule, LintRule::ShadowedPreset);
+            assert_eq!(warnings[0].path, "collections.base");
+            assert!(warnings[0].message.contains("Route 'b' (preset 'narrow')"));
+        }
+    }
+
+    #[rstest]
+    #[case(301, None, true)]
+    #[case(302, Some("Location"), false)]
+    #[case(307, Some("location"), false)]
+    #[case(304, None, false)]
+    #[case(200, None, false)]
+    fn test_lint_redirect_without_location(
+        #[case] status: u16,
+        #[case] header: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let mut preset = create_preset("p", None, status);
+        preset.variants[0].headers =
+            header.map(|h| HashMap::from([(h.into(), "/login".to_string())]));
+        let manager = create_manager(vec![create_route("r", "/r", vec![preset])], &["r"]);
+
+        let warnings = lint(&manager);
+        assert_eq!(warnings.len(), usize::from(expected));
+        if expected {
+            assert_eq!(warnings[0].rule, LintRule::RedirectWithoutLocation);
+            assert_eq!(warnings[0].path, "routes.r.presets[0].variants[0]");
+        }
+    }
+
+    #[rstest]
+    fn test_lint_invalid_expressions() {
+        let mut preset = create_preset("p", None, 200);
+        preset.query = Some(QueryOrExpression::Expression("page == '1'".to_string()));
+        preset.headers = Some(HeadersOrExpression::Expression("cel: size(".to_string()));
+        preset.payload = Some(PayloadOrExpression::Expression("items[".to_string()));
+        let manager = create_manager(vec![create_route("r", "/r", vec![preset])], &["r"]);
+
+        let warnings = lint(&manager);
+        let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["routes.r.presets[0].headers", "routes.r.presets[0].payload"]
+        );
+        assert!(warnings
+            .iter()
+            .all(|w| w.rule == LintRule::InvalidExpression));
+    }
+
+    #[rstest]
+    fn test_lint_warning_display() {
+        let warning = LintWarning::new(
+            LintRule::UnreferencedRoute,
+            "routes.orders".to_string(),
+            "Route is not referenced by any collection".to_string(),
+        );
+        assert_eq!(
+            warning.to_string(),
+            "[unreferenced-route] routes.orders: Route is not referenced by any collection"
+        );
+    }
+
+    #[rstest]
+    fn test_lint_with_files() {
+        let manager = create_manager(
+            vec![
+                create_route("a", "/users/{id}", vec![create_preset("broad", None, 200)]),
+                create_route("b", "/users/1", vec![create_preset("narrow", None, 200)]),
+                create_route("c", "/orders", vec![create_preset("default", None, 200)]),
+            ],
+            &["a", "b"],
+        );
+
+        let warnings = lint_with(&manager, |kind, id| Some(format!("{}s/{}.yaml", kind, id)));
+        let files: Vec<(&str, Option<&str>)> = warnings
+            .iter()
+            .map(|w| (w.path.as_str(), w.file.as_deref()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("routes.c", Some("routes/c.yaml")),
+                ("collections.base", Some("collections/base.yaml")),
+            ]
+        );
+        assert!(warnings[1]
+            .to_string()
+            .starts_with("[shadowed-preset] collections/base.yaml: collections.base: "));
+        assert!(lint(&manager).iter().all(|w| w.file.is_none()));
+    }
+}
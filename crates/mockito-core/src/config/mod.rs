@@ -1,4 +1,23 @@
 //! Configuration parsing and utilities.
 
+pub mod dataset;
+pub mod env;
 pub mod error;
+pub mod export;
+pub mod fixtures;
+pub mod include;
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
+pub mod lint;
+pub mod openapi;
 pub mod parser;
+pub mod project;
+pub mod proto;
+pub mod reload;
+pub mod remote;
+pub mod schema;
+#[cfg(test)]
+pub(crate) mod test_dir;
+pub mod watcher;
+
+pub use lint::{lint, lint_with};
@@ -0,0 +1,877 @@
+//! Route generation from OpenAPI 3.x documents.
+//!
+//! Each path + method pair becomes a route:
+//!
+//! - the route ID is the `operationId`, or derived from the method and path
+//!   (e.g. `get-users-id`); the URL is the path prefixed with the base path of
+//!   the first server, `{param}` segments are kept as is
+//! - the first preset (`default`) matches any request; more presets are created
+//!   from parameter and request body examples
+//! - each declared response becomes a variant with the response example as body,
+//!   or a sample generated from the response schema; named examples and error
+//!   statuses covered by range or `default` responses get variants of their own
+//!
+//! Hand-written routes can be linked to operations by ID (the `operationId` or
+//! the derived ID) to get the generated variants, see [`add_openapi_variants`].
+//!
+//! Local `$ref`s (`#/components/...`) are resolved, other references are ignored.
+
+use crate::config::error::ConfigError;
+use crate::config::include;
+use crate::types::preset::{HeadersOrExpression, PayloadOrExpression, Preset, QueryOrExpression};
+use crate::types::route::{HttpMethod, Route, Transport};
+use crate::types::variant::Variant;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Operation keys of a path item, with the matching route methods
+const METHODS: [(&str, HttpMethod); 7] = [
+    ("get", HttpMethod::Get),
+    ("post", HttpMethod::Post),
+    ("put", HttpMethod::Put),
+    ("patch", HttpMethod::Patch),
+    ("delete", HttpMethod::Delete),
+    ("head", HttpMethod::Head),
+    ("options", HttpMethod::Options),
+];
+
+/// ID of the preset matching any request
+const DEFAULT_PRESET: &str = "default";
+
+/// Common error statuses generated from range and `default` responses
+const ERROR_STATUSES: [u16; 4] = [400, 401, 404, 500];
+
+/// Nesting limit of generated samples, stops recursive schemas
+const MAX_SAMPLE_DEPTH: usize = 8;
+
+/// Load an OpenAPI document (YAML or JSON) and generate routes from it.
+///
+/// # Errors
+/// Returns error if the file can't be loaded or isn't an OpenAPI 3.x document.
+pub fn import_openapi(path: &str) -> Result<Vec<Route>, ConfigError> {
+    routes_from_openapi(&include::load_value(path)?)
+}
+
+/// Generate routes from a parsed OpenAPI document.
+///
+/// Routes are ordered by path, then by method.
+pub fn routes_from_openapi(doc: &Value) -> Result<Vec<Route>, ConfigError> {
+    let base_path = base_path(doc);
+    Ok(operations(doc)?
+        .into_iter()
+        .map(|op| {
+            let parameters = merge_parameters(
+                op.item.get("parameters"),
+                op.operation.get("parameters"),
+                doc,
+            );
+            let variants = response_variants(op.operation, doc);
+            Route {
+                id: op.id.into(),
+                url: format!("{}{}", base_path, op.path),
+                transport: Transport::Http,
+                method: Some(op.method),
+                presets: presets(&parameters, op.operation, variants, doc),
+                tags: op
+                    .operation
+                    .get("tags")
+                    .and_then(Value::as_array)
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(Value::as_str)
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                group: None,
+                proto: None,
+                graphql: None,
+                tcp: None,
+            }
+        })
+        .collect())
+}
+
+/// Add variants generated from OpenAPI operations to linked routes.
+///
+/// A route is linked to the operation whose ID (the `operationId`, or derived from
+/// method and path) equals the route ID. Generated variants are appended to every
+/// preset of a linked route, skipping variant IDs the preset already defines.
+///
+/// Returns the number of added variants.
+pub fn add_openapi_variants(routes: &mut [Route], doc: &Value) -> Result<usize, ConfigError> {
+    let operations: HashMap<String, &Value> = operations(doc)?
+        .into_iter()
+        .map(|op| (op.id, op.operation))
+        .collect();
+
+    let mut added = 0;
+    for route in routes.iter_mut() {
+        let Some(operation) = operations.get(&*route.id) else {
+            continue;
+        };
+        let variants = response_variants(operation, doc);
+        for preset in &mut route.presets {
+            for variant in &variants {
+                if preset.variants.iter().all(|v| v.id != variant.id) {
+                    preset.variants.push(variant.clone());
+                    added += 1;
+                }
+            }
+        }
+    }
+    Ok(added)
+}
+
+/// Operation of an OpenAPI document.
+struct Operation<'a> {
+    /// Unique route ID of the operation
+    id: String,
+    path: &'a str,
+    method: HttpMethod,
+    /// Path item declaring the operation
+    item: &'a Value,
+    operation: &'a Value,
+}
+
+/// List operations of a document, ordered by path, then by method.
+fn operations(doc: &Value) -> Result<Vec<Operation<'_>>, ConfigError> {
+    match doc.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => {}
+        Some(version) => {
+            return Err(ConfigError::OpenApi(format!(
+                "Unsupported OpenAPI version: {}",
+                version
+            )))
+        }
+        None => {
+            return Err(ConfigError::OpenApi(
+                "Missing `openapi` version".to_string(),
+            ))
+        }
+    }
+
+    let mut used_ids = HashSet::new();
+    let mut operations = Vec::new();
+    let Some(paths) = doc.get("paths").and_then(Value::as_object) else {
+        return Ok(operations);
+    };
+    for (path, item) in paths {
+        let item = resolve_ref(item, doc);
+        for (key, method) in METHODS {
+            let Some(operation) = item.get(key) else {
+                continue;
+            };
+            let id = match operation.get("operationId").and_then(Value::as_str) {
+                Some(id) => id.to_string(),
+                None => slug(key, path),
+            };
+            operations.push(Operation {
+                id: unique_id(id, &mut used_ids),
+                path,
+                method,
+                item,
+                operation,
+            });
+        }
+    }
+    Ok(operations)
+}
+
+/// Generate a sample value conforming to a schema.
+///
+/// Uses `example`, `default`, `const` or the first `enum` value when declared,
+/// otherwise builds a value from the schema type, properties and format.
+pub fn sample_from_schema(schema: &Value, doc: &Value) -> Value {
+    sample(schema, doc, 0)
+}
+
+fn sample(schema: &Value, doc: &Value, depth: usize) -> Value {
+    if depth > MAX_SAMPLE_DEPTH {
+        return Value::Null;
+    }
+    let schema = resolve_ref(schema, doc);
+
+    for key in ["example", "default", "const"] {
+        if let Some(value) = schema.get(key) {
+            return value.clone();
+        }
+    }
+    if let Some(value) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return value.clone();
+    }
+
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+        let mut merged = Map::new();
+        for part in all_of {
+            match sample(part, doc, depth + 1) {
+                Value::Object(obj) => merged.extend(obj),
+                other if all_of.len() == 1 => return other,
+                _ => {}
+            }
+        }
+        return Value::Object(merged);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|schemas| schemas.first())
+        {
+            return sample(first, doc, depth + 1);
+        }
+    }
+
+    match schema_type(schema) {
+        Some("object") => Value::Object(
+            schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(name, property)| (name.clone(), sample(property, doc, depth + 1)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        Some("array") => match schema.get("items") {
+            Some(items) => Value::Array(vec![sample(items, doc, depth + 1)]),
+            None => Value::Array(Vec::new()),
+        },
+        Some("string") => Value::String(
+            match schema.get("format").and_then(Value::as_str) {
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("email") => "user@example.com",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("uri") | Some("url") => "https://example.com",
+                _ => "string",
+            }
+            .to_string(),
+        ),
+        Some("integer") => schema
+            .get("minimum")
+            .filter(|m| m.is_i64() || m.is_u64())
+            .cloned()
+            .unwrap_or(Value::from(0)),
+        Some("number") => schema
+            .get("minimum")
+            .filter(|m| m.is_number())
+            .cloned()
+            .unwrap_or(Value::from(0)),
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Type of a schema, the first non-null one for OpenAPI 3.1 type arrays.
+///
+/// Schemas with properties but without a type are treated as objects.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => Some(t),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ => schema.get("properties").map(|_| "object"),
+    }
+}
+
+/// Follow local `$ref`s to the referenced value.
+fn resolve_ref<'a>(mut value: &'a Value, doc: &'a Value) -> &'a Value {
+    // Bounded to stop reference cycles
+    for _ in 0..MAX_SAMPLE_DEPTH {
+        let Some(target) = value
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| doc.pointer(pointer))
+        else {
+            break;
+        };
+        value = target;
+    }
+    value
+}
+
+/// Path of the first server URL, with server variables set to their defaults.
+fn base_path(doc: &Value) -> String {
+    let Some(server) = doc
+        .get("servers")
+        .and_then(Value::as_array)
+        .and_then(|servers| servers.first())
+    else {
+        return String::new();
+    };
+    let mut url = server
+        .get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    if let Some(variables) = server.get("variables").and_then(Value::as_object) {
+        for (name, variable) in variables {
+            if let Some(default) = variable.get("default").and_then(Value::as_str) {
+                url = url.replace(&format!("{{{}}}", name), default);
+            }
+        }
+    }
+
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|i| &rest[i..]).unwrap_or_default(),
+        None => url.as_str(),
+    };
+    path.trim_end_matches('/').to_string()
+}
+
+/// Route ID derived from method and path, e.g. `get-users-id`.
+fn slug(method: &str, path: &str) -> String {
+    let mut slug = method.to_string();
+    for part in path
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+    {
+        slug.push('-');
+        slug.push_str(part);
+    }
+    slug
+}
+
+/// Make an ID unique among already used ones by appending a counter.
+fn unique_id(id: String, used: &mut HashSet<String>) -> String {
+    let mut unique = id.clone();
+    let mut counter = 2;
+    while !used.insert(unique.clone()) {
+        unique = format!("{}-{}", id, counter);
+        counter += 1;
+    }
+    unique
+}
+
+/// Combine path item and operation parameters, operation ones take precedence.
+fn merge_parameters<'a>(
+    path_params: Option<&'a Value>,
+    operation_params: Option<&'a Value>,
+    doc: &'a Value,
+) -> Vec<&'a Value> {
+    let mut merged: Vec<&Value> = Vec::new();
+    for params in [path_params, operation_params].into_iter().flatten() {
+        for param in params.as_array().into_iter().flatten() {
+            let param = resolve_ref(param, doc);
+            let key = |p: &Value| (p.get("name").cloned(), p.get("in").cloned());
+            merged.retain(|existing| key(existing) != key(param));
+            merged.push(param);
+        }
+    }
+    merged
+}
+
+/// Build presets: a catch-all default one, then presets from examples.
+fn presets(
+    parameters: &[&Value],
+    operation: &Value,
+    variants: Vec<Variant>,
+    doc: &Value,
+) -> Vec<Preset> {
+    let mut presets = vec![Preset {
+        id: DEFAULT_PRESET.into(),
+        params: None,
+        query: None,
+        headers: None,
+        payload: None,
+        variants: variants.clone(),
+    }];
+
+    let mut params = HashMap::new();
+    let mut query = HashMap::new();
+    let mut headers = HashMap::new();
+    for param in parameters {
+        let (Some(name), Some(location)) = (
+            param.get("name").and_then(Value::as_str),
+            param.get("in").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let Some(example) = parameter_example(param, doc) else {
+            continue;
+        };
+        let target = match location {
+            "path" => &mut params,
+            "query" => &mut query,
+            "header" => &mut headers,
+            _ => continue,
+        };
+        target.insert(name.to_string(), example);
+    }
+
+    let payloads = operation
+        .get("requestBody")
+        .map(|body| resolve_ref(body, doc))
+        .and_then(|body| body.get("content"))
+        .and_then(json_media)
+        .map(|(_, media)| media_examples(media, doc))
+        .unwrap_or_default();
+    if payloads.is_empty() && params.is_empty() && query.is_empty() && headers.is_empty() {
+        return presets;
+    }
+
+    let non_empty = |map: &HashMap<String, String>| (!map.is_empty()).then(|| map.clone());
+    let example_preset = |id: String, payload: Option<Value>| Preset {
+        id: id.into(),
+        params: non_empty(&params),
+        query: non_empty(&query).map(QueryOrExpression::Map),
+        headers: non_empty(&headers).map(HeadersOrExpression::Map),
+        payload: payload.map(PayloadOrExpression::Value),
+        variants: variants.clone(),
+    };
+    if payloads.is_empty() {
+        presets.push(example_preset("example".to_string(), None));
+    }
+    for (name, payload) in payloads {
+        presets.push(example_preset(name, Some(payload)));
+    }
+    presets
+}
+
+/// Example value of a parameter as matched against request strings.
+fn parameter_example(param: &Value, doc: &Value) -> Option<String> {
+    let example = param
+        .get("example")
+        .cloned()
+        .or_else(|| {
+            media_examples(param, doc)
+                .into_iter()
+                .next()
+                .map(|(_, v)| v)
+        })
+        .or_else(|| {
+            param
+                .get("schema")
+                .and_then(|s| resolve_ref(s, doc).get("example").cloned())
+        })?;
+    match example {
+        Value::String(s) => Some(s),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Build variants from the declared responses of an operation, ordered by status.
+///
+/// Each response becomes a variant with the response code as ID (e.g. `200`,
+/// `4xx`). Responses with several named examples get an extra variant per example
+/// (e.g. `200-cat`). Range and `default` responses are expanded into variants for
+/// the common error statuses they cover and that aren't declared explicitly.
+fn response_variants(operation: &Value, doc: &Value) -> Vec<Variant> {
+    let Some(responses) = operation.get("responses").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut variants = Vec::new();
+    for (code, response) in responses {
+        let response = resolve_ref(response, doc);
+        let media = response.get("content").and_then(json_media);
+        let headers = media.map(|(media_type, _)| {
+            HashMap::from([("Content-Type".into(), media_type.to_string())])
+        });
+        let examples = media
+            .map(|(_, media)| media_examples(media, doc))
+            .unwrap_or_default();
+        let schema_sample = || {
+            media
+                .and_then(|(_, media)| media.get("schema"))
+                .map(|schema| sample_from_schema(schema, doc))
+        };
+        let variant = |id: String, status: u16, body: Option<Value>| Variant {
+            id: id.into(),
+            status: Some(status),
+            headers: headers.clone(),
+            body,
+            template: None,
+        };
+
+        let code = code.to_lowercase();
+        let status = status_code(&code);
+        let first_example = examples.first().map(|(_, example)| example.clone());
+        variants.push(variant(
+            code.clone(),
+            status,
+            first_example.or_else(schema_sample),
+        ));
+        if examples.len() > 1 {
+            for (name, example) in &examples {
+                variants.push(variant(
+                    format!("{}-{}", code, name),
+                    status,
+                    Some(example.clone()),
+                ));
+            }
+        }
+
+        for error_status in ERROR_STATUSES {
+            let declared = responses.contains_key(&error_status.to_string());
+            if !declared && covers_status(&code, error_status) {
+                // Examples may describe a specific error, schema samples fit all of them
+                variants.push(variant(
+                    error_status.to_string(),
+                    error_status,
+                    schema_sample(),
+                ));
+            }
+        }
+    }
+
+    // `4XX` and `default` may both expand to the same status, the first one wins
+    let mut seen = HashSet::new();
+    variants.retain(|v| seen.insert(v.id.clone()));
+    variants.sort_by_key(|v| v.status);
+    variants
+}
+
+/// Check if a range (`4xx`) or `default` response covers a status code.
+fn covers_status(code: &str, status: u16) -> bool {
+    match code {
+        "default" => true,
+        _ => code.len() == 3 && code.ends_with("xx") && status_code(code) == status / 100 * 100,
+    }
+}
+
+/// Status code of a response key (`200`, `4XX` or `default`).
+fn status_code(code: &str) -> u16 {
+    if let Ok(status) = code.parse() {
+        return status;
+    }
+    match code.as_bytes().first() {
+        Some(class @ b'1'..=b'5') if code.len() == 3 => u16::from(class - b'0') * 100,
+        _ => 500,
+    }
+}
+
+/// Pick the JSON media type of a content map, or the first one.
+fn json_media(content: &Value) -> Option<(&str, &Value)> {
+    let content = content.as_object()?;
+    content
+        .iter()
+        .find(|(media_type, _)| media_type.starts_with("application/json"))
+        .or_else(|| {
+            content
+                .iter()
+                .find(|(media_type, _)| media_type.contains("json"))
+        })
+        .or_else(|| content.iter().next())
+        .map(|(media_type, media)| (media_type.as_str(), media))
+}
+
+/// Named examples of a media type or parameter (`example` is named `example`).
+fn media_examples(media: &Value, doc: &Value) -> Vec<(String, Value)> {
+    if let Some(example) = media.get("example") {
+        return vec![("example".to_string(), example.clone())];
+    }
+    media
+        .get("examples")
+        .and_then(Value::as_object)
+        .map(|examples| {
+            examples
+                .iter()
+                .filter_map(|(name, example)| {
+                    let value = resolve_ref(example, doc).get("value")?;
+                    Some((name.clone(), value.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::parse_yaml;
+    use rstest::rstest;
+    use serde_json::json;
+
+    const PETSTORE: &str = r##"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.0.0
+servers:
+  - url: https://api.example.com/{version}
+    variables:
+      version:
+        default: v1
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      tags: [pets]
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+            example: 10
+      responses:
+        "200":
+          description: Pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Pet"
+        default:
+          $ref: "#/components/responses/Error"
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+            examples:
+              cat:
+                value: {name: Tom}
+              dog:
+                value: {name: Rex}
+      responses:
+        "201":
+          description: Created
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        example: "42"
+    get:
+      operationId: getPet
+      responses:
+        "200":
+          description: Pet
+          content:
+            application/json:
+              examples:
+                found:
+                  value: {id: 42, name: Tom}
+                renamed:
+                  value: {id: 42, name: Rex}
+        "400":
+          description: Bad request
+        4XX:
+          description: Not found
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        id: {type: integer, format: int64}
+        name: {type: string}
+        tag: {type: string, enum: [cat, dog]}
+  responses:
+    Error:
+      description: Error
+      content:
+        application/problem+json:
+          schema:
+            type: object
+            properties:
+              message: {type: string}
+"##;
+
+    fn petstore_routes() -> Vec<Route> {
+        let doc: Value = parse_yaml(PETSTORE).unwrap();
+        routes_from_openapi(&doc).unwrap()
+    }
+
+    #[rstest]
+    fn test_routes_from_openapi() {
+        let routes = petstore_routes();
+        let summary: Vec<(&str, &str, Option<HttpMethod>)> = routes
+            .iter()
+            .map(|r| (r.id.as_ref(), r.url.as_str(), r.method.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("listPets", "/v1/pets", Some(HttpMethod::Get)),
+                ("post-pets", "/v1/pets", Some(HttpMethod::Post)),
+                ("getPet", "/v1/pets/{petId}", Some(HttpMethod::Get)),
+            ]
+        );
+        assert_eq!(routes[0].tags, vec!["pets"]);
+    }
+
+    #[rstest]
+    fn test_routes_from_openapi_variants() {
+        let routes = petstore_routes();
+
+        let variants = &routes[0].presets[0].variants;
+        let ids: Vec<(&str, Option<u16>)> =
+            variants.iter().map(|v| (v.id.as_ref(), v.status)).collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("200", Some(200)),
+                ("400", Some(400)),
+                ("401", Some(401)),
+                ("404", Some(404)),
+                ("default", Some(500)),
+                ("500", Some(500)),
+            ]
+        );
+        assert_eq!(
+            variants[0].body,
+            Some(json!([{"id": 0, "name": "string", "tag": "cat"}]))
+        );
+        assert_eq!(
+            variants[4].headers.as_ref().unwrap()["Content-Type"],
+            "application/problem+json"
+        );
+        assert!(variants[1..]
+            .iter()
+            .all(|v| v.body == Some(json!({"message": "string"}))));
+
+        let variants = &routes[2].presets[0].variants;
+        let ids: Vec<&str> = variants.iter().map(|v| v.id.as_ref()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "200",
+                "200-found",
+                "200-renamed",
+                "400",
+                "4xx",
+                "401",
+                "404"
+            ]
+        );
+        assert_eq!(variants[0].body, Some(json!({"id": 42, "name": "Tom"})));
+        assert_eq!(variants[2].body, Some(json!({"id": 42, "name": "Rex"})));
+        assert_eq!(variants[4].body, None);
+    }
+
+    #[rstest]
+    fn test_routes_from_openapi_presets() {
+        let routes = petstore_routes();
+
+        let presets = &routes[0].presets;
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].id, "default".into());
+        assert_eq!(presets[0].query, None);
+        assert_eq!(
+            presets[1].query,
+            Some(QueryOrExpression::Map(HashMap::from([(
+                "limit".to_string(),
+                "10".to_string()
+            )])))
+        );
+
+        let ids: Vec<&str> = routes[1].presets.iter().map(|p| p.id.as_ref()).collect();
+        assert_eq!(ids, vec!["default", "cat", "dog"]);
+        assert_eq!(
+            routes[1].presets[1].payload,
+            Some(PayloadOrExpression::Value(json!({"name": "Tom"})))
+        );
+
+        let params = routes[2].presets[1].params.as_ref().unwrap();
+        assert_eq!(params["petId"], "42");
+    }
+
+    #[rstest]
+    fn test_add_openapi_variants() {
+        let doc: Value = parse_yaml(PETSTORE).unwrap();
+        let mut routes = vec![Route {
+            id: "getPet".into(),
+            url: "/pets/{petId}".to_string(),
+            transport: Transport::Http,
+            method: Some(HttpMethod::Get),
+            presets: vec![Preset {
+                id: "custom".into(),
+                params: None,
+                query: None,
+                headers: None,
+                payload: None,
+                variants: vec![Variant {
+                    id: "200".into(),
+                    status: Some(200),
+                    headers: None,
+                    body: Some(json!({"id": 1})),
+                    template: None,
+                }],
+            }],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
+        }];
+
+        assert_eq!(add_openapi_variants(&mut routes, &doc).unwrap(), 6);
+        let variants = &routes[0].presets[0].variants;
+        assert_eq!(variants.len(), 7);
+        // Existing variants are kept
+        assert_eq!(variants[0].body, Some(json!({"id": 1})));
+        assert_eq!(add_openapi_variants(&mut routes, &doc).unwrap(), 0);
+    }
+
+    #[rstest]
+    #[case("4xx", 404, true)]
+    #[case("4xx", 500, false)]
+    #[case("default", 401, true)]
+    #[case("200", 200, false)]
+    fn test_covers_status(#[case] code: &str, #[case] status: u16, #[case] expected: bool) {
+        assert_eq!(covers_status(code, status), expected);
+    }
+
+    #[rstest]
+    #[case(json!({"openapi": "2.0"}), "Unsupported OpenAPI version")]
+    #[case(json!({"swagger": "2.0"}), "Missing `openapi` version")]
+    fn test_routes_from_openapi_invalid(#[case] doc: Value, #[case] message: &str) {
+        let err = routes_from_openapi(&doc).unwrap_err();
+        assert!(err.to_string().contains(message));
+    }
+
+    #[rstest]
+    #[case(json!({"type": "string", "format": "uuid"}), json!("00000000-0000-0000-0000-000000000000"))]
+    #[case(json!({"type": ["null", "integer"], "minimum": 5}), json!(5))]
+    #[case(json!({"type": "boolean", "default": true}), json!(true))]
+    #[case(
+        json!({"allOf": [{"properties": {"a": {"type": "integer"}}}, {"properties": {"b": {"type": "boolean"}}}]}),
+        json!({"a": 0, "b": false})
+    )]
+    #[case(json!({"oneOf": [{"type": "number"}, {"type": "string"}]}), json!(0))]
+    #[case(json!({"type": "array"}), json!([]))]
+    fn test_sample_from_schema(#[case] schema: Value, #[case] expected: Value) {
+        assert_eq!(sample_from_schema(&schema, &json!({})), expected);
+    }
+
+    #[rstest]
+    fn test_sample_from_recursive_schema() {
+        let doc = json!({"components": {"schemas": {"Node": {
+            "type": "object",
+            "properties": {"child": {"$ref": "#/components/schemas/Node"}}
+        }}}});
+        let sample = sample_from_schema(&json!({"$ref": "#/components/schemas/Node"}), &doc);
+        assert!(sample["child"]["child"].is_object());
+    }
+
+    #[rstest]
+    #[case("get", "/users/{id}", "get-users-id")]
+    #[case("post", "/", "post")]
+    #[case("delete", "/Users/{userId}/Orders", "delete-users-userid-orders")]
+    fn test_slug(#[case] method: &str, #[case] path: &str, #[case] expected: &str) {
+        assert_eq!(slug(method, path), expected);
+    }
+
+    #[rstest]
+    #[case("200", 200)]
+    #[case("4XX", 400)]
+    #[case("default", 500)]
+    fn test_status_code(#[case] code: &str, #[case] expected: u16) {
+        assert_eq!(status_code(code), expected);
+    }
+}
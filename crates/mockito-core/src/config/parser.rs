@@ -1,10 +1,21 @@
-//! Configuration file parsing (YAML/JSON/JSONC).
+//! Configuration file parsing (YAML/JSON/JSONC/JSON5/TOML).
 
+use crate::config::env::substitute_env_in_value;
 use crate::config::error::ConfigError;
-use crate::types::{collection::Collection, route::Route};
-use glob::glob;
+use crate::config::remote::{self, RemoteOptions};
+use crate::config::schema::UnknownFieldPolicy;
+use crate::config::{include, schema};
+use crate::types::{collection::Collection, group::Group, route::Route, settings::Settings};
+use crate::vars::{self, Vars};
+use glob::{glob, Pattern};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::de::DeserializeOwned;
-use std::{fs, path::Path};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 
 /// Config file type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -12,6 +23,8 @@ pub enum ConfigFileType {
     Yaml,
     Json,
     Jsonc,
+    Json5,
+    Toml,
     Unknown,
 }
 
@@ -27,6 +40,8 @@ pub fn get_file_type(path: &str) -> ConfigFileType {
         "yaml" | "yml" => ConfigFileType::Yaml,
         "json" => ConfigFileType::Json,
         "jsonc" => ConfigFileType::Jsonc,
+        "json5" => ConfigFileType::Json5,
+        "toml" => ConfigFileType::Toml,
         _ => ConfigFileType::Unknown,
     }
 }
@@ -112,21 +127,151 @@ pub fn parse_jsonc<T: DeserializeOwned>(content: &str) -> Result<T, ConfigError>
 }
 
 /// Parse YAML content
+///
+/// Anchors and aliases are resolved per document, including `<<` merge keys.
+/// Multiple `---` separated documents are parsed as an array of documents, with
+/// documents that are arrays themselves flattened into it.
 pub fn parse_yaml<T: DeserializeOwned>(content: &str) -> Result<T, ConfigError> {
-    serde_yaml::from_str(content).map_err(ConfigError::from)
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let mut value = serde_yaml::Value::deserialize(document)?;
+        value.apply_merge()?;
+        documents.push(value);
+    }
+
+    let value = match documents.len() {
+        0 => serde_yaml::Value::Null,
+        1 => documents.remove(0),
+        _ => serde_yaml::Value::Sequence(
+            documents
+                .into_iter()
+                .flat_map(|document| match document {
+                    serde_yaml::Value::Sequence(items) => items,
+                    item => vec![item],
+                })
+                .collect(),
+        ),
+    };
+    serde_yaml::from_value(value).map_err(ConfigError::from)
+}
+
+/// Parse JSON5 content (unquoted keys, trailing commas, single quotes, comments)
+#[cfg(feature = "json5")]
+pub fn parse_json5<T: DeserializeOwned>(content: &str) -> Result<T, ConfigError> {
+    json5::from_str(content).map_err(ConfigError::from)
+}
+
+/// Parse JSON5 content, unsupported without the `json5` feature
+#[cfg(not(feature = "json5"))]
+pub fn parse_json5<T: DeserializeOwned>(_content: &str) -> Result<T, ConfigError> {
+    Err(ConfigError::FeatureDisabled {
+        what: "JSON5 content".to_string(),
+        feature: "json5",
+    })
+}
+
+/// Parse TOML content
+///
+/// TOML documents can't have an array at the root, so files hold a single item.
+#[cfg(feature = "toml")]
+pub fn parse_toml<T: DeserializeOwned>(content: &str) -> Result<T, ConfigError> {
+    toml::from_str(content).map_err(ConfigError::from)
+}
+
+/// Parse TOML content, unsupported without the `toml` feature
+#[cfg(not(feature = "toml"))]
+pub fn parse_toml<T: DeserializeOwned>(_content: &str) -> Result<T, ConfigError> {
+    Err(ConfigError::FeatureDisabled {
+        what: "TOML content".to_string(),
+        feature: "toml",
+    })
 }
 
 /// Parse config content based on file type
+///
+/// `${ENV:NAME}` placeholders are substituted in the strings of the parsed
+/// document, see [`substitute_env_in_value`]. Placeholders in comments are
+/// ignored, and substituted values can't change the document structure.
+/// A placeholder making up a whole value becomes a number or boolean if the
+/// substituted text is one, e.g. `port: ${ENV:PORT:-3000}`.
 pub fn parse_config<T: DeserializeOwned>(content: &str, path: &str) -> Result<T, ConfigError> {
-    match get_file_type(path) {
+    let file_type = get_file_type(path);
+    if file_type == ConfigFileType::Unknown {
+        return Err(ConfigError::UnknownFileType(path.to_string()));
+    }
+    if !content.contains("${ENV:") {
+        return parse_content(content, file_type, path);
+    }
+
+    let value: Value = parse_content(content, file_type, path)?;
+    serde_json::from_value(substitute_env_in_value(value)?).map_err(ConfigError::from)
+}
+
+/// Parse content of a known file type.
+fn parse_content<T: DeserializeOwned>(
+    content: &str,
+    file_type: ConfigFileType,
+    path: &str,
+) -> Result<T, ConfigError> {
+    match file_type {
         ConfigFileType::Yaml => parse_yaml(content),
         ConfigFileType::Json => parse_json(content),
         ConfigFileType::Jsonc => parse_jsonc(content),
+        ConfigFileType::Json5 => parse_json5(content),
+        ConfigFileType::Toml => parse_toml(content),
         ConfigFileType::Unknown => Err(ConfigError::UnknownFileType(path.to_string())),
     }
 }
 
-fn expand_glob(pattern: &str) -> Result<Vec<String>, ConfigError> {
+/// Expand a glob pattern into matching paths.
+///
+/// The pattern may be a comma-separated list, where patterns prefixed with `!`
+/// exclude paths matched by the others, e.g.
+/// `routes/**/*.yaml, !routes/**/*.draft.yaml`. See [`expand_patterns`].
+pub fn expand_glob(pattern: &str) -> Result<Vec<String>, ConfigError> {
+    let patterns: Vec<&str> = pattern
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    expand_patterns(&patterns)
+}
+
+/// Expand a list of glob patterns into matching paths.
+///
+/// Paths matched by several patterns are listed once, in order of the first
+/// matching pattern. Patterns prefixed with `!` exclude matching paths regardless
+/// of their position in the list. Patterns are expanded in parallel.
+pub fn expand_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<String>, ConfigError> {
+    let mut excludes = Vec::new();
+    for pattern in patterns {
+        if let Some(exclude) = pattern.as_ref().strip_prefix('!') {
+            let exclude = Pattern::new(strip_current_dir(exclude.trim()))
+                .map_err(|e| ConfigError::GlobPattern(format!("Invalid glob pattern: {}", e)))?;
+            excludes.push(exclude);
+        }
+    }
+
+    let includes: Vec<&str> = patterns
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|pattern| !pattern.starts_with('!'))
+        .collect();
+    let matches = map_ordered(&includes, |pattern| glob_paths(pattern, &excludes))?;
+
+    let mut paths = Vec::new();
+    let mut seen = HashSet::new();
+    for path in matches.into_iter().flatten() {
+        if seen.insert(path.clone()) {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Expand a single glob pattern, skipping excluded and non UTF-8 paths.
+fn glob_paths(pattern: &str, excludes: &[Pattern]) -> Result<Vec<String>, ConfigError> {
     let entries = glob(pattern)
         .map_err(|e| ConfigError::GlobPattern(format!("Invalid glob pattern: {}", e)))?;
 
@@ -134,58 +279,239 @@ fn expand_glob(pattern: &str) -> Result<Vec<String>, ConfigError> {
     for entry in entries {
         let path =
             entry.map_err(|e| ConfigError::GlobPattern(format!("Glob pattern error: {}", e)))?;
-        if let Some(s) = path.to_str() {
+        let Some(s) = path.to_str() else {
+            continue;
+        };
+        let excluded = excludes
+            .iter()
+            .any(|exclude| exclude.matches(strip_current_dir(s)));
+        if !excluded {
             paths.push(s.to_owned());
         }
     }
-
     Ok(paths)
 }
 
-/// Load routes from a file or glob pattern.
+/// Apply a fallible function to items in parallel, keeping their order.
+///
+/// Fails with the error of the first failing item, so errors don't depend on
+/// which thread finished first. Items are processed one by one without the
+/// `parallel` feature.
+pub(crate) fn map_ordered<I, T, F>(items: &[I], f: F) -> Result<Vec<T>, ConfigError>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<T, ConfigError> + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<T, ConfigError>> = items.par_iter().map(&f).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<T, ConfigError>> = items.iter().map(&f).collect();
+    results.into_iter().collect()
+}
+
+/// Strip a leading `./` so exclusions match regardless of how paths are written.
+fn strip_current_dir(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Key of the file-level variables block
+pub const VARS_KEY: &str = "vars";
+
+/// Size from which JSON files holding an array are loaded item by item
+pub const STREAMING_MIN_LEN: u64 = 16 * 1024 * 1024;
+
+/// Options for loading definition files.
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Options for fetching `http(s)://` files
+    pub remote: RemoteOptions,
+    /// Handling of unknown fields in definitions (rejected by default)
+    pub unknown_fields: UnknownFieldPolicy,
+}
+
+/// Load routes from a file, glob pattern or `http(s)://` URL.
+/// Each file may contain a single route or an array of routes
+/// (e.g. multiple `---` separated YAML documents), or wrap them with
+/// file-level variables, see [`apply_file_vars`].
+///
+/// Matched files are read and parsed in parallel. Routes are returned in order
+/// of the matched paths.
 pub fn load_routes(pattern: &str) -> Result<Vec<Route>, ConfigError> {
-    let paths = expand_glob(pattern)?;
-    let mut routes = Vec::new();
+    load_routes_with(pattern, &LoadOptions::default())
+}
 
-    for p in paths {
-        if !is_supported_config_file(&p) {
-            continue;
-        }
-        let content = fs::read_to_string(&p).map_err(|e| ConfigError::Io {
-            source: e,
-            path: p.clone(),
-        })?;
-        let parsed: Route = parse_config(&content, &p)?;
-        routes.push(parsed);
+/// Load routes like [`load_routes`] with the given options.
+pub fn load_routes_with(pattern: &str, options: &LoadOptions) -> Result<Vec<Route>, ConfigError> {
+    if remote::is_remote(pattern) {
+        return load_one_or_many_remote(pattern, options, "routes");
     }
 
-    Ok(routes)
+    let paths: Vec<String> = expand_glob(pattern)?
+        .into_iter()
+        .filter(|p| is_supported_config_file(p))
+        .collect();
+    let routes = map_ordered(&paths, |p| load_one_or_many::<Route>(p, options, "routes"))?;
+
+    Ok(routes.into_iter().flatten().collect())
 }
 
-/// Load collections from a file.
-/// Supports both single collection and array of collections.
+/// Load collections from a file or `http(s)://` URL.
+/// Supports both single collection and array of collections, optionally
+/// wrapped with file-level variables (`{vars: ..., collections: [...]}`).
 pub fn load_collections(path: &str) -> Result<Vec<Collection>, ConfigError> {
-    let content = fs::read_to_string(path).map_err(|e| ConfigError::Io {
-        source: e,
-        path: path.to_string(),
-    })?;
-
-    // Try to parse as array first, then as single collection
-    match parse_config::<Vec<Collection>>(&content, path) {
-        Ok(collections) => Ok(collections),
-        Err(_) => {
-            // If array parsing fails, try single collection
-            let collection = parse_config::<Collection>(&content, path)?;
-            Ok(vec![collection])
+    load_collections_with(path, &LoadOptions::default())
+}
+
+/// Load collections like [`load_collections`] with the given options.
+pub fn load_collections_with(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<Vec<Collection>, ConfigError> {
+    if remote::is_remote(path) {
+        return load_one_or_many_remote(path, options, "collections");
+    }
+    load_one_or_many(path, options, "collections")
+}
+
+/// Load route groups from a file.
+/// Supports both single group and array of groups, optionally
+/// wrapped with file-level variables (`{vars: ..., groups: [...]}`).
+pub fn load_groups(path: &str) -> Result<Vec<Group>, ConfigError> {
+    load_groups_with(path, &LoadOptions::default())
+}
+
+/// Load route groups like [`load_groups`] with the given options.
+pub fn load_groups_with(path: &str, options: &LoadOptions) -> Result<Vec<Group>, ConfigError> {
+    load_one_or_many(path, options, "groups")
+}
+
+/// Load project settings from a file.
+pub fn load_settings(path: &str) -> Result<Settings, ConfigError> {
+    schema::deserialize(include::load_value(path)?, path)
+}
+
+/// Substitute a file-level `vars` block into the other sections of a file.
+///
+/// Files may declare constants once and reference them as `{{vars.name}}` in
+/// URLs, headers and bodies:
+///
+/// ```yaml
+/// vars:
+///   version: v2
+/// routes:
+///   - id: users
+///     url: /api/{{vars.version}}/users
+/// ```
+///
+/// Placeholders of variables not declared in the file are left for collection
+/// variables, which are substituted when a collection is activated.
+pub fn apply_file_vars(
+    mut sections: Map<String, Value>,
+    path: &str,
+) -> Result<Map<String, Value>, ConfigError> {
+    let Some(vars) = sections.remove(VARS_KEY) else {
+        return Ok(sections);
+    };
+    let vars: Vars = schema::deserialize(vars, path)?;
+    Ok(sections
+        .into_iter()
+        .map(|(key, value)| {
+            let value = vars::substitute_value(&value, &vars);
+            (key, value)
+        })
+        .collect())
+}
+
+/// Load a file containing either a single item or an array of items,
+/// optionally wrapped in an `items_key` section next to file-level variables.
+///
+/// JSON files of [`STREAMING_MIN_LEN`] or more holding an array are parsed
+/// item by item instead of being read into memory at once.
+fn load_one_or_many<T: DeserializeOwned>(
+    path: &str,
+    options: &LoadOptions,
+    items_key: &str,
+) -> Result<Vec<T>, ConfigError> {
+    let large = fs::metadata(path).is_ok_and(|m| m.len() >= STREAMING_MIN_LEN);
+    if large && get_file_type(path) == ConfigFileType::Json {
+        if let Some(items) = load_streamed(path, options)? {
+            return Ok(items);
+        }
+    }
+    one_or_many(include::load_value(path)?, path, options, items_key)
+}
+
+/// Load the items of a JSON file holding an array one at a time.
+///
+/// Returns `None` if the file doesn't hold an array.
+fn load_streamed<T: DeserializeOwned>(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<Option<Vec<T>>, ConfigError> {
+    include::load_array_items(path, |item, index| {
+        schema::deserialize_item(item, path, index, &options.unknown_fields)
+    })
+}
+
+/// Fetch a remote file containing either a single item or an array of items.
+fn load_one_or_many_remote<T: DeserializeOwned>(
+    url: &str,
+    options: &LoadOptions,
+    items_key: &str,
+) -> Result<Vec<T>, ConfigError> {
+    one_or_many(
+        remote::load_value(url, &options.remote)?,
+        url,
+        options,
+        items_key,
+    )
+}
+
+fn one_or_many<T: DeserializeOwned>(
+    value: Value,
+    path: &str,
+    options: &LoadOptions,
+    items_key: &str,
+) -> Result<Vec<T>, ConfigError> {
+    let value = match value {
+        Value::Object(sections) if sections.contains_key(items_key) => {
+            let mut sections = apply_file_vars(sections, path)?;
+            let items = sections.remove(items_key).unwrap_or_default();
+            if let Some(key) = sections.keys().next() {
+                return Err(ConfigError::Schema {
+                    file: path.to_string(),
+                    field: key.clone(),
+                    line: None,
+                    column: None,
+                    message: format!(
+                        "unknown field `{}`, expected `{}` or `{}`",
+                        key, VARS_KEY, items_key
+                    ),
+                });
+            }
+            items
         }
+        value => value,
+    };
+
+    match value {
+        items @ Value::Array(_) => schema::deserialize_with(items, path, &options.unknown_fields),
+        item => Ok(vec![schema::deserialize_with(
+            item,
+            path,
+            &options.unknown_fields,
+        )?]),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::test_dir::TestDir;
     use crate::types::route::Route;
     use rstest::rstest;
+    use std::sync::Arc;
 
     #[rstest]
     #[case("test.yaml", ConfigFileType::Yaml)]
@@ -196,6 +522,9 @@ mod tests {
     #[case("test.JSON", ConfigFileType::Json)]
     #[case("test.jsonc", ConfigFileType::Jsonc)]
     #[case("test.JSONC", ConfigFileType::Jsonc)]
+    #[case("test.json5", ConfigFileType::Json5)]
+    #[case("test.toml", ConfigFileType::Toml)]
+    #[case("test.TOML", ConfigFileType::Toml)]
     #[case("test.txt", ConfigFileType::Unknown)]
     #[case("test", ConfigFileType::Unknown)]
     #[case("", ConfigFileType::Unknown)]
@@ -320,6 +649,104 @@ mod tests {
         assert!(matches!(result.unwrap_err(), ConfigError::Yaml(_)));
     }
 
+    #[cfg(feature = "json5")]
+    #[rstest]
+    fn test_parse_json5_valid() {
+        let content = "{\n  // copied from a test file\n  id: 'test',\n  tags: ['a', 'b',],\n}";
+        let result: Result<serde_json::Value, _> = parse_json5(content);
+        assert!(result.is_ok());
+        let value = result.unwrap();
+        assert_eq!(value["id"], "test");
+        assert_eq!(value["tags"], serde_json::json!(["a", "b"]));
+    }
+
+    #[cfg(feature = "json5")]
+    #[rstest]
+    fn test_parse_json5_invalid() {
+        let content = "{id: }";
+        let result: Result<serde_json::Value, _> = parse_json5(content);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ConfigError::Json5(_)));
+    }
+
+    #[cfg(feature = "toml")]
+    #[rstest]
+    fn test_parse_toml_valid() {
+        let content = "id = \"test\"\n\n[body]\nname = \"value\"";
+        let result: Result<serde_json::Value, _> = parse_toml(content);
+        assert!(result.is_ok());
+        let value = result.unwrap();
+        assert_eq!(value["id"], "test");
+        assert_eq!(value["body"]["name"], "value");
+    }
+
+    #[cfg(feature = "toml")]
+    #[rstest]
+    fn test_parse_toml_invalid() {
+        let content = "invalid = toml = [";
+        let result: Result<serde_json::Value, _> = parse_toml(content);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ConfigError::Toml(_)));
+    }
+
+    #[rstest]
+    fn test_parse_yaml_multi_document() {
+        let content = "id: a\n---\n- id: b\n- id: c\n---\nid: d";
+        let value: serde_json::Value = parse_yaml(content).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([{"id": "a"}, {"id": "b"}, {"id": "c"}, {"id": "d"}])
+        );
+    }
+
+    #[rstest]
+    fn test_parse_yaml_merge_keys() {
+        let content = r#"
+base: &base
+  status: 200
+  headers: {x-api: v1}
+variant:
+  <<: *base
+  status: 404
+"#;
+        let value: serde_json::Value = parse_yaml(content).unwrap();
+        assert_eq!(
+            value["variant"],
+            serde_json::json!({"status": 404, "headers": {"x-api": "v1"}})
+        );
+    }
+
+    #[rstest]
+    fn test_load_routes_multi_document_yaml() {
+        let test_dir = TestDir::new("parser");
+        let test_file = test_dir.join("test_routes_multi.yaml");
+        let test_content = r#"
+id: users
+url: /api/users
+transport: HTTP
+presets:
+  - id: default
+    variants:
+      - id: ok
+        status: 200
+---
+id: orders
+url: /api/orders
+transport: HTTP
+presets:
+  - id: default
+    variants:
+      - id: ok
+        status: 201
+"#;
+        std::fs::write(&test_file, test_content).unwrap();
+
+        let routes = load_routes(test_file.to_str().unwrap()).unwrap();
+        let route_ids: Vec<&str> = routes.iter().map(|r| r.id.as_ref()).collect();
+        assert_eq!(route_ids, vec!["users", "orders"]);
+        assert_eq!(routes[1].presets[0].variants[0].status, Some(201));
+    }
+
     #[rstest]
     fn test_parse_config_json() {
         let content = r#"{"id": "test", "url": "/api", "transport": "HTTP", "presets": []}"#;
@@ -342,6 +769,92 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[rstest]
+    fn test_parse_config_env_defaults() {
+        let content = r#"{"id": "test", "url": "${ENV:MOCKITO_TEST_UNSET_BASE:-/api}/users", "transport": "HTTP", "presets": []} // ${ENV:MOCKITO_TEST_UNSET}"#;
+        let route: Route = parse_config(content, "test.jsonc").unwrap();
+        assert_eq!(route.url, "/api/users");
+
+        let result: Result<Route, _> =
+            parse_config(r#"{"url": "${ENV:MOCKITO_TEST_UNSET}"}"#, "test.json");
+        assert!(matches!(result.unwrap_err(), ConfigError::MissingEnvVar(_)));
+    }
+
+    #[rstest]
+    #[case(
+        "test.yaml",
+        "# ${ENV:MOCKITO_TEST_UNSET}\nid: test\nurl: /api\ntransport: HTTP\npresets: []"
+    )]
+    #[cfg_attr(feature = "toml", case(
+        "test.toml",
+        "# ${ENV:MOCKITO_TEST_UNSET}\nid = 'test'\nurl = '/api'\ntransport = 'HTTP'\npresets = []"
+    ))]
+    #[cfg_attr(feature = "json5", case(
+        "test.json5",
+        "// ${ENV:MOCKITO_TEST_UNSET}\n{id: 'test', url: '/api', transport: 'HTTP', presets: []}"
+    ))]
+    #[case("test.jsonc", "/* ${ENV:MOCKITO_TEST_UNSET} */ {\"id\": \"test\", \"url\": \"/api\", \"transport\": \"HTTP\", \"presets\": []}")]
+    fn test_parse_config_env_in_comments(#[case] path: &str, #[case] content: &str) {
+        let route: Route = parse_config(content, path).unwrap();
+        assert_eq!(route.url, "/api");
+    }
+
+    #[rstest]
+    #[case("test.json", r#"{"id": "${ENV:MOCKITO_TEST_INJECTED}", "url": "/api", "transport": "HTTP", "presets": []}"#)]
+    #[case(
+        "test.yaml",
+        "id: ${ENV:MOCKITO_TEST_INJECTED}\nurl: /api\ntransport: HTTP\npresets: []"
+    )]
+    fn test_parse_config_env_not_injected(#[case] path: &str, #[case] content: &str) {
+        std::env::set_var("MOCKITO_TEST_INJECTED", "x\"}\nurl: /evil");
+        let route: Route = parse_config(content, path).unwrap();
+        assert_eq!(&*route.id, "x\"}\nurl: /evil");
+        assert_eq!(route.url, "/api");
+    }
+
+    #[rstest]
+    #[case("settings.yaml", "port: ${ENV:MOCKITO_TEST_PORT:-3000}\ndelay: ${ENV:MOCKITO_TEST_DELAY:-250}\ndebug: ${ENV:MOCKITO_TEST_DEBUG:-false}")]
+    #[case("settings.json", r#"{"port": "${ENV:MOCKITO_TEST_PORT:-3000}", "delay": "${ENV:MOCKITO_TEST_DELAY:-250}", "debug": "${ENV:MOCKITO_TEST_DEBUG:-false}"}"#)]
+    fn test_parse_config_env_typed(#[case] path: &str, #[case] content: &str) {
+        std::env::set_var("MOCKITO_TEST_PORT", "4000");
+        let settings: Settings = parse_config(content, path).unwrap();
+        assert_eq!(settings.port, Some(4000));
+        assert_eq!(settings.delay, Some(250));
+        assert_eq!(settings.debug, Some(false));
+    }
+
+    #[cfg(feature = "json5")]
+    #[rstest]
+    fn test_parse_config_json5() {
+        let content = "{id: 'test', url: '/api', transport: 'HTTP', presets: [],}";
+        let result: Result<Route, _> = parse_config(content, "test.json5");
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "toml")]
+    #[rstest]
+    fn test_parse_config_toml() {
+        let content = r#"
+id = "test"
+url = "/api"
+transport = "HTTP"
+
+[[presets]]
+id = "default"
+
+[[presets.variants]]
+id = "ok"
+status = 200
+body = { users = [] }
+"#;
+        let route: Route = parse_config(content, "test.toml").unwrap();
+        assert_eq!(route.presets[0].variants[0].status, Some(200));
+        assert_eq!(
+            route.presets[0].variants[0].body,
+            Some(serde_json::json!({"users": []}))
+        );
+    }
+
     #[rstest]
     #[case("test.txt")]
     #[case("test.unknown")]
@@ -361,6 +874,8 @@ mod tests {
     #[case("test.yml", true)]
     #[case("test.json", true)]
     #[case("test.jsonc", true)]
+    #[case("test.json5", true)]
+    #[case("test.toml", true)]
     #[case("test.txt", false)]
     #[case("test.unknown", false)]
     #[case("", false)]
@@ -371,7 +886,7 @@ mod tests {
     #[rstest]
     fn test_expand_glob_valid_pattern() {
         // Create a temporary test file
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_route.json");
         let test_content = r#"{"id": "test", "url": "/api", "transport": "HTTP", "presets": []}"#;
         std::fs::write(&test_file, test_content).unwrap();
@@ -382,9 +897,6 @@ mod tests {
         let paths = result.unwrap();
         assert!(!paths.is_empty());
         assert!(paths.contains(&pattern.to_string()));
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
     }
 
     #[rstest]
@@ -394,6 +906,37 @@ mod tests {
         assert!(matches!(result.unwrap_err(), ConfigError::GlobPattern(_)));
     }
 
+    #[rstest]
+    fn test_expand_glob_exclusions() {
+        let test_dir = TestDir::new("glob-exclusions");
+        std::fs::create_dir_all(test_dir.join("users")).unwrap();
+        for file in ["users/list.yaml", "users/wip.draft.yaml", "orders.yaml"] {
+            std::fs::write(test_dir.join(file), "id: x").unwrap();
+        }
+        let root = test_dir.to_str().unwrap();
+
+        let paths = expand_glob(&format!(
+            "{root}/**/*.yaml, !{root}/**/*.draft.yaml, {root}/users/*.yaml"
+        ))
+        .unwrap();
+        let names: Vec<String> = paths
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().replace('\\', "/"))
+            .collect();
+        assert_eq!(names, vec!["/orders.yaml", "/users/list.yaml"]);
+
+        // Exclusions alone match nothing
+        assert!(expand_glob(&format!("!{root}/**/*.yaml"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rstest]
+    fn test_expand_glob_invalid_exclusion() {
+        let result = expand_glob("*.yaml, ![invalid");
+        assert!(matches!(result.unwrap_err(), ConfigError::GlobPattern(_)));
+    }
+
     #[rstest]
     fn test_expand_glob_no_matches() {
         let result = expand_glob("nonexistent_*.json");
@@ -405,7 +948,7 @@ mod tests {
     #[rstest]
     fn test_load_routes_single_file() {
         // Create a temporary test file
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_route_load.json");
         let test_content = r#"{"id": "test", "url": "/api", "transport": "HTTP", "presets": []}"#;
         std::fs::write(&test_file, test_content).unwrap();
@@ -415,10 +958,72 @@ mod tests {
         assert!(result.is_ok());
         let routes = result.unwrap();
         assert_eq!(routes.len(), 1);
-        assert_eq!(routes[0].id, "test");
+        assert_eq!(routes[0].id, "test".into());
+    }
 
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
+    #[rstest]
+    fn test_load_routes_many_files_in_order() {
+        let test_dir = TestDir::new("load-many");
+        for i in 0..64 {
+            let content = format!("id: route-{i:02}\nurl: /api/{i}\ntransport: HTTP\npresets: []");
+            std::fs::write(test_dir.join(format!("{i:02}.yaml")), content).unwrap();
+        }
+        let pattern = format!("{}/*.yaml", test_dir.to_str().unwrap());
+
+        let routes = load_routes(&pattern).unwrap();
+        let ids: Vec<String> = routes.iter().map(|r| r.id.to_string()).collect();
+        let expected: Vec<String> = (0..64).map(|i| format!("route-{i:02}")).collect();
+        assert_eq!(ids, expected);
+
+        // The error of the first invalid file is reported
+        std::fs::write(test_dir.join("10.yaml"), "id: [").unwrap();
+        std::fs::write(test_dir.join("50.yaml"), "url: /missing-id").unwrap();
+        let error = load_routes(&pattern).unwrap_err();
+        assert!(matches!(error, ConfigError::Yaml(_)), "{error}");
+    }
+
+    #[rstest]
+    fn test_load_streamed_matches_load_routes() {
+        let test_dir = TestDir::new("load-streamed");
+        let test_file = test_dir.join("routes.json");
+        let content = r#"
+            [
+              {"id": "a", "url": "/${ENV:MOCKITO_STREAMED_PREFIX:-api}/a", "transport": "HTTP", "presets": []},
+              {"id": "b", "url": "/b", "transport": "HTTP", "presets": [
+                {"id": "default", "variants": [{"id": "ok", "body": "file:./users.json"}]}
+              ]}
+            ]"#;
+        std::fs::write(&test_file, content).unwrap();
+        std::fs::write(test_dir.join("users.json"), "[]").unwrap();
+        let path = test_file.to_str().unwrap();
+
+        let streamed: Vec<Route> = load_streamed(path, &LoadOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(streamed, load_routes(path).unwrap());
+        assert_eq!(streamed[0].url, "/api/a");
+
+        // Items are checked one at a time, errors point at the item
+        std::fs::write(
+            &test_file,
+            r#"[{"id": "a", "url": "/a", "transport": "HTTP", "presets": []}, {"id": "b"}]"#,
+        )
+        .unwrap();
+        let error = load_streamed::<Route>(path, &LoadOptions::default()).unwrap_err();
+        assert!(
+            matches!(&error, ConfigError::Schema { field, line: None, .. } if field == "[1]"),
+            "{error:?}"
+        );
+
+        // Files not holding an array are left to regular loading
+        std::fs::write(
+            &test_file,
+            r#"{"id": "a", "url": "/a", "transport": "HTTP", "presets": []}"#,
+        )
+        .unwrap();
+        assert!(load_streamed::<Route>(path, &LoadOptions::default())
+            .unwrap()
+            .is_none());
     }
 
     #[rstest]
@@ -439,7 +1044,7 @@ mod tests {
     #[rstest]
     fn test_load_routes_invalid_json() {
         // Create a temporary test file with invalid JSON
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_invalid.json");
         let test_content = "invalid json content";
         std::fs::write(&test_file, test_content).unwrap();
@@ -448,15 +1053,12 @@ mod tests {
         let result = load_routes(pattern);
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ConfigError::Json(_)));
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
     }
 
     #[rstest]
     fn test_load_routes_unsupported_file_type() {
         // Create a temporary test file with unsupported extension
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_route.txt");
         let test_content = "some content";
         std::fs::write(&test_file, test_content).unwrap();
@@ -466,15 +1068,12 @@ mod tests {
         assert!(result.is_ok());
         let routes = result.unwrap();
         assert!(routes.is_empty()); // Unsupported files are skipped
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
     }
 
     #[rstest]
     fn test_load_collections_json() {
         // Create a temporary test file
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_collection.json");
         let test_content = r#"{"id": "test-collection", "routes": ["route1:preset1:variant1"]}"#;
         std::fs::write(&test_file, test_content).unwrap();
@@ -484,17 +1083,29 @@ mod tests {
         assert!(result.is_ok());
         let collections = result.unwrap();
         assert_eq!(collections.len(), 1);
-        assert_eq!(collections[0].id, "test-collection");
+        assert_eq!(collections[0].id, "test-collection".into());
         assert_eq!(collections[0].routes.len(), 1);
+    }
+
+    #[rstest]
+    fn test_load_groups_yaml() {
+        // Create a temporary test file
+        let test_dir = TestDir::new("parser");
+        let test_file = test_dir.join("test_groups.yaml");
+        let test_content = "- id: api-v2\n  base_url: /api/v2\n- id: admin\n  base_url: /admin";
+        std::fs::write(&test_file, test_content).unwrap();
 
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
+        let path = test_file.to_str().unwrap();
+        let groups = load_groups(path).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].id, "api-v2");
+        assert_eq!(groups[1].base_url.as_deref(), Some("/admin"));
     }
 
     #[rstest]
     fn test_load_collections_yaml() {
         // Create a temporary test file
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_collection.yaml");
         let test_content = "id: test-collection\nroutes:\n  - route1:preset1:variant1";
         std::fs::write(&test_file, test_content).unwrap();
@@ -504,16 +1115,13 @@ mod tests {
         assert!(result.is_ok());
         let collections = result.unwrap();
         assert_eq!(collections.len(), 1);
-        assert_eq!(collections[0].id, "test-collection");
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
+        assert_eq!(collections[0].id, "test-collection".into());
     }
 
     #[rstest]
     fn test_load_collections_with_from() {
         // Create a temporary test file with 'from' field
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_collection_from.json");
         let test_content =
             r#"{"id": "child", "from": "parent", "routes": ["route1:preset1:variant1"]}"#;
@@ -524,11 +1132,8 @@ mod tests {
         assert!(result.is_ok());
         let collections = result.unwrap();
         assert_eq!(collections.len(), 1);
-        assert_eq!(collections[0].id, "child");
+        assert_eq!(collections[0].id, "child".into());
         assert_eq!(collections[0].from, Some("parent".to_string()));
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
     }
 
     #[rstest]
@@ -541,7 +1146,7 @@ mod tests {
     #[rstest]
     fn test_load_collections_invalid_json() {
         // Create a temporary test file with invalid JSON
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_invalid_collection.json");
         let test_content = "invalid json content";
         std::fs::write(&test_file, test_content).unwrap();
@@ -550,15 +1155,12 @@ mod tests {
         let result = load_collections(path);
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ConfigError::Json(_)));
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
     }
 
     #[rstest]
     fn test_load_collections_unknown_file_type() {
         // Create a temporary test file with unsupported extension
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_collection.txt");
         let test_content = "some content";
         std::fs::write(&test_file, test_content).unwrap();
@@ -570,15 +1172,75 @@ mod tests {
             result.unwrap_err(),
             ConfigError::UnknownFileType(_)
         ));
+    }
 
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
+    #[rstest]
+    fn test_load_routes_with_file_vars() {
+        let test_dir = TestDir::new("parser");
+        let test_file = test_dir.join("test_routes_file_vars.yaml");
+        let test_content = r#"
+vars:
+  version: v2
+  tenant: acme
+routes:
+  - id: users
+    url: /api/{{vars.version}}/users
+    transport: HTTP
+    presets:
+      - id: default
+        variants:
+          - id: ok
+            headers:
+              x-tenant: "{{vars.tenant}}"
+            body:
+              tenant: "{{vars.tenant}}"
+              region: "{{vars.region}}"
+"#;
+        std::fs::write(&test_file, test_content).unwrap();
+
+        let routes = load_routes(test_file.to_str().unwrap()).unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].url, "/api/v2/users");
+        let variant = &routes[0].presets[0].variants[0];
+        assert_eq!(variant.headers.as_ref().unwrap()["x-tenant"], "acme");
+        // Undeclared variables are left for collection variables
+        assert_eq!(
+            variant.body,
+            Some(serde_json::json!({"tenant": "acme", "region": "{{vars.region}}"}))
+        );
+    }
+
+    #[rstest]
+    fn test_load_collections_with_file_vars() {
+        let test_dir = TestDir::new("parser");
+        let test_file = test_dir.join("test_collections_file_vars.json");
+        let test_content = r#"{
+            "vars": {"variant": "success"},
+            "collections": [{"id": "base", "routes": ["users:default:{{vars.variant}}"]}]
+        }"#;
+        std::fs::write(&test_file, test_content).unwrap();
+
+        let collections = load_collections(test_file.to_str().unwrap()).unwrap();
+        assert_eq!(
+            collections[0].routes,
+            vec![Arc::from("users:default:success")]
+        );
+    }
+
+    #[rstest]
+    fn test_load_collections_file_vars_unknown_section() {
+        let test_dir = TestDir::new("parser");
+        let test_file = test_dir.join("test_collections_file_vars_unknown.json");
+        std::fs::write(&test_file, r#"{"collections": [], "routes": []}"#).unwrap();
+
+        let err = load_collections(test_file.to_str().unwrap()).unwrap_err();
+        assert!(matches!(&err, ConfigError::Schema { field, .. } if field == "routes"));
     }
 
     #[rstest]
     fn test_load_collections_array() {
         // Test parsing array of collections
-        let test_dir = std::env::temp_dir();
+        let test_dir = TestDir::new("parser");
         let test_file = test_dir.join("test_collections_array.json");
         let test_content =
             r#"[{"id": "collection1", "routes": []}, {"id": "collection2", "routes": []}]"#;
@@ -589,10 +1251,7 @@ mod tests {
         assert!(result.is_ok());
         let collections = result.unwrap();
         assert_eq!(collections.len(), 2);
-        assert_eq!(collections[0].id, "collection1");
-        assert_eq!(collections[1].id, "collection2");
-
-        // Cleanup
-        let _ = std::fs::remove_file(&test_file);
+        assert_eq!(collections[0].id, "collection1".into());
+        assert_eq!(collections[1].id, "collection2".into());
     }
 }
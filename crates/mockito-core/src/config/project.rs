@@ -0,0 +1,577 @@
+//! Convention-based project directory loading.
+//!
+//! A project root directory is laid out as:
+//!
+//! ```text
+//! mockito.config.yaml   # optional settings (any supported extension)
+//! collections/**        # collection files
+//! groups/**             # route group files
+//! routes/**             # route files
+//! fixtures/**           # route files (legacy layout)
+//! templates/**          # template partials, named by path without extension
+//! ```
+//!
+//! Small projects can instead keep everything in a single file with `vars`,
+//! `routes`, `collections`, `groups` and `settings` sections (all optional):
+//!
+//! ```yaml
+//! vars:
+//!   version: v2
+//! settings:
+//!   default_collection: base
+//! routes:
+//!   - id: users
+//!     url: /api/{{vars.version}}/users
+//!     transport: HTTP
+//!     presets: [...]
+//! collections:
+//!   - id: base
+//!     routes: [users]
+//! ```
+
+use crate::config::error::ConfigError;
+use crate::config::parser::{self, get_file_type, ConfigFileType, LoadOptions};
+use crate::config::reload::ProjectReloader;
+use crate::config::{include, schema};
+use crate::mocks::manager::{DefinitionKind, MocksManager};
+use crate::mocks::validation::{self, Severity, ValidationIssue};
+use crate::types::{collection::Collection, group::Group, route::Route, settings::Settings};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories holding route files
+pub(crate) const ROUTE_DIRS: [&str; 2] = ["routes", "fixtures"];
+/// Directory holding collection files
+pub(crate) const COLLECTIONS_DIR: &str = "collections";
+/// Directory holding route group files
+pub(crate) const GROUPS_DIR: &str = "groups";
+/// Directory holding template partials
+pub(crate) const TEMPLATES_DIR: &str = "templates";
+/// Settings file name without extension
+const SETTINGS_FILE: &str = "mockito.config";
+
+/// Project loaded from a directory.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// Manager populated with all discovered routes and collections
+    pub manager: MocksManager,
+    /// Project settings (defaults if no settings file exists)
+    pub settings: Settings,
+    /// Validation issues of the loaded definitions, with the files they come from
+    pub issues: Vec<ValidationIssue>,
+    /// Files the definitions were loaded from, by kind and ID
+    pub sources: HashMap<(DefinitionKind, String), String>,
+}
+
+impl Project {
+    /// Get the file a definition was loaded from.
+    pub fn source(&self, kind: DefinitionKind, id: &str) -> Option<&str> {
+        self.sources
+            .get(&(kind, id.to_string()))
+            .map(String::as_str)
+    }
+}
+
+/// Combined config file with all project sections.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProjectFile {
+    routes: Vec<Route>,
+    collections: Vec<Collection>,
+    groups: Vec<Group>,
+    settings: Settings,
+}
+
+/// Load a project from a root directory or a combined config file.
+///
+/// For a directory, discovers route files under `routes/` and `fixtures/`, collection
+/// files under `collections/`, group files under `groups/` (recursively, files of
+/// unsupported types are skipped) and an optional `mockito.config.*` settings file.
+///
+/// Loaded definitions are validated, broken references, expressions and templates
+/// are reported in [`Project::issues`] instead of failing the load.
+///
+/// # Errors
+/// Returns error if any discovered file can't be loaded or an ID is defined twice
+/// while the manager's duplicate policy rejects duplicates.
+pub fn load_project(dir: &str) -> Result<Project, ConfigError> {
+    load_project_with(dir, MocksManager::new())
+}
+
+/// Load a project from a root directory into an existing manager.
+///
+/// Useful to apply a custom duplicate policy, see [`MocksManager::with_duplicate_policy`].
+pub fn load_project_with(path: &str, manager: MocksManager) -> Result<Project, ConfigError> {
+    load_project_with_options(path, manager, LoadOptions::default())
+}
+
+/// Load a project into an existing manager, loading definition files with the given options.
+///
+/// Useful to skip unknown fields instead of failing, see [`LoadOptions::unknown_fields`].
+pub fn load_project_with_options(
+    path: &str,
+    manager: MocksManager,
+    options: LoadOptions,
+) -> Result<Project, ConfigError> {
+    let root = Path::new(path);
+    let project = if root.is_file() {
+        load_project_file(path, manager, &options)?
+    } else {
+        load_project_dir(path, manager, options)?
+    };
+
+    tracing::info!(
+        path,
+        routes = project.manager.list_routes().len(),
+        collections = project.manager.list_collections().len(),
+        "project loaded"
+    );
+    for issue in &project.issues {
+        match issue.severity {
+            Severity::Error => tracing::error!(%issue, "invalid definition"),
+            Severity::Warning => tracing::warn!(%issue, "suspicious definition"),
+        }
+    }
+    Ok(project)
+}
+
+/// Load a project from a root directory.
+fn load_project_dir(
+    path: &str,
+    mut manager: MocksManager,
+    options: LoadOptions,
+) -> Result<Project, ConfigError> {
+    let mut reloader = ProjectReloader::with_options(path, options);
+    let settings = reloader.load(&mut manager)?;
+    let template = settings.template.unwrap_or_default();
+    let partials = settings.partials.clone().unwrap_or_default();
+    let sources = definition_sources(&manager, |kind, id| {
+        reloader
+            .source(kind, id)
+            .map(|file| file.to_string_lossy().into_owned())
+    });
+    let issues = validation::validate_with(&manager, template, &partials, |kind, id| {
+        sources.get(&(kind, id.to_string())).cloned()
+    });
+
+    Ok(Project {
+        manager,
+        settings,
+        issues,
+        sources,
+    })
+}
+
+/// Load a project from a combined config file.
+fn load_project_file(
+    path: &str,
+    mut manager: MocksManager,
+    options: &LoadOptions,
+) -> Result<Project, ConfigError> {
+    let value = match include::load_value(path)? {
+        Value::Object(sections) => Value::Object(parser::apply_file_vars(sections, path)?),
+        value => value,
+    };
+    let file: ProjectFile = schema::deserialize_with(value, path, &options.unknown_fields)?;
+    manager.add_routes(file.routes)?;
+    manager.add_collections(file.collections)?;
+    manager.add_groups(file.groups)?;
+
+    let template = file.settings.template.unwrap_or_default();
+    let partials = file.settings.partials.clone().unwrap_or_default();
+    let issues =
+        validation::validate_with(&manager, template, &partials, |_, _| Some(path.to_string()));
+    let sources = definition_sources(&manager, |_, _| Some(path.to_string()));
+    Ok(Project {
+        manager,
+        settings: file.settings,
+        issues,
+        sources,
+    })
+}
+
+/// Collect the files of all definitions stored in the manager.
+fn definition_sources(
+    manager: &MocksManager,
+    source: impl Fn(DefinitionKind, &str) -> Option<String>,
+) -> HashMap<(DefinitionKind, String), String> {
+    let routes = manager
+        .list_routes()
+        .into_iter()
+        .map(|r| (DefinitionKind::Route, &*r.id));
+    let collections = manager
+        .list_collections()
+        .into_iter()
+        .map(|c| (DefinitionKind::Collection, &*c.id));
+    let groups = manager
+        .list_groups()
+        .into_iter()
+        .map(|g| (DefinitionKind::Group, g.id.as_str()));
+    routes
+        .chain(collections)
+        .chain(groups)
+        .filter_map(|(kind, id)| Some(((kind, id.to_string()), source(kind, id)?)))
+        .collect()
+}
+
+/// Glob pattern matching all files under a subdirectory of the root.
+pub(crate) fn tree_pattern(root: &Path, subdir: &str) -> String {
+    let dir = root.join(subdir).to_string_lossy().into_owned();
+    format!("{}/**/*", glob::Pattern::escape(&dir))
+}
+
+/// Glob pattern matching settings files of all types in the root.
+pub(crate) fn settings_pattern(root: &Path) -> String {
+    let root = glob::Pattern::escape(&root.to_string_lossy());
+    format!("{}/{}.*", root, SETTINGS_FILE)
+}
+
+/// Add template partials from files under `templates/` to the settings.
+///
+/// Partials are named by their path relative to the directory without extension
+/// (e.g. `templates/users/summary.hbs` is `users/summary`). Partials defined in
+/// the settings file take precedence.
+pub(crate) fn load_partials(root: &Path, settings: &mut Settings) -> Result<(), ConfigError> {
+    let dir = root.join(TEMPLATES_DIR);
+    let mut files = parser::expand_glob(&tree_pattern(root, TEMPLATES_DIR))?
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .peekable();
+    if files.peek().is_none() {
+        return Ok(());
+    }
+
+    let partials = settings.partials.get_or_insert_with(HashMap::new);
+    for path in files {
+        let Ok(relative) = path
+            .with_extension("")
+            .strip_prefix(&dir)
+            .map(Path::to_path_buf)
+        else {
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if partials.contains_key(&name) {
+            continue;
+        }
+        let source = fs::read_to_string(&path).map_err(|e| ConfigError::Io {
+            source: e,
+            path: path.to_string_lossy().into_owned(),
+        })?;
+        partials.insert(name, source);
+    }
+    Ok(())
+}
+
+/// Find the settings file in the root directory.
+pub(crate) fn find_settings_file(root: &Path) -> Result<Option<String>, ConfigError> {
+    Ok(parser::expand_glob(&settings_pattern(root))?
+        .into_iter()
+        .find(|path| get_file_type(path) != ConfigFileType::Unknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::UnknownFieldPolicy;
+    use crate::config::test_dir::TestDir;
+    use crate::mocks::manager::DuplicatePolicy;
+    use rstest::rstest;
+    use std::sync::{Arc, Mutex};
+
+    fn route_file(id: &str) -> String {
+        format!(r#"{{"id": "{id}", "url": "/api/{id}", "transport": "HTTP", "presets": []}}"#)
+    }
+
+    #[rstest]
+    fn test_load_project() {
+        let users = route_file("users");
+        let orders = route_file("orders");
+        let legacy = route_file("legacy");
+        let dir = TestDir::with_files(
+            "project_load",
+            &[
+                ("mockito.config.yaml", "default_collection: base"),
+                ("routes/users.json", &users),
+                ("routes/shop/orders.json", &orders),
+                ("routes/README.md", "not a route"),
+                ("fixtures/legacy.json", &legacy),
+                (
+                    "collections/all.yaml",
+                    "- id: base\n  routes: []\n- id: child\n  from: base\n  routes: []",
+                ),
+                (
+                    "collections/extra/other.json",
+                    r#"{"id": "other", "routes": []}"#,
+                ),
+                ("groups/api.yaml", "id: api\nbase_url: /api"),
+            ],
+        );
+
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        let route_ids: Vec<&str> = project
+            .manager
+            .list_routes()
+            .iter()
+            .map(|r| r.id.as_ref())
+            .collect();
+        assert_eq!(route_ids, vec!["legacy", "orders", "users"]);
+        let collection_ids: Vec<&str> = project
+            .manager
+            .list_collections()
+            .iter()
+            .map(|c| c.id.as_ref())
+            .collect();
+        assert_eq!(collection_ids, vec!["base", "child", "other"]);
+        assert!(project.manager.get_group("api").is_some());
+        assert!(project
+            .source(DefinitionKind::Group, "api")
+            .is_some_and(|file| file.ends_with("groups/api.yaml")));
+        assert_eq!(project.settings.default_collection.as_deref(), Some("base"));
+    }
+
+    #[rstest]
+    fn test_load_project_without_settings() {
+        let dir = TestDir::with_files("project_empty", &[]);
+
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        assert!(project.manager.list_routes().is_empty());
+        assert_eq!(project.settings, Settings::default());
+    }
+
+    #[rstest]
+    fn test_load_project_combined_file() {
+        let dir = TestDir::with_files(
+            "project_combined",
+            &[(
+                "mocks.yaml",
+                r#"
+vars:
+  resource: users
+settings:
+  default_collection: base
+groups:
+  - id: api
+    base_url: /api
+routes:
+  - id: users
+    url: /{{vars.resource}}
+    transport: HTTP
+    group: api
+    presets: []
+collections:
+  - id: base
+    routes: []
+"#,
+            )],
+        );
+
+        let project = load_project(dir.join("mocks.yaml").to_str().unwrap()).unwrap();
+        assert_eq!(project.manager.get_route("users").unwrap().url, "/users");
+        assert!(project.manager.get_group("api").is_some());
+        assert_eq!(project.manager.list_collections().len(), 1);
+        assert_eq!(project.settings.default_collection.as_deref(), Some("base"));
+        let mocks_file = dir.join("mocks.yaml").to_string_lossy().into_owned();
+        assert_eq!(project.issues.len(), 1);
+        assert_eq!(project.issues[0].path, "routes.users");
+        assert_eq!(
+            project.source(DefinitionKind::Route, "users"),
+            project.issues[0].file.as_deref()
+        );
+        assert_eq!(project.issues[0].file.as_deref(), Some(mocks_file.as_str()));
+    }
+
+    #[rstest]
+    fn test_load_project_issues() {
+        let dir = TestDir::with_files(
+            "project_issues",
+            &[
+                ("mockito.config.yaml", "template: liquid"),
+                (
+                    "routes/users.yaml",
+                    r#"
+id: users
+url: /api/users
+transport: HTTP
+presets:
+  - id: page
+    query: "${query.page ==}"
+    variants:
+      - id: ok
+        body:
+          name: "{% if payload.name %}"
+"#,
+                ),
+                (
+                    "collections/base.yaml",
+                    "id: base\nroutes: [users:page:ok, missing]",
+                ),
+            ],
+        );
+
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        let issues: Vec<(&str, String)> = project
+            .issues
+            .iter()
+            .map(|i| {
+                let file = Path::new(i.file.as_deref().unwrap());
+                (
+                    i.path.as_str(),
+                    file.strip_prefix(&dir).unwrap().display().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    "routes.users.presets[0].query",
+                    "routes/users.yaml".to_string()
+                ),
+                (
+                    "routes.users.presets[0].variants[0].body.name",
+                    "routes/users.yaml".to_string()
+                ),
+                (
+                    "collections.base.routes[1]",
+                    "collections/base.yaml".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_load_project_partials() {
+        let dir = TestDir::with_files(
+            "project_partials",
+            &[
+                (
+                    "mockito.config.yaml",
+                    "partials:\n  greeting: 'Hi {{payload.name}}'",
+                ),
+                ("templates/greeting.hbs", "Hello {{payload.name}}"),
+                (
+                    "templates/users/summary.hbs",
+                    "{{> greeting}} ({{params.id}})",
+                ),
+            ],
+        );
+
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(
+            project.settings.partials,
+            Some(HashMap::from([
+                ("greeting".to_string(), "Hi {{payload.name}}".to_string()),
+                (
+                    "users/summary".to_string(),
+                    "{{> greeting}} ({{params.id}})".to_string()
+                ),
+            ]))
+        );
+    }
+
+    #[rstest]
+    fn test_load_project_combined_file_unknown_section() {
+        let dir = TestDir::with_files(
+            "project_combined_unknown",
+            &[(
+                "mocks.yaml",
+                "settings: {}
+fixtures: []",
+            )],
+        );
+
+        let error = load_project(dir.join("mocks.yaml").to_str().unwrap()).unwrap_err();
+        assert!(matches!(error, ConfigError::Schema { ref field, .. } if field == "fixtures"));
+    }
+
+    #[rstest]
+    fn test_load_project_with_duplicate_policy() {
+        let users = route_file("users");
+        let dir = TestDir::with_files(
+            "project_duplicates",
+            &[
+                ("routes/users.json", &users),
+                ("fixtures/users.json", &users),
+            ],
+        );
+
+        let manager = MocksManager::with_duplicate_policy(DuplicatePolicy::Error);
+        let error = load_project_with(dir.to_str().unwrap(), manager).unwrap_err();
+        assert!(matches!(error, ConfigError::Duplicate(_)));
+    }
+
+    #[rstest]
+    fn test_load_project_merging_route_files() {
+        let route = |preset: &str| {
+            format!(
+                r#"{{"id": "users", "url": "/users", "transport": "HTTP", "presets": [{{"id": "{preset}", "variants": []}}]}}"#
+            )
+        };
+        let (list, search) = (route("list"), route("search"));
+        let dir = TestDir::with_files(
+            "project_merge",
+            &[
+                ("routes/users/list.json", &list),
+                ("routes/users/search.json", &search),
+            ],
+        );
+
+        let manager = MocksManager::with_duplicate_policy(DuplicatePolicy::Merge);
+        let project = load_project_with(dir.to_str().unwrap(), manager).unwrap();
+        let presets: Vec<&str> = project
+            .manager
+            .get_route("users")
+            .unwrap()
+            .presets
+            .iter()
+            .map(|p| p.id.as_ref())
+            .collect();
+        assert_eq!(presets, vec!["list", "search"]);
+    }
+
+    #[rstest]
+    fn test_load_project_with_unknown_field_policy() {
+        let dir = TestDir::with_files(
+            "project_unknown_fields",
+            &[(
+                "routes/users.yaml",
+                "id: users\nurl: /users\ntransport: HTTP\npresets:\n  - id: list\n    variants: []\n    varients:\n      - id: ok\n",
+            )],
+        );
+        let path = dir.to_str().unwrap();
+
+        let error = load_project(path).unwrap_err();
+        assert!(
+            matches!(error, ConfigError::Schema { ref field, .. } if field == "presets[0].varients")
+        );
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let options = LoadOptions {
+            unknown_fields: UnknownFieldPolicy::Warn(Arc::new(move |field| {
+                sink.lock().unwrap().push(field.to_string());
+            })),
+            ..LoadOptions::default()
+        };
+        let project = load_project_with_options(path, MocksManager::new(), options).unwrap();
+        assert!(project.manager.get_route("users").is_some());
+
+        let file = dir.join("routes/users.yaml");
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![format!(
+                "{}:7:5: unknown field `presets[0].varients` ignored",
+                file.to_string_lossy()
+            )]
+        );
+    }
+}
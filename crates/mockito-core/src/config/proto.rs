@@ -0,0 +1,436 @@
+//! Protobuf descriptor loading.
+//!
+//! Routes reference message types through a [`ProtoBinding`], whose descriptor is
+//! either a compiled descriptor set (e.g. `protoc --include_imports -o orders.desc`)
+//! or a `.proto` source file parsed without `protoc`. Descriptor paths are relative
+//! to the declaring config file and replaced with canonical paths when it's loaded.
+//!
+//! Descriptors are only loaded with the `proto` feature, paths are resolved
+//! regardless.
+
+use crate::config::error::ConfigError;
+#[cfg(feature = "proto")]
+use crate::types::proto::ProtoBinding;
+#[cfg(feature = "proto")]
+use crate::types::route::Route;
+#[cfg(feature = "proto")]
+use prost_reflect::{DescriptorPool, MessageDescriptor};
+#[cfg(feature = "proto")]
+use protobuf::Message;
+use serde_json::Value;
+#[cfg(feature = "proto")]
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Key holding the protobuf binding in routes
+const PROTO_KEY: &str = "proto";
+/// Key holding the descriptor path in protobuf bindings
+const DESCRIPTOR_KEY: &str = "descriptor";
+/// Extensions of compiled descriptor set files
+#[cfg(feature = "proto")]
+const DESCRIPTOR_SET_EXTENSIONS: [&str; 4] = ["desc", "pb", "binpb", "protoset"];
+
+/// Message types resolved from a protobuf binding.
+#[cfg(feature = "proto")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoMessages {
+    /// Request message type, if known
+    pub request: Option<MessageDescriptor>,
+    /// Response message type, if known
+    pub response: Option<MessageDescriptor>,
+}
+
+/// Load a compiled descriptor set or a `.proto` file.
+///
+/// Imports of `.proto` files are resolved relative to the file's directory.
+///
+/// # Errors
+/// Returns error if the file can't be read or parsed, or has an unsupported extension.
+#[cfg(feature = "proto")]
+pub fn load_descriptors(path: &str) -> Result<DescriptorPool, ConfigError> {
+    let file = Path::new(path);
+    let extension = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let proto_error = |message: String| ConfigError::Proto {
+        path: path.to_string(),
+        message,
+    };
+
+    let bytes = if extension == "proto" {
+        let include = file.parent().unwrap_or_else(|| Path::new("."));
+        let parsed = protobuf_parse::Parser::new()
+            .pure()
+            .include(include)
+            .input(file)
+            .parse_and_typecheck()
+            .map_err(|e| proto_error(format!("{e:#}")))?;
+        // Keep imported files, the pool needs them to resolve field types
+        let mut set = protobuf::descriptor::FileDescriptorSet::new();
+        set.file = parsed.file_descriptors;
+        set.write_to_bytes()
+            .map_err(|e| proto_error(e.to_string()))?
+    } else if DESCRIPTOR_SET_EXTENSIONS.contains(&extension) {
+        fs::read(file).map_err(|e| ConfigError::Io {
+            source: e,
+            path: path.to_string(),
+        })?
+    } else {
+        return Err(ConfigError::UnknownFileType(path.to_string()));
+    };
+
+    DescriptorPool::decode(bytes.as_slice()).map_err(|e| proto_error(e.to_string()))
+}
+
+/// Resolve the request and response message types of a binding.
+///
+/// Explicit `request`/`response` types take precedence over the `method` ones.
+///
+/// # Errors
+/// Returns error if the method or a message type isn't defined in the pool.
+#[cfg(feature = "proto")]
+pub fn resolve_messages(
+    binding: &ProtoBinding,
+    pool: &DescriptorPool,
+) -> Result<ProtoMessages, ConfigError> {
+    let proto_error = |message: String| ConfigError::Proto {
+        path: binding.descriptor.clone(),
+        message,
+    };
+
+    let (mut request, mut response) = (None, None);
+    if let Some(method_name) = &binding.method {
+        let (service_name, name) = method_name.rsplit_once('/').ok_or_else(|| {
+            proto_error(format!(
+                "Invalid method '{}', expected 'package.Service/Method'",
+                method_name
+            ))
+        })?;
+        let method = pool
+            .get_service_by_name(service_name)
+            .and_then(|service| service.methods().find(|m| m.name() == name))
+            .ok_or_else(|| proto_error(format!("Method not found: {}", method_name)))?;
+        request = Some(method.input());
+        response = Some(method.output());
+    }
+
+    let message = |name: &String| {
+        pool.get_message_by_name(name)
+            .ok_or_else(|| proto_error(format!("Message type not found: {}", name)))
+    };
+    if let Some(name) = &binding.request {
+        request = Some(message(name)?);
+    }
+    if let Some(name) = &binding.response {
+        response = Some(message(name)?);
+    }
+
+    Ok(ProtoMessages { request, response })
+}
+
+/// Descriptors of all routes with protobuf bindings, each file loaded once.
+#[cfg(feature = "proto")]
+#[derive(Debug, Clone, Default)]
+pub struct ProtoRegistry {
+    /// Loaded descriptor pools by descriptor path
+    pools: HashMap<String, DescriptorPool>,
+}
+
+#[cfg(feature = "proto")]
+impl ProtoRegistry {
+    /// Load descriptors referenced by routes.
+    ///
+    /// # Errors
+    /// Returns error if a descriptor can't be loaded or a binding references
+    /// an unknown method or message type.
+    pub fn load<'a>(routes: impl IntoIterator<Item = &'a Route>) -> Result<Self, ConfigError> {
+        let mut registry = Self::default();
+        for binding in routes.into_iter().filter_map(|r| r.proto.as_ref()) {
+            if !registry.pools.contains_key(&binding.descriptor) {
+                let pool = load_descriptors(&binding.descriptor)?;
+                registry.pools.insert(binding.descriptor.clone(), pool);
+            }
+            resolve_messages(binding, &registry.pools[&binding.descriptor])?;
+        }
+        Ok(registry)
+    }
+
+    /// Get the descriptor pool loaded from a file.
+    pub fn pool(&self, descriptor: &str) -> Option<&DescriptorPool> {
+        self.pools.get(descriptor)
+    }
+
+    /// Get message types of a route, `None` if it has no protobuf binding.
+    ///
+    /// # Errors
+    /// Returns error if the route's descriptor wasn't loaded by this registry.
+    pub fn messages(&self, route: &Route) -> Result<Option<ProtoMessages>, ConfigError> {
+        let Some(binding) = &route.proto else {
+            return Ok(None);
+        };
+        let pool = self
+            .pool(&binding.descriptor)
+            .ok_or_else(|| ConfigError::Proto {
+                path: binding.descriptor.clone(),
+                message: format!("Descriptor not loaded for route '{}'", route.id),
+            })?;
+        resolve_messages(binding, pool).map(Some)
+    }
+}
+
+/// Replace descriptor paths of protobuf bindings with canonical paths.
+///
+/// Returns error if a referenced descriptor file doesn't exist.
+pub fn resolve_descriptor_paths(value: Value, base_dir: &Path) -> Result<Value, ConfigError> {
+    match value {
+        Value::Object(obj) => obj
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::Object(mut binding) if key == PROTO_KEY => {
+                        if let Some(Value::String(path)) = binding.get(DESCRIPTOR_KEY) {
+                            let path = base_dir.join(path);
+                            let canonical =
+                                fs::canonicalize(&path).map_err(|e| ConfigError::Io {
+                                    source: e,
+                                    path: path.to_string_lossy().into_owned(),
+                                })?;
+                            binding.insert(
+                                DESCRIPTOR_KEY.to_string(),
+                                Value::String(canonical.to_string_lossy().into_owned()),
+                            );
+                        }
+                        Value::Object(binding)
+                    }
+                    value => resolve_descriptor_paths(value, base_dir)?,
+                };
+                Ok((key, value))
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        Value::Array(arr) => arr
+            .into_iter()
+            .map(|item| resolve_descriptor_paths(item, base_dir))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        other => Ok(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_dir::TestDir;
+    #[cfg(feature = "proto")]
+    use crate::types::route::Transport;
+    use rstest::rstest;
+    use serde_json::json;
+
+    const COMMON_PROTO: &str = r#"
+syntax = "proto3";
+package shop.common;
+
+message Money {
+  int64 units = 1;
+  string currency = 2;
+}
+"#;
+
+    const ORDERS_PROTO: &str = r#"
+syntax = "proto3";
+package shop.v1;
+
+import "common.proto";
+
+message GetOrderRequest {
+  string id = 1;
+}
+
+message Order {
+  string id = 1;
+  shop.common.Money total = 2;
+}
+
+service Orders {
+  rpc GetOrder(GetOrderRequest) returns (Order);
+}
+"#;
+
+    fn create_proto_dir(name: &str) -> TestDir {
+        TestDir::with_files(
+            name,
+            &[
+                ("common.proto", COMMON_PROTO),
+                ("orders.proto", ORDERS_PROTO),
+            ],
+        )
+    }
+
+    #[cfg(feature = "proto")]
+    fn binding(
+        descriptor: &Path,
+        method: Option<&str>,
+        request: Option<&str>,
+        response: Option<&str>,
+    ) -> ProtoBinding {
+        ProtoBinding {
+            descriptor: descriptor.to_string_lossy().into_owned(),
+            method: method.map(String::from),
+            request: request.map(String::from),
+            response: response.map(String::from),
+        }
+    }
+
+    #[cfg(feature = "proto")]
+    fn message_names(messages: &ProtoMessages) -> (Option<&str>, Option<&str>) {
+        (
+            messages.request.as_ref().map(|m| m.full_name()),
+            messages.response.as_ref().map(|m| m.full_name()),
+        )
+    }
+
+    #[cfg(feature = "proto")]
+    #[rstest]
+    fn test_load_descriptors_proto_and_set() {
+        let dir = create_proto_dir("proto_load");
+        let proto = dir.join("orders.proto");
+        let pool = load_descriptors(proto.to_str().unwrap()).unwrap();
+        assert!(pool.get_message_by_name("shop.common.Money").is_some());
+
+        // Compiled descriptor sets load to the same types
+        let set = dir.join("orders.desc");
+        fs::write(&set, pool.encode_to_vec()).unwrap();
+        let pool = load_descriptors(set.to_str().unwrap()).unwrap();
+        assert!(pool.get_service_by_name("shop.v1.Orders").is_some());
+    }
+
+    #[cfg(feature = "proto")]
+    #[rstest]
+    fn test_load_descriptors_errors() {
+        let dir = create_proto_dir("proto_errors");
+        fs::write(dir.join("broken.proto"), "syntax = \"proto3\";\nmessage {").unwrap();
+
+        let error = load_descriptors(dir.join("broken.proto").to_str().unwrap()).unwrap_err();
+        assert!(matches!(error, ConfigError::Proto { .. }));
+        let error = load_descriptors(dir.join("orders.txt").to_str().unwrap()).unwrap_err();
+        assert!(matches!(error, ConfigError::UnknownFileType(_)));
+    }
+
+    #[cfg(feature = "proto")]
+    #[rstest]
+    #[case(Some("shop.v1.Orders/GetOrder"), None, None, (Some("shop.v1.GetOrderRequest"), Some("shop.v1.Order")))]
+    #[case(Some("shop.v1.Orders/GetOrder"), None, Some("shop.common.Money"), (Some("shop.v1.GetOrderRequest"), Some("shop.common.Money")))]
+    #[case(None, None, Some("shop.v1.Order"), (None, Some("shop.v1.Order")))]
+    fn test_resolve_messages(
+        #[case] method: Option<&str>,
+        #[case] request: Option<&str>,
+        #[case] response: Option<&str>,
+        #[case] expected: (Option<&str>, Option<&str>),
+    ) {
+        let dir = create_proto_dir("proto_resolve");
+        let proto = dir.join("orders.proto");
+        let pool = load_descriptors(proto.to_str().unwrap()).unwrap();
+
+        let messages =
+            resolve_messages(&binding(&proto, method, request, response), &pool).unwrap();
+        assert_eq!(message_names(&messages), expected);
+    }
+
+    #[cfg(feature = "proto")]
+    #[rstest]
+    #[case(
+        Some("shop.v1.Orders/Missing"),
+        None,
+        "Method not found: shop.v1.Orders/Missing"
+    )]
+    #[case(Some("GetOrder"), None, "Invalid method 'GetOrder'")]
+    #[case(
+        None,
+        Some("shop.v1.Missing"),
+        "Message type not found: shop.v1.Missing"
+    )]
+    fn test_resolve_messages_errors(
+        #[case] method: Option<&str>,
+        #[case] request: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let dir = create_proto_dir("proto_resolve_error");
+        let proto = dir.join("orders.proto");
+        let pool = load_descriptors(proto.to_str().unwrap()).unwrap();
+
+        let error = resolve_messages(&binding(&proto, method, request, None), &pool).unwrap_err();
+        assert!(error.to_string().contains(expected), "{error}");
+    }
+
+    #[cfg(feature = "proto")]
+    #[rstest]
+    fn test_proto_registry() {
+        let dir = create_proto_dir("proto_registry");
+        let proto = dir.join("orders.proto");
+        let route = |id: &str, proto: Option<ProtoBinding>| Route {
+            id: id.into(),
+            url: format!("/{id}"),
+            transport: Transport::Http,
+            method: None,
+            presets: vec![],
+            tags: vec![],
+            group: None,
+            proto,
+            graphql: None,
+            tcp: None,
+        };
+        let routes = [
+            route(
+                "get-order",
+                Some(binding(&proto, Some("shop.v1.Orders/GetOrder"), None, None)),
+            ),
+            route(
+                "order",
+                Some(binding(&proto, None, None, Some("shop.v1.Order"))),
+            ),
+            route("health", None),
+        ];
+
+        let registry = ProtoRegistry::load(&routes).unwrap();
+        assert_eq!(registry.pools.len(), 1);
+        let messages = registry.messages(&routes[0]).unwrap().unwrap();
+        assert_eq!(
+            message_names(&messages),
+            (Some("shop.v1.GetOrderRequest"), Some("shop.v1.Order"))
+        );
+        assert!(registry.messages(&routes[2]).unwrap().is_none());
+
+        let unknown = route(
+            "unknown",
+            Some(binding(&proto, None, Some("shop.v1.Nope"), None)),
+        );
+        assert!(ProtoRegistry::load([&unknown]).is_err());
+        assert!(ProtoRegistry::default().messages(&routes[0]).is_err());
+    }
+
+    #[rstest]
+    fn test_resolve_descriptor_paths() {
+        let dir = create_proto_dir("proto_paths");
+        let value = json!({
+            "id": "get-order",
+            "proto": {"descriptor": "./orders.proto", "method": "shop.v1.Orders/GetOrder"},
+            "presets": [{"id": "p", "variants": [{"id": "v", "body": {"descriptor": "x"}}]}]
+        });
+
+        let resolved = resolve_descriptor_paths(value, &dir).unwrap();
+        let canonical = fs::canonicalize(dir.join("orders.proto")).unwrap();
+        assert_eq!(
+            resolved["proto"]["descriptor"],
+            json!(canonical.to_string_lossy())
+        );
+        assert_eq!(
+            resolved["presets"][0]["variants"][0]["body"]["descriptor"],
+            "x"
+        );
+
+        let missing = json!({"proto": {"descriptor": "missing.desc"}});
+        assert!(matches!(
+            resolve_descriptor_paths(missing, &dir),
+            Err(ConfigError::Io { .. })
+        ));
+    }
+}
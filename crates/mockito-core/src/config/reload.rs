@@ -0,0 +1,510 @@
+//! Incremental reloading of project directories.
+//!
+//! [`ProjectReloader`] remembers which definitions were loaded from which file of a
+//! project directory, so a change reported by the [`Watcher`](crate::config::watcher::Watcher)
+//! is applied by re-parsing only the changed file and patching the manager, instead
+//! of reloading the whole project:
+//!
+//! ```ignore
+//! let mut reloader = ProjectReloader::new("mocks");
+//! let mut manager = MocksManager::new();
+//! reloader.load(&mut manager)?;
+//!
+//! for event in watcher.poll()? {
+//!     if !reloader.apply(&mut manager, &event)? {
+//!         // Not a definition file (e.g. settings), reload everything
+//!     }
+//! }
+//! controller.reload(manager.clone())?;
+//! ```
+
+use crate::config::error::ConfigError;
+use crate::config::parser::{self, get_file_type, ConfigFileType, LoadOptions};
+use crate::config::project::{
+    find_settings_file, load_partials, settings_pattern, tree_pattern, COLLECTIONS_DIR, GROUPS_DIR,
+    ROUTE_DIRS,
+};
+use crate::config::watcher::{ChangeKind, WatchEvent};
+use crate::mocks::manager::{DefinitionKind, DuplicateIdError, MocksManager};
+use crate::types::{collection::Collection, group::Group, route::Route, settings::Settings};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Definition type loaded from project files.
+trait Definition: Sized + Send {
+    const KIND: DefinitionKind;
+
+    fn id(&self) -> &str;
+    fn load(path: &str, options: &LoadOptions) -> Result<Vec<Self>, ConfigError>;
+    fn add(self, manager: &mut MocksManager) -> Result<(), DuplicateIdError>;
+    fn remove(manager: &mut MocksManager, id: &str) -> Option<Self>;
+}
+
+impl Definition for Route {
+    const KIND: DefinitionKind = DefinitionKind::Route;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn load(path: &str, options: &LoadOptions) -> Result<Vec<Self>, ConfigError> {
+        parser::load_routes_with(&glob::Pattern::escape(path), options)
+    }
+
+    fn add(self, manager: &mut MocksManager) -> Result<(), DuplicateIdError> {
+        manager.add_route(self)
+    }
+
+    fn remove(manager: &mut MocksManager, id: &str) -> Option<Self> {
+        manager.remove_route(id)
+    }
+}
+
+impl Definition for Collection {
+    const KIND: DefinitionKind = DefinitionKind::Collection;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn load(path: &str, options: &LoadOptions) -> Result<Vec<Self>, ConfigError> {
+        parser::load_collections_with(path, options)
+    }
+
+    fn add(self, manager: &mut MocksManager) -> Result<(), DuplicateIdError> {
+        manager.add_collection(self)
+    }
+
+    fn remove(manager: &mut MocksManager, id: &str) -> Option<Self> {
+        manager.remove_collection(id)
+    }
+}
+
+impl Definition for Group {
+    const KIND: DefinitionKind = DefinitionKind::Group;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn load(path: &str, options: &LoadOptions) -> Result<Vec<Self>, ConfigError> {
+        parser::load_groups_with(path, options)
+    }
+
+    fn add(self, manager: &mut MocksManager) -> Result<(), DuplicateIdError> {
+        manager.add_group(self)
+    }
+
+    fn remove(manager: &mut MocksManager, id: &str) -> Option<Self> {
+        manager.remove_group(id)
+    }
+}
+
+/// Loader of a project directory applying file changes incrementally.
+#[derive(Debug, Clone)]
+pub struct ProjectReloader {
+    /// Project root directory
+    root: PathBuf,
+    /// IDs of definitions loaded from each file
+    sources: HashMap<PathBuf, Vec<String>>,
+    /// File each stored definition was last loaded from
+    owners: HashMap<(DefinitionKind, String), PathBuf>,
+    /// Options for loading definition files
+    options: LoadOptions,
+}
+
+impl ProjectReloader {
+    /// Create a reloader for a project root directory.
+    pub fn new(dir: &str) -> Self {
+        Self::with_options(dir, LoadOptions::default())
+    }
+
+    /// Create a reloader loading definition files with the given options.
+    pub fn with_options(dir: &str, options: LoadOptions) -> Self {
+        Self {
+            root: normalize(Path::new(dir)),
+            sources: HashMap::new(),
+            owners: HashMap::new(),
+            options,
+        }
+    }
+
+    /// Load all definition files of the project into a manager.
+    ///
+    /// Files are discovered like [`load_project`](crate::config::project::load_project)
+    /// does. Returns the project settings (defaults if no settings file exists)
+    /// with the partials of the `templates/` directory.
+    pub fn load(&mut self, manager: &mut MocksManager) -> Result<Settings, ConfigError> {
+        self.sources.clear();
+        self.owners.clear();
+
+        for route_dir in ROUTE_DIRS {
+            self.load_tree::<Route>(manager, route_dir)?;
+        }
+        self.load_tree::<Collection>(manager, COLLECTIONS_DIR)?;
+        self.load_tree::<Group>(manager, GROUPS_DIR)?;
+
+        let mut settings = match find_settings_file(&self.root)? {
+            Some(path) => parser::load_settings(&path)?,
+            None => Settings::default(),
+        };
+        load_partials(&self.root, &mut settings)?;
+        Ok(settings)
+    }
+
+    /// Apply a file change to the manager by re-parsing only the changed file.
+    ///
+    /// Definitions previously loaded from the file are replaced with its current
+    /// ones, or removed if the file was removed. Definitions with the same ID loaded
+    /// from other files are not restored when the file stops defining it, and routes
+    /// merged from several files with [`DuplicatePolicy::Merge`](crate::mocks::manager::DuplicatePolicy::Merge) are replaced as a
+    /// whole, so they need a full reload.
+    ///
+    /// Returns `false` if the path isn't a route, collection or group file of the
+    /// project (e.g. the settings file), so a full reload is needed to apply it.
+    ///
+    /// # Errors
+    /// Returns error if the file can't be loaded or defines an ID rejected by the
+    /// manager's duplicate policy. The manager is left unchanged in that case.
+    pub fn apply(
+        &mut self,
+        manager: &mut MocksManager,
+        event: &WatchEvent,
+    ) -> Result<bool, ConfigError> {
+        let path = normalize(Path::new(&event.path));
+        let Some(kind) = self.kind_of(&path) else {
+            return Ok(false);
+        };
+        tracing::debug!(path = %path.display(), ?kind, change = ?event.kind, "applying change");
+        match kind {
+            DefinitionKind::Route => self.patch::<Route>(manager, &path, event.kind)?,
+            DefinitionKind::Collection => self.patch::<Collection>(manager, &path, event.kind)?,
+            DefinitionKind::Group => self.patch::<Group>(manager, &path, event.kind)?,
+        }
+        Ok(true)
+    }
+
+    /// Get the file a stored definition was last loaded from.
+    pub fn source(&self, kind: DefinitionKind, id: &str) -> Option<&Path> {
+        self.owners
+            .get(&(kind, id.to_string()))
+            .map(PathBuf::as_path)
+    }
+
+    /// Get the files stored definitions were last loaded from, by kind and ID.
+    pub fn sources(&self) -> HashMap<(DefinitionKind, String), String> {
+        self.owners
+            .iter()
+            .map(|(key, path)| (key.clone(), path.to_string_lossy().into_owned()))
+            .collect()
+    }
+
+    /// Glob patterns matching the definition and settings files of the project,
+    /// to watch them with a [`Watcher`](crate::config::watcher::Watcher).
+    pub fn watch_patterns(&self) -> Vec<String> {
+        ROUTE_DIRS
+            .iter()
+            .chain(&[COLLECTIONS_DIR, GROUPS_DIR])
+            .map(|dir| tree_pattern(&self.root, dir))
+            .chain([settings_pattern(&self.root)])
+            .collect()
+    }
+
+    /// Kind of definitions a project file holds, based on its directory.
+    fn kind_of(&self, path: &Path) -> Option<DefinitionKind> {
+        if get_file_type(&path.to_string_lossy()) == ConfigFileType::Unknown {
+            return None;
+        }
+        // Paths globbed from the current directory have no `.` prefix after normalizing
+        let relative = if self.root == Path::new(".") {
+            path
+        } else {
+            path.strip_prefix(&self.root).ok()?
+        };
+        let dir = relative.components().next()?;
+        match dir.as_os_str().to_str()? {
+            d if ROUTE_DIRS.contains(&d) => Some(DefinitionKind::Route),
+            COLLECTIONS_DIR => Some(DefinitionKind::Collection),
+            GROUPS_DIR => Some(DefinitionKind::Group),
+            _ => None,
+        }
+    }
+
+    /// Load all supported files under a project subdirectory.
+    ///
+    /// Files are parsed in parallel and added to the manager in path order.
+    fn load_tree<T: Definition>(
+        &mut self,
+        manager: &mut MocksManager,
+        subdir: &str,
+    ) -> Result<(), ConfigError> {
+        let paths: Vec<PathBuf> = parser::expand_glob(&tree_pattern(&self.root, subdir))?
+            .into_iter()
+            .filter(|path| get_file_type(path) != ConfigFileType::Unknown)
+            .map(|path| normalize(Path::new(&path)))
+            .collect();
+        let loaded = parser::map_ordered(&paths, |path| {
+            T::load(&path.to_string_lossy(), &self.options)
+        })?;
+        for (path, definitions) in paths.iter().zip(loaded) {
+            self.replace(manager, path, definitions, ChangeKind::Added)?;
+        }
+        Ok(())
+    }
+
+    /// Replace definitions loaded from a file with its current content.
+    fn patch<T: Definition>(
+        &mut self,
+        manager: &mut MocksManager,
+        path: &Path,
+        change: ChangeKind,
+    ) -> Result<(), ConfigError> {
+        let definitions = match change {
+            ChangeKind::Removed => Vec::new(),
+            ChangeKind::Added | ChangeKind::Modified => {
+                T::load(&path.to_string_lossy(), &self.options)?
+            }
+        };
+        self.replace(manager, path, definitions, change)
+    }
+
+    /// Replace definitions loaded from a file with the given ones.
+    fn replace<T: Definition>(
+        &mut self,
+        manager: &mut MocksManager,
+        path: &Path,
+        definitions: Vec<T>,
+        change: ChangeKind,
+    ) -> Result<(), ConfigError> {
+        // Only remove definitions not taken over by a later loaded file
+        let previous: Vec<String> = self
+            .sources
+            .get(path)
+            .into_iter()
+            .flatten()
+            .filter(|id| {
+                self.owners
+                    .get(&(T::KIND, (*id).clone()))
+                    .map(PathBuf::as_path)
+                    == Some(path)
+            })
+            .cloned()
+            .collect();
+        let removed: Vec<T> = previous
+            .iter()
+            .filter_map(|id| T::remove(manager, id))
+            .collect();
+
+        let ids: Vec<String> = definitions.iter().map(|d| d.id().to_string()).collect();
+        for (added, definition) in definitions.into_iter().enumerate() {
+            if let Err(e) = definition.add(manager) {
+                // Duplicates are rejected before being stored, so only new IDs were added
+                for id in &ids[..added] {
+                    T::remove(manager, id);
+                }
+                for definition in removed {
+                    let _ = definition.add(manager);
+                }
+                return Err(e.into());
+            }
+        }
+
+        for id in previous {
+            self.owners.remove(&(T::KIND, id));
+        }
+        for id in &ids {
+            self.owners
+                .insert((T::KIND, id.clone()), path.to_path_buf());
+        }
+        if change == ChangeKind::Removed {
+            self.sources.remove(path);
+        } else {
+            self.sources.insert(path.to_path_buf(), ids);
+        }
+        Ok(())
+    }
+}
+
+/// Drop `.` components so paths match regardless of how they were written.
+fn normalize(path: &Path) -> PathBuf {
+    let normalized: PathBuf = path
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect();
+    if normalized.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::project::load_project;
+    use crate::config::test_dir::TestDir;
+    use crate::config::watcher::Watcher;
+    use crate::mocks::manager::DuplicatePolicy;
+    use rstest::rstest;
+    use std::fs;
+
+    fn create_project_dir(name: &str) -> TestDir {
+        TestDir::with_files(
+            name,
+            &[
+                ("routes/users.yaml", &route_file(&["users", "user"])),
+                ("routes/orders.yaml", &route_file(&["orders"])),
+                ("collections/base.yaml", "id: base\nroutes: [users, orders]"),
+                ("mockito.config.yaml", "default_collection: base"),
+            ],
+        )
+    }
+
+    fn route_file(ids: &[&str]) -> String {
+        ids.iter()
+            .map(|id| format!("- id: {id}\n  url: /{id}\n  transport: HTTP\n  presets: []\n"))
+            .collect()
+    }
+
+    fn event(dir: &Path, file: &str, kind: ChangeKind) -> WatchEvent {
+        WatchEvent {
+            path: dir.join(file).to_string_lossy().into_owned(),
+            kind,
+            parse_error: None,
+        }
+    }
+
+    fn route_ids(manager: &MocksManager) -> Vec<&str> {
+        manager
+            .list_routes()
+            .iter()
+            .map(|r| r.id.as_ref())
+            .collect()
+    }
+
+    #[rstest]
+    fn test_reloader_load() {
+        let dir = create_project_dir("reload_load");
+        let mut reloader = ProjectReloader::new(dir.to_str().unwrap());
+        let mut manager = MocksManager::new();
+
+        let settings = reloader.load(&mut manager).unwrap();
+        assert_eq!(settings.default_collection.as_deref(), Some("base"));
+        let project = load_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(manager.list_routes(), project.manager.list_routes());
+        assert_eq!(
+            manager.list_collections(),
+            project.manager.list_collections()
+        );
+    }
+
+    #[rstest]
+    fn test_reloader_apply() {
+        let dir = create_project_dir("reload_apply");
+        let mut reloader = ProjectReloader::new(dir.to_str().unwrap());
+        let mut manager = MocksManager::new();
+        reloader.load(&mut manager).unwrap();
+
+        // Modified file drops definitions it no longer declares
+        fs::write(
+            dir.join("routes/users.yaml"),
+            route_file(&["users", "admins"]),
+        )
+        .unwrap();
+        let applied = reloader
+            .apply(
+                &mut manager,
+                &event(&dir, "routes/users.yaml", ChangeKind::Modified),
+            )
+            .unwrap();
+        assert!(applied);
+        assert_eq!(route_ids(&manager), vec!["admins", "orders", "users"]);
+
+        fs::remove_file(dir.join("routes/orders.yaml")).unwrap();
+        reloader
+            .apply(
+                &mut manager,
+                &event(&dir, "routes/orders.yaml", ChangeKind::Removed),
+            )
+            .unwrap();
+        assert_eq!(route_ids(&manager), vec!["admins", "users"]);
+
+        fs::write(dir.join("collections/extra.yaml"), "id: extra\nroutes: []").unwrap();
+        reloader
+            .apply(
+                &mut manager,
+                &event(&dir, "collections/extra.yaml", ChangeKind::Added),
+            )
+            .unwrap();
+        assert_eq!(manager.list_collections().len(), 2);
+    }
+
+    #[rstest]
+    fn test_reloader_watch_patterns() {
+        let dir = create_project_dir("reload_watch");
+        let mut reloader = ProjectReloader::new(dir.to_str().unwrap());
+        let mut manager = MocksManager::new();
+        reloader.load(&mut manager).unwrap();
+        let mut watcher = Watcher::new(reloader.watch_patterns()).unwrap();
+
+        fs::write(dir.join("routes/orders.yaml"), route_file(&["carts"])).unwrap();
+        fs::write(dir.join("mockito.config.yaml"), "port: 3200").unwrap();
+        // Contents differ in length, so changes are detected within the same mtime tick
+        let events = watcher.poll().unwrap();
+        let applied: Vec<bool> = events
+            .iter()
+            .map(|e| reloader.apply(&mut manager, e).unwrap())
+            .collect();
+        assert_eq!(applied, vec![false, true]);
+        assert_eq!(route_ids(&manager), vec!["carts", "user", "users"]);
+
+        let sources = reloader.sources();
+        let carts = &sources[&(DefinitionKind::Route, "carts".to_string())];
+        assert!(carts.ends_with("routes/orders.yaml"));
+        assert!(!sources.contains_key(&(DefinitionKind::Route, "orders".to_string())));
+    }
+
+    #[rstest]
+    #[case("mockito.config.yaml")]
+    #[case("routes/README.md")]
+    #[case("other/users.yaml")]
+    fn test_reloader_apply_unhandled(#[case] file: &str) {
+        let dir = PathBuf::from("mocks");
+        let mut reloader = ProjectReloader::new("./mocks");
+        let mut manager = MocksManager::new();
+
+        let applied = reloader
+            .apply(&mut manager, &event(&dir, file, ChangeKind::Modified))
+            .unwrap();
+        assert!(!applied);
+    }
+
+    #[rstest]
+    fn test_reloader_apply_keeps_state_on_error() {
+        let dir = create_project_dir("reload_error");
+        let mut reloader = ProjectReloader::new(dir.to_str().unwrap());
+        let mut manager = MocksManager::with_duplicate_policy(DuplicatePolicy::Error);
+        reloader.load(&mut manager).unwrap();
+
+        // Parse errors keep previous definitions
+        fs::write(dir.join("routes/users.yaml"), "- id: [").unwrap();
+        let modified = event(&dir, "routes/users.yaml", ChangeKind::Modified);
+        assert!(reloader.apply(&mut manager, &modified).is_err());
+        assert_eq!(route_ids(&manager), vec!["orders", "user", "users"]);
+
+        // Duplicates rejected by the policy roll back the whole file
+        fs::write(
+            dir.join("routes/users.yaml"),
+            route_file(&["admins", "orders"]),
+        )
+        .unwrap();
+        let error = reloader.apply(&mut manager, &modified).unwrap_err();
+        assert!(matches!(error, ConfigError::Duplicate(ref e) if e.id == "orders"));
+        assert_eq!(route_ids(&manager), vec!["orders", "user", "users"]);
+
+        // Ownership is unchanged, so a valid edit still replaces the file's routes
+        fs::write(dir.join("routes/users.yaml"), route_file(&["admins"])).unwrap();
+        reloader.apply(&mut manager, &modified).unwrap();
+        assert_eq!(route_ids(&manager), vec!["admins", "orders"]);
+    }
+}
@@ -0,0 +1,345 @@
+//! Loading config files over HTTP(S).
+//!
+//! Routes and collections can be loaded from `http://` and `https://` URLs, so
+//! shared definitions hosted in an artifact store are consumed directly. The
+//! format is detected from the URL path extension, query and fragment ignored.
+//!
+//! With a cache directory configured, fetched files are stored along with their
+//! `ETag`: later requests are revalidated with `If-None-Match`, and the cached
+//! copy is used when the server can't be reached.
+//!
+//! `$include` directives are not resolved in remote files.
+//!
+//! Requests are only sent with the `remote` feature. Without it, fetching fails
+//! unless a cached copy exists.
+
+use crate::config::error::ConfigError;
+use crate::config::parser::{get_file_type, parse_config, ConfigFileType};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "remote")]
+use std::time::Duration;
+
+/// Timeout of a single remote request
+#[cfg(feature = "remote")]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Options for fetching remote config files.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteOptions {
+    /// Extra request headers (e.g. `Authorization`)
+    pub headers: HashMap<String, String>,
+    /// Directory for cached copies of fetched files
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl RemoteOptions {
+    /// Options sending the given `Authorization` header value.
+    pub fn with_authorization(value: impl Into<String>) -> Self {
+        Self {
+            headers: HashMap::from([("Authorization".to_string(), value.into())]),
+            cache_dir: None,
+        }
+    }
+}
+
+/// Check if a path is an HTTP(S) URL.
+pub fn is_remote(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Fetch a remote file and parse it into a JSON value.
+pub fn load_value(url: &str, options: &RemoteOptions) -> Result<Value, ConfigError> {
+    let path = url_path(url);
+    if get_file_type(path) == ConfigFileType::Unknown {
+        return Err(ConfigError::UnknownFileType(url.to_string()));
+    }
+    let content = fetch(url, options)?;
+    parse_config(&content, path)
+}
+
+/// Fetch the content of a remote file.
+///
+/// Returns the cached copy if the server responds with `304 Not Modified` or
+/// the request fails, and error if there is no cached copy to fall back to.
+pub fn fetch(url: &str, options: &RemoteOptions) -> Result<String, ConfigError> {
+    let cache = options
+        .cache_dir
+        .as_deref()
+        .map(|dir| CacheEntry::new(dir, url));
+    let cached = cache.as_ref().and_then(CacheEntry::read);
+
+    let remote_error = |message: String| ConfigError::Remote {
+        url: url.to_string(),
+        message,
+    };
+    let etag = cached.as_ref().and_then(|(_, etag)| etag.as_deref());
+    let result = request(url, options, etag);
+
+    match (result, cached) {
+        (Ok(Some((body, etag))), _) => {
+            if let Some(cache) = &cache {
+                cache.write(&body, etag.as_deref())?;
+            }
+            Ok(body)
+        }
+        (Ok(None), Some((body, _))) => Ok(body),
+        (Ok(None), None) => Err(remote_error(
+            "Server responded 304 Not Modified without a cached copy".to_string(),
+        )),
+        (Err(error), Some((body, _))) => {
+            tracing::warn!(url, %error, "fetch failed, using cached copy");
+            Ok(body)
+        }
+        (Err(message), None) => Err(remote_error(message)),
+    }
+}
+
+/// Send a GET request revalidating `etag`, returning the body with its `ETag`,
+/// or `None` if the server responds with `304 Not Modified`.
+#[cfg(feature = "remote")]
+fn request(
+    url: &str,
+    options: &RemoteOptions,
+    etag: Option<&str>,
+) -> Result<Option<(String, Option<String>)>, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+    let mut request = agent.get(url);
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    if let Some(etag) = etag {
+        request = request.header("If-None-Match", etag);
+    }
+
+    let mut response = request.call().map_err(|e| e.to_string())?;
+    if response.status() == 304 {
+        return Ok(None);
+    }
+    let etag = response
+        .headers()
+        .get("ETag")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| e.to_string())?;
+    Ok(Some((body, etag)))
+}
+
+/// Fail a request, requests are only sent with the `remote` feature.
+#[cfg(not(feature = "remote"))]
+fn request(
+    _url: &str,
+    _options: &RemoteOptions,
+    _etag: Option<&str>,
+) -> Result<Option<(String, Option<String>)>, String> {
+    Err("requests require the `remote` feature".to_string())
+}
+
+/// URL without query and fragment, used to detect the file type.
+fn url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Cached copy of a remote file, stored as `<hash>.body` with `<hash>.etag`.
+struct CacheEntry {
+    body: PathBuf,
+    etag: PathBuf,
+}
+
+impl CacheEntry {
+    fn new(dir: &Path, url: &str) -> Self {
+        let key = format!("{:016x}", fnv1a(url.as_bytes()));
+        Self {
+            body: dir.join(format!("{}.body", key)),
+            etag: dir.join(format!("{}.etag", key)),
+        }
+    }
+
+    /// Cached body with its ETag, if any.
+    fn read(&self) -> Option<(String, Option<String>)> {
+        let body = fs::read_to_string(&self.body).ok()?;
+        let etag = fs::read_to_string(&self.etag).ok();
+        Some((body, etag))
+    }
+
+    fn write(&self, body: &str, etag: Option<&str>) -> Result<(), ConfigError> {
+        let io_error = |path: &Path| {
+            let path = path.to_string_lossy().into_owned();
+            move |source| ConfigError::Io { source, path }
+        };
+        if let Some(dir) = self.body.parent() {
+            fs::create_dir_all(dir).map_err(io_error(dir))?;
+        }
+        fs::write(&self.body, body).map_err(io_error(&self.body))?;
+        match etag {
+            Some(etag) => fs::write(&self.etag, etag).map_err(io_error(&self.etag)),
+            None => match fs::remove_file(&self.etag) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&self.etag)(e)),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// FNV-1a hash, stable across builds unlike the std hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "remote")]
+    use crate::config::test_dir::TestDir;
+    use rstest::rstest;
+    #[cfg(feature = "remote")]
+    use std::io::{Read, Write};
+    #[cfg(feature = "remote")]
+    use std::net::TcpListener;
+    #[cfg(feature = "remote")]
+    use std::thread::{self, JoinHandle};
+
+    /// Serve the given raw responses, one per connection, returning the base URL
+    /// and a handle yielding the received requests.
+    #[cfg(feature = "remote")]
+    fn serve(responses: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                requests.push(String::from_utf8_lossy(&request).to_lowercase());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (base_url, handle)
+    }
+
+    #[cfg(feature = "remote")]
+    fn ok_response(body: &str, etag: Option<&str>) -> String {
+        let etag = etag.map(|e| format!("ETag: {}\r\n", e)).unwrap_or_default();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            body.len(),
+            etag,
+            body
+        )
+    }
+
+    #[cfg(feature = "remote")]
+    fn status_response(status: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            status
+        )
+    }
+
+    #[rstest]
+    #[case("https://example.com/routes.yaml", true)]
+    #[case("HTTP://example.com/routes.yaml", true)]
+    #[case("./routes/*.yaml", false)]
+    #[case("file:///routes.yaml", false)]
+    fn test_is_remote(#[case] path: &str, #[case] expected: bool) {
+        assert_eq!(is_remote(path), expected);
+    }
+
+    #[rstest]
+    #[case(
+        "https://example.com/routes.yaml?ref=main",
+        "https://example.com/routes.yaml"
+    )]
+    #[case(
+        "https://example.com/routes.json#top",
+        "https://example.com/routes.json"
+    )]
+    #[case("https://example.com/routes.toml", "https://example.com/routes.toml")]
+    fn test_url_path(#[case] url: &str, #[case] expected: &str) {
+        assert_eq!(url_path(url), expected);
+    }
+
+    #[cfg(feature = "remote")]
+    #[rstest]
+    fn test_load_value_sends_headers() {
+        let (base_url, server) = serve(vec![ok_response("id: users\nurl: /users", None)]);
+        let options = RemoteOptions::with_authorization("Bearer secret");
+
+        let value = load_value(&format!("{}/routes.yaml?ref=main", base_url), &options).unwrap();
+        assert_eq!(value["id"], "users");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("get /routes.yaml?ref=main "));
+        assert!(requests[0].contains("authorization: bearer secret"));
+    }
+
+    #[cfg(feature = "remote")]
+    #[rstest]
+    fn test_fetch_caches_with_etag() {
+        let cache_dir = TestDir::new("remote-etag");
+        let (base_url, server) = serve(vec![
+            ok_response("[1, 2]", Some("\"v1\"")),
+            status_response("304 Not Modified"),
+        ]);
+        let url = format!("{}/data.json", base_url);
+        let options = RemoteOptions {
+            cache_dir: Some(cache_dir.to_path_buf()),
+            ..Default::default()
+        };
+
+        assert_eq!(fetch(&url, &options).unwrap(), "[1, 2]");
+        assert_eq!(fetch(&url, &options).unwrap(), "[1, 2]");
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+
+        // Server is gone now, the cached copy is used
+        assert_eq!(fetch(&url, &options).unwrap(), "[1, 2]");
+    }
+
+    #[cfg(feature = "remote")]
+    #[rstest]
+    fn test_fetch_error_without_cache() {
+        let (base_url, server) = serve(vec![status_response("404 Not Found")]);
+        let url = format!("{}/missing.yaml", base_url);
+
+        let err = fetch(&url, &RemoteOptions::default()).unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(&err, ConfigError::Remote { url: u, .. } if *u == url));
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[rstest]
+    fn test_load_value_unknown_file_type() {
+        let url = "http://127.0.0.1:9/routes?format=yaml";
+        let err = load_value(url, &RemoteOptions::default()).unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownFileType(u) if u == url));
+    }
+
+    #[rstest]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
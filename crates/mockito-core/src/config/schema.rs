@@ -0,0 +1,368 @@
+//! Schema validation of loaded config values.
+//!
+//! Loaded values are deserialized into config types while tracking the path of the
+//! failing field (e.g. `presets[0].variants[1].status`), so unknown fields, wrong types
+//! and missing required fields are reported with their location in the source file.
+//!
+//! Unknown fields are rejected by default, so typos like `varients:` don't go unnoticed.
+//! With [`UnknownFieldPolicy::Warn`] they are skipped and reported to a callback instead.
+
+use crate::config::error::ConfigError;
+use crate::config::parser::{get_file_type, strip_json_comments, ConfigFileType};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+use std::fs;
+use std::sync::Arc;
+
+/// Prefix of serde errors about unknown fields
+const UNKNOWN_FIELD_PREFIX: &str = "unknown field `";
+
+/// Callback notified about unknown fields skipped while loading
+pub type UnknownFieldCallback = Arc<dyn Fn(&UnknownField) + Send + Sync>;
+
+/// How unknown fields in config files are handled.
+#[derive(Clone, Default)]
+pub enum UnknownFieldPolicy {
+    /// Fail loading with [`ConfigError::Schema`]
+    #[default]
+    Error,
+    /// Skip the field and report it to the callback
+    Warn(UnknownFieldCallback),
+}
+
+impl std::fmt::Debug for UnknownFieldPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnknownFieldPolicy::Error => write!(f, "Error"),
+            UnknownFieldPolicy::Warn(_) => write!(f, "Warn(..)"),
+        }
+    }
+}
+
+/// Unknown field skipped with [`UnknownFieldPolicy::Warn`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// File the field was loaded from
+    pub file: String,
+    /// Path of the field (e.g. `presets[0].varients`)
+    pub field: String,
+    /// Line in the file (1-based), if the field could be located
+    pub line: Option<usize>,
+    /// Column in the file (1-based), if the field could be located
+    pub column: Option<usize>,
+}
+
+impl std::fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": unknown field `{}` ignored", self.field)
+    }
+}
+
+/// Deserialize a value loaded from `file` into a config type, rejecting unknown fields.
+///
+/// # Errors
+/// Returns [`ConfigError::Schema`] with the failing field path. Line and column are
+/// included when the field can be located in the file itself (not in an included file).
+pub fn deserialize<T: DeserializeOwned>(value: Value, file: &str) -> Result<T, ConfigError> {
+    deserialize_with(value, file, &UnknownFieldPolicy::Error)
+}
+
+/// Deserialize a value loaded from `file` into a config type, handling unknown fields
+/// according to `policy`.
+///
+/// # Errors
+/// Returns [`ConfigError::Schema`] like [`deserialize`] for all other problems.
+pub fn deserialize_with<T: DeserializeOwned>(
+    value: Value,
+    file: &str,
+    policy: &UnknownFieldPolicy,
+) -> Result<T, ConfigError> {
+    deserialize_at(value, file, None, policy)
+}
+
+/// Deserialize an item of an array in `file` loaded on its own, see
+/// [`load_array_items`](crate::config::include::load_array_items).
+///
+/// # Errors
+/// Returns [`ConfigError::Schema`] like [`deserialize`], with the field path
+/// starting at the item index. Line and column aren't located, since that would
+/// read the whole file.
+pub fn deserialize_item<T: DeserializeOwned>(
+    value: Value,
+    file: &str,
+    index: usize,
+    policy: &UnknownFieldPolicy,
+) -> Result<T, ConfigError> {
+    deserialize_at(value, file, Some(index), policy)
+}
+
+fn deserialize_at<T: DeserializeOwned>(
+    mut value: Value,
+    file: &str,
+    index: Option<usize>,
+    policy: &UnknownFieldPolicy,
+) -> Result<T, ConfigError> {
+    loop {
+        let error = match serde_path_to_error::deserialize(&value) {
+            Ok(item) => return Ok(item),
+            Err(e) => e,
+        };
+        let field = error.path().to_string();
+        let (field, location) = match index {
+            Some(index) => (item_field(index, &field), None),
+            None => {
+                let location = locate::<T>(file, &field);
+                (field, location)
+            }
+        };
+
+        if let UnknownFieldPolicy::Warn(callback) = policy {
+            if is_unknown_field(&error.inner().to_string())
+                && remove_field(&mut value, error.path())
+            {
+                callback(&UnknownField {
+                    file: file.to_string(),
+                    field,
+                    line: location.map(|(line, _)| line),
+                    column: location.map(|(_, column)| column),
+                });
+                continue;
+            }
+        }
+
+        return Err(ConfigError::Schema {
+            file: file.to_string(),
+            field,
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            message: error.into_inner().to_string(),
+        });
+    }
+}
+
+/// Prefix a field path with the index of the array item holding it.
+fn item_field(index: usize, field: &str) -> String {
+    match field {
+        "." => format!("[{}]", index),
+        field if field.starts_with('[') => format!("[{}]{}", index, field),
+        field => format!("[{}].{}", index, field),
+    }
+}
+
+/// Check if a serde error message reports an unknown field.
+fn is_unknown_field(message: &str) -> bool {
+    message.starts_with(UNKNOWN_FIELD_PREFIX)
+}
+
+/// Remove the field at `path` from a value.
+///
+/// Returns `false` if the path doesn't lead to an object field.
+fn remove_field(value: &mut Value, path: &Path) -> bool {
+    let segments: Vec<&Segment> = path.iter().collect();
+    let Some((Segment::Map { key }, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut current = value;
+    for segment in parents {
+        let next = match segment {
+            Segment::Seq { index } => current.get_mut(*index),
+            Segment::Map { key } => current.get_mut(key.as_str()),
+            Segment::Enum { variant } => current.get_mut(variant.as_str()),
+            Segment::Unknown => None,
+        };
+        match next {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    current
+        .as_object_mut()
+        .is_some_and(|obj| obj.remove(key).is_some())
+}
+
+/// Find line and column (1-based) of a schema error by deserializing the raw file.
+///
+/// `${ENV:NAME}` placeholders are left as is: they only appear inside strings,
+/// so positions match the file on disk. Returns `None` if the raw file fails
+/// at a different field, e.g. because the error comes from an included file or
+/// a placeholder standing for a number comes first.
+fn locate<T: DeserializeOwned>(file: &str, field: &str) -> Option<(usize, usize)> {
+    let content = fs::read_to_string(file).ok()?;
+    let file_type = get_file_type(file);
+    let content = match file_type {
+        ConfigFileType::Jsonc => strip_json_comments(&content),
+        _ => content,
+    };
+
+    match file_type {
+        ConfigFileType::Json | ConfigFileType::Jsonc => {
+            let de = &mut serde_json::Deserializer::from_str(&content);
+            let e = serde_path_to_error::deserialize::<_, T>(de).err()?;
+            (e.path().to_string() == field).then(|| (e.inner().line(), e.inner().column()))
+        }
+        ConfigFileType::Yaml => {
+            let de = serde_yaml::Deserializer::from_str(&content);
+            let e = serde_path_to_error::deserialize::<_, T>(de).err()?;
+            let location = e.inner().location()?;
+            (e.path().to_string() == field).then(|| (location.line(), location.column()))
+        }
+        #[cfg(feature = "json5")]
+        ConfigFileType::Json5 => {
+            let de = &mut json5::Deserializer::from_str(&content);
+            let e = serde_path_to_error::deserialize::<_, T>(de).err()?;
+            let position = e.inner().position()?;
+            (e.path().to_string() == field).then(|| (position.line + 1, position.column + 1))
+        }
+        #[cfg(feature = "toml")]
+        ConfigFileType::Toml => {
+            let de = toml::Deserializer::parse(&content).ok()?;
+            let e = serde_path_to_error::deserialize::<_, T>(de).err()?;
+            let span = e.inner().span()?;
+            (e.path().to_string() == field).then(|| line_column(&content, span.start))
+        }
+        _ => None,
+    }
+}
+
+/// Convert a byte offset into 1-based line and column.
+#[cfg(feature = "toml")]
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::load_routes;
+    use crate::config::test_dir::TestDir;
+    use crate::types::route::Route;
+    use rstest::rstest;
+
+    #[cfg(feature = "toml")]
+    #[rstest]
+    #[case("a\nbc\ndef", 0, (1, 1))]
+    #[case("a\nbc\ndef", 3, (2, 2))]
+    #[case("a\nbc\ndef", 7, (3, 3))]
+    fn test_line_column(
+        #[case] content: &str,
+        #[case] offset: usize,
+        #[case] expected: (usize, usize),
+    ) {
+        assert_eq!(line_column(content, offset), expected);
+    }
+
+    #[rstest]
+    #[case(
+        "schema_wrong_type.yaml",
+        "id: users\nurl: /api/users\ntransport: HTTP\npresets:\n  - id: default\n    variants:\n      - id: ok\n        status: ok\n",
+        "presets[0].variants[0].status",
+        Some(8)
+    )]
+    #[case(
+        "schema_unknown_field.json",
+        "{\n  \"id\": \"users\",\n  \"url\": \"/api/users\",\n  \"transport\": \"HTTP\",\n  \"presets\": [],\n  \"delay\": 100\n}",
+        "delay",
+        Some(6)
+    )]
+    #[cfg_attr(
+        feature = "toml",
+        case(
+            "schema_missing_field.toml",
+            "id = \"users\"\ntransport = \"HTTP\"\npresets = []\n",
+            ".",
+            Some(1)
+        )
+    )]
+    fn test_load_routes_schema_errors(
+        #[case] name: &str,
+        #[case] content: &str,
+        #[case] field: &str,
+        #[case] line: Option<usize>,
+    ) {
+        let test_dir = TestDir::new("schema");
+        let test_file = test_dir.write(name, content);
+
+        let error = load_routes(test_file.to_str().unwrap()).unwrap_err();
+        match &error {
+            ConfigError::Schema {
+                field: error_field,
+                line: error_line,
+                ..
+            } => {
+                assert_eq!(error_field, field);
+                assert_eq!(*error_line, line);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[rstest]
+    fn test_deserialize_with_warn_policy() {
+        let value = serde_json::json!({
+            "id": "users",
+            "url": "/api/users",
+            "transport": "HTTP",
+            "delay": 100,
+            "presets": [{"id": "default", "variants": [], "varients": [{"id": "ok"}]}]
+        });
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let policy = UnknownFieldPolicy::Warn(Arc::new(move |field| {
+            sink.lock().unwrap().push(field.field.clone());
+        }));
+
+        let route: Route = deserialize_with(value.clone(), "missing.json", &policy).unwrap();
+        assert_eq!(route.presets[0].id, "default".into());
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec!["delay", "presets[0].varients"]
+        );
+
+        let error = deserialize::<Route>(value, "missing.json").unwrap_err();
+        assert!(matches!(error, ConfigError::Schema { ref field, .. } if field == "delay"));
+    }
+
+    #[rstest]
+    fn test_deserialize_with_warn_policy_keeps_other_errors() {
+        let value = serde_json::json!({"id": "users", "url": 1, "typo": true});
+        let policy = UnknownFieldPolicy::Warn(Arc::new(|_| {}));
+        let error = deserialize_with::<Route>(value, "missing.json", &policy).unwrap_err();
+        assert!(matches!(error, ConfigError::Schema { ref field, .. } if field == "url"));
+    }
+
+    #[rstest]
+    #[case(Some(3), Some(5), "routes.yaml:3:5: unknown field `delay` ignored")]
+    #[case(None, None, "routes.yaml: unknown field `delay` ignored")]
+    fn test_unknown_field_display(
+        #[case] line: Option<usize>,
+        #[case] column: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let field = UnknownField {
+            file: "routes.yaml".to_string(),
+            field: "delay".to_string(),
+            line,
+            column,
+        };
+        assert_eq!(field.to_string(), expected);
+    }
+
+    #[rstest]
+    fn test_deserialize_without_file() {
+        let value = serde_json::json!({"id": "users", "url": 1});
+        let error = deserialize::<Route>(value, "missing.json").unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Schema { ref field, line: None, .. } if field == "url"
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+//! Temporary directories for tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of directories created by this process, to keep names unique
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory under the system temp dir, unique per test, removed on drop.
+#[derive(Debug)]
+pub(crate) struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Create an empty directory with `name` in its file name.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "mockito-{}-{}-{}",
+            name,
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// Create a directory holding `files`, given as relative paths and contents.
+    pub fn with_files(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = Self::new(name);
+        for (path, content) in files {
+            dir.write(path, content);
+        }
+        dir
+    }
+
+    /// Write a file at a relative path, creating its parent directories.
+    pub fn write(&self, path: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,267 @@
+//! Polling file watcher for config files.
+//!
+//! [`Watcher`] tracks files matched by glob patterns and reports which of them were
+//! added, modified or removed since the last scan, together with the result of parsing
+//! changed files. Scans can be driven manually via [`Watcher::poll`] or by a background
+//! thread started with [`Watcher::spawn`].
+
+use crate::config::error::ConfigError;
+use crate::config::include;
+use crate::config::parser::{expand_patterns, get_file_type, ConfigFileType};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Kind of file change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// Change of a watched config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEvent {
+    /// Path of the changed file
+    pub path: String,
+    /// Kind of change
+    pub kind: ChangeKind,
+    /// Parse error of an added or modified file, `None` if it parsed successfully
+    /// or was removed
+    pub parse_error: Option<String>,
+}
+
+/// File state used to detect modifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Polling watcher for files matched by glob patterns.
+#[derive(Debug)]
+pub struct Watcher {
+    /// Watched glob patterns
+    patterns: Vec<String>,
+    /// Supported config files found by the last scan
+    files: HashMap<String, FileStamp>,
+}
+
+impl Watcher {
+    /// Create a watcher and record the current state of matched files.
+    ///
+    /// Files of unsupported types are ignored. Patterns prefixed with `!` exclude
+    /// files matched by the others.
+    ///
+    /// # Errors
+    /// Returns error if any glob pattern is invalid.
+    pub fn new(patterns: Vec<String>) -> Result<Self, ConfigError> {
+        let mut watcher = Self {
+            patterns,
+            files: HashMap::new(),
+        };
+        watcher.files = watcher.scan()?;
+        Ok(watcher)
+    }
+
+    /// Get watched glob patterns.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Scan matched files and return changes since the previous scan, sorted by path.
+    pub fn poll(&mut self) -> Result<Vec<WatchEvent>, ConfigError> {
+        let files = self.scan()?;
+        let mut events = Vec::new();
+
+        for (path, stamp) in &files {
+            let kind = match self.files.get(path) {
+                None => ChangeKind::Added,
+                Some(previous) if previous != stamp => ChangeKind::Modified,
+                Some(_) => continue,
+            };
+            events.push(WatchEvent {
+                path: path.clone(),
+                kind,
+                parse_error: include::load_value(path).err().map(|e| e.to_string()),
+            });
+        }
+        events.extend(
+            self.files
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .map(|path| WatchEvent {
+                    path: path.clone(),
+                    kind: ChangeKind::Removed,
+                    parse_error: None,
+                }),
+        );
+
+        events.sort_by(|a, b| a.path.cmp(&b.path));
+        self.files = files;
+        Ok(events)
+    }
+
+    /// Poll for changes on a background thread.
+    ///
+    /// `callback` is called with every non-empty batch of changes. Scan errors
+    /// (e.g. a directory became unreadable) are skipped until the next poll.
+    /// Watching stops when the returned handle is stopped or dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let watcher = Watcher::new(vec!["mocks/routes/**/*.yaml".to_string()])?;
+    /// let handle = watcher.spawn(Duration::from_millis(500), |events| {
+    ///     for event in events {
+    ///         println!("{:?} {}", event.kind, event.path);
+    ///     }
+    /// });
+    /// ```
+    pub fn spawn<F>(mut self, interval: Duration, callback: F) -> WatchHandle
+    where
+        F: Fn(Vec<WatchEvent>) + Send + 'static,
+    {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = Arc::clone(&stopped);
+        let thread = thread::spawn(move || {
+            while !thread_stopped.load(Ordering::Relaxed) {
+                thread::park_timeout(interval);
+                if thread_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Ok(events) = self.poll() {
+                    if !events.is_empty() {
+                        callback(events);
+                    }
+                }
+            }
+        });
+
+        WatchHandle {
+            stopped,
+            thread: Some(thread),
+        }
+    }
+
+    /// Collect stamps of all supported config files matched by the patterns.
+    fn scan(&self) -> Result<HashMap<String, FileStamp>, ConfigError> {
+        let mut files = HashMap::new();
+        for path in expand_patterns(&self.patterns)? {
+            if get_file_type(&path) == ConfigFileType::Unknown {
+                continue;
+            }
+            // Files removed between globbing and reading are picked up by the next scan
+            if let Ok(metadata) = fs::metadata(&path) {
+                if metadata.is_file() {
+                    let stamp = FileStamp {
+                        modified: metadata.modified().ok(),
+                        len: metadata.len(),
+                    };
+                    files.insert(path, stamp);
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Handle of a background watcher started with [`Watcher::spawn`].
+///
+/// Dropping the handle stops watching.
+#[derive(Debug)]
+pub struct WatchHandle {
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatchHandle {
+    /// Stop watching and wait for the background thread to finish.
+    pub fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_dir::TestDir;
+    use rstest::rstest;
+    use std::sync::mpsc;
+
+    fn summary(events: &[WatchEvent]) -> Vec<(&str, ChangeKind, bool)> {
+        events
+            .iter()
+            .map(|e| {
+                let name = e.path.rsplit(['/', '\\']).next().unwrap();
+                (name, e.kind, e.parse_error.is_some())
+            })
+            .collect()
+    }
+
+    #[rstest]
+    fn test_watcher_poll() {
+        let dir = TestDir::new("watcher_poll");
+        fs::write(dir.join("a.json"), r#"{"id": "a"}"#).unwrap();
+        fs::write(dir.join("b.json"), r#"{"id": "b"}"#).unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let pattern = format!("{}/*", dir.to_str().unwrap());
+        let mut watcher = Watcher::new(vec![pattern]).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        fs::write(dir.join("a.json"), r#"{"id": "a", "changed": true}"#).unwrap();
+        fs::remove_file(dir.join("b.json")).unwrap();
+        fs::write(dir.join("c.yaml"), "id: [").unwrap();
+        fs::write(dir.join("notes.txt"), "still ignored").unwrap();
+
+        assert_eq!(
+            summary(&watcher.poll().unwrap()),
+            vec![
+                ("a.json", ChangeKind::Modified, false),
+                ("b.json", ChangeKind::Removed, false),
+                ("c.yaml", ChangeKind::Added, true),
+            ]
+        );
+        assert!(watcher.poll().unwrap().is_empty());
+    }
+
+    #[rstest]
+    fn test_watcher_invalid_pattern() {
+        assert!(matches!(
+            Watcher::new(vec!["[invalid".to_string()]).unwrap_err(),
+            ConfigError::GlobPattern(_)
+        ));
+    }
+
+    #[rstest]
+    fn test_watcher_spawn() {
+        let dir = TestDir::new("watcher_spawn");
+        let pattern = format!("{}/*.json", dir.to_str().unwrap());
+        let watcher = Watcher::new(vec![pattern]).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut handle = watcher.spawn(Duration::from_millis(10), move |events| {
+            let _ = sender.send(events);
+        });
+
+        fs::write(dir.join("a.json"), "{}").unwrap();
+        let events = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(summary(&events), vec![("a.json", ChangeKind::Added, false)]);
+
+        handle.stop();
+    }
+}
@@ -0,0 +1,610 @@
+//! Evaluator of a CEL (Common Expression Language) subset.
+//!
+//! Supports the parts of CEL used by request matching rules:
+//! - literals: integers, doubles, strings, booleans, `null` and lists
+//! - field access (`payload.user.id`), indexing (`items[0]`, `headers['x-id']`)
+//! - operators: `! - * / % + == != < <= > >= in && || ?:`
+//! - functions: `size`, `has`, `int`, `double`, `string`, and the string methods
+//!   `startsWith`, `endsWith`, `contains` and `matches`, falling back to
+//!   [registered functions](crate::expression::functions)
+//!
+//! Identifiers resolve to fields of the evaluated data. Like in CEL, `&&` and `||`
+//! absorb errors of one side if the other side decides the result.
+//!
+//! An evaluation with a deadline checks it before each subexpression, so it
+//! stops shortly after the deadline passes.
+
+use crate::clock::Instant;
+use crate::expression::functions;
+use regex::Regex;
+use serde_json::{Number, Value};
+
+/// Parsed CEL expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    root: Expr,
+}
+
+impl Program {
+    /// Parse an expression.
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.ternary()?;
+        match parser.peek() {
+            None => Ok(Self { root }),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Evaluate the expression with `data` fields as variables.
+    pub fn evaluate(&self, data: &Value) -> Result<Value, String> {
+        self.evaluate_until(data, None)
+    }
+
+    /// Evaluate the expression, failing once `deadline` has passed.
+    pub fn evaluate_until(&self, data: &Value, deadline: Option<Instant>) -> Result<Value, String> {
+        eval(&self.root, &Scope { data, deadline })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+/// Punctuation, longest first so two-char operators win
+const PUNCTUATION: [&str; 22] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "+", "-", "*", "/", "%", "?", ":", ".", ",",
+    "(", ")", "[", "]",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(if text.contains('.') {
+                Token::Double(text.parse().map_err(|_| format!("invalid number {text}"))?)
+            } else {
+                Token::Int(text.parse().map_err(|_| format!("invalid number {text}"))?)
+            });
+        } else if c == '\'' || c == '"' {
+            let (s, end) = string_literal(&chars, i)?;
+            tokens.push(Token::Str(s));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|p| rest.starts_with(**p))
+                .ok_or_else(|| format!("unexpected character '{c}'"))?;
+            tokens.push(Token::Punct(punct));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Read a quoted string starting at `start`, returning it and the index after it.
+fn string_literal(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Ok((s, i + 1)),
+            '\\' => {
+                i += 1;
+                match chars.get(i) {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some(&c) => s.push(c),
+                    None => break,
+                }
+            }
+            c => s.push(c),
+        }
+        i += 1;
+    }
+    Err("unterminated string".to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Ident(String),
+    Member(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Option<Box<Expr>>, String, Vec<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume punctuation if it's next.
+    fn eat(&mut self, punct: &str) -> bool {
+        let matches = matches!(self.peek(), Some(Token::Punct(p)) if *p == punct);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", punct))
+        }
+    }
+
+    fn ternary(&mut self) -> Result<Expr, String> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.ternary()?;
+        self.expect(":")?;
+        let otherwise = self.ternary()?;
+        Ok(Expr::Ternary(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    /// Parse binary operators with precedence of at least `level`.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 5] = [
+            &["||"],
+            &["&&"],
+            &["==", "!=", "<", "<=", ">", ">=", "in"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Punct(p)) => LEVELS[level].iter().find(|op| *op == p),
+                Some(Token::Ident(name)) => LEVELS[level].iter().find(|op| *op == name),
+                _ => None,
+            };
+            let Some(op) = op else {
+                return Ok(left);
+            };
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for op in ["!", "-"] {
+            if self.eat(op) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        self.member()
+    }
+
+    fn member(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let name = match self.next() {
+                    Some(Token::Ident(name)) => name,
+                    _ => return Err("expected field name after '.'".to_string()),
+                };
+                expr = if self.eat("(") {
+                    Expr::Call(Some(Box::new(expr)), name, self.list(")")?)
+                } else {
+                    Expr::Member(Box::new(expr), name)
+                };
+            } else if self.eat("[") {
+                let index = self.ternary()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Parse comma separated expressions until the `close` punctuation.
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>, String> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.ternary()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Int(i)) => Ok(Expr::Literal(Value::from(i))),
+            Some(Token::Double(d)) => Ok(Expr::Literal(Value::from(d))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ if self.eat("(") => Expr::Call(None, name, self.list(")")?),
+                _ => Expr::Ident(name),
+            }),
+            Some(Token::Punct("(")) => {
+                let expr = self.ternary()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Punct("[")) => Ok(Expr::List(self.list("]")?)),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Data and deadline of an evaluation.
+struct Scope<'a> {
+    data: &'a Value,
+    deadline: Option<Instant>,
+}
+
+fn eval(expr: &Expr, scope: &Scope) -> Result<Value, String> {
+    if scope
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        return Err("evaluation deadline exceeded".to_string());
+    }
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::List(items) => items
+            .iter()
+            .map(|item| eval(item, scope))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Expr::Ident(name) => field(scope.data, name),
+        Expr::Member(target, name) => field(&eval(target, scope)?, name),
+        Expr::Index(target, index) => {
+            let target = eval(target, scope)?;
+            match (&target, eval(index, scope)?) {
+                (Value::Array(items), Value::Number(n)) => n
+                    .as_u64()
+                    .and_then(|i| items.get(i as usize))
+                    .cloned()
+                    .ok_or_else(|| format!("index {} out of range", n)),
+                (Value::Object(_), Value::String(key)) => field(&target, &key),
+                (_, index) => Err(format!("can't index {} with {}", target, index)),
+            }
+        }
+        Expr::Call(target, name, args) => call(target.as_deref(), name, args, scope),
+        Expr::Unary(op, operand) => match (*op, eval(operand, scope)?) {
+            ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+            ("-", Value::Number(n)) => arithmetic("-", &Value::from(0), &Value::Number(n)),
+            (op, value) => Err(format!("can't apply '{}' to {}", op, value)),
+        },
+        Expr::Binary(op @ ("&&" | "||"), left, right) => logical(op, left, right, scope),
+        Expr::Binary(op, left, right) => binary(op, &eval(left, scope)?, &eval(right, scope)?),
+        Expr::Ternary(condition, then, otherwise) => match eval(condition, scope)? {
+            Value::Bool(true) => eval(then, scope),
+            Value::Bool(false) => eval(otherwise, scope),
+            value => Err(format!("condition must be a bool, got {}", value)),
+        },
+    }
+}
+
+fn field(value: &Value, name: &str) -> Result<Value, String> {
+    value
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("no such key: {}", name))
+}
+
+/// Evaluate `&&` / `||`, where a deciding side absorbs errors of the other.
+fn logical(op: &str, left: &Expr, right: &Expr, scope: &Scope) -> Result<Value, String> {
+    let decisive = op == "||";
+    let as_bool = |result: Result<Value, String>| match result? {
+        Value::Bool(b) => Ok(b),
+        value => Err(format!("'{}' expects bools, got {}", op, value)),
+    };
+
+    let left = as_bool(eval(left, scope));
+    if left == Ok(decisive) {
+        return Ok(Value::Bool(decisive));
+    }
+    let right = as_bool(eval(right, scope));
+    if right == Ok(decisive) {
+        return Ok(Value::Bool(decisive));
+    }
+    left.and(right).map(|_| Value::Bool(!decisive))
+}
+
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    match op {
+        "==" => Ok(Value::Bool(equals(left, right))),
+        "!=" => Ok(Value::Bool(!equals(left, right))),
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = match (left, right) {
+                (Value::Number(a), Value::Number(b)) => a
+                    .as_f64()
+                    .partial_cmp(&b.as_f64())
+                    .ok_or("NaN comparison")?,
+                (Value::String(a), Value::String(b)) => a.cmp(b),
+                _ => return Err(format!("can't compare {} and {}", left, right)),
+            };
+            Ok(Value::Bool(match op {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        "in" => match right {
+            Value::Array(items) => Ok(Value::Bool(items.iter().any(|i| equals(i, left)))),
+            Value::Object(map) => match left {
+                Value::String(key) => Ok(Value::Bool(map.contains_key(key))),
+                _ => Err(format!("map keys are strings, got {}", left)),
+            },
+            _ => Err(format!("'in' expects a list or map, got {}", right)),
+        },
+        "+" => match (left, right) {
+            (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{a}{b}"))),
+            (Value::Array(a), Value::Array(b)) => Ok(Value::Array([a.clone(), b.clone()].concat())),
+            _ => arithmetic(op, left, right),
+        },
+        _ => arithmetic(op, left, right),
+    }
+}
+
+/// Compare values, treating integers and doubles with the same value as equal.
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn arithmetic(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    let (Value::Number(a), Value::Number(b)) = (left, right) else {
+        return Err(format!("can't apply '{}' to {} and {}", op, left, right));
+    };
+
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        let result = match op {
+            "+" => a.checked_add(b),
+            "-" => a.checked_sub(b),
+            "*" => a.checked_mul(b),
+            "/" => a.checked_div(b),
+            _ => a.checked_rem(b),
+        };
+        return result
+            .map(Value::from)
+            .ok_or_else(|| format!("integer overflow or division by zero in '{}'", op));
+    }
+
+    let (a, b) = (
+        a.as_f64().unwrap_or(f64::NAN),
+        b.as_f64().unwrap_or(f64::NAN),
+    );
+    let result = match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" => a / b,
+        _ => a % b,
+    };
+    Number::from_f64(result)
+        .map(Value::Number)
+        .ok_or_else(|| format!("'{}' doesn't produce a finite number", op))
+}
+
+fn call(target: Option<&Expr>, name: &str, args: &[Expr], scope: &Scope) -> Result<Value, String> {
+    // `has(a.b)` tests field presence instead of evaluating the field
+    if let ("has", None, [Expr::Member(target, field)]) = (name, target, args) {
+        return match eval(target, scope)? {
+            Value::Object(map) => Ok(Value::Bool(map.contains_key(field))),
+            value => Err(format!("has() expects a map, got {}", value)),
+        };
+    }
+
+    let mut values = Vec::with_capacity(args.len() + 1);
+    if let Some(target) = target {
+        values.push(eval(target, scope)?);
+    }
+    for arg in args {
+        values.push(eval(arg, scope)?);
+    }
+
+    match (name, values.as_slice()) {
+        ("size", [value]) => match value {
+            Value::String(s) => Ok(Value::from(s.chars().count())),
+            Value::Array(items) => Ok(Value::from(items.len())),
+            Value::Object(map) => Ok(Value::from(map.len())),
+            _ => Err(format!("size() of {}", value)),
+        },
+        ("int", [value]) => match value {
+            Value::Number(n) if n.is_i64() => Ok(value.clone()),
+            Value::Number(n) => Ok(Value::from(n.as_f64().unwrap_or_default().trunc() as i64)),
+            Value::String(s) => s
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("can't convert '{}' to int", s)),
+            _ => Err(format!("can't convert {} to int", value)),
+        },
+        ("double", [value]) => match value {
+            Value::Number(n) => Ok(Value::from(n.as_f64().unwrap_or_default())),
+            Value::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| format!("can't convert '{}' to double", s)),
+            _ => Err(format!("can't convert {} to double", value)),
+        },
+        ("string", [value]) => match value {
+            Value::String(_) => Ok(value.clone()),
+            _ => Ok(Value::String(value.to_string())),
+        },
+        ("startsWith", [Value::String(s), Value::String(p)]) => Ok(Value::Bool(s.starts_with(p))),
+        ("endsWith", [Value::String(s), Value::String(p)]) => Ok(Value::Bool(s.ends_with(p))),
+        ("contains", [Value::String(s), Value::String(p)]) => Ok(Value::Bool(s.contains(p))),
+        ("matches", [Value::String(s), Value::String(pattern)]) => Regex::new(pattern)
+            .map(|re| Value::Bool(re.is_match(s)))
+            .map_err(|e| e.to_string()),
+        _ => functions::call_function(name, &values).unwrap_or_else(|| {
+            Err(format!(
+                "no matching overload for {}({})",
+                name,
+                values.iter().map(type_name).collect::<Vec<_>>().join(", ")
+            ))
+        }),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "double",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "map",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn data() -> Value {
+        json!({
+            "method": "POST",
+            "page": "2",
+            "user": {"id": 7, "name": "Alice", "roles": ["admin", "dev"]},
+            "items": [{"id": 1, "price": 2.5}, {"id": 2, "price": 10}]
+        })
+    }
+
+    #[rstest]
+    #[case("user.id == 7", json!(true))]
+    #[case("user.id + 1", json!(8))]
+    #[case("7 / 2", json!(3))]
+    #[case("7.0 / 2", json!(3.5))]
+    #[case("-user.id", json!(-7))]
+    #[case("items[1].price > items[0].price", json!(true))]
+    #[case("int(page) >= 2 && method == 'POST'", json!(true))]
+    #[case("'admin' in user.roles", json!(true))]
+    #[case("'email' in user", json!(false))]
+    #[case("has(user.email) || user.name.startsWith(\"Al\")", json!(true))]
+    #[case("size(items) == 2 && user.roles.size() == 2", json!(true))]
+    #[case("user['name'].matches('^A[a-z]+$')", json!(true))]
+    #[case("user.id > 5 ? 'big' : 'small'", json!("big"))]
+    #[case("[1, 2] + [3]", json!([1, 2, 3]))]
+    #[case("user.name + '!'", json!("Alice!"))]
+    #[case("!(user.id == 7.0)", json!(false))]
+    #[case("string(user.id) == '7'", json!(true))]
+    fn test_evaluate(#[case] source: &str, #[case] expected: Value) {
+        let program = Program::compile(source).unwrap();
+        assert_eq!(program.evaluate(&data()), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("missing == 1 && false", json!(false))]
+    #[case("true || missing", json!(true))]
+    fn test_evaluate_absorbs_errors(#[case] source: &str, #[case] expected: Value) {
+        let program = Program::compile(source).unwrap();
+        assert_eq!(program.evaluate(&data()), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("missing == 1", "no such key: missing")]
+    #[case("user.id + 'a'", "can't apply")]
+    #[case("1 / 0", "division by zero")]
+    #[case("items[5]", "out of range")]
+    #[case("size(1)", "size() of 1")]
+    #[case("user.id.startsWith('a')", "no matching overload")]
+    fn test_evaluate_errors(#[case] source: &str, #[case] expected: &str) {
+        let program = Program::compile(source).unwrap();
+        let error = program.evaluate(&data()).unwrap_err();
+        assert!(error.contains(expected), "{error}");
+    }
+
+    #[rstest]
+    fn test_evaluate_until() {
+        let program = Program::compile("true || user.id == 7").unwrap();
+        let deadline = Instant::now();
+        assert_eq!(
+            program.evaluate_until(&data(), Some(deadline)),
+            Err("evaluation deadline exceeded".to_string())
+        );
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        assert_eq!(
+            program.evaluate_until(&data(), Some(deadline)),
+            Ok(json!(true))
+        );
+    }
+
+    #[rstest]
+    #[case("user.id ==")]
+    #[case("(user.id")]
+    #[case("'unterminated")]
+    #[case("user.id # 1")]
+    #[case("a b")]
+    fn test_compile_errors(#[case] source: &str) {
+        assert!(Program::compile(source).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Custom functions callable from expressions.
+//!
+//! Host applications register functions to extend JMESPath and CEL expressions
+//! (and `${}` template placeholders) with their own logic, e.g. to verify request
+//! signatures:
+//!
+//! ```ignore
+//! register_function("verify", Arc::new(|args: &[Value]| Ok(Value::Bool(verify(args)?))));
+//! ```
+//!
+//! ```yaml
+//! payload: "${cel: verify(payload, headers['x-signature'])}"
+//! ```
+//!
+//! Built-in functions of an engine take precedence over custom functions with
+//! the same name. JSONPath expressions can't call custom functions.
+
+use crate::expression::{value_to_variable, variable_to_value};
+use jmespath::functions::Function;
+use jmespath::{Context, ErrorReason, JmespathError, Rcvar, Runtime};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Custom function receiving evaluated arguments.
+pub type CustomFunction = Arc<dyn Fn(&[Value]) -> Result<Value, String> + Send + Sync>;
+
+/// Registered functions by name
+static FUNCTIONS: RwLock<BTreeMap<String, CustomFunction>> = RwLock::new(BTreeMap::new());
+/// JMESPath runtime with the registered functions, rebuilt when names change
+static RUNTIME: RwLock<Option<&'static Runtime>> = RwLock::new(None);
+
+/// Register a function, replacing any function with the same name.
+pub fn register_function(name: &str, function: CustomFunction) {
+    let mut functions = FUNCTIONS.write().unwrap_or_else(PoisonError::into_inner);
+    if functions.insert(name.to_string(), function).is_none() {
+        *RUNTIME.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+/// Unregister a function.
+///
+/// Returns `false` if no function with the name was registered.
+pub fn unregister_function(name: &str) -> bool {
+    let mut functions = FUNCTIONS.write().unwrap_or_else(PoisonError::into_inner);
+    let removed = functions.remove(name).is_some();
+    if removed {
+        *RUNTIME.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+    removed
+}
+
+/// Get names of all registered functions, sorted.
+pub fn function_names() -> Vec<String> {
+    FUNCTIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect()
+}
+
+/// Call a registered function.
+///
+/// Returns `None` if no function with the name is registered.
+pub(crate) fn call_function(name: &str, args: &[Value]) -> Option<Result<Value, String>> {
+    let function = FUNCTIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+        .cloned()?;
+    // The lock is released, so functions may evaluate expressions themselves
+    Some(function(args))
+}
+
+/// Get the JMESPath runtime with built-in and registered functions.
+///
+/// Runtimes are kept for the whole process, since compiled expressions borrow
+/// them. A new one is only built when a function name is added or removed.
+pub(crate) fn jmespath_runtime() -> &'static Runtime {
+    if let Some(runtime) = *RUNTIME.read().unwrap_or_else(PoisonError::into_inner) {
+        return runtime;
+    }
+
+    let functions = FUNCTIONS.read().unwrap_or_else(PoisonError::into_inner);
+    let mut cached = RUNTIME.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(runtime) = *cached {
+        return runtime;
+    }
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    for name in functions.keys() {
+        if runtime.get_function(name).is_none() {
+            runtime.register_function(name, Box::new(Dispatch(name.clone())));
+        }
+    }
+    let runtime: &'static Runtime = Box::leak(Box::new(runtime));
+    *cached = Some(runtime);
+    runtime
+}
+
+/// JMESPath function calling the registered function of the same name.
+struct Dispatch(String);
+
+impl Function for Dispatch {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        let error = |message: String| {
+            JmespathError::from_ctx(
+                ctx,
+                ErrorReason::Parse(format!("{}(): {}", self.0, message)),
+            )
+        };
+        let args = args
+            .iter()
+            .map(variable_to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        match call_function(&self.0, &args) {
+            Some(result) => result.map(|value| value_to_variable(&value)).map_err(error),
+            None => Err(error("function is no longer registered".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::evaluate_expression;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn concat(args: &[Value]) -> Result<Value, String> {
+        args.iter()
+            .map(|arg| {
+                arg.as_str()
+                    .ok_or_else(|| format!("expected string, got {}", arg))
+            })
+            .collect::<Result<String, _>>()
+            .map(Value::String)
+    }
+
+    #[rstest]
+    #[case("test_concat(payload.a, 'b')", Ok(json!("ab")))]
+    #[case("cel: test_concat(payload.a, 'b', 'c')", Ok(json!("abc")))]
+    #[case("cel: test_concat(payload.a, 1)", Err("expected string, got 1"))]
+    #[case("test_concat(payload.a, `1`)", Err("expected string, got 1"))]
+    #[case("length(payload.a)", Ok(json!(1)))]
+    fn test_custom_function(#[case] expression: &str, #[case] expected: Result<Value, &str>) {
+        register_function("test_concat", Arc::new(concat));
+        register_function("length", Arc::new(|_: &[Value]| Ok(json!("shadowed"))));
+
+        let result = evaluate_expression(expression, &json!({"payload": {"a": "a"}}));
+        match expected {
+            Ok(value) => assert_eq!(result, Ok(value)),
+            Err(message) => {
+                let error = result.unwrap_err().to_string();
+                assert!(error.contains(message), "{error}");
+            }
+        }
+        assert!(function_names().contains(&"test_concat".to_string()));
+    }
+
+    #[rstest]
+    fn test_unregister_function() {
+        register_function("test_removed", Arc::new(|_: &[Value]| Ok(json!(1))));
+        assert_eq!(
+            evaluate_expression("test_removed()", &json!({})),
+            Ok(json!(1))
+        );
+
+        assert!(unregister_function("test_removed"));
+        assert!(!unregister_function("test_removed"));
+        assert!(evaluate_expression("test_removed()", &json!({})).is_err());
+        assert!(evaluate_expression("cel: test_removed()", &json!({})).is_err());
+    }
+}
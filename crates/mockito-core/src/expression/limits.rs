@@ -0,0 +1,177 @@
+//! Limits of expression and template evaluation.
+//!
+//! Protects the matcher from pathological expressions and huge payloads:
+//!
+//! ```yaml
+//! # mockito.config.yaml
+//! limits:
+//!   max_depth: 32
+//!   max_size: 1048576
+//!   timeout_ms: 50
+//! ```
+//!
+//! Depth and size are checked before evaluating, so oversized data is never
+//! converted or walked by an engine. The time limit is enforced during CEL
+//! evaluation, which checks the deadline before each subexpression, and
+//! between the templates of a variant. JMESPath and JSONPath evaluation and a
+//! single template rendering can't be interrupted, so they fail once they
+//! return late instead of being cut short.
+
+use crate::clock::Instant;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Limits of a single expression evaluation or variant rendering, unlimited if
+/// not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct EvaluationLimits {
+    /// Maximum nesting depth of arrays and objects in evaluated data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Maximum size in bytes of evaluated data and rendered templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+    /// Maximum duration in milliseconds of an evaluation. CEL expressions and
+    /// variants are stopped at the deadline, JMESPath and JSONPath expressions
+    /// and single templates are only checked once they return.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl EvaluationLimits {
+    /// Check that data is within the depth and size limits.
+    ///
+    /// Size is the approximate length of the data encoded as JSON.
+    pub fn check_data(&self, data: &Value) -> Result<(), String> {
+        if self.max_depth.is_none() && self.max_size.is_none() {
+            return Ok(());
+        }
+        let mut size = 0;
+        self.check_value(data, 1, &mut size)
+    }
+
+    fn check_value(&self, value: &Value, depth: usize, size: &mut usize) -> Result<(), String> {
+        if let (Some(max_depth), Value::Array(_) | Value::Object(_)) = (self.max_depth, value) {
+            if depth > max_depth {
+                return Err(format!("data is nested deeper than {} levels", max_depth));
+            }
+        }
+
+        *size += match value {
+            Value::Null | Value::Bool(_) => 5,
+            Value::Number(_) => 8,
+            Value::String(s) => s.len() + 2,
+            Value::Array(items) => items.len() + 2,
+            Value::Object(fields) => fields.keys().map(|k| k.len() + 4).sum::<usize>() + 2,
+        };
+        self.check_size(*size, "data")?;
+
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .try_for_each(|item| self.check_value(item, depth + 1, size)),
+            Value::Object(fields) => fields
+                .values()
+                .try_for_each(|field| self.check_value(field, depth + 1, size)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a size in bytes against the size limit.
+    pub fn check_size(&self, size: usize, what: &str) -> Result<(), String> {
+        match self.max_size {
+            Some(max_size) if size > max_size => {
+                Err(format!("{} is larger than {} bytes", what, max_size))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the time elapsed since `start` against the time limit.
+    pub fn check_elapsed(&self, start: Instant) -> Result<(), String> {
+        match self.timeout_ms {
+            Some(timeout_ms) if start.elapsed() > Duration::from_millis(timeout_ms) => {
+                Err(format!("evaluation took longer than {} ms", timeout_ms))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Deadline of an evaluation started at `start`, if there's a time limit.
+    pub fn deadline(&self, start: Instant) -> Option<Instant> {
+        self.timeout_ms
+            .map(|timeout_ms| start + Duration::from_millis(timeout_ms))
+    }
+
+    /// Run an evaluation on data within the limits.
+    ///
+    /// `evaluate` gets the deadline to stop at. An evaluation that ignores it is
+    /// still failed if it returns late.
+    pub fn evaluate<T>(
+        &self,
+        data: &Value,
+        evaluate: impl FnOnce(Option<Instant>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.check_data(data)?;
+        let start = Instant::now();
+        let result = evaluate(self.deadline(start));
+        self.check_elapsed(start)?;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(EvaluationLimits::default(), Ok(()))]
+    #[case(EvaluationLimits { max_depth: Some(3), ..Default::default() }, Ok(()))]
+    #[case(EvaluationLimits { max_depth: Some(2), ..Default::default() }, Err("nested deeper than 2"))]
+    #[case(EvaluationLimits { max_size: Some(100), ..Default::default() }, Ok(()))]
+    #[case(EvaluationLimits { max_size: Some(20), ..Default::default() }, Err("larger than 20 bytes"))]
+    fn test_check_data(#[case] limits: EvaluationLimits, #[case] expected: Result<(), &str>) {
+        let data = json!({"items": [{"id": 1}, {"name": "abcdefgh"}]});
+        let result = limits.check_data(&data);
+        match expected {
+            Ok(()) => assert_eq!(result, Ok(())),
+            Err(message) => assert!(result.unwrap_err().contains(message)),
+        }
+    }
+
+    #[rstest]
+    fn test_evaluate_timeout() {
+        let limits = EvaluationLimits {
+            timeout_ms: Some(1),
+            ..Default::default()
+        };
+        let result = limits.evaluate(&json!({}), |_| {
+            std::thread::sleep(Duration::from_millis(5));
+            Ok(())
+        });
+        assert_eq!(result, Err("evaluation took longer than 1 ms".to_string()));
+        assert_eq!(limits.evaluate(&json!({}), |_| Ok(1)), Ok(1));
+    }
+
+    #[rstest]
+    fn test_limits_deserialize() {
+        let limits: EvaluationLimits =
+            serde_yaml::from_str("max_depth: 8\ntimeout_ms: 50").unwrap();
+        assert_eq!(
+            limits,
+            EvaluationLimits {
+                max_depth: Some(8),
+                max_size: None,
+                timeout_ms: Some(50),
+            }
+        );
+        assert!(serde_yaml::from_str::<EvaluationLimits>("timeout: 50").is_err());
+    }
+}
@@ -0,0 +1,561 @@
+//! Expression utilities for matching and response processing.
+//!
+//! Expressions are JMESPath by default. Other engines are selected per expression
+//! with a prefix:
+//!
+//! ```yaml
+//! payload: "${jmespath: payload.items[0].id == `5`}"
+//! payload: "${jsonpath: $.payload.items[?@.id == 5]}"
+//! payload: "${cel: size(payload.items) > 0 && payload.items[0].id == 5}"
+//! ```
+//!
+//! All engines compile into a [`Matcher`]. Preset expressions are evaluated
+//! against the request context, see [`crate::mocks::context`].
+
+pub mod cel;
+pub mod functions;
+mod limits;
+
+pub use limits::EvaluationLimits;
+
+use crate::clock::Instant;
+use crate::matching::{hashmap_to_value, parse_query_string};
+use jmespath::Variable;
+use serde_json::{json, Value};
+use serde_json_path::JsonPath;
+use std::rc::Rc;
+use thiserror::Error;
+
+/// Expression failure, as opposed to an expression evaluating to false.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExpressionError {
+    /// Expression doesn't compile
+    #[error("Invalid expression '{expression}': {message}")]
+    Invalid { expression: String, message: String },
+    /// Expression compiled but failed on the data
+    #[error("Failed to evaluate '{expression}': {message}")]
+    Evaluation { expression: String, message: String },
+}
+
+impl ExpressionError {
+    fn invalid(expression: &str, message: impl ToString) -> Self {
+        Self::Invalid {
+            expression: expression.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn evaluation(expression: &str, message: impl ToString) -> Self {
+        Self::Evaluation {
+            expression: expression.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Expression engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// JMESPath (default)
+    JmesPath,
+    /// JSONPath (RFC 9535), matching if any selected node is truthy
+    JsonPath,
+    /// Common Expression Language subset, see [`cel`]
+    Cel,
+}
+
+impl Engine {
+    const ALL: [Engine; 3] = [Engine::JmesPath, Engine::JsonPath, Engine::Cel];
+
+    /// Prefix selecting the engine in expressions (e.g. `cel`)
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Engine::JmesPath => "jmespath",
+            Engine::JsonPath => "jsonpath",
+            Engine::Cel => "cel",
+        }
+    }
+}
+
+/// Split an expression into its engine and source without the engine prefix.
+pub fn parse_engine(expression: &str) -> (Engine, &str) {
+    let trimmed = expression.trim_start();
+    Engine::ALL
+        .into_iter()
+        .find_map(|engine| {
+            let source = trimmed.strip_prefix(engine.prefix())?.strip_prefix(':')?;
+            Some((engine, source.trim()))
+        })
+        .unwrap_or((Engine::JmesPath, expression))
+}
+
+/// Compiled expression evaluated against request data.
+pub trait Matcher {
+    /// Evaluate the expression on data.
+    fn evaluate(&self, data: &Value) -> Result<Value, String>;
+
+    /// Check if the expression evaluates to a truthy value on data.
+    fn matches(&self, data: &Value) -> Result<bool, String> {
+        self.evaluate(data)
+            .map(|value| jmespath_result_to_bool(&value))
+    }
+
+    /// Check if the expression evaluates to a truthy value, failing once
+    /// `deadline` has passed.
+    ///
+    /// Engines that can't be interrupted ignore the deadline.
+    fn matches_until(&self, data: &Value, _deadline: Option<Instant>) -> Result<bool, String> {
+        self.matches(data)
+    }
+}
+
+impl Matcher for jmespath::Expression<'_> {
+    fn evaluate(&self, data: &Value) -> Result<Value, String> {
+        let result = self
+            .search(value_to_variable(data))
+            .map_err(|e| e.to_string())?;
+        variable_to_value(&result)
+    }
+}
+
+impl Matcher for JsonPath {
+    /// Evaluates to the array of selected nodes.
+    fn evaluate(&self, data: &Value) -> Result<Value, String> {
+        Ok(Value::Array(
+            self.query(data).all().into_iter().cloned().collect(),
+        ))
+    }
+
+    fn matches(&self, data: &Value) -> Result<bool, String> {
+        Ok(self
+            .query(data)
+            .all()
+            .into_iter()
+            .any(jmespath_result_to_bool))
+    }
+}
+
+impl Matcher for cel::Program {
+    fn evaluate(&self, data: &Value) -> Result<Value, String> {
+        cel::Program::evaluate(self, data)
+    }
+
+    fn matches_until(&self, data: &Value, deadline: Option<Instant>) -> Result<bool, String> {
+        self.evaluate_until(data, deadline)
+            .map(|value| jmespath_result_to_bool(&value))
+    }
+}
+
+/// Compile an expression with the engine selected by its prefix.
+pub fn compile(expression: &str) -> Result<Box<dyn Matcher>, ExpressionError> {
+    let (engine, source) = parse_engine(expression);
+    let invalid = |e: String| ExpressionError::invalid(expression, e);
+    Ok(match engine {
+        Engine::JmesPath => Box::new(
+            functions::jmespath_runtime()
+                .compile(source)
+                .map_err(|e| invalid(e.to_string()))?,
+        ),
+        Engine::JsonPath => Box::new(JsonPath::parse(source).map_err(|e| invalid(e.to_string()))?),
+        Engine::Cel => Box::new(cel::Program::compile(source).map_err(invalid)?),
+    })
+}
+
+/// Match data using an expression of any engine.
+pub fn match_expression(expression: &str, data: &Value) -> Result<bool, ExpressionError> {
+    match_expression_with(expression, data, &EvaluationLimits::default())
+}
+
+/// Match data using an expression of any engine within evaluation limits.
+///
+/// Returns an evaluation error if the data or evaluation exceeds the limits.
+pub fn match_expression_with(
+    expression: &str,
+    data: &Value,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionError> {
+    let matcher = compile(expression)?;
+    limits
+        .evaluate(data, |deadline| matcher.matches_until(data, deadline))
+        .map_err(|e| ExpressionError::evaluation(expression, e))
+}
+
+/// Evaluate an expression of any engine on data.
+pub fn evaluate_expression(expression: &str, data: &Value) -> Result<Value, ExpressionError> {
+    compile(expression)?
+        .evaluate(data)
+        .map_err(|e| ExpressionError::evaluation(expression, e))
+}
+
+/// Evaluate an expression against a sample request, to try it out while
+/// authoring fixtures.
+///
+/// The sample has the shape of the request context (see [`crate::mocks::context`]).
+/// Missing `method`, `url` and `payload` default to `null`, missing `params` and
+/// `headers` to empty objects, and a missing `query` is parsed from the `url`.
+/// The expression may be wrapped in `${}` as in config files.
+///
+/// Returns error if the expression is invalid, fails on the sample or the sample
+/// isn't an object.
+pub fn test(expression: &str, request: &Value) -> Result<Value, ExpressionError> {
+    let expression = if is_expression(expression) {
+        &expression[2..expression.len() - 1]
+    } else {
+        expression
+    };
+    let Value::Object(sample) = request else {
+        return Err(ExpressionError::evaluation(
+            expression,
+            "sample request must be an object",
+        ));
+    };
+
+    let mut context = sample.clone();
+    for key in ["method", "url", "payload"] {
+        context.entry(key).or_insert(Value::Null);
+    }
+    for key in ["params", "headers"] {
+        context.entry(key).or_insert_with(|| json!({}));
+    }
+    if !context.contains_key("query") {
+        let query = context["url"]
+            .as_str()
+            .and_then(|url| url.split_once('?'))
+            .map(|(_, query)| hashmap_to_value(&parse_query_string(query)))
+            .unwrap_or_else(|| json!({}));
+        context.insert("query".to_owned(), query);
+    }
+
+    evaluate_expression(expression, &Value::Object(context))
+}
+
+/// Check if a string is an expression (starts with ${ and ends with })
+pub fn is_expression(s: &str) -> bool {
+    s.starts_with("${") && s.ends_with('}')
+}
+
+/// Convert serde_json::Value to jmespath::Variable.
+pub fn value_to_variable(value: &Value) -> Rc<Variable> {
+    match value {
+        Value::Null => Rc::new(Variable::Null),
+        Value::Bool(b) => Rc::new(Variable::Bool(*b)),
+        Value::Number(n) => Rc::new(Variable::Number(n.clone())),
+        Value::String(s) => Rc::new(Variable::String(s.clone())),
+        Value::Array(arr) => {
+            let vars: Vec<Rc<Variable>> = arr.iter().map(value_to_variable).collect();
+            Rc::new(Variable::Array(vars))
+        }
+        Value::Object(obj) => {
+            let map: std::collections::BTreeMap<String, Rc<Variable>> = obj
+                .iter()
+                .map(|(k, v)| (k.clone(), value_to_variable(v)))
+                .collect();
+            Rc::new(Variable::Object(map))
+        }
+    }
+}
+
+/// Convert jmespath::Variable to serde_json::Value.
+pub fn variable_to_value(var: &Rc<Variable>) -> Result<Value, String> {
+    match var.as_ref() {
+        Variable::Null => Ok(Value::Null),
+        Variable::Bool(b) => Ok(Value::Bool(*b)),
+        Variable::Number(n) => Ok(Value::Number(n.clone())),
+        Variable::String(s) => Ok(Value::String(s.clone())),
+        Variable::Array(arr) => {
+            let values: Result<Vec<Value>, String> = arr.iter().map(variable_to_value).collect();
+            Ok(Value::Array(values?))
+        }
+        Variable::Object(obj) => {
+            let map: Result<serde_json::Map<String, Value>, String> = obj
+                .iter()
+                .map(|(k, v)| variable_to_value(v).map(|val| (k.clone(), val)))
+                .collect();
+            Ok(Value::Object(map?))
+        }
+        Variable::Expref(_) => Err("Expression references not supported".to_string()),
+    }
+}
+
+/// Convert JMESPath expression result to boolean.
+pub fn jmespath_result_to_bool(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Null => false,
+        Value::Number(n) => n.as_f64().map(|f| f != 0.0).unwrap_or(false),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// Match data using JMESPath expression.
+///
+/// Returns `Ok(false)` if the expression evaluates to a falsy value, and an error
+/// if it doesn't compile or fails on the data.
+pub fn match_with_jmespath(expression: &str, data: &Value) -> Result<bool, ExpressionError> {
+    evaluate_jmespath(expression, data).map(|value| jmespath_result_to_bool(&value))
+}
+
+/// Evaluate JMESPath expression on data and return the result as Value.
+pub fn evaluate_jmespath(expression: &str, data: &Value) -> Result<Value, ExpressionError> {
+    // Parse JMESPath expression
+    let expr = functions::jmespath_runtime()
+        .compile(expression)
+        .map_err(|e| ExpressionError::invalid(expression, e))?;
+
+    // Convert data to jmespath Variable
+    let data_var = value_to_variable(data);
+
+    // Execute expression on data
+    let result = expr
+        .search(&data_var)
+        .map_err(|e| ExpressionError::evaluation(expression, e))?;
+
+    // Convert jmespath Variable to serde_json::Value
+    variable_to_value(&result).map_err(|e| ExpressionError::evaluation(expression, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[rstest]
+    fn test_value_to_variable_null() {
+        let value = json!(null);
+        let var = value_to_variable(&value);
+        assert!(matches!(*var, Variable::Null));
+    }
+
+    #[rstest]
+    fn test_value_to_variable_bool() {
+        let value = json!(true);
+        let var = value_to_variable(&value);
+        assert!(matches!(*var, Variable::Bool(true)));
+    }
+
+    #[rstest]
+    fn test_variable_to_value_all_types() {
+        // Test Null
+        let var_null = Rc::new(Variable::Null);
+        assert_eq!(variable_to_value(&var_null).unwrap(), json!(null));
+
+        // Test Bool
+        let var_bool = Rc::new(Variable::Bool(true));
+        assert_eq!(variable_to_value(&var_bool).unwrap(), json!(true));
+
+        // Test Number
+        let var_num = Rc::new(Variable::Number(serde_json::Number::from(123)));
+        assert_eq!(variable_to_value(&var_num).unwrap(), json!(123));
+
+        // Test String
+        let var_str = Rc::new(Variable::String("test".to_string()));
+        assert_eq!(variable_to_value(&var_str).unwrap(), json!("test"));
+
+        // Test Array
+        let var_arr = Rc::new(Variable::Array(vec![
+            Rc::new(Variable::Number(serde_json::Number::from(1))),
+            Rc::new(Variable::Number(serde_json::Number::from(2))),
+        ]));
+        assert_eq!(variable_to_value(&var_arr).unwrap(), json!([1, 2]));
+
+        // Test Object
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(
+            "key".to_string(),
+            Rc::new(Variable::String("value".to_string())),
+        );
+        let var_obj = Rc::new(Variable::Object(map));
+        assert_eq!(
+            variable_to_value(&var_obj).unwrap(),
+            json!({"key": "value"})
+        );
+    }
+
+    #[rstest]
+    #[case(Value::Bool(true), true)]
+    #[case(Value::Bool(false), false)]
+    #[case(Value::Null, false)]
+    #[case(json!(0), false)]
+    #[case(json!(1), true)]
+    #[case(json!(-1), true)]
+    #[case(json!(""), false)]
+    #[case(json!("test"), true)]
+    #[case(json!([]), false)]
+    #[case(json!([1, 2]), true)]
+    #[case(json!({}), false)]
+    #[case(json!({"key": "value"}), true)]
+    fn test_jmespath_result_to_bool(#[case] value: Value, #[case] expected: bool) {
+        assert_eq!(jmespath_result_to_bool(&value), expected);
+    }
+
+    /// Kind of an expression result, to compare without error messages.
+    fn kind<T>(result: Result<T, ExpressionError>) -> Result<T, &'static str> {
+        result.map_err(|e| match e {
+            ExpressionError::Invalid { .. } => "invalid",
+            ExpressionError::Evaluation { .. } => "evaluation",
+        })
+    }
+
+    #[rstest]
+    #[case("value > `3`", Ok(true))]
+    #[case("value > `10`", Ok(false))]
+    #[case("value >", Err("invalid"))]
+    #[case("abs(name)", Err("evaluation"))]
+    fn test_match_with_jmespath(
+        #[case] expression: &str,
+        #[case] expected: Result<bool, &'static str>,
+    ) {
+        let data = json!({"value": 5, "name": "five"});
+        assert_eq!(kind(match_with_jmespath(expression, &data)), expected);
+    }
+
+    #[rstest]
+    fn test_expression_error_display() {
+        let error = match_with_jmespath("[invalid", &json!({})).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Invalid expression '[invalid': "));
+    }
+
+    #[rstest]
+    #[case("value", Ok(json!(5)))]
+    #[case("items[0].id", Ok(json!(1)))]
+    #[case("items[*].id", Ok(json!([1, 2, 3])))]
+    #[case("nonexistent", Ok(json!(null)))]
+    #[case("[invalid", Err("invalid"))]
+    #[case("length(value)", Err("evaluation"))]
+    fn test_evaluate_jmespath(
+        #[case] expression: &str,
+        #[case] expected: Result<Value, &'static str>,
+    ) {
+        let data = json!({
+            "value": 5,
+            "items": [
+                {"id": 1},
+                {"id": 2},
+                {"id": 3}
+            ]
+        });
+        assert_eq!(kind(evaluate_jmespath(expression, &data)), expected);
+    }
+
+    #[rstest]
+    #[case("value > `3`", Engine::JmesPath, "value > `3`")]
+    #[case("jmespath: value", Engine::JmesPath, "value")]
+    #[case("jsonpath:$.items[*]", Engine::JsonPath, "$.items[*]")]
+    #[case(" cel: value > 3 ", Engine::Cel, "value > 3")]
+    #[case("celery", Engine::JmesPath, "celery")]
+    fn test_parse_engine(#[case] expression: &str, #[case] engine: Engine, #[case] source: &str) {
+        assert_eq!(parse_engine(expression), (engine, source));
+    }
+
+    #[rstest]
+    #[case("value > `3`", Ok(true))]
+    #[case("jmespath: items[?id == `2`]", Ok(true))]
+    #[case("jsonpath: $.items[?@.id == 2]", Ok(true))]
+    #[case("jsonpath: $.items[?@.id == 9]", Ok(false))]
+    #[case("jsonpath: $.flag", Ok(false))]
+    #[case("cel: value > 3 && items[1].id == 2", Ok(true))]
+    #[case("cel: missing > 3", Err("evaluation"))]
+    #[case("cel: value >", Err("invalid"))]
+    fn test_match_expression(
+        #[case] expression: &str,
+        #[case] expected: Result<bool, &'static str>,
+    ) {
+        let data = json!({"value": 5, "flag": false, "items": [{"id": 1}, {"id": 2}]});
+        assert_eq!(kind(match_expression(expression, &data)), expected);
+    }
+
+    #[rstest]
+    #[case("${query.page}", json!({"url": "/users?page=2"}), Ok(json!("2")))]
+    #[case("query.page", json!({"url": "/users?page=2", "query": {"page": "3"}}), Ok(json!("3")))]
+    #[case("method == 'POST' && payload.id == `5`", json!({"method": "POST", "payload": {"id": 5}}), Ok(json!(true)))]
+    #[case("cel: size(headers) == 0 && size(params) == 0", json!({}), Ok(json!(true)))]
+    #[case("payload.id", json!({}), Ok(json!(null)))]
+    #[case("${payload.[}", json!({}), Err("invalid"))]
+    #[case("payload", json!([]), Err("evaluation"))]
+    fn test_test(
+        #[case] expression: &str,
+        #[case] request: Value,
+        #[case] expected: Result<Value, &'static str>,
+    ) {
+        assert_eq!(kind(test(expression, &request)), expected);
+    }
+
+    #[rstest]
+    fn test_match_expression_with_limits() {
+        let data = json!({"items": [{"id": 1}, {"id": 2}]});
+        let limits = EvaluationLimits {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            match_expression_with("items[0].id == `1`", &data, &limits),
+            Err(ExpressionError::Evaluation { message, .. }) if message.contains("nested deeper")
+        ));
+        assert_eq!(
+            match_expression_with("items[0].id == `1`", &data, &EvaluationLimits::default()),
+            Ok(true)
+        );
+    }
+
+    #[rstest]
+    fn test_match_expression_with_timeout() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        functions::register_function(
+            "test_slow",
+            Arc::new(|_: &[Value]| {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(2));
+                Ok(json!(true))
+            }),
+        );
+        let expression = format!("cel: size([{}]) > 0", vec!["test_slow()"; 500].join(", "));
+        let limits = EvaluationLimits {
+            timeout_ms: Some(10),
+            ..Default::default()
+        };
+
+        let result = match_expression_with(&expression, &json!({}), &limits);
+        assert!(matches!(
+            result,
+            Err(ExpressionError::Evaluation { message, .. }) if message.contains("longer than 10 ms")
+        ));
+        // Cut off at the deadline instead of running all 500 calls
+        assert!(CALLS.load(Ordering::SeqCst) < 100);
+    }
+
+    #[rstest]
+    #[case("items[0].id", Ok(json!(1)))]
+    #[case("jsonpath: $.items[*].id", Ok(json!([1, 2])))]
+    #[case("cel: items[0].id + 1", Ok(json!(2)))]
+    #[case("jsonpath: $[", Err("invalid"))]
+    fn test_evaluate_expression(
+        #[case] expression: &str,
+        #[case] expected: Result<Value, &'static str>,
+    ) {
+        let data = json!({"items": [{"id": 1}, {"id": 2}]});
+        assert_eq!(kind(evaluate_expression(expression, &data)), expected);
+    }
+
+    #[rstest]
+    #[case("${expression}", true)]
+    #[case("${query.page == '1'}", true)]
+    #[case("${headers.myheader == 1}", true)]
+    #[case("${payload.items[0].id == 5}", true)]
+    #[case("expression", false)]
+    #[case("${expression", false)]
+    #[case("expression}", false)]
+    #[case("${}", true)]
+    #[case("", false)]
+    #[case("${expression} extra", false)]
+    fn test_is_expression(#[case] s: &str, #[case] expected: bool) {
+        assert_eq!(is_expression(s), expected);
+    }
+}
@@ -1,7 +1,49 @@
 //! Mockito core library
+//!
+//! # Logging
+//!
+//! The library reports through the `tracing` crate, with module paths as event
+//! targets, so levels can be set per module:
+//! - `mockito_core::config`: loaded projects (`info`), validation issues
+//!   (`error`, `warn`), remote files served from the cache (`warn`) and applied
+//!   file changes (`debug`)
+//! - `mockito_core::mocks::table`: preset expressions that don't compile and
+//!   variants failing to render (`warn`), request matching (`debug`)
+//! - `mockito_core::mocks::manager`, `mockito_core::mocks::controller`: collection
+//!   resolution and controller state changes (`debug`)
+//!
+//! Nothing is recorded until the application installs a subscriber, e.g.:
+//!
+//! ```ignore
+//! tracing_subscriber::fmt()
+//!     .with_env_filter("mockito_core=info,mockito_core::mocks::table=debug")
+//!     .init();
+//! ```
+//!
+//! Without the `tracing` feature, events are still reported, but collection and
+//! route resolution aren't wrapped in spans.
+//!
+//! # Features
+//!
+//! All features are enabled by default:
+//! - `csv`: CSV datasets
+//! - `handlebars`, `liquid`: template engines, `jwt`: the Handlebars `jwt`
+//!   helper and [`template::jwt`]
+//! - `json5`, `toml`: config file formats
+//! - `json-schema`: [`config::json_schema`]
+//! - `parallel`: parsing config files on several threads
+//! - `proto`: gRPC descriptor sets and `.proto` files
+//! - `remote`: remote config files
+//! - `tracing`: spans of collection and route resolution
+//!
+//! Config using a disabled feature fails to load or render with an error naming
+//! the feature.
 
+pub mod clock;
 pub mod config;
 pub mod expression;
 pub mod matching;
 pub mod mocks;
+pub mod template;
 pub mod types;
+pub mod vars;
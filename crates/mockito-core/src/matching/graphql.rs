@@ -0,0 +1,331 @@
+//! GraphQL request parsing and operation matching.
+//!
+//! Requests carry a GraphQL document either as a JSON payload
+//! (`{"query": ..., "operationName": ..., "variables": {...}}`), as query
+//! parameters of a `GET` request (with `variables` holding JSON), or inside a
+//! `graphql-ws` `subscribe`/`start` message sent over a WebSocket.
+//!
+//! Documents are only scanned for their top-level operations, selections are
+//! not parsed.
+
+use crate::types::graphql::{GraphqlOperation, OperationType};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// GraphQL operation executed by a request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphqlRequest {
+    #[serde(rename = "type")]
+    pub operation_type: OperationType,
+    /// Operation name, `None` for anonymous operations
+    pub name: Option<String>,
+    /// Operation variables, an empty object if the request has none
+    pub variables: Value,
+}
+
+/// Get the GraphQL operation executed by a request, from its payload or its
+/// query parameters.
+///
+/// Returns `None` if the request has no document, or if it doesn't select
+/// exactly one of the operations it defines.
+pub fn graphql_request(
+    payload: Option<&Value>,
+    query: &HashMap<String, String>,
+) -> Option<GraphqlRequest> {
+    if let Some(body) = payload.and_then(graphql_body) {
+        let document = body.get("query")?.as_str()?;
+        let operation_name = body.get("operationName").and_then(Value::as_str);
+        let variables = body.get("variables").cloned();
+        return select_operation(document, operation_name, variables);
+    }
+
+    let document = query.get("query")?;
+    let operation_name = query
+        .get("operationName")
+        .map(String::as_str)
+        .filter(|name| !name.is_empty());
+    let variables = query
+        .get("variables")
+        .and_then(|variables| serde_json::from_str(variables).ok());
+    select_operation(document, operation_name, variables)
+}
+
+/// Check if a request executes an operation.
+///
+/// Returns `false` if the request isn't a GraphQL request.
+pub fn operation_matches(expected: &GraphqlOperation, actual: Option<&GraphqlRequest>) -> bool {
+    let Some(actual) = actual else {
+        return false;
+    };
+    expected
+        .operation_type
+        .is_none_or(|operation_type| operation_type == actual.operation_type)
+        && expected
+            .name
+            .as_ref()
+            .is_none_or(|name| actual.name.as_ref() == Some(name))
+}
+
+/// Get the GraphQL request object of a payload, unwrapping `graphql-ws` messages.
+fn graphql_body(payload: &Value) -> Option<&Map<String, Value>> {
+    let body = payload.as_object()?;
+    if body.get("query").is_some_and(Value::is_string) {
+        return Some(body);
+    }
+    match body.get("type").and_then(Value::as_str) {
+        Some("subscribe" | "start") => body.get("payload")?.as_object(),
+        _ => None,
+    }
+}
+
+/// Select the executed operation of a document.
+///
+/// Without an operation name, the document must define a single operation.
+fn select_operation(
+    document: &str,
+    operation_name: Option<&str>,
+    variables: Option<Value>,
+) -> Option<GraphqlRequest> {
+    let operations = operations(document);
+    let (operation_type, name) = match operation_name {
+        Some(operation_name) => operations
+            .into_iter()
+            .find(|(_, name)| name.as_deref() == Some(operation_name))?,
+        None if operations.len() == 1 => operations.into_iter().next()?,
+        None => return None,
+    };
+    let variables = match variables {
+        Some(variables @ Value::Object(_)) => variables,
+        _ => Value::Object(Map::new()),
+    };
+    Some(GraphqlRequest {
+        operation_type,
+        name,
+        variables,
+    })
+}
+
+/// Lexical token of a document, as far as scanning operations needs.
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    Punctuator(char),
+    /// String, number or other value
+    Other,
+}
+
+/// Get the type and name of the operations defined at the top level of a
+/// document, in order.
+///
+/// A selection set without keyword is an anonymous query. Fragment definitions
+/// are skipped.
+fn operations(document: &str) -> Vec<(OperationType, Option<String>)> {
+    let mut operations = Vec::new();
+    // Nesting of braces, parentheses and brackets
+    let mut depth = 0usize;
+    // Operation whose keyword was seen, until its selection set opens
+    let mut pending: Option<(OperationType, Option<String>)> = None;
+    // Whether a name following the operation keyword names the operation
+    let mut expects_name = false;
+    let mut in_fragment = false;
+
+    for token in tokens(document) {
+        let names_operation = expects_name;
+        expects_name = false;
+        match token {
+            Token::Punctuator('{') if depth == 0 => {
+                match pending.take() {
+                    Some(operation) => operations.push(operation),
+                    None if !in_fragment => operations.push((OperationType::Query, None)),
+                    None => {}
+                }
+                in_fragment = false;
+                depth += 1;
+            }
+            Token::Punctuator('{' | '(' | '[') => depth += 1,
+            Token::Punctuator('}' | ')' | ']') => depth = depth.saturating_sub(1),
+            Token::Name(name) if depth == 0 => match &mut pending {
+                Some((_, operation_name)) if names_operation => {
+                    *operation_name = Some(name.to_string());
+                }
+                Some(_) => {}
+                None if in_fragment => {}
+                None => {
+                    let operation_type = match name {
+                        "query" => OperationType::Query,
+                        "mutation" => OperationType::Mutation,
+                        "subscription" => OperationType::Subscription,
+                        "fragment" => {
+                            in_fragment = true;
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    pending = Some((operation_type, None));
+                    expects_name = true;
+                }
+            },
+            _ => {}
+        }
+    }
+    operations
+}
+
+/// Split a document into tokens, skipping whitespace, commas and comments.
+fn tokens(document: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = document;
+    std::iter::from_fn(move || loop {
+        let mut chars = rest.chars();
+        let c = chars.next()?;
+        match c {
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => rest = chars.as_str(),
+            '#' => rest = rest.find('\n').map_or("", |end| &rest[end..]),
+            '"' if rest.starts_with("\"\"\"") => {
+                rest = skip_block_string(&rest[3..]);
+                return Some(Token::Other);
+            }
+            '"' => {
+                rest = skip_string(chars.as_str());
+                return Some(Token::Other);
+            }
+            c if c == '_' || c.is_ascii_alphabetic() => {
+                let end = rest
+                    .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+                let name = &rest[..end];
+                rest = &rest[end..];
+                return Some(Token::Name(name));
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' => {
+                rest = chars.as_str();
+                return Some(Token::Punctuator(c));
+            }
+            _ => {
+                rest = chars.as_str();
+                return Some(Token::Other);
+            }
+        }
+    })
+}
+
+/// Skip the rest of a string after its opening quote.
+///
+/// Unterminated strings end at the end of the line.
+fn skip_string(rest: &str) -> &str {
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' | '\n' => return &rest[i + 1..],
+            _ => {}
+        }
+    }
+    ""
+}
+
+/// Skip the rest of a block string after its opening quotes.
+fn skip_block_string(rest: &str) -> &str {
+    let mut offset = 0;
+    while let Some(i) = rest[offset..].find("\"\"\"") {
+        let end = offset + i;
+        if rest[..end].ends_with('\\') {
+            offset = end + 3;
+            continue;
+        }
+        return &rest[end + 3..];
+    }
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("{ user { id } }", vec![(OperationType::Query, None)])]
+    #[case(
+        "query GetUser($id: ID! = \"{\") @cached { user(id: $id) { id } }",
+        vec![(OperationType::Query, Some("GetUser"))]
+    )]
+    #[case(
+        "# mutation Commented\nmutation { a } subscription OnEvent { b }",
+        vec![(OperationType::Mutation, None), (OperationType::Subscription, Some("OnEvent"))]
+    )]
+    #[case(
+        "fragment Fields on User { id } query Q($f: In = {a: [1]}) { ...Fields }",
+        vec![(OperationType::Query, Some("Q"))]
+    )]
+    #[case(
+        "query @live { a(text: \"\"\"}\\\"\"\"\"\"\") }",
+        vec![(OperationType::Query, None)]
+    )]
+    #[case("not graphql", vec![])]
+    fn test_operations(
+        #[case] document: &str,
+        #[case] expected: Vec<(OperationType, Option<&str>)>,
+    ) {
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(operation_type, name)| (operation_type, name.map(str::to_string)))
+            .collect();
+        assert_eq!(operations(document), expected);
+    }
+
+    #[rstest]
+    #[case(json!({"query": "query A { a } query B { b }", "operationName": "B"}), Some("B"))]
+    #[case(json!({"query": "query A { a } query B { b }"}), None)]
+    #[case(json!({"query": "query A { a }", "operationName": "C"}), None)]
+    #[case(
+        json!({"type": "subscribe", "id": "1", "payload": {"query": "subscription S { s }"}}),
+        Some("S")
+    )]
+    #[case(json!({"type": "next", "payload": {"query": "subscription S { s }"}}), None)]
+    fn test_graphql_request_payload(#[case] payload: Value, #[case] name: Option<&str>) {
+        let request = graphql_request(Some(&payload), &HashMap::new());
+        assert_eq!(request.and_then(|r| r.name), name.map(str::to_string));
+    }
+
+    #[rstest]
+    fn test_graphql_request_query() {
+        let query = HashMap::from([
+            ("query".to_string(), "query GetUser { user }".to_string()),
+            ("variables".to_string(), r#"{"id": "42"}"#.to_string()),
+        ]);
+
+        let request = graphql_request(None, &query).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({"type": "query", "name": "GetUser", "variables": {"id": "42"}})
+        );
+    }
+
+    #[rstest]
+    #[case(None, None, true)]
+    #[case(Some(OperationType::Query), None, true)]
+    #[case(Some(OperationType::Mutation), None, false)]
+    #[case(Some(OperationType::Query), Some("GetUser"), true)]
+    #[case(None, Some("GetOrder"), false)]
+    fn test_operation_matches(
+        #[case] operation_type: Option<OperationType>,
+        #[case] name: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let operation = GraphqlOperation {
+            operation_type,
+            name: name.map(str::to_string),
+        };
+        let request = GraphqlRequest {
+            operation_type: OperationType::Query,
+            name: Some("GetUser".to_string()),
+            variables: json!({}),
+        };
+
+        assert_eq!(operation_matches(&operation, Some(&request)), expected);
+        assert!(!operation_matches(&operation, None));
+    }
+}
@@ -1,8 +1,8 @@
-//! Headers intersection check (case-insensitive) and JMESPath expressions.
+//! Headers intersection check (case-insensitive) and expressions.
 
-use crate::expression::match_with_jmespath;
-use crate::matching::intersection::hashmap_to_value;
+use crate::expression::{match_expression_with, EvaluationLimits, ExpressionError};
 use crate::types::preset::HeadersOrExpression;
+use serde_json::Value;
 use std::collections::HashMap;
 
 fn normalize_headers(headers: Option<&HashMap<String, String>>) -> HashMap<String, String> {
@@ -36,29 +36,28 @@ pub fn headers_intersects(
     subset.iter().all(|(k, v)| target.get(k) == Some(v))
 }
 
-/// Match headers using JMESPath expression.
-fn match_headers_with_expression(expression: &str, headers: &HashMap<String, String>) -> bool {
-    let headers_json = hashmap_to_value(headers);
-    match_with_jmespath(expression, &headers_json)
-}
-
-/// Match headers using either HashMap intersection or JMESPath expression.
+/// Match headers using either HashMap intersection or an expression.
+///
+/// Expressions are evaluated against the request `context` within the
+/// evaluation `limits`, and address headers with the `headers.` prefix.
 pub fn headers_matches(
     expected: Option<&HeadersOrExpression>,
     actual: &HashMap<String, String>,
-) -> bool {
+    context: &Value,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionError> {
     match expected {
         Some(HeadersOrExpression::Expression(expr)) => {
-            // Use JMESPath expression
-            match_headers_with_expression(expr, actual)
+            // Use expression
+            match_expression_with(expr, context, limits)
         }
         Some(HeadersOrExpression::Map(expected_map)) => {
             // Use HashMap intersection
-            headers_intersects(Some(actual), Some(expected_map))
+            Ok(headers_intersects(Some(actual), Some(expected_map)))
         }
         None => {
             // No headers specified = match any actual
-            true
+            Ok(true)
         }
     }
 }
@@ -66,7 +65,9 @@ pub fn headers_matches(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::matching::hashmap_to_value;
     use rstest::rstest;
+    use serde_json::json;
 
     fn h(pairs: &[(&str, &str)]) -> HashMap<String, String> {
         pairs
@@ -91,4 +92,26 @@ mod tests {
     ) {
         assert_eq!(headers_intersects(target, subset), expected);
     }
+
+    #[rstest]
+    #[case("headers.\"x-api-key\" == 'secret'", true)]
+    #[case("contains(headers.accept, 'text/html')", true)]
+    #[case("headers.\"x-api-key\" == 'secret' && method == 'POST'", false)]
+    fn test_headers_matches_expression(#[case] expression: &str, #[case] expected: bool) {
+        let actual = h(&[
+            ("x-api-key", "secret"),
+            ("accept", "text/html,application/json"),
+        ]);
+        let context = json!({"method": "GET", "headers": hashmap_to_value(&actual)});
+        let headers = HeadersOrExpression::Expression(expression.to_string());
+        assert_eq!(
+            headers_matches(
+                Some(&headers),
+                &actual,
+                &context,
+                &EvaluationLimits::default()
+            ),
+            Ok(expected)
+        );
+    }
 }
@@ -1,13 +1,17 @@
 //! Request matching utilities.
 
+mod graphql;
 mod headers;
 mod intersection;
 mod payload;
 mod query;
+mod tcp;
 mod url;
 
+pub use graphql::{graphql_request, operation_matches, GraphqlRequest};
 pub use headers::{headers_intersects, headers_matches};
-pub use intersection::{hashmap_intersects, object_intersects};
-pub use payload::payload_matches;
+pub use intersection::{hashmap_intersects, hashmap_to_value, object_intersects};
+pub use payload::{payload_from_bytes, payload_matches};
 pub use query::{parse_query_string, query_matches};
-pub use url::{url_matches, UrlMatchResult};
+pub use tcp::{message_bytes, message_matches, message_payload};
+pub use url::{url_matches, UrlMatchResult, UrlPattern};
@@ -1,28 +1,73 @@
-//! Request payload (JSON) matching with object intersection and JMESPath expressions.
+//! Request payload (JSON) matching with object intersection and expressions.
 
-use crate::expression::match_with_jmespath;
+use crate::config::fixtures::{encode_data_uri, BINARY_MEDIA_TYPE};
+use crate::expression::{match_expression_with, EvaluationLimits, ExpressionError};
 use crate::matching::intersection::object_intersects;
 use crate::types::preset::PayloadOrExpression;
 use serde_json::Value;
 
-/// Match request payload using either object intersection or JMESPath expression.
-pub fn payload_matches(payload: Option<&PayloadOrExpression>, actual: &Value) -> bool {
+/// Size from which JSON bodies are parsed with simd-json.
+///
+/// simd-json parses in place, so smaller bodies aren't worth copying into a
+/// mutable buffer.
+#[cfg(feature = "simd-json")]
+const SIMD_JSON_MIN_LEN: usize = 64 * 1024;
+
+/// Decode a raw request body into the payload matched by presets.
+///
+/// JSON bodies become their value and other UTF-8 bodies a string. Binary bodies
+/// become a base64 `data:` URI with the given media type, so presets can match
+/// them with the literal URI.
+///
+/// With the `simd-json` feature, large JSON bodies are parsed with SIMD instructions.
+pub fn payload_from_bytes(bytes: &[u8], media_type: Option<&str>) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => parse_json(text).unwrap_or_else(|| Value::String(text.to_string())),
+        Err(_) => Value::String(encode_data_uri(
+            media_type.unwrap_or(BINARY_MEDIA_TYPE),
+            bytes,
+        )),
+    }
+}
+
+/// Parse a JSON body, returning `None` if it isn't valid JSON.
+fn parse_json(text: &str) -> Option<Value> {
+    #[cfg(feature = "simd-json")]
+    if text.len() >= SIMD_JSON_MIN_LEN {
+        let mut buffer = text.as_bytes().to_vec();
+        return simd_json::serde::from_slice(&mut buffer).ok();
+    }
+    serde_json::from_str(text).ok()
+}
+
+/// Match request payload using either object intersection or an expression.
+///
+/// Expressions are evaluated against the request `context` within the
+/// evaluation `limits`, and address the payload with the `payload.` prefix.
+///
+/// Returns error if the expression is invalid or can't be evaluated.
+pub fn payload_matches(
+    payload: Option<&PayloadOrExpression>,
+    actual: &Value,
+    context: &Value,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionError> {
     match payload {
         Some(PayloadOrExpression::Expression(expr)) => {
-            // Use JMESPath expression
-            match_with_jmespath(expr, actual)
+            // Use expression
+            match_expression_with(expr, context, limits)
         }
         Some(PayloadOrExpression::Value(expected)) => {
             // Use object intersection or direct comparison
             if expected.is_object() && actual.is_object() {
-                object_intersects(Some(actual), Some(expected))
+                Ok(object_intersects(Some(actual), Some(expected)))
             } else {
-                expected == actual
+                Ok(expected == actual)
             }
         }
         None => {
             // No payload specified = match any actual
-            true
+            Ok(true)
         }
     }
 }
@@ -30,6 +75,7 @@ pub fn payload_matches(payload: Option<&PayloadOrExpression>, actual: &Value) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::expression::match_with_jmespath;
     use rstest::rstest;
     use serde_json::json;
 
@@ -41,7 +87,7 @@ mod tests {
         #[case] expected: bool,
     ) {
         let body = json!({"items": [{"id": 1}, {"id": 2}, {"id": 5}]});
-        assert_eq!(match_with_jmespath(expression, &body), expected);
+        assert_eq!(match_with_jmespath(expression, &body).unwrap(), expected);
     }
 
     #[rstest]
@@ -52,7 +98,7 @@ mod tests {
         #[case] expected: bool,
     ) {
         let body = json!({"items": [{"id": 1}, {"id": 5}, {"id": 3}]});
-        assert_eq!(match_with_jmespath(expression, &body), expected);
+        assert_eq!(match_with_jmespath(expression, &body).unwrap(), expected);
     }
 
     #[rstest]
@@ -63,10 +109,7 @@ mod tests {
                 {"name": "Jane", "age": 15}
             ]
         });
-        assert!(match_with_jmespath(
-            "length(users[?age > `18`].name) > `0`",
-            &body
-        ));
+        assert!(match_with_jmespath("length(users[?age > `18`].name) > `0`", &body).unwrap());
     }
 
     #[rstest]
@@ -77,7 +120,42 @@ mod tests {
         #[case] expected: bool,
     ) {
         let body = json!({"value": 5});
-        assert_eq!(match_with_jmespath(expression, &body), expected);
+        assert_eq!(match_with_jmespath(expression, &body).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(b"{\"id\": 1}".as_slice(), None, json!({"id": 1}))]
+    #[case(b"id=1".as_slice(), None, json!("id=1"))]
+    #[case(b"".as_slice(), None, json!(""))]
+    #[case(b"\x89PNG".as_slice(), Some("image/png"), json!("data:image/png;base64,iVBORw=="))]
+    #[case(b"\xff".as_slice(), None, json!("data:application/octet-stream;base64,/w=="))]
+    fn test_payload_from_bytes(
+        #[case] bytes: &[u8],
+        #[case] media_type: Option<&str>,
+        #[case] expected: Value,
+    ) {
+        assert_eq!(payload_from_bytes(bytes, media_type), expected);
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn test_payload_from_large_bytes(#[case] valid: bool) {
+        let items: Vec<Value> = (0..10_000)
+            .map(|id| json!({"id": id, "name": format!("item {}", id), "price": 1.5}))
+            .collect();
+        let payload = json!({"batch": "b-1", "items": items});
+        let mut body = payload.to_string();
+        if !valid {
+            body.push(',');
+        }
+
+        let expected = if valid {
+            payload
+        } else {
+            Value::String(body.clone())
+        };
+        assert_eq!(payload_from_bytes(body.as_bytes(), None), expected);
     }
 
     #[rstest]
@@ -85,118 +163,189 @@ mod tests {
         let body = json!({"userId": 123, "name": "John"});
         let payload = PayloadOrExpression::Value(json!({"userId": 123}));
 
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_expression_notation() {
         let body = json!({"items": [{"id": 5}]});
-        let payload = PayloadOrExpression::Expression("contains(items[*].id, `5`)".to_string());
-        assert!(payload_matches(Some(&payload), &body));
+        let payload =
+            PayloadOrExpression::Expression("contains(payload.items[*].id, `5`)".to_string());
+        let context = json!({ "payload": body });
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &context,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_no_payload() {
         let body = json!({"any": "value"});
-        assert!(payload_matches(None, &body));
+        assert!(payload_matches(None, &body, &Value::Null, &EvaluationLimits::default()).unwrap());
     }
 
     #[rstest]
     fn test_match_payload_with_expression_invalid_syntax() {
         let body = json!({"value": 5});
         // Invalid JMESPath syntax
-        assert!(!match_with_jmespath("[invalid", &body));
+        assert!(matches!(
+            match_with_jmespath("[invalid", &body),
+            Err(ExpressionError::Invalid { .. })
+        ));
     }
 
     #[rstest]
     fn test_match_payload_with_expression_null_result() {
         let body = json!({"value": null});
         // Expression that returns null
-        assert!(!match_with_jmespath("value", &body));
+        assert!(!match_with_jmespath("value", &body).unwrap());
     }
 
     #[rstest]
     fn test_match_payload_with_expression_number_result() {
-        let body = json!({"value": 5});
+        let body = json!({"value": 5, "zero": 0});
         // Expression that returns number
-        assert!(match_with_jmespath("value", &body));
-        assert!(!match_with_jmespath("value - value", &body)); // Returns 0
+        assert!(match_with_jmespath("value", &body).unwrap());
+        assert!(!match_with_jmespath("zero", &body).unwrap());
     }
 
     #[rstest]
     fn test_match_payload_with_expression_string_result() {
         let body = json!({"value": "test"});
         // Expression that returns string
-        assert!(match_with_jmespath("value", &body));
+        assert!(match_with_jmespath("value", &body).unwrap());
 
         let body_empty = json!({"value": ""});
-        assert!(!match_with_jmespath("value", &body_empty));
+        assert!(!match_with_jmespath("value", &body_empty).unwrap());
     }
 
     #[rstest]
     fn test_match_payload_with_expression_array_result() {
         let body = json!({"items": [1, 2, 3]});
         // Expression that returns array
-        assert!(match_with_jmespath("items", &body));
+        assert!(match_with_jmespath("items", &body).unwrap());
 
         let body_empty = json!({"items": []});
-        assert!(!match_with_jmespath("items", &body_empty));
+        assert!(!match_with_jmespath("items", &body_empty).unwrap());
     }
 
     #[rstest]
     fn test_match_payload_with_expression_object_result() {
         let body = json!({"user": {"name": "John"}});
         // Expression that returns object
-        assert!(match_with_jmespath("user", &body));
+        assert!(match_with_jmespath("user", &body).unwrap());
 
         let body_empty = json!({"user": {}});
-        assert!(!match_with_jmespath("user", &body_empty));
+        assert!(!match_with_jmespath("user", &body_empty).unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_string() {
         let body = json!("test");
         let payload = PayloadOrExpression::Value(json!("test"));
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
 
         let payload_different = PayloadOrExpression::Value(json!("different"));
-        assert!(!payload_matches(Some(&payload_different), &body));
+        assert!(!payload_matches(
+            Some(&payload_different),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_number() {
         let body = json!(42);
         let payload = PayloadOrExpression::Value(json!(42));
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
 
         let payload_different = PayloadOrExpression::Value(json!(100));
-        assert!(!payload_matches(Some(&payload_different), &body));
+        assert!(!payload_matches(
+            Some(&payload_different),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_array() {
         let body = json!([1, 2, 3]);
         let payload = PayloadOrExpression::Value(json!([1, 2, 3]));
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
 
         let payload_different = PayloadOrExpression::Value(json!([4, 5, 6]));
-        assert!(!payload_matches(Some(&payload_different), &body));
+        assert!(!payload_matches(
+            Some(&payload_different),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_boolean() {
         let body = json!(true);
         let payload = PayloadOrExpression::Value(json!(true));
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
 
         let payload_different = PayloadOrExpression::Value(json!(false));
-        assert!(!payload_matches(Some(&payload_different), &body));
+        assert!(!payload_matches(
+            Some(&payload_different),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_payload_matches_null() {
         let body = json!(null);
         let payload = PayloadOrExpression::Value(json!(null));
-        assert!(payload_matches(Some(&payload), &body));
+        assert!(payload_matches(
+            Some(&payload),
+            &body,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 }
@@ -1,70 +1,71 @@
-//! Query parameters matching with HashMap intersection and JMESPath expressions.
+//! Query parameters matching with HashMap intersection and expressions.
 
-use crate::expression::match_with_jmespath;
-use crate::matching::intersection::{hashmap_intersects, hashmap_to_value};
+use crate::expression::{match_expression_with, EvaluationLimits, ExpressionError};
+use crate::matching::intersection::hashmap_intersects;
 use crate::types::preset::QueryOrExpression;
+use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 /// Parse query string into HashMap with URL decoding.
+///
+/// Values of repeated keys are joined with `,`.
 pub fn parse_query_string(query_str: &str) -> HashMap<String, String> {
-    let mut result = HashMap::new();
-
-    if query_str.is_empty() {
-        return result;
-    }
+    let mut result: HashMap<String, String> = HashMap::new();
 
     for pair in query_str.split('&') {
         if pair.is_empty() {
             continue;
         }
 
-        let parts: Vec<&str> = pair.splitn(2, '=').collect();
-        let key = urlencoding::decode(parts[0])
-            .unwrap_or_else(|_| parts[0].into())
-            .to_string();
-        let value = if parts.len() > 1 {
-            urlencoding::decode(parts[1])
-                .unwrap_or_else(|_| parts[1].into())
-                .to_string()
-        } else {
-            String::new()
-        };
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode_component(key);
+        let value = decode_component(value);
 
         // Handle multiple values for the same key
-        if let Some(existing) = result.get_mut(&key) {
+        if let Some(existing) = result.get_mut(key.as_ref()) {
             existing.push(',');
             existing.push_str(&value);
         } else {
-            result.insert(key, value);
+            result.insert(key.into_owned(), value.into_owned());
         }
     }
 
     result
 }
 
-/// Match query parameters using JMESPath expression.
-fn match_query_with_expression(expression: &str, query_params: &HashMap<String, String>) -> bool {
-    let query_json = hashmap_to_value(query_params);
-    match_with_jmespath(expression, &query_json)
+/// Percent-decode a query key or value, borrowing it if it has no escapes.
+///
+/// Invalid escapes are kept as written.
+fn decode_component(component: &str) -> Cow<'_, str> {
+    if !component.contains('%') {
+        return Cow::Borrowed(component);
+    }
+    urlencoding::decode(component).unwrap_or(Cow::Borrowed(component))
 }
 
-/// Match query parameters using either HashMap intersection or JMESPath expression.
+/// Match query parameters using either HashMap intersection or an expression.
+///
+/// Expressions are evaluated against the request `context` within the
+/// evaluation `limits`, and address query parameters with the `query.` prefix.
 pub fn query_matches(
     expected: Option<&QueryOrExpression>,
     actual: &HashMap<String, String>,
-) -> bool {
+    context: &Value,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionError> {
     match expected {
         Some(QueryOrExpression::Expression(expr)) => {
-            // Use JMESPath expression
-            match_query_with_expression(expr, actual)
+            // Use expression
+            match_expression_with(expr, context, limits)
         }
         Some(QueryOrExpression::Map(expected_map)) => {
             // Use HashMap intersection
-            hashmap_intersects(Some(expected_map), Some(actual))
+            Ok(hashmap_intersects(Some(expected_map), Some(actual)))
         }
         None => {
             // No query specified = match any actual
-            true
+            Ok(true)
         }
     }
 }
@@ -72,7 +73,9 @@ pub fn query_matches(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::matching::hashmap_to_value;
     use rstest::rstest;
+    use serde_json::json;
 
     fn h(pairs: &[(&str, &str)]) -> HashMap<String, String> {
         pairs
@@ -96,61 +99,88 @@ mod tests {
     // Test key without value
     #[case("page=&limit=10", &[("page", ""), ("limit", "10")])]
     #[case("page&limit=10", &[("page", ""), ("limit", "10")])]
+    #[case("filter=a=b", &[("filter", "a=b")])]
+    #[case("q=100%", &[("q", "100%")])]
+    #[case("q=%E2%9C%93&q=x", &[("q", "\u{2713},x")])]
     fn test_parse_query_string(#[case] query_str: &str, #[case] expected: &[(&str, &str)]) {
         let result = parse_query_string(query_str);
         let expected_map = h(expected);
         assert_eq!(result, expected_map);
     }
 
+    /// Match query parameters with an expression evaluated on a context holding them.
+    fn match_expression_on(expression: &str, query: &HashMap<String, String>) -> bool {
+        let expected = QueryOrExpression::Expression(expression.to_string());
+        let context = json!({"query": hashmap_to_value(query)});
+        query_matches(
+            Some(&expected),
+            query,
+            &context,
+            &EvaluationLimits::default(),
+        )
+        .unwrap()
+    }
+
     #[rstest]
-    #[case("page == '1'", true)]
-    #[case("page == '2'", false)]
-    #[case("page == '1' && limit == '10'", true)]
-    #[case("page == '1' && limit == '20'", false)]
-    #[case("page != null && limit != null", true)]
-    #[case("page != null && limit != null && sort != null", false)]
+    #[case("query.page == '1'", true)]
+    #[case("query.page == '2'", false)]
+    #[case("query.page == '1' && query.limit == '10'", true)]
+    #[case("query.page == '1' && query.limit == '20'", false)]
+    #[case("query.page != null && query.limit != null", true)]
+    #[case(
+        "query.page != null && query.limit != null && query.sort != null",
+        false
+    )]
     fn test_match_query_with_expression_simple(#[case] expression: &str, #[case] expected: bool) {
         let query = h(&[("page", "1"), ("limit", "10")]);
-        assert_eq!(match_query_with_expression(expression, &query), expected);
+        assert_eq!(match_expression_on(expression, &query), expected);
     }
 
     #[rstest]
-    #[case("to_number(page) > `0`", true)]
-    #[case("to_number(page) > `5`", false)]
-    #[case("to_number(page) > `0` && to_number(limit) <= `100`", true)]
-    #[case("to_number(page) > `0` && to_number(limit) <= `5`", false)]
+    #[case("to_number(query.page) > `0`", true)]
+    #[case("to_number(query.page) > `5`", false)]
+    #[case("to_number(query.page) > `0` && to_number(query.limit) <= `100`", true)]
+    #[case("to_number(query.page) > `0` && to_number(query.limit) <= `5`", false)]
     fn test_match_query_with_expression_numeric(#[case] expression: &str, #[case] expected: bool) {
         let query = h(&[("page", "1"), ("limit", "10")]);
-        assert_eq!(match_query_with_expression(expression, &query), expected);
+        assert_eq!(match_expression_on(expression, &query), expected);
     }
 
     #[rstest]
-    #[case("contains(tags, 'important')", true)]
-    #[case("contains(tags, 'unimportant')", false)]
-    #[case("tags[0] == 'important'", true)]
+    #[case("contains(query.tags, 'important')", true)]
+    #[case("contains(query.tags, 'unimportant')", false)]
+    #[case("query.tags[0] == 'important'", true)]
     fn test_match_query_with_expression_array(#[case] expression: &str, #[case] expected: bool) {
         let mut query = HashMap::new();
         query.insert("tags".to_string(), "important,urgent,normal".to_string());
-        assert_eq!(match_query_with_expression(expression, &query), expected);
+        assert_eq!(match_expression_on(expression, &query), expected);
     }
 
     #[rstest]
     fn test_query_matches_hashmap() {
         let expected = QueryOrExpression::Map(h(&[("page", "1")]));
         let actual = h(&[("page", "1"), ("limit", "10")]);
-        assert!(query_matches(Some(&expected), &actual));
+        assert!(query_matches(
+            Some(&expected),
+            &actual,
+            &Value::Null,
+            &EvaluationLimits::default()
+        )
+        .unwrap());
     }
 
     #[rstest]
     fn test_query_matches_expression() {
         let actual = h(&[("page", "1"), ("limit", "10")]);
-        let expected = QueryOrExpression::Expression("page == '1' && limit == '10'".to_string());
-        assert!(query_matches(Some(&expected), &actual));
+        assert!(match_expression_on(
+            "query.page == '1' && query.limit == '10'",
+            &actual
+        ));
     }
 
     #[rstest]
     fn test_query_matches_no_expected() {
         let actual = h(&[("page", "1")]);
-        assert!(query_matches(None, &actual));
+        assert!(query_matches(None, &actual, &Value::Null, &EvaluationLimits::default()).unwrap());
     }
 }
@@ -0,0 +1,90 @@
+//! Raw TCP message matching.
+//!
+//! TCP requests carry the message read from the connection as their payload: a
+//! string for UTF-8 messages, or a base64 `data:` URI for binary ones.
+
+use crate::config::fixtures::{encode_data_uri, parse_data_uri, BINARY_MEDIA_TYPE, DATA_PREFIX};
+use crate::types::tcp::TcpMatch;
+use regex::bytes::Regex;
+use serde_json::Value;
+
+/// Build the payload of a TCP request from the message bytes.
+pub fn message_payload(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Value::String(text.to_string()),
+        Err(_) => Value::String(encode_data_uri(BINARY_MEDIA_TYPE, bytes)),
+    }
+}
+
+/// Get the message bytes of a TCP request payload.
+///
+/// Returns `None` if the payload isn't a string or holds a malformed `data:` URI.
+pub fn message_bytes(payload: Option<&Value>) -> Option<Vec<u8>> {
+    let text = payload?.as_str()?;
+    if text.starts_with(DATA_PREFIX) {
+        return parse_data_uri(text).ok().map(|fixture| fixture.data);
+    }
+    Some(text.as_bytes().to_vec())
+}
+
+/// Check if a TCP message matches the expected prefix and pattern.
+///
+/// Returns `false` if there is no message or the pattern is not a valid regex.
+pub fn message_matches(expected: &TcpMatch, message: Option<&[u8]>) -> bool {
+    let Some(message) = message else {
+        return false;
+    };
+    expected
+        .prefix
+        .as_ref()
+        .is_none_or(|prefix| message.starts_with(prefix.as_bytes()))
+        && expected
+            .pattern
+            .as_ref()
+            .is_none_or(|pattern| Regex::new(pattern).is_ok_and(|regex| regex.is_match(message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"EHLO mail.test\r\n".as_slice())]
+    #[case(b"\x00\xff\x10".as_slice())]
+    fn test_message_payload_round_trip(#[case] bytes: &[u8]) {
+        let payload = message_payload(bytes);
+        assert_eq!(message_bytes(Some(&payload)).as_deref(), Some(bytes));
+    }
+
+    #[rstest]
+    #[case(Some("EHLO"), None, true)]
+    #[case(Some("HELO"), None, false)]
+    #[case(None, Some(r"^EHLO \S+\r\n$"), true)]
+    #[case(Some("EHLO"), Some("(?i)helo"), false)]
+    #[case(None, Some(r"(?-u)\xff"), false)]
+    #[case(None, Some("("), false)]
+    #[case(None, None, true)]
+    fn test_message_matches(
+        #[case] prefix: Option<&str>,
+        #[case] pattern: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let tcp = TcpMatch {
+            prefix: prefix.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+        };
+
+        assert_eq!(message_matches(&tcp, Some(b"EHLO mail.test\r\n")), expected);
+        assert!(!message_matches(&tcp, None));
+    }
+
+    #[rstest]
+    fn test_message_matches_binary() {
+        let tcp = TcpMatch {
+            prefix: None,
+            pattern: Some(r"(?-u)^\x00\xff".to_string()),
+        };
+        assert!(message_matches(&tcp, Some(b"\x00\xff\x10")));
+    }
+}
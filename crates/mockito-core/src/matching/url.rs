@@ -9,25 +9,57 @@ pub struct UrlMatchResult {
     pub params: HashMap<String, String>,
 }
 
+/// Match a URL against a route URL pattern.
+///
+/// Compiles the pattern on every call, use [`UrlPattern`] to match many URLs
+/// against the same pattern.
 pub fn url_matches(pattern: &str, url: &str) -> UrlMatchResult {
-    let pattern = normalize_url(pattern);
-    let url = normalize_url(url);
+    UrlPattern::new(pattern).matches(url)
+}
+
+/// Route URL pattern like `/api/users/{id}`, compiled once for matching.
+#[derive(Debug, Clone)]
+pub struct UrlPattern {
+    regex: Regex,
+    /// Names of `{name}` segments, in order of their capture groups
+    param_names: Vec<String>,
+}
 
-    let (regex, param_names) = pattern_to_regex(&pattern);
+impl UrlPattern {
+    /// Compile a pattern, ignoring trailing slashes and the query string.
+    pub fn new(pattern: &str) -> Self {
+        let (regex, param_names) = pattern_to_regex(&normalize_url(pattern));
+        Self { regex, param_names }
+    }
+
+    /// Match a URL, capturing path parameters.
+    pub fn matches(&self, url: &str) -> UrlMatchResult {
+        let url = normalize_url(url);
+        let Some(caps) = self.regex.captures(&url) else {
+            return UrlMatchResult::default();
+        };
 
-    let Some(caps) = regex.captures(&url) else {
-        return UrlMatchResult::default();
-    };
+        let params = self
+            .param_names
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                caps.get(i + 1)
+                    .map(|m| (name.clone(), m.as_str().to_owned()))
+            })
+            .collect();
 
-    let params = param_names
-        .into_iter()
-        .enumerate()
-        .filter_map(|(i, name)| caps.get(i + 1).map(|m| (name, m.as_str().to_owned())))
-        .collect();
+        UrlMatchResult {
+            matched: true,
+            params,
+        }
+    }
+}
 
-    UrlMatchResult {
-        matched: true,
-        params,
+/// Patterns are equal if they compile to the same regex.
+impl PartialEq for UrlPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
     }
 }
 
@@ -95,5 +127,17 @@ mod tests {
         for (k, v) in params {
             assert_eq!(result.params.get(*k), Some(&(*v).to_owned()));
         }
+        assert_eq!(UrlPattern::new(pattern).matches(url), result);
+    }
+
+    #[rstest]
+    fn test_url_pattern_reuse() {
+        let pattern = UrlPattern::new("/api/users/{id}/");
+        assert_eq!(pattern, UrlPattern::new("/api/users/{id}"));
+        assert_ne!(pattern, UrlPattern::new("/api/users/{name}/posts"));
+
+        assert_eq!(pattern.matches("/api/users/1").params["id"], "1");
+        assert_eq!(pattern.matches("/api/users/2?page=1").params["id"], "2");
+        assert!(!pattern.matches("/api/users").matched);
     }
 }
@@ -0,0 +1,150 @@
+//! Audit log of controller state changes.
+//!
+//! [`AuditLog`] keeps every change applied to a controller (collection switches,
+//! route overrides, resets, reloads, ...) with the time it happened and the label
+//! set by the caller at that time. Labeling changes with the running test makes
+//! state leaking from one test into the next visible:
+//!
+//! ```ignore
+//! controller.set_audit_label(Some("checkout fails without stock"));
+//! controller.use_routes(&["stock:empty:ok".to_string()])?;
+//! // ...later, in a flaky test
+//! for entry in controller.audit_log() {
+//!     println!("{}", entry);
+//! }
+//! ```
+//!
+//! The log is bounded, keeping the latest [`AUDIT_CAPACITY`] changes.
+
+use crate::clock;
+use crate::mocks::controller::ControllerEvent;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of changes kept in an audit log
+pub const AUDIT_CAPACITY: usize = 1000;
+
+/// Change recorded in an [`AuditLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Time the change was applied
+    pub timestamp: SystemTime,
+    /// Label set when the change was applied, see [`AuditLog::set_label`]
+    pub label: Option<String>,
+    pub event: ControllerEvent,
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        write!(f, "{}", millis)?;
+        if let Some(label) = &self.label {
+            write!(f, " [{}]", label)?;
+        }
+        write!(f, " {:?}", self.event)
+    }
+}
+
+/// Bounded log of controller state changes, oldest first.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: Mutex<VecDeque<AuditEntry>>,
+    label: Mutex<Option<String>>,
+}
+
+impl AuditLog {
+    /// Record a change with the current label, dropping the oldest one if the
+    /// log is full.
+    pub fn record(&self, event: &ControllerEvent) {
+        let entry = AuditEntry {
+            timestamp: clock::now(),
+            label: self.label(),
+            event: event.clone(),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= AUDIT_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Label changes recorded from now on, e.g. with the name of the running test.
+    pub fn set_label(&self, label: Option<String>) {
+        *self.label.lock().unwrap_or_else(PoisonError::into_inner) = label;
+    }
+
+    /// Get the label of changes recorded from now on.
+    pub fn label(&self) -> Option<String> {
+        self.label
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get all recorded changes, oldest first.
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Remove all recorded changes, keeping the label.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_record_with_label() {
+        let log = AuditLog::default();
+        log.record(&ControllerEvent::RoutesReset);
+        log.set_label(Some("test a".to_string()));
+        log.record(&ControllerEvent::Reloaded);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].label, None);
+        assert_eq!(entries[1].label.as_deref(), Some("test a"));
+        assert!(entries[1].to_string().ends_with(" [test a] Reloaded"));
+
+        log.clear();
+        assert!(log.entries().is_empty());
+        assert_eq!(log.label().as_deref(), Some("test a"));
+    }
+
+    #[rstest]
+    fn test_record_keeps_latest() {
+        let log = AuditLog::default();
+        for i in 0..=AUDIT_CAPACITY {
+            log.record(&ControllerEvent::CollectionChanged {
+                collection_id: i.to_string(),
+            });
+        }
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), AUDIT_CAPACITY);
+        assert_eq!(
+            entries[0].event,
+            ControllerEvent::CollectionChanged {
+                collection_id: "1".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Request context evaluated by preset expressions.
+//!
+//! Every preset expression (query, headers and payload) runs against the same
+//! JSON view of the request, so conditions can combine fields:
+//!
+//! ```yaml
+//! payload: "${query.dryRun == 'true' && payload.items[0].id == `5`}"
+//! ```
+//!
+//! The context has the shape:
+//!
+//! ```json
+//! {
+//!   "method": "POST",
+//!   "url": "/api/orders/7?dryRun=true",
+//!   "params": {"id": "7"},
+//!   "query": {"dryRun": "true"},
+//!   "headers": {"content-type": "application/json"},
+//!   "payload": {"items": [{"id": 5}]}
+//! }
+//! ```
+//!
+//! Query and header values holding commas are split into arrays. `method` and
+//! `payload` are `null` when the request has none.
+//!
+//! GraphQL requests also get a `graphql` field holding the executed operation,
+//! see [`graphql_request`]:
+//!
+//! ```json
+//! {"type": "query", "name": "GetUser", "variables": {"id": "42"}}
+//! ```
+
+use crate::matching::{graphql_request, hashmap_to_value, parse_query_string, GraphqlRequest};
+use crate::mocks::controller::Request;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Key of path parameters in the context
+const PARAMS_KEY: &str = "params";
+/// Key of the GraphQL operation in the context
+const GRAPHQL_KEY: &str = "graphql";
+
+/// JSON view of a request shared by all preset expressions.
+///
+/// Built once per lookup. Path parameters depend on the route pattern, so they
+/// are replaced via [`set_params`](Self::set_params) for each candidate route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    /// Query parameters, parsed from the URL if the request has none
+    query: HashMap<String, String>,
+    /// Operation of GraphQL requests
+    graphql: Option<GraphqlRequest>,
+    /// Context evaluated by expressions
+    value: Value,
+}
+
+impl RequestContext {
+    /// Build the context of a request.
+    pub fn new(request: &Request) -> Self {
+        let query = match &request.query {
+            Some(query) => query.clone(),
+            None => request
+                .url
+                .split('?')
+                .nth(1)
+                .map(parse_query_string)
+                .unwrap_or_default(),
+        };
+        let graphql = graphql_request(request.payload.as_ref(), &query);
+        let mut value = json!({
+            "method": request.method,
+            "url": request.url,
+            PARAMS_KEY: {},
+            "query": hashmap_to_value(&query),
+            "headers": request.headers.as_ref().map(hashmap_to_value).unwrap_or_else(|| json!({})),
+            "payload": request.payload,
+        });
+        if let Some(graphql) = &graphql {
+            value[GRAPHQL_KEY] = serde_json::to_value(graphql).unwrap_or_default();
+        }
+
+        Self {
+            query,
+            graphql,
+            value,
+        }
+    }
+
+    /// Replace path parameters extracted by the matched route pattern.
+    pub fn set_params(&mut self, params: &HashMap<String, String>) {
+        self.value[PARAMS_KEY] = params
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+    }
+
+    /// Query parameters of the request.
+    pub fn query(&self) -> &HashMap<String, String> {
+        &self.query
+    }
+
+    /// Operation executed by the request, if it is a GraphQL request.
+    pub fn graphql(&self) -> Option<&GraphqlRequest> {
+        self.graphql.as_ref()
+    }
+
+    /// Context evaluated by expressions.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::route::{HttpMethod, Transport};
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_request_context() {
+        let request = Request {
+            url: "/api/orders/7?tags=a,b".to_string(),
+            method: Some(HttpMethod::Post),
+            transport: Transport::Http,
+            headers: Some(HashMap::from([(
+                "x-api-key".to_string(),
+                "secret".to_string(),
+            )])),
+            query: None,
+            payload: Some(json!({"id": 5})),
+        };
+
+        let mut context = RequestContext::new(&request);
+        context.set_params(&HashMap::from([("id".to_string(), "7".to_string())]));
+
+        assert_eq!(context.query().get("tags").map(String::as_str), Some("a,b"));
+        assert_eq!(
+            context.value(),
+            &json!({
+                "method": "POST",
+                "url": "/api/orders/7?tags=a,b",
+                "params": {"id": "7"},
+                "query": {"tags": ["a", "b"]},
+                "headers": {"x-api-key": "secret"},
+                "payload": {"id": 5}
+            })
+        );
+    }
+
+    #[rstest]
+    fn test_request_context_empty() {
+        let request = Request {
+            url: "/ws".to_string(),
+            method: None,
+            transport: Transport::WebSocket,
+            headers: None,
+            query: None,
+            payload: None,
+        };
+
+        let context = RequestContext::new(&request);
+        assert!(context.query().is_empty());
+        assert_eq!(
+            context.value(),
+            &json!({
+                "method": null,
+                "url": "/ws",
+                "params": {},
+                "query": {},
+                "headers": {},
+                "payload": null
+            })
+        );
+    }
+
+    #[rstest]
+    fn test_request_context_graphql() {
+        let request = Request {
+            url: "/graphql".to_string(),
+            method: Some(HttpMethod::Post),
+            transport: Transport::Http,
+            headers: None,
+            query: None,
+            payload: Some(json!({
+                "query": "query GetUser($id: ID!) { user(id: $id) { name } }",
+                "variables": {"id": "42"}
+            })),
+        };
+
+        let context = RequestContext::new(&request);
+        assert_eq!(
+            context
+                .graphql()
+                .and_then(|graphql| graphql.name.as_deref()),
+            Some("GetUser")
+        );
+        assert_eq!(
+            context.value()["graphql"],
+            json!({"type": "query", "name": "GetUser", "variables": {"id": "42"}})
+        );
+    }
+}
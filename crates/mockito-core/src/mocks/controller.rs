@@ -3,21 +3,38 @@
 //! This module provides `MocksController` which manages active routes from collections
 //! and provides fast route lookup by request matching.
 
-use crate::matching::{
-    headers_matches, parse_query_string, payload_matches, query_matches, url_matches,
+use crate::clock::Instant;
+use crate::config::error::ConfigError;
+use crate::config::export::ExportFormat;
+use crate::config::project::Project;
+use crate::mocks::audit::{AuditEntry, AuditLog};
+use crate::mocks::coverage::CoverageReport;
+use crate::mocks::har::export_har;
+use crate::mocks::history::{
+    HistoryState, RecordedRequest, RequestHistory, DEFAULT_HISTORY_CAPACITY, NEAR_MATCH_LIMIT,
 };
+use crate::mocks::latency::RouteLatency;
 use crate::mocks::manager::{ActiveRoute, MocksManager, ResolveError};
-use crate::types::preset::Preset;
-use crate::types::route::{HttpMethod, Transport};
+use crate::mocks::table::{ExpressionDiagnostic, RouteExplanation, RouteTable};
+use crate::mocks::tap::LiveEvent;
+use crate::mocks::unmatched::{not_found, UnmatchedRequest};
+use crate::mocks::verify::{verify_no_unmatched, Verification, VerificationError};
+use crate::template::TemplateError;
+use crate::types::route::{HttpMethod, RouteReference, Transport};
+use crate::types::settings::Settings;
+use crate::types::variant::Variant;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
 
 /// HTTP request for route matching.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Request {
-    /// Request URL (path + query string)
+    /// Request URL (path + query string, or listen address for TCP)
     pub url: String,
-    /// HTTP method (required for HTTP routes, `None` for WebSocket)
+    /// HTTP method (required for HTTP routes, `None` for WebSocket, SSE and TCP)
     pub method: Option<HttpMethod>,
     /// Transport type
     pub transport: Transport,
@@ -25,25 +42,138 @@ pub struct Request {
     pub headers: Option<HashMap<String, String>>,
     /// Query parameters (parsed from URL if `None`)
     pub query: Option<HashMap<String, String>>,
-    /// Request body/payload
+    /// Request body/payload (message read from the connection for TCP)
     pub payload: Option<Value>,
 }
 
+/// Route change applied on top of the active collection.
+///
+/// Overrides are recorded so they can be re-applied when manager data is reloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Override {
+    /// Single route reference applied via `use_routes` or `use_socket`
+    Route {
+        reference: String,
+        transport: Transport,
+    },
+    /// Preset and variant applied to tagged routes via `use_tag`
+    Tag {
+        tag: String,
+        preset_id: String,
+        variant_id: String,
+    },
+    /// Tagged routes deactivated via `disable_tag`
+    DisableTag { tag: String },
+}
+
+/// Change of controller state, delivered to listeners registered via
+/// [`MocksController::on_change`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControllerEvent {
+    /// Collection activated via `use_collection`
+    CollectionChanged { collection_id: String },
+    /// Routes overridden via `use_routes`, `use_socket` or `use_tag`
+    RoutesOverridden { route_ids: Vec<String> },
+    /// Tagged routes deactivated via `disable_tag`
+    TagDisabled { tag: String },
+    /// Routes disabled via `disable_routes`
+    RoutesDisabled { route_ids: Vec<String> },
+    /// Routes enabled via `enable_routes`
+    RoutesEnabled { route_ids: Vec<String> },
+    /// Routes reset to collection defaults via `reset_routes`
+    RoutesReset,
+    /// Manager data replaced via `reload`
+    Reloaded,
+    /// State restored from a snapshot via `restore`
+    Restored,
+}
+
+/// Callback notified about controller changes
+pub type ChangeListener = Arc<dyn Fn(&ControllerEvent) + Send + Sync>;
+
+/// ID of a registered change listener, used to remove it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+/// Registered change listeners.
+#[derive(Clone, Default)]
+struct Listeners {
+    next_id: u64,
+    entries: Vec<(ListenerId, ChangeListener)>,
+}
+
+impl Listeners {
+    fn emit(&self, event: ControllerEvent) {
+        tracing::debug!(?event, "controller state changed");
+        for (_, listener) in &self.entries {
+            listener(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Listeners({})", self.entries.len())
+    }
+}
+
+/// Captured controller state.
+///
+/// Created by [`MocksController::snapshot`] and applied with [`MocksController::restore`].
+/// Snapshots are independent of the controller, so it can be mutated freely afterwards.
+#[derive(Debug, Clone)]
+pub struct ControllerSnapshot {
+    /// Active collection ID at capture time
+    active_collection_id: Option<String>,
+    /// Route table at capture time, including overrides
+    route_table: Arc<RouteTable>,
+    /// Overrides applied at capture time
+    overrides: Vec<Override>,
+    /// Disabled route IDs at capture time
+    disabled_routes: HashSet<String>,
+    /// Request history with unmatched requests and serve times at capture time
+    history: HistoryState,
+}
+
+impl ControllerSnapshot {
+    /// Get the active collection ID captured in the snapshot.
+    pub fn active_collection_id(&self) -> Option<&str> {
+        self.active_collection_id.as_deref()
+    }
+}
+
 /// Manager for controlling active routes and collection switching.
 ///
 /// `MocksController` provides:
 /// - Collection activation via `use_collection()`
 /// - Fast route lookup via `find_route()`
-/// - Cached active routes for performance
+/// - Immutable route table for lock-free concurrent lookup via `route_table()`
 /// - Request matching against route presets
+/// - Isolated child controllers via `fork()`
+/// - Hot-swapping manager data via `reload()`
+/// - Temporarily disabling routes via `disable_routes()`
+/// - Change notifications via `on_change()`
+/// - Request verification via `get_requests()` and `verify()`
 #[derive(Debug, Clone)]
 pub struct MocksController {
-    /// Mocks manager for storing and resolving collections/routes
-    mocks_manager: MocksManager,
+    /// Mocks manager for storing and resolving collections/routes (shared between forks)
+    mocks_manager: Arc<MocksManager>,
     /// Currently active collection ID
     active_collection_id: Option<String>,
-    /// Cached active routes from the current collection
-    cached_active_routes: Vec<ActiveRoute>,
+    /// Active routes table, rebuilt on every change
+    route_table: Arc<RouteTable>,
+    /// Overrides applied since the active collection was selected, in order
+    overrides: Vec<Override>,
+    /// IDs of routes skipped during matching
+    disabled_routes: HashSet<String>,
+    /// Listeners notified about state changes
+    listeners: Listeners,
+    /// Project settings (shared between forks)
+    settings: Arc<Settings>,
+    /// Requests looked up via `find_route` or `respond` (shared by clones, not forks)
+    history: Arc<RequestHistory>,
+    /// State changes applied so far (shared by clones, not forks)
+    audit: Arc<AuditLog>,
 }
 
 impl MocksController {
@@ -54,36 +184,226 @@ impl MocksController {
     /// before passing it to the controller.
     pub fn new(mocks_manager: MocksManager) -> Self {
         Self {
-            mocks_manager,
+            mocks_manager: Arc::new(mocks_manager),
             active_collection_id: None,
-            cached_active_routes: Vec::new(),
+            route_table: Arc::new(RouteTable::default()),
+            overrides: Vec::new(),
+            disabled_routes: HashSet::new(),
+            listeners: Listeners::default(),
+            settings: Arc::new(Settings::default()),
+            history: Arc::default(),
+            audit: Arc::default(),
+        }
+    }
+
+    /// Create a controller from a loaded project.
+    ///
+    /// Keeps the project settings for the server and activates the default
+    /// collection if the settings define one.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let controller = MocksController::from_project(load_project("./mocks")?)?;
+    /// let port = controller.settings().port.unwrap_or(3100);
+    /// ```
+    pub fn from_project(project: Project) -> Result<Self, ResolveError> {
+        let mut controller = Self::new(project.manager);
+        if let Some(collection_id) = &project.settings.default_collection {
+            controller.use_collection(collection_id)?;
+        }
+        if let Some(capacity) = project.settings.history {
+            controller.history.set_capacity(capacity);
+        }
+        if let Some(delay) = project.settings.delay {
+            controller.history.set_delay(Duration::from_millis(delay));
+        }
+        controller.settings = Arc::new(project.settings);
+        Ok(controller)
+    }
+
+    /// Estimate the memory used by loaded definitions, in bytes.
+    ///
+    /// Forks share the definitions, so they report the same size. See
+    /// [`MocksManager::estimated_size`].
+    pub fn estimated_size(&self) -> usize {
+        self.mocks_manager.estimated_size()
+    }
+
+    /// Get the project settings (defaults for controllers not created from a project).
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Create an isolated child controller.
+    ///
+    /// The child shares the underlying manager data with this controller (no copy of
+    /// routes and collections is made) and starts from the current active collection
+    /// and routes. After that, state is independent: overrides applied to the child
+    /// don't affect the parent and vice versa. Change listeners are not inherited and
+    /// the child records requests in its own, initially empty history.
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.use_collection("base")?;
+    /// let mut worker = controller.fork();
+    /// worker.use_routes(&["users-api:error:not-found"])?; // parent is unaffected
+    /// ```
+    pub fn fork(&self) -> Self {
+        let history = RequestHistory::new(self.history.capacity());
+        history.set_delay(self.history.delay());
+        Self {
+            mocks_manager: Arc::clone(&self.mocks_manager),
+            active_collection_id: self.active_collection_id.clone(),
+            route_table: Arc::clone(&self.route_table),
+            overrides: self.overrides.clone(),
+            disabled_routes: self.disabled_routes.clone(),
+            listeners: Listeners::default(),
+            settings: Arc::clone(&self.settings),
+            history: Arc::new(history),
+            audit: Arc::default(),
+        }
+    }
+
+    /// Register a listener notified about every state change.
+    ///
+    /// Listeners are called synchronously after the change is applied.
+    /// Failed operations don't emit events.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let id = controller.on_change(|event| println!("{:?}", event));
+    /// controller.use_collection("base")?; // CollectionChanged { collection_id: "base" }
+    /// controller.remove_listener(id);
+    /// ```
+    pub fn on_change<F>(&mut self, listener: F) -> ListenerId
+    where
+        F: Fn(&ControllerEvent) + Send + Sync + 'static,
+    {
+        let id = ListenerId(self.listeners.next_id);
+        self.listeners.next_id += 1;
+        self.listeners.entries.push((id, Arc::new(listener)));
+        id
+    }
+
+    /// Remove a listener registered via `on_change`.
+    ///
+    /// Returns `false` if the listener was already removed.
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        let len = self.listeners.entries.len();
+        self.listeners
+            .entries
+            .retain(|(entry_id, _)| *entry_id != id);
+        self.listeners.entries.len() != len
+    }
+
+    /// Record a state change in the audit log and notify listeners.
+    fn emit(&self, event: ControllerEvent) {
+        self.audit.record(&event);
+        self.listeners.emit(event);
+    }
+
+    /// Label state changes applied from now on in the audit log, `None` removes the label.
+    ///
+    /// Set it to the name of the running test, so changes leaking into later
+    /// tests can be traced back to it.
+    pub fn set_audit_label(&self, label: Option<&str>) {
+        self.audit.set_label(label.map(String::from));
+    }
+
+    /// Get the state changes applied so far, oldest first.
+    ///
+    /// Every change that emits a [`ControllerEvent`] is recorded with its time
+    /// and the label set by [`set_audit_label`](Self::set_audit_label). Clones
+    /// share the log and it's kept on reload, forks start with an empty one.
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.set_audit_label(Some("checkout"));
+    /// controller.use_collection("base")?;
+    /// assert_eq!(controller.audit_log()[0].label.as_deref(), Some("checkout"));
+    /// ```
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.audit.entries()
+    }
+
+    /// Remove all state changes from the audit log, keeping the label.
+    pub fn clear_audit_log(&self) {
+        self.audit.clear();
+    }
+
+    /// Replace the underlying manager data.
+    ///
+    /// Re-resolves the active collection against the new data and re-applies
+    /// overrides in their original order. Overrides that are no longer compatible
+    /// (e.g. a route or variant was removed) are dropped. Disabled routes stay disabled.
+    ///
+    /// # Errors
+    /// Returns error if the active collection can't be resolved with the new data.
+    /// In that case, the controller keeps its previous data and state.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let manager = load_manager()?; // e.g. after files changed on disk
+    /// controller.reload(manager)?;
+    /// ```
+    pub fn reload(&mut self, mocks_manager: MocksManager) -> Result<(), ResolveError> {
+        let mut reloaded = Self::new(mocks_manager);
+        reloaded.disabled_routes = self.disabled_routes.clone();
+        reloaded.settings = Arc::clone(&self.settings);
+        reloaded.history = Arc::clone(&self.history);
+        if let Some(collection_id) = &self.active_collection_id {
+            reloaded.use_collection(collection_id)?;
         }
+
+        for override_ in &self.overrides {
+            // Incompatible overrides are skipped
+            let _ = reloaded.apply_override(override_);
+        }
+
+        // Replayed changes are neither emitted nor audited
+        reloaded.listeners = std::mem::take(&mut self.listeners);
+        reloaded.audit = Arc::clone(&self.audit);
+        *self = reloaded;
+        self.emit(ControllerEvent::Reloaded);
+        Ok(())
     }
 
     /// Activate a collection by ID.
     ///
-    /// This resolves the collection and caches the active routes for fast lookup.
+    /// This resolves the collection and builds a new route table for lookup.
     /// Returns error if collection not found or resolution fails.
     pub fn use_collection(&mut self, collection_id: &str) -> Result<(), ResolveError> {
+        self.activate_collection(collection_id)?;
+        self.emit(ControllerEvent::CollectionChanged {
+            collection_id: collection_id.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Resolve a collection and replace active routes with it, dropping overrides.
+    fn activate_collection(&mut self, collection_id: &str) -> Result<(), ResolveError> {
         let active_routes = self.mocks_manager.resolve_collection(collection_id)?;
         self.active_collection_id = Some(collection_id.to_string());
-        self.cached_active_routes = active_routes;
+        self.overrides.clear();
+        self.set_active_routes(active_routes);
         Ok(())
     }
 
     /// Apply specific HTTP routes without changing the entire collection.
     ///
     /// This method allows dynamic route switching by:
-    /// - Resolving provided route references (`route:preset:variant`)
+    /// - Resolving provided route references (`route:preset:variant`),
+    ///   expanding wildcard references like `payments-*:default:timeout`
     /// - Merging them with existing active routes
     /// - Overriding routes with the same route ID
     ///
     /// # Arguments
-    /// * `routes` - Array of route reference strings in format `route_id:preset_id:variant_id`
+    /// * `routes` - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
     ///
     /// # Errors
     /// Returns error if:
     /// - Route, preset, or variant not found
+    /// - Wildcard reference matches no HTTP routes
     /// - Route is a WebSocket route (use `use_socket` instead)
     ///
     /// # Example
@@ -93,43 +413,27 @@ impl MocksController {
     /// ```
     pub fn use_routes(&mut self, routes: &[String]) -> Result<(), ResolveError> {
         // Resolve all new routes first (fail fast if any route is invalid)
-        let mut new_routes: Vec<ActiveRoute> = Vec::with_capacity(routes.len());
-        for route_ref in routes {
-            let active_route = self.mocks_manager.resolve_http_route_reference(route_ref)?;
-            new_routes.push(active_route);
-        }
-
-        // Build a set of new route IDs for quick lookup
-        let new_route_ids: std::collections::HashSet<&str> =
-            new_routes.iter().map(|r| r.route.id.as_str()).collect();
-
-        // Merge: keep existing routes that are not overridden, then add new routes
-        let mut merged_routes: Vec<ActiveRoute> = self
-            .cached_active_routes
-            .iter()
-            .filter(|existing| !new_route_ids.contains(existing.route.id.as_str()))
-            .cloned()
-            .collect();
-
-        merged_routes.extend(new_routes);
-
-        self.cached_active_routes = merged_routes;
+        let new_routes = self.resolve_references(routes, Transport::Http)?;
+        self.merge_active_routes(new_routes)?;
+        self.record_route_overrides(routes, Transport::Http);
         Ok(())
     }
 
     /// Apply specific WebSocket routes without changing the entire collection.
     ///
     /// This method allows dynamic WebSocket route switching by:
-    /// - Resolving provided route references (`route:preset:variant`)
+    /// - Resolving provided route references (`route:preset:variant`),
+    ///   expanding wildcard references like `ws-*:default:error`
     /// - Merging them with existing active routes
     /// - Overriding routes with the same route ID
     ///
     /// # Arguments
-    /// * `routes` - Array of route reference strings in format `route_id:preset_id:variant_id`
+    /// * `routes` - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
     ///
     /// # Errors
     /// Returns error if:
     /// - Route, preset, or variant not found
+    /// - Wildcard reference matches no WebSocket routes
     /// - Route is not a WebSocket route (use `use_routes` instead)
     ///
     /// # Example
@@ -139,37 +443,297 @@ impl MocksController {
     /// ```
     pub fn use_socket(&mut self, routes: &[String]) -> Result<(), ResolveError> {
         // Resolve all new routes first (fail fast if any route is invalid)
-        let mut new_routes: Vec<ActiveRoute> = Vec::with_capacity(routes.len());
+        let new_routes = self.resolve_references(routes, Transport::WebSocket)?;
+        self.merge_active_routes(new_routes)?;
+        self.record_route_overrides(routes, Transport::WebSocket);
+        Ok(())
+    }
+
+    /// Resolve route references for a transport, expanding wildcard references.
+    ///
+    /// Wildcard references only expand to routes of the given transport, while
+    /// plain references to a route of another transport are rejected.
+    fn resolve_references(
+        &self,
+        routes: &[String],
+        transport: Transport,
+    ) -> Result<Vec<ActiveRoute>, ResolveError> {
+        let mut active_routes: Vec<ActiveRoute> = Vec::with_capacity(routes.len());
         for route_ref in routes {
-            let active_route = self
-                .mocks_manager
-                .resolve_websocket_route_reference(route_ref)?;
-            new_routes.push(active_route);
+            if RouteReference::parse(route_ref).is_some_and(|r| r.is_wildcard()) {
+                active_routes.extend(
+                    self.mocks_manager
+                        .resolve_wildcard_reference(route_ref, &transport)?,
+                );
+                continue;
+            }
+
+            let active_route = match transport {
+                Transport::Http | Transport::Sse | Transport::Tcp => {
+                    self.mocks_manager.resolve_http_route_reference(route_ref)?
+                }
+                Transport::WebSocket => self
+                    .mocks_manager
+                    .resolve_websocket_route_reference(route_ref)?,
+            };
+            active_routes.push(active_route);
+        }
+        Ok(active_routes)
+    }
+
+    /// Apply a preset and variant to every route tagged with `tag`.
+    ///
+    /// Works like `use_routes`/`use_socket` for all tagged routes at once,
+    /// so whole functional areas can be switched without enumerating references.
+    /// Routes of both transports are affected.
+    ///
+    /// # Errors
+    /// Returns error if:
+    /// - No route is tagged with `tag`
+    /// - A tagged route doesn't have the given preset or variant
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.use_collection("base")?;
+    /// controller.use_tag("payments", "default", "timeout")?;
+    /// ```
+    pub fn use_tag(
+        &mut self,
+        tag: &str,
+        preset_id: &str,
+        variant_id: &str,
+    ) -> Result<(), ResolveError> {
+        let tagged_routes = self.mocks_manager.routes_with_tag(tag);
+        if tagged_routes.is_empty() {
+            return Err(ResolveError::TagNotFound {
+                tag: tag.to_string(),
+            });
+        }
+
+        // Resolve all tagged routes first (fail fast if any route is invalid)
+        let mut new_routes: Vec<ActiveRoute> = Vec::with_capacity(tagged_routes.len());
+        for route in tagged_routes {
+            let route_ref = RouteReference {
+                route_id: route.id.to_string(),
+                preset_id: Some(preset_id.to_string()),
+                variant_id: Some(variant_id.to_string()),
+            };
+            new_routes.push(self.mocks_manager.resolve_parsed_reference(&route_ref)?);
+        }
+
+        self.merge_active_routes(new_routes)?;
+        self.overrides.push(Override::Tag {
+            tag: tag.to_string(),
+            preset_id: preset_id.to_string(),
+            variant_id: variant_id.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Deactivate every active route tagged with `tag`.
+    ///
+    /// Requests to the removed routes no longer match until the routes are
+    /// activated again (e.g. via `use_tag`, `use_routes` or `reset_routes`).
+    ///
+    /// # Errors
+    /// Returns error if no route is tagged with `tag`.
+    pub fn disable_tag(&mut self, tag: &str) -> Result<(), ResolveError> {
+        if self.mocks_manager.routes_with_tag(tag).is_empty() {
+            return Err(ResolveError::TagNotFound {
+                tag: tag.to_string(),
+            });
+        }
+
+        let remaining_routes = self
+            .route_table
+            .routes()
+            .iter()
+            .filter(|active_route| !active_route.route.tags.iter().any(|t| t == tag))
+            .cloned()
+            .collect();
+        self.set_active_routes(remaining_routes);
+        self.overrides.push(Override::DisableTag {
+            tag: tag.to_string(),
+        });
+        self.emit(ControllerEvent::TagDisabled {
+            tag: tag.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Disable routes by ID.
+    ///
+    /// Disabled routes stay active but are skipped during matching, so requests
+    /// fall through to other routes or get no match at all. Routes stay disabled
+    /// across collection switches until enabled again or `reset_routes` is called.
+    ///
+    /// # Errors
+    /// Returns error if any route is not found (no route is disabled in that case).
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.disable_routes(&["users-api".to_string()])?;
+    /// // Requests to users-api now get no match
+    /// controller.enable_routes(&["users-api".to_string()])?;
+    /// ```
+    pub fn disable_routes(&mut self, route_ids: &[String]) -> Result<(), ResolveError> {
+        self.check_routes_exist(route_ids)?;
+        self.disabled_routes.extend(route_ids.iter().cloned());
+        self.rebuild_route_table();
+        self.emit(ControllerEvent::RoutesDisabled {
+            route_ids: route_ids.to_vec(),
+        });
+        Ok(())
+    }
+
+    /// Enable routes previously disabled via `disable_routes`.
+    ///
+    /// # Errors
+    /// Returns error if any route is not found (no route is enabled in that case).
+    pub fn enable_routes(&mut self, route_ids: &[String]) -> Result<(), ResolveError> {
+        self.check_routes_exist(route_ids)?;
+        for route_id in route_ids {
+            self.disabled_routes.remove(route_id);
+        }
+        self.rebuild_route_table();
+        self.emit(ControllerEvent::RoutesEnabled {
+            route_ids: route_ids.to_vec(),
+        });
+        Ok(())
+    }
+
+    /// Get IDs of disabled routes, sorted.
+    pub fn disabled_routes(&self) -> Vec<&str> {
+        let mut route_ids: Vec<&str> = self.disabled_routes.iter().map(String::as_str).collect();
+        route_ids.sort_unstable();
+        route_ids
+    }
+
+    /// Check that all routes exist in the manager.
+    fn check_routes_exist(&self, route_ids: &[String]) -> Result<(), ResolveError> {
+        match route_ids
+            .iter()
+            .find(|route_id| self.mocks_manager.get_route(route_id).is_none())
+        {
+            Some(route_id) => Err(ResolveError::RouteNotFound {
+                route_id: route_id.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Record route references applied via `use_routes`/`use_socket`.
+    ///
+    /// References are recorded one by one, so a single incompatible reference
+    /// doesn't prevent the others from being re-applied on reload.
+    fn record_route_overrides(&mut self, routes: &[String], transport: Transport) {
+        self.overrides
+            .extend(routes.iter().map(|reference| Override::Route {
+                reference: reference.clone(),
+                transport: transport.clone(),
+            }));
+    }
+
+    /// Apply a recorded override.
+    fn apply_override(&mut self, override_: &Override) -> Result<(), ResolveError> {
+        match override_ {
+            Override::Route {
+                reference,
+                transport: Transport::Http | Transport::Sse | Transport::Tcp,
+            } => self.use_routes(std::slice::from_ref(reference)),
+            Override::Route {
+                reference,
+                transport: Transport::WebSocket,
+            } => self.use_socket(std::slice::from_ref(reference)),
+            Override::Tag {
+                tag,
+                preset_id,
+                variant_id,
+            } => self.use_tag(tag, preset_id, variant_id),
+            Override::DisableTag { tag } => self.disable_tag(tag),
+        }
+    }
+
+    /// Merge resolved routes into the active routes.
+    ///
+    /// Keeps existing routes that are not overridden, then appends new routes.
+    /// Routes with the same route ID are overridden.
+    /// Variables of the active collection are substituted into new routes.
+    /// Listeners are notified with the IDs of the new routes.
+    fn merge_active_routes(
+        &mut self,
+        mut new_routes: Vec<ActiveRoute>,
+    ) -> Result<(), ResolveError> {
+        if let Some(collection_id) = &self.active_collection_id {
+            self.mocks_manager
+                .apply_collection_vars(collection_id, &mut new_routes)?;
         }
 
         // Build a set of new route IDs for quick lookup
         let new_route_ids: std::collections::HashSet<&str> =
-            new_routes.iter().map(|r| r.route.id.as_str()).collect();
+            new_routes.iter().map(|r| &*r.route.id).collect();
 
         // Merge: keep existing routes that are not overridden, then add new routes
         let mut merged_routes: Vec<ActiveRoute> = self
-            .cached_active_routes
+            .route_table
+            .routes()
             .iter()
-            .filter(|existing| !new_route_ids.contains(existing.route.id.as_str()))
+            .filter(|existing| !new_route_ids.contains(&*existing.route.id))
             .cloned()
             .collect();
 
+        let route_ids = new_routes.iter().map(|r| r.route.id.to_string()).collect();
         merged_routes.extend(new_routes);
 
-        self.cached_active_routes = merged_routes;
+        self.set_active_routes(merged_routes);
+        self.emit(ControllerEvent::RoutesOverridden { route_ids });
         Ok(())
     }
 
+    /// Replace active routes with a freshly built route table.
+    ///
+    /// Tables handed out earlier via `route_table()` are left untouched.
+    fn set_active_routes(&mut self, active_routes: Vec<ActiveRoute>) {
+        self.route_table = Arc::new(
+            RouteTable::with_disabled(active_routes, self.disabled_routes.clone())
+                .with_limits(self.settings.limits.unwrap_or_default())
+                .with_partials(self.settings.partials.clone().unwrap_or_default())
+                .with_match_cache(self.settings.match_cache.unwrap_or(0)),
+        );
+    }
+
+    /// Cache the route matched by up to `capacity` distinct requests, `None` disables caching.
+    ///
+    /// Overrides the `match_cache` setting. The cache is cleared on every change
+    /// of active routes, see [`RouteTable::with_match_cache`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.set_match_cache(Some(1024));
+    /// ```
+    pub fn set_match_cache(&mut self, capacity: Option<usize>) {
+        Arc::make_mut(&mut self.settings).match_cache = capacity;
+        self.rebuild_route_table();
+    }
+
+    /// Rebuild the route table from current routes, e.g. after disabled routes change.
+    fn rebuild_route_table(&mut self) {
+        self.set_active_routes(self.route_table.routes().to_vec());
+    }
+
     /// Get all currently active routes.
     ///
-    /// Returns cached active routes from the current collection.
+    /// Returns active routes from the current route table.
     pub fn get_active_routes(&self) -> &[ActiveRoute] {
-        &self.cached_active_routes
+        self.route_table.routes()
+    }
+
+    /// Get the current route table.
+    ///
+    /// The table is an immutable snapshot: it stays valid while the controller
+    /// changes and can be shared across threads for lock-free lookup.
+    pub fn route_table(&self) -> Arc<RouteTable> {
+        Arc::clone(&self.route_table)
     }
 
     /// Get currently active collection ID.
@@ -183,9 +747,11 @@ impl MocksController {
     ///
     /// If a collection is selected, restores routes to the collection's initial state.
     /// If no collection is selected, clears all routes (empty state).
+    /// Disabled routes are enabled again in both cases.
     ///
     /// # Errors
-    /// Returns error if collection resolution fails (should not happen for already-selected collection).
+    /// Returns error if collection resolution fails (should not happen for already-selected collection),
+    /// leaving routes, overrides and disabled routes unchanged.
     ///
     /// # Example
     /// ```ignore
@@ -194,139 +760,278 @@ impl MocksController {
     /// controller.reset_routes()?; // Restores to "base" collection state
     /// ```
     pub fn reset_routes(&mut self) -> Result<(), ResolveError> {
-        if let Some(collection_id) = self.active_collection_id.clone() {
+        // Resolve before clearing anything, so a failed reset changes nothing
+        let active_routes = match &self.active_collection_id {
             // Restore to collection state
-            self.use_collection(&collection_id)
-        } else {
+            Some(collection_id) => self.mocks_manager.resolve_collection(collection_id)?,
             // No collection selected - clear all routes
-            self.cached_active_routes.clear();
-            Ok(())
+            None => Vec::new(),
+        };
+        self.disabled_routes.clear();
+        self.overrides.clear();
+        self.set_active_routes(active_routes);
+        self.emit(ControllerEvent::RoutesReset);
+        Ok(())
+    }
+
+    /// Capture the current controller state.
+    ///
+    /// The snapshot holds the active collection and all active routes, including
+    /// overrides applied via `use_routes`, `use_socket` or `use_tag`. It also holds
+    /// the request history with unmatched requests and serve times, so requests made
+    /// after the snapshot aren't verified once it's restored. Coverage hit counts are
+    /// cumulative and the audit log is append-only, so neither is captured.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let snapshot = controller.snapshot();
+    /// controller.use_routes(&["users-api:error:not-found"])?;
+    /// controller.restore(snapshot); // Back to the captured state
+    /// ```
+    pub fn snapshot(&self) -> ControllerSnapshot {
+        ControllerSnapshot {
+            active_collection_id: self.active_collection_id.clone(),
+            route_table: Arc::clone(&self.route_table),
+            overrides: self.overrides.clone(),
+            disabled_routes: self.disabled_routes.clone(),
+            history: self.history.state(),
         }
     }
 
+    /// Restore controller state from a snapshot.
+    ///
+    /// Routes are restored exactly as captured, without re-resolving the collection.
+    /// The request history is rolled back to the captured one. The audit log keeps
+    /// the changes made since the snapshot, followed by the restore.
+    pub fn restore(&mut self, snapshot: ControllerSnapshot) {
+        self.active_collection_id = snapshot.active_collection_id;
+        self.route_table = snapshot.route_table;
+        self.overrides = snapshot.overrides;
+        self.disabled_routes = snapshot.disabled_routes;
+        self.history.restore(snapshot.history);
+        self.emit(ControllerEvent::Restored);
+    }
+
     /// Find a route that matches the given request.
     ///
-    /// Searches through cached active routes and returns the first matching route.
+    /// Searches the current route table and returns the first matching route.
     /// Matching is performed in order: URL, method, transport, headers, query, payload.
     ///
-    /// Returns `None` if no matching route is found.
+    /// Returns `None` if no matching route is found. The request is recorded
+    /// in the request history.
     pub fn find_route(&self, request: &Request) -> Option<&ActiveRoute> {
-        self.cached_active_routes
-            .iter()
-            .find(|active_route| self.route_matches_request(active_route, request))
+        let started = Instant::now();
+        let found = self.route_table.find_route(request);
+        self.history
+            .record(&self.route_table, request, found, None, started.elapsed());
+        found
     }
 
-    /// Check if an active route matches the given request.
+    /// Find all routes that match the given request.
     ///
-    /// Matches transport, method, URL, headers, query, and payload.
-    /// Supports JMESPath expressions for query and payload matching.
-    fn route_matches_request(&self, active_route: &ActiveRoute, request: &Request) -> bool {
-        let route = &active_route.route;
-        let preset = &active_route.preset;
+    /// Returns every matching route in priority order. The first one is the
+    /// route returned by `find_route`, the rest are shadowed by it.
+    pub fn find_routes(&self, request: &Request) -> Vec<&ActiveRoute> {
+        self.route_table.find_routes(request)
+    }
 
-        // Check transport
-        if route.transport != request.transport {
-            return false;
-        }
+    /// Find the route matching the given request and render its variant.
+    ///
+    /// Variants without their own template engine use the `template` setting.
+    ///
+    /// Returns `None` if no matching route is found, or error if a template of
+    /// the variant fails to render. The request is recorded in the request history.
+    pub fn respond(&self, request: &Request) -> Result<Option<Variant>, TemplateError> {
+        let started = Instant::now();
+        let default = self.settings.template.unwrap_or_default();
+        let found = self.route_table.find_route_with_context(request);
+        let active_route = found.as_ref().map(|(active_route, _)| *active_route);
+        let result = found
+            .map(|(active_route, context)| self.route_table.render(active_route, &context, default))
+            .transpose();
+        let response = result.as_ref().ok().and_then(Option::as_ref);
+        self.history.record(
+            &self.route_table,
+            request,
+            active_route,
+            response,
+            started.elapsed(),
+        );
+        result
+    }
 
-        // Check HTTP method (for HTTP routes)
-        if route.transport == Transport::Http {
-            if let Some(route_method) = &route.method {
-                if let Some(request_method) = &request.method {
-                    if route_method != request_method {
-                        return false;
-                    }
-                } else {
-                    return false; // Route requires method but request doesn't have it
-                }
-            }
-        }
+    /// Build the 404 response of a request that matched no route.
+    ///
+    /// With the `debug` setting enabled, the body lists the routes closest to
+    /// matching the request and the condition each of them failed.
+    pub fn not_found(&self, request: &Request) -> Variant {
+        let near_matches = self
+            .settings
+            .debug
+            .unwrap_or(false)
+            .then(|| self.route_table.nearest(request, NEAR_MATCH_LIMIT));
+        not_found(request, near_matches.as_deref())
+    }
 
-        // Check URL pattern
-        let url_result = url_matches(&route.url, &request.url);
-        if !url_result.matched {
-            return false;
-        }
+    /// Get the request history, shared with bindings recording their own lookups.
+    pub fn history(&self) -> Arc<RequestHistory> {
+        Arc::clone(&self.history)
+    }
 
-        // Check URL path parameters (from preset.params)
-        if let Some(expected_params) = &preset.params {
-            // URL params are extracted from URL pattern matching
-            // Check if all expected params are present in matched params
-            for (key, expected_value) in expected_params {
-                if let Some(actual_value) = url_result.params.get(key) {
-                    if actual_value != expected_value {
-                        return false;
-                    }
-                } else {
-                    return false; // Expected param not found
-                }
-            }
-        }
+    /// Get requests looked up via `find_route` or `respond`, oldest first.
+    ///
+    /// Only the latest requests are kept, see the `history` setting.
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.respond(&request)?;
+    /// assert_eq!(controller.get_requests().len(), 1);
+    /// ```
+    pub fn get_requests(&self) -> Vec<RecordedRequest> {
+        self.history.requests()
+    }
 
-        // Check headers
-        let empty_headers = HashMap::new();
-        let request_headers = request.headers.as_ref().unwrap_or(&empty_headers);
-        if !headers_matches(preset.headers.as_ref(), request_headers) {
-            return false;
-        }
+    /// Get recorded requests that matched a route, oldest first.
+    pub fn get_requests_for(&self, route_id: &str) -> Vec<RecordedRequest> {
+        self.history.requests_for(route_id)
+    }
 
-        // Check query parameters
-        let request_query = if let Some(query) = request.query.as_ref() {
-            query
-        } else {
-            // Parse query from URL if not provided separately
-            let parsed_query = if let Some(query_str) = request.url.split('?').nth(1) {
-                parse_query_string(query_str)
-            } else {
-                HashMap::new()
-            };
-            // Use helper method to avoid lifetime issues with temporary
-            if !self.check_query_with_parsed(preset, Some(&parsed_query)) {
-                return false;
-            }
-            // Continue to payload check
-            return self.check_payload(preset, &request.payload);
-        };
+    /// Remove all requests from the request history, with their serve times.
+    pub fn clear_history(&self) {
+        self.history.clear();
+    }
 
-        if !query_matches(preset.query.as_ref(), request_query) {
-            return false;
-        }
+    /// Verify the recorded requests that matched a route.
+    ///
+    /// The verification covers requests recorded up to this call.
+    ///
+    /// # Example
+    /// ```ignore
+    /// controller.verify("users").times(2)?;
+    /// controller.verify("users").with_payload(json!({"name": "Ann"})).once()?;
+    /// ```
+    pub fn verify(&self, route_id: &str) -> Verification {
+        Verification::new(route_id, self.history.requests_for(route_id))
+    }
 
-        // Check payload/body
-        self.check_payload(preset, &request.payload)
+    /// Check that every recorded request matched a route.
+    ///
+    /// # Errors
+    /// Returns [`VerificationError::UnmatchedRequests`] listing the requests that didn't.
+    pub fn verify_no_unmatched(&self) -> Result<(), VerificationError> {
+        verify_no_unmatched(&self.history.requests())
     }
 
-    /// Check query parameters with parsed query from URL.
-    fn check_query_with_parsed(
-        &self,
-        preset: &Preset,
-        parsed_query: Option<&HashMap<String, String>>,
-    ) -> bool {
-        let empty_query = HashMap::new();
-        query_matches(preset.query.as_ref(), parsed_query.unwrap_or(&empty_query))
-    }
-
-    /// Check request payload/body.
-    ///
-    /// Returns `false` if preset expects payload but request doesn't have it.
-    fn check_payload(&self, preset: &Preset, request_payload: &Option<Value>) -> bool {
-        if let Some(request_payload) = request_payload {
-            payload_matches(preset.payload.as_ref(), request_payload)
-        } else if preset.payload.is_some() {
-            // Preset expects payload but request doesn't have it
-            false
-        } else {
-            true
-        }
+    /// Get requests that matched no route, grouped by transport, method and URL path.
+    ///
+    /// Unmatched requests are captured apart from the request history, so they're
+    /// kept when the history is full or disabled. See [`UnmatchedRequest::suggestion`]
+    /// for a route skeleton answering a request.
+    pub fn get_unmatched(&self) -> Vec<UnmatchedRequest> {
+        self.history.unmatched().requests()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::types::collection::Collection;
-    use crate::types::preset::{
-        HeadersOrExpression, PayloadOrExpression, Preset, QueryOrExpression,
+    /// Render route skeletons answering the requests that matched no route.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Paste into a routes file to mock the missing endpoints
+    /// println!("{}", controller.unmatched_report(ExportFormat::Yaml)?);
+    /// ```
+    pub fn unmatched_report(&self, format: ExportFormat) -> Result<String, ConfigError> {
+        self.history.unmatched().report(format)
+    }
+
+    /// Render the request history with the served responses as a HAR log, in JSON.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Open in browser devtools or attach to a bug report
+    /// std::fs::write("traffic.har", controller.export_har())?;
+    /// ```
+    pub fn export_har(&self) -> String {
+        export_har(&self.history.requests())
+    }
+
+    /// Receive a [`LiveEvent`] for every request looked up from now on.
+    ///
+    /// Meant for the admin WebSocket at [`LIVE_PATH`](crate::mocks::tap::LIVE_PATH),
+    /// which sends each event as a JSON text frame. Events are streamed
+    /// regardless of the history capacity.
+    pub fn subscribe_live(&self) -> broadcast::Receiver<LiveEvent> {
+        self.history.tap().subscribe()
+    }
+
+    /// Get serve time percentiles of the routes that answered requests.
+    ///
+    /// Serve times cover route lookup, response rendering and the injected
+    /// `delay`, so a perf test can tell mock latency from client-side time.
+    /// Unlike the request history they aggregate every request since the last
+    /// [`clear_history`](Self::clear_history).
+    ///
+    /// # Example
+    /// ```ignore
+    /// for route in controller.latency_stats() {
+    ///     println!("{}: p95 {:?}", route.route_id, route.stats.p95);
+    /// }
+    /// ```
+    pub fn latency_stats(&self) -> Vec<RouteLatency> {
+        self.history.latencies().routes()
+    }
+
+    /// Report the routes, presets and variants that answered requests so far.
+    ///
+    /// Hit counts are kept when the request history is cleared or the mocks are
+    /// reloaded, so they cover a whole test run. Routes that no longer exist
+    /// aren't reported.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let report = controller.coverage_report();
+    /// assert!(report.untouched().is_empty(), "{}", report);
+    /// ```
+    pub fn coverage_report(&self) -> CoverageReport {
+        self.history.coverage().report(&self.mocks_manager)
+    }
+
+    /// Reset the hit counts of [`coverage_report`](Self::coverage_report).
+    pub fn reset_coverage(&self) {
+        self.history.coverage().clear();
+    }
+
+    /// Keep up to `capacity` requests in the request history, `None` restores the default.
+    ///
+    /// Overrides the `history` setting. A capacity of 0 disables recording.
+    pub fn set_history_capacity(&self, capacity: Option<usize>) {
+        self.history
+            .set_capacity(capacity.unwrap_or(DEFAULT_HISTORY_CAPACITY));
+    }
+
+    /// Report expression errors raised while matching the given request.
+    ///
+    /// Routes with a failing expression never match, so this explains why a
+    /// request falls through to a later route or matches nothing.
+    pub fn diagnose(&self, request: &Request) -> Vec<ExpressionDiagnostic> {
+        self.route_table.diagnose(request)
+    }
+
+    /// Explain how every active route matches the given request.
+    ///
+    /// Reports the first condition each route fails with expected and actual
+    /// values, see [`RouteTable::explain`].
+    pub fn explain(&self, request: &Request) -> Vec<RouteExplanation> {
+        self.route_table.explain(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::table::MatchStep;
+    use crate::mocks::tap::LiveEventKind;
+    use crate::types::collection::Collection;
+    use crate::types::preset::{
+        HeadersOrExpression, PayloadOrExpression, Preset, QueryOrExpression,
     };
     use crate::types::route::{HttpMethod, Route, Transport};
     use crate::types::variant::Variant;
@@ -336,17 +1041,22 @@ mod tests {
 
     fn create_test_route(id: &str, url: &str) -> Route {
         Route {
-            id: id.to_string(),
+            id: id.into(),
             url: url.to_string(),
             transport: Transport::Http,
             method: Some(HttpMethod::Get),
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         }
     }
 
     fn create_test_preset(id: &str) -> Preset {
         Preset {
-            id: id.to_string(),
+            id: id.into(),
             params: None,
             query: None,
             headers: None,
@@ -357,10 +1067,11 @@ mod tests {
 
     fn create_test_variant(id: &str) -> Variant {
         Variant {
-            id: id.to_string(),
+            id: id.into(),
             status: Some(200),
             headers: None,
             body: None,
+            template: None,
         }
     }
 
@@ -372,6 +1083,41 @@ mod tests {
         assert_eq!(controller.get_active_routes().len(), 0);
     }
 
+    #[rstest]
+    fn test_from_project() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("route1", "/api/users");
+        let mut preset = create_test_preset("preset1");
+        preset.variants.push(create_test_variant("variant1"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        manager
+            .add_collection(Collection {
+                id: "base".into(),
+                from: None,
+                vars: None,
+                routes: vec!["route1:preset1:variant1".into()],
+            })
+            .unwrap();
+        let settings = Settings {
+            port: Some(3100),
+            default_collection: Some("base".to_string()),
+            ..Settings::default()
+        };
+
+        let controller = MocksController::from_project(Project {
+            manager,
+            settings: settings.clone(),
+            issues: vec![],
+            sources: HashMap::new(),
+        })
+        .unwrap();
+        assert_eq!(controller.active_collection_id(), Some("base"));
+        assert_eq!(controller.get_active_routes().len(), 1);
+        assert_eq!(controller.settings(), &settings);
+        assert_eq!(controller.fork().settings(), &settings);
+    }
+
     #[rstest]
     fn test_use_collection() {
         // Create manager and add routes/collections
@@ -380,14 +1126,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Create controller with manager
         let mut controller = MocksController::new(manager);
@@ -420,24 +1167,25 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2", "/api/posts");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
+            vars: None,
             routes: vec![
-                "route1:preset1:variant1".to_string(),
-                "route2:preset2:variant2".to_string(),
+                "route1:preset1:variant1".into(),
+                "route2:preset2:variant2".into(),
             ],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -446,8 +1194,8 @@ mod tests {
         // Get active routes
         let active_routes = controller.get_active_routes();
         assert_eq!(active_routes.len(), 2);
-        assert_eq!(active_routes[0].route.id, "route1");
-        assert_eq!(active_routes[1].route.id, "route2");
+        assert_eq!(active_routes[0].route.id, "route1".into());
+        assert_eq!(active_routes[1].route.id, "route2".into());
     }
 
     #[rstest]
@@ -459,15 +1207,16 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -485,7 +1234,7 @@ mod tests {
 
         let found = controller.find_route(&request);
         assert!(found.is_some());
-        assert_eq!(found.unwrap().route.id, "route1");
+        assert_eq!(found.unwrap().route.id, "route1".into());
     }
 
     #[rstest]
@@ -500,15 +1249,16 @@ mod tests {
         preset.params = Some(params);
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -553,15 +1303,16 @@ mod tests {
         preset.headers = Some(HeadersOrExpression::Map(headers));
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -610,15 +1361,16 @@ mod tests {
         preset.query = Some(QueryOrExpression::Map(query));
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -664,15 +1416,16 @@ mod tests {
         preset.payload = Some(PayloadOrExpression::Value(json!({"name": "John"})));
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -714,15 +1467,16 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Activate collection
         let mut controller = MocksController::new(manager);
@@ -751,39 +1505,41 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2", "/api/posts");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         // Create collections
         let collection1 = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection1);
+        manager.add_collection(collection1).unwrap();
 
         let collection2 = Collection {
-            id: "collection2".to_string(),
+            id: "collection2".into(),
             from: None,
-            routes: vec!["route2:preset2:variant2".to_string()],
+            vars: None,
+            routes: vec!["route2:preset2:variant2".into()],
         };
-        manager.add_collection(collection2);
+        manager.add_collection(collection2).unwrap();
 
         // Activate first collection
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
 
         // Switch to second collection
         controller.use_collection("collection2").unwrap();
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route2");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route2".into());
     }
 
     #[rstest]
@@ -793,14 +1549,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         assert_eq!(controller.active_collection_id(), None);
@@ -809,7 +1566,7 @@ mod tests {
         // Activate collection to verify manager data is used
         controller.use_collection("collection1").unwrap();
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
     }
 
     #[rstest]
@@ -818,23 +1575,29 @@ mod tests {
 
         // Create WebSocket route
         let mut route = Route {
-            id: "route1".to_string(),
+            id: "route1".into(),
             url: "/ws".to_string(),
             transport: Transport::WebSocket,
             method: None,
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         };
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
@@ -862,14 +1625,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
@@ -897,14 +1661,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
@@ -933,14 +1698,15 @@ mod tests {
         preset.payload = Some(PayloadOrExpression::Value(json!({"name": "John"})));
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
@@ -964,23 +1730,29 @@ mod tests {
 
         // Create WebSocket route
         let mut route = Route {
-            id: "route1".to_string(),
+            id: "route1".into(),
             url: "/ws".to_string(),
             transport: Transport::WebSocket,
             method: None,
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         };
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
@@ -996,7 +1768,7 @@ mod tests {
 
         let found = controller.find_route(&request);
         assert!(found.is_some());
-        assert_eq!(found.unwrap().route.id, "route1");
+        assert_eq!(found.unwrap().route.id, "route1".into());
     }
 
     // ============ use_routes tests ============
@@ -1011,21 +1783,25 @@ mod tests {
         preset.variants.push(create_test_variant("variant1"));
         preset.variants.push(create_test_variant("variant2"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
         // Initial state
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].variant.id, "variant1");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant1".into()
+        );
 
         // Switch to variant2 using use_routes
         controller
@@ -1034,7 +1810,10 @@ mod tests {
 
         // Should still have 1 route but with variant2
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].variant.id, "variant2");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant2".into()
+        );
     }
 
     #[rstest]
@@ -1046,27 +1825,28 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2", "/api/posts");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
         // Initial state: only route1
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
 
         // Add route2 using use_routes
         controller
@@ -1078,7 +1858,7 @@ mod tests {
         let route_ids: Vec<&str> = controller
             .get_active_routes()
             .iter()
-            .map(|r| r.route.id.as_str())
+            .map(|r| r.route.id.as_ref())
             .collect();
         assert!(route_ids.contains(&"route1"));
         assert!(route_ids.contains(&"route2"));
@@ -1099,20 +1879,24 @@ mod tests {
 
         route.presets.push(preset1);
         route.presets.push(preset2);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
         // Initial: preset1
-        assert_eq!(controller.get_active_routes()[0].preset.id, "preset1");
+        assert_eq!(
+            controller.get_active_routes()[0].preset().id,
+            "preset1".into()
+        );
 
         // Override with preset2
         controller
@@ -1121,7 +1905,10 @@ mod tests {
 
         // Should have 1 route with preset2 (not 2 routes)
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].preset.id, "preset2");
+        assert_eq!(
+            controller.get_active_routes()[0].preset().id,
+            "preset2".into()
+        );
     }
 
     #[rstest]
@@ -1132,7 +1919,7 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1144,7 +1931,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
     }
 
     #[rstest]
@@ -1166,7 +1953,7 @@ mod tests {
         let mut manager = MocksManager::new();
 
         let route = create_test_route("route1", "/api/users");
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1187,7 +1974,7 @@ mod tests {
         let preset = create_test_preset("preset1");
         // No variants
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1205,7 +1992,7 @@ mod tests {
         let manager = MocksManager::new();
         let mut controller = MocksController::new(manager);
 
-        let result = controller.use_routes(&["invalid-format".to_string()]);
+        let result = controller.use_routes(&["route1:preset1:variant1:extra".to_string()]);
 
         assert!(result.is_err());
         assert!(matches!(
@@ -1224,29 +2011,27 @@ mod tests {
         preset1.variants.push(create_test_variant("v1"));
         preset1.variants.push(create_test_variant("v2"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2", "/api/posts");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("v1"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         let mut route3 = create_test_route("route3", "/api/comments");
         let mut preset3 = create_test_preset("preset3");
         preset3.variants.push(create_test_variant("v1"));
         route3.presets.push(preset3);
-        manager.add_route(route3);
+        manager.add_route(route3).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec![
-                "route1:preset1:v1".to_string(),
-                "route2:preset2:v1".to_string(),
-            ],
+            vars: None,
+            routes: vec!["route1:preset1:v1".into(), "route2:preset2:v1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1263,13 +2048,22 @@ mod tests {
         assert_eq!(controller.get_active_routes().len(), 3);
 
         let routes = controller.get_active_routes();
-        let route1 = routes.iter().find(|r| r.route.id == "route1").unwrap();
-        let route2 = routes.iter().find(|r| r.route.id == "route2").unwrap();
-        let route3 = routes.iter().find(|r| r.route.id == "route3").unwrap();
+        let route1 = routes
+            .iter()
+            .find(|r| r.route.id == "route1".into())
+            .unwrap();
+        let route2 = routes
+            .iter()
+            .find(|r| r.route.id == "route2".into())
+            .unwrap();
+        let route3 = routes
+            .iter()
+            .find(|r| r.route.id == "route3".into())
+            .unwrap();
 
-        assert_eq!(route1.variant.id, "v2"); // Overridden
-        assert_eq!(route2.variant.id, "v1"); // Original
-        assert_eq!(route3.variant.id, "v1"); // New
+        assert_eq!(route1.variant().id, "v2".into()); // Overridden
+        assert_eq!(route2.variant().id, "v1".into()); // Original
+        assert_eq!(route3.variant().id, "v1".into()); // New
     }
 
     #[rstest]
@@ -1280,14 +2074,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1303,7 +2098,7 @@ mod tests {
 
         // Original routes should remain unchanged (fail fast)
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
     }
 
     #[rstest]
@@ -1312,16 +2107,21 @@ mod tests {
 
         // Create WebSocket route
         let mut ws_route = Route {
-            id: "ws-route".to_string(),
+            id: "ws-route".into(),
             url: "/ws".to_string(),
             transport: Transport::WebSocket,
             method: None,
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         };
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         ws_route.presets.push(preset);
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1339,11 +2139,16 @@ mod tests {
 
     fn create_test_ws_route(id: &str, url: &str) -> Route {
         Route {
-            id: id.to_string(),
+            id: id.into(),
             url: url.to_string(),
             transport: Transport::WebSocket,
             method: None,
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         }
     }
 
@@ -1355,7 +2160,7 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         ws_route.presets.push(preset);
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1365,7 +2170,10 @@ mod tests {
             .unwrap();
 
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "ws-route");
+        assert_eq!(
+            controller.get_active_routes()[0].route.id,
+            "ws-route".into()
+        );
         assert_eq!(
             controller.get_active_routes()[0].route.transport,
             Transport::WebSocket
@@ -1382,20 +2190,24 @@ mod tests {
         preset.variants.push(create_test_variant("message"));
         preset.variants.push(create_test_variant("error"));
         ws_route.presets.push(preset);
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["ws-route:default:message".to_string()],
+            vars: None,
+            routes: vec!["ws-route:default:message".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
         // Initial state
-        assert_eq!(controller.get_active_routes()[0].variant.id, "message");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "message".into()
+        );
 
         // Switch to error variant
         controller
@@ -1403,7 +2215,10 @@ mod tests {
             .unwrap();
 
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].variant.id, "error");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "error".into()
+        );
     }
 
     #[rstest]
@@ -1415,20 +2230,21 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         ws_route1.presets.push(preset1);
-        manager.add_route(ws_route1);
+        manager.add_route(ws_route1).unwrap();
 
         let mut ws_route2 = create_test_ws_route("ws-route2", "/ws/2");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         ws_route2.presets.push(preset2);
-        manager.add_route(ws_route2);
+        manager.add_route(ws_route2).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["ws-route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["ws-route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1443,7 +2259,7 @@ mod tests {
         let route_ids: Vec<&str> = controller
             .get_active_routes()
             .iter()
-            .map(|r| r.route.id.as_str())
+            .map(|r| r.route.id.as_ref())
             .collect();
         assert!(route_ids.contains(&"ws-route1"));
         assert!(route_ids.contains(&"ws-route2"));
@@ -1458,7 +2274,7 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         http_route.presets.push(preset);
-        manager.add_route(http_route);
+        manager.add_route(http_route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1493,7 +2309,7 @@ mod tests {
         let mut manager = MocksManager::new();
 
         let ws_route = create_test_ws_route("ws-route", "/ws");
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1514,7 +2330,7 @@ mod tests {
         let preset = create_test_preset("preset1");
         // No variants
         ws_route.presets.push(preset);
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1535,14 +2351,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         ws_route.presets.push(preset);
-        manager.add_route(ws_route);
+        manager.add_route(ws_route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["ws-route:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["ws-route:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1558,7 +2375,10 @@ mod tests {
 
         // Original routes should remain unchanged
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "ws-route");
+        assert_eq!(
+            controller.get_active_routes()[0].route.id,
+            "ws-route".into()
+        );
     }
 
     #[rstest]
@@ -1570,13 +2390,13 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("v1"));
         ws_route1.presets.push(preset1);
-        manager.add_route(ws_route1);
+        manager.add_route(ws_route1).unwrap();
 
         let mut ws_route2 = create_test_ws_route("ws-route2", "/ws/2");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("v1"));
         ws_route2.presets.push(preset2);
-        manager.add_route(ws_route2);
+        manager.add_route(ws_route2).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1603,33 +2423,43 @@ mod tests {
         preset.variants.push(create_test_variant("variant1"));
         preset.variants.push(create_test_variant("variant2"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
 
         // Initial state
-        assert_eq!(controller.get_active_routes()[0].variant.id, "variant1");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant1".into()
+        );
 
         // Change variant
         controller
             .use_routes(&["route1:preset1:variant2".to_string()])
             .unwrap();
-        assert_eq!(controller.get_active_routes()[0].variant.id, "variant2");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant2".into()
+        );
 
         // Reset to collection state
         controller.reset_routes().unwrap();
 
         // Should be back to variant1
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].variant.id, "variant1");
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant1".into()
+        );
     }
 
     #[rstest]
@@ -1640,7 +2470,7 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let mut controller = MocksController::new(manager);
 
@@ -1665,14 +2495,15 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1690,6 +2521,35 @@ mod tests {
         assert_eq!(controller.active_collection_id(), Some("collection1"));
     }
 
+    #[rstest]
+    fn test_reset_routes_failure_keeps_state() {
+        let mut manager = MocksManager::new();
+
+        let mut route = create_test_route("route1", "/api/users");
+        let mut preset = create_test_preset("preset1");
+        preset.variants.push(create_test_variant("variant1"));
+        preset.variants.push(create_test_variant("variant2"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        let mut controller = MocksController::new(manager);
+        controller
+            .use_routes(&["route1:preset1:variant2".to_string()])
+            .unwrap();
+        controller.disable_routes(&["route1".to_string()]).unwrap();
+
+        // Selected collection no longer resolves
+        controller.active_collection_id = Some("missing".to_string());
+        assert!(controller.reset_routes().is_err());
+
+        assert_eq!(controller.disabled_routes(), vec!["route1"]);
+        assert_eq!(controller.overrides.len(), 1);
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "variant2".into()
+        );
+    }
+
     #[rstest]
     fn test_reset_routes_on_empty_controller() {
         let manager = MocksManager::new();
@@ -1718,21 +2578,22 @@ mod tests {
         preset2.variants.push(create_test_variant("v2"));
         route.presets.push(preset1);
         route.presets.push(preset2);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create another route
         let mut route2 = create_test_route("route2", "/api/posts");
         let mut preset3 = create_test_preset("preset3");
         preset3.variants.push(create_test_variant("v3"));
         route2.presets.push(preset3);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:v1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:v1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let mut controller = MocksController::new(manager);
         controller.use_collection("collection1").unwrap();
@@ -1753,8 +2614,855 @@ mod tests {
 
         // Should be back to original collection state (1 route with preset1)
         assert_eq!(controller.get_active_routes().len(), 1);
-        assert_eq!(controller.get_active_routes()[0].route.id, "route1");
-        assert_eq!(controller.get_active_routes()[0].preset.id, "preset1");
-        assert_eq!(controller.get_active_routes()[0].variant.id, "v1");
+        assert_eq!(controller.get_active_routes()[0].route.id, "route1".into());
+        assert_eq!(
+            controller.get_active_routes()[0].preset().id,
+            "preset1".into()
+        );
+        assert_eq!(controller.get_active_routes()[0].variant().id, "v1".into());
+    }
+
+    // ============ use_tag / disable_tag tests ============
+
+    fn create_tagged_manager() -> MocksManager {
+        let mut manager = MocksManager::new();
+
+        for (id, url) in [("pay1", "/api/pay/1"), ("pay2", "/api/pay/2")] {
+            let mut route = create_test_route(id, url);
+            route.tags = vec!["payments".to_string()];
+            let mut preset = create_test_preset("default");
+            preset.variants.push(create_test_variant("ok"));
+            preset.variants.push(create_test_variant("timeout"));
+            route.presets.push(preset);
+            manager.add_route(route).unwrap();
+        }
+
+        let mut route = create_test_route("users", "/api/users");
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        let collection = Collection {
+            id: "base".into(),
+            from: None,
+            vars: None,
+            routes: vec![
+                "pay1:default:ok".into(),
+                "pay2:default:ok".into(),
+                "users:default:ok".into(),
+            ],
+        };
+        manager.add_collection(collection).unwrap();
+        manager
+    }
+
+    #[rstest]
+    fn test_use_tag_switches_all_tagged_routes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        controller
+            .use_tag("payments", "default", "timeout")
+            .unwrap();
+
+        let routes = controller.get_active_routes();
+        assert_eq!(routes.len(), 3);
+        for route in routes {
+            let expected = if route.route.id == "users".into() {
+                "ok"
+            } else {
+                "timeout"
+            };
+            assert_eq!(route.variant().id, expected.into());
+        }
+    }
+
+    #[rstest]
+    fn test_use_tag_not_found() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        let result = controller.use_tag("unknown", "default", "ok");
+        assert!(matches!(
+            result.unwrap_err(),
+            ResolveError::TagNotFound { .. }
+        ));
+    }
+
+    #[rstest]
+    fn test_use_tag_fail_fast_on_missing_variant() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        let result = controller.use_tag("payments", "default", "nonexistent");
+        assert!(matches!(
+            result.unwrap_err(),
+            ResolveError::VariantNotFound { .. }
+        ));
+        assert!(controller
+            .get_active_routes()
+            .iter()
+            .all(|r| r.variant().id == "ok".into()));
+    }
+
+    #[rstest]
+    fn test_disable_tag_removes_tagged_routes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        controller.disable_tag("payments").unwrap();
+
+        assert_eq!(controller.get_active_routes().len(), 1);
+        assert_eq!(controller.get_active_routes()[0].route.id, "users".into());
+
+        // Reset restores the collection state
+        controller.reset_routes().unwrap();
+        assert_eq!(controller.get_active_routes().len(), 3);
+    }
+
+    #[rstest]
+    fn test_disable_tag_not_found() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        assert!(matches!(
+            controller.disable_tag("unknown").unwrap_err(),
+            ResolveError::TagNotFound { .. }
+        ));
+    }
+
+    // ============ snapshot / restore tests ============
+
+    #[rstest]
+    fn test_snapshot_restore_rolls_back_overrides() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        let snapshot = controller.snapshot();
+        let initial_routes = controller.get_active_routes().to_vec();
+
+        controller
+            .use_tag("payments", "default", "timeout")
+            .unwrap();
+        controller.disable_tag("payments").unwrap();
+        assert_ne!(controller.get_active_routes(), initial_routes.as_slice());
+
+        controller.restore(snapshot);
+        assert_eq!(controller.active_collection_id(), Some("base"));
+        assert_eq!(controller.get_active_routes(), initial_routes.as_slice());
+    }
+
+    #[rstest]
+    fn test_snapshot_restore_collection() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        let snapshot = controller.snapshot();
+        assert_eq!(snapshot.active_collection_id(), None);
+
+        controller.use_collection("base").unwrap();
+        controller.restore(snapshot.clone());
+        assert_eq!(controller.active_collection_id(), None);
+        assert!(controller.get_active_routes().is_empty());
+
+        // Snapshot can be applied multiple times
+        controller.use_collection("base").unwrap();
+        controller.restore(snapshot);
+        assert!(controller.get_active_routes().is_empty());
+    }
+
+    #[rstest]
+    fn test_snapshot_restore_history() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.respond(&create_users_request()).unwrap();
+        let snapshot = controller.snapshot();
+        let audit = controller.audit_log();
+
+        controller.respond(&create_users_request()).unwrap();
+        controller
+            .respond(&Request {
+                url: "/api/missing".to_string(),
+                ..create_users_request()
+            })
+            .unwrap();
+        controller
+            .use_tag("payments", "default", "timeout")
+            .unwrap();
+        assert!(controller.verify("users").times(2).is_ok());
+
+        controller.restore(snapshot);
+        assert!(controller.verify("users").times(1).is_ok());
+        assert!(controller.get_unmatched().is_empty());
+        assert_eq!(controller.latency_stats()[0].stats.count, 1);
+        // Changes made since the snapshot stay in the audit log
+        let log = controller.audit_log();
+        assert_eq!(log[..audit.len()], audit[..]);
+        assert_eq!(log.len(), audit.len() + 2);
+        assert!(matches!(
+            log[audit.len()].event,
+            ControllerEvent::RoutesOverridden { .. }
+        ));
+        assert!(matches!(
+            log.last().unwrap().event,
+            ControllerEvent::Restored
+        ));
+    }
+
+    // ============ fork tests ============
+
+    #[rstest]
+    fn test_fork_shares_manager() {
+        let controller = MocksController::new(create_tagged_manager());
+        let fork = controller.fork();
+        assert!(Arc::ptr_eq(&controller.mocks_manager, &fork.mocks_manager));
+    }
+
+    #[rstest]
+    fn test_fork_has_independent_state() {
+        let mut parent = MocksController::new(create_tagged_manager());
+        parent.use_collection("base").unwrap();
+
+        let mut child = parent.fork();
+        assert_eq!(child.active_collection_id(), Some("base"));
+        assert_eq!(child.get_active_routes(), parent.get_active_routes());
+
+        child.use_tag("payments", "default", "timeout").unwrap();
+        assert!(parent
+            .get_active_routes()
+            .iter()
+            .all(|route| route.variant().id == "ok".into()));
+
+        parent.disable_tag("payments").unwrap();
+        assert_eq!(parent.get_active_routes().len(), 1);
+        assert_eq!(child.get_active_routes().len(), 3);
+    }
+
+    // ============ route table tests ============
+
+    #[rstest]
+    fn test_route_table_unaffected_by_changes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        let table = controller.route_table();
+        controller.disable_tag("payments").unwrap();
+
+        assert_eq!(table.routes().len(), 3);
+        assert_eq!(controller.route_table().routes().len(), 1);
+    }
+
+    // ============ wildcard reference tests ============
+
+    #[rstest]
+    fn test_use_routes_wildcard() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        controller
+            .use_routes(&["pay*:default:timeout".to_string()])
+            .unwrap();
+
+        let routes = controller.get_active_routes();
+        assert_eq!(routes.len(), 3);
+        for route in routes {
+            let expected = if route.route.id == "users".into() {
+                "ok"
+            } else {
+                "timeout"
+            };
+            assert_eq!(route.variant().id, expected.into());
+        }
+    }
+
+    #[rstest]
+    fn test_use_socket_wildcard_ignores_http_routes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+
+        let result = controller.use_socket(&["*:default:ok".to_string()]);
+        assert!(matches!(
+            result.unwrap_err(),
+            ResolveError::NoWildcardMatches { .. }
+        ));
+        assert!(controller.get_active_routes().is_empty());
+    }
+
+    #[rstest]
+    fn test_use_routes_applies_collection_vars() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users", "/api/{{vars.tenant}}/users");
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        preset.variants.push(create_test_variant("error"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        manager
+            .add_collection(Collection {
+                id: "acme".into(),
+                from: None,
+                vars: Some(crate::vars::Vars::from([(
+                    "tenant".to_string(),
+                    json!("acme"),
+                )])),
+                routes: vec!["users".into()],
+            })
+            .unwrap();
+
+        let mut controller = MocksController::new(manager);
+        controller.use_collection("acme").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+
+        let routes = controller.get_active_routes();
+        assert_eq!(routes[0].variant().id, "error".into());
+        assert_eq!(routes[0].route.url, "/api/acme/users");
+    }
+
+    // ============ reload tests ============
+
+    fn create_reload_manager(variants: &[&str]) -> MocksManager {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users", "/api/users");
+        let mut preset = create_test_preset("default");
+        for variant_id in variants {
+            preset.variants.push(create_test_variant(variant_id));
+        }
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        manager
+            .add_collection(Collection {
+                id: "base".into(),
+                from: None,
+                vars: None,
+                routes: vec!["users:default:ok".into()],
+            })
+            .unwrap();
+        manager
+    }
+
+    #[rstest]
+    fn test_reload_reapplies_overrides() {
+        let mut controller = MocksController::new(create_reload_manager(&["ok", "error"]));
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+
+        let mut manager = create_reload_manager(&["ok", "error"]);
+        let mut route = create_test_route("posts", "/api/posts");
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        controller.reload(manager).unwrap();
+
+        assert_eq!(controller.active_collection_id(), Some("base"));
+        assert_eq!(controller.get_active_routes().len(), 1);
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "error".into()
+        );
+
+        // New data is available for further changes
+        controller
+            .use_routes(&["posts:default:ok".to_string()])
+            .unwrap();
+        assert_eq!(controller.get_active_routes().len(), 2);
+    }
+
+    #[rstest]
+    fn test_reload_drops_incompatible_overrides() {
+        let mut controller = MocksController::new(create_reload_manager(&["ok", "error"]));
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+
+        controller
+            .reload(create_reload_manager(&["ok", "timeout"]))
+            .unwrap();
+        assert_eq!(controller.get_active_routes()[0].variant().id, "ok".into());
+
+        // Dropped override is not re-applied on later reloads
+        controller
+            .reload(create_reload_manager(&["ok", "error"]))
+            .unwrap();
+        assert_eq!(controller.get_active_routes()[0].variant().id, "ok".into());
+    }
+
+    #[rstest]
+    fn test_reload_keeps_state_on_error() {
+        let mut controller = MocksController::new(create_reload_manager(&["ok", "error"]));
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+
+        let result = controller.reload(MocksManager::new());
+        assert!(matches!(
+            result.unwrap_err(),
+            ResolveError::CollectionNotFound { .. }
+        ));
+        assert_eq!(
+            controller.get_active_routes()[0].variant().id,
+            "error".into()
+        );
+    }
+
+    #[rstest]
+    fn test_reset_routes_clears_overrides_for_reload() {
+        let mut controller = MocksController::new(create_reload_manager(&["ok", "error"]));
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+        controller.reset_routes().unwrap();
+
+        controller
+            .reload(create_reload_manager(&["ok", "error"]))
+            .unwrap();
+        assert_eq!(controller.get_active_routes()[0].variant().id, "ok".into());
+    }
+
+    // ============ disable_routes / enable_routes tests ============
+
+    fn create_users_request() -> Request {
+        Request {
+            url: "/api/users".to_string(),
+            method: Some(HttpMethod::Get),
+            transport: Transport::Http,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    #[rstest]
+    fn test_disable_and_enable_routes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        controller.disable_routes(&["users".to_string()]).unwrap();
+        assert_eq!(controller.disabled_routes(), vec!["users"]);
+        assert_eq!(controller.get_active_routes().len(), 3);
+        assert!(controller.find_route(&create_users_request()).is_none());
+
+        // Disabled routes stay disabled across collection switches and overrides
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:ok".to_string()])
+            .unwrap();
+        assert!(controller.find_route(&create_users_request()).is_none());
+
+        controller.enable_routes(&["users".to_string()]).unwrap();
+        assert!(controller.disabled_routes().is_empty());
+        assert!(controller.find_route(&create_users_request()).is_some());
+    }
+
+    #[rstest]
+    fn test_match_cache_cleared_on_changes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.set_match_cache(Some(16));
+        controller.use_collection("base").unwrap();
+        let request = create_users_request();
+
+        assert!(controller.find_route(&request).is_some());
+        assert_eq!(controller.route_table().cached_matches(), 1);
+
+        controller.disable_routes(&["users".to_string()]).unwrap();
+        assert_eq!(controller.route_table().cached_matches(), 0);
+        assert!(controller.find_route(&request).is_none());
+
+        controller.enable_routes(&["users".to_string()]).unwrap();
+        assert!(controller.find_route(&request).is_some());
+
+        controller.set_match_cache(None);
+        assert!(controller.find_route(&request).is_some());
+        assert_eq!(controller.route_table().cached_matches(), 0);
+    }
+
+    #[rstest]
+    fn test_history_records_requests() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        let unmatched = Request {
+            url: "/api/missing".to_string(),
+            ..create_users_request()
+        };
+
+        controller.find_route(&create_users_request());
+        controller.respond(&unmatched).unwrap();
+        controller.respond(&create_users_request()).unwrap();
+
+        let requests = controller.get_requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].route_id.as_deref(), Some("users"));
+        assert_eq!(requests[0].preset_id.as_deref(), Some("default"));
+        assert_eq!(requests[0].variant_id.as_deref(), Some("ok"));
+        assert_eq!(requests[1].request, unmatched);
+        assert!(!requests[1].is_matched());
+        assert_eq!(controller.get_requests_for("users").len(), 2);
+        assert!(controller.get_requests_for("pay1").is_empty());
+
+        controller.clear_history();
+        assert!(controller.get_requests().is_empty());
+    }
+
+    #[rstest]
+    fn test_verify_requests() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        let request = Request {
+            url: "/api/users?page=2".to_string(),
+            ..create_users_request()
+        };
+
+        controller.respond(&request).unwrap();
+        controller.respond(&create_users_request()).unwrap();
+
+        assert!(controller.verify("users").times(2).is_ok());
+        assert!(controller.verify("pay1").never().is_ok());
+        let page = HashMap::from([("page".to_string(), "2".to_string())]);
+        assert!(controller.verify("users").with_query(page).once().is_ok());
+        assert!(controller.verify_no_unmatched().is_ok());
+
+        controller.find_route(&Request {
+            url: "/api/missing".to_string(),
+            ..create_users_request()
+        });
+        assert!(matches!(
+            controller.verify_no_unmatched(),
+            Err(VerificationError::UnmatchedRequests { requests }) if requests.len() == 1
+        ));
+    }
+
+    #[rstest]
+    fn test_unmatched_captured_without_history() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.set_history_capacity(Some(0));
+        let missing = Request {
+            url: "/api/orders?status=open".to_string(),
+            ..create_users_request()
+        };
+
+        controller.find_route(&missing);
+        controller.respond(&missing).unwrap();
+        controller.respond(&create_users_request()).unwrap();
+
+        assert!(controller.get_requests().is_empty());
+        let unmatched = controller.get_unmatched();
+        assert_eq!(unmatched.len(), 1);
+        assert_eq!(unmatched[0].count, 2);
+        assert_eq!(&*unmatched[0].suggestion().id, "get-api-orders");
+        let report = controller.unmatched_report(ExportFormat::Yaml).unwrap();
+        assert!(report.contains("url: /api/orders\n"));
+
+        controller.clear_history();
+        assert!(controller.get_unmatched().is_empty());
+    }
+
+    #[rstest]
+    fn test_unmatched_near_matches() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        let wrong_method = Request {
+            method: Some(HttpMethod::Post),
+            ..create_users_request()
+        };
+
+        assert!(controller.find_route(&wrong_method).is_none());
+
+        let recorded = &controller.get_requests()[0];
+        let nearest = &recorded.near_matches[0];
+        assert_eq!(nearest.route_id, "users");
+        assert_eq!(nearest.failure.as_ref().unwrap().step, MatchStep::Method);
+        let body = controller.not_found(&wrong_method).body.unwrap();
+        assert!(body.get("near_matches").is_none());
+
+        controller.settings = Arc::new(Settings {
+            debug: Some(true),
+            ..Settings::default()
+        });
+        let body = controller.not_found(&wrong_method).body.unwrap();
+        assert_eq!(body["near_matches"][0]["route_id"], "users");
+        assert_eq!(body["near_matches"][0]["step"], "method");
+    }
+
+    #[rstest]
+    fn test_export_har() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        let response = controller.respond(&create_users_request()).unwrap();
+
+        let har: Value = serde_json::from_str(&controller.export_har()).unwrap();
+
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["_routeId"], "users");
+        assert_eq!(
+            entries[0]["response"]["status"],
+            response.as_ref().unwrap().status.unwrap_or(200)
+        );
+        assert_eq!(controller.get_requests()[0].response, response);
+    }
+
+    #[rstest]
+    fn test_latency_stats() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.history.set_delay(Duration::from_millis(100));
+        controller.respond(&create_users_request()).unwrap();
+        controller.find_route(&create_users_request());
+
+        let recorded = controller.get_requests();
+        assert_eq!(recorded[0].delay, Duration::from_millis(100));
+        let stats = controller.latency_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].route_id, "users");
+        assert_eq!(stats[0].stats.count, 2);
+        assert!(stats[0].stats.min >= Duration::from_millis(100));
+        assert_eq!(stats[0].variants[0].stats, stats[0].stats);
+        assert_eq!(
+            controller.fork().history().delay(),
+            Duration::from_millis(100)
+        );
+
+        controller.clear_history();
+        assert!(controller.latency_stats().is_empty());
+    }
+
+    #[rstest]
+    fn test_subscribe_live() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.set_history_capacity(Some(0));
+        let mut events = controller.subscribe_live();
+
+        let response = controller.respond(&create_users_request()).unwrap();
+        controller.find_route(&Request {
+            url: "/api/missing".to_string(),
+            ..create_users_request()
+        });
+
+        let matched = events.try_recv().unwrap();
+        assert_eq!(matched.kind, LiveEventKind::Matched);
+        assert_eq!(matched.route_id.as_deref(), Some("users"));
+        assert_eq!(matched.status, response.unwrap().status.or(Some(200)));
+        let unmatched = events.try_recv().unwrap();
+        assert_eq!(unmatched.kind, LiveEventKind::Unmatched);
+        assert_eq!(unmatched.url, "/api/missing");
+        assert!(events.try_recv().is_err());
+    }
+
+    #[rstest]
+    fn test_audit_log() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.set_audit_label(Some("test a"));
+        controller
+            .use_routes(&["users:default:ok".to_string()])
+            .unwrap();
+        assert!(controller.use_collection("missing").is_err());
+        controller.reload(create_tagged_manager()).unwrap();
+        controller.set_audit_label(None);
+        controller.reset_routes().unwrap();
+
+        let log = controller.audit_log();
+        let events: Vec<_> = log.iter().map(|entry| &entry.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                &ControllerEvent::CollectionChanged {
+                    collection_id: "base".to_string()
+                },
+                &ControllerEvent::RoutesOverridden {
+                    route_ids: vec!["users".to_string()]
+                },
+                &ControllerEvent::Reloaded,
+                &ControllerEvent::RoutesReset,
+            ]
+        );
+        let labels: Vec<_> = log.iter().map(|entry| entry.label.as_deref()).collect();
+        assert_eq!(labels, vec![None, Some("test a"), Some("test a"), None]);
+        assert!(controller.fork().audit_log().is_empty());
+
+        controller.clear_audit_log();
+        assert!(controller.audit_log().is_empty());
+    }
+
+    #[rstest]
+    fn test_coverage_report() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.set_history_capacity(Some(0));
+        controller.respond(&create_users_request()).unwrap();
+        controller.find_route(&create_users_request());
+
+        controller.clear_history();
+        controller.reload(create_tagged_manager()).unwrap();
+
+        let report = controller.coverage_report();
+        let users = report
+            .routes
+            .iter()
+            .find(|r| r.route_id == "users")
+            .unwrap();
+        assert_eq!(users.hits, 2);
+        assert_eq!(users.presets[0].variants[0].hits, 2);
+        assert_eq!(report.untouched(), vec!["pay1", "pay2"]);
+        assert_eq!(report.covered_variants(), (1, 5));
+
+        controller.reset_coverage();
+        assert_eq!(controller.coverage_report().covered_variants(), (0, 5));
+    }
+
+    #[rstest]
+    fn test_history_kept_on_reload_not_forked() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.set_history_capacity(Some(1));
+        controller.find_route(&create_users_request());
+        controller.find_route(&create_users_request());
+
+        let fork = controller.fork();
+        assert!(fork.get_requests().is_empty());
+        assert_eq!(fork.history().capacity(), 1);
+
+        controller.reload(create_tagged_manager()).unwrap();
+        assert_eq!(controller.get_requests().len(), 1);
+    }
+
+    #[rstest]
+    fn test_disable_routes_not_found() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+
+        let result = controller.disable_routes(&["users".to_string(), "missing".to_string()]);
+        assert_eq!(
+            result.unwrap_err(),
+            ResolveError::RouteNotFound {
+                route_id: "missing".to_string()
+            }
+        );
+        assert!(controller.disabled_routes().is_empty());
+    }
+
+    #[rstest]
+    fn test_reset_routes_enables_disabled_routes() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        controller.use_collection("base").unwrap();
+        controller.disable_routes(&["users".to_string()]).unwrap();
+
+        controller.reset_routes().unwrap();
+        assert!(controller.disabled_routes().is_empty());
+        assert!(controller.find_route(&create_users_request()).is_some());
+    }
+
+    #[rstest]
+    fn test_find_routes_returns_all_matches() {
+        let mut manager = create_tagged_manager();
+        let mut route = create_test_route("users-any", "/api/{resource}");
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        let mut controller = MocksController::new(manager);
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users-any:default:ok".to_string()])
+            .unwrap();
+
+        let found: Vec<&str> = controller
+            .find_routes(&create_users_request())
+            .iter()
+            .map(|r| r.route.id.as_ref())
+            .collect();
+        assert_eq!(found, vec!["users", "users-any"]);
+        assert_eq!(
+            controller
+                .find_route(&create_users_request())
+                .unwrap()
+                .route
+                .id,
+            found[0].into()
+        );
+    }
+
+    // ============ change listener tests ============
+
+    fn record_events(
+        controller: &mut MocksController,
+    ) -> Arc<std::sync::Mutex<Vec<ControllerEvent>>> {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        controller.on_change(move |event| sink.lock().unwrap().push(event.clone()));
+        events
+    }
+
+    #[rstest]
+    fn test_on_change_emits_events() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        let events = record_events(&mut controller);
+
+        controller.use_collection("base").unwrap();
+        controller
+            .use_tag("payments", "default", "timeout")
+            .unwrap();
+        controller.disable_routes(&["users".to_string()]).unwrap();
+        controller.reset_routes().unwrap();
+        // Failed operations emit nothing
+        assert!(controller.use_collection("unknown").is_err());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ControllerEvent::CollectionChanged {
+                    collection_id: "base".to_string()
+                },
+                ControllerEvent::RoutesOverridden {
+                    route_ids: vec!["pay1".to_string(), "pay2".to_string()]
+                },
+                ControllerEvent::RoutesDisabled {
+                    route_ids: vec!["users".to_string()]
+                },
+                ControllerEvent::RoutesReset,
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_on_change_survives_reload() {
+        let mut controller = MocksController::new(create_reload_manager(&["ok", "error"]));
+        controller.use_collection("base").unwrap();
+        controller
+            .use_routes(&["users:default:error".to_string()])
+            .unwrap();
+        let events = record_events(&mut controller);
+
+        controller
+            .reload(create_reload_manager(&["ok", "error"]))
+            .unwrap();
+        controller.reset_routes().unwrap();
+
+        // Overrides re-applied during reload are not reported separately
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![ControllerEvent::Reloaded, ControllerEvent::RoutesReset]
+        );
+    }
+
+    #[rstest]
+    fn test_remove_listener() {
+        let mut controller = MocksController::new(create_tagged_manager());
+        let events = record_events(&mut controller);
+        let id = controller.on_change(|_| panic!("listener should be removed"));
+
+        assert!(controller.remove_listener(id));
+        assert!(!controller.remove_listener(id));
+
+        // Forks don't inherit listeners
+        let mut fork = controller.fork();
+        fork.use_collection("base").unwrap();
+        assert!(events.lock().unwrap().is_empty());
+
+        controller.use_collection("base").unwrap();
+        assert_eq!(events.lock().unwrap().len(), 1);
     }
 }
@@ -0,0 +1,287 @@
+//! Coverage of defined routes by matched requests.
+//!
+//! [`Coverage`] counts how often each route, preset and variant answered a
+//! request. Unlike the request history it is unbounded, so it covers a whole
+//! test run. [`Coverage::report`] lists every route defined in a manager with
+//! its hit counts, so routes, presets and variants that never answered a
+//! request can be pruned, or error paths nobody tests can be found:
+//!
+//! ```ignore
+//! let report = controller.coverage_report();
+//! for reference in report.untouched() {
+//!     println!("never matched: {}", reference);
+//! }
+//! ```
+
+use crate::mocks::manager::{ActiveRoute, MocksManager};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Route, preset and variant IDs
+pub(crate) type VariantKey = (Arc<str>, Arc<str>, Arc<str>);
+
+/// Hit counts of matched variants.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    hits: Mutex<HashMap<VariantKey, usize>>,
+}
+
+impl Coverage {
+    /// Count a request answered by an active route.
+    pub fn record(&self, active_route: &ActiveRoute) {
+        let key = (
+            Arc::clone(&active_route.route.id),
+            Arc::clone(&active_route.preset().id),
+            Arc::clone(&active_route.variant().id),
+        );
+        *self
+            .hits
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_default() += 1;
+    }
+
+    /// Report the coverage of all routes defined in a manager.
+    pub fn report(&self, manager: &MocksManager) -> CoverageReport {
+        let hits = self.hits.lock().unwrap_or_else(PoisonError::into_inner);
+        let routes = manager
+            .list_routes()
+            .into_iter()
+            .map(|route| {
+                let presets: Vec<_> = route
+                    .presets
+                    .iter()
+                    .map(|preset| {
+                        let variants: Vec<_> = preset
+                            .variants
+                            .iter()
+                            .map(|variant| {
+                                let key = (
+                                    Arc::clone(&route.id),
+                                    Arc::clone(&preset.id),
+                                    Arc::clone(&variant.id),
+                                );
+                                VariantCoverage {
+                                    variant_id: variant.id.to_string(),
+                                    hits: hits.get(&key).copied().unwrap_or(0),
+                                }
+                            })
+                            .collect();
+                        PresetCoverage {
+                            preset_id: preset.id.to_string(),
+                            hits: variants.iter().map(|v| v.hits).sum(),
+                            variants,
+                        }
+                    })
+                    .collect();
+                RouteCoverage {
+                    route_id: route.id.to_string(),
+                    hits: presets.iter().map(|p| p.hits).sum(),
+                    presets,
+                }
+            })
+            .collect();
+        CoverageReport { routes }
+    }
+
+    /// Reset all hit counts.
+    pub fn clear(&self) {
+        self.hits
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Hit counts of all defined routes, see [`Coverage::report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Routes sorted by ID
+    pub routes: Vec<RouteCoverage>,
+}
+
+/// Hit counts of a route and its presets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteCoverage {
+    pub route_id: String,
+    /// Number of requests the route answered
+    pub hits: usize,
+    pub presets: Vec<PresetCoverage>,
+}
+
+/// Hit counts of a preset and its variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetCoverage {
+    pub preset_id: String,
+    /// Number of requests the preset answered
+    pub hits: usize,
+    pub variants: Vec<VariantCoverage>,
+}
+
+/// Hit count of a variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantCoverage {
+    pub variant_id: String,
+    /// Number of requests the variant answered
+    pub hits: usize,
+}
+
+impl CoverageReport {
+    /// Get references of everything that never answered a request.
+    ///
+    /// Untouched routes are listed as `route`, untouched presets of matched
+    /// routes as `route:preset` and untouched variants of matched presets as
+    /// `route:preset:variant`.
+    pub fn untouched(&self) -> Vec<String> {
+        let mut untouched = Vec::new();
+        for route in &self.routes {
+            if route.hits == 0 {
+                untouched.push(route.route_id.clone());
+                continue;
+            }
+            for preset in &route.presets {
+                if preset.hits == 0 {
+                    untouched.push(format!("{}:{}", route.route_id, preset.preset_id));
+                    continue;
+                }
+                for variant in preset.variants.iter().filter(|v| v.hits == 0) {
+                    untouched.push(format!(
+                        "{}:{}:{}",
+                        route.route_id, preset.preset_id, variant.variant_id
+                    ));
+                }
+            }
+        }
+        untouched
+    }
+
+    /// Get the number of variants that answered a request and of all variants.
+    pub fn covered_variants(&self) -> (usize, usize) {
+        let variants = self
+            .routes
+            .iter()
+            .flat_map(|route| &route.presets)
+            .flat_map(|preset| &preset.variants);
+        variants.fold((0, 0), |(covered, total), variant| {
+            (covered + usize::from(variant.hits > 0), total + 1)
+        })
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (covered, total) = self.covered_variants();
+        let percent = if total == 0 {
+            100.0
+        } else {
+            covered as f64 * 100.0 / total as f64
+        };
+        write!(
+            f,
+            "{} of {} variants matched ({:.0}%)",
+            covered, total, percent
+        )?;
+        let untouched = self.untouched();
+        if !untouched.is_empty() {
+            write!(f, "\nNever matched:")?;
+        }
+        for reference in untouched {
+            write!(f, "\n  {}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::preset::Preset;
+    use crate::types::route::{HttpMethod, Route, Transport};
+    use crate::types::variant::Variant;
+    use rstest::rstest;
+
+    fn variant(id: &str) -> Variant {
+        Variant {
+            id: id.into(),
+            status: Some(200),
+            headers: None,
+            body: None,
+            template: None,
+        }
+    }
+
+    fn preset(id: &str, variants: &[&str]) -> Preset {
+        Preset {
+            id: id.into(),
+            params: None,
+            query: None,
+            headers: None,
+            payload: None,
+            variants: variants.iter().map(|id| variant(id)).collect(),
+        }
+    }
+
+    fn route(id: &str, presets: Vec<Preset>) -> Route {
+        Route {
+            id: id.into(),
+            url: format!("/{}", id),
+            transport: Transport::Http,
+            method: Some(HttpMethod::Get),
+            presets,
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
+        }
+    }
+
+    fn active(route: &Route, preset: usize, variant: usize) -> ActiveRoute {
+        let preset = &route.presets[preset];
+        ActiveRoute::new(
+            route.clone(),
+            preset.clone(),
+            preset.variants[variant].clone(),
+        )
+    }
+
+    #[rstest]
+    fn test_report() {
+        let users = route(
+            "users",
+            vec![
+                preset("default", &["ok", "error"]),
+                preset("admin", &["ok"]),
+            ],
+        );
+        let orders = route("orders", vec![preset("default", &["ok"])]);
+        let mut manager = MocksManager::new();
+        manager.add_route(users.clone()).unwrap();
+        manager.add_route(orders).unwrap();
+        let coverage = Coverage::default();
+
+        coverage.record(&active(&users, 0, 0));
+        coverage.record(&active(&users, 0, 0));
+
+        let report = coverage.report(&manager);
+        let users = &report.routes[1];
+        assert_eq!(users.hits, 2);
+        assert_eq!(users.presets[0].variants[0].hits, 2);
+        assert_eq!(users.presets[0].variants[1].hits, 0);
+        assert_eq!(
+            report.untouched(),
+            vec!["orders", "users:default:error", "users:admin"]
+        );
+        assert_eq!(report.covered_variants(), (1, 4));
+        assert_eq!(
+            report.to_string(),
+            "1 of 4 variants matched (25%)\nNever matched:\n  orders\n  \
+             users:default:error\n  users:admin"
+        );
+
+        coverage.clear();
+        assert_eq!(coverage.report(&manager).covered_variants(), (0, 4));
+    }
+}
@@ -0,0 +1,464 @@
+//! Export of the request history as a HAR file.
+//!
+//! [`export_har`] renders recorded requests and the responses they were served
+//! as an [HTTP Archive 1.2](http://www.softwareishard.com/blog/har-12-spec/) log,
+//! which browser devtools and HAR viewers open directly. Each entry also carries
+//! the matched `_routeId`, `_presetId` and `_variantId`.
+//!
+//! Recorded URLs are paths, so entries are made absolute with the request `Host`
+//! header, or `localhost` without one. Requests that matched no route are
+//! exported with the 404 response built by
+//! [`not_found`](crate::mocks::unmatched::not_found).
+
+use crate::matching::parse_query_string;
+use crate::mocks::controller::Request;
+use crate::mocks::history::RecordedRequest;
+use crate::mocks::unmatched::not_found;
+use crate::types::route::{HttpMethod, Transport};
+use crate::types::variant::Variant;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the HAR format written by [`export_har`]
+pub const HAR_VERSION: &str = "1.2";
+
+/// Render recorded requests as a HAR log, in JSON.
+///
+/// Requests answered by [`MocksController::respond`](crate::mocks::controller::MocksController::respond)
+/// hold the rendered response. Requests only looked up by `find_route` have no
+/// response and are exported with status 0.
+pub fn export_har(requests: &[RecordedRequest]) -> String {
+    let har = Har {
+        log: Log {
+            version: HAR_VERSION,
+            creator: Creator {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            entries: requests.iter().map(entry).collect(),
+        },
+    };
+    // Entries only hold strings, numbers and JSON values, which always serialize
+    serde_json::to_string_pretty(&har).expect("HAR log is serializable") + "\n"
+}
+
+#[derive(Serialize)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Serialize)]
+struct Log {
+    version: &'static str,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    /// Total time in milliseconds
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: Value,
+    timings: Timings,
+    #[serde(rename = "_routeId", skip_serializing_if = "Option::is_none")]
+    route_id: Option<String>,
+    #[serde(rename = "_presetId", skip_serializing_if = "Option::is_none")]
+    preset_id: Option<String>,
+    #[serde(rename = "_variantId", skip_serializing_if = "Option::is_none")]
+    variant_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: &'static str,
+    url: String,
+    http_version: &'static str,
+    cookies: Vec<Value>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: &'static str,
+    http_version: &'static str,
+    cookies: Vec<Value>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: &'static str,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: usize,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[derive(Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+const HTTP_VERSION: &str = "HTTP/1.1";
+
+fn entry(recorded: &RecordedRequest) -> Entry {
+    // The server sends the response after the injected delay
+    let time = (recorded.latency + recorded.delay).as_secs_f64() * 1000.0;
+    let started = recorded
+        .timestamp
+        .checked_sub(recorded.latency)
+        .unwrap_or(recorded.timestamp);
+    let response = match &recorded.response {
+        Some(variant) => response(variant),
+        None if !recorded.is_matched() => response(&not_found(&recorded.request, None)),
+        None => HarResponse {
+            status: 0,
+            status_text: "",
+            http_version: HTTP_VERSION,
+            cookies: Vec::new(),
+            headers: Vec::new(),
+            content: Content {
+                size: 0,
+                mime_type: String::new(),
+                text: None,
+                encoding: None,
+                comment: Some("Looked up without rendering a response".to_string()),
+            },
+            redirect_url: "",
+            headers_size: -1,
+            body_size: -1,
+        },
+    };
+    Entry {
+        started_date_time: iso8601(started),
+        time,
+        request: request(&recorded.request),
+        response,
+        cache: Value::Object(Default::default()),
+        timings: Timings {
+            send: 0.0,
+            wait: time,
+            receive: 0.0,
+        },
+        route_id: recorded.route_id.clone(),
+        preset_id: recorded.preset_id.clone(),
+        variant_id: recorded.variant_id.clone(),
+    }
+}
+
+fn request(request: &Request) -> HarRequest {
+    let headers = request.headers.clone().unwrap_or_default();
+    let scheme = match request.transport {
+        Transport::Http | Transport::Sse => "http",
+        Transport::WebSocket => "ws",
+        Transport::Tcp => "tcp",
+    };
+    // TCP URLs are the listen address
+    let host = match request.transport {
+        Transport::Tcp => "",
+        _ => header(&headers, "host").unwrap_or("localhost"),
+    };
+    let query = match &request.query {
+        Some(query) => query.clone(),
+        None => request
+            .url
+            .split('?')
+            .nth(1)
+            .map(parse_query_string)
+            .unwrap_or_default(),
+    };
+    let post_data = request.payload.as_ref().map(|payload| PostData {
+        mime_type: header(&headers, "content-type")
+            .unwrap_or("application/json")
+            .to_string(),
+        text: body_text(payload),
+    });
+    HarRequest {
+        method: request.method.as_ref().map_or("GET", HttpMethod::as_str),
+        url: format!("{}://{}{}", scheme, host, request.url),
+        http_version: HTTP_VERSION,
+        cookies: Vec::new(),
+        headers: name_values(headers),
+        query_string: name_values(query),
+        post_data,
+        headers_size: -1,
+        body_size: -1,
+    }
+}
+
+fn response(variant: &Variant) -> HarResponse {
+    let headers: HashMap<String, String> = variant
+        .headers
+        .iter()
+        .flatten()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+    let mime_type = header(&headers, "content-type").map(str::to_string);
+    let content = if let Some(fixture) = variant.body_data() {
+        Content {
+            size: fixture.data.len(),
+            mime_type: mime_type.unwrap_or(fixture.media_type),
+            text: Some(base64::engine::general_purpose::STANDARD.encode(&fixture.data)),
+            encoding: Some("base64"),
+            comment: None,
+        }
+    } else if let Some(path) = variant.body_file() {
+        Content {
+            size: 0,
+            mime_type: mime_type.unwrap_or_default(),
+            text: None,
+            encoding: None,
+            comment: Some(format!("Fixture file {}", path.display())),
+        }
+    } else if let Some(body) = &variant.body {
+        let text = body_text(body);
+        let default = match body {
+            Value::String(_) => "text/plain",
+            _ => "application/json",
+        };
+        Content {
+            size: text.len(),
+            mime_type: mime_type.unwrap_or_else(|| default.to_string()),
+            text: Some(text),
+            encoding: None,
+            comment: None,
+        }
+    } else {
+        Content {
+            size: 0,
+            mime_type: mime_type.unwrap_or_default(),
+            text: None,
+            encoding: None,
+            comment: None,
+        }
+    };
+    let status = variant.status.unwrap_or(200);
+    HarResponse {
+        status,
+        status_text: status_text(status),
+        http_version: HTTP_VERSION,
+        cookies: Vec::new(),
+        headers: name_values(headers),
+        content,
+        redirect_url: "",
+        headers_size: -1,
+        body_size: -1,
+    }
+}
+
+/// Find a header by case-insensitive name.
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Text of a JSON body, strings are sent as is.
+fn body_text(body: &Value) -> String {
+    match body {
+        Value::String(text) => text.clone(),
+        body => body.to_string(),
+    }
+}
+
+/// Convert a map to name/value pairs sorted by name.
+fn name_values(map: HashMap<String, String>) -> Vec<NameValue> {
+    let mut pairs: Vec<_> = map
+        .into_iter()
+        .map(|(name, value)| NameValue { name, value })
+        .collect();
+    pairs.sort_by(|a, b| a.name.cmp(&b.name));
+    pairs
+}
+
+/// Reason phrase of common status codes, empty for others.
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+/// Format a time as an ISO 8601 UTC date with milliseconds.
+fn iso8601(time: SystemTime) -> String {
+    let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = elapsed.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        elapsed.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn recorded(response: Option<Variant>, matched: bool) -> RecordedRequest {
+        RecordedRequest {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_250),
+            request: Request {
+                url: "/api/users?page=2".to_string(),
+                method: Some(HttpMethod::Post),
+                transport: Transport::Http,
+                headers: Some(HashMap::from([(
+                    "Host".to_string(),
+                    "api.test:8080".to_string(),
+                )])),
+                query: None,
+                payload: Some(json!({"name": "Ann"})),
+            },
+            route_id: matched.then(|| "users".to_string()),
+            preset_id: matched.then(|| "default".to_string()),
+            variant_id: matched.then(|| "ok".to_string()),
+            latency: Duration::from_millis(250),
+            delay: Duration::ZERO,
+            near_matches: Vec::new(),
+            response,
+        }
+    }
+
+    fn log(requests: &[RecordedRequest]) -> Value {
+        serde_json::from_str(&export_har(requests)).unwrap()
+    }
+
+    #[rstest]
+    fn test_export_responded() {
+        let variant = Variant {
+            id: "ok".into(),
+            status: Some(201),
+            headers: Some(HashMap::from([("X-Id".into(), "7".to_string())])),
+            body: Some(json!({"id": 7})),
+            template: None,
+        };
+        let har = log(&[recorded(Some(variant), true)]);
+
+        assert_eq!(har["log"]["version"], "1.2");
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(entry["startedDateTime"], "2023-11-14T22:13:20.000Z");
+        assert_eq!(entry["time"], 250.0);
+        assert_eq!(entry["_routeId"], "users");
+        assert_eq!(entry["request"]["method"], "POST");
+        assert_eq!(
+            entry["request"]["url"],
+            "http://api.test:8080/api/users?page=2"
+        );
+        assert_eq!(
+            entry["request"]["queryString"],
+            json!([{"name": "page", "value": "2"}])
+        );
+        assert_eq!(entry["request"]["postData"]["text"], r#"{"name":"Ann"}"#);
+        assert_eq!(entry["response"]["status"], 201);
+        assert_eq!(entry["response"]["statusText"], "Created");
+        assert_eq!(
+            entry["response"]["headers"],
+            json!([{"name": "X-Id", "value": "7"}])
+        );
+        assert_eq!(
+            entry["response"]["content"],
+            json!({"size": 8, "mimeType": "application/json", "text": r#"{"id":7}"#})
+        );
+    }
+
+    #[rstest]
+    fn test_export_without_response() {
+        let har = log(&[recorded(None, false), recorded(None, true)]);
+
+        let unmatched = &har["log"]["entries"][0];
+        assert_eq!(unmatched["response"]["status"], 404);
+        assert!(unmatched.get("_routeId").is_none());
+        let looked_up = &har["log"]["entries"][1];
+        assert_eq!(looked_up["response"]["status"], 0);
+    }
+
+    #[rstest]
+    #[case(0, "1970-01-01T00:00:00.000Z")]
+    #[case(951_782_400_999, "2000-02-29T00:00:00.999Z")]
+    #[case(1_735_689_599_000, "2024-12-31T23:59:59.000Z")]
+    fn test_iso8601(#[case] millis: u64, #[case] expected: &str) {
+        let time = UNIX_EPOCH + Duration::from_millis(millis);
+        assert_eq!(iso8601(time), expected);
+    }
+}
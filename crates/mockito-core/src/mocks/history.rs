@@ -0,0 +1,367 @@
+//! History of requests looked up by a controller.
+//!
+//! [`RequestHistory`] keeps the most recent requests with the route they matched,
+//! so tests can verify which requests a mock received after the fact. The history
+//! is bounded: once full, the oldest request is dropped for every new one.
+//!
+//! Requests that matched no route are recorded with the routes closest to
+//! matching them, so a 404 shows which condition of the intended route failed.
+//!
+//! Requests that matched no route are also captured apart from the history, see
+//! [`UnmatchedRequests`], so they're reported even after dropping out of it.
+
+use crate::clock;
+use crate::mocks::controller::Request;
+use crate::mocks::coverage::{Coverage, VariantKey};
+use crate::mocks::latency::{Latencies, LatencyHistogram};
+use crate::mocks::manager::ActiveRoute;
+use crate::mocks::table::{RouteExplanation, RouteTable};
+use crate::mocks::tap::{LiveEvent, RequestTap};
+use crate::mocks::unmatched::{UnmatchedRequest, UnmatchedRequests};
+use crate::types::variant::Variant;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+/// Number of requests kept if the `history` setting isn't set
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1000;
+
+/// Number of closest routes recorded for requests that matched no route
+pub const NEAR_MATCH_LIMIT: usize = 3;
+
+/// Request recorded in a [`RequestHistory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// Time the request was recorded
+    pub timestamp: SystemTime,
+    /// Looked up request
+    pub request: Request,
+    /// Matched route ID, `None` if no route matched
+    pub route_id: Option<String>,
+    /// Active preset ID of the matched route
+    pub preset_id: Option<String>,
+    /// Active variant ID of the matched route
+    pub variant_id: Option<String>,
+    /// Time spent looking up the route and rendering the response
+    pub latency: Duration,
+    /// Delay injected by the server before sending the response, see the
+    /// `delay` setting
+    pub delay: Duration,
+    /// Routes closest to matching the request if no route matched, closest first,
+    /// see [`RouteTable::nearest`]
+    pub near_matches: Vec<RouteExplanation>,
+    /// Response rendered by `respond`, `None` for `find_route` lookups and
+    /// requests that matched no route
+    pub response: Option<Variant>,
+}
+
+impl RecordedRequest {
+    /// Record a request with the route it matched.
+    pub fn new(request: &Request, active_route: Option<&ActiveRoute>, latency: Duration) -> Self {
+        Self {
+            timestamp: clock::now(),
+            request: request.clone(),
+            route_id: active_route.map(|a| a.route.id.to_string()),
+            preset_id: active_route.map(|a| a.preset().id.to_string()),
+            variant_id: active_route.map(|a| a.variant().id.to_string()),
+            latency,
+            delay: Duration::ZERO,
+            near_matches: Vec::new(),
+            response: None,
+        }
+    }
+
+    /// Whether the request matched a route.
+    pub fn is_matched(&self) -> bool {
+        self.route_id.is_some()
+    }
+}
+
+/// Recorded requests, captured unmatched requests and serve times of a
+/// [`RequestHistory`], taken by [`RequestHistory::state`].
+#[derive(Debug, Clone, Default)]
+pub struct HistoryState {
+    requests: VecDeque<RecordedRequest>,
+    unmatched: Vec<UnmatchedRequest>,
+    latencies: HashMap<VariantKey, LatencyHistogram>,
+}
+
+/// Bounded history of looked up requests, oldest first.
+///
+/// Recording only takes a short lock, so the history can be shared by threads
+/// looking up requests concurrently.
+#[derive(Debug)]
+pub struct RequestHistory {
+    /// Maximum number of kept requests
+    capacity: AtomicUsize,
+    entries: Mutex<VecDeque<RecordedRequest>>,
+    /// Requests that matched no route, captured regardless of capacity
+    unmatched: UnmatchedRequests,
+    /// Hit counts of matched variants, kept when the history is cleared
+    coverage: Coverage,
+    /// Serve times of matched variants
+    latencies: Latencies,
+    /// Delay injected into responses of matched requests, in milliseconds
+    delay: AtomicU64,
+    /// Live stream of recorded requests
+    tap: RequestTap,
+}
+
+impl RequestHistory {
+    /// Create a history keeping up to `capacity` requests.
+    ///
+    /// A capacity of 0 disables recording.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            entries: Mutex::new(VecDeque::new()),
+            unmatched: UnmatchedRequests::default(),
+            coverage: Coverage::default(),
+            latencies: Latencies::default(),
+            delay: AtomicU64::new(0),
+            tap: RequestTap::default(),
+        }
+    }
+
+    /// Record a request looked up in `table`, dropping the oldest one if the
+    /// history is full.
+    ///
+    /// Requests that matched no route are recorded with their nearest routes in
+    /// the table. The `response` rendered for the request, if any, is recorded
+    /// with it.
+    pub fn record(
+        &self,
+        table: &RouteTable,
+        request: &Request,
+        active_route: Option<&ActiveRoute>,
+        response: Option<&Variant>,
+        latency: Duration,
+    ) {
+        let delay = match active_route {
+            Some(active_route) => {
+                let delay = self.delay();
+                self.coverage.record(active_route);
+                self.latencies.record(active_route, latency + delay);
+                delay
+            }
+            None => {
+                self.unmatched.record(request);
+                Duration::ZERO
+            }
+        };
+        self.tap
+            .publish(|| LiveEvent::new(request, active_route, response, latency, delay));
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        let mut entry = RecordedRequest::new(request, active_route, latency);
+        entry.delay = delay;
+        entry.response = response.cloned();
+        if active_route.is_none() {
+            entry.near_matches = table.nearest(request, NEAR_MATCH_LIMIT);
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        while entries.len() >= capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Get all recorded requests, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Get the recorded requests that matched a route, oldest first.
+    pub fn requests_for(&self, route_id: &str) -> Vec<RecordedRequest> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|entry| entry.route_id.as_deref() == Some(route_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the captured requests that matched no route.
+    pub fn unmatched(&self) -> &UnmatchedRequests {
+        &self.unmatched
+    }
+
+    /// Get the hit counts of matched variants.
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    /// Get the serve time statistics of matched variants.
+    pub fn latencies(&self) -> &Latencies {
+        &self.latencies
+    }
+
+    /// Get the live stream of recorded requests, see [`RequestTap`].
+    pub fn tap(&self) -> &RequestTap {
+        &self.tap
+    }
+
+    /// Get the number of recorded requests.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no request is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of kept requests.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Delay injected into responses of matched requests.
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay.load(Ordering::Relaxed))
+    }
+
+    /// Change the delay injected into responses of matched requests, recorded
+    /// with them and added to their serve time.
+    pub fn set_delay(&self, delay: Duration) {
+        let millis = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+        self.delay.store(millis, Ordering::Relaxed);
+    }
+
+    /// Change the maximum number of kept requests, dropping the oldest ones beyond it.
+    ///
+    /// A capacity of 0 disables recording and clears the history.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let excess = entries.len().saturating_sub(capacity);
+        entries.drain(..excess);
+    }
+
+    /// Copy the recorded and captured unmatched requests and serve times.
+    ///
+    /// Coverage is cumulative and not part of the state.
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            requests: self
+                .entries
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            unmatched: self.unmatched.requests(),
+            latencies: self.latencies.histograms(),
+        }
+    }
+
+    /// Replace the recorded and captured unmatched requests and serve times
+    /// with a copy taken by [`state`](Self::state).
+    ///
+    /// Only the latest requests within the current capacity are kept.
+    pub fn restore(&self, state: HistoryState) {
+        let mut requests = state.requests;
+        let excess = requests.len().saturating_sub(self.capacity());
+        requests.drain(..excess);
+        *self.entries.lock().unwrap_or_else(PoisonError::into_inner) = requests;
+        self.unmatched.restore(state.unmatched);
+        self.latencies.restore(state.latencies);
+    }
+
+    /// Remove all recorded and captured unmatched requests and serve times.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.unmatched.clear();
+        self.latencies.clear();
+    }
+}
+
+impl Default for RequestHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::route::{HttpMethod, Transport};
+    use rstest::rstest;
+
+    fn request(url: &str) -> Request {
+        Request {
+            url: url.to_string(),
+            method: Some(HttpMethod::Get),
+            transport: Transport::Http,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    #[rstest]
+    #[case(3, 5, vec!["/2", "/3", "/4"])]
+    #[case(10, 3, vec!["/0", "/1", "/2"])]
+    #[case(0, 3, vec![])]
+    fn test_record_keeps_latest(
+        #[case] capacity: usize,
+        #[case] count: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let history = RequestHistory::new(capacity);
+        for i in 0..count {
+            history.record(
+                &RouteTable::default(),
+                &request(&format!("/{}", i)),
+                None,
+                None,
+                Duration::ZERO,
+            );
+        }
+
+        let urls: Vec<_> = history
+            .requests()
+            .into_iter()
+            .map(|entry| entry.request.url)
+            .collect();
+        assert_eq!(urls, expected);
+    }
+
+    #[rstest]
+    fn test_set_capacity_drops_oldest() {
+        let history = RequestHistory::new(5);
+        for i in 0..5 {
+            history.record(
+                &RouteTable::default(),
+                &request(&format!("/{}", i)),
+                None,
+                None,
+                Duration::ZERO,
+            );
+        }
+
+        history.set_capacity(2);
+
+        let urls: Vec<_> = history
+            .requests()
+            .into_iter()
+            .map(|entry| entry.request.url)
+            .collect();
+        assert_eq!(urls, vec!["/3", "/4"]);
+        history.set_capacity(0);
+        assert!(history.is_empty());
+    }
+}
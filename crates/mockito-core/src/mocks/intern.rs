@@ -0,0 +1,75 @@
+//! String pool for definition IDs and header names.
+//!
+//! Large projects repeat the same strings over and over: every preset of a
+//! route has an `ok` variant, every variant sets `Content-Type` and collections
+//! reference the same routes. [`Interner`] keeps one shared copy of each string,
+//! so repeated strings cost a pointer instead of an allocation, and pooled
+//! copies compare equal by pointer before falling back to their contents.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Pool of shared strings.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the pooled copy of a string, adding the string if it isn't pooled yet.
+    pub fn intern(&mut self, s: &Arc<str>) -> Arc<str> {
+        if let Some(pooled) = self.strings.get(s) {
+            return Arc::clone(pooled);
+        }
+        self.strings.insert(Arc::clone(s));
+        Arc::clone(s)
+    }
+
+    /// Replace a string with its pooled copy.
+    pub fn intern_in_place(&mut self, s: &mut Arc<str>) {
+        *s = self.intern(s);
+    }
+
+    /// Number of distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Check if the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_intern_shares_equal_strings() {
+        let mut interner = Interner::new();
+        let first = interner.intern(&Arc::from("ok"));
+        let second = interner.intern(&Arc::from("ok"));
+        let other = interner.intern(&Arc::from("error"));
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[rstest]
+    fn test_intern_in_place() {
+        let mut interner = Interner::new();
+        let pooled = interner.intern(&Arc::from("Content-Type"));
+        let mut header: Arc<str> = Arc::from("Content-Type");
+
+        interner.intern_in_place(&mut header);
+        assert!(Arc::ptr_eq(&pooled, &header));
+    }
+}
@@ -0,0 +1,305 @@
+//! Serve time statistics of matched routes.
+//!
+//! [`Latencies`] aggregates the serve time of every request answered by a
+//! variant into a [`LatencyHistogram`]: the time spent looking up the route and
+//! rendering the response, plus the delay the server injects before sending it
+//! (the `delay` setting). Clients measuring a longer time than the mock's serve
+//! time spend the difference on their own side.
+//!
+//! Histograms use log-linear buckets, so they take little memory regardless of
+//! the number of requests and report percentiles within about 6% of the exact
+//! values.
+
+use crate::mocks::coverage::VariantKey;
+use crate::mocks::manager::ActiveRoute;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Bits of precision kept below the highest set bit of a sample
+const PRECISION_BITS: u32 = 4;
+/// Buckets per power of two, samples below it get a bucket each
+const SUB_BUCKETS: u64 = 1 << PRECISION_BITS;
+
+/// Histogram of durations with microsecond resolution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Sample counts by bucket index, see [`bucket`]
+    buckets: BTreeMap<u32, u64>,
+    count: u64,
+    /// Sum of samples in microseconds
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    /// Add a sample.
+    pub fn record(&mut self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        *self.buckets.entry(bucket(micros)).or_default() += 1;
+        self.min = if self.count == 0 {
+            micros
+        } else {
+            self.min.min(micros)
+        };
+        self.max = self.max.max(micros);
+        self.count += 1;
+        self.sum += u128::from(micros);
+    }
+
+    /// Add all samples of another histogram.
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        if other.count == 0 {
+            return;
+        }
+        for (index, count) in &other.buckets {
+            *self.buckets.entry(*index).or_default() += count;
+        }
+        self.min = if self.count == 0 {
+            other.min
+        } else {
+            self.min.min(other.min)
+        };
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+
+    /// Number of samples.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Get the duration below which a `quantile` (0 to 1) of the samples fall.
+    ///
+    /// Returns zero if there are no samples.
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                let micros = bucket_upper(*index).clamp(self.min, self.max);
+                return Duration::from_micros(micros);
+            }
+        }
+        Duration::from_micros(self.max)
+    }
+
+    /// Summarize the samples.
+    pub fn stats(&self) -> LatencyStats {
+        let mean = match self.count {
+            0 => 0,
+            count => u64::try_from(self.sum / u128::from(count)).unwrap_or(u64::MAX),
+        };
+        LatencyStats {
+            count: self.count,
+            min: Duration::from_micros(self.min),
+            max: Duration::from_micros(self.max),
+            mean: Duration::from_micros(mean),
+            p50: self.quantile(0.5),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+        }
+    }
+}
+
+/// Get the bucket of a sample in microseconds.
+///
+/// Samples below [`SUB_BUCKETS`] get a bucket each. Larger samples share a
+/// bucket with those having the same highest bit and [`PRECISION_BITS`] bits
+/// below it.
+fn bucket(micros: u64) -> u32 {
+    if micros < SUB_BUCKETS {
+        return micros as u32;
+    }
+    let exponent = 63 - micros.leading_zeros();
+    let shift = exponent - PRECISION_BITS;
+    let sub_bucket = (micros >> shift) as u32 & (SUB_BUCKETS as u32 - 1);
+    (shift + 1) * SUB_BUCKETS as u32 + sub_bucket
+}
+
+/// Get the largest sample in microseconds falling into a bucket.
+fn bucket_upper(index: u32) -> u64 {
+    let sub_buckets = SUB_BUCKETS as u32;
+    if index < sub_buckets {
+        return u64::from(index);
+    }
+    let shift = index / sub_buckets - 1;
+    let lower = (SUB_BUCKETS + u64::from(index % sub_buckets)) << shift;
+    lower.saturating_add((1 << shift) - 1)
+}
+
+/// Summary of serve times.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Number of requests
+    pub count: u64,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// Median
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+/// Serve times of a route, overall and by variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteLatency {
+    pub route_id: String,
+    /// Serve times of all variants of the route
+    pub stats: LatencyStats,
+    /// Variants that answered requests, sorted by preset and variant ID
+    pub variants: Vec<VariantLatency>,
+}
+
+/// Serve times of a variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantLatency {
+    pub preset_id: String,
+    pub variant_id: String,
+    pub stats: LatencyStats,
+}
+
+/// Serve time histograms of matched variants.
+#[derive(Debug, Default)]
+pub struct Latencies {
+    histograms: Mutex<HashMap<VariantKey, LatencyHistogram>>,
+}
+
+impl Latencies {
+    /// Add the serve time of a request answered by an active route.
+    pub fn record(&self, active_route: &ActiveRoute, serve_time: Duration) {
+        let key = (
+            Arc::clone(&active_route.route.id),
+            Arc::clone(&active_route.preset().id),
+            Arc::clone(&active_route.variant().id),
+        );
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_default()
+            .record(serve_time);
+    }
+
+    /// Get the serve times of routes that answered requests, sorted by route ID.
+    pub fn routes(&self) -> Vec<RouteLatency> {
+        let histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut by_route: BTreeMap<&str, Vec<(&VariantKey, &LatencyHistogram)>> = BTreeMap::new();
+        for (key, histogram) in histograms.iter() {
+            by_route.entry(&key.0).or_default().push((key, histogram));
+        }
+        by_route
+            .into_iter()
+            .map(|(route_id, mut variants)| {
+                variants.sort_by(|(a, _), (b, _)| (&a.1, &a.2).cmp(&(&b.1, &b.2)));
+                let mut total = LatencyHistogram::default();
+                for (_, histogram) in &variants {
+                    total.merge(histogram);
+                }
+                RouteLatency {
+                    route_id: route_id.to_string(),
+                    stats: total.stats(),
+                    variants: variants
+                        .into_iter()
+                        .map(|((_, preset_id, variant_id), histogram)| VariantLatency {
+                            preset_id: preset_id.to_string(),
+                            variant_id: variant_id.to_string(),
+                            stats: histogram.stats(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Get a copy of all histograms, to restore them later.
+    pub(crate) fn histograms(&self) -> HashMap<VariantKey, LatencyHistogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace all histograms with a copy taken by [`histograms`](Self::histograms).
+    pub(crate) fn restore(&self, histograms: HashMap<VariantKey, LatencyHistogram>) {
+        *self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = histograms;
+    }
+
+    /// Remove all samples.
+    pub fn clear(&self) {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0)]
+    #[case(15)]
+    #[case(16)]
+    #[case(17)]
+    #[case(1_000)]
+    #[case(123_456_789)]
+    #[case(u64::MAX)]
+    fn test_bucket_bounds(#[case] micros: u64) {
+        let index = bucket(micros);
+        assert!(bucket_upper(index) >= micros);
+        assert!(index == 0 || bucket_upper(index - 1) < micros);
+        // Relative error of the upper bound is below 1 / SUB_BUCKETS
+        assert!(bucket_upper(index) - micros <= micros / SUB_BUCKETS);
+    }
+
+    #[rstest]
+    fn test_stats() {
+        let mut histogram = LatencyHistogram::default();
+        for millis in 1..=100 {
+            histogram.record(Duration::from_millis(millis));
+        }
+
+        let stats = histogram.stats();
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        for (actual, expected) in [(stats.p50, 50), (stats.p95, 95), (stats.p99, 99)] {
+            let expected = Duration::from_millis(expected);
+            assert!(actual >= expected && actual <= expected.mul_f64(1.0625));
+        }
+        assert_eq!(LatencyHistogram::default().stats(), LatencyStats::default());
+    }
+
+    #[rstest]
+    fn test_merge() {
+        let mut fast = LatencyHistogram::default();
+        fast.record(Duration::from_millis(2));
+        let mut slow = LatencyHistogram::default();
+        slow.record(Duration::from_millis(200));
+
+        let mut total = LatencyHistogram::default();
+        total.merge(&slow);
+        total.merge(&fast);
+
+        assert_eq!(total.count(), 2);
+        assert_eq!(total.stats().min, Duration::from_millis(2));
+        assert_eq!(total.quantile(1.0), Duration::from_millis(200));
+    }
+}
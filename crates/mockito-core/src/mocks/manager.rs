@@ -4,23 +4,96 @@
 //! It is used by `MocksController` for handling dynamic changes to mocked routes
 //! from added collections/routes.
 
+use crate::config::error::ConfigError;
+use crate::config::export::{self, ExportFormat};
+use crate::mocks::intern::Interner;
+use crate::mocks::validation::{self, ValidationIssue};
 use crate::types::collection::Collection;
+use crate::types::group::Group;
 use crate::types::preset::Preset;
 use crate::types::route::{Route, RouteReference, Transport};
 use crate::types::variant::Variant;
+use crate::vars::{self, Vars};
+use glob::Pattern;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::sync::Arc;
 
 /// Active route with selected preset and variant.
 ///
 /// Represents a fully resolved route that can be used for mocking.
+///
+/// The route is shared with the [`MocksManager`] it was resolved from, so resolving
+/// doesn't copy presets and response bodies. It's only copied once modified, e.g.
+/// when a group or collection variables rewrite its URL and responses.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ActiveRoute {
     /// Base route definition
-    pub route: Route,
+    pub route: Arc<Route>,
+    /// Index of the selected preset in `route.presets`
+    preset_index: usize,
+    /// Index of the selected variant in the preset's variants
+    variant_index: usize,
+}
+
+impl ActiveRoute {
+    /// Create an active route from a route, preset and variant.
+    ///
+    /// The presets of `route` are replaced with `preset` holding only `variant`.
+    pub fn new(route: Route, preset: Preset, variant: Variant) -> Self {
+        let route = Route {
+            presets: vec![Preset {
+                variants: vec![variant],
+                ..preset
+            }],
+            ..route
+        };
+        Self::select(Arc::new(route), 0, 0)
+    }
+
+    /// Select a preset and variant of a route by index.
+    ///
+    /// # Panics
+    /// Panics if the route has no such preset or variant.
+    pub(crate) fn select(route: Arc<Route>, preset_index: usize, variant_index: usize) -> Self {
+        assert!(
+            variant_index < route.presets[preset_index].variants.len(),
+            "variant index out of bounds"
+        );
+        Self {
+            route,
+            preset_index,
+            variant_index,
+        }
+    }
+
     /// Selected preset from the route
-    pub preset: Preset,
+    pub fn preset(&self) -> &Preset {
+        &self.route.presets[self.preset_index]
+    }
+
     /// Selected variant from the preset
-    pub variant: Variant,
+    pub fn variant(&self) -> &Variant {
+        &self.preset().variants[self.variant_index]
+    }
+
+    /// Get the route for modification, copying it if it's shared.
+    pub fn route_mut(&mut self) -> &mut Route {
+        Arc::make_mut(&mut self.route)
+    }
+
+    /// Get the selected preset for modification, copying the route if it's shared.
+    pub fn preset_mut(&mut self) -> &mut Preset {
+        let preset_index = self.preset_index;
+        &mut self.route_mut().presets[preset_index]
+    }
+
+    /// Get the selected variant for modification, copying the route if it's shared.
+    pub fn variant_mut(&mut self) -> &mut Variant {
+        let variant_index = self.variant_index;
+        &mut self.preset_mut().variants[variant_index]
+    }
 }
 
 /// Manager for storing and resolving collections and routes.
@@ -36,49 +109,323 @@ pub struct ActiveRoute {
 #[derive(Debug, Clone)]
 pub struct MocksManager {
     /// Map of collection ID to Collection
-    collections: HashMap<String, Collection>,
-    /// Map of route ID to Route
-    routes: HashMap<String, Route>,
+    collections: HashMap<Arc<str>, Collection>,
+    /// Map of route ID to Route, shared with resolved active routes
+    routes: HashMap<Arc<str>, Arc<Route>>,
+    /// Map of group ID to Group
+    groups: HashMap<String, Group>,
+    /// Pool of IDs and header names shared by stored definitions
+    interner: Interner,
+    /// Policy applied when an added definition reuses an existing ID
+    duplicate_policy: DuplicatePolicy,
 }
 
 impl MocksManager {
     /// Create a new MocksManager
     pub fn new() -> Self {
+        Self::with_duplicate_policy(DuplicatePolicy::default())
+    }
+
+    /// Create a new MocksManager handling duplicate IDs with the given policy
+    pub fn with_duplicate_policy(duplicate_policy: DuplicatePolicy) -> Self {
         Self {
             collections: HashMap::new(),
             routes: HashMap::new(),
+            groups: HashMap::new(),
+            interner: Interner::new(),
+            duplicate_policy,
         }
     }
 
     /// Add a collection to the manager
-    pub fn add_collection(&mut self, collection: Collection) {
-        self.collections.insert(collection.id.clone(), collection);
+    ///
+    /// Returns error if the ID is taken and the duplicate policy is [`DuplicatePolicy::Error`].
+    pub fn add_collection(&mut self, mut collection: Collection) -> Result<(), DuplicateIdError> {
+        intern_collection(&mut self.interner, &mut collection);
+        insert_definition(
+            &mut self.collections,
+            &self.duplicate_policy,
+            DefinitionKind::Collection,
+            collection.id.clone(),
+            collection,
+            replace,
+        )
     }
 
-    /// Add multiple collections to the manager
-    pub fn add_collections(&mut self, collections: Vec<Collection>) {
+    /// Add multiple collections to the manager, stopping at the first rejected duplicate
+    pub fn add_collections(
+        &mut self,
+        collections: Vec<Collection>,
+    ) -> Result<(), DuplicateIdError> {
         for collection in collections {
-            self.add_collection(collection);
+            self.add_collection(collection)?;
         }
+        Ok(())
     }
 
     /// Add a route to the manager
-    pub fn add_route(&mut self, route: Route) {
-        self.routes.insert(route.id.clone(), route);
+    ///
+    /// Returns error if the ID is taken and the duplicate policy is [`DuplicatePolicy::Error`].
+    pub fn add_route(&mut self, mut route: Route) -> Result<(), DuplicateIdError> {
+        intern_route(&mut self.interner, &mut route);
+        insert_definition(
+            &mut self.routes,
+            &self.duplicate_policy,
+            DefinitionKind::Route,
+            route.id.clone(),
+            Arc::new(route),
+            |existing, route| Arc::make_mut(existing).merge(Arc::unwrap_or_clone(route)),
+        )
     }
 
-    /// Add multiple routes to the manager
-    pub fn add_routes(&mut self, routes: Vec<Route>) {
+    /// Add multiple routes to the manager, stopping at the first rejected duplicate
+    pub fn add_routes(&mut self, routes: Vec<Route>) -> Result<(), DuplicateIdError> {
         for route in routes {
-            self.add_route(route);
+            self.add_route(route)?;
+        }
+        Ok(())
+    }
+
+    /// Add a route group to the manager
+    ///
+    /// Returns error if the ID is taken and the duplicate policy is [`DuplicatePolicy::Error`].
+    pub fn add_group(&mut self, mut group: Group) -> Result<(), DuplicateIdError> {
+        if let Some(headers) = &mut group.headers {
+            intern_keys(&mut self.interner, headers);
+        }
+        insert_definition(
+            &mut self.groups,
+            &self.duplicate_policy,
+            DefinitionKind::Group,
+            group.id.clone(),
+            group,
+            replace,
+        )
+    }
+
+    /// Add multiple route groups to the manager, stopping at the first rejected duplicate
+    pub fn add_groups(&mut self, groups: Vec<Group>) -> Result<(), DuplicateIdError> {
+        for group in groups {
+            self.add_group(group)?;
+        }
+        Ok(())
+    }
+
+    /// Remove a collection by ID, returning it if it was stored
+    pub fn remove_collection(&mut self, collection_id: &str) -> Option<Collection> {
+        self.collections.remove(collection_id)
+    }
+
+    /// Remove a route by ID, returning it if it was stored
+    pub fn remove_route(&mut self, route_id: &str) -> Option<Route> {
+        self.routes.remove(route_id).map(Arc::unwrap_or_clone)
+    }
+
+    /// Remove a route group by ID, returning it if it was stored
+    pub fn remove_group(&mut self, group_id: &str) -> Option<Group> {
+        self.groups.remove(group_id)
+    }
+
+    /// Merge routes, collections and groups of another manager into this one.
+    ///
+    /// Definitions with IDs present in both managers are resolved by `strategy`.
+    /// The duplicate policy of this manager is not applied.
+    ///
+    /// # Errors
+    /// With [`MergeStrategy::Error`], returns the first conflicting ID (routes first,
+    /// then collections, then groups, each by ID). This manager is left unchanged.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut manager = load_manager("fixtures/shared")?;
+    /// manager.merge(load_manager("fixtures/app")?, MergeStrategy::Replace)?;
+    /// ```
+    pub fn merge(
+        &mut self,
+        other: MocksManager,
+        strategy: MergeStrategy,
+    ) -> Result<(), DuplicateIdError> {
+        if strategy == MergeStrategy::Error {
+            let conflict = first_conflict(&self.routes, &other.routes, DefinitionKind::Route)
+                .or_else(|| {
+                    first_conflict(
+                        &self.collections,
+                        &other.collections,
+                        DefinitionKind::Collection,
+                    )
+                })
+                .or_else(|| first_conflict(&self.groups, &other.groups, DefinitionKind::Group));
+            if let Some(conflict) = conflict {
+                return Err(conflict);
+            }
+        }
+
+        // Move strings of the other manager into this manager's pool
+        let interner = &mut self.interner;
+        let routes = other
+            .routes
+            .into_values()
+            .map(|mut route| {
+                intern_route(interner, Arc::make_mut(&mut route));
+                (Arc::clone(&route.id), route)
+            })
+            .collect();
+        let collections = other
+            .collections
+            .into_values()
+            .map(|mut collection| {
+                intern_collection(interner, &mut collection);
+                (Arc::clone(&collection.id), collection)
+            })
+            .collect();
+        let mut groups = other.groups;
+        for headers in groups.values_mut().filter_map(|g| g.headers.as_mut()) {
+            intern_keys(interner, headers);
+        }
+
+        merge_definitions(&mut self.routes, routes, strategy);
+        merge_definitions(&mut self.collections, collections, strategy);
+        merge_definitions(&mut self.groups, groups, strategy);
+        Ok(())
+    }
+
+    /// Get a route group by ID.
+    pub fn get_group(&self, group_id: &str) -> Option<&Group> {
+        self.groups.get(group_id)
+    }
+
+    /// List all route groups, sorted by group ID.
+    pub fn list_groups(&self) -> Vec<&Group> {
+        let mut groups: Vec<&Group> = self.groups.values().collect();
+        groups.sort_by(|a, b| a.id.cmp(&b.id));
+        groups
+    }
+
+    /// List all collections, sorted by collection ID.
+    pub fn list_collections(&self) -> Vec<&Collection> {
+        let mut collections: Vec<&Collection> = self.collections.values().collect();
+        collections.sort_by(|a, b| a.id.cmp(&b.id));
+        collections
+    }
+
+    /// List all routes, sorted by route ID.
+    pub fn list_routes(&self) -> Vec<&Route> {
+        let mut routes: Vec<&Route> = self.routes.values().map(Arc::as_ref).collect();
+        routes.sort_by(|a, b| a.id.cmp(&b.id));
+        routes
+    }
+
+    /// Get a route by ID.
+    ///
+    /// Returns `None` if route not found.
+    pub fn get_route(&self, route_id: &str) -> Option<&Route> {
+        self.routes.get(route_id).map(Arc::as_ref)
+    }
+
+    /// Estimate the memory used by stored definitions, in bytes.
+    ///
+    /// The estimate is the serialized size of routes, collections and groups,
+    /// which is dominated by inline bodies. Fixture files aren't included, since
+    /// they're read when served, see [`FixtureCache`](crate::config::fixtures::FixtureCache).
+    pub fn estimated_size(&self) -> usize {
+        let routes: usize = self.routes.values().map(serialized_size).sum();
+        let collections: usize = self.collections.values().map(serialized_size).sum();
+        let groups: usize = self.groups.values().map(serialized_size).sum();
+        routes + collections + groups
+    }
+
+    /// Get the inheritance chain of a collection.
+    ///
+    /// Returns collection IDs ordered from the root ancestor down to `collection_id`,
+    /// i.e. in the same order routes are applied during resolution.
+    ///
+    /// Returns error if any collection in the chain is not found or the chain is circular.
+    pub fn collection_tree(&self, collection_id: &str) -> Result<Vec<String>, ResolveError> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut current = Some(collection_id.to_string());
+
+        while let Some(id) = current {
+            if chain.contains(&id) {
+                chain.push(id);
+                return Err(ResolveError::CircularDependency { path: chain });
+            }
+
+            let collection = self.collections.get(id.as_str()).ok_or_else(|| {
+                ResolveError::CollectionNotFound {
+                    collection_id: id.clone(),
+                }
+            })?;
+
+            current = collection.from.clone();
+            chain.push(id);
+        }
+
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Get variables of a collection merged along its inheritance chain.
+    ///
+    /// Child collection variables override parent ones with the same name.
+    pub fn collection_vars(&self, collection_id: &str) -> Result<Vars, ResolveError> {
+        let mut merged = Vars::new();
+        for id in self.collection_tree(collection_id)? {
+            if let Some(vars) = self
+                .collections
+                .get(id.as_str())
+                .and_then(|c| c.vars.as_ref())
+            {
+                merged.extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
         }
+        Ok(merged)
+    }
+
+    /// Substitute variables of a collection into resolved routes.
+    ///
+    /// Placeholders in route URLs, response headers and bodies are replaced,
+    /// see [`collection_vars`](Self::collection_vars).
+    pub fn apply_collection_vars(
+        &self,
+        collection_id: &str,
+        active_routes: &mut [ActiveRoute],
+    ) -> Result<(), ResolveError> {
+        let collection_vars = self.collection_vars(collection_id)?;
+        if !collection_vars.is_empty() {
+            for active_route in active_routes {
+                substitute_vars(active_route, &collection_vars);
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate all stored collections and routes in one pass.
+    ///
+    /// Checks every collection's route references and inheritance chain,
+    /// duplicate preset/variant IDs and per-route preset/variant integrity.
+    /// Returns an empty list if no issues were found.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validation::validate(self)
+    }
+
+    /// Write all routes, collections and groups to config files in a directory.
+    ///
+    /// Files follow the project directory layout, see [`export`](crate::config::export).
+    /// Returns the paths of written files.
+    pub fn export(&self, dir: &str, format: ExportFormat) -> Result<Vec<String>, ConfigError> {
+        export::export(self, dir, format)
     }
 
     /// Resolve a single route reference to an ActiveRoute.
     ///
-    /// Route reference format: `route_id:preset_id:variant_id`
+    /// Route reference format: `route_id[:preset_id[:variant_id]]`.
+    /// Omitted preset and variant default to the first ones defined.
     ///
     /// Returns error if route, preset, or variant not found.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(Display, level = "debug"))
+    )]
     pub fn resolve_route_reference(
         &self,
         route_ref_str: &str,
@@ -89,40 +436,108 @@ impl MocksManager {
             }
         })?;
 
-        // Get route
-        let route =
-            self.routes
-                .get(&route_ref.route_id)
-                .ok_or_else(|| ResolveError::RouteNotFound {
-                    route_id: route_ref.route_id.clone(),
-                })?;
+        self.resolve_parsed_reference(&route_ref)
+    }
 
-        // Get preset
-        let preset = route
-            .presets
-            .iter()
-            .find(|p| p.id == route_ref.preset_id)
-            .ok_or_else(|| ResolveError::PresetNotFound {
+    /// Resolve an already parsed route reference to an ActiveRoute.
+    ///
+    /// Returns error if route, preset, or variant not found, or if a default
+    /// preset/variant is requested but none is defined.
+    pub fn resolve_parsed_reference(
+        &self,
+        route_ref: &RouteReference,
+    ) -> Result<ActiveRoute, ResolveError> {
+        // Get route
+        let route = self
+            .routes
+            .get(route_ref.route_id.as_str())
+            .ok_or_else(|| ResolveError::RouteNotFound {
                 route_id: route_ref.route_id.clone(),
-                preset_id: route_ref.preset_id.clone(),
             })?;
 
-        // Get variant
-        let variant = preset
-            .variants
-            .iter()
-            .find(|v| v.id == route_ref.variant_id)
-            .ok_or_else(|| ResolveError::VariantNotFound {
-                route_id: route_ref.route_id.clone(),
-                preset_id: route_ref.preset_id.clone(),
-                variant_id: route_ref.variant_id.clone(),
+        // Get preset (first one by default)
+        let preset_index = match &route_ref.preset_id {
+            Some(preset_id) => route
+                .presets
+                .iter()
+                .position(|p| *p.id == **preset_id)
+                .ok_or_else(|| ResolveError::PresetNotFound {
+                    route_id: route_ref.route_id.clone(),
+                    preset_id: preset_id.clone(),
+                })?,
+            None if route.presets.is_empty() => {
+                return Err(ResolveError::NoPresets {
+                    route_id: route_ref.route_id.clone(),
+                })
+            }
+            None => 0,
+        };
+        let preset = &route.presets[preset_index];
+
+        // Get variant (first one by default)
+        let variant_index = match &route_ref.variant_id {
+            Some(variant_id) => preset
+                .variants
+                .iter()
+                .position(|v| *v.id == **variant_id)
+                .ok_or_else(|| ResolveError::VariantNotFound {
+                    route_id: route_ref.route_id.clone(),
+                    preset_id: preset.id.to_string(),
+                    variant_id: variant_id.clone(),
+                })?,
+            None if preset.variants.is_empty() => {
+                return Err(ResolveError::NoVariants {
+                    route_id: route_ref.route_id.clone(),
+                    preset_id: preset.id.to_string(),
+                })
+            }
+            None => 0,
+        };
+
+        self.apply_group(ActiveRoute::select(
+            Arc::clone(route),
+            preset_index,
+            variant_index,
+        ))
+    }
+
+    /// Apply the route group: prepend its base URL and merge its response headers.
+    ///
+    /// Variant headers take precedence over group headers.
+    /// Returns error if the route references an unknown group.
+    fn apply_group(&self, mut active_route: ActiveRoute) -> Result<ActiveRoute, ResolveError> {
+        let Some(group_id) = &active_route.route.group else {
+            return Ok(active_route);
+        };
+        let group = self
+            .groups
+            .get(group_id)
+            .ok_or_else(|| ResolveError::GroupNotFound {
+                route_id: active_route.route.id.to_string(),
+                group_id: group_id.clone(),
             })?;
 
-        Ok(ActiveRoute {
-            route: route.clone(),
-            preset: preset.clone(),
-            variant: variant.clone(),
-        })
+        let url = group.prefix_url(&active_route.route.url);
+        active_route.route_mut().url = url;
+        if let Some(group_headers) = &group.headers {
+            let variant = active_route.variant_mut();
+            let mut headers = group_headers.clone();
+            headers.extend(variant.headers.take().unwrap_or_default());
+            variant.headers = Some(headers);
+        }
+        Ok(active_route)
+    }
+
+    /// Get all routes tagged with `tag`, sorted by route ID.
+    pub fn routes_with_tag(&self, tag: &str) -> Vec<&Route> {
+        let mut routes: Vec<&Route> = self
+            .routes
+            .values()
+            .filter(|route| route.tags.iter().any(|t| t == tag))
+            .map(Arc::as_ref)
+            .collect();
+        routes.sort_by(|a, b| a.id.cmp(&b.id));
+        routes
     }
 
     /// Resolve a WebSocket route reference to an ActiveRoute.
@@ -142,7 +557,7 @@ impl MocksManager {
         // Validate transport is WebSocket
         if active_route.route.transport != Transport::WebSocket {
             return Err(ResolveError::TransportMismatch {
-                route_id: active_route.route.id,
+                route_id: active_route.route.id.to_string(),
                 expected: "a websocket".to_string(),
                 actual: "not a websocket".to_string(),
                 suggestion: "Use 'useRoutes' instead".to_string(),
@@ -169,7 +584,7 @@ impl MocksManager {
         // Validate transport is HTTP
         if active_route.route.transport == Transport::WebSocket {
             return Err(ResolveError::TransportMismatch {
-                route_id: active_route.route.id,
+                route_id: active_route.route.id.to_string(),
                 expected: "an HTTP".to_string(),
                 actual: "a websocket".to_string(),
                 suggestion: "Use 'useSocket' instead".to_string(),
@@ -179,15 +594,94 @@ impl MocksManager {
         Ok(active_route)
     }
 
+    /// Resolve a wildcard reference to all matching routes.
+    ///
+    /// Wildcard references only select routes of the same transport as
+    /// `transport`: WebSocket routes are resolved separately from HTTP, SSE and
+    /// TCP routes.
+    ///
+    /// Each part of the reference is a glob pattern (e.g. `*:*:error` or
+    /// `payments-*:default:timeout`). Omitted preset and variant default to the
+    /// first ones defined, as with plain references.
+    ///
+    /// For every route whose ID matches, the first preset/variant pair matching the
+    /// patterns is selected. Routes without such a pair are skipped.
+    /// Returned routes are sorted by route ID.
+    ///
+    /// Returns error if the reference is malformed or no route matches.
+    pub fn resolve_wildcard_reference(
+        &self,
+        route_ref_str: &str,
+        transport: &Transport,
+    ) -> Result<Vec<ActiveRoute>, ResolveError> {
+        let invalid_reference = || ResolveError::InvalidRouteReference {
+            reference: route_ref_str.to_string(),
+        };
+        let route_ref = RouteReference::parse(route_ref_str).ok_or_else(invalid_reference)?;
+        let compile = |part: &str| Pattern::new(part).map_err(|_| invalid_reference());
+        let route_pattern = compile(&route_ref.route_id)?;
+        let preset_pattern = route_ref.preset_id.as_deref().map(compile).transpose()?;
+        let variant_pattern = route_ref.variant_id.as_deref().map(compile).transpose()?;
+
+        let mut active_routes = Vec::new();
+        let mut routes: Vec<&Arc<Route>> = self.routes.values().collect();
+        routes.sort_by(|a, b| a.id.cmp(&b.id));
+        for route in routes {
+            if route.transport.is_socket() != transport.is_socket()
+                || !route_pattern.matches(&route.id)
+            {
+                continue;
+            }
+
+            let presets: Vec<(usize, &Preset)> = match &preset_pattern {
+                Some(pattern) => route
+                    .presets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| pattern.matches(&p.id))
+                    .collect(),
+                None => route.presets.iter().enumerate().take(1).collect(),
+            };
+
+            let selected = presets.into_iter().find_map(|(preset_index, preset)| {
+                let variant_index = match &variant_pattern {
+                    Some(pattern) => preset.variants.iter().position(|v| pattern.matches(&v.id)),
+                    None => (!preset.variants.is_empty()).then_some(0),
+                };
+                variant_index.map(|variant_index| (preset_index, variant_index))
+            });
+
+            if let Some((preset_index, variant_index)) = selected {
+                active_routes.push(self.apply_group(ActiveRoute::select(
+                    Arc::clone(route),
+                    preset_index,
+                    variant_index,
+                ))?);
+            }
+        }
+
+        if active_routes.is_empty() {
+            return Err(ResolveError::NoWildcardMatches {
+                reference: route_ref_str.to_string(),
+            });
+        }
+
+        Ok(active_routes)
+    }
+
     /// Resolve a collection by ID, returning all active routes.
     ///
     /// Supports inheritance via `from` field and detects circular dependencies.
     /// Child collections override parent routes with the same route_id.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(Display, level = "debug"))
+    )]
     pub fn resolve_collection(
         &self,
         collection_id: &str,
     ) -> Result<Vec<ActiveRoute>, ResolveError> {
-        let mut visited = HashSet::new();
+        let mut visited = Vec::new();
         let mut route_map = HashMap::new(); // route_id -> ActiveRoute (for deduplication)
 
         self.resolve_collection_recursive(collection_id, &mut visited, &mut route_map)?;
@@ -204,6 +698,10 @@ impl MocksManager {
             &mut result,
         )?;
 
+        // Substitute collection variables into resolved routes
+        self.apply_collection_vars(collection_id, &mut result)?;
+
+        tracing::debug!(routes = result.len(), "collection resolved");
         Ok(result)
     }
 
@@ -211,17 +709,18 @@ impl MocksManager {
     ///
     /// Detects circular dependencies and resolves parent collections first.
     /// Child routes override parent routes with the same route_id.
+    /// `visited` holds the inheritance path walked so far, reported on cycles.
     fn resolve_collection_recursive(
         &self,
         collection_id: &str,
-        visited: &mut HashSet<String>,
+        visited: &mut Vec<String>,
         route_map: &mut HashMap<String, ActiveRoute>,
     ) -> Result<(), ResolveError> {
         // Detect circular dependency
-        if visited.contains(collection_id) {
-            return Err(ResolveError::CircularDependency {
-                collection_id: collection_id.to_string(),
-            });
+        if visited.iter().any(|id| id == collection_id) {
+            let mut path = visited.clone();
+            path.push(collection_id.to_string());
+            return Err(ResolveError::CircularDependency { path });
         }
 
         // Get collection
@@ -232,7 +731,7 @@ impl MocksManager {
         })?;
 
         // Mark as visited
-        visited.insert(collection_id.to_string());
+        visited.push(collection_id.to_string());
 
         // First, resolve parent collection if exists
         if let Some(parent_id) = &collection.from {
@@ -243,11 +742,11 @@ impl MocksManager {
         for route_ref_str in &collection.routes {
             let active_route = self.resolve_route_reference(route_ref_str)?;
             // Child routes override parent routes
-            route_map.insert(active_route.route.id.clone(), active_route);
+            route_map.insert(active_route.route.id.to_string(), active_route);
         }
 
         // Remove from visited after processing (allows reuse in different branches)
-        visited.remove(collection_id);
+        visited.pop();
 
         Ok(())
     }
@@ -277,7 +776,7 @@ impl MocksManager {
         for route_ref_str in &collection.routes {
             let route_ref = RouteReference::parse(route_ref_str).ok_or_else(|| {
                 ResolveError::InvalidRouteReference {
-                    reference: route_ref_str.clone(),
+                    reference: route_ref_str.to_string(),
                 }
             })?;
 
@@ -294,12 +793,228 @@ impl MocksManager {
     }
 }
 
+/// Substitute collection variables into route URL, response headers and body.
+fn substitute_vars(active_route: &mut ActiveRoute, collection_vars: &Vars) {
+    let url = vars::substitute_str(&active_route.route.url, collection_vars);
+    active_route.route_mut().url = url;
+
+    let variant = active_route.variant_mut();
+    if let Some(headers) = &mut variant.headers {
+        for value in headers.values_mut() {
+            *value = vars::substitute_str(value, collection_vars);
+        }
+    }
+    if let Some(body) = &variant.body {
+        variant.body = Some(vars::substitute_value(body, collection_vars));
+    }
+}
+
+/// Get the size of a value serialized to JSON, without allocating it.
+fn serialized_size<T: serde::Serialize>(value: &T) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    // Writing to the counter can't fail, definitions always serialize
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
 impl Default for MocksManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Replace IDs of a route, its presets and variants, and response header names
+/// with pooled copies.
+fn intern_route(interner: &mut Interner, route: &mut Route) {
+    interner.intern_in_place(&mut route.id);
+    for preset in &mut route.presets {
+        interner.intern_in_place(&mut preset.id);
+        for variant in &mut preset.variants {
+            interner.intern_in_place(&mut variant.id);
+            if let Some(headers) = &mut variant.headers {
+                intern_keys(interner, headers);
+            }
+        }
+    }
+}
+
+/// Replace the ID and route references of a collection with pooled copies.
+fn intern_collection(interner: &mut Interner, collection: &mut Collection) {
+    interner.intern_in_place(&mut collection.id);
+    for route_ref in &mut collection.routes {
+        interner.intern_in_place(route_ref);
+    }
+}
+
+/// Replace header names with pooled copies.
+fn intern_keys(interner: &mut Interner, headers: &mut HashMap<Arc<str>, String>) {
+    *headers = headers
+        .drain()
+        .map(|(name, value)| (interner.intern(&name), value))
+        .collect();
+}
+
+/// Insert a definition by ID, applying the duplicate policy if the ID is taken.
+///
+/// `merge` combines definitions under [`DuplicatePolicy::Merge`].
+fn insert_definition<K: Eq + Hash + Display, T>(
+    definitions: &mut HashMap<K, T>,
+    policy: &DuplicatePolicy,
+    kind: DefinitionKind,
+    id: K,
+    definition: T,
+    merge: fn(&mut T, T),
+) -> Result<(), DuplicateIdError> {
+    if let Some(existing) = definitions.get_mut(&id) {
+        match policy {
+            DuplicatePolicy::Override => {}
+            DuplicatePolicy::Warn(callback) => callback(&DuplicateIdError {
+                kind,
+                id: id.to_string(),
+            }),
+            DuplicatePolicy::Error => {
+                return Err(DuplicateIdError {
+                    kind,
+                    id: id.to_string(),
+                })
+            }
+            DuplicatePolicy::Merge => {
+                merge(existing, definition);
+                return Ok(());
+            }
+        }
+    }
+    definitions.insert(id, definition);
+    Ok(())
+}
+
+/// Merge function replacing the existing definition.
+fn replace<T>(existing: &mut T, definition: T) {
+    *existing = definition;
+}
+
+/// Find the smallest ID defined in both maps.
+fn first_conflict<K: Eq + Hash + Ord + Display, T>(
+    existing: &HashMap<K, T>,
+    incoming: &HashMap<K, T>,
+    kind: DefinitionKind,
+) -> Option<DuplicateIdError> {
+    incoming
+        .keys()
+        .filter(|id| existing.contains_key(*id))
+        .min()
+        .map(|id| DuplicateIdError {
+            kind,
+            id: id.to_string(),
+        })
+}
+
+/// Move incoming definitions into existing ones, resolving conflicts by strategy.
+fn merge_definitions<K: Eq + Hash, T>(
+    existing: &mut HashMap<K, T>,
+    incoming: HashMap<K, T>,
+    strategy: MergeStrategy,
+) {
+    for (id, definition) in incoming {
+        match strategy {
+            MergeStrategy::KeepExisting => {
+                existing.entry(id).or_insert(definition);
+            }
+            MergeStrategy::Replace | MergeStrategy::Error => {
+                existing.insert(id, definition);
+            }
+        }
+    }
+}
+
+/// How [`MocksManager::merge`] resolves definitions present in both managers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep definitions of the manager being merged into
+    KeepExisting,
+    /// Replace definitions with the ones from the merged manager
+    Replace,
+    /// Reject the merge if any ID is defined in both managers
+    Error,
+}
+
+/// Kind of definition stored in [`MocksManager`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefinitionKind {
+    Route,
+    Collection,
+    Group,
+}
+
+impl std::fmt::Display for DefinitionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefinitionKind::Route => write!(f, "route"),
+            DefinitionKind::Collection => write!(f, "collection"),
+            DefinitionKind::Group => write!(f, "group"),
+        }
+    }
+}
+
+/// Callback notified when a definition overrides an existing one with the same ID
+pub type DuplicateCallback = Arc<dyn Fn(&DuplicateIdError) + Send + Sync>;
+
+/// How [`MocksManager`] handles definitions added with an already existing ID.
+#[derive(Clone, Default)]
+pub enum DuplicatePolicy {
+    /// Silently replace the existing definition
+    #[default]
+    Override,
+    /// Replace the existing definition and report the duplicate to the callback
+    Warn(DuplicateCallback),
+    /// Keep the existing definition and return an error
+    Error,
+    /// Merge routes with [`Route::merge`] so their presets can be split across files,
+    /// replace other definitions
+    Merge,
+}
+
+impl std::fmt::Debug for DuplicatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicatePolicy::Override => write!(f, "Override"),
+            DuplicatePolicy::Warn(_) => write!(f, "Warn(..)"),
+            DuplicatePolicy::Error => write!(f, "Error"),
+            DuplicatePolicy::Merge => write!(f, "Merge"),
+        }
+    }
+}
+
+/// Definition added with an ID that is already taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateIdError {
+    /// Kind of the duplicated definition
+    pub kind: DefinitionKind,
+    /// Duplicated ID
+    pub id: String,
+}
+
+impl std::fmt::Display for DuplicateIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Duplicate {} ID: {}", self.kind, self.id)
+    }
+}
+
+impl std::error::Error for DuplicateIdError {}
+
 /// Errors that can occur during collection resolution
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveError {
@@ -315,10 +1030,20 @@ pub enum ResolveError {
         preset_id: String,
         variant_id: String,
     },
+    /// Route has no presets to resolve a shorthand reference to
+    NoPresets { route_id: String },
+    /// Preset has no variants to resolve a shorthand reference to
+    NoVariants { route_id: String, preset_id: String },
     /// Invalid route reference format
     InvalidRouteReference { reference: String },
-    /// Circular dependency detected
-    CircularDependency { collection_id: String },
+    /// Group referenced by a route not found
+    GroupNotFound { route_id: String, group_id: String },
+    /// No route is tagged with the given tag
+    TagNotFound { tag: String },
+    /// Wildcard route reference matched no routes
+    NoWildcardMatches { reference: String },
+    /// Circular dependency detected, with the inheritance path forming the cycle
+    CircularDependency { path: Vec<String> },
     /// Transport type mismatch (e.g., HTTP route used with useSocket)
     TransportMismatch {
         route_id: String,
@@ -358,16 +1083,34 @@ impl std::fmt::Display for ResolveError {
                     variant_id, preset_id, route_id
                 )
             }
-            ResolveError::InvalidRouteReference { reference } => {
-                write!(f, "Invalid route reference format: {}", reference)
+            ResolveError::NoPresets { route_id } => {
+                write!(f, "Route '{}' has no presets", route_id)
             }
-            ResolveError::CircularDependency { collection_id } => {
+            ResolveError::NoVariants {
+                route_id,
+                preset_id,
+            } => {
                 write!(
                     f,
-                    "Circular dependency detected involving collection: {}",
-                    collection_id
+                    "Preset '{}' of route '{}' has no variants",
+                    preset_id, route_id
                 )
             }
+            ResolveError::InvalidRouteReference { reference } => {
+                write!(f, "Invalid route reference format: {}", reference)
+            }
+            ResolveError::GroupNotFound { route_id, group_id } => {
+                write!(f, "Group '{}' not found for route '{}'", group_id, route_id)
+            }
+            ResolveError::TagNotFound { tag } => {
+                write!(f, "No routes found with tag: {}", tag)
+            }
+            ResolveError::NoWildcardMatches { reference } => {
+                write!(f, "No routes match wildcard reference: {}", reference)
+            }
+            ResolveError::CircularDependency { path } => {
+                write!(f, "Circular dependency detected: {}", path.join(" -> "))
+            }
             ResolveError::TransportMismatch {
                 route_id,
                 expected: _,
@@ -394,17 +1137,22 @@ mod tests {
 
     fn create_test_route(id: &str) -> Route {
         Route {
-            id: id.to_string(),
+            id: id.into(),
             url: format!("/api/{}", id),
             transport: Transport::Http,
             method: Some(HttpMethod::Get),
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         }
     }
 
     fn create_test_preset(id: &str) -> Preset {
         Preset {
-            id: id.to_string(),
+            id: id.into(),
             params: None,
             query: None,
             headers: None,
@@ -415,10 +1163,11 @@ mod tests {
 
     fn create_test_variant(id: &str) -> Variant {
         Variant {
-            id: id.to_string(),
+            id: id.into(),
             status: Some(200),
             headers: None,
             body: None,
+            template: None,
         }
     }
 
@@ -431,22 +1180,23 @@ mod tests {
         let mut preset = create_test_preset("preset1");
         preset.variants.push(create_test_variant("variant1"));
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create collection
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         // Resolve
         let result = manager.resolve_collection("collection1").unwrap();
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].route.id, "route1");
-        assert_eq!(result[0].preset.id, "preset1");
-        assert_eq!(result[0].variant.id, "variant1");
+        assert_eq!(result[0].route.id, "route1".into());
+        assert_eq!(result[0].preset().id, "preset1".into());
+        assert_eq!(result[0].variant().id, "variant1".into());
     }
 
     #[rstest]
@@ -458,37 +1208,39 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         // Create parent collection
         let parent = Collection {
-            id: "parent".to_string(),
+            id: "parent".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(parent);
+        manager.add_collection(parent).unwrap();
 
         // Create child collection
         let child = Collection {
-            id: "child".to_string(),
+            id: "child".into(),
             from: Some("parent".to_string()),
-            routes: vec!["route2:preset2:variant2".to_string()],
+            vars: None,
+            routes: vec!["route2:preset2:variant2".into()],
         };
-        manager.add_collection(child);
+        manager.add_collection(child).unwrap();
 
         // Resolve child collection
         let result = manager.resolve_collection("child").unwrap();
         assert_eq!(result.len(), 2);
         // Parent routes first
-        assert_eq!(result[0].route.id, "route1");
+        assert_eq!(result[0].route.id, "route1".into());
         // Then child routes
-        assert_eq!(result[1].route.id, "route2");
+        assert_eq!(result[1].route.id, "route2".into());
     }
 
     #[rstest]
@@ -506,31 +1258,33 @@ mod tests {
 
         route.presets.push(preset1);
         route.presets.push(preset2);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         // Create parent collection
         let parent = Collection {
-            id: "parent".to_string(),
+            id: "parent".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(parent);
+        manager.add_collection(parent).unwrap();
 
         // Create child collection with same route but different preset
         let child = Collection {
-            id: "child".to_string(),
+            id: "child".into(),
             from: Some("parent".to_string()),
-            routes: vec!["route1:preset2:variant2".to_string()],
+            vars: None,
+            routes: vec!["route1:preset2:variant2".into()],
         };
-        manager.add_collection(child);
+        manager.add_collection(child).unwrap();
 
         // Resolve child collection
         let result = manager.resolve_collection("child").unwrap();
         // Child should override parent, so only one route with preset2
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].route.id, "route1");
-        assert_eq!(result[0].preset.id, "preset2");
-        assert_eq!(result[0].variant.id, "variant2");
+        assert_eq!(result[0].route.id, "route1".into());
+        assert_eq!(result[0].preset().id, "preset2".into());
+        assert_eq!(result[0].variant().id, "variant2".into());
     }
 
     #[rstest]
@@ -539,26 +1293,51 @@ mod tests {
 
         // Create circular dependency: A -> B -> A
         let collection_a = Collection {
-            id: "A".to_string(),
+            id: "A".into(),
             from: Some("B".to_string()),
+            vars: None,
             routes: vec![],
         };
         let collection_b = Collection {
-            id: "B".to_string(),
+            id: "B".into(),
             from: Some("A".to_string()),
+            vars: None,
             routes: vec![],
         };
 
-        manager.add_collection(collection_a);
-        manager.add_collection(collection_b);
+        manager.add_collection(collection_a).unwrap();
+        manager.add_collection(collection_b).unwrap();
 
         // Should detect circular dependency
         let result = manager.resolve_collection("A");
         assert!(result.is_err());
-        assert!(matches!(
+        assert_eq!(
             result.unwrap_err(),
-            ResolveError::CircularDependency { .. }
-        ));
+            ResolveError::CircularDependency {
+                path: vec!["A".to_string(), "B".to_string(), "A".to_string()],
+            }
+        );
+    }
+
+    #[rstest]
+    fn test_resolve_collection_deep_circular_dependency() {
+        let mut manager = MocksManager::new();
+        for (id, from) in [("child", "base"), ("base", "auth"), ("auth", "base")] {
+            manager
+                .add_collection(Collection {
+                    id: id.into(),
+                    from: Some(from.to_string()),
+                    vars: None,
+                    routes: vec![],
+                })
+                .unwrap();
+        }
+
+        let error = manager.resolve_collection("child").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Circular dependency detected: child -> base -> auth -> base"
+        );
     }
 
     #[rstest]
@@ -576,11 +1355,12 @@ mod tests {
     fn test_resolve_collection_route_not_found() {
         let mut manager = MocksManager::new();
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["nonexistent:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["nonexistent:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let result = manager.resolve_collection("collection1");
         assert!(result.is_err());
@@ -594,11 +1374,12 @@ mod tests {
     fn test_resolve_collection_invalid_reference() {
         let mut manager = MocksManager::new();
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["invalid-format".to_string()],
+            vars: None,
+            routes: vec!["route1::variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let result = manager.resolve_collection("collection1");
         assert!(result.is_err());
@@ -617,50 +1398,53 @@ mod tests {
         let mut preset1 = create_test_preset("preset1");
         preset1.variants.push(create_test_variant("variant1"));
         route1.presets.push(preset1);
-        manager.add_route(route1);
+        manager.add_route(route1).unwrap();
 
         let mut route2 = create_test_route("route2");
         let mut preset2 = create_test_preset("preset2");
         preset2.variants.push(create_test_variant("variant2"));
         route2.presets.push(preset2);
-        manager.add_route(route2);
+        manager.add_route(route2).unwrap();
 
         let mut route3 = create_test_route("route3");
         let mut preset3 = create_test_preset("preset3");
         preset3.variants.push(create_test_variant("variant3"));
         route3.presets.push(preset3);
-        manager.add_route(route3);
+        manager.add_route(route3).unwrap();
 
         // Create grandparent
         let grandparent = Collection {
-            id: "grandparent".to_string(),
+            id: "grandparent".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(grandparent);
+        manager.add_collection(grandparent).unwrap();
 
         // Create parent
         let parent = Collection {
-            id: "parent".to_string(),
+            id: "parent".into(),
             from: Some("grandparent".to_string()),
-            routes: vec!["route2:preset2:variant2".to_string()],
+            vars: None,
+            routes: vec!["route2:preset2:variant2".into()],
         };
-        manager.add_collection(parent);
+        manager.add_collection(parent).unwrap();
 
         // Create child
         let child = Collection {
-            id: "child".to_string(),
+            id: "child".into(),
             from: Some("parent".to_string()),
-            routes: vec!["route3:preset3:variant3".to_string()],
+            vars: None,
+            routes: vec!["route3:preset3:variant3".into()],
         };
-        manager.add_collection(child);
+        manager.add_collection(child).unwrap();
 
         // Resolve child collection
         let result = manager.resolve_collection("child").unwrap();
         assert_eq!(result.len(), 3);
-        assert_eq!(result[0].route.id, "route1"); // grandparent
-        assert_eq!(result[1].route.id, "route2"); // parent
-        assert_eq!(result[2].route.id, "route3"); // child
+        assert_eq!(result[0].route.id, "route1".into()); // grandparent
+        assert_eq!(result[1].route.id, "route2".into()); // parent
+        assert_eq!(result[2].route.id, "route3".into()); // child
     }
 
     #[rstest]
@@ -668,17 +1452,19 @@ mod tests {
         let mut manager = MocksManager::new();
         let collections = vec![
             Collection {
-                id: "collection1".to_string(),
+                id: "collection1".into(),
                 from: None,
+                vars: None,
                 routes: vec![],
             },
             Collection {
-                id: "collection2".to_string(),
+                id: "collection2".into(),
                 from: None,
+                vars: None,
                 routes: vec![],
             },
         ];
-        manager.add_collections(collections);
+        manager.add_collections(collections).unwrap();
         assert_eq!(manager.collections.len(), 2);
     }
 
@@ -686,7 +1472,7 @@ mod tests {
     fn test_add_routes() {
         let mut manager = MocksManager::new();
         let routes = vec![create_test_route("route1"), create_test_route("route2")];
-        manager.add_routes(routes);
+        manager.add_routes(routes).unwrap();
         assert_eq!(manager.routes.len(), 2);
     }
 
@@ -695,14 +1481,15 @@ mod tests {
         let mut manager = MocksManager::new();
         let route = create_test_route("route1");
         // Route has no presets
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let result = manager.resolve_collection("collection1");
         assert!(result.is_err());
@@ -719,14 +1506,15 @@ mod tests {
         let preset = create_test_preset("preset1");
         // Preset has no variants
         route.presets.push(preset);
-        manager.add_route(route);
+        manager.add_route(route).unwrap();
 
         let collection = Collection {
-            id: "collection1".to_string(),
+            id: "collection1".into(),
             from: None,
-            routes: vec!["route1:preset1:variant1".to_string()],
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
         };
-        manager.add_collection(collection);
+        manager.add_collection(collection).unwrap();
 
         let result = manager.resolve_collection("collection1");
         assert!(result.is_err());
@@ -736,6 +1524,494 @@ mod tests {
         ));
     }
 
+    #[rstest]
+    fn test_list_collections_and_routes_sorted() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_routes(vec![create_test_route("b"), create_test_route("a")])
+            .unwrap();
+        manager
+            .add_collections(vec![
+                Collection {
+                    id: "second".into(),
+                    from: None,
+                    vars: None,
+                    routes: vec![],
+                },
+                Collection {
+                    id: "first".into(),
+                    from: None,
+                    vars: None,
+                    routes: vec![],
+                },
+            ])
+            .unwrap();
+
+        let route_ids: Vec<&str> = manager
+            .list_routes()
+            .iter()
+            .map(|r| r.id.as_ref())
+            .collect();
+        assert_eq!(route_ids, vec!["a", "b"]);
+
+        let collection_ids: Vec<&str> = manager
+            .list_collections()
+            .iter()
+            .map(|c| c.id.as_ref())
+            .collect();
+        assert_eq!(collection_ids, vec!["first", "second"]);
+    }
+
+    #[rstest]
+    #[case("a", true)]
+    #[case("missing", false)]
+    fn test_get_route(#[case] route_id: &str, #[case] found: bool) {
+        let mut manager = MocksManager::new();
+        manager.add_route(create_test_route("a")).unwrap();
+        assert_eq!(manager.get_route(route_id).is_some(), found);
+    }
+
+    #[rstest]
+    fn test_collection_tree() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_collections(vec![
+                Collection {
+                    id: "grandparent".into(),
+                    from: None,
+                    vars: None,
+                    routes: vec![],
+                },
+                Collection {
+                    id: "parent".into(),
+                    from: Some("grandparent".to_string()),
+                    vars: None,
+                    routes: vec![],
+                },
+                Collection {
+                    id: "child".into(),
+                    from: Some("parent".to_string()),
+                    vars: None,
+                    routes: vec![],
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(
+            manager.collection_tree("child").unwrap(),
+            vec!["grandparent", "parent", "child"]
+        );
+        assert_eq!(
+            manager.collection_tree("grandparent").unwrap(),
+            vec!["grandparent"]
+        );
+    }
+
+    #[rstest]
+    fn test_collection_tree_errors() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_collections(vec![
+                Collection {
+                    id: "A".into(),
+                    from: Some("B".to_string()),
+                    vars: None,
+                    routes: vec![],
+                },
+                Collection {
+                    id: "B".into(),
+                    from: Some("A".to_string()),
+                    vars: None,
+                    routes: vec![],
+                },
+                Collection {
+                    id: "orphan".into(),
+                    from: Some("missing".to_string()),
+                    vars: None,
+                    routes: vec![],
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(
+            manager.collection_tree("A").unwrap_err(),
+            ResolveError::CircularDependency {
+                path: vec!["A".to_string(), "B".to_string(), "A".to_string()],
+            }
+        );
+        assert!(matches!(
+            manager.collection_tree("orphan").unwrap_err(),
+            ResolveError::CollectionNotFound { .. }
+        ));
+    }
+
+    #[rstest]
+    fn test_routes_with_tag() {
+        let mut manager = MocksManager::new();
+
+        let mut route1 = create_test_route("route1");
+        route1.tags = vec!["payments".to_string()];
+        let mut route2 = create_test_route("route2");
+        route2.tags = vec!["payments".to_string(), "billing".to_string()];
+        let route3 = create_test_route("route3");
+        manager.add_routes(vec![route2, route3, route1]).unwrap();
+
+        let ids: Vec<&str> = manager
+            .routes_with_tag("payments")
+            .iter()
+            .map(|r| r.id.as_ref())
+            .collect();
+        assert_eq!(ids, vec!["route1", "route2"]);
+        assert_eq!(manager.routes_with_tag("billing").len(), 1);
+        assert!(manager.routes_with_tag("unknown").is_empty());
+    }
+
+    #[rstest]
+    #[case("route1", "preset1", "v1")]
+    #[case("route1:preset2", "preset2", "v2")]
+    #[case("route1:preset2:v3", "preset2", "v3")]
+    fn test_resolve_shorthand_reference(
+        #[case] reference: &str,
+        #[case] preset_id: &str,
+        #[case] variant_id: &str,
+    ) {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("route1");
+        let mut preset1 = create_test_preset("preset1");
+        preset1.variants.push(create_test_variant("v1"));
+        let mut preset2 = create_test_preset("preset2");
+        preset2.variants.push(create_test_variant("v2"));
+        preset2.variants.push(create_test_variant("v3"));
+        route.presets.push(preset1);
+        route.presets.push(preset2);
+        manager.add_route(route).unwrap();
+
+        let active_route = manager.resolve_route_reference(reference).unwrap();
+        assert_eq!(active_route.route.id, "route1".into());
+        assert_eq!(active_route.preset().id, preset_id.into());
+        assert_eq!(active_route.variant().id, variant_id.into());
+    }
+
+    #[rstest]
+    fn test_resolve_shorthand_reference_without_defaults() {
+        let mut manager = MocksManager::new();
+        manager.add_route(create_test_route("empty")).unwrap();
+        let mut route = create_test_route("no-variants");
+        route.presets.push(create_test_preset("preset1"));
+        manager.add_route(route).unwrap();
+
+        assert_eq!(
+            manager.resolve_route_reference("empty").unwrap_err(),
+            ResolveError::NoPresets {
+                route_id: "empty".to_string()
+            }
+        );
+        assert_eq!(
+            manager.resolve_route_reference("no-variants").unwrap_err(),
+            ResolveError::NoVariants {
+                route_id: "no-variants".to_string(),
+                preset_id: "preset1".to_string()
+            }
+        );
+    }
+
+    #[rstest]
+    fn test_resolve_collection_shorthand_references() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("route1");
+        let mut preset = create_test_preset("preset1");
+        preset.variants.push(create_test_variant("v1"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        manager
+            .add_collection(Collection {
+                id: "base".into(),
+                from: None,
+                vars: None,
+                routes: vec!["route1".into()],
+            })
+            .unwrap();
+
+        let active_routes = manager.resolve_collection("base").unwrap();
+        assert_eq!(active_routes.len(), 1);
+        assert_eq!(active_routes[0].variant().id, "v1".into());
+    }
+
+    fn create_wildcard_manager() -> MocksManager {
+        let mut manager = MocksManager::new();
+        for id in ["payments-card", "payments-wallet", "users"] {
+            let mut route = create_test_route(id);
+            let mut preset = create_test_preset("default");
+            preset.variants.push(create_test_variant("ok"));
+            if id != "payments-wallet" {
+                preset.variants.push(create_test_variant("error"));
+            }
+            route.presets.push(preset);
+            manager.add_route(route).unwrap();
+        }
+
+        let mut ws_route = create_test_route("payments-ws");
+        ws_route.transport = Transport::WebSocket;
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("error"));
+        ws_route.presets.push(preset);
+        manager.add_route(ws_route).unwrap();
+        manager
+    }
+
+    #[rstest]
+    #[case("*:*:error", &[("payments-card", "error"), ("users", "error")])]
+    #[case("payments-*", &[("payments-card", "ok"), ("payments-wallet", "ok")])]
+    #[case("payments-*:default:error", &[("payments-card", "error")])]
+    #[case("*:def*:e?ror", &[("payments-card", "error"), ("users", "error")])]
+    fn test_resolve_wildcard_reference(#[case] reference: &str, #[case] expected: &[(&str, &str)]) {
+        let manager = create_wildcard_manager();
+        let active_routes = manager
+            .resolve_wildcard_reference(reference, &Transport::Http)
+            .unwrap();
+        let resolved: Vec<(&str, &str)> = active_routes
+            .iter()
+            .map(|a| (a.route.id.as_ref(), a.variant().id.as_ref()))
+            .collect();
+        assert_eq!(resolved, expected);
+    }
+
+    #[rstest]
+    fn test_resolve_wildcard_reference_by_transport() {
+        let manager = create_wildcard_manager();
+        let active_routes = manager
+            .resolve_wildcard_reference("payments-*:*:error", &Transport::WebSocket)
+            .unwrap();
+        assert_eq!(active_routes.len(), 1);
+        assert_eq!(active_routes[0].route.id, "payments-ws".into());
+    }
+
+    #[rstest]
+    #[case("orders-*", ResolveError::NoWildcardMatches { reference: "orders-*".to_string() })]
+    #[case("*:*:timeout", ResolveError::NoWildcardMatches { reference: "*:*:timeout".to_string() })]
+    #[case("[*", ResolveError::InvalidRouteReference { reference: "[*".to_string() })]
+    fn test_resolve_wildcard_reference_errors(
+        #[case] reference: &str,
+        #[case] expected: ResolveError,
+    ) {
+        let manager = create_wildcard_manager();
+        assert_eq!(
+            manager
+                .resolve_wildcard_reference(reference, &Transport::Http)
+                .unwrap_err(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_resolve_collection_with_vars() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users");
+        route.url = "/api/{{vars.tenant}}/users/{id}".to_string();
+        let mut preset = create_test_preset("default");
+        let mut variant = create_test_variant("ok");
+        variant.headers = Some(HashMap::from([(
+            "X-Tenant".into(),
+            "{{vars.tenant}}".to_string(),
+        )]));
+        variant.body = Some(serde_json::json!({
+            "tenant": "{{vars.tenant}}",
+            "region": "{{vars.region}}",
+            "limit": "{{vars.limit}}"
+        }));
+        preset.variants.push(variant);
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        manager
+            .add_collection(Collection {
+                id: "template".into(),
+                from: None,
+                vars: Some(Vars::from([
+                    ("tenant".to_string(), serde_json::json!("default")),
+                    ("region".to_string(), serde_json::json!("eu")),
+                ])),
+                routes: vec!["users".into()],
+            })
+            .unwrap();
+        manager
+            .add_collection(Collection {
+                id: "acme".into(),
+                from: Some("template".to_string()),
+                vars: Some(Vars::from([
+                    ("tenant".to_string(), serde_json::json!("acme")),
+                    ("limit".to_string(), serde_json::json!(10)),
+                ])),
+                routes: vec![],
+            })
+            .unwrap();
+
+        let active_routes = manager.resolve_collection("acme").unwrap();
+        let active_route = &active_routes[0];
+        assert_eq!(active_route.route.url, "/api/acme/users/{id}");
+        assert_eq!(
+            active_route.variant().headers.as_ref().unwrap()["X-Tenant"],
+            "acme"
+        );
+        assert_eq!(
+            active_route.variant().body,
+            Some(serde_json::json!({"tenant": "acme", "region": "eu", "limit": 10}))
+        );
+
+        // Stored route data stays a template
+        let template_routes = manager.resolve_collection("template").unwrap();
+        assert_eq!(template_routes[0].route.url, "/api/default/users/{id}");
+        assert_eq!(
+            manager.get_route("users").unwrap().url,
+            "/api/{{vars.tenant}}/users/{id}"
+        );
+    }
+
+    #[rstest]
+    fn test_resolve_route_in_group() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_group(Group {
+                id: "api-v2".to_string(),
+                base_url: Some("/api/v2".to_string()),
+                headers: Some(HashMap::from([
+                    ("X-Api-Version".into(), "2".to_string()),
+                    ("Content-Type".into(), "application/json".to_string()),
+                ])),
+            })
+            .unwrap();
+        let mut route = create_test_route("users");
+        route.url = "/users".to_string();
+        route.group = Some("api-v2".to_string());
+        let mut preset = create_test_preset("default");
+        let mut variant = create_test_variant("ok");
+        variant.headers = Some(HashMap::from([(
+            "Content-Type".into(),
+            "text/plain".to_string(),
+        )]));
+        preset.variants.push(variant);
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        let active_route = manager.resolve_route_reference("users").unwrap();
+        assert_eq!(active_route.route.url, "/api/v2/users");
+        let headers = active_route.variant().headers.clone().unwrap();
+        assert_eq!(headers["X-Api-Version"], "2");
+        assert_eq!(headers["Content-Type"], "text/plain");
+
+        // Stored route keeps its own URL
+        assert_eq!(manager.get_route("users").unwrap().url, "/users");
+    }
+
+    #[rstest]
+    fn test_ids_and_header_names_are_pooled() {
+        let mut manager = MocksManager::new();
+        for id in ["users", "orders"] {
+            let mut route = create_test_route(id);
+            let mut preset = create_test_preset("default");
+            let mut variant = create_test_variant("ok");
+            variant.headers = Some(HashMap::from([(
+                "Content-Type".into(),
+                "application/json".to_string(),
+            )]));
+            preset.variants.push(variant);
+            route.presets.push(preset);
+            manager.add_route(route).unwrap();
+        }
+        for id in ["base", "copy"] {
+            manager
+                .add_collection(Collection::builder(id).route("users:default:ok").build())
+                .unwrap();
+        }
+
+        let users = manager.get_route("users").unwrap();
+        let orders = manager.get_route("orders").unwrap();
+        let variant = |route: &Route| route.presets[0].variants[0].clone();
+        assert!(Arc::ptr_eq(&users.presets[0].id, &orders.presets[0].id));
+        assert!(Arc::ptr_eq(&variant(users).id, &variant(orders).id));
+        let header_name = |route: &Route| {
+            let headers = variant(route).headers.unwrap();
+            Arc::clone(headers.keys().next().unwrap())
+        };
+        assert!(Arc::ptr_eq(&header_name(users), &header_name(orders)));
+
+        let collections = manager.list_collections();
+        assert!(Arc::ptr_eq(
+            &collections[0].routes[0],
+            &collections[1].routes[0]
+        ));
+    }
+
+    #[rstest]
+    fn test_estimated_size() {
+        let mut manager = MocksManager::new();
+        assert_eq!(manager.estimated_size(), 0);
+
+        let mut route = create_test_route("users");
+        let mut preset = create_test_preset("default");
+        let mut variant = create_test_variant("ok");
+        variant.body = Some(serde_json::json!({"data": "x".repeat(10_000)}));
+        preset.variants.push(variant);
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+        let size = manager.estimated_size();
+        assert!((10_000..11_000).contains(&size), "{size}");
+
+        manager
+            .add_collection(
+                Collection::builder("base")
+                    .route("users:default:ok")
+                    .build(),
+            )
+            .unwrap();
+        assert!(manager.estimated_size() > size);
+        manager.remove_route("users");
+        assert!(manager.estimated_size() < 1_000);
+    }
+
+    #[rstest]
+    fn test_resolved_routes_share_stored_route() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users");
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        preset.variants.push(create_test_variant("error"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        let first = manager.resolve_route_reference("users").unwrap();
+        let mut second = manager
+            .resolve_route_reference("users:default:error")
+            .unwrap();
+        assert!(Arc::ptr_eq(&first.route, &second.route));
+        assert_eq!(second.variant().id, "error".into());
+
+        // Modifying a resolved route copies it
+        second.variant_mut().status = Some(500);
+        assert!(!Arc::ptr_eq(&first.route, &second.route));
+        assert_eq!(first.route.presets[0].variants[1].status, Some(200));
+        assert_eq!(second.variant().status, Some(500));
+    }
+
+    #[rstest]
+    fn test_resolve_route_group_not_found() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users");
+        route.group = Some("missing".to_string());
+        let mut preset = create_test_preset("default");
+        preset.variants.push(create_test_variant("ok"));
+        route.presets.push(preset);
+        manager.add_route(route).unwrap();
+
+        assert_eq!(
+            manager.resolve_route_reference("users").unwrap_err(),
+            ResolveError::GroupNotFound {
+                route_id: "users".to_string(),
+                group_id: "missing".to_string(),
+            }
+        );
+    }
+
     #[rstest]
     fn test_resolve_error_display() {
         let error = ResolveError::CollectionNotFound {
@@ -768,17 +2044,203 @@ mod tests {
         assert!(error.to_string().contains("preset1"));
         assert!(error.to_string().contains("variant1"));
 
+        let error = ResolveError::NoPresets {
+            route_id: "route1".to_string(),
+        };
+        assert!(error.to_string().contains("no presets"));
+        assert!(error.to_string().contains("route1"));
+
+        let error = ResolveError::NoVariants {
+            route_id: "route1".to_string(),
+            preset_id: "preset1".to_string(),
+        };
+        assert!(error.to_string().contains("no variants"));
+        assert!(error.to_string().contains("preset1"));
+
         let error = ResolveError::InvalidRouteReference {
             reference: "invalid".to_string(),
         };
         assert!(error.to_string().contains("Invalid route reference"));
         assert!(error.to_string().contains("invalid"));
 
+        let error = ResolveError::GroupNotFound {
+            route_id: "route1".to_string(),
+            group_id: "api".to_string(),
+        };
+        assert!(error.to_string().contains("Group 'api' not found"));
+        assert!(error.to_string().contains("route1"));
+
+        let error = ResolveError::TagNotFound {
+            tag: "payments".to_string(),
+        };
+        assert!(error.to_string().contains("tag"));
+        assert!(error.to_string().contains("payments"));
+
+        let error = ResolveError::NoWildcardMatches {
+            reference: "payments-*".to_string(),
+        };
+        assert!(error.to_string().contains("wildcard"));
+        assert!(error.to_string().contains("payments-*"));
+
         let error = ResolveError::CircularDependency {
-            collection_id: "A".to_string(),
+            path: vec!["base".to_string(), "auth".to_string(), "base".to_string()],
         };
         assert!(error.to_string().contains("Circular dependency"));
-        assert!(error.to_string().contains("A"));
+        assert!(error.to_string().contains("base -> auth -> base"));
+    }
+
+    #[rstest]
+    fn test_duplicate_policy_override() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("route1");
+        manager.add_route(route.clone()).unwrap();
+
+        route.url = "/api/replaced".to_string();
+        manager.add_route(route).unwrap();
+        assert_eq!(manager.get_route("route1").unwrap().url, "/api/replaced");
+    }
+
+    #[rstest]
+    fn test_duplicate_policy_warn() {
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let mut manager =
+            MocksManager::with_duplicate_policy(DuplicatePolicy::Warn(Arc::new(move |e| {
+                sink.lock().unwrap().push(e.clone());
+            })));
+
+        let mut route = create_test_route("route1");
+        manager
+            .add_routes(vec![route.clone(), create_test_route("route2")])
+            .unwrap();
+        route.url = "/api/replaced".to_string();
+        manager.add_route(route).unwrap();
+
+        assert_eq!(manager.get_route("route1").unwrap().url, "/api/replaced");
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![DuplicateIdError {
+                kind: DefinitionKind::Route,
+                id: "route1".to_string(),
+            }]
+        );
+    }
+
+    #[rstest]
+    fn test_duplicate_policy_error() {
+        let mut manager = MocksManager::with_duplicate_policy(DuplicatePolicy::Error);
+        let collection = Collection {
+            id: "base".into(),
+            from: None,
+            vars: None,
+            routes: vec!["route1:preset1:variant1".into()],
+        };
+        manager.add_collection(collection.clone()).unwrap();
+
+        let error = manager
+            .add_collection(Collection {
+                routes: vec![],
+                ..collection
+            })
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate collection ID: base");
+        // Existing definition is kept
+        assert_eq!(manager.list_collections()[0].routes.len(), 1);
+    }
+
+    #[rstest]
+    fn test_remove_definitions() {
+        let mut manager = MocksManager::new();
+        manager.add_route(create_test_route("users")).unwrap();
+        manager
+            .add_group(Group {
+                id: "api".to_string(),
+                base_url: None,
+                headers: None,
+            })
+            .unwrap();
+
+        assert_eq!(manager.remove_route("users").unwrap().id, "users".into());
+        assert!(manager.remove_route("users").is_none());
+        assert!(manager.remove_group("api").is_some());
+        assert!(manager.remove_collection("base").is_none());
+        assert!(manager.list_routes().is_empty());
+        assert!(manager.list_groups().is_empty());
+    }
+
+    #[rstest]
+    fn test_duplicate_policy_merge() {
+        let mut manager = MocksManager::with_duplicate_policy(DuplicatePolicy::Merge);
+        let mut users = create_test_route("users");
+        users.presets = vec![create_test_preset("preset1")];
+        manager.add_route(users.clone()).unwrap();
+        users.presets = vec![create_test_preset("other")];
+        users.url = "/ignored".to_string();
+        manager.add_route(users).unwrap();
+
+        let route = manager.get_route("users").unwrap();
+        let preset_ids: Vec<&str> = route.presets.iter().map(|p| p.id.as_ref()).collect();
+        assert_eq!(preset_ids, vec!["preset1", "other"]);
+        assert_eq!(route.url, "/api/users");
+
+        // Other definitions are replaced
+        let group = |base_url: &str| Group {
+            id: "api".to_string(),
+            base_url: Some(base_url.to_string()),
+            headers: None,
+        };
+        manager.add_group(group("/v1")).unwrap();
+        manager.add_group(group("/v2")).unwrap();
+        assert_eq!(
+            manager.get_group("api").unwrap().base_url.as_deref(),
+            Some("/v2")
+        );
+    }
+
+    fn create_merge_managers() -> (MocksManager, MocksManager) {
+        let mut shared = MocksManager::new();
+        shared.add_route(create_test_route("users")).unwrap();
+        shared.add_route(create_test_route("health")).unwrap();
+
+        let mut app = MocksManager::new();
+        let mut users = create_test_route("users");
+        users.url = "/app/users".to_string();
+        app.add_route(users).unwrap();
+        app.add_route(create_test_route("orders")).unwrap();
+        (shared, app)
+    }
+
+    #[rstest]
+    #[case(MergeStrategy::KeepExisting, "/api/users")]
+    #[case(MergeStrategy::Replace, "/app/users")]
+    fn test_merge(#[case] strategy: MergeStrategy, #[case] users_url: &str) {
+        let (mut shared, app) = create_merge_managers();
+        shared.merge(app, strategy).unwrap();
+
+        let route_ids: Vec<&str> = shared.list_routes().iter().map(|r| r.id.as_ref()).collect();
+        assert_eq!(route_ids, vec!["health", "orders", "users"]);
+        assert_eq!(shared.get_route("users").unwrap().url, users_url);
+    }
+
+    #[rstest]
+    fn test_merge_error_strategy() {
+        let (mut shared, app) = create_merge_managers();
+        let error = shared.merge(app, MergeStrategy::Error).unwrap_err();
+        assert_eq!(
+            error,
+            DuplicateIdError {
+                kind: DefinitionKind::Route,
+                id: "users".to_string(),
+            }
+        );
+        // Nothing is merged on conflict
+        assert!(shared.get_route("orders").is_none());
+
+        let (mut shared, _) = create_merge_managers();
+        let mut other = MocksManager::new();
+        other.add_route(create_test_route("orders")).unwrap();
+        shared.merge(other, MergeStrategy::Error).unwrap();
+        assert!(shared.get_route("orders").is_some());
     }
 
     #[rstest]
@@ -0,0 +1,249 @@
+//! Cache of request match results for a route table.
+//!
+//! Polling clients send the same request over and over. [`MatchCache`] remembers
+//! which route matched a request, keyed by the parts of the request the routes of
+//! the table look at: method, URL and the headers and query parameters presets
+//! match on.
+//!
+//! A cache belongs to one [`RouteTable`](crate::mocks::table::RouteTable). Tables
+//! are rebuilt on every change of the active routes, so cached results never
+//! outlive the routes they were computed from.
+//!
+//! Results only depend on the key if matching is deterministic, so tables with
+//! routes matching by expression (which may call custom functions) aren't
+//! cached, and requests with a payload aren't cached if a route matches payloads.
+//! GraphQL routes match the operation of the payload or query parameters, so
+//! they count as matching both, and TCP routes match the message payload.
+
+use crate::mocks::controller::Request;
+use crate::mocks::manager::ActiveRoute;
+use crate::types::preset::{HeadersOrExpression, PayloadOrExpression, QueryOrExpression};
+use crate::types::route::{HttpMethod, Transport};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Mutex, PoisonError};
+
+/// Parts of a request route matching depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MatchKey {
+    transport: Transport,
+    method: Option<HttpMethod>,
+    /// URL without trailing slash of the path
+    url: String,
+    /// Values of the matched header names, in order of `MatchCache::header_names`
+    headers: Vec<Option<String>>,
+    /// Query parameters given apart from the URL, sorted
+    query: Vec<(String, String)>,
+}
+
+/// LRU cache of matched route indices by request.
+pub(crate) struct MatchCache {
+    /// Maximum number of cached requests
+    capacity: usize,
+    /// Lowercase names of headers matched by any route
+    header_names: Vec<String>,
+    /// Whether any route matches query parameters
+    matches_query: bool,
+    /// Whether any route matches payloads
+    matches_payload: bool,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Index of the first matching route (`None` if no route matches) and last use
+    entries: HashMap<MatchKey, (Option<usize>, u64)>,
+    /// Incremented on every access
+    clock: u64,
+}
+
+impl MatchCache {
+    /// Create a cache for the enabled routes of a table.
+    ///
+    /// Returns `None` if `capacity` is 0 or a route matches by expression.
+    pub(crate) fn new(
+        routes: &[ActiveRoute],
+        disabled: &HashSet<String>,
+        capacity: usize,
+    ) -> Option<Self> {
+        if capacity == 0 {
+            return None;
+        }
+        let mut header_names = BTreeSet::new();
+        let mut matches_query = false;
+        let mut matches_payload = false;
+        for active_route in routes {
+            if disabled.contains(&*active_route.route.id) {
+                continue;
+            }
+            if active_route.route.graphql.is_some() {
+                matches_query = true;
+                matches_payload = true;
+            }
+            if active_route.route.tcp.is_some() {
+                matches_payload = true;
+            }
+            let preset = active_route.preset();
+            match &preset.headers {
+                Some(HeadersOrExpression::Expression(_)) => return None,
+                Some(HeadersOrExpression::Map(headers)) => {
+                    header_names.extend(headers.keys().map(|name| name.to_lowercase()));
+                }
+                None => {}
+            }
+            match &preset.query {
+                Some(QueryOrExpression::Expression(_)) => return None,
+                Some(QueryOrExpression::Map(_)) => matches_query = true,
+                None => {}
+            }
+            match &preset.payload {
+                Some(PayloadOrExpression::Expression(_)) => return None,
+                Some(PayloadOrExpression::Value(_)) => matches_payload = true,
+                None => {}
+            }
+        }
+
+        Some(Self {
+            capacity,
+            header_names: header_names.into_iter().collect(),
+            matches_query,
+            matches_payload,
+            state: Mutex::new(CacheState::default()),
+        })
+    }
+
+    /// Get the key of a request, `None` if its result can't be cached.
+    pub(crate) fn key(&self, request: &Request) -> Option<MatchKey> {
+        if self.matches_payload && request.payload.is_some() {
+            return None;
+        }
+        let headers = self
+            .header_names
+            .iter()
+            .map(|name| {
+                let headers = request.headers.as_ref()?;
+                headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.clone())
+            })
+            .collect();
+        let query = match &request.query {
+            Some(query) if self.matches_query => {
+                let mut query: Vec<_> = query
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                query.sort_unstable();
+                query
+            }
+            _ => Vec::new(),
+        };
+
+        Some(MatchKey {
+            transport: request.transport.clone(),
+            method: request.method.clone(),
+            url: normalize_url(&request.url),
+            headers,
+            query,
+        })
+    }
+
+    /// Get the cached result of a request.
+    ///
+    /// Returns `Some(None)` if the request is known to match no route.
+    pub(crate) fn get(&self, key: &MatchKey) -> Option<Option<usize>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.clock += 1;
+        let clock = state.clock;
+        let (index, last_used) = state.entries.get_mut(key)?;
+        *last_used = clock;
+        Some(*index)
+    }
+
+    /// Cache the result of a request, evicting the least recently used one if full.
+    pub(crate) fn insert(&self, key: MatchKey, index: Option<usize>) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.clock += 1;
+        let clock = state.clock;
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(key, (index, clock));
+    }
+
+    /// Get the number of cached requests.
+    pub(crate) fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .len()
+    }
+}
+
+/// Cloned caches start empty, since results belong to the table they were computed for.
+impl Clone for MatchCache {
+    fn clone(&self) -> Self {
+        Self {
+            capacity: self.capacity,
+            header_names: self.header_names.clone(),
+            matches_query: self.matches_query,
+            matches_payload: self.matches_payload,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+}
+
+/// Caches are equal if they are configured alike, regardless of cached results.
+impl PartialEq for MatchCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.capacity == other.capacity
+            && self.header_names == other.header_names
+            && self.matches_query == other.matches_query
+            && self.matches_payload == other.matches_payload
+    }
+}
+
+impl std::fmt::Debug for MatchCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MatchCache({}/{})", self.len(), self.capacity)
+    }
+}
+
+/// Drop a trailing slash of the URL path, which route matching ignores.
+fn normalize_url(url: &str) -> String {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url, None),
+    };
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    };
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("/api/users", "/api/users")]
+    #[case("/api/users/", "/api/users")]
+    #[case("/api/users/?page=1", "/api/users?page=1")]
+    #[case("/", "/")]
+    fn test_normalize_url(#[case] url: &str, #[case] expected: &str) {
+        assert_eq!(normalize_url(url), expected);
+    }
+}
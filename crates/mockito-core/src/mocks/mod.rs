@@ -3,6 +3,42 @@
 //! This module provides functionality for managing mock routes and collections:
 //! - [`MocksManager`]: Stores and resolves collections and routes with inheritance support
 //! - [`MocksController`]: Manages active routes and provides fast route lookup by request matching
+//! - [`RouteTable`]: Immutable snapshot of active routes for lock-free concurrent lookup
+//! - [`RequestHistory`](history::RequestHistory): Bounded history of looked up requests
+//! - [`Verification`](verify::Verification): Assertions about recorded requests
+//! - [`CoverageReport`](coverage::CoverageReport): Routes, presets and variants never matched
+//! - [`export_har`](har::export_har): HAR export of the request history
+//! - [`RouteLatency`](latency::RouteLatency): Serve time percentiles by route and variant
+//! - [`RequestTap`](tap::RequestTap): Live stream of looked up requests for admin dashboards
+//! - [`AuditLog`](audit::AuditLog): Labeled log of controller state changes
+//! - [`SseScript`](sse::SseScript): Event scripts streamed by SSE routes
+//! - [`TcpServer`](tcp::TcpServer): Server answering TCP routes with reply scripts
+//! - [`RequestContext`]: JSON view of a request evaluated by preset expressions
+//! - [`Interner`](intern::Interner): Pool sharing repeated IDs and header names
+//! - [`validation`]: Upfront validation of references, inheritance chains and route integrity
+//!
+//! # Tracing
+//!
+//! Matching, resolution and rendering are instrumented with the `tracing` crate.
+//! Each lookup runs in a `match_request` span with the request `transport`,
+//! `method` and `url` and the matched `route_id`, `preset_id` and `variant_id`,
+//! so mock behavior can be correlated with application traces. See the
+//! [crate documentation](crate#logging) for installing a subscriber.
 
+pub mod audit;
+pub mod context;
 pub mod controller;
+pub mod coverage;
+pub mod har;
+pub mod history;
+pub mod intern;
+pub mod latency;
 pub mod manager;
+mod match_cache;
+pub mod sse;
+pub mod table;
+pub mod tap;
+pub mod tcp;
+pub mod unmatched;
+pub mod validation;
+pub mod verify;
@@ -0,0 +1,281 @@
+//! Server-Sent Events scripts of SSE routes.
+//!
+//! Variants of routes with `transport: SSE` hold the events to stream in their
+//! body instead of a response body:
+//!
+//! ```yaml
+//! id: order-updates
+//! url: /api/orders/{id}/events
+//! transport: SSE
+//! presets:
+//!   - id: default
+//!     variants:
+//!       - id: shipped
+//!         body:
+//!           retry: 5000
+//!           events:
+//!             - id: "1"
+//!               event: status
+//!               data: { status: packed }
+//!             - id: "2"
+//!               event: status
+//!               delay: 1000
+//!               data: { status: shipped }
+//! ```
+//!
+//! Servers answer SSE requests (`Accept: text/event-stream`) by rendering the
+//! variant like any other, then sending the frames of its [`SseScript`] with
+//! the [`SSE_CONTENT_TYPE`] content type. Reconnecting clients send the ID of
+//! the last event they received in the [`LAST_EVENT_ID_HEADER`] header, and
+//! [`SseScript::resume`] skips the events up to it:
+//!
+//! ```ignore
+//! let variant = controller.respond(&request)?.unwrap();
+//! let script = SseScript::from_variant(&variant)?.resume(last_event_id(&request));
+//! for frame in script.frames() {
+//!     tokio::time::sleep(frame.delay).await;
+//!     stream.send(frame.text).await?;
+//! }
+//! ```
+
+use crate::mocks::controller::Request;
+use crate::types::variant::Variant;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Content type of event streams
+pub const SSE_CONTENT_TYPE: &str = "text/event-stream";
+
+/// Request header holding the ID of the last event a reconnecting client received
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// Events streamed by a variant of an SSE route, held in its body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SseScript {
+    /// Reconnection time sent to clients (`retry:` field), in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<u64>,
+    /// Events in sending order
+    #[serde(default)]
+    pub events: Vec<SseEvent>,
+}
+
+/// Event of an [`SseScript`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SseEvent {
+    /// Event ID (`id:` field), clients resume after it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Event type (`event:` field), `message` if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// Event data, serialized as JSON unless it is a string
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+    /// Delay before sending the event, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+/// Chunk of an event stream, sent after a delay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseFrame {
+    /// Time to wait before sending the frame
+    pub delay: Duration,
+    /// Frame in the `text/event-stream` format
+    pub text: String,
+}
+
+/// Variant body that isn't a valid [`SseScript`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid event script in variant '{variant_id}': {message}")]
+pub struct SseScriptError {
+    pub variant_id: String,
+    pub message: String,
+}
+
+impl SseScript {
+    /// Read the script of a rendered variant, empty if the variant has no body.
+    pub fn from_variant(variant: &Variant) -> Result<Self, SseScriptError> {
+        let Some(body) = &variant.body else {
+            return Ok(Self::default());
+        };
+        Self::deserialize(body).map_err(|e| SseScriptError {
+            variant_id: variant.id.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Skip the events a reconnecting client already received.
+    ///
+    /// Events up to the one with `last_event_id` are dropped. All events are
+    /// kept if the ID is `None` or no event has it.
+    pub fn resume(mut self, last_event_id: Option<&str>) -> Self {
+        let last = last_event_id.and_then(|id| {
+            self.events
+                .iter()
+                .position(|event| event.id.as_deref() == Some(id))
+        });
+        if let Some(last) = last {
+            self.events.drain(..=last);
+        }
+        self
+    }
+
+    /// Get the frames to send: the `retry:` field if set, then each event after
+    /// its delay.
+    pub fn frames(&self) -> Vec<SseFrame> {
+        let retry = self.retry.map(|retry| SseFrame {
+            delay: Duration::ZERO,
+            text: format!("retry: {}\n\n", retry),
+        });
+        retry
+            .into_iter()
+            .chain(self.events.iter().map(|event| SseFrame {
+                delay: Duration::from_millis(event.delay.unwrap_or(0)),
+                text: event.to_string(),
+            }))
+            .collect()
+    }
+}
+
+/// Writes the whole stream, without delays.
+impl fmt::Display for SseScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in self.frames() {
+            f.write_str(&frame.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the event in the `text/event-stream` format, ending with a blank line.
+///
+/// Multiline data is sent as one `data:` field per line.
+impl fmt::Display for SseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {}", id)?;
+        }
+        if let Some(event) = &self.event {
+            writeln!(f, "event: {}", event)?;
+        }
+        let data = match &self.data {
+            Value::Null => None,
+            Value::String(data) => Some(data.clone()),
+            data => Some(data.to_string()),
+        };
+        for line in data.iter().flat_map(|data| data.lines()) {
+            writeln!(f, "data: {}", line)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Check if request headers accept an event stream, making it an SSE request.
+pub fn accepts_event_stream(headers: Option<&HashMap<String, String>>) -> bool {
+    headers.into_iter().flatten().any(|(name, value)| {
+        name.eq_ignore_ascii_case("accept")
+            && value
+                .split(',')
+                .any(|media_type| media_type.trim().starts_with(SSE_CONTENT_TYPE))
+    })
+}
+
+/// Get the ID of the last event a reconnecting client received.
+pub fn last_event_id(request: &Request) -> Option<&str> {
+    request
+        .headers
+        .iter()
+        .flatten()
+        .find(|(name, _)| name.eq_ignore_ascii_case(LAST_EVENT_ID_HEADER))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::route::Transport;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn variant(body: Value) -> Variant {
+        Variant {
+            id: "ticks".into(),
+            status: None,
+            headers: None,
+            body: Some(body),
+            template: None,
+        }
+    }
+
+    fn script() -> SseScript {
+        SseScript::from_variant(&variant(json!({
+            "retry": 3000,
+            "events": [
+                {"id": "1", "event": "tick", "data": {"n": 1}},
+                {"id": "2", "delay": 500, "data": "line 1\nline 2"},
+                {"data": 3}
+            ]
+        })))
+        .unwrap()
+    }
+
+    #[rstest]
+    fn test_frames() {
+        let frames = script().frames();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[2].delay, Duration::from_millis(500));
+        assert_eq!(
+            script().to_string(),
+            "retry: 3000\n\n\
+             id: 1\nevent: tick\ndata: {\"n\":1}\n\n\
+             id: 2\ndata: line 1\ndata: line 2\n\n\
+             data: 3\n\n"
+        );
+    }
+
+    #[rstest]
+    #[case(None, 3)]
+    #[case(Some("1"), 2)]
+    #[case(Some("2"), 1)]
+    #[case(Some("unknown"), 3)]
+    fn test_resume(#[case] last_event_id: Option<&str>, #[case] remaining: usize) {
+        assert_eq!(script().resume(last_event_id).events.len(), remaining);
+    }
+
+    #[rstest]
+    fn test_invalid_script() {
+        let error =
+            SseScript::from_variant(&variant(json!({"events": [{"name": "x"}]}))).unwrap_err();
+        assert_eq!(error.variant_id, "ticks");
+        assert!(error
+            .to_string()
+            .starts_with("Invalid event script in variant 'ticks'"));
+    }
+
+    #[rstest]
+    fn test_request_headers() {
+        let request = Request {
+            url: "/events".to_string(),
+            method: None,
+            transport: Transport::Sse,
+            headers: Some(HashMap::from([
+                ("Accept".to_string(), "text/event-stream".to_string()),
+                ("Last-Event-ID".to_string(), "7".to_string()),
+            ])),
+            query: None,
+            payload: None,
+        };
+
+        assert!(accepts_event_stream(request.headers.as_ref()));
+        assert!(!accepts_event_stream(None));
+        assert_eq!(last_event_id(&request), Some("7"));
+    }
+}
@@ -0,0 +1,1542 @@
+//! Immutable table of active routes used for request matching.
+//!
+//! `RouteTable` is never mutated after creation. The controller builds a new table on
+//! every state change and hands it out behind an `Arc`, so lookups can run concurrently
+//! without holding any lock on the controller.
+//!
+//! Routes are indexed by transport and method, then by the static prefix of their URL
+//! pattern, so a lookup only runs full matching for a small set of candidate routes.
+//! Tables built [`with_match_cache`](RouteTable::with_match_cache) also remember the
+//! route matched by repeated identical requests.
+//!
+//! Every lookup runs in a `match_request` tracing span holding the request and the
+//! matched route, preset and variant, and rendering runs in a `render_variant` span.
+
+use crate::expression::{EvaluationLimits, ExpressionError};
+use crate::matching::{
+    hashmap_intersects, headers_intersects, headers_matches, message_bytes, message_matches,
+    object_intersects, operation_matches, payload_matches, query_matches, UrlPattern,
+};
+use crate::mocks::context::RequestContext;
+use crate::mocks::controller::Request;
+use crate::mocks::manager::ActiveRoute;
+use crate::mocks::match_cache::MatchCache;
+use crate::template::{render_variant, Engine, TemplateError};
+use crate::types::preset::Preset;
+use crate::types::route::{HttpMethod, Route, Transport};
+use crate::types::variant::Variant;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Request field matched by a preset expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchField {
+    Headers,
+    Query,
+    Payload,
+}
+
+impl fmt::Display for MatchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Headers => "headers",
+            Self::Query => "query",
+            Self::Payload => "payload",
+        })
+    }
+}
+
+/// Expression error raised while matching a request against a route preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionDiagnostic {
+    /// ID of the route
+    pub route_id: String,
+    /// ID of the active preset
+    pub preset_id: String,
+    /// Field whose expression failed
+    pub field: MatchField,
+    /// Expression error
+    pub error: ExpressionError,
+}
+
+impl fmt::Display for ExpressionDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} {}: {}",
+            self.route_id, self.preset_id, self.field, self.error
+        )
+    }
+}
+
+/// Condition of a route checked against a request, in matching order.
+///
+/// The later the first failed condition, the closer a route is to matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchStep {
+    Enabled,
+    Transport,
+    Method,
+    Url,
+    /// GraphQL operation, see [`Route::graphql`]
+    Operation,
+    /// TCP message, see [`Route::tcp`]
+    Message,
+    Params,
+    Headers,
+    Query,
+    Payload,
+}
+
+impl fmt::Display for MatchStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Enabled => "enabled",
+            Self::Transport => "transport",
+            Self::Method => "method",
+            Self::Url => "url",
+            Self::Operation => "operation",
+            Self::Message => "message",
+            Self::Params => "params",
+            Self::Headers => "headers",
+            Self::Query => "query",
+            Self::Payload => "payload",
+        })
+    }
+}
+
+/// First condition of a route a request failed, see [`RouteTable::explain`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchFailure {
+    /// Failed condition
+    pub step: MatchStep,
+    /// Value the route expects, as defined in the route or preset
+    pub expected: Value,
+    /// Value of the request
+    pub actual: Value,
+    /// Error of the preset expression, if the condition failed with one
+    pub error: Option<ExpressionError>,
+}
+
+impl MatchFailure {
+    /// Get the first path parameter, header, query parameter or payload field the
+    /// request doesn't satisfy.
+    ///
+    /// Nested payload fields are joined by dots, e.g. `user.name`. Returns `None`
+    /// for other conditions and for expressions.
+    pub fn field(&self) -> Option<String> {
+        let expected = self.expected.as_object()?;
+        let actual = string_map(&self.actual);
+        let single = |key: &String, value: &Value| {
+            let value = value.as_str().unwrap_or_default().to_string();
+            HashMap::from([(key.clone(), value)])
+        };
+        let field = match self.step {
+            MatchStep::Params => expected
+                .iter()
+                .find(|(key, value)| self.actual.get(key.as_str()) != Some(*value)),
+            MatchStep::Headers => expected
+                .iter()
+                .find(|(key, value)| !headers_intersects(Some(&actual), Some(&single(key, value)))),
+            MatchStep::Query => expected
+                .iter()
+                .find(|(key, value)| !hashmap_intersects(Some(&single(key, value)), Some(&actual))),
+            MatchStep::Payload => return payload_field(expected, &self.actual, ""),
+            _ => None,
+        };
+        field.map(|(key, _)| key.clone())
+    }
+}
+
+/// Outcome of matching a request against one active route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteExplanation {
+    /// ID of the route
+    pub route_id: String,
+    /// ID of the active preset
+    pub preset_id: String,
+    /// Why the route doesn't match, `None` if it does
+    pub failure: Option<MatchFailure>,
+}
+
+/// Index bucket key: transport and method (`None` for routes matching any method).
+type BucketKey = (Transport, Option<HttpMethod>);
+
+/// Immutable snapshot of active routes with request matching.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteTable {
+    /// Active routes in priority order
+    routes: Vec<ActiveRoute>,
+    /// Compiled URL patterns of the routes, by route index
+    patterns: Vec<UrlPattern>,
+    /// Route indices grouped by transport and method
+    index: HashMap<BucketKey, PathTrie>,
+    /// IDs of routes skipped during matching
+    disabled: HashSet<String>,
+    /// Limits of expression evaluation and template rendering
+    limits: EvaluationLimits,
+    /// Template fragments included from variants with `{{> name}}`
+    partials: HashMap<String, String>,
+    /// Matched routes of recent requests
+    match_cache: Option<MatchCache>,
+}
+
+impl RouteTable {
+    /// Create a table from active routes in priority order.
+    pub fn new(routes: Vec<ActiveRoute>) -> Self {
+        Self::with_disabled(routes, HashSet::new())
+    }
+
+    /// Create a table where routes with the given IDs are skipped during matching.
+    ///
+    /// Disabled routes are still listed in `routes()`. Preset expressions of the
+    /// enabled routes that don't compile are logged as warnings.
+    pub fn with_disabled(routes: Vec<ActiveRoute>, disabled: HashSet<String>) -> Self {
+        let warn = tracing::enabled!(tracing::Level::WARN);
+        let mut index: HashMap<BucketKey, PathTrie> = HashMap::new();
+        for (i, active_route) in routes.iter().enumerate() {
+            if disabled.contains(&*active_route.route.id) {
+                continue;
+            }
+            if warn {
+                warn_invalid_expressions(active_route);
+            }
+            index
+                .entry(bucket_key(&active_route.route))
+                .or_default()
+                .insert(&active_route.route.url, i);
+        }
+
+        let patterns = routes
+            .iter()
+            .map(|active_route| UrlPattern::new(&active_route.route.url))
+            .collect();
+
+        Self {
+            routes,
+            patterns,
+            index,
+            disabled,
+            limits: EvaluationLimits::default(),
+            partials: HashMap::new(),
+            match_cache: None,
+        }
+    }
+
+    /// Set limits of expression evaluation and template rendering.
+    pub fn with_limits(mut self, limits: EvaluationLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set template fragments included from variants, see [`crate::template::partials`].
+    pub fn with_partials(mut self, partials: HashMap<String, String>) -> Self {
+        self.partials = partials;
+        self
+    }
+
+    /// Cache the route matched by up to `capacity` distinct requests.
+    ///
+    /// Requests are told apart by transport, method, URL, and the headers and
+    /// query parameters presets match on. Tables with routes matching by
+    /// expression aren't cached, neither are requests with a payload if a route
+    /// matches payloads. A capacity of 0 disables the cache.
+    pub fn with_match_cache(mut self, capacity: usize) -> Self {
+        self.match_cache = MatchCache::new(&self.routes, &self.disabled, capacity);
+        self
+    }
+
+    /// Get the number of requests in the match cache.
+    pub fn cached_matches(&self) -> usize {
+        self.match_cache.as_ref().map_or(0, MatchCache::len)
+    }
+
+    /// Get all routes in priority order.
+    pub fn routes(&self) -> &[ActiveRoute] {
+        &self.routes
+    }
+
+    /// Check if a route is skipped during matching.
+    pub fn is_disabled(&self, route_id: &str) -> bool {
+        self.disabled.contains(route_id)
+    }
+
+    /// Find a route that matches the given request.
+    ///
+    /// Returns the first matching enabled route in priority order.
+    /// Matching is performed in order: transport, method, URL, params, headers, query, payload.
+    ///
+    /// Returns `None` if no matching route is found.
+    pub fn find_route(&self, request: &Request) -> Option<&ActiveRoute> {
+        let mut context = RequestContext::new(request);
+        self.find_index(request, &mut context)
+            .map(|i| &self.routes[i])
+    }
+
+    /// Find the route matching the given request, with the request context.
+    ///
+    /// Like [`find_route`](Self::find_route), but also returns the context the
+    /// route was matched with, holding its path params. Bindings use it to answer
+    /// a route their own way and [`render`](Self::render) the variant otherwise.
+    pub fn find_route_with_context(
+        &self,
+        request: &Request,
+    ) -> Option<(&ActiveRoute, RequestContext)> {
+        let mut context = RequestContext::new(request);
+        let found = self
+            .find_index(request, &mut context)
+            .map(|i| &self.routes[i]);
+        // Matching stops at the found route, so the context holds its path params
+        found.map(|active_route| (active_route, context))
+    }
+
+    /// Render the variant of a route against a request context.
+    ///
+    /// Variants without their own template engine use `default`.
+    pub fn render(
+        &self,
+        active_route: &ActiveRoute,
+        context: &RequestContext,
+        default: Engine,
+    ) -> Result<Variant, TemplateError> {
+        let _span = tracing::debug_span!(
+            "render_variant",
+            route_id = &*active_route.route.id,
+            preset_id = &*active_route.preset().id,
+            variant_id = &*active_route.variant().id,
+        )
+        .entered();
+        render_variant(
+            active_route.variant(),
+            context.value(),
+            default,
+            &self.partials,
+            &self.limits,
+        )
+        .inspect_err(|error| tracing::warn!(%error, "variant failed to render"))
+    }
+
+    /// Find the route matching the given request and render its variant.
+    ///
+    /// Templates in the variant headers and body are rendered against the
+    /// request context, see [`crate::template`]. Variants without their own
+    /// template engine use `default`.
+    ///
+    /// Returns `None` if no matching route is found, or error if a template
+    /// fails to render.
+    pub fn respond(
+        &self,
+        request: &Request,
+        default: Engine,
+    ) -> Result<Option<Variant>, TemplateError> {
+        self.find_route_with_context(request)
+            .map(|(active_route, context)| self.render(active_route, &context, default))
+            .transpose()
+    }
+
+    /// Find all routes that match the given request.
+    ///
+    /// Returns every matching enabled route in priority order, so the first one
+    /// is the route returned by `find_route`.
+    pub fn find_routes(&self, request: &Request) -> Vec<&ActiveRoute> {
+        let mut context = RequestContext::new(request);
+        self.candidates(request)
+            .into_iter()
+            .filter(|&i| self.matches(i, request, &mut context).unwrap_or(false))
+            .map(|i| &self.routes[i])
+            .collect()
+    }
+
+    /// Collect expression errors raised while matching the given request.
+    ///
+    /// Routes whose preset expression is invalid or fails to evaluate are
+    /// treated as not matching by `find_route`, this reports why.
+    pub fn diagnose(&self, request: &Request) -> Vec<ExpressionDiagnostic> {
+        let mut context = RequestContext::new(request);
+        self.candidates(request)
+            .into_iter()
+            .filter_map(|i| self.matches(i, request, &mut context).err())
+            .collect()
+    }
+
+    /// Explain how every active route matches the given request.
+    ///
+    /// Returns one explanation per route in priority order, including disabled
+    /// routes and routes the index would skip. Routes failing at a later
+    /// [`MatchStep`] are closer matches.
+    pub fn explain(&self, request: &Request) -> Vec<RouteExplanation> {
+        let mut context = RequestContext::new(request);
+        self.routes
+            .iter()
+            .zip(&self.patterns)
+            .map(|(active_route, pattern)| {
+                let step = if self.is_disabled(&active_route.route.id) {
+                    Err(Mismatch {
+                        step: MatchStep::Enabled,
+                        diagnostic: None,
+                    })
+                } else {
+                    check_route(active_route, pattern, request, &mut context, &self.limits)
+                };
+                RouteExplanation {
+                    route_id: active_route.route.id.to_string(),
+                    preset_id: active_route.preset().id.to_string(),
+                    failure: step.err().map(|mismatch| MatchFailure {
+                        expected: expected_value(active_route, mismatch.step),
+                        actual: actual_value(request, &context, mismatch.step),
+                        step: mismatch.step,
+                        error: mismatch.diagnostic.map(|d| d.error),
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Find the routes closest to matching a request.
+    ///
+    /// Only enabled routes of the request transport that don't match are
+    /// considered. Routes whose URL matches the request come first, then routes
+    /// failing at a later [`MatchStep`], in priority order otherwise. Returns up
+    /// to `limit` explanations.
+    pub fn nearest(&self, request: &Request, limit: usize) -> Vec<RouteExplanation> {
+        let mut candidates: Vec<_> = self
+            .explain(request)
+            .into_iter()
+            .zip(&self.patterns)
+            .filter_map(|(explanation, pattern)| {
+                let step = explanation.failure.as_ref()?.step;
+                let url_matches = pattern.matches(&request.url).matched;
+                (step > MatchStep::Transport).then_some(((url_matches, step), explanation))
+            })
+            .collect();
+        candidates.sort_by(|(a, _), (b, _)| b.cmp(a));
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(_, explanation)| explanation)
+            .collect()
+    }
+
+    /// Find the index of the first route matching the request, in a `match_request`
+    /// span recording the matched route.
+    fn find_index(&self, request: &Request, context: &mut RequestContext) -> Option<usize> {
+        let span = tracing::debug_span!(
+            "match_request",
+            transport = ?request.transport,
+            method = request.method.as_ref().map(HttpMethod::as_str),
+            url = %request.url,
+            route_id = tracing::field::Empty,
+            preset_id = tracing::field::Empty,
+            variant_id = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let found = self.lookup_index(request, context);
+        match found {
+            Some(i) => {
+                let active_route = &self.routes[i];
+                span.record("route_id", &*active_route.route.id);
+                span.record("preset_id", &*active_route.preset().id);
+                span.record("variant_id", &*active_route.variant().id);
+                tracing::debug!("request matched");
+            }
+            None => tracing::debug!("no route matched"),
+        }
+        found
+    }
+
+    /// Find the index of the first route matching the request.
+    ///
+    /// The matched route is checked against `context` even on cache hits, so the
+    /// context holds its path params.
+    fn lookup_index(&self, request: &Request, context: &mut RequestContext) -> Option<usize> {
+        let key = self
+            .match_cache
+            .as_ref()
+            .and_then(|cache| Some((cache, cache.key(request)?)));
+        if let Some((cache, key)) = &key {
+            match cache.get(key) {
+                Some(None) => return None,
+                Some(Some(i)) if self.matches(i, request, context).unwrap_or(false) => {
+                    tracing::trace!("match cache hit");
+                    return Some(i);
+                }
+                _ => {}
+            }
+        }
+
+        let found = self
+            .candidates(request)
+            .into_iter()
+            .find(|&i| self.matches(i, request, context).unwrap_or(false));
+        if let Some((cache, key)) = key {
+            cache.insert(key, found);
+        }
+        found
+    }
+
+    /// Check if the route at index `i` matches the request, see [`route_matches_request`].
+    fn matches(
+        &self,
+        i: usize,
+        request: &Request,
+        context: &mut RequestContext,
+    ) -> Result<bool, ExpressionDiagnostic> {
+        route_matches_request(
+            &self.routes[i],
+            &self.patterns[i],
+            request,
+            context,
+            &self.limits,
+        )
+        .inspect_err(|diagnostic| tracing::debug!(%diagnostic, "preset expression failed"))
+    }
+
+    /// Get indices of routes that may match the request, in priority order.
+    fn candidates(&self, request: &Request) -> Vec<usize> {
+        let mut candidates = Vec::new();
+        for key in request_bucket_keys(request) {
+            if let Some(trie) = self.index.get(&key) {
+                trie.collect(&request.url, &mut candidates);
+            }
+        }
+
+        // Candidates come from several buckets and trie nodes, restore priority order
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// Trie over static URL path segments.
+///
+/// Each route is stored at the node reached by the static segments of its pattern
+/// before the first parameter segment. A lookup collects routes from every node on
+/// the request path, which is a superset of the routes whose pattern can match.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PathTrie {
+    /// Indices of routes whose static prefix ends at this node
+    routes: Vec<usize>,
+    /// Child nodes by path segment
+    children: HashMap<String, PathTrie>,
+}
+
+impl PathTrie {
+    /// Insert a route index under the static prefix of a URL pattern.
+    fn insert(&mut self, pattern: &str, index: usize) {
+        let mut node = self;
+        for segment in path_segments(pattern) {
+            if segment.contains('{') {
+                break;
+            }
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        node.routes.push(index);
+    }
+
+    /// Collect indices of all routes whose static prefix matches the request URL.
+    fn collect(&self, url: &str, out: &mut Vec<usize>) {
+        let mut node = self;
+        out.extend_from_slice(&node.routes);
+        for segment in path_segments(url) {
+            match node.children.get(segment) {
+                Some(child) => {
+                    node = child;
+                    out.extend_from_slice(&node.routes);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Split a URL or pattern path into non-empty segments, ignoring the query string.
+fn path_segments(url: &str) -> impl Iterator<Item = &str> {
+    url.split('?')
+        .next()
+        .unwrap_or("")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+}
+
+/// Get the index bucket of a route.
+///
+/// Method is ignored for WebSocket, SSE and TCP routes.
+fn bucket_key(route: &Route) -> BucketKey {
+    match route.transport {
+        Transport::Http => (Transport::Http, route.method.clone()),
+        Transport::WebSocket => (Transport::WebSocket, None),
+        Transport::Sse => (Transport::Sse, None),
+        Transport::Tcp => (Transport::Tcp, None),
+    }
+}
+
+/// Get the index buckets that may contain routes matching the request.
+fn request_bucket_keys(request: &Request) -> Vec<BucketKey> {
+    match (&request.transport, &request.method) {
+        (Transport::Http, Some(method)) => vec![
+            (Transport::Http, Some(method.clone())),
+            (Transport::Http, None),
+        ],
+        (Transport::Http, None) => vec![(Transport::Http, None)],
+        (Transport::WebSocket, _) => vec![(Transport::WebSocket, None)],
+        (Transport::Sse, _) => vec![(Transport::Sse, None)],
+        (Transport::Tcp, _) => vec![(Transport::Tcp, None)],
+    }
+}
+
+/// Check if an active route matches the given request.
+///
+/// Returns error if a preset expression is invalid or can't be evaluated
+/// against the request.
+fn route_matches_request(
+    active_route: &ActiveRoute,
+    pattern: &UrlPattern,
+    request: &Request,
+    context: &mut RequestContext,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionDiagnostic> {
+    match check_route(active_route, pattern, request, context, limits) {
+        Ok(()) => Ok(true),
+        Err(Mismatch {
+            diagnostic: Some(diagnostic),
+            ..
+        }) => Err(diagnostic),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Condition of a route a request failed.
+struct Mismatch {
+    step: MatchStep,
+    /// Expression error the condition failed with
+    diagnostic: Option<ExpressionDiagnostic>,
+}
+
+/// Check the conditions of an active route against a request, in matching order.
+///
+/// Matches transport, method, URL, GraphQL operation, TCP message, headers,
+/// query, and payload.
+/// Expressions for headers, query and payload are evaluated against the shared
+/// request `context`, whose path parameters are set from the route URL pattern.
+///
+/// The URL is matched with `pattern`, compiled from the route URL.
+///
+/// Returns the first failed condition.
+fn check_route(
+    active_route: &ActiveRoute,
+    pattern: &UrlPattern,
+    request: &Request,
+    context: &mut RequestContext,
+    limits: &EvaluationLimits,
+) -> Result<(), Mismatch> {
+    let route = &active_route.route;
+    let preset = active_route.preset();
+    let fail = |step| {
+        Err(Mismatch {
+            step,
+            diagnostic: None,
+        })
+    };
+
+    // Check transport
+    if route.transport != request.transport {
+        return fail(MatchStep::Transport);
+    }
+
+    // Check HTTP method (for HTTP routes)
+    if route.transport == Transport::Http {
+        if let Some(route_method) = &route.method {
+            // Route requires method but request doesn't have it
+            if request.method.as_ref() != Some(route_method) {
+                return fail(MatchStep::Method);
+            }
+        }
+    }
+
+    // Check URL pattern
+    let url_result = pattern.matches(&request.url);
+    if !url_result.matched {
+        return fail(MatchStep::Url);
+    }
+    context.set_params(&url_result.params);
+
+    // Check GraphQL operation
+    if let Some(operation) = &route.graphql {
+        if !operation_matches(operation, context.graphql()) {
+            return fail(MatchStep::Operation);
+        }
+    }
+
+    // Check TCP message
+    if let Some(tcp) = &route.tcp {
+        let message = message_bytes(request.payload.as_ref());
+        if !message_matches(tcp, message.as_deref()) {
+            return fail(MatchStep::Message);
+        }
+    }
+
+    // Check URL path parameters (from preset.params)
+    if let Some(expected_params) = &preset.params {
+        // Check if all expected params are present in matched params
+        let params_match = expected_params
+            .iter()
+            .all(|(key, expected_value)| url_result.params.get(key) == Some(expected_value));
+        if !params_match {
+            return fail(MatchStep::Params);
+        }
+    }
+
+    let context = &*context;
+    let check = |step, field, result: Result<bool, ExpressionError>| match result {
+        Ok(true) => Ok(()),
+        Ok(false) => fail(step),
+        Err(error) => Err(Mismatch {
+            step,
+            diagnostic: Some(ExpressionDiagnostic {
+                route_id: route.id.to_string(),
+                preset_id: preset.id.to_string(),
+                field,
+                error,
+            }),
+        }),
+    };
+
+    // Check headers
+    let empty_headers = HashMap::new();
+    let request_headers = request.headers.as_ref().unwrap_or(&empty_headers);
+    check(
+        MatchStep::Headers,
+        MatchField::Headers,
+        headers_matches(
+            preset.headers.as_ref(),
+            request_headers,
+            context.value(),
+            limits,
+        ),
+    )?;
+
+    // Check query parameters
+    check(
+        MatchStep::Query,
+        MatchField::Query,
+        query_matches(
+            preset.query.as_ref(),
+            context.query(),
+            context.value(),
+            limits,
+        ),
+    )?;
+
+    // Check payload/body
+    check(
+        MatchStep::Payload,
+        MatchField::Payload,
+        check_payload(preset, &request.payload, context, limits),
+    )
+}
+
+/// Get what a route expects at a matching step.
+fn expected_value(active_route: &ActiveRoute, step: MatchStep) -> Value {
+    let route = &active_route.route;
+    let preset = active_route.preset();
+    let value = match step {
+        MatchStep::Enabled => Ok(Value::Bool(true)),
+        MatchStep::Transport => serde_json::to_value(&route.transport),
+        MatchStep::Method => serde_json::to_value(&route.method),
+        MatchStep::Url => serde_json::to_value(&route.url),
+        MatchStep::Operation => serde_json::to_value(&route.graphql),
+        MatchStep::Message => serde_json::to_value(&route.tcp),
+        MatchStep::Params => serde_json::to_value(&preset.params),
+        MatchStep::Headers => serde_json::to_value(&preset.headers),
+        MatchStep::Query => serde_json::to_value(&preset.query),
+        MatchStep::Payload => serde_json::to_value(&preset.payload),
+    };
+    value.unwrap_or_default()
+}
+
+/// Get what a request has at a matching step.
+///
+/// The context holds the path params of the route last checked.
+fn actual_value(request: &Request, context: &RequestContext, step: MatchStep) -> Value {
+    let value = match step {
+        MatchStep::Enabled => Ok(Value::Bool(false)),
+        MatchStep::Transport => serde_json::to_value(&request.transport),
+        MatchStep::Method => serde_json::to_value(&request.method),
+        MatchStep::Url => serde_json::to_value(&request.url),
+        MatchStep::Operation => serde_json::to_value(context.graphql()),
+        MatchStep::Message => serde_json::to_value(&request.payload),
+        MatchStep::Params => Ok(context.value()["params"].clone()),
+        MatchStep::Headers => Ok(context.value()["headers"].clone()),
+        MatchStep::Query => serde_json::to_value(context.query()),
+        MatchStep::Payload => serde_json::to_value(&request.payload),
+    };
+    value.unwrap_or_default()
+}
+
+/// Log preset expressions of an active route that don't compile.
+///
+/// Such presets never match, which is otherwise only visible through
+/// [`RouteTable::diagnose`].
+fn warn_invalid_expressions(active_route: &ActiveRoute) {
+    for (field, expression) in active_route.preset().expressions() {
+        if let Err(error) = crate::expression::compile(expression) {
+            tracing::warn!(
+                route_id = &*active_route.route.id,
+                preset_id = &*active_route.preset().id,
+                field,
+                %error,
+                "preset expression doesn't compile"
+            );
+        }
+    }
+}
+
+/// Collect the string values of a JSON object, ignoring other values.
+fn string_map(value: &Value) -> HashMap<String, String> {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+        .collect()
+}
+
+/// Get the path of the first field of an expected payload object that the
+/// actual payload doesn't contain, see [`MatchFailure::field`].
+fn payload_field(expected: &Map<String, Value>, actual: &Value, prefix: &str) -> Option<String> {
+    expected.iter().find_map(|(key, value)| {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let actual = actual.get(key);
+        match (value, actual) {
+            _ if object_intersects(actual, Some(value)) => None,
+            (Value::Object(expected), Some(actual @ Value::Object(_))) => {
+                payload_field(expected, actual, &path).or(Some(path))
+            }
+            _ => Some(path),
+        }
+    })
+}
+
+/// Check request payload/body.
+///
+/// Returns `false` if preset expects payload but request doesn't have it.
+fn check_payload(
+    preset: &Preset,
+    request_payload: &Option<Value>,
+    context: &RequestContext,
+    limits: &EvaluationLimits,
+) -> Result<bool, ExpressionError> {
+    if let Some(request_payload) = request_payload {
+        payload_matches(
+            preset.payload.as_ref(),
+            request_payload,
+            context.value(),
+            limits,
+        )
+    } else if preset.payload.is_some() {
+        // Preset expects payload but request doesn't have it
+        Ok(false)
+    } else {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::tcp::tcp_request;
+    use crate::types::graphql::{GraphqlOperation, OperationType};
+    use crate::types::preset::{HeadersOrExpression, PayloadOrExpression, QueryOrExpression};
+    use crate::types::tcp::TcpMatch;
+    use crate::types::variant::Variant;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn create_active_route(id: &str, url: &str, variant_id: &str) -> ActiveRoute {
+        ActiveRoute::new(
+            Route {
+                id: id.into(),
+                url: url.to_string(),
+                transport: Transport::Http,
+                method: Some(HttpMethod::Get),
+                presets: vec![],
+                tags: vec![],
+                group: None,
+                proto: None,
+                graphql: None,
+                tcp: None,
+            },
+            Preset {
+                id: "default".into(),
+                params: None,
+                query: None,
+                headers: None,
+                payload: None,
+                variants: vec![],
+            },
+            Variant {
+                id: variant_id.into(),
+                status: Some(200),
+                headers: None,
+                body: None,
+                template: None,
+            },
+        )
+    }
+
+    fn create_request(url: &str) -> Request {
+        Request {
+            url: url.to_string(),
+            method: Some(HttpMethod::Get),
+            transport: Transport::Http,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    #[rstest]
+    fn test_empty_table() {
+        let table = RouteTable::default();
+        assert!(table.routes().is_empty());
+        assert!(table.find_route(&create_request("/api/users")).is_none());
+    }
+
+    #[rstest]
+    fn test_find_route_priority_order() {
+        let table = RouteTable::new(vec![
+            create_active_route("specific", "/api/users/me", "me"),
+            create_active_route("generic", "/api/users/{id}", "by-id"),
+        ]);
+
+        let found = table.find_route(&create_request("/api/users/me")).unwrap();
+        assert_eq!(found.route.id, "specific".into());
+
+        let found = table.find_route(&create_request("/api/users/42")).unwrap();
+        assert_eq!(found.route.id, "generic".into());
+    }
+
+    #[rstest]
+    fn test_concurrent_lookup() {
+        let table = Arc::new(RouteTable::new(vec![create_active_route(
+            "users",
+            "/api/users",
+            "ok",
+        )]));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let table = Arc::clone(&table);
+                thread::spawn(move || {
+                    table
+                        .find_route(&create_request("/api/users"))
+                        .map(|r| r.variant().id.clone())
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().as_deref(), Some("ok"));
+        }
+    }
+
+    #[rstest]
+    fn test_find_route_priority_across_buckets() {
+        let mut any_method = create_active_route("any", "/api/{resource}", "any");
+        any_method.route_mut().method = None;
+        let table = RouteTable::new(vec![
+            any_method,
+            create_active_route("users", "/api/users", "users"),
+        ]);
+
+        let found = table.find_route(&create_request("/api/users")).unwrap();
+        assert_eq!(found.route.id, "any".into());
+
+        let mut request = create_request("/api/users");
+        request.method = None;
+        let found = table.find_route(&request).unwrap();
+        assert_eq!(found.route.id, "any".into());
+    }
+
+    #[rstest]
+    #[case("/api/users/42", Some("by-id"))]
+    #[case("/api/users/42/", Some("by-id"))]
+    #[case("/api/users/42?page=1", Some("by-id"))]
+    #[case("/api/posts/1", Some("post"))]
+    #[case("/", Some("root"))]
+    #[case("/api/unknown", None)]
+    #[case("/other", None)]
+    fn test_find_route_indexed(#[case] url: &str, #[case] expected: Option<&str>) {
+        let mut routes: Vec<ActiveRoute> = (0..100)
+            .map(|i| create_active_route(&format!("r{i}"), &format!("/api/r{i}/{{id}}"), "ok"))
+            .collect();
+        routes.push(create_active_route("by-id", "/api/users/{id}", "by-id"));
+        routes.push(create_active_route("post", "/api/posts/{id}", "post"));
+        routes.push(create_active_route("root", "/", "root"));
+        let table = RouteTable::new(routes);
+
+        let found = table.find_route(&create_request(url));
+        assert_eq!(found.map(|r| r.variant().id.as_ref()), expected);
+    }
+
+    #[rstest]
+    #[case(HttpMethod::Get, "/api/s7/42", Some("get-7"), 2)]
+    #[case(HttpMethod::Post, "/api/s7/42", Some("post-7"), 2)]
+    #[case(HttpMethod::Delete, "/api/s7/42", Some("any"), 1)]
+    #[case(HttpMethod::Get, "/api/s249/42", Some("get-249"), 2)]
+    #[case(HttpMethod::Get, "/api/missing/42", Some("any"), 1)]
+    #[case(HttpMethod::Get, "/other", None, 0)]
+    fn test_find_route_candidates(
+        #[case] method: HttpMethod,
+        #[case] url: &str,
+        #[case] expected: Option<&str>,
+        #[case] candidates: usize,
+    ) {
+        let mut routes = Vec::new();
+        for i in 0..250 {
+            for (method, name) in [(HttpMethod::Get, "get"), (HttpMethod::Post, "post")] {
+                let mut route = create_active_route(
+                    &format!("{name}-{i}"),
+                    &format!("/api/s{i}/{{id}}"),
+                    &format!("{name}-{i}"),
+                );
+                route.route_mut().method = Some(method);
+                routes.push(route);
+            }
+        }
+        let mut any = create_active_route("any", "/api/{resource}/{id}", "any");
+        any.route_mut().method = None;
+        routes.push(any);
+        let table = RouteTable::new(routes);
+
+        let mut request = create_request(url);
+        request.method = Some(method);
+        // Only routes in the method buckets under the request path are evaluated
+        assert_eq!(table.candidates(&request).len(), candidates);
+        let found = table.find_route(&request);
+        assert_eq!(found.map(|r| r.variant().id.as_ref()), expected);
+    }
+
+    #[rstest]
+    fn test_find_route_method_bucket() {
+        let mut post = create_active_route("create", "/api/users", "created");
+        post.route_mut().method = Some(HttpMethod::Post);
+        let table = RouteTable::new(vec![
+            post,
+            create_active_route("list", "/api/users", "list"),
+        ]);
+
+        let mut request = create_request("/api/users");
+        request.method = Some(HttpMethod::Post);
+        assert_eq!(
+            table.find_route(&request).unwrap().route.id,
+            "create".into()
+        );
+
+        request.method = Some(HttpMethod::Get);
+        assert_eq!(table.find_route(&request).unwrap().route.id, "list".into());
+
+        request.method = Some(HttpMethod::Delete);
+        assert!(table.find_route(&request).is_none());
+    }
+
+    #[rstest]
+    fn test_find_route_websocket_ignores_method() {
+        let mut ws = create_active_route("ws", "/ws/events", "ws");
+        ws.route_mut().transport = Transport::WebSocket;
+        let table = RouteTable::new(vec![ws]);
+
+        let mut request = create_request("/ws/events");
+        request.transport = Transport::WebSocket;
+        assert_eq!(table.find_route(&request).unwrap().route.id, "ws".into());
+
+        request.method = None;
+        assert_eq!(table.find_route(&request).unwrap().route.id, "ws".into());
+    }
+
+    #[rstest]
+    fn test_find_route_sse() {
+        let mut sse = create_active_route("sse", "/events", "sse");
+        sse.route_mut().transport = Transport::Sse;
+        let table = RouteTable::new(vec![create_active_route("http", "/events", "http"), sse]);
+
+        let mut request = create_request("/events");
+        assert_eq!(table.find_route(&request).unwrap().route.id, "http".into());
+
+        request.transport = Transport::Sse;
+        assert_eq!(table.find_route(&request).unwrap().route.id, "sse".into());
+        request.method = None;
+        assert_eq!(table.find_route(&request).unwrap().route.id, "sse".into());
+    }
+
+    #[rstest]
+    fn test_find_route_tcp() {
+        let tcp_route = |id: &str, prefix: Option<&str>, pattern: Option<&str>| {
+            let mut active_route = create_active_route(id, "127.0.0.1:2525", id);
+            let route = active_route.route_mut();
+            route.transport = Transport::Tcp;
+            route.method = None;
+            route.tcp = Some(TcpMatch {
+                prefix: prefix.map(str::to_string),
+                pattern: pattern.map(str::to_string),
+            });
+            active_route
+        };
+        let table = RouteTable::new(vec![
+            tcp_route("ehlo", Some("EHLO "), None),
+            tcp_route("binary", None, Some(r"(?-u)^\x00\xff")),
+        ]);
+
+        let request = tcp_request("127.0.0.1:2525", b"EHLO mail.test\r\n");
+        assert_eq!(table.find_route(&request).unwrap().route.id, "ehlo".into());
+        let request = tcp_request("127.0.0.1:2525", b"\x00\xff\x01");
+        assert_eq!(
+            table.find_route(&request).unwrap().route.id,
+            "binary".into()
+        );
+
+        let request = tcp_request("127.0.0.1:2525", b"QUIT\r\n");
+        assert!(table.find_route(&request).is_none());
+        let explanations = table.explain(&request);
+        assert!(explanations
+            .iter()
+            .all(|e| e.failure.as_ref().unwrap().step == MatchStep::Message));
+    }
+
+    #[rstest]
+    fn test_find_route_skips_disabled() {
+        let table = RouteTable::with_disabled(
+            vec![
+                create_active_route("me", "/api/users/me", "me"),
+                create_active_route("by-id", "/api/users/{id}", "by-id"),
+            ],
+            HashSet::from(["me".to_string()]),
+        );
+
+        assert_eq!(table.routes().len(), 2);
+        assert!(table.is_disabled("me"));
+        assert!(!table.is_disabled("by-id"));
+        let found = table.find_route(&create_request("/api/users/me")).unwrap();
+        assert_eq!(found.route.id, "by-id".into());
+    }
+
+    #[rstest]
+    fn test_find_routes_priority_order() {
+        let mut any_method = create_active_route("any", "/api/{resource}/{id}", "any");
+        any_method.route_mut().method = None;
+        let table = RouteTable::with_disabled(
+            vec![
+                create_active_route("me", "/api/users/me", "me"),
+                create_active_route("disabled", "/api/users/{id}", "disabled"),
+                any_method,
+                create_active_route("by-id", "/api/users/{id}", "by-id"),
+                create_active_route("posts", "/api/posts/{id}", "posts"),
+            ],
+            HashSet::from(["disabled".to_string()]),
+        );
+
+        let found: Vec<&str> = table
+            .find_routes(&create_request("/api/users/me"))
+            .iter()
+            .map(|r| r.route.id.as_ref())
+            .collect();
+        assert_eq!(found, vec!["me", "any", "by-id"]);
+        assert!(table.find_routes(&create_request("/other")).is_empty());
+    }
+
+    #[rstest]
+    fn test_diagnose_expression_errors() {
+        let mut invalid = create_active_route("invalid", "/api/users", "invalid");
+        invalid.preset_mut().query =
+            Some(QueryOrExpression::Expression("query.page ==".to_string()));
+        let mut failing = create_active_route("failing", "/api/users", "failing");
+        failing.preset_mut().payload = Some(PayloadOrExpression::Expression(
+            "cel: payload.missing > 1".to_string(),
+        ));
+        let table = RouteTable::new(vec![
+            invalid,
+            failing,
+            create_active_route("list", "/api/users", "list"),
+        ]);
+
+        let mut request = create_request("/api/users?page=1");
+        request.payload = Some(json!({"name": "John"}));
+        assert_eq!(table.find_route(&request).unwrap().route.id, "list".into());
+
+        let diagnostics = table.diagnose(&request);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].route_id, "invalid");
+        assert_eq!(diagnostics[0].field, MatchField::Query);
+        assert!(matches!(
+            diagnostics[0].error,
+            ExpressionError::Invalid { .. }
+        ));
+        assert_eq!(diagnostics[1].route_id, "failing");
+        assert_eq!(diagnostics[1].field, MatchField::Payload);
+        assert!(matches!(
+            diagnostics[1].error,
+            ExpressionError::Evaluation { .. }
+        ));
+        assert!(diagnostics[1].to_string().starts_with("failing:"));
+    }
+
+    #[rstest]
+    fn test_explain() {
+        let mut post = create_active_route("create", "/api/users", "created");
+        post.route_mut().method = Some(HttpMethod::Post);
+        let mut admin = create_active_route("admin", "/api/users/{id}", "admin");
+        admin.preset_mut().params = Some(HashMap::from([("id".to_string(), "1".to_string())]));
+        let mut invalid = create_active_route("invalid", "/api/users/{id}", "invalid");
+        invalid.preset_mut().query =
+            Some(QueryOrExpression::Expression("query.page ==".to_string()));
+        let table = RouteTable::with_disabled(
+            vec![
+                create_active_route("disabled", "/api/users/{id}", "disabled"),
+                post,
+                create_active_route("orders", "/api/orders/{id}", "orders"),
+                admin,
+                invalid,
+                create_active_route("user", "/api/users/{id}", "user"),
+            ],
+            HashSet::from(["disabled".to_string()]),
+        );
+
+        let explanations = table.explain(&create_request("/api/users/42"));
+        let failures: Vec<_> = explanations
+            .iter()
+            .map(|e| {
+                let failure = e.failure.as_ref();
+                (
+                    e.route_id.as_str(),
+                    failure.map(|f| f.step),
+                    failure.map(|f| (f.expected.clone(), f.actual.clone())),
+                )
+            })
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                (
+                    "disabled",
+                    Some(MatchStep::Enabled),
+                    Some((json!(true), json!(false)))
+                ),
+                (
+                    "create",
+                    Some(MatchStep::Method),
+                    Some((json!("POST"), json!("GET")))
+                ),
+                (
+                    "orders",
+                    Some(MatchStep::Url),
+                    Some((json!("/api/orders/{id}"), json!("/api/users/42")))
+                ),
+                (
+                    "admin",
+                    Some(MatchStep::Params),
+                    Some((json!({"id": "1"}), json!({"id": "42"})))
+                ),
+                (
+                    "invalid",
+                    Some(MatchStep::Query),
+                    Some((json!("query.page =="), json!({})))
+                ),
+                ("user", None, None),
+            ]
+        );
+        assert!(explanations[4].failure.as_ref().unwrap().error.is_some());
+        assert!(explanations[3].failure.as_ref().unwrap().error.is_none());
+    }
+
+    #[rstest]
+    fn test_nearest() {
+        let mut post = create_active_route("create", "/api/users/{id}", "created");
+        post.route_mut().method = Some(HttpMethod::Post);
+        let mut admin = create_active_route("admin", "/api/users/{id}", "admin");
+        admin.preset_mut().params = Some(HashMap::from([("id".to_string(), "1".to_string())]));
+        let mut paged = create_active_route("paged", "/api/users/{id}", "paged");
+        paged.preset_mut().query = Some(QueryOrExpression::Map(HashMap::from([(
+            "page".to_string(),
+            "2".to_string(),
+        )])));
+        let mut socket = create_active_route("socket", "/api/users/{id}", "socket");
+        socket.route_mut().transport = Transport::WebSocket;
+        let table = RouteTable::new(vec![
+            create_active_route("orders", "/api/orders/{id}", "orders"),
+            post,
+            socket,
+            admin,
+            paged,
+        ]);
+        let request = create_request("/api/users/42");
+
+        let nearest = |limit| -> Vec<_> {
+            table
+                .nearest(&request, limit)
+                .into_iter()
+                .map(|e| {
+                    let failure = e.failure.unwrap();
+                    (e.route_id, failure.step, failure.field())
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            nearest(3),
+            vec![
+                (
+                    "paged".to_string(),
+                    MatchStep::Query,
+                    Some("page".to_string())
+                ),
+                (
+                    "admin".to_string(),
+                    MatchStep::Params,
+                    Some("id".to_string())
+                ),
+                ("create".to_string(), MatchStep::Method, None),
+            ]
+        );
+        assert_eq!(nearest(10).len(), 4);
+        assert_eq!(nearest(10)[3].0, "orders");
+    }
+
+    #[rstest]
+    #[case(MatchStep::Headers, json!({"X-Api": "v1"}), json!({"x-api": "v2"}), Some("X-Api"))]
+    #[case(MatchStep::Headers, json!({"X-Api": "v1"}), json!({"x-api": "v1"}), None)]
+    #[case(MatchStep::Query, json!({"tags": "a,b"}), json!({"tags": "c"}), Some("tags"))]
+    #[case(
+        MatchStep::Payload,
+        json!({"user": {"name": "a", "age": 1}}),
+        json!({"user": {"name": "a", "age": 2}}),
+        Some("user.age")
+    )]
+    #[case(MatchStep::Payload, json!({"id": 1}), json!(null), Some("id"))]
+    #[case(MatchStep::Payload, json!([1]), json!([2]), None)]
+    #[case(MatchStep::Query, json!("query.page == '2'"), json!({}), None)]
+    #[case(MatchStep::Method, json!("POST"), json!("GET"), None)]
+    fn test_match_failure_field(
+        #[case] step: MatchStep,
+        #[case] expected: Value,
+        #[case] actual: Value,
+        #[case] field: Option<&str>,
+    ) {
+        let failure = MatchFailure {
+            step,
+            expected,
+            actual,
+            error: None,
+        };
+
+        assert_eq!(failure.field().as_deref(), field);
+    }
+
+    #[rstest]
+    fn test_find_route_evaluation_limits() {
+        let mut large = create_active_route("large", "/api/users", "large");
+        large.preset_mut().payload = Some(PayloadOrExpression::Expression(
+            "length(payload.items) > `2`".to_string(),
+        ));
+        let routes = vec![large, create_active_route("list", "/api/users", "list")];
+        let mut request = create_request("/api/users");
+        request.payload = Some(json!({"items": [1, 2, 3, 4]}));
+
+        let table = RouteTable::new(routes.clone());
+        assert_eq!(table.find_route(&request).unwrap().route.id, "large".into());
+
+        let table = RouteTable::new(routes).with_limits(EvaluationLimits {
+            max_size: Some(64),
+            ..Default::default()
+        });
+        request.payload = Some(json!({"items": vec!["x".repeat(10); 8]}));
+        assert_eq!(table.find_route(&request).unwrap().route.id, "list".into());
+
+        let diagnostics = table.diagnose(&request);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].field, MatchField::Payload);
+        assert!(diagnostics[0].to_string().contains("larger than 64 bytes"));
+    }
+
+    #[rstest]
+    #[case("/api/orders/7?dryRun=true", Some("dry-run"))]
+    #[case("/api/orders/8?dryRun=true", Some("default"))]
+    #[case("/api/orders/7", Some("default"))]
+    fn test_find_route_cross_field_expression(#[case] url: &str, #[case] expected: Option<&str>) {
+        let mut dry_run = create_active_route("dry-run", "/api/orders/{id}", "dry-run");
+        dry_run.preset_mut().payload = Some(PayloadOrExpression::Expression(
+            "query.dryRun == 'true' && params.id == '7' && payload.total > `0`".to_string(),
+        ));
+        let table = RouteTable::new(vec![
+            dry_run,
+            create_active_route("default", "/api/orders/{id}", "default"),
+        ]);
+
+        let mut request = create_request(url);
+        request.payload = Some(json!({"total": 10}));
+        let found = table.find_route(&request);
+        assert_eq!(found.map(|r| r.route.id.as_ref()), expected);
+        assert!(table.diagnose(&request).is_empty());
+    }
+
+    #[cfg(feature = "handlebars")]
+    #[rstest]
+    fn test_respond_renders_templates() {
+        let mut user = create_active_route("user", "/api/users/{id}", "user");
+        user.variant_mut().body = Some(json!({"id": "{{params.id}}", "name": "{{query.name}}"}));
+        let table = RouteTable::new(vec![create_active_route("me", "/api/users/me", "me"), user]);
+
+        let variant = table
+            .respond(
+                &create_request("/api/users/42?name=Ann"),
+                Engine::Handlebars,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(variant.body, Some(json!({"id": "42", "name": "Ann"})));
+        assert!(table
+            .respond(&create_request("/other"), Engine::Handlebars)
+            .unwrap()
+            .is_none());
+    }
+
+    #[rstest]
+    fn test_find_route_with_context() {
+        let table = RouteTable::new(vec![create_active_route("user", "/api/users/{id}", "user")]);
+
+        let (active_route, context) = table
+            .find_route_with_context(&create_request("/api/users/42"))
+            .unwrap();
+        assert_eq!(active_route.route.id, "user".into());
+        assert_eq!(context.value()["params"], json!({"id": "42"}));
+        assert!(table
+            .find_route_with_context(&create_request("/other"))
+            .is_none());
+    }
+    #[rstest]
+    fn test_match_cache() {
+        let mut tenant = create_active_route("tenant", "/api/users/{id}", "tenant");
+        tenant.preset_mut().headers = Some(HeadersOrExpression::Map(HashMap::from([(
+            "X-Tenant".to_string(),
+            "acme".to_string(),
+        )])));
+        let table = RouteTable::new(vec![
+            tenant,
+            create_active_route("user", "/api/users/{id}", "user"),
+        ])
+        .with_match_cache(2);
+
+        let request = |url: &str, tenant: &str| {
+            let mut request = create_request(url);
+            request.headers = Some(HashMap::from([
+                ("x-tenant".to_string(), tenant.to_string()),
+                ("x-request-id".to_string(), url.len().to_string()),
+            ]));
+            request
+        };
+        let find = |request: &Request| table.find_route(request).map(|r| r.route.id.to_string());
+
+        assert_eq!(find(&request("/api/users/1", "acme")).unwrap(), "tenant");
+        assert_eq!(find(&request("/api/users/1/", "acme")).unwrap(), "tenant");
+        assert_eq!(table.cached_matches(), 1);
+        assert_eq!(find(&request("/api/users/1", "other")).unwrap(), "user");
+        assert_eq!(table.cached_matches(), 2);
+
+        // Cache hits still set path params of the context
+        let (_, context) = table
+            .find_route_with_context(&request("/api/users/1", "acme"))
+            .unwrap();
+        assert_eq!(context.value()["params"], json!({"id": "1"}));
+
+        // Requests matching no route are cached too, evicting the least recently used
+        assert!(find(&request("/other", "acme")).is_none());
+        assert!(find(&request("/other", "acme")).is_none());
+        assert_eq!(table.cached_matches(), 2);
+        assert_eq!(find(&request("/api/users/1", "other")).unwrap(), "user");
+    }
+
+    #[rstest]
+    fn test_match_cache_skips_expressions_and_payloads() {
+        let mut admin = create_active_route("admin", "/api/users", "admin");
+        admin.preset_mut().query = Some(QueryOrExpression::Expression(
+            "query.role == 'admin'".to_string(),
+        ));
+        let table = RouteTable::new(vec![admin]).with_match_cache(8);
+        assert!(table
+            .find_route(&create_request("/api/users?role=admin"))
+            .is_some());
+        assert_eq!(table.cached_matches(), 0);
+
+        let mut create = create_active_route("create", "/api/users", "create");
+        create.preset_mut().payload = Some(PayloadOrExpression::Value(json!({"name": "Ann"})));
+        let table = RouteTable::new(vec![create]).with_match_cache(8);
+        let mut request = create_request("/api/users");
+        request.payload = Some(json!({"name": "Ann"}));
+        assert!(table.find_route(&request).is_some());
+        request.payload = None;
+        assert!(table.find_route(&request).is_none());
+        assert_eq!(table.cached_matches(), 1);
+    }
+
+    #[rstest]
+    fn test_find_route_graphql() {
+        let graphql = |id: &str, operation_type, name: &str| {
+            let mut active_route = create_active_route(id, "/graphql", id);
+            let route = active_route.route_mut();
+            route.method = Some(HttpMethod::Post);
+            route.graphql = Some(GraphqlOperation {
+                operation_type: Some(operation_type),
+                name: Some(name.to_string()),
+            });
+            active_route
+        };
+        let mut missing_user = graphql("missing-user", OperationType::Query, "GetUser");
+        missing_user.preset_mut().payload = Some(PayloadOrExpression::Expression(
+            "graphql.variables.id == '0'".to_string(),
+        ));
+        let table = RouteTable::new(vec![
+            missing_user,
+            graphql("get-user", OperationType::Query, "GetUser"),
+            graphql("create-user", OperationType::Mutation, "CreateUser"),
+        ])
+        .with_match_cache(8);
+        let request = |document: &str, id: &str| Request {
+            method: Some(HttpMethod::Post),
+            payload: Some(json!({"query": document, "variables": {"id": id}})),
+            ..create_request("/graphql")
+        };
+        let found = |request: &Request| table.find_route(request).map(|r| r.route.id.to_string());
+
+        let get_user = "query GetUser($id: ID!) { user(id: $id) { name } }";
+        assert_eq!(
+            found(&request(get_user, "0")).as_deref(),
+            Some("missing-user")
+        );
+        assert_eq!(found(&request(get_user, "1")).as_deref(), Some("get-user"));
+        assert_eq!(
+            found(&request("mutation CreateUser { createUser { id } }", "1")).as_deref(),
+            Some("create-user")
+        );
+        assert_eq!(found(&request("query CreateUser { user }", "1")), None);
+
+        let failure = table.explain(&request("{ user }", "1"))[1]
+            .failure
+            .clone()
+            .unwrap();
+        assert_eq!(failure.step, MatchStep::Operation);
+        assert_eq!(
+            failure.expected,
+            json!({"type": "query", "name": "GetUser"})
+        );
+        assert_eq!(
+            failure.actual,
+            json!({"type": "query", "name": null, "variables": {"id": "1"}})
+        );
+    }
+}
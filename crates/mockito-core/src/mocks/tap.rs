@@ -0,0 +1,198 @@
+//! Live stream of looked up requests.
+//!
+//! [`RequestTap`] broadcasts a [`LiveEvent`] for every request recorded in a
+//! request history, matched or not, so a developer dashboard can show traffic
+//! hitting the mock in real time. The HTTP server answering requests with the
+//! controller exposes the stream on a WebSocket at [`LIVE_PATH`], sending each
+//! event as a JSON text frame:
+//!
+//! ```ignore
+//! let mut events = controller.subscribe_live();
+//! while let Ok(event) = events.recv().await {
+//!     socket.send(Message::Text(event.to_json())).await?;
+//! }
+//! ```
+//!
+//! Events are only built while someone is subscribed. Subscribers falling more
+//! than [`LIVE_BUFFER`] events behind skip the oldest ones and receive
+//! [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged).
+//!
+//! The crate's only server is [`TcpServer`](crate::mocks::tcp::TcpServer),
+//! answering TCP routes. Requests it answers through the controller show up in
+//! the stream, but it doesn't serve HTTP, so it can't expose the endpoint.
+
+use crate::clock;
+use crate::mocks::controller::Request;
+use crate::mocks::manager::ActiveRoute;
+use crate::types::route::{HttpMethod, Transport};
+use crate::types::variant::Variant;
+use serde::Serialize;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Admin WebSocket endpoint streaming live events
+pub const LIVE_PATH: &str = "/__admin/live";
+
+/// Number of events buffered for each subscriber
+pub const LIVE_BUFFER: usize = 256;
+
+/// Whether a live event is about a matched request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveEventKind {
+    Matched,
+    Unmatched,
+}
+
+/// Request looked up by a controller, as streamed to live subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    #[serde(rename = "type")]
+    pub kind: LiveEventKind,
+    /// Time the request was recorded, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub method: Option<HttpMethod>,
+    pub transport: Transport,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant_id: Option<String>,
+    /// Status of the rendered response, `None` if none was rendered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Time spent looking up the route and rendering the response, in milliseconds
+    pub latency_ms: f64,
+    /// Delay injected before sending the response, in milliseconds
+    pub delay_ms: f64,
+}
+
+impl LiveEvent {
+    /// Describe a recorded request.
+    pub fn new(
+        request: &Request,
+        active_route: Option<&ActiveRoute>,
+        response: Option<&Variant>,
+        latency: Duration,
+        delay: Duration,
+    ) -> Self {
+        let timestamp = clock::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self {
+            kind: match active_route {
+                Some(_) => LiveEventKind::Matched,
+                None => LiveEventKind::Unmatched,
+            },
+            timestamp: u64::try_from(timestamp.as_millis()).unwrap_or(u64::MAX),
+            method: request.method.clone(),
+            transport: request.transport.clone(),
+            url: request.url.clone(),
+            route_id: active_route.map(|a| a.route.id.to_string()),
+            preset_id: active_route.map(|a| a.preset().id.to_string()),
+            variant_id: active_route.map(|a| a.variant().id.to_string()),
+            status: response.map(|variant| variant.status.unwrap_or(200)),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            delay_ms: delay.as_secs_f64() * 1000.0,
+        }
+    }
+
+    /// Serialize the event as a JSON text frame.
+    pub fn to_json(&self) -> String {
+        // Events only hold strings, numbers and unit enums, which always serialize
+        serde_json::to_string(self).expect("live event is serializable")
+    }
+}
+
+/// Broadcast of live events to subscribers.
+#[derive(Debug)]
+pub struct RequestTap {
+    sender: broadcast::Sender<LiveEvent>,
+}
+
+impl RequestTap {
+    /// Receive events of requests recorded from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of current subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Send an event to subscribers, building it only if there are any.
+    pub fn publish(&self, event: impl FnOnce() -> LiveEvent) {
+        if self.sender.receiver_count() > 0 {
+            // Fails only if the last subscriber left meanwhile
+            let _ = self.sender.send(event());
+        }
+    }
+}
+
+impl Default for RequestTap {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(LIVE_BUFFER).0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    fn request() -> Request {
+        Request {
+            url: "/api/orders".to_string(),
+            method: Some(HttpMethod::Get),
+            transport: Transport::Http,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    #[rstest]
+    fn test_publish_to_subscribers() {
+        let tap = RequestTap::default();
+        let mut built = false;
+        tap.publish(|| {
+            built = true;
+            LiveEvent::new(&request(), None, None, Duration::ZERO, Duration::ZERO)
+        });
+        assert!(!built);
+
+        let mut events = tap.subscribe();
+        assert_eq!(tap.subscribers(), 1);
+        tap.publish(|| {
+            LiveEvent::new(
+                &request(),
+                None,
+                None,
+                Duration::from_micros(1500),
+                Duration::ZERO,
+            )
+        });
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.kind, LiveEventKind::Unmatched);
+        let mut frame: Value = serde_json::from_str(&event.to_json()).unwrap();
+        assert!(frame["timestamp"].as_u64().unwrap() > 0);
+        frame.as_object_mut().unwrap().remove("timestamp");
+        assert_eq!(
+            frame,
+            json!({
+                "type": "unmatched",
+                "method": "GET",
+                "transport": "HTTP",
+                "url": "/api/orders",
+                "latencyMs": 1.5,
+                "delayMs": 0.0,
+            })
+        );
+    }
+}
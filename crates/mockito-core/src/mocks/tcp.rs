@@ -0,0 +1,398 @@
+//! Reply scripts and server of TCP routes.
+//!
+//! Routes with `transport: TCP` answer raw TCP messages sent to the address in
+//! their URL, matched on their first bytes or a regex (see [`TcpMatch`]).
+//! Variants hold the bytes to send back in their body instead of a response
+//! body:
+//!
+//! ```yaml
+//! id: smtp-mail-from
+//! url: 127.0.0.1:2525
+//! transport: TCP
+//! tcp:
+//!   prefix: "MAIL FROM:"
+//! presets:
+//!   - id: default
+//!     variants:
+//!       - id: ok
+//!         body: "250 OK\r\n"
+//!       - id: rejected
+//!         body:
+//!           replies:
+//!             - data: "550 Mailbox unavailable\r\n"
+//!               delay: 200
+//!           close: true
+//! ```
+//!
+//! A string body is a single reply. Reply data is sent as text, unless it is a
+//! `data:` URI holding binary bytes, e.g. `data:;base64,AAE=`.
+//!
+//! [`TcpServer`] listens on a route address and answers each message read from
+//! a connection with the variant returned by a responder, usually
+//! [`MocksController::respond`](crate::mocks::controller::MocksController::respond):
+//!
+//! ```ignore
+//! let controller = Arc::new(RwLock::new(controller));
+//! let server = TcpServer::bind("127.0.0.1:2525", move |request| {
+//!     controller.read().unwrap().respond(request).ok().flatten()
+//! })?;
+//! ```
+//!
+//! Each read is one message, so clients must wait for replies before sending the
+//! next command. Connections sending a message no route matches are closed.
+//!
+//! [`TcpMatch`]: crate::types::tcp::TcpMatch
+
+use crate::config::fixtures::{parse_data_uri, DATA_PREFIX};
+use crate::matching::message_payload;
+use crate::mocks::controller::Request;
+use crate::types::route::Transport;
+use crate::types::variant::Variant;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Maximum size of a message read from a connection
+pub const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Interval at which idle connections check if the server stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Replies sent by a variant of a TCP route, held in its body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TcpScript {
+    /// Replies in sending order
+    #[serde(default)]
+    pub replies: Vec<TcpReply>,
+    /// Whether to close the connection after the replies
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub close: bool,
+}
+
+/// Reply of a [`TcpScript`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TcpReply {
+    /// Text to send, or a `data:` URI holding binary bytes
+    pub data: String,
+    /// Delay before sending the reply, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+/// Chunk of bytes sent on a connection after a delay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpFrame {
+    /// Time to wait before sending the bytes
+    pub delay: Duration,
+    pub data: Vec<u8>,
+}
+
+/// Variant body that isn't a valid [`TcpScript`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid reply script in variant '{variant_id}': {message}")]
+pub struct TcpScriptError {
+    pub variant_id: String,
+    pub message: String,
+}
+
+impl TcpScript {
+    /// Read the script of a rendered variant, empty if the variant has no body.
+    ///
+    /// Returns error if the body isn't a script or a reply holds a malformed
+    /// `data:` URI.
+    pub fn from_variant(variant: &Variant) -> Result<Self, TcpScriptError> {
+        let error = |message: String| TcpScriptError {
+            variant_id: variant.id.to_string(),
+            message,
+        };
+        let script = match &variant.body {
+            None => Self::default(),
+            Some(Value::String(data)) => Self {
+                replies: vec![TcpReply {
+                    data: data.clone(),
+                    delay: None,
+                }],
+                close: false,
+            },
+            Some(body) => Self::deserialize(body).map_err(|e| error(e.to_string()))?,
+        };
+        for reply in &script.replies {
+            reply.bytes().map_err(error)?;
+        }
+        Ok(script)
+    }
+
+    /// Get the frames to send, each reply after its delay.
+    ///
+    /// Replies holding a malformed `data:` URI are sent as text.
+    pub fn frames(&self) -> Vec<TcpFrame> {
+        self.replies
+            .iter()
+            .map(|reply| TcpFrame {
+                delay: Duration::from_millis(reply.delay.unwrap_or(0)),
+                data: reply
+                    .bytes()
+                    .unwrap_or_else(|_| reply.data.as_bytes().to_vec()),
+            })
+            .collect()
+    }
+}
+
+impl TcpReply {
+    /// Get the bytes to send, decoding `data:` URIs.
+    ///
+    /// Returns error if the data is a malformed `data:` URI.
+    pub fn bytes(&self) -> Result<Vec<u8>, String> {
+        if self.data.starts_with(DATA_PREFIX) {
+            return parse_data_uri(&self.data)
+                .map(|fixture| fixture.data)
+                .map_err(|e| e.to_string());
+        }
+        Ok(self.data.as_bytes().to_vec())
+    }
+}
+
+/// Build the request of a message read from a connection to `url`.
+pub fn tcp_request(url: &str, message: &[u8]) -> Request {
+    Request {
+        url: url.to_string(),
+        method: None,
+        transport: Transport::Tcp,
+        headers: None,
+        query: None,
+        payload: Some(message_payload(message)),
+    }
+}
+
+type Responder = dyn Fn(&Request) -> Option<Variant> + Send + Sync;
+
+/// Blocking TCP server answering messages with reply scripts.
+///
+/// Runs on its own threads, one accepting connections and one per connection.
+/// The server stops when dropped.
+#[derive(Debug)]
+pub struct TcpServer {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl TcpServer {
+    /// Listen on the address of a TCP route URL.
+    ///
+    /// Messages become requests to `url` (see [`tcp_request`]) answered with the
+    /// script of the variant returned by `responder`.
+    ///
+    /// Returns error if the address can't be bound.
+    pub fn bind(
+        url: &str,
+        responder: impl Fn(&Request) -> Option<Variant> + Send + Sync + 'static,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(url)?;
+        let address = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let responder: Arc<Responder> = Arc::new(responder);
+        let url = url.to_string();
+
+        let accept_stopped = stopped.clone();
+        let accept = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accept_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        tracing::debug!(%error, "failed to accept connection");
+                        continue;
+                    }
+                };
+                let url = url.clone();
+                let responder = responder.clone();
+                let stopped = accept_stopped.clone();
+                thread::spawn(move || {
+                    if let Err(error) = serve(stream, &url, &*responder, &stopped) {
+                        tracing::debug!(url, %error, "connection failed");
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            address,
+            stopped,
+            accept: Some(accept),
+        })
+    }
+
+    /// Get the bound address, useful when binding port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Stop accepting connections and close open ones.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let Some(accept) = self.accept.take() else {
+            return;
+        };
+        self.stopped.store(true, Ordering::Relaxed);
+        // Wake up the accepting thread, blocked until the next connection
+        let mut address = self.address;
+        if address.ip().is_unspecified() {
+            address.set_ip(match address {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        if TcpStream::connect_timeout(&address, POLL_INTERVAL).is_ok() {
+            let _ = accept.join();
+        }
+    }
+}
+
+impl Drop for TcpServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Answer the messages of a connection until the client or a script closes it.
+fn serve(
+    mut stream: TcpStream,
+    url: &str,
+    responder: &Responder,
+    stopped: &AtomicBool,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut buffer = vec![0; MAX_MESSAGE_BYTES];
+    loop {
+        let read = match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                if stopped.load(Ordering::Relaxed) {
+                    return stream.shutdown(Shutdown::Both);
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let request = tcp_request(url, &buffer[..read]);
+        let Some(variant) = responder(&request) else {
+            return stream.shutdown(Shutdown::Both);
+        };
+        let script = TcpScript::from_variant(&variant)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        for frame in script.frames() {
+            thread::sleep(frame.delay);
+            stream.write_all(&frame.data)?;
+        }
+        if script.close {
+            return stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::message_bytes;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn variant(body: Value) -> Variant {
+        Variant {
+            id: "ok".into(),
+            status: None,
+            headers: None,
+            body: Some(body),
+            template: None,
+        }
+    }
+
+    #[rstest]
+    #[case(json!("250 OK\r\n"), vec![b"250 OK\r\n".to_vec()], false)]
+    #[case(
+        json!({"replies": [{"data": "220 ready\r\n"}, {"data": "data:;base64,AP8=", "delay": 10}], "close": true}),
+        vec![b"220 ready\r\n".to_vec(), vec![0x00, 0xff]],
+        true
+    )]
+    fn test_script_frames(
+        #[case] body: Value,
+        #[case] expected: Vec<Vec<u8>>,
+        #[case] close: bool,
+    ) {
+        let script = TcpScript::from_variant(&variant(body)).unwrap();
+        let frames = script.frames();
+        assert_eq!(script.close, close);
+        assert_eq!(
+            frames.iter().map(|f| f.data.clone()).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            frames.last().unwrap().delay,
+            Duration::from_millis(if close { 10 } else { 0 })
+        );
+    }
+
+    #[rstest]
+    #[case(json!({"replies": [{"text": "x"}]}))]
+    #[case(json!({"replies": [{"data": "data:;base64,!!"}]}))]
+    fn test_invalid_script(#[case] body: Value) {
+        let error = TcpScript::from_variant(&variant(body)).unwrap_err();
+        assert_eq!(error.variant_id, "ok");
+        assert!(error
+            .to_string()
+            .starts_with("Invalid reply script in variant 'ok'"));
+    }
+
+    #[rstest]
+    fn test_server_replies() {
+        let server = TcpServer::bind("127.0.0.1:0", |request| {
+            assert_eq!(request.url, "127.0.0.1:0");
+            let message = message_bytes(request.payload.as_ref())?;
+            match message.as_slice() {
+                b"EHLO test\r\n" => Some(variant(json!("250 Hello\r\n"))),
+                b"QUIT\r\n" => Some(variant(json!({
+                    "replies": [{"data": "221 Bye\r\n"}],
+                    "close": true
+                }))),
+                _ => None,
+            }
+        })
+        .unwrap();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        let mut reply = [0; 64];
+
+        client.write_all(b"EHLO test\r\n").unwrap();
+        let read = client.read(&mut reply).unwrap();
+        assert_eq!(&reply[..read], b"250 Hello\r\n");
+
+        client.write_all(b"QUIT\r\n").unwrap();
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"221 Bye\r\n");
+
+        // Unmatched messages close the connection
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client.write_all(b"NOOP\r\n").unwrap();
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).unwrap();
+        assert!(rest.is_empty());
+
+        server.stop();
+    }
+}
@@ -0,0 +1,524 @@
+//! Capture of requests that matched no route.
+//!
+//! [`UnmatchedRequests`] groups requests by transport, method and URL path, so
+//! a client polling a missing endpoint produces one entry with a hit count. Each
+//! entry suggests a route skeleton answering it, and [`UnmatchedRequests::report`]
+//! renders all suggestions as config ready to paste into a routes file:
+//!
+//! ```yaml
+//! # 3 requests: GET /api/orders?status=open
+//! id: get-api-orders
+//! url: /api/orders
+//! transport: HTTP
+//! method: GET
+//! presets:
+//! - id: default
+//!   query:
+//!     status: open
+//!   variants:
+//!   - id: ok
+//!     status: 200
+//!     body: {}
+//! ```
+//!
+//! [`not_found`] builds the 404 response of an unmatched request, optionally
+//! listing the routes closest to matching it.
+
+use crate::clock;
+use crate::config::error::ConfigError;
+use crate::config::export::ExportFormat;
+use crate::matching::message_bytes;
+use crate::mocks::context::RequestContext;
+use crate::mocks::controller::Request;
+use crate::mocks::table::RouteExplanation;
+use crate::types::graphql::GraphqlOperation;
+use crate::types::preset::{Preset, QueryOrExpression};
+use crate::types::route::{HttpMethod, Route, Transport};
+use crate::types::tcp::TcpMatch;
+use crate::types::variant::Variant;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+/// Number of distinct unmatched requests kept
+pub const DEFAULT_UNMATCHED_CAPACITY: usize = 100;
+
+/// Request that matched no route, with how often it was seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedRequest {
+    /// Latest request with the same transport, method and path
+    pub request: Request,
+    /// Number of requests with the same transport, method and path
+    pub count: usize,
+    /// Time the first of the requests was seen
+    pub first_seen: SystemTime,
+    /// Time the latest of the requests was seen
+    pub last_seen: SystemTime,
+}
+
+impl UnmatchedRequest {
+    /// Get a route skeleton matching the request, see [`suggest_route`].
+    pub fn suggestion(&self) -> Route {
+        suggest_route(&self.request)
+    }
+}
+
+/// Unmatched requests grouped by transport, method and URL path, oldest first.
+///
+/// Once `capacity` distinct requests are kept, the least recently seen one is
+/// dropped for every new one.
+#[derive(Debug)]
+pub struct UnmatchedRequests {
+    capacity: usize,
+    entries: Mutex<Vec<UnmatchedRequest>>,
+}
+
+impl UnmatchedRequests {
+    /// Create a capture keeping up to `capacity` distinct requests.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record a request that matched no route.
+    pub fn record(&self, request: &Request) {
+        if self.capacity == 0 {
+            return;
+        }
+        let now = clock::now();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let existing = entries
+            .iter_mut()
+            .find(|entry| same_endpoint(&entry.request, request));
+        if let Some(entry) = existing {
+            entry.request = request.clone();
+            entry.count += 1;
+            entry.last_seen = now;
+            return;
+        }
+        if entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.last_seen)
+                .map(|(i, _)| i);
+            if let Some(oldest) = oldest {
+                entries.remove(oldest);
+            }
+        }
+        entries.push(UnmatchedRequest {
+            request: request.clone(),
+            count: 1,
+            first_seen: now,
+            last_seen: now,
+        });
+    }
+
+    /// Get all captured requests, in order of their first occurrence.
+    pub fn requests(&self) -> Vec<UnmatchedRequest> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Render route skeletons of all captured requests.
+    ///
+    /// YAML reports hold one document per request, commented with the request and
+    /// its count. JSON reports hold an array of routes.
+    ///
+    /// Returns error if a route can't be serialized.
+    pub fn report(&self, format: ExportFormat) -> Result<String, ConfigError> {
+        let requests = self.requests();
+        match format {
+            ExportFormat::Json => {
+                let routes: Vec<_> = requests.iter().map(UnmatchedRequest::suggestion).collect();
+                format.serialize(&routes)
+            }
+            ExportFormat::Yaml => {
+                let mut report = String::new();
+                for (i, unmatched) in requests.iter().enumerate() {
+                    if i > 0 {
+                        report.push_str("---\n");
+                    }
+                    report.push_str(&format!(
+                        "# {} {}: {}\n",
+                        unmatched.count,
+                        if unmatched.count == 1 {
+                            "request"
+                        } else {
+                            "requests"
+                        },
+                        describe(&unmatched.request)
+                    ));
+                    report.push_str(&format.serialize(&unmatched.suggestion())?);
+                }
+                Ok(report)
+            }
+        }
+    }
+
+    /// Get the number of distinct captured requests.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no request is captured.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replace all captured requests, e.g. with ones taken by
+    /// [`requests`](Self::requests).
+    pub fn restore(&self, requests: Vec<UnmatchedRequest>) {
+        *self.entries.lock().unwrap_or_else(PoisonError::into_inner) = requests;
+    }
+
+    /// Remove all captured requests.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl Default for UnmatchedRequests {
+    fn default() -> Self {
+        Self::new(DEFAULT_UNMATCHED_CAPACITY)
+    }
+}
+
+/// Build a route skeleton matching a request.
+///
+/// The route is named after the method and path, with a `default` preset matching
+/// the query parameters of the request and an `ok` variant answering with an
+/// empty JSON object.
+///
+/// Routes of GraphQL requests are named after the operation and answer it
+/// instead, with an `ok` variant holding empty `data`. Variants of SSE routes
+/// stream no events. Routes of TCP messages are named after the first word of
+/// the message, which they match as prefix, and send no replies.
+pub fn suggest_route(request: &Request) -> Route {
+    let path = path(&request.url);
+    let context = RequestContext::new(request);
+    let graphql = context.graphql().map(|graphql| GraphqlOperation {
+        operation_type: Some(graphql.operation_type),
+        name: graphql.name.clone(),
+    });
+    let tcp = (request.transport == Transport::Tcp).then(|| TcpMatch {
+        prefix: command(request),
+        pattern: None,
+    });
+    let (id, query, body) = match context.graphql() {
+        Some(graphql) => {
+            let name = graphql.name.as_deref().unwrap_or_default();
+            let id = slug([graphql.operation_type.as_str(), name]);
+            (id, HashMap::new(), json!({"data": {}}))
+        }
+        None if tcp.is_some() => {
+            let command = tcp.as_ref().and_then(|tcp| tcp.prefix.as_deref());
+            let words = command
+                .into_iter()
+                .flat_map(|command| command.split(|c: char| !c.is_ascii_alphanumeric()));
+            let id = slug(std::iter::once("tcp").chain(words));
+            (id, HashMap::new(), json!({"replies": []}))
+        }
+        None => {
+            let method = request.method.as_ref().map(HttpMethod::as_str);
+            let words = path.split(|c: char| !c.is_ascii_alphanumeric());
+            let id = slug(method.into_iter().chain(words));
+            let body = match request.transport {
+                Transport::Sse => json!({"events": []}),
+                _ => json!({}),
+            };
+            (id, context.query().clone(), body)
+        }
+    };
+    let status = match request.transport {
+        Transport::Http | Transport::Sse => Some(200),
+        Transport::WebSocket | Transport::Tcp => None,
+    };
+
+    Route {
+        id: id.into(),
+        url: path.to_string(),
+        transport: request.transport.clone(),
+        method: request.method.clone(),
+        presets: vec![Preset {
+            id: "default".into(),
+            params: None,
+            query: (!query.is_empty()).then_some(QueryOrExpression::Map(query)),
+            headers: None,
+            payload: None,
+            variants: vec![Variant {
+                id: "ok".into(),
+                status,
+                headers: None,
+                body: Some(body),
+                template: None,
+            }],
+        }],
+        tags: Vec::new(),
+        group: None,
+        proto: None,
+        graphql,
+        tcp,
+    }
+}
+
+/// Get the first word of a TCP message.
+fn command(request: &Request) -> Option<String> {
+    let message = message_bytes(request.payload.as_ref())?;
+    let message = std::str::from_utf8(&message).ok()?;
+    message.split_whitespace().next().map(str::to_string)
+}
+
+/// Join lowercase words into a route ID, `root` if there are none.
+fn slug<'a>(words: impl IntoIterator<Item = &'a str>) -> String {
+    let id = words
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if id.is_empty() {
+        "root".to_string()
+    } else {
+        id
+    }
+}
+
+/// Build the 404 response of a request that matched no route.
+///
+/// The body holds an `error` message and, if `near_matches` is set, the routes
+/// closest to matching with the condition each of them failed.
+pub fn not_found(request: &Request, near_matches: Option<&[RouteExplanation]>) -> Variant {
+    let mut body = json!({
+        "error": format!("No route matches {}", describe(request)),
+    });
+    if let Some(near_matches) = near_matches {
+        body["near_matches"] = near_matches.iter().map(near_match).collect();
+    }
+
+    Variant {
+        id: "not-found".into(),
+        status: Some(404),
+        headers: None,
+        body: Some(body),
+        template: None,
+    }
+}
+
+/// Describe a route that failed to match in a 404 body.
+fn near_match(explanation: &RouteExplanation) -> Value {
+    let failure = explanation.failure.as_ref();
+    json!({
+        "route_id": explanation.route_id,
+        "preset_id": explanation.preset_id,
+        "step": failure.map(|f| f.step.to_string()),
+        "field": failure.and_then(|f| f.field()),
+        "expected": failure.map(|f| &f.expected),
+        "actual": failure.map(|f| &f.actual),
+    })
+}
+
+/// Whether requests go to the same transport, method and URL path.
+fn same_endpoint(a: &Request, b: &Request) -> bool {
+    a.transport == b.transport && a.method == b.method && path(&a.url) == path(&b.url)
+}
+
+/// URL without query string.
+fn path(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+/// Describe a request by method and URL.
+fn describe(request: &Request) -> String {
+    match &request.method {
+        Some(method) => format!("{} {}", method.as_str(), request.url),
+        None => request.url.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::table::{MatchFailure, MatchStep};
+    use crate::mocks::tcp::tcp_request;
+    use rstest::rstest;
+
+    fn request(method: Option<HttpMethod>, url: &str) -> Request {
+        let transport = match method {
+            Some(_) => Transport::Http,
+            None => Transport::WebSocket,
+        };
+        Request {
+            url: url.to_string(),
+            method,
+            transport,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    #[rstest]
+    #[case(
+        Some(HttpMethod::Get),
+        "/api/orders?status=open",
+        "get-api-orders",
+        "/api/orders"
+    )]
+    #[case(
+        Some(HttpMethod::Delete),
+        "/api/users/42/",
+        "delete-api-users-42",
+        "/api/users/42/"
+    )]
+    #[case(Some(HttpMethod::Get), "/", "get", "/")]
+    #[case(None, "/", "root", "/")]
+    #[case(None, "/ws/chat", "ws-chat", "/ws/chat")]
+    fn test_suggest_route(
+        #[case] method: Option<HttpMethod>,
+        #[case] url: &str,
+        #[case] id: &str,
+        #[case] route_url: &str,
+    ) {
+        let route = suggest_route(&request(method.clone(), url));
+
+        assert_eq!(&*route.id, id);
+        assert_eq!(route.url, route_url);
+        assert_eq!(route.method, method);
+    }
+
+    #[rstest]
+    fn test_suggest_route_graphql() {
+        let mut request = request(Some(HttpMethod::Get), "/graphql");
+        request.query = Some(HashMap::from([(
+            "query".to_string(),
+            "query GetUser { user { id } }".to_string(),
+        )]));
+
+        let route = suggest_route(&request);
+        assert_eq!(&*route.id, "query-getuser");
+        assert_eq!(
+            route
+                .graphql
+                .and_then(|operation| operation.name)
+                .as_deref(),
+            Some("GetUser")
+        );
+        assert_eq!(route.presets[0].query, None);
+        assert_eq!(route.presets[0].variants[0].body, Some(json!({"data": {}})));
+    }
+
+    #[rstest]
+    fn test_suggest_route_tcp() {
+        let route = suggest_route(&tcp_request("127.0.0.1:2525", b"HELO mail.test\r\n"));
+        assert_eq!(&*route.id, "tcp-helo");
+        assert_eq!(route.url, "127.0.0.1:2525");
+        assert_eq!(
+            route.tcp.and_then(|tcp| tcp.prefix).as_deref(),
+            Some("HELO")
+        );
+        assert_eq!(route.presets[0].variants[0].status, None);
+        assert_eq!(
+            route.presets[0].variants[0].body,
+            Some(json!({"replies": []}))
+        );
+    }
+
+    #[rstest]
+    fn test_record_groups_by_path() {
+        let unmatched = UnmatchedRequests::new(2);
+        unmatched.record(&request(Some(HttpMethod::Get), "/a?page=1"));
+        unmatched.record(&request(Some(HttpMethod::Get), "/a?page=2"));
+        unmatched.record(&request(Some(HttpMethod::Post), "/a"));
+        unmatched.record(&request(Some(HttpMethod::Get), "/a"));
+        unmatched.record(&request(Some(HttpMethod::Get), "/b"));
+
+        let requests = unmatched.requests();
+        let urls: Vec<_> = requests
+            .iter()
+            .map(|entry| (entry.request.url.as_str(), entry.count))
+            .collect();
+        assert_eq!(urls, vec![("/a", 3), ("/b", 1)]);
+    }
+
+    #[rstest]
+    fn test_report_yaml() {
+        let unmatched = UnmatchedRequests::default();
+        unmatched.record(&request(Some(HttpMethod::Get), "/api/orders?status=open"));
+        unmatched.record(&request(Some(HttpMethod::Get), "/api/orders?status=open"));
+
+        let report = unmatched.report(ExportFormat::Yaml).unwrap();
+
+        assert!(
+            report.starts_with("# 2 requests: GET /api/orders?status=open\nid: get-api-orders\n")
+        );
+        let route: Route = serde_yaml::from_str(&report).unwrap();
+        assert_eq!(route, unmatched.requests()[0].suggestion());
+        let preset = &route.presets[0];
+        assert_eq!(
+            preset.query,
+            Some(QueryOrExpression::Map(
+                [("status".to_string(), "open".to_string())].into()
+            ))
+        );
+    }
+
+    #[rstest]
+    fn test_not_found() {
+        let request = request(Some(HttpMethod::Get), "/api/users?page=2");
+        let near_matches = [RouteExplanation {
+            route_id: "users".to_string(),
+            preset_id: "default".to_string(),
+            failure: Some(MatchFailure {
+                step: MatchStep::Query,
+                expected: json!({"page": "1"}),
+                actual: json!({"page": "2"}),
+                error: None,
+            }),
+        }];
+
+        let variant = not_found(&request, None);
+        assert_eq!(variant.status, Some(404));
+        assert_eq!(
+            variant.body,
+            Some(json!({"error": "No route matches GET /api/users?page=2"}))
+        );
+
+        let variant = not_found(&request, Some(&near_matches));
+        assert_eq!(
+            variant.body.unwrap()["near_matches"],
+            json!([{
+                "route_id": "users",
+                "preset_id": "default",
+                "step": "query",
+                "field": "page",
+                "expected": {"page": "1"},
+                "actual": {"page": "2"},
+            }])
+        );
+    }
+
+    #[rstest]
+    fn test_report_json() {
+        let unmatched = UnmatchedRequests::default();
+        unmatched.record(&request(Some(HttpMethod::Get), "/a"));
+        unmatched.record(&request(Some(HttpMethod::Post), "/b"));
+
+        let report = unmatched.report(ExportFormat::Json).unwrap();
+
+        let routes: Vec<Route> = serde_json::from_str(&report).unwrap();
+        let ids: Vec<_> = routes.iter().map(|route| &*route.id).collect();
+        assert_eq!(ids, vec!["get-a", "post-b"]);
+    }
+}
@@ -0,0 +1,736 @@
+//! Upfront validation of collections and routes.
+//!
+//! Validation checks all data stored in [`MocksManager`] in one pass, so broken
+//! references are reported before any collection is activated. Preset expressions
+//! and variant templates are compiled too, so broken ones are reported before any
+//! request is made. Variants of GraphQL routes are checked to answer with
+//! spec-compliant GraphQL responses, variants of SSE routes to hold valid event
+//! scripts.
+
+use crate::expression;
+use crate::mocks::manager::{DefinitionKind, MocksManager, ResolveError};
+use crate::mocks::sse::SseScript;
+use crate::mocks::tcp::TcpScript;
+use crate::template::{has_partials, inline_partials, Engine, TemplateEngine};
+use crate::types::route::Transport;
+use crate::types::variant::Variant;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Severity of a validation issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Data is broken and resolution will fail
+    Error,
+    /// Data is suspicious but still resolvable
+    Warning,
+}
+
+/// Problem found during validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Issue severity
+    pub severity: Severity,
+    /// Location of the problem (e.g. `collections.base.routes[1]`)
+    pub path: String,
+    /// Human-readable description
+    pub message: String,
+    /// File the definition was loaded from, if known
+    pub file: Option<String>,
+}
+
+impl ValidationIssue {
+    fn error(path: String, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            path,
+            message,
+            file: None,
+        }
+    }
+
+    fn warning(path: String, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            path,
+            message,
+            file: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "[{}] ", severity)?;
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate all routes and collections stored in the manager.
+///
+/// Issues are ordered by routes first, then collections, both sorted by ID.
+pub fn validate(manager: &MocksManager) -> Vec<ValidationIssue> {
+    validate_with(manager, Engine::default(), &HashMap::new(), |_, _| None)
+}
+
+/// Validate all routes and collections stored in the manager, attaching files.
+///
+/// Variants without their own template engine are checked with `template`,
+/// after inlining `partials`. `source` returns the file a definition was loaded
+/// from, if known.
+pub fn validate_with(
+    manager: &MocksManager,
+    template: Engine,
+    partials: &HashMap<String, String>,
+    source: impl Fn(DefinitionKind, &str) -> Option<String>,
+) -> Vec<ValidationIssue> {
+    let templates = Templates {
+        default: template,
+        partials,
+    };
+    let mut issues = Vec::new();
+    validate_routes(manager, &templates, &source, &mut issues);
+    validate_collections(manager, &source, &mut issues);
+    issues
+}
+
+/// Template options variants are checked with.
+struct Templates<'a> {
+    /// Engine of variants without their own
+    default: Engine,
+    /// Fragments included with `{{> name}}`
+    partials: &'a HashMap<String, String>,
+}
+
+/// Set the file of issues found for a definition.
+fn attach_file(issues: &mut [ValidationIssue], file: Option<String>) {
+    for issue in issues {
+        issue.file = file.clone();
+    }
+}
+
+/// Check per-route preset/variant integrity, expressions and templates.
+fn validate_routes(
+    manager: &MocksManager,
+    templates: &Templates,
+    source: &impl Fn(DefinitionKind, &str) -> Option<String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    for route in manager.list_routes() {
+        let route_path = format!("routes.{}", route.id);
+        let start = issues.len();
+
+        if let Some(group_id) = &route.group {
+            if manager.get_group(group_id).is_none() {
+                issues.push(ValidationIssue::error(
+                    format!("{}.group", route_path),
+                    format!("Group not found: {}", group_id),
+                ));
+            }
+        }
+
+        if let Some(pattern) = route.tcp.as_ref().and_then(|tcp| tcp.pattern.as_ref()) {
+            if let Err(e) = regex::bytes::Regex::new(pattern) {
+                issues.push(ValidationIssue::error(
+                    format!("{}.tcp.pattern", route_path),
+                    format!("Invalid message pattern: {}", e),
+                ));
+            }
+        }
+
+        if route.presets.is_empty() {
+            issues.push(ValidationIssue::warning(
+                route_path.clone(),
+                "Route has no presets".to_string(),
+            ));
+        }
+
+        let mut preset_ids = HashSet::new();
+        for (i, preset) in route.presets.iter().enumerate() {
+            let preset_path = format!("{}.presets[{}]", route_path, i);
+
+            if !preset_ids.insert(&*preset.id) {
+                issues.push(ValidationIssue::error(
+                    preset_path.clone(),
+                    format!("Duplicate preset ID '{}'", preset.id),
+                ));
+            }
+
+            for (field, expression) in preset.expressions() {
+                if let Err(e) = expression::compile(expression) {
+                    issues.push(ValidationIssue::warning(
+                        format!("{}.{}", preset_path, field),
+                        e.to_string(),
+                    ));
+                }
+            }
+
+            if preset.variants.is_empty() {
+                issues.push(ValidationIssue::warning(
+                    preset_path.clone(),
+                    format!("Preset '{}' has no variants", preset.id),
+                ));
+            }
+
+            let mut variant_ids = HashSet::new();
+            for (j, variant) in preset.variants.iter().enumerate() {
+                let variant_path = format!("{}.variants[{}]", preset_path, j);
+                if !variant_ids.insert(&*variant.id) {
+                    issues.push(ValidationIssue::error(
+                        variant_path.clone(),
+                        format!("Duplicate variant ID '{}'", variant.id),
+                    ));
+                }
+                validate_templates(variant, &variant_path, templates, issues);
+                if route.graphql.is_some() {
+                    validate_graphql_response(variant, &variant_path, issues);
+                }
+                let script_error = match route.transport {
+                    Transport::Sse => SseScript::from_variant(variant)
+                        .err()
+                        .map(|e| e.to_string()),
+                    Transport::Tcp => TcpScript::from_variant(variant)
+                        .err()
+                        .map(|e| e.to_string()),
+                    Transport::Http | Transport::WebSocket => None,
+                };
+                if let Some(message) = script_error {
+                    issues.push(ValidationIssue::warning(
+                        format!("{}.body", variant_path),
+                        message,
+                    ));
+                }
+            }
+        }
+
+        attach_file(
+            &mut issues[start..],
+            source(DefinitionKind::Route, &route.id),
+        );
+    }
+}
+
+/// Check that templates in variant headers and body compile.
+fn validate_templates(
+    variant: &Variant,
+    variant_path: &str,
+    templates: &Templates,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let checker = TemplateChecker {
+        engine: variant
+            .template
+            .unwrap_or(templates.default)
+            .template_engine(),
+        partials: templates.partials,
+    };
+    if let Some(headers) = &variant.headers {
+        let mut names: Vec<&Arc<str>> = headers.keys().collect();
+        names.sort();
+        for name in names {
+            let path = format!("{}.headers.{}", variant_path, name);
+            checker.check(&headers[name], path, issues);
+        }
+    }
+    if let Some(body) = &variant.body {
+        checker.check_body(body, format!("{}.body", variant_path), issues);
+    }
+}
+
+/// Check that a variant answering a GraphQL operation has a spec-compliant
+/// response body: `data` and/or a non-empty list of `errors` with a `message`,
+/// plus optional `extensions`.
+///
+/// Bodies other than JSON objects, such as `file:` fixtures, aren't checked.
+fn validate_graphql_response(
+    variant: &Variant,
+    variant_path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(Value::Object(body)) = &variant.body else {
+        return;
+    };
+    let path = format!("{}.body", variant_path);
+    let unexpected = body
+        .keys()
+        .find(|key| !matches!(key.as_str(), "data" | "errors" | "extensions"));
+    if let Some(key) = unexpected {
+        issues.push(ValidationIssue::warning(
+            format!("{}.{}", path, key),
+            format!("Unexpected key '{}' in GraphQL response", key),
+        ));
+    }
+    match body.get("errors") {
+        None if !body.contains_key("data") => issues.push(ValidationIssue::warning(
+            path,
+            "GraphQL response has neither 'data' nor 'errors'".to_string(),
+        )),
+        None => {}
+        Some(Value::Array(errors)) if !errors.is_empty() => {
+            for (i, error) in errors.iter().enumerate() {
+                if !error.get("message").is_some_and(Value::is_string) {
+                    issues.push(ValidationIssue::warning(
+                        format!("{}.errors[{}]", path, i),
+                        "GraphQL error has no 'message' string".to_string(),
+                    ));
+                }
+            }
+        }
+        Some(_) => issues.push(ValidationIssue::warning(
+            format!("{}.errors", path),
+            "GraphQL 'errors' must be a non-empty list".to_string(),
+        )),
+    }
+}
+
+/// Compiles the templates of one variant.
+struct TemplateChecker<'a> {
+    engine: &'a dyn TemplateEngine,
+    partials: &'a HashMap<String, String>,
+}
+
+impl TemplateChecker<'_> {
+    /// Check that template strings in a body value compile.
+    fn check_body(&self, value: &Value, path: String, issues: &mut Vec<ValidationIssue>) {
+        match value {
+            Value::String(s) => self.check(s, path, issues),
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.check_body(item, format!("{}[{}]", path, i), issues);
+                }
+            }
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    self.check_body(field, format!("{}.{}", path, key), issues);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check(&self, template: &str, path: String, issues: &mut Vec<ValidationIssue>) {
+        if !self.engine.is_template(template) && !has_partials(template) {
+            return;
+        }
+        let compiled = inline_partials(template, self.partials)
+            .and_then(|source| self.engine.compile(&source));
+        if let Err(message) = compiled {
+            issues.push(ValidationIssue::warning(
+                path,
+                format!("Invalid template '{}': {}", template, message),
+            ));
+        }
+    }
+}
+
+/// Check collection inheritance chains and route references.
+fn validate_collections(
+    manager: &MocksManager,
+    source: &impl Fn(DefinitionKind, &str) -> Option<String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    for collection in manager.list_collections() {
+        let collection_path = format!("collections.{}", collection.id);
+        let start = issues.len();
+
+        // Only the direct parent is checked here: missing ancestors are reported
+        // on the collection that references them.
+        if let Some(parent_id) = &collection.from {
+            match manager.collection_tree(&collection.id) {
+                Err(ResolveError::CollectionNotFound { collection_id })
+                    if &collection_id == parent_id =>
+                {
+                    issues.push(ValidationIssue::error(
+                        format!("{}.from", collection_path),
+                        format!("Parent collection not found: {}", parent_id),
+                    ));
+                }
+                Err(e @ ResolveError::CircularDependency { .. }) => {
+                    issues.push(ValidationIssue::error(
+                        format!("{}.from", collection_path),
+                        e.to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let mut route_ids = HashSet::new();
+        for (i, route_ref) in collection.routes.iter().enumerate() {
+            let ref_path = format!("{}.routes[{}]", collection_path, i);
+
+            match manager.resolve_route_reference(route_ref) {
+                Ok(active_route) => {
+                    if !route_ids.insert(active_route.route.id.clone()) {
+                        issues.push(ValidationIssue::warning(
+                            ref_path,
+                            format!(
+                                "Route '{}' is referenced multiple times, the last reference wins",
+                                active_route.route.id
+                            ),
+                        ));
+                    }
+                }
+                Err(e) => issues.push(ValidationIssue::error(ref_path, e.to_string())),
+            }
+        }
+
+        attach_file(
+            &mut issues[start..],
+            source(DefinitionKind::Collection, &collection.id),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::collection::Collection;
+    use crate::types::graphql::GraphqlOperation;
+    use crate::types::preset::Preset;
+    #[cfg(feature = "handlebars")]
+    use crate::types::preset::{PayloadOrExpression, QueryOrExpression};
+    use crate::types::route::{HttpMethod, Route, Transport};
+    use crate::types::tcp::TcpMatch;
+    use crate::types::variant::Variant;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn create_test_route(id: &str, presets: &[(&str, &[&str])]) -> Route {
+        Route {
+            id: id.into(),
+            url: format!("/api/{}", id),
+            transport: Transport::Http,
+            method: Some(HttpMethod::Get),
+            presets: presets
+                .iter()
+                .map(|(preset_id, variant_ids)| Preset {
+                    id: (*preset_id).into(),
+                    params: None,
+                    query: None,
+                    headers: None,
+                    payload: None,
+                    variants: variant_ids
+                        .iter()
+                        .map(|variant_id| Variant {
+                            id: (*variant_id).into(),
+                            status: Some(200),
+                            headers: None,
+                            body: None,
+                            template: None,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
+        }
+    }
+
+    fn create_test_collection(id: &str, from: Option<&str>, routes: &[&str]) -> Collection {
+        Collection {
+            id: id.into(),
+            from: from.map(str::to_string),
+            vars: None,
+            routes: routes.iter().map(|r| (*r).into()).collect(),
+        }
+    }
+
+    #[rstest]
+    fn test_validate_valid_data() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_route(create_test_route("route1", &[("preset1", &["variant1"])]))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection(
+                "base",
+                None,
+                &["route1:preset1:variant1"],
+            ))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection("child", Some("base"), &[]))
+            .unwrap();
+
+        assert!(manager.validate().is_empty());
+    }
+
+    #[rstest]
+    #[case("missing:preset1:variant1", "Route not found")]
+    #[case("route1:missing:variant1", "Preset 'missing' not found")]
+    #[case("route1:preset1:missing", "Variant 'missing' not found")]
+    #[case("route1::variant1", "Invalid route reference")]
+    fn test_validate_broken_references(#[case] reference: &str, #[case] message: &str) {
+        let mut manager = MocksManager::new();
+        manager
+            .add_route(create_test_route("route1", &[("preset1", &["variant1"])]))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection("base", None, &[reference]))
+            .unwrap();
+
+        let issues = manager.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].path, "collections.base.routes[0]");
+        assert!(issues[0].message.contains(message));
+    }
+
+    #[rstest]
+    fn test_validate_inheritance() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_collection(create_test_collection("orphan", Some("missing"), &[]))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection("A", Some("B"), &[]))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection("B", Some("A"), &[]))
+            .unwrap();
+
+        let issues = manager.validate();
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "collections.A.from",
+                "collections.B.from",
+                "collections.orphan.from"
+            ]
+        );
+        assert!(issues.iter().all(|i| i.severity == Severity::Error));
+        assert!(issues[0].message.contains("Circular dependency"));
+        assert!(issues[0].message.contains("A -> B -> A"));
+        assert!(issues[2].message.contains("missing"));
+    }
+
+    #[rstest]
+    fn test_validate_route_integrity() {
+        let mut manager = MocksManager::new();
+        manager.add_route(create_test_route("empty", &[])).unwrap();
+        manager
+            .add_route(create_test_route(
+                "dupes",
+                &[("p", &["v", "v"]), ("p", &[]), ("q", &["v"])],
+            ))
+            .unwrap();
+
+        let issues = manager.validate();
+        let summary: Vec<(Severity, &str)> = issues
+            .iter()
+            .map(|i| (i.severity, i.path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, "routes.dupes.presets[0].variants[1]"),
+                (Severity::Error, "routes.dupes.presets[1]"),
+                (Severity::Warning, "routes.dupes.presets[1]"),
+                (Severity::Warning, "routes.empty"),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_validate_route_group() {
+        let mut manager = MocksManager::new();
+        let mut route = create_test_route("users", &[("p", &["v"])]);
+        route.group = Some("missing".to_string());
+        manager.add_route(route).unwrap();
+
+        let issues = manager.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].path, "routes.users.group");
+    }
+
+    #[rstest]
+    fn test_validate_duplicate_route_in_collection() {
+        let mut manager = MocksManager::new();
+        manager
+            .add_route(create_test_route("route1", &[("p", &["a", "b"])]))
+            .unwrap();
+        manager
+            .add_collection(create_test_collection(
+                "base",
+                None,
+                &["route1:p:a", "route1:p:b"],
+            ))
+            .unwrap();
+
+        let issues = manager.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].path, "collections.base.routes[1]");
+    }
+
+    #[cfg(feature = "handlebars")]
+    #[rstest]
+    fn test_validate_expressions_and_templates() {
+        let mut route = create_test_route("users", &[("p", &["v", "w"])]);
+        let preset = &mut route.presets[0];
+        preset.query = Some(QueryOrExpression::Expression("query.page ==".to_string()));
+        preset.payload = Some(PayloadOrExpression::Expression(
+            "payload.id == `5`".to_string(),
+        ));
+        preset.variants[0].headers = Some(HashMap::from([(
+            "x-id".into(),
+            "{{#if params.id}}".to_string(),
+        )]));
+        preset.variants[0].body = Some(json!({"items": ["{{params.id}}", "{{/each}}"]}));
+        preset.variants[1].template = Some(Engine::Interpolation);
+        preset.variants[1].body = Some(json!("${cel: size(}"));
+        let mut manager = MocksManager::new();
+        manager.add_route(route).unwrap();
+
+        let issues = validate_with(&manager, Engine::Handlebars, &HashMap::new(), |kind, id| {
+            Some(format!("{}s/{}.yaml", kind, id))
+        });
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "routes.users.presets[0].query",
+                "routes.users.presets[0].variants[0].headers.x-id",
+                "routes.users.presets[0].variants[0].body.items[1]",
+                "routes.users.presets[0].variants[1].body",
+            ]
+        );
+        assert!(issues.iter().all(|i| i.severity == Severity::Warning));
+        assert!(issues[0]
+            .message
+            .starts_with("Invalid expression 'query.page =='"));
+        assert!(issues[1]
+            .message
+            .starts_with("Invalid template '{{#if params.id}}'"));
+        assert_eq!(issues[0].file.as_deref(), Some("routes/users.yaml"));
+
+        let issues = manager.validate();
+        assert_eq!(issues.len(), 4);
+        assert_eq!(issues[0].file, None);
+    }
+
+    #[rstest]
+    #[case(json!({"data": {"user": null}}), vec![])]
+    #[case(json!({"data": null, "errors": [{"message": "Not found", "path": ["user"]}]}), vec![])]
+    #[case(json!({"user": {"id": 1}}), vec!["body.user", "body"])]
+    #[case(json!({"errors": []}), vec!["body.errors"])]
+    #[case(json!({"errors": [{"message": "a"}, {"code": 1}]}), vec!["body.errors[1]"])]
+    #[case(json!("file:./user.json"), vec![])]
+    fn test_validate_graphql_response(#[case] body: Value, #[case] paths: Vec<&str>) {
+        let mut route = create_test_route("user", &[("p", &["v"])]);
+        route.graphql = Some(GraphqlOperation::default());
+        route.presets[0].variants[0].body = Some(body);
+        let mut manager = MocksManager::new();
+        manager.add_route(route).unwrap();
+
+        let issues = manager.validate();
+        let paths: Vec<String> = paths
+            .into_iter()
+            .map(|path| format!("routes.user.presets[0].variants[0].{}", path))
+            .collect();
+        assert_eq!(
+            issues.iter().map(|i| &i.path).collect::<Vec<_>>(),
+            paths.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[cfg_attr(feature = "handlebars", case(json!({"retry": 1000, "events": [{"id": "1", "data": "{{params.id}}"}]}), 0))]
+    #[case(json!({"events": [{"data": "a", "repeat": true}]}), 1)]
+    #[case(json!("event: a"), 1)]
+    fn test_validate_sse_scripts(#[case] body: Value, #[case] warnings: usize) {
+        let mut route = create_test_route("events", &[("p", &["v"])]);
+        route.transport = Transport::Sse;
+        route.method = None;
+        route.presets[0].variants[0].body = Some(body);
+        let mut manager = MocksManager::new();
+        manager.add_route(route).unwrap();
+
+        let issues = manager.validate();
+        assert_eq!(issues.len(), warnings);
+        assert!(issues
+            .iter()
+            .all(|i| i.path == "routes.events.presets[0].variants[0].body"));
+    }
+
+    #[rstest]
+    #[case(Some(r"^MAIL FROM:<\S+>"), json!("250 OK\r\n"), vec![])]
+    #[case(Some("(?-u)^\\x00"), json!({"replies": [{"data": "data:;base64,AP8="}], "close": true}), vec![])]
+    #[case(Some("("), json!("250 OK\r\n"), vec!["tcp.pattern"])]
+    #[case(None, json!({"replies": ["250 OK"]}), vec!["presets[0].variants[0].body"])]
+    fn test_validate_tcp(
+        #[case] pattern: Option<&str>,
+        #[case] body: Value,
+        #[case] paths: Vec<&str>,
+    ) {
+        let mut route = create_test_route("smtp", &[("p", &["v"])]);
+        route.transport = Transport::Tcp;
+        route.method = None;
+        route.tcp = Some(TcpMatch {
+            prefix: None,
+            pattern: pattern.map(str::to_string),
+        });
+        route.presets[0].variants[0].body = Some(body);
+        let mut manager = MocksManager::new();
+        manager.add_route(route).unwrap();
+
+        let issues = manager.validate();
+        assert_eq!(
+            issues.iter().map(|i| i.path.as_str()).collect::<Vec<_>>(),
+            paths
+                .into_iter()
+                .map(|path| format!("routes.smtp.{}", path))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[cfg_attr(feature = "liquid", case(&[("name", "{{ payload.name }}")], 0))]
+    #[case(&[("name", "{% if payload.name %}")], 1)]
+    #[case(&[], 1)]
+    fn test_validate_partials(#[case] partials: &[(&str, &str)], #[case] warnings: usize) {
+        let mut route = create_test_route("users", &[("p", &["v"])]);
+        route.presets[0].variants[0].body = Some(json!({"name": "{{> name}}"}));
+        let mut manager = MocksManager::new();
+        manager.add_route(route).unwrap();
+
+        let partials = partials
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect();
+        let issues = validate_with(&manager, Engine::Liquid, &partials, |_, _| None);
+        assert_eq!(issues.len(), warnings);
+    }
+
+    #[rstest]
+    fn test_validation_issue_display() {
+        let mut issue = ValidationIssue::error(
+            "collections.base.routes[0]".to_string(),
+            "Route not found: x".to_string(),
+        );
+        assert_eq!(
+            issue.to_string(),
+            "[error] collections.base.routes[0]: Route not found: x"
+        );
+
+        issue.file = Some("collections/base.yaml".to_string());
+        assert_eq!(
+            issue.to_string(),
+            "[error] collections/base.yaml: collections.base.routes[0]: Route not found: x"
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Assertions about requests recorded in a request history.
+//!
+//! [`Verification`] checks how often a route was requested, optionally counting
+//! only requests with given headers, query parameters or payload. Conditions
+//! match like preset conditions: headers and query parameters by intersection,
+//! object payloads by intersection and other payloads by equality.
+//!
+//! Failed checks return a [`VerificationError`] listing the requests of the route
+//! that didn't satisfy the conditions, with expected and actual values, so test
+//! failures show what the mock received instead.
+
+use crate::matching::{hashmap_intersects, headers_intersects, object_intersects};
+use crate::mocks::context::RequestContext;
+use crate::mocks::history::RecordedRequest;
+use crate::mocks::table::{MatchFailure, MatchStep};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Expected number of requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Times {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
+
+impl Times {
+    /// Whether `count` requests satisfy the expectation.
+    pub fn contains(&self, count: usize) -> bool {
+        match *self {
+            Self::Exactly(expected) => count == expected,
+            Self::AtLeast(expected) => count >= expected,
+            Self::AtMost(expected) => count <= expected,
+        }
+    }
+}
+
+impl fmt::Display for Times {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, count) = match *self {
+            Self::Exactly(count) => ("exactly", count),
+            Self::AtLeast(count) => ("at least", count),
+            Self::AtMost(count) => ("at most", count),
+        };
+        write!(f, "{} {}", prefix, plural(count, "time"))
+    }
+}
+
+/// Recorded request of a route that doesn't satisfy a condition of a [`Verification`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestMismatch {
+    /// Recorded request
+    pub request: RecordedRequest,
+    /// First unsatisfied condition
+    pub failure: MatchFailure,
+}
+
+/// Failed verification of recorded requests.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationError {
+    /// Route was requested an unexpected number of times
+    CountMismatch {
+        route_id: String,
+        expected: Times,
+        actual: usize,
+        /// Requests of the route not counted because they failed a condition
+        mismatches: Vec<RequestMismatch>,
+    },
+    /// Requests matched no route
+    UnmatchedRequests { requests: Vec<RecordedRequest> },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::CountMismatch {
+                route_id,
+                expected,
+                actual,
+                mismatches,
+            } => {
+                write!(
+                    f,
+                    "Expected route '{}' to be requested {}, but it was requested {}",
+                    route_id,
+                    expected,
+                    plural(*actual, "time")
+                )?;
+                if !mismatches.is_empty() {
+                    write!(
+                        f,
+                        "\n{} of the route didn't match:",
+                        plural(mismatches.len(), "other request")
+                    )?;
+                }
+                for mismatch in mismatches {
+                    write!(
+                        f,
+                        "\n  {}: {} expected {}, got {}",
+                        describe(&mismatch.request),
+                        mismatch.failure.step,
+                        mismatch.failure.expected,
+                        mismatch.failure.actual
+                    )?;
+                }
+                Ok(())
+            }
+            VerificationError::UnmatchedRequests { requests } => {
+                write!(
+                    f,
+                    "Expected no unmatched requests, but {} matched no route:",
+                    plural(requests.len(), "request")
+                )?;
+                for request in requests {
+                    write!(f, "\n  {}", describe(request))?;
+                    if let Some(nearest) = request.near_matches.first() {
+                        if let Some(failure) = &nearest.failure {
+                            write!(
+                                f,
+                                " (closest route '{}': {} expected {}, got {})",
+                                nearest.route_id, failure.step, failure.expected, failure.actual
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Check of the requests recorded for a route.
+///
+/// Created by [`MocksController::verify`](crate::mocks::controller::MocksController::verify)
+/// from the requests recorded at that time.
+///
+/// # Example
+/// ```ignore
+/// controller
+///     .verify("users")
+///     .with_query(HashMap::from([("page".to_string(), "2".to_string())]))
+///     .times(1)?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verification {
+    route_id: String,
+    /// Recorded requests that matched the route
+    requests: Vec<RecordedRequest>,
+    headers: Option<HashMap<String, String>>,
+    query: Option<HashMap<String, String>>,
+    payload: Option<Value>,
+}
+
+impl Verification {
+    /// Create a verification of the recorded requests that matched a route.
+    pub fn new(route_id: impl Into<String>, requests: Vec<RecordedRequest>) -> Self {
+        Self {
+            route_id: route_id.into(),
+            requests,
+            headers: None,
+            query: None,
+            payload: None,
+        }
+    }
+
+    /// Only count requests with the given headers, compared case-insensitively by name.
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Only count requests with the given query parameters.
+    pub fn with_query(mut self, query: HashMap<String, String>) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    /// Only count requests with the given payload, a subset of it for objects.
+    pub fn with_payload(mut self, payload: Value) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Get the number of requests satisfying the conditions.
+    pub fn count(&self) -> usize {
+        self.requests
+            .iter()
+            .filter(|recorded| self.mismatch(recorded).is_none())
+            .count()
+    }
+
+    /// Check that the route was requested exactly `count` times.
+    pub fn times(&self, count: usize) -> Result<(), VerificationError> {
+        self.check(Times::Exactly(count))
+    }
+
+    /// Check that the route was requested at least `count` times.
+    pub fn at_least(&self, count: usize) -> Result<(), VerificationError> {
+        self.check(Times::AtLeast(count))
+    }
+
+    /// Check that the route was requested at most `count` times.
+    pub fn at_most(&self, count: usize) -> Result<(), VerificationError> {
+        self.check(Times::AtMost(count))
+    }
+
+    /// Check that the route was requested exactly once.
+    pub fn once(&self) -> Result<(), VerificationError> {
+        self.times(1)
+    }
+
+    /// Check that the route was never requested.
+    pub fn never(&self) -> Result<(), VerificationError> {
+        self.times(0)
+    }
+
+    /// Check that the number of requests satisfying the conditions is `expected`.
+    pub fn check(&self, expected: Times) -> Result<(), VerificationError> {
+        let mut actual = 0;
+        let mut mismatches = Vec::new();
+        for recorded in &self.requests {
+            match self.mismatch(recorded) {
+                Some(failure) => mismatches.push(RequestMismatch {
+                    request: recorded.clone(),
+                    failure,
+                }),
+                None => actual += 1,
+            }
+        }
+        if expected.contains(actual) {
+            return Ok(());
+        }
+        Err(VerificationError::CountMismatch {
+            route_id: self.route_id.clone(),
+            expected,
+            actual,
+            mismatches,
+        })
+    }
+
+    /// Get the first condition a recorded request doesn't satisfy.
+    fn mismatch(&self, recorded: &RecordedRequest) -> Option<MatchFailure> {
+        let request = &recorded.request;
+        let failure = |step, expected: Value, actual: Value| MatchFailure {
+            step,
+            expected,
+            actual,
+            error: None,
+        };
+
+        if let Some(headers) = &self.headers {
+            if !headers_intersects(request.headers.as_ref(), Some(headers)) {
+                return Some(failure(
+                    MatchStep::Headers,
+                    to_value(headers),
+                    request.headers.as_ref().map(to_value).unwrap_or_default(),
+                ));
+            }
+        }
+        if let Some(query) = &self.query {
+            let context = RequestContext::new(request);
+            if !hashmap_intersects(Some(query), Some(context.query())) {
+                return Some(failure(
+                    MatchStep::Query,
+                    to_value(query),
+                    to_value(context.query()),
+                ));
+            }
+        }
+        if let Some(payload) = &self.payload {
+            let matches = match &request.payload {
+                Some(actual) if payload.is_object() && actual.is_object() => {
+                    object_intersects(Some(actual), Some(payload))
+                }
+                Some(actual) => actual == payload,
+                None => false,
+            };
+            if !matches {
+                return Some(failure(
+                    MatchStep::Payload,
+                    payload.clone(),
+                    request.payload.clone().unwrap_or_default(),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Check that no recorded request matched no route.
+pub fn verify_no_unmatched(requests: &[RecordedRequest]) -> Result<(), VerificationError> {
+    let unmatched: Vec<_> = requests
+        .iter()
+        .filter(|recorded| !recorded.is_matched())
+        .cloned()
+        .collect();
+    if unmatched.is_empty() {
+        return Ok(());
+    }
+    Err(VerificationError::UnmatchedRequests {
+        requests: unmatched,
+    })
+}
+
+fn to_value(map: &HashMap<String, String>) -> Value {
+    serde_json::to_value(map).unwrap_or_default()
+}
+
+/// Describe a recorded request by method and URL.
+fn describe(recorded: &RecordedRequest) -> String {
+    match &recorded.request.method {
+        Some(method) => format!("{} {}", method.as_str(), recorded.request.url),
+        None => recorded.request.url.clone(),
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::controller::Request;
+    use crate::mocks::table::RouteExplanation;
+    use crate::types::route::{HttpMethod, Transport};
+    use rstest::rstest;
+    use serde_json::json;
+    use std::time::{Duration, SystemTime};
+
+    fn recorded(url: &str, payload: Option<Value>) -> RecordedRequest {
+        RecordedRequest {
+            timestamp: SystemTime::now(),
+            request: Request {
+                url: url.to_string(),
+                method: Some(HttpMethod::Post),
+                transport: Transport::Http,
+                headers: Some(HashMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer token".to_string(),
+                )])),
+                query: None,
+                payload,
+            },
+            route_id: Some("users".to_string()),
+            preset_id: Some("default".to_string()),
+            variant_id: Some("ok".to_string()),
+            latency: Duration::ZERO,
+            delay: Duration::ZERO,
+            near_matches: Vec::new(),
+            response: None,
+        }
+    }
+
+    fn verification() -> Verification {
+        Verification::new(
+            "users",
+            vec![
+                recorded("/api/users?page=1", Some(json!({"name": "Ann", "age": 30}))),
+                recorded("/api/users?page=2", Some(json!({"name": "Bob"}))),
+                recorded("/api/users?page=2", None),
+            ],
+        )
+    }
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(verification(), 3)]
+    #[case(verification().with_query(map(&[("page", "2")])), 2)]
+    #[case(verification().with_headers(map(&[("authorization", "Bearer token")])), 3)]
+    #[case(verification().with_headers(map(&[("authorization", "other")])), 0)]
+    #[case(verification().with_payload(json!({"name": "Ann"})), 1)]
+    #[case(verification().with_query(map(&[("page", "2")])).with_payload(json!({"name": "Bob"})), 1)]
+    fn test_count(#[case] verification: Verification, #[case] expected: usize) {
+        assert_eq!(verification.count(), expected);
+    }
+
+    #[rstest]
+    #[case(Times::Exactly(2), true)]
+    #[case(Times::Exactly(3), false)]
+    #[case(Times::AtLeast(1), true)]
+    #[case(Times::AtLeast(3), false)]
+    #[case(Times::AtMost(2), true)]
+    #[case(Times::AtMost(1), false)]
+    fn test_check(#[case] expected: Times, #[case] ok: bool) {
+        let verification = verification().with_query(map(&[("page", "2")]));
+        assert_eq!(verification.check(expected).is_ok(), ok);
+    }
+
+    #[rstest]
+    fn test_count_mismatch_lists_requests() {
+        let error = verification()
+            .with_query(map(&[("page", "3")]))
+            .once()
+            .unwrap_err();
+
+        let VerificationError::CountMismatch {
+            actual, mismatches, ..
+        } = &error
+        else {
+            panic!("unexpected error: {:?}", error);
+        };
+        assert_eq!(*actual, 0);
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[0].failure.step, MatchStep::Query);
+        assert_eq!(mismatches[0].failure.expected, json!({"page": "3"}));
+        assert_eq!(mismatches[0].failure.actual, json!({"page": "1"}));
+        assert_eq!(
+            error.to_string().lines().collect::<Vec<_>>()[..3],
+            [
+                "Expected route 'users' to be requested exactly 1 time, but it was requested 0 times",
+                "3 other requests of the route didn't match:",
+                r#"  POST /api/users?page=1: query expected {"page":"3"}, got {"page":"1"}"#,
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_verify_no_unmatched() {
+        let mut unmatched = recorded("/api/missing", None);
+        unmatched.route_id = None;
+        let mut near = recorded("/api/users?page=2", None);
+        near.route_id = None;
+        near.near_matches = vec![RouteExplanation {
+            route_id: "users".to_string(),
+            preset_id: "default".to_string(),
+            failure: Some(MatchFailure {
+                step: MatchStep::Query,
+                expected: json!({"page": "1"}),
+                actual: json!({"page": "2"}),
+                error: None,
+            }),
+        }];
+
+        assert!(verify_no_unmatched(&[recorded("/api/users", None)]).is_ok());
+        let error = verify_no_unmatched(&[recorded("/api/users", None), unmatched]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected no unmatched requests, but 1 request matched no route:\n  POST /api/missing"
+        );
+        let error = verify_no_unmatched(&[near]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected no unmatched requests, but 1 request matched no route:\n  \
+             POST /api/users?page=2 (closest route 'users': query expected {\"page\":\"1\"}, got {\"page\":\"2\"})"
+        );
+    }
+}
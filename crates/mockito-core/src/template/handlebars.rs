@@ -0,0 +1,132 @@
+//! Handlebars template engine.
+
+#[cfg(feature = "jwt")]
+use super::jwt;
+use super::TemplateEngine;
+use ::handlebars::{handlebars_helper, no_escape, Handlebars, Template};
+#[cfg(feature = "jwt")]
+use ::handlebars::{Context, Helper, HelperResult, Output, RenderContext, RenderErrorReason};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+handlebars_helper!(json: |value: Json| value.to_string());
+
+/// `{{jwt sign ...}}` and `{{jwt jwks ...}}`, see [`jwt`].
+///
+/// The command is a bare word or a string.
+#[cfg(feature = "jwt")]
+fn jwt_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let command = h.param(0).and_then(|param| match param.value() {
+        Value::String(command) => Some(command.as_str()),
+        _ => param.relative_path().map(String::as_str),
+    });
+    let args = h
+        .hash()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.value().clone()))
+        .collect();
+    let rendered =
+        jwt::run_helper(command.unwrap_or_default(), args).map_err(RenderErrorReason::Other)?;
+    out.write(&rendered)?;
+    Ok(())
+}
+
+/// Handlebars templates (`{{payload.name}}`, `{{#each}}`, ...).
+///
+/// Output isn't HTML-escaped. Besides the built-in helpers (`if`, `each`, `with`,
+/// `lookup`, ...), `json` writes a value as JSON and `jwt` mints signed tokens
+/// (see [`jwt`](super::jwt), needs the `jwt` feature).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HandlebarsEngine;
+
+/// Get the shared Handlebars registry.
+fn registry() -> &'static Handlebars<'static> {
+    static REGISTRY: OnceLock<Handlebars<'static>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(no_escape);
+        registry.register_helper("json", Box::new(json));
+        #[cfg(feature = "jwt")]
+        registry.register_helper("jwt", Box::new(jwt_helper));
+        registry
+    })
+}
+
+impl TemplateEngine for HandlebarsEngine {
+    fn is_template(&self, s: &str) -> bool {
+        s.contains("{{")
+    }
+
+    fn render(&self, template: &str, context: &Value) -> Result<String, String> {
+        registry()
+            .render_template(template, context)
+            .map_err(|e| e.to_string())
+    }
+
+    fn compile(&self, template: &str) -> Result<(), String> {
+        Template::compile(template)
+            .map(drop)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("Hello {{payload.name}}{{#if query.excited}}!{{/if}}", "Hello <Ann>!")]
+    #[case("{{lookup headers 'x-request-id'}}", "abc")]
+    #[case("{{#each payload.ids}}{{@index}}:{{this}} {{/each}}", "0:1 1:2 ")]
+    #[case("{{json payload.ids}}", "[1,2]")]
+    #[case("{{payload.missing}}", "")]
+    fn test_render(#[case] template: &str, #[case] expected: &str) {
+        let context = json!({
+            "query": {"excited": "true"},
+            "headers": {"x-request-id": "abc"},
+            "payload": {"name": "<Ann>", "ids": [1, 2]}
+        });
+        assert_eq!(
+            HandlebarsEngine.render(template, &context).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_render_jwt() {
+        let context = json!({"params": {"id": "42"}});
+        let token = HandlebarsEngine
+            .render(
+                "{{jwt sign sub=params.id alg='HS256' key='secret'}}",
+                &context,
+            )
+            .unwrap();
+        assert_eq!(token.split('.').count(), 3);
+
+        let jwks = HandlebarsEngine
+            .render("{{jwt \"jwks\"}}", &context)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&jwks).unwrap(),
+            jwt::development_jwks()
+        );
+        assert!(HandlebarsEngine.render("{{jwt}}", &context).is_err());
+    }
+
+    #[rstest]
+    fn test_render_invalid() {
+        assert!(HandlebarsEngine
+            .render("{{#each payload.ids}}", &json!({}))
+            .is_err());
+        assert!(HandlebarsEngine.compile("{{#each payload.ids}}").is_err());
+        assert!(HandlebarsEngine.compile("{{json payload}}").is_ok());
+    }
+}
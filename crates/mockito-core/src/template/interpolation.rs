@@ -0,0 +1,102 @@
+//! Expression interpolation template engine.
+
+use super::TemplateEngine;
+use crate::expression::{self, evaluate_expression};
+use serde_json::Value;
+
+/// Replaces `${expression}` placeholders with expression results.
+///
+/// Placeholders use any expression engine (`${payload.name}`,
+/// `${cel: size(payload.items)}`). Strings are inserted as is, other values
+/// as JSON, `null` as an empty string. Expressions end at the first `}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterpolationEngine;
+
+/// Part of an interpolation template.
+enum Part<'a> {
+    /// Text copied as is
+    Text(&'a str),
+    /// Expression of a `${...}` placeholder
+    Placeholder(&'a str),
+}
+
+/// Split a template into text and placeholders.
+fn parse(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        parts.push(Part::Text(&rest[..start]));
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder at '{}'", &rest[start..]))?;
+        parts.push(Part::Placeholder(&after[..end]));
+        rest = &after[end + 1..];
+    }
+    parts.push(Part::Text(rest));
+    Ok(parts)
+}
+
+impl TemplateEngine for InterpolationEngine {
+    fn is_template(&self, s: &str) -> bool {
+        s.contains("${")
+    }
+
+    fn render(&self, template: &str, context: &Value) -> Result<String, String> {
+        let mut output = String::with_capacity(template.len());
+        for part in parse(template)? {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Placeholder(expression) => {
+                    match evaluate_expression(expression, context).map_err(|e| e.to_string())? {
+                        Value::Null => {}
+                        Value::String(s) => output.push_str(&s),
+                        other => output.push_str(&other.to_string()),
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn compile(&self, template: &str) -> Result<(), String> {
+        parse(template)?
+            .into_iter()
+            .try_for_each(|part| match part {
+                Part::Text(_) => Ok(()),
+                Part::Placeholder(expression) => expression::compile(expression)
+                    .map(drop)
+                    .map_err(|e| e.to_string()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("Hello ${payload.name}!", Ok("Hello Ann!"))]
+    #[case("${payload.ids}", Ok("[1,2]"))]
+    #[case("${cel: size(payload.ids)} ids", Ok("2 ids"))]
+    #[case("${payload.missing}", Ok(""))]
+    #[case("no placeholders", Ok("no placeholders"))]
+    #[case("${payload.name", Err(()))]
+    #[case("${payload.[}", Err(()))]
+    fn test_render(#[case] template: &str, #[case] expected: Result<&str, ()>) {
+        let context = json!({"payload": {"name": "Ann", "ids": [1, 2]}});
+        let rendered = InterpolationEngine.render(template, &context);
+        assert_eq!(rendered.as_deref().map_err(|_| ()), expected);
+    }
+
+    #[rstest]
+    #[case("Hello ${payload.name}!", true)]
+    #[case("${cel: size(payload.ids)} ids", true)]
+    #[case("${payload.name", false)]
+    #[case("${payload.[}", false)]
+    fn test_compile(#[case] template: &str, #[case] valid: bool) {
+        assert_eq!(InterpolationEngine.compile(template).is_ok(), valid);
+    }
+}
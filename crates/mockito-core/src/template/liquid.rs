@@ -0,0 +1,77 @@
+//! Liquid template engine.
+
+use super::TemplateEngine;
+use ::liquid::{Parser, ParserBuilder};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Liquid templates (`{{ payload.name }}`, `{% for %}`, filters) with the
+/// standard library of tags and filters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LiquidEngine;
+
+/// Get the shared Liquid parser.
+fn parser() -> &'static Parser {
+    static PARSER: OnceLock<Parser> = OnceLock::new();
+    PARSER.get_or_init(|| {
+        ParserBuilder::with_stdlib()
+            .build()
+            .expect("Liquid standard library is valid")
+    })
+}
+
+impl TemplateEngine for LiquidEngine {
+    fn is_template(&self, s: &str) -> bool {
+        s.contains("{{") || s.contains("{%")
+    }
+
+    fn render(&self, template: &str, context: &Value) -> Result<String, String> {
+        let globals = ::liquid::model::to_object(context).map_err(|e| e.to_string())?;
+        parser()
+            .parse(template)
+            .and_then(|template| template.render(&globals))
+            .map_err(|e| e.to_string())
+    }
+
+    fn compile(&self, template: &str) -> Result<(), String> {
+        parser()
+            .parse(template)
+            .map(drop)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("Hello {{ payload.name | upcase }}", "Hello ANN")]
+    #[case("{% for id in payload.ids %}{{ id }};{% endfor %}", "1;2;")]
+    #[case("{% if query.page == '2' %}second{% else %}other{% endif %}", "second")]
+    #[case("{{ payload.ids | size }}", "2")]
+    fn test_render(#[case] template: &str, #[case] expected: &str) {
+        let context = json!({
+            "query": {"page": "2"},
+            "payload": {"name": "Ann", "ids": [1, 2]}
+        });
+        assert_eq!(LiquidEngine.render(template, &context).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("{% for id in payload.ids %}")]
+    #[case("{{ payload.missing }}")]
+    fn test_render_invalid(#[case] template: &str) {
+        let context = json!({"payload": {}});
+        assert!(LiquidEngine.render(template, &context).is_err());
+    }
+
+    #[rstest]
+    #[case("{% for id in payload.ids %}", false)]
+    #[case("{{ payload.missing }}", true)]
+    fn test_compile(#[case] template: &str, #[case] valid: bool) {
+        assert_eq!(LiquidEngine.compile(template).is_ok(), valid);
+    }
+}
@@ -0,0 +1,506 @@
+//! Response templating.
+//!
+//! Strings in variant bodies and header values are rendered against the request
+//! context (see [`crate::mocks::context`]) when a response is served. The engine
+//! is selected per variant, falling back to the `template` setting of the
+//! project and then to Handlebars:
+//!
+//! ```yaml
+//! variants:
+//!   - id: echo
+//!     template: liquid
+//!     headers:
+//!       x-request-id: "{{ headers['x-request-id'] }}"
+//!     body:
+//!       greeting: "Hello {{ payload.name }}"
+//!       items: "[{% for id in payload.ids %}{% unless forloop.first %},{% endunless %}{\"id\": {{ id }}}{% endfor %}]"
+//! ```
+//!
+//! Only strings the engine recognizes as templates or including partials (see
+//! [`partials`]) are rendered. A body string rendering to a JSON array or object
+//! is replaced by the parsed value, so list-shaped responses can be built by
+//! iterating request arrays.
+//!
+//! Handlebars templates can mint signed JWTs and render the matching key set
+//! with the `jwt` helper, see [`jwt`].
+//!
+//! Built-in engines implement [`TemplateEngine`], other implementations can be
+//! used with [`render_variant_with`].
+//!
+//! Handlebars, Liquid and the `jwt` helper are behind the `handlebars`, `liquid`
+//! and `jwt` features. Without them, templates of the engine fail to render.
+
+#[cfg(feature = "handlebars")]
+mod handlebars;
+mod interpolation;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+#[cfg(feature = "liquid")]
+mod liquid;
+pub mod partials;
+
+#[cfg(feature = "handlebars")]
+pub use self::handlebars::HandlebarsEngine;
+pub use self::interpolation::InterpolationEngine;
+#[cfg(feature = "liquid")]
+pub use self::liquid::LiquidEngine;
+pub use self::partials::{has_partials, inline_partials};
+
+use crate::clock::Instant;
+use crate::expression::EvaluationLimits;
+use crate::types::variant::Variant;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::Cell;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Template rendering failure.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Failed to render template '{template}': {message}")]
+pub struct TemplateError {
+    /// Template source
+    pub template: String,
+    /// Parse or render error
+    pub message: String,
+}
+
+/// Templating backend rendering strings against a request context.
+pub trait TemplateEngine: Send + Sync {
+    /// Check if a string holds template syntax of the engine.
+    fn is_template(&self, s: &str) -> bool;
+
+    /// Render a template against a request context.
+    fn render(&self, template: &str, context: &Value) -> Result<String, String>;
+
+    /// Check the syntax of a template without rendering it.
+    ///
+    /// Accepts any template unless the engine overrides it.
+    fn compile(&self, _template: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Built-in template engine, selected by name in config files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    /// Handlebars, see [`HandlebarsEngine`]
+    #[default]
+    Handlebars,
+    /// Liquid, see [`LiquidEngine`]
+    Liquid,
+    /// `${expression}` placeholders, see [`InterpolationEngine`]
+    Interpolation,
+}
+
+impl Engine {
+    /// Get the engine implementation.
+    pub fn template_engine(self) -> &'static dyn TemplateEngine {
+        match self {
+            Engine::Handlebars => HANDLEBARS,
+            Engine::Liquid => LIQUID,
+            Engine::Interpolation => &InterpolationEngine,
+        }
+    }
+}
+
+#[cfg(feature = "handlebars")]
+const HANDLEBARS: &dyn TemplateEngine = &HandlebarsEngine;
+#[cfg(not(feature = "handlebars"))]
+const HANDLEBARS: &dyn TemplateEngine = &UnavailableEngine("handlebars");
+
+#[cfg(feature = "liquid")]
+const LIQUID: &dyn TemplateEngine = &LiquidEngine;
+#[cfg(not(feature = "liquid"))]
+const LIQUID: &dyn TemplateEngine = &UnavailableEngine("liquid");
+
+/// Stand-in for a built-in engine whose feature is disabled.
+///
+/// Recognizes `{{` and `{%` as template syntax, so templates fail to render
+/// instead of being served verbatim.
+#[cfg(not(all(feature = "handlebars", feature = "liquid")))]
+struct UnavailableEngine(&'static str);
+
+#[cfg(not(all(feature = "handlebars", feature = "liquid")))]
+impl TemplateEngine for UnavailableEngine {
+    fn is_template(&self, s: &str) -> bool {
+        s.contains("{{") || s.contains("{%")
+    }
+
+    fn render(&self, template: &str, _context: &Value) -> Result<String, String> {
+        self.compile(template).map(|()| String::new())
+    }
+
+    fn compile(&self, _template: &str) -> Result<(), String> {
+        Err(format!("template engine requires the `{}` feature", self.0))
+    }
+}
+
+/// Render a template against a request context with a built-in engine.
+pub fn render(engine: Engine, template: &str, context: &Value) -> Result<String, TemplateError> {
+    engine
+        .template_engine()
+        .render(template, context)
+        .map_err(|message| TemplateError {
+            template: template.to_owned(),
+            message,
+        })
+}
+
+/// Render templates in the headers and body of a variant.
+///
+/// Uses the engine of the variant, or `default` if it has none.
+pub fn render_variant(
+    variant: &Variant,
+    context: &Value,
+    default: Engine,
+    partials: &HashMap<String, String>,
+    limits: &EvaluationLimits,
+) -> Result<Variant, TemplateError> {
+    let engine = variant.template.unwrap_or(default);
+    render_variant_with(variant, context, engine.template_engine(), partials, limits)
+}
+
+/// Render templates in the headers and body of a variant with any engine.
+///
+/// `{{> name}}` includes are replaced by the named `partials` first. The context is checked against the depth and size `limits` before the first
+/// template is rendered. Each rendered string must fit the size limit and the
+/// whole variant the time limit, which is checked after each template so
+/// rendering stops at the first template that ends past the deadline.
+///
+/// Returns the variant unchanged if it holds no templates.
+pub fn render_variant_with(
+    variant: &Variant,
+    context: &Value,
+    engine: &dyn TemplateEngine,
+    partials: &HashMap<String, String>,
+    limits: &EvaluationLimits,
+) -> Result<Variant, TemplateError> {
+    let renderer = Renderer {
+        engine,
+        context,
+        partials,
+        limits,
+        start: Instant::now(),
+        checked: Cell::new(false),
+    };
+    let headers = variant
+        .headers
+        .as_ref()
+        .map(|headers| {
+            headers
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), renderer.render_header(value)?)))
+                .collect::<Result<_, TemplateError>>()
+        })
+        .transpose()?;
+    let body = variant
+        .body
+        .as_ref()
+        .map(|body| renderer.render_value(body))
+        .transpose()?;
+
+    Ok(Variant {
+        headers,
+        body,
+        ..variant.clone()
+    })
+}
+
+/// Renders the templates of one variant within evaluation limits.
+struct Renderer<'a> {
+    engine: &'a dyn TemplateEngine,
+    context: &'a Value,
+    partials: &'a HashMap<String, String>,
+    limits: &'a EvaluationLimits,
+    /// Start of rendering, for the time limit
+    start: Instant,
+    /// Whether the context was checked against the limits
+    checked: Cell<bool>,
+}
+
+impl Renderer<'_> {
+    /// Check if a string needs rendering.
+    fn is_template(&self, s: &str) -> bool {
+        self.engine.is_template(s) || has_partials(s)
+    }
+
+    /// Render a template, checking the context on first use.
+    fn render(&self, template: &str) -> Result<String, TemplateError> {
+        let error = |message| TemplateError {
+            template: template.to_owned(),
+            message,
+        };
+        if !self.checked.replace(true) {
+            self.limits.check_data(self.context).map_err(error)?;
+        }
+        let source = inline_partials(template, self.partials).map_err(error)?;
+        let rendered = self.engine.render(&source, self.context).map_err(error)?;
+        self.limits
+            .check_size(rendered.len(), "rendered template")
+            .and_then(|()| self.limits.check_elapsed(self.start))
+            .map_err(error)?;
+        Ok(rendered)
+    }
+
+    /// Render a header value if it's a template.
+    fn render_header(&self, value: &str) -> Result<String, TemplateError> {
+        if self.is_template(value) {
+            self.render(value)
+        } else {
+            Ok(value.to_owned())
+        }
+    }
+
+    /// Render template strings in a body value.
+    ///
+    /// Strings rendering to a JSON array or object are replaced by the parsed value.
+    fn render_value(&self, value: &Value) -> Result<Value, TemplateError> {
+        match value {
+            Value::String(s) if self.is_template(s) => {
+                let rendered = self.render(s)?;
+                match serde_json::from_str::<Value>(&rendered) {
+                    Ok(parsed @ (Value::Array(_) | Value::Object(_))) => Ok(parsed),
+                    _ => Ok(Value::String(rendered)),
+                }
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.render_value(item))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), self.render_value(value)?)))
+                .collect::<Result<_, _>>()
+                .map(Value::Object),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn context() -> Value {
+        json!({
+            "method": "POST",
+            "params": {"id": "7"},
+            "headers": {"x-request-id": "abc"},
+            "payload": {"ids": [1, 2]}
+        })
+    }
+
+    fn variant(template: Option<Engine>, body: Value) -> Variant {
+        Variant {
+            id: "echo".into(),
+            status: Some(201),
+            headers: None,
+            body: Some(body),
+            template,
+        }
+    }
+
+    #[cfg(feature = "handlebars")]
+    #[rstest]
+    fn test_render_variant() {
+        let mut variant = variant(
+            None,
+            json!({
+                "id": "{{params.id}}",
+                "items": "[{{#each payload.ids}}{{#unless @first}},{{/unless}}{\"id\": {{this}}}{{/each}}]",
+                "count": 2,
+                "tags": ["{{method}}", "static"]
+            }),
+        );
+        variant.headers = Some(HashMap::from([
+            (
+                "x-request-id".into(),
+                "{{lookup headers 'x-request-id'}}".to_string(),
+            ),
+            ("content-type".into(), "application/json".to_string()),
+        ]));
+
+        let rendered = render_variant(
+            &variant,
+            &context(),
+            Engine::Handlebars,
+            &HashMap::new(),
+            &EvaluationLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(rendered.status, Some(201));
+        assert_eq!(
+            rendered.headers,
+            Some(HashMap::from([
+                ("x-request-id".into(), "abc".to_string()),
+                ("content-type".into(), "application/json".to_string()),
+            ]))
+        );
+        assert_eq!(
+            rendered.body,
+            Some(json!({
+                "id": "7",
+                "items": [{"id": 1}, {"id": 2}],
+                "count": 2,
+                "tags": ["POST", "static"]
+            }))
+        );
+    }
+
+    #[rstest]
+    #[cfg_attr(feature = "handlebars", case(None, Engine::Handlebars, json!("id {{params.id}} ${params.id}"), json!("id 7 ${params.id}")))]
+    #[case(None, Engine::Interpolation, json!("id {{params.id}} ${params.id}"), json!("id {{params.id}} 7"))]
+    #[cfg_attr(feature = "liquid", case(Some(Engine::Liquid), Engine::Interpolation, json!("{{ payload.ids | size }}"), json!("2")))]
+    #[case(Some(Engine::Interpolation), Engine::Handlebars, json!(["${payload.ids}"]), json!([[1, 2]]))]
+    fn test_render_variant_engine(
+        #[case] template: Option<Engine>,
+        #[case] default: Engine,
+        #[case] body: Value,
+        #[case] expected: Value,
+    ) {
+        let rendered = render_variant(
+            &variant(template, body),
+            &context(),
+            default,
+            &HashMap::new(),
+            &EvaluationLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(rendered.body, Some(expected));
+    }
+
+    #[cfg(not(feature = "handlebars"))]
+    #[rstest]
+    fn test_render_variant_engine_disabled() {
+        let err = render_variant(
+            &variant(None, json!("id {{params.id}}")),
+            &context(),
+            Engine::Handlebars,
+            &HashMap::new(),
+            &EvaluationLimits::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.message.contains("`handlebars` feature"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[rstest]
+    fn test_render_variant_with_custom_engine() {
+        struct Upper;
+        impl TemplateEngine for Upper {
+            fn is_template(&self, s: &str) -> bool {
+                s.starts_with('!')
+            }
+
+            fn render(&self, template: &str, _: &Value) -> Result<String, String> {
+                Ok(template[1..].to_uppercase())
+            }
+        }
+
+        let rendered = render_variant_with(
+            &variant(None, json!(["!loud", "quiet"])),
+            &context(),
+            &Upper,
+            &HashMap::new(),
+            &EvaluationLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(rendered.body, Some(json!(["LOUD", "quiet"])));
+    }
+
+    #[cfg(feature = "handlebars")]
+    #[rstest]
+    #[case(EvaluationLimits { max_depth: Some(1), ..Default::default() }, "nested deeper")]
+    #[case(EvaluationLimits { max_size: Some(1000), ..Default::default() }, "rendered template is larger")]
+    fn test_render_variant_limits(#[case] limits: EvaluationLimits, #[case] expected: &str) {
+        let body = json!("{{#each payload.ids}}{{json ../payload}}{{/each}}");
+        let mut context = context();
+        context["payload"]["ids"] = json!((0..100).collect::<Vec<_>>());
+
+        let err = render_variant(
+            &variant(None, body),
+            &context,
+            Engine::Handlebars,
+            &HashMap::new(),
+            &limits,
+        )
+        .unwrap_err();
+        assert!(err.message.contains(expected), "{}", err.message);
+
+        let static_variant = variant(None, json!({"static": true}));
+        assert!(render_variant(
+            &static_variant,
+            &context,
+            Engine::Handlebars,
+            &HashMap::new(),
+            &limits
+        )
+        .is_ok());
+    }
+
+    #[rstest]
+    #[cfg_attr(
+        feature = "handlebars",
+        case(Engine::Handlebars, "{{payload.ids.[0]}}")
+    )]
+    #[cfg_attr(feature = "liquid", case(Engine::Liquid, "{{ payload.ids[0] }}"))]
+    #[case(Engine::Interpolation, "${payload.ids[0]}")]
+    fn test_render_variant_partials(#[case] engine: Engine, #[case] partial: &str) {
+        let partials = HashMap::from([
+            ("first".to_string(), partial.to_string()),
+            (
+                "summary".to_string(),
+                "{\"first\": {{> first}}}".to_string(),
+            ),
+        ]);
+        let body = json!({"summary": "{{> summary}}", "plain": "{{>first}}"});
+
+        let rendered = render_variant(
+            &variant(None, body),
+            &context(),
+            engine,
+            &partials,
+            &EvaluationLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered.body,
+            Some(json!({"summary": {"first": 1}, "plain": "1"}))
+        );
+
+        let err = render_variant(
+            &variant(None, json!("{{> missing}}")),
+            &context(),
+            engine,
+            &partials,
+            &EvaluationLimits::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.template, "{{> missing}}");
+    }
+
+    #[rstest]
+    fn test_render_error() {
+        let err = render(Engine::Handlebars, "{{#each payload.ids}}", &context()).unwrap_err();
+        assert_eq!(err.template, "{{#each payload.ids}}");
+    }
+
+    #[rstest]
+    fn test_engine_deserialize() {
+        let engine: Engine = serde_yaml::from_str("liquid").unwrap();
+        assert_eq!(engine, Engine::Liquid);
+        assert!(serde_yaml::from_str::<Engine>("mustache").is_err());
+    }
+}
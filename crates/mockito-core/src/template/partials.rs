@@ -0,0 +1,114 @@
+//! Shared template fragments.
+//!
+//! Partials are defined once, in the `partials` setting or as files under the
+//! `templates/` directory of a project, and included by name:
+//!
+//! ```yaml
+//! # mockito.config.yaml
+//! partials:
+//!   user_summary: '{"id": "{{params.id}}", "name": "{{payload.name}}"}'
+//! ```
+//!
+//! ```yaml
+//! body:
+//!   user: "{{> user_summary}}"
+//! ```
+//!
+//! Includes are replaced by the partial source before rendering, so partials use
+//! the syntax of the including variant's engine and may include other partials.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Opening of a partial include
+const INCLUDE_START: &str = "{{>";
+/// Closing of a partial include
+const INCLUDE_END: &str = "}}";
+/// Maximum nesting of partials including other partials
+const MAX_DEPTH: usize = 16;
+
+/// Check if a string includes partials.
+pub fn has_partials(s: &str) -> bool {
+    s.contains(INCLUDE_START)
+}
+
+/// Replace `{{> name}}` includes with the source of the named partials.
+///
+/// Returns the template unchanged if it includes no partials.
+///
+/// # Errors
+/// Returns error if a partial isn't defined or partials include each other in a
+/// cycle.
+pub fn inline_partials<'a>(
+    template: &'a str,
+    partials: &HashMap<String, String>,
+) -> Result<Cow<'a, str>, String> {
+    if !has_partials(template) {
+        return Ok(Cow::Borrowed(template));
+    }
+    let mut output = String::with_capacity(template.len());
+    inline(template, partials, 0, &mut output)?;
+    Ok(Cow::Owned(output))
+}
+
+fn inline(
+    template: &str,
+    partials: &HashMap<String, String>,
+    depth: usize,
+    output: &mut String,
+) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "Partials are nested deeper than {} levels, check for circular includes",
+            MAX_DEPTH
+        ));
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find(INCLUDE_START) {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + INCLUDE_START.len()..];
+        let end = after
+            .find(INCLUDE_END)
+            .ok_or_else(|| format!("Unclosed partial include at '{}'", &rest[start..]))?;
+        let name = after[..end].trim();
+        let partial = partials
+            .get(name)
+            .ok_or_else(|| format!("Partial '{}' not found", name))?;
+        inline(partial, partials, depth + 1, output)?;
+        rest = &after[end + INCLUDE_END.len()..];
+    }
+    output.push_str(rest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn partials() -> HashMap<String, String> {
+        HashMap::from([
+            ("name".to_string(), "{{payload.name}}".to_string()),
+            (
+                "users/summary".to_string(),
+                "{{> name}} ({{params.id}})".to_string(),
+            ),
+            ("loop".to_string(), "{{>loop}}".to_string()),
+        ])
+    }
+
+    #[rstest]
+    #[case("plain {{payload.id}}", Ok("plain {{payload.id}}"))]
+    #[case("Hi {{> name}}!", Ok("Hi {{payload.name}}!"))]
+    #[case("{{>users/summary }}", Ok("{{payload.name}} ({{params.id}})"))]
+    #[case("{{> missing}}", Err("Partial 'missing' not found"))]
+    #[case("{{> name", Err("Unclosed partial include"))]
+    #[case("{{> loop}}", Err("circular includes"))]
+    fn test_inline_partials(#[case] template: &str, #[case] expected: Result<&str, &str>) {
+        let result = inline_partials(template, &partials());
+        match expected {
+            Ok(inlined) => assert_eq!(result.unwrap(), inlined),
+            Err(message) => assert!(result.unwrap_err().contains(message)),
+        }
+    }
+}
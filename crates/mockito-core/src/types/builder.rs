@@ -0,0 +1,564 @@
+//! Builders constructing definitions in code.
+//!
+//! Builders fill optional fields and IDs, so tests can define mocks without
+//! spelling out whole structs:
+//!
+//! ```
+//! use mockito_core::types::collection::Collection;
+//! use mockito_core::types::route::Route;
+//! use serde_json::json;
+//!
+//! let route = Route::builder("/users/{id}")
+//!     .get()
+//!     .preset(|p| {
+//!         p.query("page", "1")
+//!             .variant(|v| v.status(200).json(json!({"id": "{{params.id}}"})))
+//!     })
+//!     .build();
+//! assert_eq!(&*route.id, "get-users-id");
+//!
+//! let collection = Collection::builder("base").route("get-users-id:preset-1:variant-1");
+//! ```
+//!
+//! Unless set with `id()`, route IDs are derived from the method and URL (or
+//! from the GraphQL operation, e.g. `query-getuser`), and presets and variants
+//! are numbered in order (`preset-1`, `variant-1`, ...).
+
+use crate::template::Engine;
+use crate::types::collection::Collection;
+use crate::types::graphql::{GraphqlOperation, OperationType};
+use crate::types::preset::{HeadersOrExpression, PayloadOrExpression, Preset, QueryOrExpression};
+use crate::types::route::{HttpMethod, Route, Transport};
+use crate::types::tcp::TcpMatch;
+use crate::types::variant::Variant;
+use crate::vars::Vars;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+impl Route {
+    /// Start building an HTTP route matching `url`.
+    pub fn builder(url: impl Into<String>) -> RouteBuilder {
+        RouteBuilder::new(url)
+    }
+}
+
+impl Collection {
+    /// Start building a collection.
+    pub fn builder(id: impl Into<Arc<str>>) -> CollectionBuilder {
+        CollectionBuilder::new(id)
+    }
+}
+
+/// Builder of a [`Route`], see [`Route::builder`].
+#[derive(Debug, Clone)]
+pub struct RouteBuilder {
+    id: Option<Arc<str>>,
+    url: String,
+    transport: Transport,
+    method: Option<HttpMethod>,
+    presets: Vec<Preset>,
+    tags: Vec<String>,
+    group: Option<String>,
+    graphql: Option<GraphqlOperation>,
+    tcp: Option<TcpMatch>,
+}
+
+impl RouteBuilder {
+    fn new(url: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            url: url.into(),
+            transport: Transport::Http,
+            method: None,
+            presets: Vec::new(),
+            tags: Vec::new(),
+            group: None,
+            graphql: None,
+            tcp: None,
+        }
+    }
+
+    /// Set the route ID, derived from the method and URL by default.
+    pub fn id(mut self, id: impl Into<Arc<str>>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Match requests with an HTTP method.
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.transport = Transport::Http;
+        self.method = Some(method);
+        self
+    }
+
+    /// Match `GET` requests.
+    pub fn get(self) -> Self {
+        self.method(HttpMethod::Get)
+    }
+
+    /// Match `POST` requests.
+    pub fn post(self) -> Self {
+        self.method(HttpMethod::Post)
+    }
+
+    /// Match `PUT` requests.
+    pub fn put(self) -> Self {
+        self.method(HttpMethod::Put)
+    }
+
+    /// Match `PATCH` requests.
+    pub fn patch(self) -> Self {
+        self.method(HttpMethod::Patch)
+    }
+
+    /// Match `DELETE` requests.
+    pub fn delete(self) -> Self {
+        self.method(HttpMethod::Delete)
+    }
+
+    /// Match `HEAD` requests.
+    pub fn head(self) -> Self {
+        self.method(HttpMethod::Head)
+    }
+
+    /// Match `OPTIONS` requests.
+    pub fn options(self) -> Self {
+        self.method(HttpMethod::Options)
+    }
+
+    /// Match WebSocket connections instead of HTTP requests.
+    pub fn websocket(mut self) -> Self {
+        self.transport = Transport::WebSocket;
+        self.method = None;
+        self
+    }
+
+    /// Stream Server-Sent Events instead of answering HTTP requests.
+    ///
+    /// Variant bodies hold the event scripts, see [`crate::mocks::sse`].
+    pub fn sse(mut self) -> Self {
+        self.transport = Transport::Sse;
+        self.method = None;
+        self
+    }
+
+    /// Answer raw TCP messages sent to the address in the route URL.
+    ///
+    /// Variant bodies hold the reply scripts, see [`crate::mocks::tcp`].
+    pub fn tcp(mut self) -> Self {
+        self.transport = Transport::Tcp;
+        self.method = None;
+        self
+    }
+
+    /// Answer TCP messages starting with `prefix`.
+    pub fn message_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.tcp.get_or_insert_with(TcpMatch::default).prefix = Some(prefix.into());
+        self.tcp()
+    }
+
+    /// Answer TCP messages matching a bytes regex.
+    pub fn message_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.tcp.get_or_insert_with(TcpMatch::default).pattern = Some(pattern.into());
+        self.tcp()
+    }
+
+    /// Add a tag.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set the group providing URL prefix and shared response headers.
+    pub fn group(mut self, group_id: impl Into<String>) -> Self {
+        self.group = Some(group_id.into());
+        self
+    }
+
+    /// Answer a GraphQL operation sent to the route URL.
+    pub fn graphql(mut self, operation_type: OperationType, name: impl Into<String>) -> Self {
+        self.graphql = Some(GraphqlOperation {
+            operation_type: Some(operation_type),
+            name: Some(name.into()),
+        });
+        self
+    }
+
+    /// Add a preset built by `f`.
+    pub fn preset(mut self, f: impl FnOnce(PresetBuilder) -> PresetBuilder) -> Self {
+        let number = self.presets.len() + 1;
+        self.presets.push(f(PresetBuilder::new(number)).build());
+        self
+    }
+
+    /// Build the route.
+    pub fn build(self) -> Route {
+        let id = self.id.unwrap_or_else(|| match &self.graphql {
+            Some(operation) => derive_operation_id(operation).into(),
+            None => derive_id(&self.transport, self.method.as_ref(), &self.url).into(),
+        });
+        Route {
+            id,
+            url: self.url,
+            transport: self.transport,
+            method: self.method,
+            presets: self.presets,
+            tags: self.tags,
+            group: self.group,
+            proto: None,
+            graphql: self.graphql,
+            tcp: self.tcp,
+        }
+    }
+}
+
+impl From<RouteBuilder> for Route {
+    fn from(builder: RouteBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Derive a route ID like `get-users-id` from the method and URL.
+///
+/// Routes without method are prefixed with their transport, e.g. `sse-events`
+/// or `tcp-127-0-0-1-2525`.
+fn derive_id(transport: &Transport, method: Option<&HttpMethod>, url: &str) -> String {
+    let method = match method {
+        Some(HttpMethod::Get) => "get",
+        Some(HttpMethod::Post) => "post",
+        Some(HttpMethod::Put) => "put",
+        Some(HttpMethod::Patch) => "patch",
+        Some(HttpMethod::Delete) => "delete",
+        Some(HttpMethod::Head) => "head",
+        Some(HttpMethod::Options) => "options",
+        None => match transport {
+            Transport::Sse => "sse",
+            Transport::Tcp => "tcp",
+            Transport::Http | Transport::WebSocket => "ws",
+        },
+    };
+    let words = url
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase);
+    std::iter::once(method.to_string())
+        .chain(words)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Derive a route ID like `query-getuser` from a GraphQL operation.
+fn derive_operation_id(operation: &GraphqlOperation) -> String {
+    let operation_type = operation.operation_type.map_or("operation", |t| t.as_str());
+    let words = operation
+        .name
+        .iter()
+        .flat_map(|name| name.split(|c: char| !c.is_ascii_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase);
+    std::iter::once(operation_type.to_string())
+        .chain(words)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Builder of a [`Preset`], see [`RouteBuilder::preset`].
+#[derive(Debug, Clone)]
+pub struct PresetBuilder {
+    preset: Preset,
+}
+
+impl PresetBuilder {
+    fn new(number: usize) -> Self {
+        Self {
+            preset: Preset {
+                id: format!("preset-{}", number).into(),
+                params: None,
+                query: None,
+                headers: None,
+                payload: None,
+                variants: Vec::new(),
+            },
+        }
+    }
+
+    /// Set the preset ID, `preset-<number>` by default.
+    pub fn id(mut self, id: impl Into<Arc<str>>) -> Self {
+        self.preset.id = id.into();
+        self
+    }
+
+    /// Match a URL path parameter.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.preset
+            .params
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Match a query parameter, replacing a query expression.
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let query = match self.preset.query.take() {
+            Some(QueryOrExpression::Map(query)) => query,
+            _ => HashMap::new(),
+        };
+        self.preset.query = Some(QueryOrExpression::Map(insert(query, name, value)));
+        self
+    }
+
+    /// Match query parameters with an expression, written without `${}`.
+    pub fn query_expression(mut self, expression: impl Into<String>) -> Self {
+        self.preset.query = Some(QueryOrExpression::Expression(expression.into()));
+        self
+    }
+
+    /// Match a request header, replacing a headers expression.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let headers = match self.preset.headers.take() {
+            Some(HeadersOrExpression::Map(headers)) => headers,
+            _ => HashMap::new(),
+        };
+        self.preset.headers = Some(HeadersOrExpression::Map(insert(headers, name, value)));
+        self
+    }
+
+    /// Match request headers with an expression, written without `${}`.
+    pub fn headers_expression(mut self, expression: impl Into<String>) -> Self {
+        self.preset.headers = Some(HeadersOrExpression::Expression(expression.into()));
+        self
+    }
+
+    /// Match payloads containing `payload`.
+    pub fn payload(mut self, payload: Value) -> Self {
+        self.preset.payload = Some(PayloadOrExpression::Value(payload));
+        self
+    }
+
+    /// Match payloads with an expression, written without `${}`.
+    pub fn payload_expression(mut self, expression: impl Into<String>) -> Self {
+        self.preset.payload = Some(PayloadOrExpression::Expression(expression.into()));
+        self
+    }
+
+    /// Add a variant built by `f`.
+    pub fn variant(mut self, f: impl FnOnce(VariantBuilder) -> VariantBuilder) -> Self {
+        let number = self.preset.variants.len() + 1;
+        self.preset
+            .variants
+            .push(f(VariantBuilder::new(number)).build());
+        self
+    }
+
+    /// Build the preset.
+    pub fn build(self) -> Preset {
+        self.preset
+    }
+}
+
+/// Builder of a [`Variant`], see [`PresetBuilder::variant`].
+#[derive(Debug, Clone)]
+pub struct VariantBuilder {
+    variant: Variant,
+}
+
+impl VariantBuilder {
+    fn new(number: usize) -> Self {
+        Self {
+            variant: Variant {
+                id: format!("variant-{}", number).into(),
+                status: None,
+                headers: None,
+                body: None,
+                template: None,
+            },
+        }
+    }
+
+    /// Set the variant ID, `variant-<number>` by default.
+    pub fn id(mut self, id: impl Into<Arc<str>>) -> Self {
+        self.variant.id = id.into();
+        self
+    }
+
+    /// Set the response status.
+    pub fn status(mut self, status: u16) -> Self {
+        self.variant.status = Some(status);
+        self
+    }
+
+    /// Add a response header.
+    pub fn header(mut self, name: impl Into<Arc<str>>, value: impl Into<String>) -> Self {
+        let headers = self.variant.headers.take().unwrap_or_default();
+        self.variant.headers = Some(insert(headers, name, value));
+        self
+    }
+
+    /// Set a JSON response body.
+    pub fn json(mut self, body: Value) -> Self {
+        self.variant.body = Some(body);
+        self
+    }
+
+    /// Set a text response body.
+    pub fn text(self, body: impl Into<String>) -> Self {
+        self.json(Value::String(body.into()))
+    }
+
+    /// Set a GraphQL response body failing with an error `message`.
+    ///
+    /// The body is a spec-compliant `{"data": null, "errors": [{"message": ...}]}`.
+    pub fn graphql_error(self, message: impl Into<String>) -> Self {
+        let message = message.into();
+        self.json(json!({"data": null, "errors": [{"message": message}]}))
+    }
+
+    /// Set the template engine rendering headers and body.
+    pub fn template(mut self, engine: Engine) -> Self {
+        self.variant.template = Some(engine);
+        self
+    }
+
+    /// Build the variant.
+    pub fn build(self) -> Variant {
+        self.variant
+    }
+}
+
+/// Builder of a [`Collection`], see [`Collection::builder`].
+#[derive(Debug, Clone)]
+pub struct CollectionBuilder {
+    collection: Collection,
+}
+
+impl CollectionBuilder {
+    fn new(id: impl Into<Arc<str>>) -> Self {
+        Self {
+            collection: Collection {
+                id: id.into(),
+                from: None,
+                routes: Vec::new(),
+                vars: None,
+            },
+        }
+    }
+
+    /// Inherit the routes of a parent collection.
+    pub fn from(mut self, parent_id: impl Into<String>) -> Self {
+        self.collection.from = Some(parent_id.into());
+        self
+    }
+
+    /// Add a `route:preset:variant` reference.
+    pub fn route(mut self, reference: impl Into<Arc<str>>) -> Self {
+        self.collection.routes.push(reference.into());
+        self
+    }
+
+    /// Set a variable for `{{vars.name}}` placeholders.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.collection
+            .vars
+            .get_or_insert_with(Vars::default)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Build the collection.
+    pub fn build(self) -> Collection {
+        self.collection
+    }
+}
+
+impl From<CollectionBuilder> for Collection {
+    fn from(builder: CollectionBuilder) -> Self {
+        builder.build()
+    }
+}
+
+fn insert<K: Eq + Hash>(
+    mut map: HashMap<K, String>,
+    name: impl Into<K>,
+    value: impl Into<String>,
+) -> HashMap<K, String> {
+    map.insert(name.into(), value.into());
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Route::builder("/api/users/{id}").get(), "get-api-users-id")]
+    #[case(Route::builder("/api/users/{id}").get().id("user"), "user")]
+    #[case(Route::builder("/ws/chat").websocket(), "ws-ws-chat")]
+    #[case(Route::builder("/Orders").post(), "post-orders")]
+    #[case(Route::builder("/events").sse(), "sse-events")]
+    #[case(Route::builder("127.0.0.1:2525").tcp(), "tcp-127-0-0-1-2525")]
+    #[case(
+        Route::builder("/graphql").graphql(OperationType::Mutation, "CreateOrder"),
+        "mutation-createorder"
+    )]
+    fn test_route_builder_id(#[case] builder: RouteBuilder, #[case] expected: &str) {
+        assert_eq!(builder.build().id, expected.into());
+    }
+
+    #[rstest]
+    fn test_route_builder() {
+        let route = Route::builder("/users/{id}")
+            .get()
+            .tag("users")
+            .preset(|p| {
+                p.query("page", "1")
+                    .header("x-tenant", "acme")
+                    .variant(|v| v.status(200).json(json!({"id": 1})))
+                    .variant(|v| v.id("error").status(500).text("failed"))
+            })
+            .preset(|p| p.id("search").payload_expression("payload.q != null"))
+            .build();
+
+        let expected: Route = serde_json::from_value(json!({
+            "id": "get-users-id",
+            "url": "/users/{id}",
+            "transport": "HTTP",
+            "method": "GET",
+            "tags": ["users"],
+            "presets": [
+                {
+                    "id": "preset-1",
+                    "query": {"page": "1"},
+                    "headers": {"x-tenant": "acme"},
+                    "variants": [
+                        {"id": "variant-1", "status": 200, "body": {"id": 1}},
+                        {"id": "error", "status": 500, "body": "failed"}
+                    ]
+                },
+                {"id": "search", "payload": "${payload.q != null}", "variants": []}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(route, expected);
+    }
+
+    #[rstest]
+    fn test_collection_builder() {
+        let collection = Collection::builder("acme")
+            .from("base")
+            .route("get-users-id:preset-1:variant-1")
+            .var("tenant", "acme")
+            .build();
+
+        let expected: Collection = serde_json::from_value(json!({
+            "id": "acme",
+            "from": "base",
+            "routes": ["get-users-id:preset-1:variant-1"],
+            "vars": {"tenant": "acme"}
+        }))
+        .unwrap();
+        assert_eq!(collection, expected);
+    }
+}
@@ -1,15 +1,56 @@
 //! Collection types.
 
+use crate::vars::Vars;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Collection of routes for a specific scenario.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct Collection {
     /// Unique identifier for this collection
-    pub id: String,
+    pub id: Arc<str>,
     /// ID of parent collection to inherit routes from
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<String>,
     /// List of route references in format 'routeId:presetId:variantId'
-    pub routes: Vec<String>,
+    pub routes: Vec<Arc<str>>,
+    /// Variables substituted into route URLs, response headers and bodies
+    /// via `{{vars.name}}` placeholders (child values override parent ones)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vars: Option<Vars>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    fn test_collection_vars_deserialize() {
+        let yaml = r#"
+id: acme
+from: template
+vars:
+  tenant: acme
+  limit: 10
+routes: []
+"#;
+        let collection: Collection = serde_yaml::from_str(yaml).unwrap();
+        let vars = collection.vars.unwrap();
+        assert_eq!(vars["tenant"], json!("acme"));
+        assert_eq!(vars["limit"], json!(10));
+    }
+
+    #[rstest]
+    fn test_collection_without_vars() {
+        let collection: Collection =
+            serde_json::from_str(r#"{"id": "base", "routes": []}"#).unwrap();
+        assert!(collection.vars.is_none());
+        assert!(!serde_json::to_string(&collection).unwrap().contains("vars"));
+    }
 }
@@ -0,0 +1,60 @@
+//! GraphQL operation binding types.
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// GraphQL operation answered by a route.
+///
+/// Routes of one GraphQL endpoint share its URL and are told apart by the
+/// operation of the request document:
+///
+/// ```yaml
+/// url: /graphql
+/// transport: HTTP
+/// graphql:
+///   type: query
+///   name: GetUser
+/// ```
+///
+/// Presets of such routes match operation variables with payload expressions,
+/// e.g. `${graphql.variables.id == '42'}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct GraphqlOperation {
+    /// Operation type, any if not set
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub operation_type: Option<OperationType>,
+    /// Operation name, any (including anonymous operations) if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Type of a GraphQL operation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum OperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationType {
+    /// Get the keyword of the operation type, e.g. `query`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        }
+    }
+}
+
+impl fmt::Display for OperationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,67 @@
+//! Route group types.
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Group of routes sharing a URL prefix and response headers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Group {
+    /// Unique identifier for this group
+    pub id: String,
+    /// URL prefix prepended to URLs of member routes (e.g. `/api/v2`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Response headers shared by member routes (variant headers take precedence)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<Arc<str>, String>>,
+}
+
+impl Group {
+    /// Prepend the group base URL to a route URL.
+    pub fn prefix_url(&self, url: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => {
+                let base_url = base_url.trim_end_matches('/');
+                if url.starts_with('/') {
+                    format!("{base_url}{url}")
+                } else {
+                    format!("{base_url}/{url}")
+                }
+            }
+            None => url.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some("/api/v2"), "/users", "/api/v2/users")]
+    #[case(Some("/api/v2/"), "/users/{id}", "/api/v2/users/{id}")]
+    #[case(Some("/api/v2"), "users", "/api/v2/users")]
+    #[case(None, "/users", "/users")]
+    fn test_prefix_url(#[case] base_url: Option<&str>, #[case] url: &str, #[case] expected: &str) {
+        let group = Group {
+            id: "api".to_string(),
+            base_url: base_url.map(str::to_string),
+            headers: None,
+        };
+        assert_eq!(group.prefix_url(url), expected);
+    }
+
+    #[rstest]
+    fn test_group_deserialize() {
+        let yaml = "id: api-v2\nbase_url: /api/v2\nheaders:\n  X-Api-Version: '2'";
+        let group: Group = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(group.base_url.as_deref(), Some("/api/v2"));
+        assert_eq!(group.headers.unwrap()["X-Api-Version"], "2");
+    }
+}
@@ -1,6 +1,12 @@
 //! Core domain types for routes, presets, and variants.
 
+pub mod builder;
 pub mod collection;
+pub mod graphql;
+pub mod group;
 pub mod preset;
+pub mod proto;
 pub mod route;
+pub mod settings;
+pub mod tcp;
 pub mod variant;
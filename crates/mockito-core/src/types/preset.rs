@@ -2,9 +2,14 @@
 
 use crate::expression::is_expression;
 use crate::types::variant::Variant;
+#[cfg(feature = "json-schema")]
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+#[cfg(feature = "json-schema")]
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Query parameters value - either a map or an expression string
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,6 +61,17 @@ impl<'de> Deserialize<'de> for QueryOrExpression {
     }
 }
 
+#[cfg(feature = "json-schema")]
+impl JsonSchema for QueryOrExpression {
+    fn schema_name() -> Cow<'static, str> {
+        "QueryOrExpression".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        map_or_expression_schema("Query parameters to match or an expression")
+    }
+}
+
 /// Headers value - either a map or an expression string
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HeadersOrExpression {
@@ -106,6 +122,33 @@ impl<'de> Deserialize<'de> for HeadersOrExpression {
     }
 }
 
+#[cfg(feature = "json-schema")]
+impl JsonSchema for HeadersOrExpression {
+    fn schema_name() -> Cow<'static, str> {
+        "HeadersOrExpression".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        map_or_expression_schema("Request headers to match or an expression")
+    }
+}
+
+/// Schema of a string map or a `${...}` expression string.
+#[cfg(feature = "json-schema")]
+fn map_or_expression_schema(description: &str) -> Schema {
+    json_schema!({
+        "description": description,
+        "anyOf": [
+            {"type": "object", "additionalProperties": {"type": "string"}},
+            {"type": "string", "pattern": EXPRESSION_PATTERN}
+        ]
+    })
+}
+
+/// Pattern of expression strings in JSON Schemas
+#[cfg(feature = "json-schema")]
+const EXPRESSION_PATTERN: &str = "^\\$\\{.*\\}$";
+
 /// Payload value - either a JSON value or an expression string
 #[derive(Debug, Clone, PartialEq)]
 pub enum PayloadOrExpression {
@@ -128,6 +171,20 @@ impl Serialize for PayloadOrExpression {
     }
 }
 
+#[cfg(feature = "json-schema")]
+impl JsonSchema for PayloadOrExpression {
+    fn schema_name() -> Cow<'static, str> {
+        "PayloadOrExpression".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        // Any JSON value, strings like `${...}` are expressions
+        json_schema!({
+            "description": "Request body to match or an expression"
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for PayloadOrExpression {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -150,9 +207,11 @@ impl<'de> Deserialize<'de> for PayloadOrExpression {
 
 /// Request matching preset with response variants.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct Preset {
     /// Unique identifier for this preset within the route
-    pub id: String,
+    pub id: Arc<str>,
     /// URL path parameters to match
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<HashMap<String, String>>,
@@ -169,6 +228,29 @@ pub struct Preset {
     pub variants: Vec<Variant>,
 }
 
+impl Preset {
+    /// Get the expressions of the preset with the names of their fields.
+    pub fn expressions(&self) -> Vec<(&'static str, &str)> {
+        let query = match &self.query {
+            Some(QueryOrExpression::Expression(expression)) => Some(("query", expression)),
+            _ => None,
+        };
+        let headers = match &self.headers {
+            Some(HeadersOrExpression::Expression(expression)) => Some(("headers", expression)),
+            _ => None,
+        };
+        let payload = match &self.payload {
+            Some(PayloadOrExpression::Expression(expression)) => Some(("payload", expression)),
+            _ => None,
+        };
+        [query, headers, payload]
+            .into_iter()
+            .flatten()
+            .map(|(field, expression)| (field, expression.as_str()))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,7 +261,7 @@ mod tests {
     #[rstest]
     fn test_preset_serialize_deserialize() {
         let preset = Preset {
-            id: "test-preset".to_string(),
+            id: "test-preset".into(),
             params: Some({
                 let mut map = HashMap::new();
                 map.insert("id".to_string(), "123".to_string());
@@ -216,7 +298,7 @@ mod tests {
     #[case("payload")]
     fn test_preset_optional_fields_omitted_when_none(#[case] field: &str) {
         let preset = Preset {
-            id: "minimal-preset".to_string(),
+            id: "minimal-preset".into(),
             params: None,
             query: None,
             headers: None,
@@ -245,14 +327,15 @@ mod tests {
     #[case("variant3", None)]
     fn test_preset_with_variants(#[case] variant_id: &str, #[case] status: Option<u16>) {
         let variant = Variant {
-            id: variant_id.to_string(),
+            id: variant_id.into(),
             status,
             headers: None,
             body: None,
+            template: None,
         };
 
         let preset = Preset {
-            id: "preset-with-variants".to_string(),
+            id: "preset-with-variants".into(),
             params: None,
             query: None,
             headers: None,
@@ -264,7 +347,7 @@ mod tests {
         let deserialized: Preset = serde_json::from_str(&json).expect("Should deserialize");
 
         assert_eq!(deserialized.variants.len(), 1);
-        assert_eq!(deserialized.variants[0].id, variant_id);
+        assert_eq!(deserialized.variants[0].id, variant_id.into());
         assert_eq!(deserialized.variants[0].status, status);
     }
 
@@ -277,7 +360,7 @@ mod tests {
         params.insert(param_key.to_string(), param_value.to_string());
 
         let preset = Preset {
-            id: "test".to_string(),
+            id: "test".into(),
             params: Some(params.clone()),
             query: None,
             headers: None,
@@ -290,4 +373,28 @@ mod tests {
 
         assert_eq!(deserialized.params, Some(params));
     }
+
+    #[rstest]
+    fn test_preset_expressions() {
+        let preset = Preset {
+            id: "test".into(),
+            params: None,
+            query: Some(QueryOrExpression::Expression(
+                "query.page == '1'".to_string(),
+            )),
+            headers: Some(HeadersOrExpression::Map(HashMap::new())),
+            payload: Some(PayloadOrExpression::Expression(
+                "payload.id == `5`".to_string(),
+            )),
+            variants: vec![],
+        };
+
+        assert_eq!(
+            preset.expressions(),
+            vec![
+                ("query", "query.page == '1'"),
+                ("payload", "payload.id == `5`")
+            ]
+        );
+    }
 }
@@ -0,0 +1,31 @@
+//! Protobuf binding types.
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Protobuf message types used by a route.
+///
+/// Message types are taken from the gRPC `method` unless set explicitly:
+///
+/// ```yaml
+/// proto:
+///   descriptor: ./protos/orders.desc
+///   method: shop.v1.Orders/GetOrder
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ProtoBinding {
+    /// Compiled descriptor set (`.desc`) or `.proto` file, relative to the declaring file
+    pub descriptor: String,
+    /// gRPC method as `package.Service/Method`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Fully qualified request message type, overrides the method input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<String>,
+    /// Fully qualified response message type, overrides the method output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+}
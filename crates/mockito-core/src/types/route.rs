@@ -1,18 +1,40 @@
 //! Core route types.
 
+use crate::types::graphql::GraphqlOperation;
 use crate::types::preset::Preset;
+use crate::types::proto::ProtoBinding;
+use crate::types::tcp::TcpMatch;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Transport type for route matching.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// SSE routes stream the Server-Sent Events held in variant bodies, see
+/// [`crate::mocks::sse`]. TCP routes answer raw TCP messages sent to the
+/// address in their URL, see [`crate::mocks::tcp`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Transport {
     Http,
     WebSocket,
+    Sse,
+    Tcp,
+}
+
+impl Transport {
+    /// Check if routes of the transport are switched with `use_socket` rather
+    /// than `use_routes`.
+    pub fn is_socket(&self) -> bool {
+        matches!(self, Transport::WebSocket)
+    }
 }
 
 /// HTTP method for route matching.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpMethod {
     Get,
@@ -24,51 +46,114 @@ pub enum HttpMethod {
     Options,
 }
 
+impl HttpMethod {
+    /// Get the method name as sent in requests, e.g. `GET`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+        }
+    }
+}
+
 /// Mock route definition.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct Route {
     /// Unique identifier for this route
-    pub id: String,
+    pub id: Arc<str>,
     /// URL pattern (supports {param} placeholders)
     pub url: String,
-    /// Transport type (HTTP or WebSocket)
+    /// Transport type (HTTP, WebSocket, SSE or TCP)
     pub transport: Transport,
     /// HTTP method (for HTTP routes)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<HttpMethod>,
     /// Request matching presets
     pub presets: Vec<Preset>,
+    /// Tags grouping this route into functional areas (e.g. `payments`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// ID of the group providing URL prefix and shared response headers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Protobuf message types of requests and responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proto: Option<ProtoBinding>,
+    /// GraphQL operation answered by the route, for routes of a GraphQL endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<GraphqlOperation>,
+    /// Client messages answered by the route, for TCP routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<TcpMatch>,
+}
+
+impl Route {
+    /// Merge another definition of the same route into this one.
+    ///
+    /// Presets are united by ID, variants of presets defined in both are united
+    /// by ID too, with the other definition's variants replacing same-ID ones.
+    /// Tags are united, other fields of this definition are kept.
+    pub fn merge(&mut self, other: Route) {
+        for preset in other.presets {
+            match self.presets.iter_mut().find(|p| p.id == preset.id) {
+                Some(existing) => {
+                    for variant in preset.variants {
+                        match existing.variants.iter_mut().find(|v| v.id == variant.id) {
+                            Some(existing) => *existing = variant,
+                            None => existing.variants.push(variant),
+                        }
+                    }
+                }
+                None => self.presets.push(preset),
+            }
+        }
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
 }
 
-/// Parsed route reference in format `route_id:preset_id:variant_id`.
+/// Parsed route reference in format `route_id[:preset_id[:variant_id]]`.
+///
+/// Omitted preset and variant resolve to the first preset of the route and
+/// the first variant of the preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RouteReference {
     pub route_id: String,
-    pub preset_id: String,
-    pub variant_id: String,
+    pub preset_id: Option<String>,
+    pub variant_id: Option<String>,
 }
 
 impl RouteReference {
     pub fn parse(s: &str) -> Option<Self> {
         let parts: Vec<&str> = s.split(':').collect();
-        if parts.len() != 3 {
-            return None;
-        }
-
-        // Safe to unwrap because we checked parts.len() == 3
-        let route_id = parts[0];
-        let preset_id = parts[1];
-        let variant_id = parts[2];
-
-        if route_id.is_empty() || preset_id.is_empty() || variant_id.is_empty() {
+        if parts.len() > 3 || parts.iter().any(|part| part.is_empty()) {
             return None;
         }
 
         Some(Self {
-            route_id: route_id.to_owned(),
-            preset_id: preset_id.to_owned(),
-            variant_id: variant_id.to_owned(),
+            route_id: parts[0].to_owned(),
+            preset_id: parts.get(1).map(|&p| p.to_owned()),
+            variant_id: parts.get(2).map(|&v| v.to_owned()),
         })
     }
+
+    /// Check if any part of the reference is a wildcard pattern (`*` or `?`).
+    pub fn is_wildcard(&self) -> bool {
+        let is_pattern = |part: &str| part.contains(['*', '?']);
+        is_pattern(&self.route_id)
+            || self.preset_id.as_deref().is_some_and(is_pattern)
+            || self.variant_id.as_deref().is_some_and(is_pattern)
+    }
 }
 
 #[cfg(test)]
@@ -77,28 +162,25 @@ mod tests {
     use rstest::rstest;
 
     #[rstest]
-    #[case("route1:preset1:variant1", Some(("route1", "preset1", "variant1")))]
-    #[case("a:b:c", Some(("a", "b", "c")))]
-    #[case("route-id:preset-id:variant-id", Some(("route-id", "preset-id", "variant-id")))]
+    #[case("route1:preset1:variant1", ("route1", Some("preset1"), Some("variant1")))]
+    #[case("a:b:c", ("a", Some("b"), Some("c")))]
+    #[case("route-id:preset-id:variant-id", ("route-id", Some("preset-id"), Some("variant-id")))]
+    #[case("route1:preset1", ("route1", Some("preset1"), None))]
+    #[case("route1", ("route1", None, None))]
     fn test_route_reference_parse_valid(
         #[case] input: &str,
-        #[case] expected: Option<(&str, &str, &str)>,
+        #[case] expected: (&str, Option<&str>, Option<&str>),
     ) {
-        let result = RouteReference::parse(input);
-        if let Some(expected) = expected {
-            let parsed = result.expect("Should parse successfully");
-            assert_eq!(parsed.route_id, expected.0);
-            assert_eq!(parsed.preset_id, expected.1);
-            assert_eq!(parsed.variant_id, expected.2);
-        } else {
-            assert!(result.is_none());
-        }
+        let parsed = RouteReference::parse(input).expect("Should parse successfully");
+        assert_eq!(parsed.route_id, expected.0);
+        assert_eq!(parsed.preset_id.as_deref(), expected.1);
+        assert_eq!(parsed.variant_id.as_deref(), expected.2);
     }
 
     #[rstest]
     #[case("")]
-    #[case("route1")]
-    #[case("route1:preset1")]
+    #[case("route1:")]
+    #[case(":preset1")]
     #[case("route1:preset1:variant1:extra")]
     #[case(":preset1:variant1")]
     #[case("route1::variant1")]
@@ -108,6 +190,18 @@ mod tests {
         assert!(RouteReference::parse(input).is_none());
     }
 
+    #[rstest]
+    #[case("route1:preset1:variant1", false)]
+    #[case("route1", false)]
+    #[case("*", true)]
+    #[case("payments-*:default:timeout", true)]
+    #[case("route1:*", true)]
+    #[case("route1:preset1:err?r", true)]
+    fn test_route_reference_is_wildcard(#[case] input: &str, #[case] expected: bool) {
+        let parsed = RouteReference::parse(input).expect("Should parse successfully");
+        assert_eq!(parsed.is_wildcard(), expected);
+    }
+
     #[rstest]
     #[case(Transport::Http)]
     #[case(Transport::WebSocket)]
@@ -155,11 +249,16 @@ mod tests {
         #[case] should_have_method: bool,
     ) {
         let route = Route {
-            id: id.to_string(),
+            id: id.into(),
             url: url.to_string(),
             transport,
             method,
             presets: vec![],
+            tags: vec![],
+            group: None,
+            proto: None,
+            graphql: None,
+            tcp: None,
         };
 
         let json = serde_json::to_string(&route).expect("Should serialize");
@@ -176,4 +275,55 @@ mod tests {
         assert_eq!(deserialized.method, route.method);
         assert_eq!(deserialized.presets.len(), 0);
     }
+
+    #[rstest]
+    #[case(r#"{"id": "r", "url": "/", "transport": "HTTP", "presets": []}"#, &[])]
+    #[case(r#"{"id": "r", "url": "/", "transport": "HTTP", "presets": [], "tags": ["payments"]}"#, &["payments"])]
+    fn test_route_tags_deserialize(#[case] json: &str, #[case] expected: &[&str]) {
+        let route: Route = serde_json::from_str(json).expect("Should deserialize");
+        assert_eq!(route.tags, expected);
+
+        let serialized = serde_json::to_string(&route).expect("Should serialize");
+        assert_eq!(serialized.contains("tags"), !expected.is_empty());
+    }
+
+    #[rstest]
+    fn test_route_merge() {
+        let route = |presets: &str, tags: &str| -> Route {
+            serde_json::from_str(&format!(
+                r#"{{"id": "users", "url": "/users", "transport": "HTTP", "presets": {presets}, "tags": {tags}}}"#
+            ))
+            .unwrap()
+        };
+        let mut users = route(
+            r#"[{"id": "list", "variants": [{"id": "ok", "status": 200}, {"id": "empty", "status": 200}]}]"#,
+            r#"["users"]"#,
+        );
+        let other = route(
+            r#"[{"id": "list", "variants": [{"id": "empty", "status": 204}, {"id": "error", "status": 500}]},
+                {"id": "search", "variants": [{"id": "ok"}]}]"#,
+            r#"["users", "search"]"#,
+        );
+
+        users.merge(other);
+        let summary: Vec<String> = users
+            .presets
+            .iter()
+            .flat_map(|p| {
+                p.variants
+                    .iter()
+                    .map(move |v| format!("{}:{}:{:?}", p.id, v.id, v.status))
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "list:ok:Some(200)",
+                "list:empty:Some(204)",
+                "list:error:Some(500)",
+                "search:ok:None",
+            ]
+        );
+        assert_eq!(users.tags, vec!["users", "search"]);
+    }
 }
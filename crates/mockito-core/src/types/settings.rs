@@ -0,0 +1,192 @@
+//! Project settings types.
+
+use crate::expression::EvaluationLimits;
+use crate::template::Engine;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Project-wide settings, loaded from a `mockito.config.*` file.
+///
+/// All fields are optional, so consumers fall back to their own defaults for
+/// settings missing in the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Port the mock server listens on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Collection activated when the controller starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_collection: Option<String>,
+    /// Delay in milliseconds applied to responses without their own delay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+    /// CORS headers added to responses, CORS is disabled if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cors: Option<CorsSettings>,
+    /// Reloading of config files on changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchSettings>,
+    /// Size limits of request and response bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_limits: Option<BodyLimits>,
+    /// Template engine of variants without their own, Handlebars if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<Engine>,
+    /// Limits of expression evaluation and template rendering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<EvaluationLimits>,
+    /// Template fragments included from variants with `{{> name}}`, merged with
+    /// files of the `templates/` project directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partials: Option<HashMap<String, String>>,
+    /// Number of distinct requests whose matched route is cached, caching is
+    /// disabled if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_cache: Option<usize>,
+    /// Size limit in bytes of fixture file contents kept in memory, 64 MiB if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixture_cache: Option<usize>,
+    /// Number of looked up requests kept in the request history, 1000 if not set
+    /// and recording is disabled if 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<usize>,
+    /// Include debugging details in generated responses, like the routes closest
+    /// to an unmatched request in 404 bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<bool>,
+}
+
+/// CORS settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct CorsSettings {
+    /// Allowed origins (`*` allows any origin)
+    #[serde(default = "default_cors_origins")]
+    pub origins: Vec<String>,
+    /// Allowed request headers, any header is allowed if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+    /// Allow credentials (cookies, authorization headers)
+    #[serde(default)]
+    pub credentials: bool,
+}
+
+impl Default for CorsSettings {
+    fn default() -> Self {
+        Self {
+            origins: default_cors_origins(),
+            headers: Vec::new(),
+            credentials: false,
+        }
+    }
+}
+
+fn default_cors_origins() -> Vec<String> {
+    vec!["*".to_owned()]
+}
+
+/// Config file watching settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct WatchSettings {
+    /// Reload config files on changes
+    #[serde(default = "default_watch_enabled")]
+    pub enabled: bool,
+    /// Polling interval in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_watch_enabled(),
+            interval_ms: None,
+        }
+    }
+}
+
+fn default_watch_enabled() -> bool {
+    true
+}
+
+/// Body size limits in bytes, unlimited if not set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct BodyLimits {
+    /// Maximum size of request bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<u64>,
+    /// Maximum size of response bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("default_collection: base", Some("base"))]
+    #[case("{}", None)]
+    fn test_settings_deserialize(#[case] yaml: &str, #[case] expected: Option<&str>) {
+        let settings: Settings = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(settings.default_collection.as_deref(), expected);
+    }
+
+    #[rstest]
+    fn test_settings_deserialize_all() {
+        let settings: Settings = serde_yaml::from_str(
+            "port: 3100
+delay: 250
+cors:
+  headers: [x-api-key]
+watch:
+  interval_ms: 1000
+body_limits:
+  request: 1048576",
+        )
+        .unwrap();
+
+        assert_eq!(settings.port, Some(3100));
+        assert_eq!(settings.delay, Some(250));
+        assert_eq!(
+            settings.cors,
+            Some(CorsSettings {
+                origins: vec!["*".to_owned()],
+                headers: vec!["x-api-key".to_owned()],
+                credentials: false,
+            })
+        );
+        assert_eq!(
+            settings.watch,
+            Some(WatchSettings {
+                enabled: true,
+                interval_ms: Some(1000),
+            })
+        );
+        assert_eq!(
+            settings.body_limits,
+            Some(BodyLimits {
+                request: Some(1048576),
+                response: None,
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("port: 70000")]
+    #[case("cors: {origin: '*'}")]
+    #[case("watch: {interval: 100}")]
+    fn test_settings_deserialize_invalid(#[case] yaml: &str) {
+        assert!(serde_yaml::from_str::<Settings>(yaml).is_err());
+    }
+}
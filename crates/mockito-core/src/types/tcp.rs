@@ -0,0 +1,31 @@
+//! Raw TCP message matching types.
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Client messages answered by a TCP route.
+///
+/// Each message read from a connection is matched against the routes listening
+/// on the address, so every command of a line protocol gets its own route:
+///
+/// ```yaml
+/// url: 127.0.0.1:2525
+/// transport: TCP
+/// tcp:
+///   prefix: "MAIL FROM:"
+/// ```
+///
+/// Both conditions must hold if both are set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TcpMatch {
+    /// Text the message starts with, compared byte by byte
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Regex searched in the message bytes, `(?-u)` allows matching non-UTF-8
+    /// bytes like `\xff`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
@@ -1,22 +1,49 @@
 //! Response variant types.
 
+use crate::config::fixtures::{fixture_path, inline_fixture, InlineFixture};
+use crate::template::Engine;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 /// Response variant for a preset.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct Variant {
     /// Unique identifier for this variant within the preset
-    pub id: String,
+    pub id: Arc<str>,
     /// HTTP status code for the response (100-599)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "json-schema", schemars(range(min = 100, max = 599)))]
     pub status: Option<u16>,
     /// Response headers
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub headers: Option<HashMap<String, String>>,
+    pub headers: Option<HashMap<Arc<str>, String>>,
     /// Response body (JSON)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<serde_json::Value>,
+    /// Template engine rendering headers and body, the project default if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<Engine>,
+}
+
+impl Variant {
+    /// Get the fixture file referenced by a `file:` body, if any.
+    ///
+    /// Paths of variants loaded from config files are canonical, see
+    /// [`crate::config::fixtures`].
+    pub fn body_file(&self) -> Option<&Path> {
+        self.body.as_ref().and_then(fixture_path)
+    }
+
+    /// Get the decoded content of a `file:data:` body, if any.
+    pub fn body_data(&self) -> Option<InlineFixture> {
+        self.body.as_ref().and_then(inline_fixture)
+    }
 }
 
 #[cfg(test)]
@@ -28,14 +55,15 @@ mod tests {
     #[rstest]
     fn test_variant_serialize_deserialize() {
         let variant = Variant {
-            id: "test-variant".to_string(),
+            id: "test-variant".into(),
             status: Some(200),
             headers: Some({
                 let mut map = HashMap::new();
-                map.insert("Content-Type".to_string(), "application/json".to_string());
+                map.insert("Content-Type".into(), "application/json".to_string());
                 map
             }),
             body: Some(json!({"message": "success"})),
+            template: None,
         };
 
         let json = serde_json::to_string(&variant).expect("Should serialize");
@@ -47,16 +75,56 @@ mod tests {
         assert_eq!(deserialized.body, variant.body);
     }
 
+    #[rstest]
+    #[case(Some(json!("file:/fixtures/users.json")), Some("/fixtures/users.json"))]
+    #[case(Some(json!({"file": "users.json"})), None)]
+    #[case(None, None)]
+    fn test_variant_body_file(
+        #[case] body: Option<serde_json::Value>,
+        #[case] expected: Option<&str>,
+    ) {
+        let variant = Variant {
+            id: "v".into(),
+            status: None,
+            headers: None,
+            body,
+            template: None,
+        };
+        assert_eq!(variant.body_file(), expected.map(Path::new));
+    }
+
+    #[rstest]
+    #[case(Some(json!("file:data:text/plain;base64,aGk=")), Some(b"hi".as_slice()))]
+    #[case(Some(json!("file:/fixtures/users.json")), None)]
+    #[case(None, None)]
+    fn test_variant_body_data(
+        #[case] body: Option<serde_json::Value>,
+        #[case] expected: Option<&[u8]>,
+    ) {
+        let variant = Variant {
+            id: "v".into(),
+            status: None,
+            headers: None,
+            body,
+            template: None,
+        };
+        assert_eq!(
+            variant.body_data().map(|f| f.data),
+            expected.map(<[u8]>::to_vec)
+        );
+    }
+
     #[rstest]
     #[case("status")]
     #[case("headers")]
     #[case("body")]
     fn test_variant_optional_fields_omitted_when_none(#[case] field: &str) {
         let variant = Variant {
-            id: "minimal-variant".to_string(),
+            id: "minimal-variant".into(),
             status: None,
             headers: None,
             body: None,
+            template: None,
         };
 
         let json = serde_json::to_string(&variant).expect("Should serialize");
@@ -82,10 +150,11 @@ mod tests {
     #[case(503)]
     fn test_variant_status_codes(#[case] status: u16) {
         let variant = Variant {
-            id: "test".to_string(),
+            id: "test".into(),
             status: Some(status),
             headers: None,
             body: None,
+            template: None,
         };
 
         let json = serde_json::to_string(&variant).expect("Should serialize");
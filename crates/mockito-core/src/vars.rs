@@ -0,0 +1,91 @@
+//! Collection variables substitution.
+//!
+//! Collections may declare variables that are substituted into route URLs,
+//! response headers and bodies using `{{vars.name}}` placeholders.
+
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Collection variables by name.
+pub type Vars = HashMap<String, Value>;
+
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*vars\.([A-Za-z0-9_-]+)\s*\}\}").expect("valid regex"));
+
+/// Substitute variable placeholders in a string.
+///
+/// String variables are inserted as is, other values as their JSON representation.
+/// Placeholders of undefined variables are left untouched.
+pub fn substitute_str(s: &str, vars: &Vars) -> String {
+    PLACEHOLDER
+        .replace_all(s, |caps: &Captures| match vars.get(&caps[1]) {
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Substitute variable placeholders in all strings of a JSON value.
+///
+/// A string consisting of a single placeholder is replaced by the variable value
+/// itself, so non-string variables keep their type (e.g. `"{{vars.limit}}"` -> `10`).
+pub fn substitute_value(value: &Value, vars: &Vars) -> Value {
+    match value {
+        Value::String(s) => {
+            if let Some(caps) = PLACEHOLDER.captures(s) {
+                if caps[0].len() == s.len() {
+                    if let Some(var) = vars.get(&caps[1]) {
+                        return var.clone();
+                    }
+                }
+            }
+            Value::String(substitute_str(s, vars))
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(|v| substitute_value(v, vars)).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), substitute_value(v, vars)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn create_vars() -> Vars {
+        HashMap::from([
+            ("tenant".to_string(), json!("acme")),
+            ("limit".to_string(), json!(10)),
+        ])
+    }
+
+    #[rstest]
+    #[case("/api/{{vars.tenant}}/users", "/api/acme/users")]
+    #[case("/api/{{ vars.tenant }}/users/{id}", "/api/acme/users/{id}")]
+    #[case("limit={{vars.limit}}", "limit=10")]
+    #[case("{{vars.unknown}}", "{{vars.unknown}}")]
+    #[case("no placeholders", "no placeholders")]
+    fn test_substitute_str(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(substitute_str(input, &create_vars()), expected);
+    }
+
+    #[rstest]
+    #[case(json!("{{vars.limit}}"), json!(10))]
+    #[case(json!("max {{vars.limit}}"), json!("max 10"))]
+    #[case(
+        json!({"tenant": "{{vars.tenant}}", "items": ["{{vars.limit}}", 1]}),
+        json!({"tenant": "acme", "items": [10, 1]})
+    )]
+    #[case(json!(true), json!(true))]
+    fn test_substitute_value(#[case] input: Value, #[case] expected: Value) {
+        assert_eq!(substitute_value(&input, &create_vars()), expected);
+    }
+}
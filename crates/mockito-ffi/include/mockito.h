@@ -0,0 +1,63 @@
+/*
+ * C ABI for Mockito core library.
+ *
+ * Strings are NUL-terminated UTF-8, requests and results are JSON text.
+ * Failed calls return NULL or -1, and mockito_last_error() describes the
+ * failure on the calling thread. Returned strings are released with
+ * mockito_string_free(), controllers with mockito_controller_free().
+ */
+
+#ifndef MOCKITO_H
+#define MOCKITO_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Controller of active routes */
+typedef struct MockitoController MockitoController;
+
+/* Library version, must not be freed */
+const char *mockito_version(void);
+
+/* Message of the last failed call on this thread or NULL, valid until the next call */
+const char *mockito_last_error(void);
+
+/* Release a string returned by the library */
+void mockito_string_free(char *s);
+
+/* Load a controller from collection, route and optional group files (paths may be globs).
+ * groups_path and default_collection may be NULL. */
+MockitoController *mockito_controller_load(const char *collections_path,
+                                           const char *routes_path,
+                                           const char *groups_path,
+                                           const char *default_collection);
+
+/* Load a controller from a project directory or combined config file */
+MockitoController *mockito_controller_load_project(const char *path);
+
+/* Release a controller */
+void mockito_controller_free(MockitoController *controller);
+
+/* Switch to a collection, returns 0 or -1 */
+int mockito_controller_use_collection(MockitoController *controller, const char *collection_id);
+
+/* Activate a JSON array of "route:preset:variant" references, returns 0 or -1 */
+int mockito_controller_use_routes(MockitoController *controller, const char *routes_json);
+
+/* Go back to the routes of the collection, returns 0 or -1 */
+int mockito_controller_reset_routes(MockitoController *controller);
+
+/* Find the route matching a JSON request {"url", "method", "transport", "headers"?, "query"?, "payload"?}.
+ * Returns {"route", "preset", "variant"} JSON, "null" if nothing matches, or NULL on failure. */
+char *mockito_controller_find_route(MockitoController *controller, const char *request_json);
+
+/* Find the route matching a JSON request and render its variant.
+ * Returns the variant JSON, "null" if nothing matches, or NULL on failure. */
+char *mockito_controller_respond(MockitoController *controller, const char *request_json);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MOCKITO_H */
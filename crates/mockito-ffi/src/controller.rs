@@ -0,0 +1,552 @@
+//! Controller functions: load mocks, switch routes and match requests.
+
+use crate::error::{clear_last_error, read_optional_str, read_str, set_last_error};
+use mockito_core::config::{parser, project};
+use mockito_core::mocks::controller::{
+    MocksController as CoreMocksController, Request as CoreRequest,
+};
+use mockito_core::mocks::manager::{
+    ActiveRoute as CoreActiveRoute, MocksManager as CoreMocksManager,
+};
+use mockito_core::types::preset::Preset;
+use mockito_core::types::route::{HttpMethod, Route, Transport};
+use mockito_core::types::variant::Variant;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// Controller of active routes, opaque to C
+pub struct MockitoController {
+    inner: CoreMocksController,
+}
+
+/// Request matched against active routes, passed as JSON
+#[derive(Deserialize)]
+struct Request {
+    /// Request URL (path + query string)
+    url: String,
+    /// HTTP method (required for HTTP routes)
+    method: Option<HttpMethod>,
+    transport: Transport,
+    headers: Option<HashMap<String, String>>,
+    /// Query parameters (parsed from URL if not provided)
+    query: Option<HashMap<String, String>>,
+    payload: Option<Value>,
+}
+
+impl From<Request> for CoreRequest {
+    fn from(r: Request) -> Self {
+        Self {
+            url: r.url,
+            method: r.method,
+            transport: r.transport,
+            headers: r.headers,
+            query: r.query,
+            payload: r.payload,
+        }
+    }
+}
+
+/// Route with selected preset and variant, returned as `{"route", "preset", "variant"}`
+#[derive(Serialize)]
+struct ActiveRoute<'a> {
+    route: &'a Route,
+    preset: &'a Preset,
+    variant: &'a Variant,
+}
+
+impl<'a> From<&'a CoreActiveRoute> for ActiveRoute<'a> {
+    fn from(a: &'a CoreActiveRoute) -> Self {
+        Self {
+            route: &*a.route,
+            preset: a.preset(),
+            variant: a.variant(),
+        }
+    }
+}
+
+/// Run the body of an exported function.
+///
+/// Errors and panics are recorded for `mockito_last_error()` and turned into
+/// `failed`, since neither may cross the C boundary.
+fn call<T>(failed: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    clear_last_error();
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            failed
+        }
+        Err(_) => {
+            set_last_error("Internal error: mockito panicked");
+            failed
+        }
+    }
+}
+
+/// Borrow the controller behind a pointer.
+///
+/// # Safety
+/// `controller` must be `NULL` or returned by a `mockito_controller_load*` function.
+unsafe fn borrow_controller<'a>(
+    controller: *mut MockitoController,
+) -> Result<&'a mut CoreMocksController, String> {
+    controller
+        .as_mut()
+        .map(|controller| &mut controller.inner)
+        .ok_or_else(|| "controller is NULL".to_string())
+}
+
+/// Borrow the controller behind a pointer for a call that doesn't change it.
+///
+/// Such calls may run concurrently on the same controller, so they must not
+/// create a mutable reference.
+///
+/// # Safety
+/// `controller` must be `NULL` or returned by a `mockito_controller_load*` function.
+unsafe fn borrow_controller_ref<'a>(
+    controller: *const MockitoController,
+) -> Result<&'a CoreMocksController, String> {
+    controller
+        .as_ref()
+        .map(|controller| &controller.inner)
+        .ok_or_else(|| "controller is NULL".to_string())
+}
+
+/// Serialize a result into a string owned by the caller.
+fn to_json<T: Serialize>(value: &T) -> Result<*mut c_char, String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    CString::new(json)
+        .map(CString::into_raw)
+        .map_err(|e| e.to_string())
+}
+
+fn into_handle(inner: CoreMocksController) -> *mut MockitoController {
+    Box::into_raw(Box::new(MockitoController { inner }))
+}
+
+/// Load a controller from collection, route and optional group files.
+///
+/// Paths may be glob patterns. `groups_path` and `default_collection` may be `NULL`.
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// Arguments must be `NULL` or valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_load(
+    collections_path: *const c_char,
+    routes_path: *const c_char,
+    groups_path: *const c_char,
+    default_collection: *const c_char,
+) -> *mut MockitoController {
+    call(ptr::null_mut(), || {
+        let collections_path = read_str(collections_path, "collections_path")?;
+        let routes_path = read_str(routes_path, "routes_path")?;
+        let groups_path = read_optional_str(groups_path, "groups_path")?;
+        let default_collection = read_optional_str(default_collection, "default_collection")?;
+
+        let routes = parser::load_routes(routes_path)
+            .map_err(|e| format!("Failed to load routes: {}", e))?;
+        let collections = parser::load_collections(collections_path)
+            .map_err(|e| format!("Failed to load collections: {}", e))?;
+        let groups = match groups_path {
+            Some(path) => {
+                parser::load_groups(path).map_err(|e| format!("Failed to load groups: {}", e))?
+            }
+            None => Vec::new(),
+        };
+        let mut manager = CoreMocksManager::new();
+        manager
+            .add_routes(routes)
+            .and_then(|_| manager.add_collections(collections))
+            .and_then(|_| manager.add_groups(groups))
+            .map_err(|e| e.to_string())?;
+
+        let mut inner = CoreMocksController::new(manager);
+        if let Some(collection_id) = default_collection {
+            inner
+                .use_collection(collection_id)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(into_handle(inner))
+    })
+}
+
+/// Load a controller from a project directory or combined config file.
+///
+/// Activates the default collection from the `mockito.config.*` settings file.
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// `path` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_load_project(
+    path: *const c_char,
+) -> *mut MockitoController {
+    call(ptr::null_mut(), || {
+        let path = read_str(path, "path")?;
+        let project =
+            project::load_project(path).map_err(|e| format!("Failed to load project: {}", e))?;
+        let inner = CoreMocksController::from_project(project).map_err(|e| e.to_string())?;
+        Ok(into_handle(inner))
+    })
+}
+
+/// Release a controller.
+///
+/// # Safety
+/// `controller` must be `NULL` or a controller that isn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_free(controller: *mut MockitoController) {
+    if !controller.is_null() {
+        drop(Box::from_raw(controller));
+    }
+}
+
+/// Switch to a collection.
+///
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `controller` must be a live controller and `collection_id` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_use_collection(
+    controller: *mut MockitoController,
+    collection_id: *const c_char,
+) -> c_int {
+    call(-1, || {
+        let inner = borrow_controller(controller)?;
+        let collection_id = read_str(collection_id, "collection_id")?;
+        inner
+            .use_collection(collection_id)
+            .map_err(|e| e.to_string())?;
+        Ok(0)
+    })
+}
+
+/// Activate routes on top of the collection.
+///
+/// `routes_json` is a JSON array of `route:preset:variant` references.
+///
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `controller` must be a live controller and `routes_json` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_use_routes(
+    controller: *mut MockitoController,
+    routes_json: *const c_char,
+) -> c_int {
+    call(-1, || {
+        let inner = borrow_controller(controller)?;
+        let routes: Vec<String> = serde_json::from_str(read_str(routes_json, "routes_json")?)
+            .map_err(|e| format!("Invalid routes: {}", e))?;
+        inner.use_routes(&routes).map_err(|e| e.to_string())?;
+        Ok(0)
+    })
+}
+
+/// Drop route overrides and go back to the routes of the collection.
+///
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `controller` must be a live controller.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_reset_routes(
+    controller: *mut MockitoController,
+) -> c_int {
+    call(-1, || {
+        borrow_controller(controller)?
+            .reset_routes()
+            .map_err(|e| e.to_string())?;
+        Ok(0)
+    })
+}
+
+/// Find the active route matching a request.
+///
+/// `request_json` is an object of `url`, `method`, `transport` and optional
+/// `headers`, `query` and `payload`.
+///
+/// Returns `{"route", "preset", "variant"}` JSON, `null` JSON if no route
+/// matches, or `NULL` on failure.
+///
+/// # Safety
+/// `controller` must be a live controller and `request_json` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_find_route(
+    controller: *mut MockitoController,
+    request_json: *const c_char,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let inner = borrow_controller_ref(controller)?;
+        let request = read_request(request_json)?;
+        to_json(&inner.find_route(&request).map(ActiveRoute::from))
+    })
+}
+
+/// Find the active route matching a request and render its variant.
+///
+/// Returns the rendered variant JSON, `null` JSON if no route matches, or
+/// `NULL` on failure, e.g. if a template fails to render.
+///
+/// # Safety
+/// `controller` must be a live controller and `request_json` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_controller_respond(
+    controller: *mut MockitoController,
+    request_json: *const c_char,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let inner = borrow_controller_ref(controller)?;
+        let request = read_request(request_json)?;
+        let variant = inner.respond(&request).map_err(|e| e.to_string())?;
+        to_json(&variant)
+    })
+}
+
+/// Parse a request argument.
+///
+/// # Safety
+/// `request_json` must be `NULL` or a valid NUL-terminated string.
+unsafe fn read_request(request_json: *const c_char) -> Result<CoreRequest, String> {
+    serde_json::from_str::<Request>(read_str(request_json, "request_json")?)
+        .map(Into::into)
+        .map_err(|e| format!("Invalid request: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::mockito_last_error;
+    use crate::mockito_string_free;
+    use rstest::rstest;
+    use std::ffi::CStr;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+
+    const ROUTES: &str = r#"
+- id: users
+  url: /api/users
+  method: GET
+  transport: HTTP
+  presets:
+    - id: default
+      variants:
+        - id: ok
+          status: 200
+          body: [{"id": 1}]
+        - id: error
+          status: 500
+"#;
+
+    const COLLECTIONS: &str = "- id: base\n  routes: [users:default:ok]";
+
+    /// Directory with the route and collection files, removed on drop
+    struct MockFiles(PathBuf);
+
+    impl MockFiles {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("mockito-ffi-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("routes.yaml"), ROUTES).unwrap();
+            fs::write(dir.join("collections.yaml"), COLLECTIONS).unwrap();
+            Self(dir)
+        }
+
+        fn path(&self, file: &str) -> CString {
+            CString::new(self.0.join(file).to_str().unwrap()).unwrap()
+        }
+    }
+
+    impl Drop for MockFiles {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn load(files: &MockFiles) -> *mut MockitoController {
+        let collection = CString::new("base").unwrap();
+        let controller = unsafe {
+            mockito_controller_load(
+                files.path("collections.yaml").as_ptr(),
+                files.path("routes.yaml").as_ptr(),
+                ptr::null(),
+                collection.as_ptr(),
+            )
+        };
+        assert!(!controller.is_null(), "{:?}", last_error());
+        controller
+    }
+
+    fn last_error() -> Option<String> {
+        let message = mockito_last_error();
+        (!message.is_null()).then(|| {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    /// Take a JSON string returned by the library.
+    fn take_json(s: *mut c_char) -> Value {
+        assert!(!s.is_null(), "{:?}", last_error());
+        let value = serde_json::from_slice(unsafe { CStr::from_ptr(s) }.to_bytes()).unwrap();
+        unsafe { mockito_string_free(s) };
+        value
+    }
+
+    fn request(url: &str) -> CString {
+        CString::new(format!(
+            r#"{{"url": "{}", "method": "GET", "transport": "HTTP"}}"#,
+            url
+        ))
+        .unwrap()
+    }
+
+    #[rstest]
+    fn test_load_use_routes_respond() {
+        let files = MockFiles::new("respond");
+        let controller = load(&files);
+        let users = request("/api/users");
+
+        let variant = take_json(unsafe { mockito_controller_respond(controller, users.as_ptr()) });
+        assert_eq!(variant["id"], "ok");
+        assert_eq!(variant["body"], serde_json::json!([{"id": 1}]));
+
+        let routes = CString::new(r#"["users:default:error"]"#).unwrap();
+        assert_eq!(
+            unsafe { mockito_controller_use_routes(controller, routes.as_ptr()) },
+            0
+        );
+        let active =
+            take_json(unsafe { mockito_controller_find_route(controller, users.as_ptr()) });
+        assert_eq!(active["route"]["id"], "users");
+        assert_eq!(active["variant"]["id"], "error");
+
+        assert_eq!(unsafe { mockito_controller_reset_routes(controller) }, 0);
+        let variant = take_json(unsafe { mockito_controller_respond(controller, users.as_ptr()) });
+        assert_eq!(variant["id"], "ok");
+
+        let missing = request("/api/missing");
+        let variant =
+            take_json(unsafe { mockito_controller_respond(controller, missing.as_ptr()) });
+        assert_eq!(variant, Value::Null);
+        assert_eq!(last_error(), None);
+
+        unsafe { mockito_controller_free(controller) };
+    }
+
+    #[rstest]
+    fn test_concurrent_lookups() {
+        let files = MockFiles::new("concurrent");
+        // Raw pointers aren't `Send`, the address is shared instead
+        let controller = load(&files) as usize;
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(move || {
+                    let users = request("/api/users");
+                    for _ in 0..50 {
+                        let controller = controller as *mut MockitoController;
+                        let active = take_json(unsafe {
+                            mockito_controller_find_route(controller, users.as_ptr())
+                        });
+                        assert_eq!(active["variant"]["id"], "ok");
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        unsafe { mockito_controller_free(controller as *mut MockitoController) };
+    }
+
+    #[rstest]
+    fn test_null_arguments() {
+        let files = MockFiles::new("null");
+        let users = request("/api/users");
+
+        let controller = unsafe {
+            mockito_controller_load(
+                ptr::null(),
+                files.path("routes.yaml").as_ptr(),
+                ptr::null(),
+                ptr::null(),
+            )
+        };
+        assert!(controller.is_null());
+        assert_eq!(last_error().as_deref(), Some("collections_path is NULL"));
+
+        let found = unsafe { mockito_controller_find_route(ptr::null_mut(), users.as_ptr()) };
+        assert!(found.is_null());
+        assert_eq!(last_error().as_deref(), Some("controller is NULL"));
+
+        assert_eq!(
+            unsafe { mockito_controller_reset_routes(ptr::null_mut()) },
+            -1
+        );
+        assert_eq!(last_error().as_deref(), Some("controller is NULL"));
+
+        let controller = load(&files);
+        let variant = unsafe { mockito_controller_respond(controller, ptr::null()) };
+        assert!(variant.is_null());
+        assert_eq!(last_error().as_deref(), Some("request_json is NULL"));
+
+        // Freeing NULL is a no-op
+        unsafe { mockito_controller_free(ptr::null_mut()) };
+        unsafe { mockito_controller_free(controller) };
+    }
+
+    #[rstest]
+    fn test_last_error() {
+        let files = MockFiles::new("last_error");
+        let controller = load(&files);
+
+        let collection = CString::new("missing").unwrap();
+        assert_eq!(
+            unsafe { mockito_controller_use_collection(controller, collection.as_ptr()) },
+            -1
+        );
+        assert!(last_error().is_some_and(|message| message.contains("missing")));
+
+        let routes = CString::new("not json").unwrap();
+        assert_eq!(
+            unsafe { mockito_controller_use_routes(controller, routes.as_ptr()) },
+            -1
+        );
+        assert!(last_error().is_some_and(|message| message.starts_with("Invalid routes")));
+
+        // Successful calls clear the error
+        let collection = CString::new("base").unwrap();
+        assert_eq!(
+            unsafe { mockito_controller_use_collection(controller, collection.as_ptr()) },
+            0
+        );
+        assert_eq!(last_error(), None);
+
+        fs::write(files.0.join("broken.yaml"), "- id: [").unwrap();
+        let broken = unsafe {
+            mockito_controller_load(
+                files.path("collections.yaml").as_ptr(),
+                files.path("broken.yaml").as_ptr(),
+                ptr::null(),
+                ptr::null(),
+            )
+        };
+        assert!(broken.is_null());
+        assert!(last_error().is_some_and(|message| message.starts_with("Failed to load routes")));
+
+        unsafe { mockito_controller_free(controller) };
+    }
+}
@@ -0,0 +1,65 @@
+//! Error reporting through `mockito_last_error()`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt::Display;
+use std::ptr;
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Record the error of a failed call.
+pub(crate) fn set_last_error(error: impl Display) {
+    // Interior NULs would truncate the message, so they're dropped
+    let message = error.to_string().replace('\0', "");
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Forget the error of an earlier call, at the start of every call.
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Read a string argument.
+///
+/// # Safety
+/// `s` must be `NULL` or a valid NUL-terminated string.
+pub(crate) unsafe fn read_str<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| format!("{} isn't valid UTF-8: {}", name, e))
+}
+
+/// Read an optional string argument, `NULL` meaning none.
+///
+/// # Safety
+/// `s` must be `NULL` or a valid NUL-terminated string.
+pub(crate) unsafe fn read_optional_str<'a>(
+    s: *const c_char,
+    name: &str,
+) -> Result<Option<&'a str>, String> {
+    if s.is_null() {
+        Ok(None)
+    } else {
+        read_str(s, name).map(Some)
+    }
+}
+
+/// Message of the last failed call on the calling thread, or `NULL`.
+///
+/// The string is owned by the library and valid until the next call on the
+/// same thread, it must not be freed.
+#[no_mangle]
+pub extern "C" fn mockito_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
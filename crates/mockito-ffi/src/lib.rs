@@ -0,0 +1,39 @@
+//! C ABI for Mockito core library.
+//!
+//! Lets test harnesses in other languages (Go, C++) load mocks and match
+//! requests without Node.js. Declarations are in `include/mockito.h`, Bun and
+//! Deno load the library through `bindings/ffi_binding`.
+//!
+//! Conventions of all functions:
+//! - Strings are NUL-terminated UTF-8. Requests and results are JSON text.
+//! - Failed calls return `NULL` or `-1`, and `mockito_last_error()` describes
+//!   the failure on the calling thread.
+//! - Returned strings are owned by the caller and released with
+//!   `mockito_string_free()`, controllers with `mockito_controller_free()`.
+//! - Controllers may be shared between threads, as long as calls changing the
+//!   active routes don't run concurrently with other calls on the same controller.
+
+mod controller;
+mod error;
+
+pub use controller::*;
+pub use error::*;
+
+use std::ffi::{c_char, CString};
+
+/// Library version, a static string that must not be freed.
+#[no_mangle]
+pub extern "C" fn mockito_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Release a string returned by the library.
+///
+/// # Safety
+/// `s` must be `NULL` or a string returned by the library that isn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mockito_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
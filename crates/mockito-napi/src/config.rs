@@ -1,15 +1,52 @@
 //! Config parsing bindings for Node.js.
 
-use mockito_core::expression::is_expression;
+use mockito_core::config::fixtures::{inline_fixture_body, BINARY_MEDIA_TYPE};
+use mockito_core::config::json_schema::{self, ConfigFileKind as CoreConfigFileKind};
+use mockito_core::expression::{is_expression, EvaluationLimits as CoreEvaluationLimits};
+use mockito_core::template::Engine as CoreTemplateEngine;
 use mockito_core::types::{
     collection::Collection as CoreCollection,
+    graphql::{GraphqlOperation as CoreGraphqlOperation, OperationType as CoreOperationType},
+    group::Group as CoreGroup,
     preset::{HeadersOrExpression, PayloadOrExpression, Preset as CorePreset, QueryOrExpression},
+    proto::ProtoBinding as CoreProtoBinding,
     route::{HttpMethod as CoreHttpMethod, Route as CoreRoute, Transport as CoreTransport},
+    settings::{
+        BodyLimits as CoreBodyLimits, CorsSettings as CoreCorsSettings, Settings as CoreSettings,
+        WatchSettings as CoreWatchSettings,
+    },
+    tcp::TcpMatch as CoreTcpMatch,
     variant::Variant as CoreVariant,
 };
+use napi::bindgen_prelude::Buffer;
 use napi_derive::napi;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Get the `Content-Type` header, ignoring the case of header names.
+pub(crate) fn content_type(headers: Option<&HashMap<String, String>>) -> Option<&str> {
+    headers?
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Convert pooled header names of core types to owned strings.
+fn to_js_headers(headers: HashMap<Arc<str>, String>) -> HashMap<String, String> {
+    headers
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+/// Convert header names to the shared strings used by core types.
+fn from_js_headers(headers: HashMap<String, String>) -> HashMap<Arc<str>, String> {
+    headers
+        .into_iter()
+        .map(|(name, value)| (name.into(), value))
+        .collect()
+}
 
 /// Transport type for route matching
 #[napi]
@@ -17,6 +54,11 @@ use std::collections::HashMap;
 pub enum Transport {
     Http,
     WebSocket,
+    /// Server-Sent Events, streaming the event scripts of variant bodies
+    Sse,
+    /// Raw TCP, answering messages sent to the URL address with the reply
+    /// scripts of variant bodies
+    Tcp,
 }
 
 impl From<CoreTransport> for Transport {
@@ -24,6 +66,8 @@ impl From<CoreTransport> for Transport {
         match t {
             CoreTransport::Http => Transport::Http,
             CoreTransport::WebSocket => Transport::WebSocket,
+            CoreTransport::Sse => Transport::Sse,
+            CoreTransport::Tcp => Transport::Tcp,
         }
     }
 }
@@ -33,6 +77,8 @@ impl From<Transport> for CoreTransport {
         match t {
             Transport::Http => CoreTransport::Http,
             Transport::WebSocket => CoreTransport::WebSocket,
+            Transport::Sse => CoreTransport::Sse,
+            Transport::Tcp => CoreTransport::Tcp,
         }
     }
 }
@@ -78,93 +124,265 @@ impl From<HttpMethod> for CoreHttpMethod {
     }
 }
 
+/// Engine rendering templates in variant headers and body
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateEngine {
+    Handlebars,
+    Liquid,
+    /// `${expression}` placeholders
+    Interpolation,
+}
+
+impl From<CoreTemplateEngine> for TemplateEngine {
+    fn from(e: CoreTemplateEngine) -> Self {
+        match e {
+            CoreTemplateEngine::Handlebars => TemplateEngine::Handlebars,
+            CoreTemplateEngine::Liquid => TemplateEngine::Liquid,
+            CoreTemplateEngine::Interpolation => TemplateEngine::Interpolation,
+        }
+    }
+}
+
+impl From<TemplateEngine> for CoreTemplateEngine {
+    fn from(e: TemplateEngine) -> Self {
+        match e {
+            TemplateEngine::Handlebars => CoreTemplateEngine::Handlebars,
+            TemplateEngine::Liquid => CoreTemplateEngine::Liquid,
+            TemplateEngine::Interpolation => CoreTemplateEngine::Interpolation,
+        }
+    }
+}
+
 /// Response variant
 #[napi(object)]
-#[derive(Clone)]
 pub struct Variant {
     pub id: String,
     pub status: Option<u32>,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<serde_json::Value>,
+    /// Binary body, takes precedence over `body`.
+    ///
+    /// Set for inline `file:data:` bodies, and by `MocksController.respond()` for
+    /// `file:` fixtures. Stored as a `file:data:` body with the `Content-Type` header.
+    pub body_buffer: Option<Buffer>,
+    /// Template engine of headers and body, the project default if not set
+    pub template: Option<TemplateEngine>,
+}
+
+// `Buffer` isn't `Clone`, the copy owns its bytes
+impl Clone for Variant {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            status: self.status,
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            body_buffer: self.body_buffer.as_deref().map(Buffer::from),
+            template: self.template,
+        }
+    }
 }
 
 impl From<CoreVariant> for Variant {
     fn from(v: CoreVariant) -> Self {
         Self {
-            id: v.id,
+            body_buffer: v.body_data().map(|fixture| Buffer::from(fixture.data)),
+            id: v.id.to_string(),
             status: v.status.map(|s| s as u32),
-            headers: v.headers,
+            headers: v.headers.map(to_js_headers),
             body: v.body,
+            template: v.template.map(Into::into),
         }
     }
 }
 
 impl From<&CoreVariant> for Variant {
     fn from(v: &CoreVariant) -> Self {
-        Self {
-            id: v.id.clone(),
-            status: v.status.map(|s| s as u32),
-            headers: v.headers.clone(),
-            body: v.body.clone(),
-        }
+        Self::from(v.clone())
     }
 }
 
 /// Request matching preset
+///
+/// Fields matched by a map or value and by an expression come in pairs, like
+/// `headers` and `headersExpression`. The expression takes precedence if both are set.
 #[napi(object)]
 #[derive(Clone)]
 pub struct Preset {
     pub id: String,
     pub variants: Vec<Variant>,
-    /// Headers to match (can be an object or expression string like "${headers.myheader == 1}")
-    pub headers: Option<serde_json::Value>,
-    /// Query parameters to match (can be an object or expression string like "${query.page == '1'}")
-    pub query: Option<serde_json::Value>,
+    /// Headers to match
+    pub headers: Option<HashMap<String, String>>,
+    /// Expression matching headers, like `headers.myheader == '1'`
+    pub headers_expression: Option<String>,
+    /// Query parameters to match
+    pub query: Option<HashMap<String, String>>,
+    /// Expression matching query parameters, like `query.page == '1'`
+    pub query_expression: Option<String>,
     pub params: Option<HashMap<String, String>>,
-    /// Payload to match (can be any JSON value or expression string like "${payload.items[0].id == 5}")
+    /// Payload to match, any JSON value
     pub payload: Option<serde_json::Value>,
+    /// Expression matching the payload, like `payload.items[0].id == 5`
+    pub payload_expression: Option<String>,
 }
 
 impl From<CorePreset> for Preset {
     fn from(p: CorePreset) -> Self {
+        let (headers, headers_expression) = match p.headers {
+            Some(HeadersOrExpression::Map(map)) => (Some(map), None),
+            Some(HeadersOrExpression::Expression(expr)) => (None, Some(expr)),
+            None => (None, None),
+        };
+        let (query, query_expression) = match p.query {
+            Some(QueryOrExpression::Map(map)) => (Some(map), None),
+            Some(QueryOrExpression::Expression(expr)) => (None, Some(expr)),
+            None => (None, None),
+        };
+        let (payload, payload_expression) = match p.payload {
+            Some(PayloadOrExpression::Value(value)) => (Some(value), None),
+            Some(PayloadOrExpression::Expression(expr)) => (None, Some(expr)),
+            None => (None, None),
+        };
         Self {
-            id: p.id,
+            id: p.id.to_string(),
             variants: p.variants.into_iter().map(Variant::from).collect(),
-            headers: p.headers.map(|h| match h {
-                HeadersOrExpression::Map(map) => serde_json::to_value(map).unwrap_or(Value::Null),
-                HeadersOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
-            query: p.query.map(|q| match q {
-                QueryOrExpression::Map(map) => serde_json::to_value(map).unwrap_or(Value::Null),
-                QueryOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
+            headers,
+            headers_expression,
+            query,
+            query_expression,
             params: p.params,
-            payload: p.payload.map(|p| match p {
-                PayloadOrExpression::Value(v) => v,
-                PayloadOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
+            payload,
+            payload_expression,
         }
     }
 }
 
 impl From<&CorePreset> for Preset {
     fn from(p: &CorePreset) -> Self {
+        Self::from(p.clone())
+    }
+}
+
+/// Protobuf message types used by a route
+#[napi(object)]
+#[derive(Clone)]
+pub struct ProtoBinding {
+    /// Compiled descriptor set (`.desc`) or `.proto` file
+    pub descriptor: String,
+    /// gRPC method as "package.Service/Method"
+    pub method: Option<String>,
+    /// Fully qualified request message type, overrides the method input
+    pub request: Option<String>,
+    /// Fully qualified response message type, overrides the method output
+    pub response: Option<String>,
+}
+
+impl From<CoreProtoBinding> for ProtoBinding {
+    fn from(p: CoreProtoBinding) -> Self {
+        Self {
+            descriptor: p.descriptor,
+            method: p.method,
+            request: p.request,
+            response: p.response,
+        }
+    }
+}
+
+impl From<ProtoBinding> for CoreProtoBinding {
+    fn from(p: ProtoBinding) -> Self {
+        Self {
+            descriptor: p.descriptor,
+            method: p.method,
+            request: p.request,
+            response: p.response,
+        }
+    }
+}
+
+/// Type of a GraphQL operation
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl From<CoreOperationType> for OperationType {
+    fn from(t: CoreOperationType) -> Self {
+        match t {
+            CoreOperationType::Query => OperationType::Query,
+            CoreOperationType::Mutation => OperationType::Mutation,
+            CoreOperationType::Subscription => OperationType::Subscription,
+        }
+    }
+}
+
+impl From<OperationType> for CoreOperationType {
+    fn from(t: OperationType) -> Self {
+        match t {
+            OperationType::Query => CoreOperationType::Query,
+            OperationType::Mutation => CoreOperationType::Mutation,
+            OperationType::Subscription => CoreOperationType::Subscription,
+        }
+    }
+}
+
+/// GraphQL operation answered by a route
+#[napi(object)]
+#[derive(Clone)]
+pub struct GraphqlOperation {
+    /// Operation type, any if not set
+    #[napi(js_name = "type")]
+    pub operation_type: Option<OperationType>,
+    /// Operation name, any (including anonymous operations) if not set
+    pub name: Option<String>,
+}
+
+impl From<CoreGraphqlOperation> for GraphqlOperation {
+    fn from(o: CoreGraphqlOperation) -> Self {
         Self {
-            id: p.id.clone(),
-            variants: p.variants.iter().map(Variant::from).collect(),
-            headers: p.headers.as_ref().map(|h| match h {
-                HeadersOrExpression::Map(map) => serde_json::to_value(map).unwrap_or(Value::Null),
-                HeadersOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
-            query: p.query.as_ref().map(|q| match q {
-                QueryOrExpression::Map(map) => serde_json::to_value(map).unwrap_or(Value::Null),
-                QueryOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
-            params: p.params.clone(),
-            payload: p.payload.as_ref().map(|p| match p {
-                PayloadOrExpression::Value(v) => v.clone(),
-                PayloadOrExpression::Expression(expr) => Value::String(format!("${{{}}}", expr)),
-            }),
+            operation_type: o.operation_type.map(Into::into),
+            name: o.name,
+        }
+    }
+}
+
+impl From<GraphqlOperation> for CoreGraphqlOperation {
+    fn from(o: GraphqlOperation) -> Self {
+        Self {
+            operation_type: o.operation_type.map(Into::into),
+            name: o.name,
+        }
+    }
+}
+
+/// Client messages answered by a TCP route
+#[napi(object)]
+#[derive(Clone)]
+pub struct TcpMatch {
+    /// Text the message starts with, compared byte by byte
+    pub prefix: Option<String>,
+    /// Regex searched in the message bytes, `(?-u)` allows matching non-UTF-8
+    /// bytes like `\xff`
+    pub pattern: Option<String>,
+}
+
+impl From<CoreTcpMatch> for TcpMatch {
+    fn from(t: CoreTcpMatch) -> Self {
+        Self {
+            prefix: t.prefix,
+            pattern: t.pattern,
+        }
+    }
+}
+
+impl From<TcpMatch> for CoreTcpMatch {
+    fn from(t: TcpMatch) -> Self {
+        Self {
+            prefix: t.prefix,
+            pattern: t.pattern,
         }
     }
 }
@@ -178,16 +396,31 @@ pub struct Route {
     pub transport: Transport,
     pub method: Option<HttpMethod>,
     pub presets: Vec<Preset>,
+    /// Tags grouping this route into functional areas (e.g. "payments")
+    pub tags: Option<Vec<String>>,
+    /// ID of the group providing URL prefix and shared response headers
+    pub group: Option<String>,
+    /// Protobuf message types of requests and responses
+    pub proto: Option<ProtoBinding>,
+    /// GraphQL operation answered by the route, for routes of a GraphQL endpoint
+    pub graphql: Option<GraphqlOperation>,
+    /// Client messages answered by the route, for TCP routes
+    pub tcp: Option<TcpMatch>,
 }
 
 impl From<CoreRoute> for Route {
     fn from(r: CoreRoute) -> Self {
         Self {
-            id: r.id,
+            id: r.id.to_string(),
             url: r.url,
             transport: r.transport.into(),
             method: r.method.map(|m| m.into()),
             presets: r.presets.into_iter().map(Preset::from).collect(),
+            tags: (!r.tags.is_empty()).then_some(r.tags),
+            group: r.group,
+            proto: r.proto.map(ProtoBinding::from),
+            graphql: r.graphql.map(GraphqlOperation::from),
+            tcp: r.tcp.map(TcpMatch::from),
         }
     }
 }
@@ -195,11 +428,16 @@ impl From<CoreRoute> for Route {
 impl From<&CoreRoute> for Route {
     fn from(r: &CoreRoute) -> Self {
         Self {
-            id: r.id.clone(),
+            id: r.id.to_string(),
             url: r.url.clone(),
             transport: r.transport.clone().into(),
             method: r.method.clone().map(|m| m.into()),
             presets: r.presets.iter().map(Preset::from).collect(),
+            tags: (!r.tags.is_empty()).then(|| r.tags.clone()),
+            group: r.group.clone(),
+            proto: r.proto.clone().map(ProtoBinding::from),
+            graphql: r.graphql.clone().map(GraphqlOperation::from),
+            tcp: r.tcp.clone().map(TcpMatch::from),
         }
     }
 }
@@ -210,142 +448,81 @@ impl From<&CoreRoute> for Route {
 
 impl From<Variant> for CoreVariant {
     fn from(v: Variant) -> Self {
+        let body = match &v.body_buffer {
+            Some(data) => {
+                let media_type = content_type(v.headers.as_ref()).unwrap_or(BINARY_MEDIA_TYPE);
+                Some(inline_fixture_body(media_type, data))
+            }
+            None => v.body,
+        };
         Self {
-            id: v.id,
+            id: v.id.into(),
             status: v.status.map(|s| s as u16),
-            headers: v.headers,
-            body: v.body,
+            headers: v.headers.map(from_js_headers),
+            body,
+            template: v.template.map(Into::into),
         }
     }
 }
 
 impl From<&Variant> for CoreVariant {
     fn from(v: &Variant) -> Self {
-        Self {
-            id: v.id.clone(),
-            status: v.status.map(|s| s as u16),
-            headers: v.headers.clone(),
-            body: v.body.clone(),
-        }
+        Self::from(v.clone())
+    }
+}
+
+/// Strip the optional `${}` wrapper of an expression.
+fn unwrap_expression(expr: String) -> String {
+    if !is_expression(&expr) {
+        return expr;
     }
+    expr.strip_prefix("${")
+        .and_then(|s| s.strip_suffix('}'))
+        .map(String::from)
+        .unwrap_or(expr)
 }
 
 impl From<Preset> for CorePreset {
     fn from(p: Preset) -> Self {
         Self {
-            id: p.id,
+            id: p.id.into(),
             variants: p.variants.into_iter().map(CoreVariant::from).collect(),
-            headers: p.headers.map(|v| {
-                if let Value::String(s) = &v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return HeadersOrExpression::Expression(expr.to_string());
-                    }
-                }
-                if let Ok(map) = serde_json::from_value::<HashMap<String, String>>(v) {
-                    HeadersOrExpression::Map(map)
-                } else {
-                    HeadersOrExpression::Map(HashMap::new())
-                }
-            }),
-            query: p.query.map(|v| {
-                if let Value::String(s) = &v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return QueryOrExpression::Expression(expr.to_string());
-                    }
-                }
-                if let Ok(map) = serde_json::from_value::<HashMap<String, String>>(v) {
-                    QueryOrExpression::Map(map)
-                } else {
-                    QueryOrExpression::Map(HashMap::new())
-                }
-            }),
+            headers: match (p.headers_expression, p.headers) {
+                (Some(expr), _) => Some(HeadersOrExpression::Expression(unwrap_expression(expr))),
+                (None, map) => map.map(HeadersOrExpression::Map),
+            },
+            query: match (p.query_expression, p.query) {
+                (Some(expr), _) => Some(QueryOrExpression::Expression(unwrap_expression(expr))),
+                (None, map) => map.map(QueryOrExpression::Map),
+            },
             params: p.params,
-            payload: p.payload.map(|v| {
-                if let Value::String(s) = &v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return PayloadOrExpression::Expression(expr.to_string());
-                    }
-                }
-                PayloadOrExpression::Value(v)
-            }),
+            payload: match (p.payload_expression, p.payload) {
+                (Some(expr), _) => Some(PayloadOrExpression::Expression(unwrap_expression(expr))),
+                (None, value) => value.map(PayloadOrExpression::Value),
+            },
         }
     }
 }
 
 impl From<&Preset> for CorePreset {
     fn from(p: &Preset) -> Self {
-        Self {
-            id: p.id.clone(),
-            variants: p.variants.iter().map(CoreVariant::from).collect(),
-            headers: p.headers.as_ref().map(|v| {
-                if let Value::String(s) = v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return HeadersOrExpression::Expression(expr.to_string());
-                    }
-                }
-                if let Ok(map) = serde_json::from_value::<HashMap<String, String>>(v.clone()) {
-                    HeadersOrExpression::Map(map)
-                } else {
-                    HeadersOrExpression::Map(HashMap::new())
-                }
-            }),
-            query: p.query.as_ref().map(|v| {
-                if let Value::String(s) = v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return QueryOrExpression::Expression(expr.to_string());
-                    }
-                }
-                if let Ok(map) = serde_json::from_value::<HashMap<String, String>>(v.clone()) {
-                    QueryOrExpression::Map(map)
-                } else {
-                    QueryOrExpression::Map(HashMap::new())
-                }
-            }),
-            params: p.params.clone(),
-            payload: p.payload.as_ref().map(|v| {
-                if let Value::String(s) = v {
-                    if is_expression(s) {
-                        let expr = s
-                            .strip_prefix("${")
-                            .and_then(|s| s.strip_suffix('}'))
-                            .unwrap_or(s);
-                        return PayloadOrExpression::Expression(expr.to_string());
-                    }
-                }
-                PayloadOrExpression::Value(v.clone())
-            }),
-        }
+        Self::from(p.clone())
     }
 }
 
 impl From<Route> for CoreRoute {
     fn from(r: Route) -> Self {
         Self {
-            id: r.id,
+            id: r.id.into(),
             url: r.url,
             transport: r.transport.into(),
             method: r.method.map(|m| m.into()),
             presets: r.presets.into_iter().map(CorePreset::from).collect(),
+            tags: r.tags.unwrap_or_default(),
+            group: r.group,
+            proto: r.proto.map(CoreProtoBinding::from),
+            graphql: r.graphql.map(CoreGraphqlOperation::from),
+            tcp: r.tcp.map(CoreTcpMatch::from),
         }
     }
 }
@@ -353,11 +530,16 @@ impl From<Route> for CoreRoute {
 impl From<&Route> for CoreRoute {
     fn from(r: &Route) -> Self {
         Self {
-            id: r.id.clone(),
+            id: r.id.as_str().into(),
             url: r.url.clone(),
             transport: r.transport.into(),
             method: r.method.map(|m| m.into()),
             presets: r.presets.iter().map(CorePreset::from).collect(),
+            tags: r.tags.clone().unwrap_or_default(),
+            group: r.group.clone(),
+            proto: r.proto.clone().map(CoreProtoBinding::from),
+            graphql: r.graphql.clone().map(CoreGraphqlOperation::from),
+            tcp: r.tcp.clone().map(CoreTcpMatch::from),
         }
     }
 }
@@ -369,14 +551,17 @@ pub struct Collection {
     pub id: String,
     pub from: Option<String>,
     pub routes: Vec<String>,
+    /// Variables substituted into route URLs, response headers and bodies via `{{vars.name}}`
+    pub vars: Option<HashMap<String, Value>>,
 }
 
 impl From<CoreCollection> for Collection {
     fn from(c: CoreCollection) -> Self {
         Self {
-            id: c.id,
+            id: c.id.to_string(),
             from: c.from,
-            routes: c.routes,
+            routes: c.routes.iter().map(|r| r.to_string()).collect(),
+            vars: c.vars,
         }
     }
 }
@@ -384,9 +569,10 @@ impl From<CoreCollection> for Collection {
 impl From<&CoreCollection> for Collection {
     fn from(c: &CoreCollection) -> Self {
         Self {
-            id: c.id.clone(),
+            id: c.id.to_string(),
             from: c.from.clone(),
-            routes: c.routes.clone(),
+            routes: c.routes.iter().map(|r| r.to_string()).collect(),
+            vars: c.vars.clone(),
         }
     }
 }
@@ -394,9 +580,10 @@ impl From<&CoreCollection> for Collection {
 impl From<Collection> for CoreCollection {
     fn from(c: Collection) -> Self {
         Self {
-            id: c.id,
+            id: c.id.into(),
             from: c.from,
-            routes: c.routes,
+            routes: c.routes.into_iter().map(Into::into).collect(),
+            vars: c.vars,
         }
     }
 }
@@ -404,9 +591,222 @@ impl From<Collection> for CoreCollection {
 impl From<&Collection> for CoreCollection {
     fn from(c: &Collection) -> Self {
         Self {
-            id: c.id.clone(),
+            id: c.id.as_str().into(),
             from: c.from.clone(),
-            routes: c.routes.clone(),
+            routes: c.routes.iter().map(|r| r.as_str().into()).collect(),
+            vars: c.vars.clone(),
+        }
+    }
+}
+
+/// Group of routes sharing a URL prefix and response headers
+#[napi(object)]
+#[derive(Clone)]
+pub struct Group {
+    pub id: String,
+    /// URL prefix prepended to URLs of member routes (e.g. "/api/v2")
+    pub base_url: Option<String>,
+    /// Response headers shared by member routes (variant headers take precedence)
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl From<&CoreGroup> for Group {
+    fn from(g: &CoreGroup) -> Self {
+        Self {
+            id: g.id.clone(),
+            base_url: g.base_url.clone(),
+            headers: g.headers.clone().map(to_js_headers),
+        }
+    }
+}
+
+impl From<Group> for CoreGroup {
+    fn from(g: Group) -> Self {
+        Self {
+            id: g.id,
+            base_url: g.base_url,
+            headers: g.headers.map(from_js_headers),
+        }
+    }
+}
+
+/// CORS settings
+#[napi(object)]
+#[derive(Clone)]
+pub struct CorsSettings {
+    /// Allowed origins ("*" allows any origin)
+    pub origins: Vec<String>,
+    /// Allowed request headers, any header is allowed if empty
+    pub headers: Vec<String>,
+    /// Allow credentials (cookies, authorization headers)
+    pub credentials: bool,
+}
+
+impl From<&CoreCorsSettings> for CorsSettings {
+    fn from(c: &CoreCorsSettings) -> Self {
+        Self {
+            origins: c.origins.clone(),
+            headers: c.headers.clone(),
+            credentials: c.credentials,
         }
     }
 }
+
+/// Config file watching settings
+#[napi(object)]
+#[derive(Clone)]
+pub struct WatchSettings {
+    /// Reload config files on changes
+    pub enabled: bool,
+    /// Polling interval in milliseconds
+    pub interval_ms: Option<u32>,
+}
+
+impl From<&CoreWatchSettings> for WatchSettings {
+    fn from(w: &CoreWatchSettings) -> Self {
+        Self {
+            enabled: w.enabled,
+            interval_ms: w.interval_ms.map(saturating_u32),
+        }
+    }
+}
+
+/// Body size limits in bytes, unlimited if not set
+#[napi(object)]
+#[derive(Clone)]
+pub struct BodyLimits {
+    /// Maximum size of request bodies
+    pub request: Option<i64>,
+    /// Maximum size of response bodies
+    pub response: Option<i64>,
+}
+
+impl From<&CoreBodyLimits> for BodyLimits {
+    fn from(l: &CoreBodyLimits) -> Self {
+        Self {
+            request: l.request.map(saturating_i64),
+            response: l.response.map(saturating_i64),
+        }
+    }
+}
+
+/// Limits of expression evaluation and template rendering, unlimited if not set
+#[napi(object)]
+#[derive(Clone)]
+pub struct EvaluationLimits {
+    /// Maximum nesting depth of arrays and objects in evaluated data
+    pub max_depth: Option<u32>,
+    /// Maximum size in bytes of evaluated data and rendered templates
+    pub max_size: Option<i64>,
+    /// Maximum duration in milliseconds of an evaluation. CEL expressions and
+    /// variants are stopped at the deadline, JMESPath and JSONPath expressions
+    /// and single templates are only checked once they return.
+    pub timeout_ms: Option<u32>,
+}
+
+impl From<&CoreEvaluationLimits> for EvaluationLimits {
+    fn from(l: &CoreEvaluationLimits) -> Self {
+        Self {
+            max_depth: l.max_depth.map(|d| saturating_u32(d as u64)),
+            max_size: l.max_size.map(|s| saturating_i64(s as u64)),
+            timeout_ms: l.timeout_ms.map(saturating_u32),
+        }
+    }
+}
+
+/// Project settings from the `mockito.config.*` file
+#[napi(object)]
+#[derive(Clone)]
+pub struct Settings {
+    /// Port the mock server listens on
+    pub port: Option<u32>,
+    /// Collection activated when the controller starts
+    pub default_collection: Option<String>,
+    /// Delay in milliseconds applied to responses without their own delay
+    pub delay: Option<u32>,
+    /// CORS headers added to responses, CORS is disabled if not set
+    pub cors: Option<CorsSettings>,
+    /// Reloading of config files on changes
+    pub watch: Option<WatchSettings>,
+    /// Size limits of request and response bodies
+    pub body_limits: Option<BodyLimits>,
+    /// Template engine of variants without their own, Handlebars if not set
+    pub template: Option<TemplateEngine>,
+    /// Limits of expression evaluation and template rendering
+    pub limits: Option<EvaluationLimits>,
+    /// Template fragments included from variants with `{{> name}}`
+    pub partials: Option<HashMap<String, String>>,
+    /// Number of distinct requests whose matched route is cached, caching is
+    /// disabled if not set
+    pub match_cache: Option<u32>,
+    /// Size limit in bytes of fixture file contents kept in memory, 64 MiB if not set
+    pub fixture_cache: Option<i64>,
+    /// Number of looked up requests kept in the request history, 1000 if not set
+    /// and recording is disabled if 0
+    pub history: Option<u32>,
+    /// Include debugging details in generated responses, like the routes closest
+    /// to an unmatched request in 404 bodies
+    pub debug: Option<bool>,
+}
+
+impl From<&CoreSettings> for Settings {
+    fn from(s: &CoreSettings) -> Self {
+        Self {
+            port: s.port.map(u32::from),
+            default_collection: s.default_collection.clone(),
+            delay: s.delay.map(saturating_u32),
+            cors: s.cors.as_ref().map(Into::into),
+            watch: s.watch.as_ref().map(Into::into),
+            body_limits: s.body_limits.as_ref().map(Into::into),
+            template: s.template.map(Into::into),
+            limits: s.limits.as_ref().map(Into::into),
+            partials: s.partials.clone(),
+            match_cache: s.match_cache.map(|c| saturating_u32(c as u64)),
+            fixture_cache: s.fixture_cache.map(|c| saturating_i64(c as u64)),
+            history: s.history.map(|c| saturating_u32(c as u64)),
+            debug: s.debug,
+        }
+    }
+}
+
+fn saturating_u32(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+pub(crate) fn saturating_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Kind of config file described by a JSON Schema
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFileKind {
+    Routes,
+    Collections,
+    Groups,
+    Settings,
+    Project,
+}
+
+impl From<ConfigFileKind> for CoreConfigFileKind {
+    fn from(k: ConfigFileKind) -> Self {
+        match k {
+            ConfigFileKind::Routes => CoreConfigFileKind::Routes,
+            ConfigFileKind::Collections => CoreConfigFileKind::Collections,
+            ConfigFileKind::Groups => CoreConfigFileKind::Groups,
+            ConfigFileKind::Settings => CoreConfigFileKind::Settings,
+            ConfigFileKind::Project => CoreConfigFileKind::Project,
+        }
+    }
+}
+
+/// Generate the JSON Schema (draft-07) of a config file kind.
+///
+/// Useful for editor autocomplete and validation of fixture files.
+///
+/// @param kind - Kind of config file
+/// @returns JSON Schema object
+#[napi]
+pub fn config_json_schema(kind: ConfigFileKind) -> Value {
+    json_schema::json_schema(kind.into())
+}
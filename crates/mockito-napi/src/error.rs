@@ -0,0 +1,355 @@
+//! Errors thrown to JavaScript.
+//!
+//! Thrown errors carry a machine-readable `code` and fields describing the
+//! failure, so callers can branch on the kind of error:
+//!
+//! ```js
+//! try {
+//!   controller.useRoutes(['users:admin:ok']);
+//! } catch (e) {
+//!   if (e.code === 'PRESET_NOT_FOUND') console.log(e.routeId, e.presetId);
+//! }
+//! ```
+
+use mockito_core::config::error::ConfigError;
+use mockito_core::expression::ExpressionError;
+use mockito_core::mocks::history::RecordedRequest;
+use mockito_core::mocks::manager::{DuplicateIdError, ResolveError};
+use mockito_core::mocks::sse::SseScriptError;
+use mockito_core::mocks::tcp::TcpScriptError;
+use mockito_core::mocks::verify::VerificationError;
+use mockito_core::template::jwt::JwtError;
+use mockito_core::template::TemplateError;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::{json, Value};
+
+/// Kind of a thrown error, set as its `code`
+#[napi(string_enum = "UPPER_SNAKE")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Collection not found (`collectionId`)
+    CollectionNotFound,
+    /// Route not found (`routeId` or wildcard `reference`)
+    RouteNotFound,
+    /// Preset not found in a route (`routeId`, `presetId`)
+    PresetNotFound,
+    /// Variant not found in a preset (`routeId`, `presetId`, `variantId`)
+    VariantNotFound,
+    /// Group referenced by a route not found (`routeId`, `groupId`)
+    GroupNotFound,
+    /// No route is tagged with the tag (`tag`)
+    TagNotFound,
+    /// Route reference isn't `route:preset:variant` (`reference`)
+    InvalidRouteReference,
+    /// Collections inherit from each other in a cycle (`path`)
+    CircularDependency,
+    /// HTTP route used as socket route or vice versa (`routeId`, `expected`, `actual`, `suggestion`)
+    TransportMismatch,
+    /// Definition added with an ID that is already taken (`kind`, `id`)
+    DuplicateId,
+    /// Config file has invalid syntax or an unknown file type
+    ConfigParse,
+    /// Config value doesn't match the expected structure (`file`, `field`, `line`, `column`)
+    ConfigSchema,
+    /// Config is invalid otherwise, e.g. a missing environment variable or include
+    ConfigInvalid,
+    /// File can't be read or fetched (`path` or `url`)
+    Io,
+    /// Template fails to render (`template`)
+    Template,
+    /// Expression doesn't compile (`expression`)
+    ExpressionInvalid,
+    /// Expression fails on the request (`expression`)
+    ExpressionEvaluation,
+    /// No shared controller with the handle (`handle`)
+    SharedControllerNotFound,
+    /// Awaited request didn't arrive in time (`routeId`, `timeoutMs`)
+    Timeout,
+    /// Manager wasn't loaded from files, so there's nothing to watch
+    NotWatchable,
+    /// Argument has an invalid value
+    InvalidArgument,
+    /// Recorded requests don't satisfy a verification (`routeId`, `expected`,
+    /// `actual`, `mismatches` or unmatched `requests`)
+    VerificationFailed,
+    /// A tracing subscriber is already installed in the process
+    TracingInstalled,
+}
+
+impl AsRef<str> for ErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            ErrorCode::CollectionNotFound => "COLLECTION_NOT_FOUND",
+            ErrorCode::RouteNotFound => "ROUTE_NOT_FOUND",
+            ErrorCode::PresetNotFound => "PRESET_NOT_FOUND",
+            ErrorCode::VariantNotFound => "VARIANT_NOT_FOUND",
+            ErrorCode::GroupNotFound => "GROUP_NOT_FOUND",
+            ErrorCode::TagNotFound => "TAG_NOT_FOUND",
+            ErrorCode::InvalidRouteReference => "INVALID_ROUTE_REFERENCE",
+            ErrorCode::CircularDependency => "CIRCULAR_DEPENDENCY",
+            ErrorCode::TransportMismatch => "TRANSPORT_MISMATCH",
+            ErrorCode::DuplicateId => "DUPLICATE_ID",
+            ErrorCode::ConfigParse => "CONFIG_PARSE",
+            ErrorCode::ConfigSchema => "CONFIG_SCHEMA",
+            ErrorCode::ConfigInvalid => "CONFIG_INVALID",
+            ErrorCode::Io => "IO",
+            ErrorCode::Template => "TEMPLATE",
+            ErrorCode::ExpressionInvalid => "EXPRESSION_INVALID",
+            ErrorCode::ExpressionEvaluation => "EXPRESSION_EVALUATION",
+            ErrorCode::SharedControllerNotFound => "SHARED_CONTROLLER_NOT_FOUND",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::NotWatchable => "NOT_WATCHABLE",
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::VerificationFailed => "VERIFICATION_FAILED",
+            ErrorCode::TracingInstalled => "TRACING_INSTALLED",
+        }
+    }
+}
+
+/// Error with a code and context fields, converted to a JS error when thrown.
+#[derive(Debug)]
+pub struct MockitoError {
+    code: ErrorCode,
+    message: String,
+    /// Fields set on the JS error, named in camelCase
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl MockitoError {
+    pub(crate) fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a context field.
+    pub(crate) fn with(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        self.fields.push((name, value.into()));
+        self
+    }
+
+    /// Prefix the message, e.g. with the failed operation.
+    pub(crate) fn context(mut self, prefix: &str) -> Self {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+
+    /// Create the JS error with `code` and context fields.
+    pub(crate) fn into_napi(self, env: &Env) -> Error {
+        let error = JsError::from(Error::new(self.code, self.message)).into_unknown(*env);
+        let result = error.coerce_to_object().and_then(|mut object| {
+            for (name, value) in self.fields {
+                object.set_named_property(name, value)?;
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => Error::from(error),
+            Err(e) => e,
+        }
+    }
+}
+
+impl std::fmt::Display for MockitoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Convert an error to a JS error with `code` and context fields.
+pub(crate) fn js_error(env: &Env, error: impl Into<MockitoError>) -> Error {
+    error.into().into_napi(env)
+}
+
+impl From<ResolveError> for MockitoError {
+    fn from(error: ResolveError) -> Self {
+        let message = error.to_string();
+        match error {
+            ResolveError::CollectionNotFound { collection_id } => {
+                Self::new(ErrorCode::CollectionNotFound, message)
+                    .with("collectionId", collection_id)
+            }
+            ResolveError::RouteNotFound { route_id } => {
+                Self::new(ErrorCode::RouteNotFound, message).with("routeId", route_id)
+            }
+            ResolveError::PresetNotFound {
+                route_id,
+                preset_id,
+            } => Self::new(ErrorCode::PresetNotFound, message)
+                .with("routeId", route_id)
+                .with("presetId", preset_id),
+            ResolveError::VariantNotFound {
+                route_id,
+                preset_id,
+                variant_id,
+            } => Self::new(ErrorCode::VariantNotFound, message)
+                .with("routeId", route_id)
+                .with("presetId", preset_id)
+                .with("variantId", variant_id),
+            ResolveError::NoPresets { route_id } => {
+                Self::new(ErrorCode::PresetNotFound, message).with("routeId", route_id)
+            }
+            ResolveError::NoVariants {
+                route_id,
+                preset_id,
+            } => Self::new(ErrorCode::VariantNotFound, message)
+                .with("routeId", route_id)
+                .with("presetId", preset_id),
+            ResolveError::InvalidRouteReference { reference } => {
+                Self::new(ErrorCode::InvalidRouteReference, message).with("reference", reference)
+            }
+            ResolveError::GroupNotFound { route_id, group_id } => {
+                Self::new(ErrorCode::GroupNotFound, message)
+                    .with("routeId", route_id)
+                    .with("groupId", group_id)
+            }
+            ResolveError::TagNotFound { tag } => {
+                Self::new(ErrorCode::TagNotFound, message).with("tag", tag)
+            }
+            ResolveError::NoWildcardMatches { reference } => {
+                Self::new(ErrorCode::RouteNotFound, message).with("reference", reference)
+            }
+            ResolveError::CircularDependency { path } => {
+                Self::new(ErrorCode::CircularDependency, message).with("path", path)
+            }
+            ResolveError::TransportMismatch {
+                route_id,
+                expected,
+                actual,
+                suggestion,
+            } => Self::new(ErrorCode::TransportMismatch, message)
+                .with("routeId", route_id)
+                .with("expected", expected)
+                .with("actual", actual)
+                .with("suggestion", suggestion),
+        }
+    }
+}
+
+impl From<DuplicateIdError> for MockitoError {
+    fn from(error: DuplicateIdError) -> Self {
+        Self::new(ErrorCode::DuplicateId, error.to_string())
+            .with("kind", error.kind.to_string())
+            .with("id", error.id)
+    }
+}
+
+impl From<ConfigError> for MockitoError {
+    fn from(error: ConfigError) -> Self {
+        let message = error.to_string();
+        match error {
+            ConfigError::Json(_)
+            | ConfigError::Yaml(_)
+            | ConfigError::Json5(_)
+            | ConfigError::Toml(_)
+            | ConfigError::UnknownFileType(_) => Self::new(ErrorCode::ConfigParse, message),
+            ConfigError::Schema {
+                file,
+                field,
+                line,
+                column,
+                ..
+            } => Self::new(ErrorCode::ConfigSchema, message)
+                .with("file", file)
+                .with("field", field)
+                .with("line", line)
+                .with("column", column),
+            ConfigError::Duplicate(error) => error.into(),
+            ConfigError::Io { path, .. } => Self::new(ErrorCode::Io, message).with("path", path),
+            ConfigError::Remote { url, .. } => Self::new(ErrorCode::Io, message).with("url", url),
+            ConfigError::Proto { path, .. } | ConfigError::Dataset { path, .. } => {
+                Self::new(ErrorCode::ConfigInvalid, message).with("path", path)
+            }
+            ConfigError::MissingEnvVar(_)
+            | ConfigError::IncludeCycle(_)
+            | ConfigError::InvalidInclude(_)
+            | ConfigError::OpenApi(_)
+            | ConfigError::InlineFixture(_)
+            | ConfigError::FeatureDisabled { .. }
+            | ConfigError::GlobPattern(_) => Self::new(ErrorCode::ConfigInvalid, message),
+        }
+    }
+}
+
+impl From<TemplateError> for MockitoError {
+    fn from(error: TemplateError) -> Self {
+        Self::new(ErrorCode::Template, error.to_string()).with("template", error.template)
+    }
+}
+
+impl From<SseScriptError> for MockitoError {
+    fn from(error: SseScriptError) -> Self {
+        Self::new(ErrorCode::InvalidArgument, error.to_string()).with("variantId", error.variant_id)
+    }
+}
+
+impl From<JwtError> for MockitoError {
+    fn from(error: JwtError) -> Self {
+        Self::new(ErrorCode::InvalidArgument, error.to_string())
+    }
+}
+
+impl From<TcpScriptError> for MockitoError {
+    fn from(error: TcpScriptError) -> Self {
+        Self::new(ErrorCode::InvalidArgument, error.to_string()).with("variantId", error.variant_id)
+    }
+}
+
+impl From<ExpressionError> for MockitoError {
+    fn from(error: ExpressionError) -> Self {
+        let message = error.to_string();
+        match error {
+            ExpressionError::Invalid { expression, .. } => {
+                Self::new(ErrorCode::ExpressionInvalid, message).with("expression", expression)
+            }
+            ExpressionError::Evaluation { expression, .. } => {
+                Self::new(ErrorCode::ExpressionEvaluation, message).with("expression", expression)
+            }
+        }
+    }
+}
+
+impl From<VerificationError> for MockitoError {
+    fn from(error: VerificationError) -> Self {
+        let message = error.to_string();
+        match error {
+            VerificationError::CountMismatch {
+                route_id,
+                expected,
+                actual,
+                mismatches,
+            } => {
+                let mismatches: Vec<_> = mismatches
+                    .into_iter()
+                    .map(|mismatch| {
+                        let mut value = request_value(&mismatch.request);
+                        value["step"] = mismatch.failure.step.to_string().into();
+                        value["expected"] = mismatch.failure.expected;
+                        value["actual"] = mismatch.failure.actual;
+                        value
+                    })
+                    .collect();
+                Self::new(ErrorCode::VerificationFailed, message)
+                    .with("routeId", route_id)
+                    .with("expected", expected.to_string())
+                    .with("actual", actual)
+                    .with("mismatches", mismatches)
+            }
+            VerificationError::UnmatchedRequests { requests } => {
+                let requests: Vec<_> = requests.iter().map(request_value).collect();
+                Self::new(ErrorCode::VerificationFailed, message).with("requests", requests)
+            }
+        }
+    }
+}
+
+/// Describe a recorded request by method and URL.
+fn request_value(recorded: &RecordedRequest) -> Value {
+    json!({
+        "method": recorded.request.method,
+        "url": recorded.request.url,
+    })
+}
@@ -0,0 +1,108 @@
+//! NAPI bindings for expression utilities.
+
+use crate::error::js_error;
+use mockito_core::expression::{self, functions};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Evaluate an expression against a sample request.
+///
+/// Lets fixture authors try expressions on captured requests without running tests.
+///
+/// @param expression - Expression, optionally wrapped in `${}`
+/// @param request - Sample request context (`method`, `url`, `params`, `query`, `headers`, `payload`)
+/// @returns Evaluation result
+/// @throws Error if the expression is invalid or fails on the sample
+#[napi]
+pub fn test_expression(env: Env, expression: String, request: Value) -> Result<Value> {
+    expression::test(&expression, &request).map_err(|e| js_error(&env, e))
+}
+
+/// Arguments spread into a call of a registered JS function.
+pub struct FunctionArguments(Vec<Value>);
+
+impl JsValuesTupleIntoVec for FunctionArguments {
+    // Same as the implementations of napi, `env` is the env of the calling function
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    fn into_vec(self, env: sys::napi_env) -> Result<Vec<sys::napi_value>> {
+        self.0
+            .into_iter()
+            .map(|arg| unsafe { Value::to_napi_value(env, arg) })
+            .collect()
+    }
+}
+
+type JsFunction = FunctionRef<FunctionArguments, Value>;
+
+thread_local! {
+    /// JS functions registered on this thread, with the env they belong to
+    static JS_FUNCTIONS: RefCell<HashMap<String, (sys::napi_env, Rc<JsFunction>)>> =
+        RefCell::new(HashMap::new());
+    /// Whether the env cleanup hook of this thread was added
+    static CLEANUP_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Register a JS function callable from JMESPath and CEL expressions and `${}`
+/// template placeholders.
+///
+/// Expressions call the function synchronously with the evaluated arguments, so
+/// it must not return a promise. Functions are only callable on the JS thread
+/// that registered them. Built-in functions take precedence.
+///
+/// @param name - Function name used in expressions
+/// @param func - Function receiving the arguments and returning a JSON value
+/// @throws Error if the function throws while evaluating an expression
+#[napi]
+pub fn register_expression_function(
+    env: Env,
+    name: String,
+    func: FunctionRef<FunctionArguments, Value>,
+) -> Result<()> {
+    if !CLEANUP_HOOK.replace(true) {
+        env.add_env_cleanup_hook((), |()| {
+            let functions = JS_FUNCTIONS.take();
+            for name in functions.keys() {
+                functions::unregister_function(name);
+            }
+        })?;
+    }
+
+    JS_FUNCTIONS
+        .with_borrow_mut(|functions| functions.insert(name.clone(), (env.raw(), Rc::new(func))));
+    let key = name.clone();
+    functions::register_function(&name, Arc::new(move |args| call_js_function(&key, args)));
+    Ok(())
+}
+
+/// Unregister a JS function registered with `registerExpressionFunction()`.
+///
+/// @param name - Function name
+/// @returns `false` if no function with the name was registered on this thread
+#[napi]
+pub fn unregister_expression_function(name: String) -> bool {
+    let removed = JS_FUNCTIONS.with_borrow_mut(|functions| functions.remove(&name));
+    removed.is_some() && functions::unregister_function(&name)
+}
+
+/// Call a JS function registered on the current thread.
+fn call_js_function(name: &str, args: &[Value]) -> std::result::Result<Value, String> {
+    // Cloned out, so the function may register other functions while running
+    let (env, function) = JS_FUNCTIONS
+        .with_borrow(|functions| functions.get(name).cloned())
+        .ok_or_else(|| {
+            format!(
+                "JS function '{}' can only be called on the thread that registered it",
+                name
+            )
+        })?;
+    let env = Env::from_raw(env);
+    function
+        .borrow_back(&env)
+        .and_then(|function| function.call(FunctionArguments(args.to_vec())))
+        .map_err(|e| e.reason.clone())
+}
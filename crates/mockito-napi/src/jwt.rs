@@ -0,0 +1,81 @@
+//! NAPI bindings for JWT signing.
+
+use crate::error::js_error;
+use mockito_core::template::jwt::{
+    self, claims_with_expiry, JwtAlgorithm, JwtError, JwtKey, DEFAULT_EXPIRES_IN,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::{Map, Value};
+
+/// Key signing JWTs, see `jwtSign()`
+#[napi(object)]
+#[derive(Default)]
+pub struct JwtOptions {
+    /// Signing algorithm, e.g. `HS256`, `RS256`, `ES256` or `EdDSA` (default `RS256`)
+    pub algorithm: Option<String>,
+    /// HMAC secret for `HS*`, PEM PKCS#8 private key otherwise (built-in
+    /// development key if not set)
+    pub key: Option<String>,
+    /// Key ID, the JWK thumbprint of custom asymmetric keys if not set
+    pub kid: Option<String>,
+    /// Token lifetime in seconds, used for `exp` (default 3600)
+    pub expires_in: Option<u32>,
+}
+
+/// Load the key described by options.
+fn load_key(options: &JwtOptions) -> std::result::Result<JwtKey, JwtError> {
+    let algorithm: JwtAlgorithm = match &options.algorithm {
+        Some(algorithm) => algorithm.parse()?,
+        None => JwtAlgorithm::Rs256,
+    };
+    let key = match &options.key {
+        Some(key) => JwtKey::new(algorithm, key)?,
+        None => JwtKey::development(algorithm).clone(),
+    };
+    Ok(match &options.kid {
+        Some(kid) => key.with_kid(kid),
+        None => key,
+    })
+}
+
+/// Mint a signed JWT, e.g. in a route handler.
+///
+/// `iat` and `exp` are added unless set in `claims`. Without `key`, tokens are
+/// signed with built-in development keys, published by `jwks()`.
+///
+/// @param claims - Token claims
+/// @param options - Signing key and lifetime
+/// @throws Error if the algorithm is unknown or the key invalid
+#[napi]
+pub fn jwt_sign(
+    env: Env,
+    claims: Option<Map<String, Value>>,
+    options: Option<JwtOptions>,
+) -> Result<String> {
+    let options = options.unwrap_or_default();
+    let key = load_key(&options).map_err(|e| js_error(&env, e))?;
+    let expires_in = options.expires_in.map_or(DEFAULT_EXPIRES_IN, u64::from);
+    key.sign(&claims_with_expiry(claims.unwrap_or_default(), expires_in))
+        .map_err(|e| js_error(&env, e))
+}
+
+/// Build the JWKS (`{"keys": [...]}`) verifying tokens of `jwtSign()`.
+///
+/// Without keys, holds the development keys of all asymmetric algorithms. HMAC
+/// keys have no public key and are skipped.
+///
+/// @param keys - Keys whose public keys to publish
+/// @throws Error if an algorithm is unknown or a key invalid
+#[napi]
+pub fn jwks(env: Env, keys: Option<Vec<JwtOptions>>) -> Result<Value> {
+    let Some(keys) = keys else {
+        return Ok(jwt::development_jwks());
+    };
+    let keys = keys
+        .iter()
+        .map(load_key)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| js_error(&env, e))?;
+    Ok(jwt::jwks(&keys))
+}
@@ -5,10 +5,26 @@
 use napi_derive::napi;
 
 mod config;
+mod error;
+mod expression;
+mod jwt;
+mod matching;
 mod mocks;
+mod sse;
+mod tcp;
+mod trace;
+mod watcher;
 
 pub use config::*;
+pub use error::ErrorCode;
+pub use expression::*;
+pub use jwt::*;
+pub use matching::*;
 pub use mocks::*;
+pub use sse::*;
+pub use tcp::*;
+pub use trace::*;
+pub use watcher::*;
 
 /// Library version
 #[napi]
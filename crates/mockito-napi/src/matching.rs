@@ -0,0 +1,119 @@
+//! NAPI bindings for request matching utilities.
+//!
+//! Expected values are written like in route files: objects are matched by
+//! intersection and `${...}` strings are expressions.
+
+use crate::error::{js_error, ErrorCode, MockitoError};
+use mockito_core::expression::EvaluationLimits;
+use mockito_core::matching;
+use mockito_core::types::preset::{HeadersOrExpression, PayloadOrExpression, QueryOrExpression};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Result of matching a URL against a route pattern
+#[napi(object)]
+pub struct UrlMatch {
+    pub matched: bool,
+    /// Path parameters captured by `{name}` segments
+    pub params: HashMap<String, String>,
+}
+
+/// Match a URL against a route URL pattern.
+///
+/// Trailing slashes and the query string are ignored.
+///
+/// @param pattern - Route URL pattern like `/api/users/{id}`
+/// @param url - Request URL
+#[napi]
+pub fn url_matches(pattern: String, url: String) -> UrlMatch {
+    let result = matching::url_matches(&pattern, &url);
+    UrlMatch {
+        matched: result.matched,
+        params: result.params,
+    }
+}
+
+/// Match query parameters like a preset `query`.
+///
+/// @param expected - Query parameters or `${...}` expression
+/// @param query - Query parameters of the request
+/// @param request - Request context evaluated by expressions (default: `{ query }`)
+/// @throws Error if `expected` is malformed or the expression fails
+#[napi]
+pub fn query_matches(
+    env: Env,
+    expected: Value,
+    query: HashMap<String, String>,
+    request: Option<Value>,
+) -> Result<bool> {
+    let expected = parse_expected::<QueryOrExpression>(expected).map_err(|e| js_error(&env, e))?;
+    let context = request.unwrap_or_else(|| json!({ "query": query }));
+    matching::query_matches(
+        Some(&expected),
+        &query,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(|e| js_error(&env, e))
+}
+
+/// Match request headers like a preset `headers`, ignoring the case of names.
+///
+/// @param expected - Headers or `${...}` expression
+/// @param headers - Headers of the request
+/// @param request - Request context evaluated by expressions (default: `{ headers }`)
+/// @throws Error if `expected` is malformed or the expression fails
+#[napi]
+pub fn headers_matches(
+    env: Env,
+    expected: Value,
+    headers: HashMap<String, String>,
+    request: Option<Value>,
+) -> Result<bool> {
+    let expected =
+        parse_expected::<HeadersOrExpression>(expected).map_err(|e| js_error(&env, e))?;
+    let context = request.unwrap_or_else(|| json!({ "headers": headers }));
+    matching::headers_matches(
+        Some(&expected),
+        &headers,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(|e| js_error(&env, e))
+}
+
+/// Match a request payload like a preset `payload`.
+///
+/// Objects match if the payload contains all expected fields, other values must be equal.
+///
+/// @param expected - Expected payload or `${...}` expression
+/// @param payload - Payload of the request
+/// @param request - Request context evaluated by expressions (default: `{ payload }`)
+/// @throws Error if the expression fails
+#[napi]
+pub fn payload_matches(
+    env: Env,
+    expected: Value,
+    payload: Value,
+    request: Option<Value>,
+) -> Result<bool> {
+    let expected =
+        parse_expected::<PayloadOrExpression>(expected).map_err(|e| js_error(&env, e))?;
+    let context = request.unwrap_or_else(|| json!({ "payload": payload }));
+    matching::payload_matches(
+        Some(&expected),
+        &payload,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(|e| js_error(&env, e))
+}
+
+/// Parse an expected value like a preset field of a route file.
+fn parse_expected<T: DeserializeOwned>(expected: Value) -> std::result::Result<T, MockitoError> {
+    serde_json::from_value(expected)
+        .map_err(|e| MockitoError::new(ErrorCode::InvalidArgument, e.to_string()))
+}
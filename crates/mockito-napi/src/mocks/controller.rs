@@ -1,18 +1,893 @@
 //! NAPI bindings for controller utilities.
 
-use crate::config::{Preset, Route, Variant};
-use crate::mocks::manager::ActiveRoute;
-use mockito_core::config::parser;
+use crate::config::{
+    content_type, saturating_i64, HttpMethod, Route, Settings, Transport, Variant,
+};
+use crate::error::{js_error, ErrorCode, MockitoError};
+use crate::mocks::manager::{
+    load_manager, load_project, ActiveRoute, DuplicatePolicy, ExportFormat, LoadSource,
+    MocksManager,
+};
+use arc_swap::ArcSwap;
+use mockito_core::config::fixtures::FixtureCache;
+use mockito_core::matching::{message_payload, payload_from_bytes};
 use mockito_core::mocks::{
-    controller::MocksController as CoreMocksController, manager::MocksManager,
+    audit::AuditEntry as CoreAuditEntry,
+    context::RequestContext,
+    controller::{
+        ControllerEvent as CoreControllerEvent, ControllerSnapshot as CoreControllerSnapshot,
+        ListenerId, MocksController as CoreMocksController, Request as CoreRequest,
+    },
+    coverage::{
+        CoverageReport as CoreCoverageReport, PresetCoverage as CorePresetCoverage,
+        RouteCoverage as CoreRouteCoverage, VariantCoverage as CoreVariantCoverage,
+    },
+    har::export_har,
+    history::{RecordedRequest as CoreRecordedRequest, RequestHistory, DEFAULT_HISTORY_CAPACITY},
+    latency::{
+        LatencyStats as CoreLatencyStats, RouteLatency as CoreRouteLatency,
+        VariantLatency as CoreVariantLatency,
+    },
+    manager::{ActiveRoute as CoreActiveRoute, ResolveError},
+    table::{
+        ExpressionDiagnostic as CoreExpressionDiagnostic, MatchFailure as CoreMatchFailure,
+        MatchStep as CoreMatchStep, RouteExplanation as CoreRouteExplanation, RouteTable,
+    },
+    unmatched::UnmatchedRequest as CoreUnmatchedRequest,
+    verify::{verify_no_unmatched, Verification as CoreVerification},
 };
+use mockito_core::template::Engine as CoreTemplateEngine;
+use mockito_core::types::route::Transport as CoreTransport;
+use mockito_core::types::variant::Variant as CoreVariant;
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::tokio::{
+    self,
+    sync::{broadcast::error::RecvError, oneshot},
+    task::AbortHandle,
+};
 use napi_derive::napi;
-use std::sync::{Arc, Mutex};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default timeout of `MocksController.nextRequest()` in milliseconds
+const DEFAULT_NEXT_REQUEST_TIMEOUT_MS: u32 = 5000;
+
+/// Request to match against active routes
+#[napi(object)]
+pub struct Request {
+    /// Request URL (path + query string, or listen address for TCP)
+    pub url: String,
+    /// HTTP method (required for HTTP routes)
+    pub method: Option<HttpMethod>,
+    pub transport: Transport,
+    pub headers: Option<HashMap<String, String>>,
+    /// Query parameters (parsed from URL if not provided)
+    pub query: Option<HashMap<String, String>>,
+    pub payload: Option<Value>,
+    /// Raw body, used if `payload` isn't set.
+    ///
+    /// JSON bodies are matched as their value and other UTF-8 bodies as a string.
+    /// Binary bodies are matched as a base64 `data:` URI with the `Content-Type` header.
+    /// TCP messages are matched as a string, or a base64 `data:` URI if binary.
+    pub payload_buffer: Option<Uint8Array>,
+}
+
+impl From<Request> for CoreRequest {
+    fn from(r: Request) -> Self {
+        let transport = CoreTransport::from(r.transport);
+        let payload = r.payload.or_else(|| {
+            let media_type = content_type(r.headers.as_ref());
+            r.payload_buffer.map(|body| match transport {
+                CoreTransport::Tcp => message_payload(&body),
+                _ => payload_from_bytes(&body, media_type),
+            })
+        });
+        Self {
+            url: r.url,
+            method: r.method.map(Into::into),
+            transport,
+            headers: r.headers,
+            query: r.query,
+            payload,
+        }
+    }
+}
+
+/// Response returned by a route handler, see `MocksController.onRoute()`
+#[napi(object)]
+pub struct RouteResponse {
+    pub status: Option<u32>,
+    pub headers: Option<HashMap<String, String>>,
+    pub body: Option<Value>,
+    /// Binary body, takes precedence over `body`
+    pub body_buffer: Option<Buffer>,
+}
+
+impl RouteResponse {
+    /// Turn into a variant replacing the static variant `variant_id`.
+    fn into_variant(self, variant_id: &str) -> Variant {
+        Variant {
+            id: variant_id.to_string(),
+            status: self.status,
+            headers: self.headers,
+            body: self.body,
+            body_buffer: self.body_buffer,
+            template: None,
+        }
+    }
+}
+
+/// JS function answering requests to a route, `undefined` falls back to the variant
+type RouteHandler = FunctionRef<Value, Option<RouteResponse>>;
+
+/// Preset expression that failed while matching a request, see `MocksController.diagnose()`
+#[napi(object)]
+pub struct ExpressionDiagnostic {
+    pub route_id: String,
+    pub preset_id: String,
+    /// Matched request field: `headers`, `query` or `payload`
+    pub field: String,
+    pub message: String,
+}
+
+impl From<CoreExpressionDiagnostic> for ExpressionDiagnostic {
+    fn from(d: CoreExpressionDiagnostic) -> Self {
+        Self {
+            route_id: d.route_id,
+            preset_id: d.preset_id,
+            field: d.field.to_string(),
+            message: d.error.to_string(),
+        }
+    }
+}
+
+/// Condition of a route checked against a request, in matching order.
+///
+/// The later the first failed condition, the closer a route is to matching.
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchStep {
+    Enabled,
+    Transport,
+    Method,
+    Url,
+    /// GraphQL operation of the route
+    Operation,
+    /// TCP message of the route
+    Message,
+    Params,
+    Headers,
+    Query,
+    Payload,
+}
+
+impl From<CoreMatchStep> for MatchStep {
+    fn from(s: CoreMatchStep) -> Self {
+        match s {
+            CoreMatchStep::Enabled => MatchStep::Enabled,
+            CoreMatchStep::Transport => MatchStep::Transport,
+            CoreMatchStep::Method => MatchStep::Method,
+            CoreMatchStep::Url => MatchStep::Url,
+            CoreMatchStep::Operation => MatchStep::Operation,
+            CoreMatchStep::Message => MatchStep::Message,
+            CoreMatchStep::Params => MatchStep::Params,
+            CoreMatchStep::Headers => MatchStep::Headers,
+            CoreMatchStep::Query => MatchStep::Query,
+            CoreMatchStep::Payload => MatchStep::Payload,
+        }
+    }
+}
+
+/// First condition of a route a request failed
+#[napi(object)]
+pub struct MatchFailure {
+    pub step: MatchStep,
+    /// First path parameter, header, query parameter or payload field the request
+    /// doesn't satisfy, with nested payload fields joined by dots
+    pub field: Option<String>,
+    /// Value the route expects, as defined in the route or preset
+    pub expected: Value,
+    /// Value of the request
+    pub actual: Value,
+    /// Error of the preset expression, if the condition failed with one
+    pub error: Option<String>,
+}
+
+impl From<CoreMatchFailure> for MatchFailure {
+    fn from(f: CoreMatchFailure) -> Self {
+        Self {
+            step: f.step.into(),
+            field: f.field(),
+            expected: f.expected,
+            actual: f.actual,
+            error: f.error.map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Outcome of matching a request against one active route, see `MocksController.explain()`
+#[napi(object)]
+pub struct RouteExplanation {
+    pub route_id: String,
+    pub preset_id: String,
+    /// Why the route doesn't match, absent if it does
+    pub failure: Option<MatchFailure>,
+}
+
+impl From<CoreRouteExplanation> for RouteExplanation {
+    fn from(e: CoreRouteExplanation) -> Self {
+        Self {
+            route_id: e.route_id,
+            preset_id: e.preset_id,
+            failure: e.failure.map(Into::into),
+        }
+    }
+}
+
+/// Memory used by a controller in bytes, see `MocksController.memoryUsage()`
+#[napi(object)]
+pub struct MemoryUsage {
+    /// Estimated size of loaded routes, collections and groups, shared by forks
+    pub definitions: i64,
+    /// Size of fixture file contents kept in memory, shared by handles of `share()`
+    pub fixtures: i64,
+    /// Size limit of fixture file contents kept in memory
+    pub fixture_limit: i64,
+}
+
+/// Request recorded in the request history, see `MocksController.getRequests()`
+#[napi(object)]
+pub struct RecordedRequest {
+    /// Time the request was recorded, in milliseconds since the Unix epoch
+    pub timestamp: f64,
+    pub url: String,
+    pub method: Option<HttpMethod>,
+    pub transport: Transport,
+    pub headers: Option<HashMap<String, String>>,
+    pub query: Option<HashMap<String, String>>,
+    pub payload: Option<Value>,
+    /// Matched route ID, `null` if no route matched
+    pub route_id: Option<String>,
+    /// Active preset ID of the matched route
+    pub preset_id: Option<String>,
+    /// Active variant ID of the matched route
+    pub variant_id: Option<String>,
+    /// Time in milliseconds spent looking up the route and rendering the response
+    pub latency: f64,
+    /// Delay in milliseconds injected before sending the response, see the `delay` setting
+    pub delay: f64,
+    /// Routes closest to matching the request if no route matched, closest first
+    pub near_matches: Vec<RouteExplanation>,
+    /// Response rendered by `respond()`, `null` for `findRoute()` lookups and
+    /// requests that matched no route
+    pub response: Option<Variant>,
+}
+
+impl From<CoreRecordedRequest> for RecordedRequest {
+    fn from(r: CoreRecordedRequest) -> Self {
+        Self {
+            timestamp: epoch_millis(r.timestamp),
+            url: r.request.url,
+            method: r.request.method.map(Into::into),
+            transport: r.request.transport.into(),
+            headers: r.request.headers,
+            query: r.request.query,
+            payload: r.request.payload,
+            route_id: r.route_id,
+            preset_id: r.preset_id,
+            variant_id: r.variant_id,
+            latency: millis(r.latency),
+            delay: millis(r.delay),
+            near_matches: r.near_matches.into_iter().map(Into::into).collect(),
+            response: r.response.map(Into::into),
+        }
+    }
+}
+
+/// Request that matched no route, see `MocksController.getUnmatched()`
+#[napi(object)]
+pub struct UnmatchedRequest {
+    /// URL of the latest request with the same transport, method and path
+    pub url: String,
+    pub method: Option<HttpMethod>,
+    pub transport: Transport,
+    /// Number of requests with the same transport, method and path
+    pub count: u32,
+    /// Time the first of the requests was seen, in milliseconds since the Unix epoch
+    pub first_seen: f64,
+    /// Time the latest of the requests was seen, in milliseconds since the Unix epoch
+    pub last_seen: f64,
+    /// Route skeleton matching the request
+    pub suggestion: Route,
+}
+
+impl From<CoreUnmatchedRequest> for UnmatchedRequest {
+    fn from(u: CoreUnmatchedRequest) -> Self {
+        let suggestion = u.suggestion().into();
+        Self {
+            url: u.request.url,
+            method: u.request.method.map(Into::into),
+            transport: u.request.transport.into(),
+            count: u32::try_from(u.count).unwrap_or(u32::MAX),
+            first_seen: epoch_millis(u.first_seen),
+            last_seen: epoch_millis(u.last_seen),
+            suggestion,
+        }
+    }
+}
+
+/// Serve time percentiles of a route, see `MocksController.latencyStats()`
+#[napi(object)]
+pub struct RouteLatency {
+    pub route_id: String,
+    /// Serve times of all variants of the route
+    pub stats: LatencyStats,
+    /// Variants that answered requests, sorted by preset and variant ID
+    pub variants: Vec<VariantLatency>,
+}
+
+/// Serve time percentiles of a variant
+#[napi(object)]
+pub struct VariantLatency {
+    pub preset_id: String,
+    pub variant_id: String,
+    pub stats: LatencyStats,
+}
+
+/// Summary of serve times, in milliseconds
+#[napi(object)]
+pub struct LatencyStats {
+    /// Number of requests
+    pub count: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Median
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl From<CoreRouteLatency> for RouteLatency {
+    fn from(route: CoreRouteLatency) -> Self {
+        Self {
+            route_id: route.route_id,
+            stats: route.stats.into(),
+            variants: route.variants.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<CoreVariantLatency> for VariantLatency {
+    fn from(variant: CoreVariantLatency) -> Self {
+        Self {
+            preset_id: variant.preset_id,
+            variant_id: variant.variant_id,
+            stats: variant.stats.into(),
+        }
+    }
+}
+
+impl From<CoreLatencyStats> for LatencyStats {
+    fn from(stats: CoreLatencyStats) -> Self {
+        Self {
+            count: stats.count as f64,
+            min: millis(stats.min),
+            max: millis(stats.max),
+            mean: millis(stats.mean),
+            p50: millis(stats.p50),
+            p95: millis(stats.p95),
+            p99: millis(stats.p99),
+        }
+    }
+}
+
+/// Duration in milliseconds.
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Milliseconds since the Unix epoch.
+fn epoch_millis(time: SystemTime) -> f64 {
+    millis(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+/// Routes, presets and variants that answered requests, see `MocksController.coverageReport()`
+#[napi(object)]
+pub struct CoverageReport {
+    /// Number of variants that answered a request
+    pub covered: u32,
+    /// Number of defined variants
+    pub total: u32,
+    /// Never matched `route`, `route:preset` and `route:preset:variant` references,
+    /// most general first
+    pub untouched: Vec<String>,
+    /// Hit counts of all routes, sorted by ID
+    pub routes: Vec<RouteCoverage>,
+}
+
+/// Hit counts of a route and its presets
+#[napi(object)]
+pub struct RouteCoverage {
+    pub route_id: String,
+    /// Number of requests the route answered
+    pub hits: u32,
+    pub presets: Vec<PresetCoverage>,
+}
+
+/// Hit counts of a preset and its variants
+#[napi(object)]
+pub struct PresetCoverage {
+    pub preset_id: String,
+    /// Number of requests the preset answered
+    pub hits: u32,
+    pub variants: Vec<VariantCoverage>,
+}
+
+/// Hit count of a variant
+#[napi(object)]
+pub struct VariantCoverage {
+    pub variant_id: String,
+    /// Number of requests the variant answered
+    pub hits: u32,
+}
+
+impl From<CoreCoverageReport> for CoverageReport {
+    fn from(report: CoreCoverageReport) -> Self {
+        let (covered, total) = report.covered_variants();
+        Self {
+            covered: count(covered),
+            total: count(total),
+            untouched: report.untouched(),
+            routes: report.routes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<CoreRouteCoverage> for RouteCoverage {
+    fn from(route: CoreRouteCoverage) -> Self {
+        Self {
+            route_id: route.route_id,
+            hits: count(route.hits),
+            presets: route.presets.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<CorePresetCoverage> for PresetCoverage {
+    fn from(preset: CorePresetCoverage) -> Self {
+        Self {
+            preset_id: preset.preset_id,
+            hits: count(preset.hits),
+            variants: preset.variants.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<CoreVariantCoverage> for VariantCoverage {
+    fn from(variant: CoreVariantCoverage) -> Self {
+        Self {
+            variant_id: variant.variant_id,
+            hits: count(variant.hits),
+        }
+    }
+}
+
+/// Saturating conversion of a count to a JS number.
+fn count(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+/// Check of the requests recorded for a route, see `MocksController.verify()`
+///
+/// Conditions return a new verification, so a base verification can be reused:
+///
+/// ```js
+/// const users = controller.verify('users');
+/// users.times(2);
+/// users.withQuery({ page: '2' }).once();
+/// ```
+#[napi]
+pub struct Verification {
+    inner: CoreVerification,
+}
+
+#[napi]
+impl Verification {
+    /// Only count requests with the given headers, compared case-insensitively by name
+    #[napi]
+    pub fn with_headers(&self, headers: HashMap<String, String>) -> Verification {
+        Self {
+            inner: self.inner.clone().with_headers(headers),
+        }
+    }
+
+    /// Only count requests with the given query parameters
+    #[napi]
+    pub fn with_query(&self, query: HashMap<String, String>) -> Verification {
+        Self {
+            inner: self.inner.clone().with_query(query),
+        }
+    }
+
+    /// Only count requests with the given payload, a subset of it for objects
+    #[napi]
+    pub fn with_payload(&self, payload: Value) -> Verification {
+        Self {
+            inner: self.inner.clone().with_payload(payload),
+        }
+    }
+
+    /// Number of recorded requests satisfying the conditions
+    #[napi]
+    pub fn count(&self) -> u32 {
+        u32::try_from(self.inner.count()).unwrap_or(u32::MAX)
+    }
+
+    /// Check that the route was requested exactly `count` times.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+    #[napi]
+    pub fn times(&self, env: Env, count: u32) -> Result<()> {
+        self.inner
+            .times(count as usize)
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Check that the route was requested at least `count` times.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+    #[napi]
+    pub fn at_least(&self, env: Env, count: u32) -> Result<()> {
+        self.inner
+            .at_least(count as usize)
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Check that the route was requested at most `count` times.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+    #[napi]
+    pub fn at_most(&self, env: Env, count: u32) -> Result<()> {
+        self.inner
+            .at_most(count as usize)
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Check that the route was requested exactly once.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing requests that didn't satisfy the conditions
+    #[napi]
+    pub fn once(&self, env: Env) -> Result<()> {
+        self.inner.once().map_err(|e| js_error(&env, e))
+    }
+
+    /// Check that the route was never requested.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing the requests of the route
+    #[napi]
+    pub fn never(&self, env: Env) -> Result<()> {
+        self.inner.never().map_err(|e| js_error(&env, e))
+    }
+}
+
+/// Opaque captured controller state, see `MocksController.snapshot()`
+#[napi]
+pub struct ControllerSnapshot {
+    inner: CoreControllerSnapshot,
+}
+
+#[napi]
+impl ControllerSnapshot {
+    /// Active collection ID at capture time
+    #[napi(getter)]
+    pub fn collection(&self) -> Option<String> {
+        self.inner.active_collection_id().map(String::from)
+    }
+}
+
+/// Kind of controller state change
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerEventKind {
+    CollectionChanged,
+    RoutesOverridden,
+    TagDisabled,
+    RoutesDisabled,
+    RoutesEnabled,
+    RoutesReset,
+    Reloaded,
+    Restored,
+}
+
+/// Controller state change delivered to `MocksController.onChange()` callbacks
+#[napi(object)]
+pub struct ControllerEvent {
+    pub kind: ControllerEventKind,
+    /// Activated collection ID (`CollectionChanged`)
+    pub collection_id: Option<String>,
+    /// Affected route IDs (`RoutesOverridden`, `RoutesDisabled`, `RoutesEnabled`)
+    pub route_ids: Option<Vec<String>>,
+    /// Affected tag (`TagDisabled`)
+    pub tag: Option<String>,
+}
+
+impl From<&CoreControllerEvent> for ControllerEvent {
+    fn from(e: &CoreControllerEvent) -> Self {
+        let event = |kind| Self {
+            kind,
+            collection_id: None,
+            route_ids: None,
+            tag: None,
+        };
+        match e {
+            CoreControllerEvent::CollectionChanged { collection_id } => Self {
+                collection_id: Some(collection_id.clone()),
+                ..event(ControllerEventKind::CollectionChanged)
+            },
+            CoreControllerEvent::RoutesOverridden { route_ids } => Self {
+                route_ids: Some(route_ids.clone()),
+                ..event(ControllerEventKind::RoutesOverridden)
+            },
+            CoreControllerEvent::TagDisabled { tag } => Self {
+                tag: Some(tag.clone()),
+                ..event(ControllerEventKind::TagDisabled)
+            },
+            CoreControllerEvent::RoutesDisabled { route_ids } => Self {
+                route_ids: Some(route_ids.clone()),
+                ..event(ControllerEventKind::RoutesDisabled)
+            },
+            CoreControllerEvent::RoutesEnabled { route_ids } => Self {
+                route_ids: Some(route_ids.clone()),
+                ..event(ControllerEventKind::RoutesEnabled)
+            },
+            CoreControllerEvent::RoutesReset => event(ControllerEventKind::RoutesReset),
+            CoreControllerEvent::Reloaded => event(ControllerEventKind::Reloaded),
+            CoreControllerEvent::Restored => event(ControllerEventKind::Restored),
+        }
+    }
+}
+
+/// State change recorded in the audit log, see `MocksController.getAuditLog()`
+#[napi(object)]
+pub struct AuditEntry {
+    /// Time the change was applied, in milliseconds since the Unix epoch
+    pub timestamp: f64,
+    /// Label set by `setAuditLabel()` when the change was applied
+    pub label: Option<String>,
+    pub event: ControllerEvent,
+}
+
+impl From<CoreAuditEntry> for AuditEntry {
+    fn from(entry: CoreAuditEntry) -> Self {
+        Self {
+            timestamp: epoch_millis(entry.timestamp),
+            label: entry.label,
+            event: ControllerEvent::from(&entry.event),
+        }
+    }
+}
+
+/// Event subscribed to by `MocksController.on()`
+#[napi(string_enum = "camelCase")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerEventName {
+    /// A request matched a route
+    Request,
+    /// A request matched no route
+    Unmatched,
+    /// Another collection was activated
+    CollectionChanged,
+    /// Routes and collections were reloaded
+    Reloaded,
+}
+
+/// Request looked up by `MocksController.respond()` or `findRoute()`, delivered to
+/// `on('request')` and `on('unmatched')` callbacks
+#[napi(object)]
+pub struct RequestEvent {
+    pub url: String,
+    pub method: Option<HttpMethod>,
+    pub transport: Transport,
+    /// Matched route ID (`request`)
+    pub route_id: Option<String>,
+    /// Active preset ID of the matched route (`request`)
+    pub preset_id: Option<String>,
+    /// Active variant ID of the matched route (`request`)
+    pub variant_id: Option<String>,
+}
+
+impl RequestEvent {
+    fn new(request: &CoreRequest, active_route: Option<&CoreActiveRoute>) -> Self {
+        Self {
+            url: request.url.clone(),
+            method: request.method.clone().map(Into::into),
+            transport: request.transport.clone().into(),
+            route_id: active_route.map(|a| a.route.id.to_string()),
+            preset_id: active_route.map(|a| a.preset().id.to_string()),
+            variant_id: active_route.map(|a| a.variant().id.to_string()),
+        }
+    }
+}
+
+/// Callback of `MocksController.on()`
+type EventCallback = ThreadsafeFunction<
+    Either<RequestEvent, ControllerEvent>,
+    (),
+    Either<RequestEvent, ControllerEvent>,
+    Status,
+    false,
+    true,
+>;
+
+/// Callbacks of request events, see `MocksController.on()` and `nextRequest()`
+#[derive(Default)]
+struct RequestListeners {
+    next_id: u64,
+    /// Callbacks with whether they receive matched or unmatched requests
+    entries: Vec<(u64, bool, EventCallback)>,
+    /// Pending `nextRequest()` calls with the awaited route ID
+    waiters: Vec<(Option<String>, oneshot::Sender<RequestEvent>)>,
+}
+
+impl RequestListeners {
+    /// Notify callbacks about a request and the route it matched.
+    fn notify(&self, request: &CoreRequest, active_route: Option<&CoreActiveRoute>) {
+        for (_, matched, callback) in &self.entries {
+            if *matched == active_route.is_some() {
+                callback.call(
+                    Either::A(RequestEvent::new(request, active_route)),
+                    ThreadsafeFunctionCallMode::NonBlocking,
+                );
+            }
+        }
+    }
+
+    /// Resolve waiters awaiting a request to the route it matched.
+    fn resolve_waiters(&mut self, request: &CoreRequest, active_route: Option<&CoreActiveRoute>) {
+        let route_id = active_route.map(|a| &*a.route.id);
+        for (awaited, sender) in std::mem::take(&mut self.waiters) {
+            let resolves = match &awaited {
+                Some(awaited) => route_id == Some(awaited.as_str()),
+                None => true,
+            };
+            if resolves {
+                // Fails if the wait timed out meanwhile
+                let _ = sender.send(RequestEvent::new(request, active_route));
+            } else if !sender.is_closed() {
+                self.waiters.push((awaited, sender));
+            }
+        }
+    }
+}
+
+/// Listener behind a subscription
+enum Listener {
+    /// Change listener of the core controller
+    Change(ListenerId),
+    /// Request listener of the binding
+    Request(u64),
+    /// Task forwarding live events
+    Live(AbortHandle),
+}
+
+/// Subscription to controller events, see `MocksController.onChange()`, `on()` and `onLive()`
+#[napi]
+pub struct ChangeSubscription {
+    controller: Arc<RwLock<CoreMocksController>>,
+    request_listeners: Arc<RwLock<RequestListeners>>,
+    listener: Listener,
+}
+
+#[napi]
+impl ChangeSubscription {
+    /// Stop receiving events. Returns `false` if already unsubscribed.
+    #[napi]
+    pub fn unsubscribe(&self) -> bool {
+        match &self.listener {
+            Listener::Change(listener_id) => {
+                let mut controller = self.controller.write().unwrap();
+                controller.remove_listener(*listener_id)
+            }
+            Listener::Request(id) => {
+                let mut listeners = self.request_listeners.write().unwrap();
+                let len = listeners.entries.len();
+                listeners.entries.retain(|(entry_id, _, _)| entry_id != id);
+                listeners.entries.len() != len
+            }
+            Listener::Live(task) => {
+                let running = !task.is_finished();
+                task.abort();
+                running
+            }
+        }
+    }
+}
+
+/// Load a core controller, activating the default collection.
+///
+/// Project controllers activate the default collection of the project settings.
+fn load_controller(
+    source: &LoadSource,
+    default_collection: Option<&str>,
+) -> std::result::Result<CoreMocksController, MockitoError> {
+    let mut controller = match source {
+        LoadSource::Files { .. } => CoreMocksController::new(source.load_manager()?),
+        LoadSource::Project {
+            path,
+            duplicate_policy,
+        } => CoreMocksController::from_project(load_project(path, *duplicate_policy)?)?,
+    };
+    if let Some(collection_id) = default_collection {
+        controller.use_collection(collection_id)?;
+    }
+    Ok(controller)
+}
+
+/// Loading of a controller on a background thread.
+pub struct LoadControllerTask {
+    source: LoadSource,
+    default_collection: Option<String>,
+}
+
+impl Task for LoadControllerTask {
+    type Output = std::result::Result<CoreMocksController, MockitoError>;
+    type JsValue = MocksController;
+
+    // Failures are converted in `resolve`, since JS errors need an env
+    fn compute(&mut self) -> Result<Self::Output> {
+        Ok(load_controller(
+            &self.source,
+            self.default_collection.as_deref(),
+        ))
+    }
+
+    fn resolve(&mut self, env: Env, controller: Self::Output) -> Result<Self::JsValue> {
+        controller
+            .map(MocksController::from_core)
+            .map_err(|e| e.into_napi(&env))
+    }
+}
+
+/// Controller state reachable from other threads, see `MocksController.share()`
+struct SharedController {
+    inner: Weak<RwLock<CoreMocksController>>,
+    route_table: Weak<ArcSwap<RouteTable>>,
+    template: CoreTemplateEngine,
+    fixtures: Weak<FixtureCache>,
+}
+
+/// Shared controllers by handle, entries of dropped controllers are removed on `share()`
+static SHARED_CONTROLLERS: Mutex<BTreeMap<u32, SharedController>> = Mutex::new(BTreeMap::new());
+/// Next shared controller handle
+static NEXT_SHARED_HANDLE: AtomicU32 = AtomicU32::new(1);
 
 #[napi]
 pub struct MocksController {
-    inner: Arc<Mutex<CoreMocksController>>,
+    /// Core controller, locked exclusively only while changes are applied,
+    /// so getters on several threads don't wait for each other
+    inner: Arc<RwLock<CoreMocksController>>,
+    /// Current route table, published after every change for lock-free lookup,
+    /// shared with handles on other threads
+    route_table: Arc<ArcSwap<RouteTable>>,
+    /// Template engine of variants without their own, from project settings
+    template: CoreTemplateEngine,
+    /// JS handlers by route ID, see `onRoute()`
+    handlers: RefCell<HashMap<String, Rc<RouteHandler>>>,
+    /// Callbacks notified about looked up requests
+    request_listeners: Arc<RwLock<RequestListeners>>,
+    /// Contents of fixture files served by `respond()`, read on first use,
+    /// shared with handles on other threads
+    fixtures: Arc<FixtureCache>,
+    /// Requests looked up by `respond()` or `findRoute()`, shared with the core
+    /// controller and handles on other threads
+    history: Arc<RequestHistory>,
 }
 
 #[napi]
@@ -22,114 +897,950 @@ impl MocksController {
     /// @param collectionsPath - Path or glob pattern to collections file(s)
     /// @param routesPath - Path or glob pattern to routes file(s)
     /// @param defaultCollection - Optional default collection ID
+    /// @param groupsPath - Optional path to route groups file
     #[napi(constructor)]
     pub fn new(
+        env: Env,
         collections_path: String,
         routes_path: String,
         default_collection: Option<String>,
+        groups_path: Option<String>,
     ) -> Result<Self> {
-        // Load routes and collections
-        let routes = parser::load_routes(&routes_path)
-            .map_err(|e| Error::from_reason(format!("Failed to load routes: {e}")))?;
-        let collections = parser::load_collections(&collections_path)
-            .map_err(|e| Error::from_reason(format!("Failed to load collections: {e}")))?;
-
-        // Create manager and add data
-        let mut manager = MocksManager::new();
-        manager.add_routes(routes);
-        manager.add_collections(collections);
-
-        // Create controller
-        let controller = CoreMocksController::new(manager);
-
-        // Activate default collection if provided
-        let result = Self {
-            inner: Arc::new(Mutex::new(controller)),
+        let source = LoadSource::Files {
+            collections_path,
+            routes_path,
+            groups_path,
         };
+        load_controller(&source, default_collection.as_deref())
+            .map(Self::from_core)
+            .map_err(|e| e.into_napi(&env))
+    }
 
+    /// Create a controller from a project directory or combined config file
+    ///
+    /// Discovers `routes/**`, `fixtures/**`, `collections/**` and `groups/**` files under a directory
+    /// and activates the default collection from the `mockito.config.*` settings file.
+    /// The rest of the settings is available via the `settings` getter.
+    ///
+    /// @param path - Project root directory or combined config file
+    /// @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+    #[napi(factory)]
+    pub fn from_project(
+        env: Env,
+        path: String,
+        duplicate_policy: Option<DuplicatePolicy>,
+    ) -> Result<Self> {
+        let source = LoadSource::Project {
+            path,
+            duplicate_policy,
+        };
+        load_controller(&source, None)
+            .map(Self::from_core)
+            .map_err(|e| e.into_napi(&env))
+    }
+
+    /// Create a controller serving the routes of a mocks manager
+    ///
+    /// The controller works on a copy, so later changes to the manager don't affect it.
+    /// Together with `MocksManager.create()` this defines mocks inline without config files.
+    ///
+    /// @param manager - Mocks manager with routes and collections
+    /// @param defaultCollection - Optional default collection ID
+    #[napi(factory)]
+    pub fn from_manager(
+        env: Env,
+        manager: &MocksManager,
+        default_collection: Option<String>,
+    ) -> Result<Self> {
+        let mut controller = CoreMocksController::new(manager.to_core());
         if let Some(collection_id) = default_collection {
-            result.use_collection(collection_id)?;
+            controller
+                .use_collection(&collection_id)
+                .map_err(|e| js_error(&env, e))?;
         }
+        Ok(Self::from_core(controller))
+    }
 
-        Ok(result)
+    /// Load a controller on a background thread
+    ///
+    /// Same as the constructor, without blocking the event loop while files are parsed.
+    ///
+    /// @param collectionsPath - Path or glob pattern to collections file(s)
+    /// @param routesPath - Path or glob pattern to routes file(s)
+    /// @param defaultCollection - Optional default collection ID
+    /// @param groupsPath - Optional path to route groups file
+    #[napi(ts_return_type = "Promise<MocksController>")]
+    pub fn load(
+        collections_path: String,
+        routes_path: String,
+        default_collection: Option<String>,
+        groups_path: Option<String>,
+    ) -> AsyncTask<LoadControllerTask> {
+        AsyncTask::new(LoadControllerTask {
+            source: LoadSource::Files {
+                collections_path,
+                routes_path,
+                groups_path,
+            },
+            default_collection,
+        })
+    }
+
+    /// Load a controller from a project directory or combined config file on a background thread
+    ///
+    /// Same as `fromProject`, without blocking the event loop while files are parsed.
+    ///
+    /// @param path - Project root directory or combined config file
+    /// @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+    #[napi(ts_return_type = "Promise<MocksController>")]
+    pub fn load_project(
+        path: String,
+        duplicate_policy: Option<DuplicatePolicy>,
+    ) -> AsyncTask<LoadControllerTask> {
+        AsyncTask::new(LoadControllerTask {
+            source: LoadSource::Project {
+                path,
+                duplicate_policy,
+            },
+            default_collection: None,
+        })
+    }
+
+    /// Share the controller with worker threads.
+    ///
+    /// Pass the returned handle to a worker (e.g. via `workerData`) and call
+    /// `MocksController.fromShared()` there. All handles use the same loaded routes
+    /// and state, so route switches in one thread are seen by all. Route handlers
+    /// and `on()` subscriptions stay with the handle they were registered on.
+    ///
+    /// The handle is valid while any controller using it is alive.
+    ///
+    /// @returns Handle of the shared controller
+    #[napi]
+    pub fn share(&self) -> u32 {
+        let mut shared = SHARED_CONTROLLERS.lock().unwrap();
+        shared.retain(|_, controller| controller.inner.strong_count() > 0);
+        let existing = shared
+            .iter()
+            .find(|(_, controller)| Weak::ptr_eq(&controller.inner, &Arc::downgrade(&self.inner)));
+        if let Some((handle, _)) = existing {
+            return *handle;
+        }
+
+        let handle = NEXT_SHARED_HANDLE.fetch_add(1, Ordering::Relaxed);
+        shared.insert(
+            handle,
+            SharedController {
+                inner: Arc::downgrade(&self.inner),
+                route_table: Arc::downgrade(&self.route_table),
+                template: self.template,
+                fixtures: Arc::downgrade(&self.fixtures),
+            },
+        );
+        handle
+    }
+
+    /// Use a controller shared by `share()`, e.g. from a worker thread.
+    ///
+    /// @param handle - Handle returned by `share()`
+    /// @throws Error if no controller with the handle is alive
+    #[napi(factory)]
+    pub fn from_shared(env: Env, handle: u32) -> Result<Self> {
+        let shared = SHARED_CONTROLLERS.lock().unwrap();
+        let controller = shared.get(&handle);
+        let inner = controller.and_then(|c| c.inner.upgrade());
+        let route_table = controller.and_then(|c| c.route_table.upgrade());
+        match (controller, inner, route_table) {
+            (Some(controller), Some(inner), Some(route_table)) => {
+                let history = inner.read().unwrap().history();
+                Ok(Self {
+                    inner,
+                    route_table,
+                    template: controller.template,
+                    handlers: RefCell::new(HashMap::new()),
+                    request_listeners: Arc::default(),
+                    fixtures: controller.fixtures.upgrade().unwrap_or_default(),
+                    history,
+                })
+            }
+            _ => Err(js_error(
+                &env,
+                MockitoError::new(
+                    ErrorCode::SharedControllerNotFound,
+                    format!("No shared controller with handle {}", handle),
+                )
+                .with("handle", handle),
+            )),
+        }
+    }
+
+    /// Reload routes and collections from files.
+    ///
+    /// Re-resolves the current collection and re-applies route overrides that are
+    /// still compatible with the new data. If loading or resolution fails, the
+    /// controller keeps its previous state.
+    ///
+    /// @param collectionsPath - Path or glob pattern to collections file(s)
+    /// @param routesPath - Path or glob pattern to routes file(s)
+    /// @param groupsPath - Optional path to route groups file
+    #[napi]
+    pub fn reload(
+        &self,
+        env: Env,
+        collections_path: String,
+        routes_path: String,
+        groups_path: Option<String>,
+    ) -> Result<()> {
+        let manager = load_manager(&collections_path, &routes_path, groups_path.as_deref())
+            .map_err(|e| e.into_napi(&env))?;
+        self.update(&env, |controller| controller.reload(manager))
     }
 
     /// Apply a collection by ID
     #[napi]
-    pub fn use_collection(&self, collection_id: String) -> Result<()> {
-        let mut controller = self.inner.lock().unwrap();
-        controller
-            .use_collection(&collection_id)
-            .map_err(|e| Error::from_reason(e.to_string()))
+    pub fn use_collection(&self, env: Env, collection_id: String) -> Result<()> {
+        self.update(&env, |controller| controller.use_collection(&collection_id))
     }
 
     /// Apply specific HTTP routes without changing the entire collection.
     ///
     /// This method allows dynamic route switching by:
-    /// - Resolving provided route references (`route:preset:variant`)
+    /// - Resolving provided route references (`route:preset:variant`),
+    ///   expanding wildcard references like `payments-*:default:timeout`
     /// - Merging them with existing active routes
     /// - Overriding routes with the same route ID
     ///
-    /// @param routes - Array of route reference strings in format `route_id:preset_id:variant_id`
+    /// @param routes - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
     /// @throws Error if route, preset, or variant not found
     /// @throws Error if route is a WebSocket route (use useSocket instead)
     #[napi]
-    pub fn use_routes(&self, routes: Vec<String>) -> Result<()> {
-        let mut controller = self.inner.lock().unwrap();
-        controller
-            .use_routes(&routes)
-            .map_err(|e| Error::from_reason(e.to_string()))
+    pub fn use_routes(&self, env: Env, routes: Vec<String>) -> Result<()> {
+        self.update(&env, |controller| controller.use_routes(&routes))
     }
 
     /// Apply specific WebSocket routes without changing the entire collection.
     ///
     /// This method allows dynamic WebSocket route switching by:
-    /// - Resolving provided route references (`route:preset:variant`)
+    /// - Resolving provided route references (`route:preset:variant`),
+    ///   expanding wildcard references like `ws-*:default:error`
     /// - Merging them with existing active routes
     /// - Overriding routes with the same route ID
     ///
-    /// @param routes - Array of route reference strings in format `route_id:preset_id:variant_id`
+    /// @param routes - Array of route reference strings in format `route_id[:preset_id[:variant_id]]`
     /// @throws Error if route, preset, or variant not found
     /// @throws Error if route is not a WebSocket route (use useRoutes instead)
     #[napi]
-    pub fn use_socket(&self, routes: Vec<String>) -> Result<()> {
-        let mut controller = self.inner.lock().unwrap();
+    pub fn use_socket(&self, env: Env, routes: Vec<String>) -> Result<()> {
+        self.update(&env, |controller| controller.use_socket(&routes))
+    }
+
+    /// Apply a preset and variant to every route tagged with `tag`.
+    ///
+    /// @param tag - Route tag (e.g. "payments")
+    /// @param presetId - Preset to activate on each tagged route
+    /// @param variantId - Variant to activate on each tagged route
+    /// @throws Error if no route has the tag or a tagged route lacks the preset/variant
+    #[napi]
+    pub fn use_tag(
+        &self,
+        env: Env,
+        tag: String,
+        preset_id: String,
+        variant_id: String,
+    ) -> Result<()> {
+        self.update(&env, |controller| {
+            controller.use_tag(&tag, &preset_id, &variant_id)
+        })
+    }
+
+    /// Deactivate every active route tagged with `tag`.
+    ///
+    /// @param tag - Route tag (e.g. "payments")
+    /// @throws Error if no route has the tag
+    #[napi]
+    pub fn disable_tag(&self, env: Env, tag: String) -> Result<()> {
+        self.update(&env, |controller| controller.disable_tag(&tag))
+    }
+
+    /// Disable routes by ID so they are skipped during matching.
+    ///
+    /// Routes stay disabled across collection switches until enabled again
+    /// or `resetRoutes` is called.
+    ///
+    /// @param routeIds - IDs of routes to disable
+    /// @throws Error if any route is not found
+    #[napi]
+    pub fn disable_routes(&self, env: Env, route_ids: Vec<String>) -> Result<()> {
+        self.update(&env, |controller| controller.disable_routes(&route_ids))
+    }
+
+    /// Enable routes previously disabled via `disableRoutes`.
+    ///
+    /// @param routeIds - IDs of routes to enable
+    /// @throws Error if any route is not found
+    #[napi]
+    pub fn enable_routes(&self, env: Env, route_ids: Vec<String>) -> Result<()> {
+        self.update(&env, |controller| controller.enable_routes(&route_ids))
+    }
+
+    /// Get IDs of disabled routes, sorted
+    #[napi]
+    pub fn get_disabled_routes(&self) -> Vec<String> {
+        let controller = self.inner.read().unwrap();
         controller
-            .use_socket(&routes)
-            .map_err(|e| Error::from_reason(e.to_string()))
+            .disabled_routes()
+            .into_iter()
+            .map(String::from)
+            .collect()
     }
 
     /// Reset routes to collection defaults or clear all routes.
     ///
     /// If a collection is selected, restores routes to the collection's initial state.
     /// If no collection is selected, clears all routes (empty state).
+    /// Disabled routes are enabled again in both cases.
     #[napi]
-    pub fn reset_routes(&self) -> Result<()> {
-        let mut controller = self.inner.lock().unwrap();
-        controller
-            .reset_routes()
-            .map_err(|e| Error::from_reason(e.to_string()))
+    pub fn reset_routes(&self, env: Env) -> Result<()> {
+        self.update(&env, |controller| controller.reset_routes())
+    }
+
+    /// Cache the route matched by repeated identical requests.
+    ///
+    /// Overrides the `match_cache` setting. Cached matches are dropped on every
+    /// route change. Routes matching by expression disable the cache.
+    ///
+    /// @param capacity - Number of distinct requests to cache, `null` or 0 disables caching
+    #[napi]
+    pub fn set_match_cache(&self, env: Env, capacity: Option<u32>) -> Result<()> {
+        self.update(&env, |controller| {
+            controller.set_match_cache(capacity.map(|c| c as usize));
+            Ok(())
+        })
+    }
+
+    /// Report memory used by loaded definitions and cached fixture files.
+    ///
+    /// Definition sizes are estimated from their serialized size.
+    #[napi]
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let definitions = self.inner.read().unwrap().estimated_size();
+        MemoryUsage {
+            definitions: saturating_i64(definitions as u64),
+            fixtures: saturating_i64(self.fixtures.size() as u64),
+            fixture_limit: saturating_i64(self.fixtures.capacity() as u64),
+        }
+    }
+
+    /// Limit the memory kept for fixture file contents.
+    ///
+    /// Overrides the `fixture_cache` setting. Least recently served fixtures are
+    /// evicted beyond the limit and read from disk again when next served.
+    ///
+    /// @param bytes - Size limit, 0 reads fixtures on every use
+    /// @throws Error if the limit is negative
+    #[napi]
+    pub fn set_fixture_cache_limit(&self, env: Env, bytes: i64) -> Result<()> {
+        let bytes = usize::try_from(bytes).map_err(|_| {
+            js_error(
+                &env,
+                MockitoError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("Fixture cache limit must not be negative, got {}", bytes),
+                ),
+            )
+        })?;
+        self.fixtures.set_capacity(bytes);
+        Ok(())
+    }
+
+    /// Create an isolated child controller.
+    ///
+    /// The child shares loaded routes and collections with this controller and starts
+    /// from its current state, but overrides applied to either don't affect the other.
+    /// Useful for giving each parallel test worker its own controller.
+    #[napi]
+    pub fn fork(&self) -> MocksController {
+        let controller = self.inner.read().unwrap();
+        Self::from_core(controller.fork())
+    }
+
+    /// Capture the current controller state (active collection, route overrides and
+    /// request history).
+    ///
+    /// Use with `restore` to roll back changes and requests made during a test.
+    /// Coverage hit counts are cumulative and the audit log is append-only, so
+    /// neither is captured.
+    #[napi]
+    pub fn snapshot(&self) -> ControllerSnapshot {
+        let controller = self.inner.read().unwrap();
+        ControllerSnapshot {
+            inner: controller.snapshot(),
+        }
+    }
+
+    /// Restore controller state from a snapshot.
+    ///
+    /// A snapshot can be restored multiple times.
+    #[napi]
+    pub fn restore(&self, snapshot: &ControllerSnapshot) {
+        let mut controller = self.inner.write().unwrap();
+        controller.restore(snapshot.inner.clone());
+        self.route_table.store(controller.route_table());
     }
 
     /// Get current collection ID
     #[napi(getter)]
     pub fn current_collection(&self) -> Option<String> {
-        let controller = self.inner.lock().unwrap();
+        let controller = self.inner.read().unwrap();
         controller.active_collection_id().map(String::from)
     }
 
+    /// Get project settings (empty for controllers not created with `fromProject`)
+    #[napi(getter)]
+    pub fn settings(&self) -> Settings {
+        let controller = self.inner.read().unwrap();
+        controller.settings().into()
+    }
+
     /// Get all active routes (HTTP + WS)
     #[napi]
     pub fn get_active_routes(&self) -> Vec<ActiveRoute> {
-        let controller = self.inner.lock().unwrap();
+        let route_table = self.route_table.load();
+        route_table.routes().iter().map(ActiveRoute::from).collect()
+    }
+
+    /// Find the active route matching a request.
+    ///
+    /// Lookup doesn't lock the controller, so it never waits for concurrent
+    /// route switching and always sees a consistent set of routes.
+    ///
+    /// @returns Matching route or `null` if no route matches
+    #[napi]
+    pub fn find_route(&self, request: Request) -> Option<ActiveRoute> {
+        let started = Instant::now();
+        let route_table = self.route_table.load();
+        let request = CoreRequest::from(request);
+        let found = route_table.find_route(&request);
+        self.history
+            .record(&route_table, &request, found, None, started.elapsed());
+        self.emit_request(&request, found);
+        found.map(ActiveRoute::from)
+    }
+
+    /// Find the active route matching a request and render its variant.
+    ///
+    /// Templates in variant headers and body are rendered against the request,
+    /// with the project `template` engine if the variant has none. Routes with a
+    /// handler registered by `onRoute()` are answered by the handler instead.
+    /// Bodies of `file:` fixtures and inline `file:data:` bodies are loaded into `bodyBuffer`.
+    /// The request is recorded in the history even if answering it fails.
+    ///
+    /// @returns Rendered variant or `null` if no route matches
+    /// @throws Error if a template fails to render, a fixture can't be read or a handler throws
+    #[napi]
+    pub fn respond(&self, env: Env, request: Request) -> Result<Option<Variant>> {
+        let started = Instant::now();
+        let route_table = self.route_table.load();
+        let request = CoreRequest::from(request);
+        let found = route_table.find_route_with_context(&request);
+        let active_route = found.as_ref().map(|(active_route, _)| *active_route);
+        self.emit_request(&request, active_route);
+        let variant = found
+            .map(|(active_route, context)| self.answer(&env, &route_table, active_route, context))
+            .transpose();
+        // Fixture contents are kept inline, so skip converting when nothing is recorded
+        let response = variant
+            .as_ref()
+            .ok()
+            .and_then(Option::as_ref)
+            .filter(|_| self.history.capacity() > 0)
+            .map(CoreVariant::from);
+        self.history.record(
+            &route_table,
+            &request,
+            active_route,
+            response.as_ref(),
+            started.elapsed(),
+        );
+        variant
+    }
+
+    /// Build the 404 response of a request that matched no route.
+    ///
+    /// The body holds an `error` message. With the `debug` setting enabled, it
+    /// also lists the routes closest to matching the request in `near_matches`,
+    /// with the condition each of them failed.
+    #[napi]
+    pub fn not_found(&self, request: Request) -> Variant {
+        let controller = self.inner.read().unwrap();
+        controller.not_found(&CoreRequest::from(request)).into()
+    }
+
+    /// Get requests looked up by `respond()` or `findRoute()`, oldest first.
+    ///
+    /// Only the latest requests are kept, see the `history` setting. Handles of
+    /// `share()` record into the same history, forks into their own.
+    #[napi]
+    pub fn get_requests(&self) -> Vec<RecordedRequest> {
+        self.history
+            .requests()
+            .into_iter()
+            .map(RecordedRequest::from)
+            .collect()
+    }
+
+    /// Get recorded requests that matched a route, oldest first.
+    ///
+    /// @param routeId - Route ID
+    #[napi]
+    pub fn get_requests_for(&self, route_id: String) -> Vec<RecordedRequest> {
+        self.history
+            .requests_for(&route_id)
+            .into_iter()
+            .map(RecordedRequest::from)
+            .collect()
+    }
+
+    /// Remove all requests from the request history, with their serve times.
+    #[napi]
+    pub fn clear_history(&self) {
+        self.history.clear();
+    }
+
+    /// Verify the recorded requests that matched a route.
+    ///
+    /// The verification covers requests recorded up to this call.
+    ///
+    /// @param routeId - Route ID
+    #[napi]
+    pub fn verify(&self, route_id: String) -> Verification {
+        let requests = self.history.requests_for(&route_id);
+        Verification {
+            inner: CoreVerification::new(route_id, requests),
+        }
+    }
+
+    /// Check that every recorded request matched a route.
+    ///
+    /// @throws Error with code `VERIFICATION_FAILED` listing the unmatched `requests`
+    #[napi]
+    pub fn verify_no_unmatched(&self, env: Env) -> Result<()> {
+        verify_no_unmatched(&self.history.requests()).map_err(|e| js_error(&env, e))
+    }
+
+    /// Get requests that matched no route, grouped by transport, method and URL path.
+    ///
+    /// Unmatched requests are captured even if the request history is disabled,
+    /// and removed by `clearHistory()`.
+    #[napi]
+    pub fn get_unmatched(&self) -> Vec<UnmatchedRequest> {
+        self.history
+            .unmatched()
+            .requests()
+            .into_iter()
+            .map(UnmatchedRequest::from)
+            .collect()
+    }
+
+    /// Render route skeletons answering the requests that matched no route.
+    ///
+    /// YAML reports hold one route document per request, commented with the
+    /// request and its count, ready to paste into a routes file. JSON reports
+    /// hold an array of routes.
+    ///
+    /// @param format - Format of the report, YAML if not set
+    #[napi]
+    pub fn unmatched_report(&self, env: Env, format: Option<ExportFormat>) -> Result<String> {
+        self.history
+            .unmatched()
+            .report(format.unwrap_or(ExportFormat::Yaml).into())
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Render the request history with the served responses as a HAR log, in JSON.
+    ///
+    /// The log opens in browser devtools and HAR viewers. Entries carry the
+    /// matched `_routeId`, `_presetId` and `_variantId`; requests that matched
+    /// no route are exported with a 404 response.
+    ///
+    /// ```js
+    /// fs.writeFileSync('traffic.har', controller.exportHar());
+    /// ```
+    #[napi]
+    pub fn export_har(&self) -> String {
+        export_har(&self.history.requests())
+    }
+
+    /// Get serve time percentiles of the routes that answered requests.
+    ///
+    /// Serve times cover route lookup, response rendering and the injected
+    /// `delay`, so a perf test can tell mock latency from client-side time.
+    /// Unlike the request history they aggregate every request since the last
+    /// `clearHistory()`.
+    #[napi]
+    pub fn latency_stats(&self) -> Vec<RouteLatency> {
+        self.history
+            .latencies()
+            .routes()
+            .into_iter()
+            .map(RouteLatency::from)
+            .collect()
+    }
+
+    /// Report the routes, presets and variants that answered requests so far.
+    ///
+    /// Hit counts are kept by `clearHistory()` and reloads, so they cover a
+    /// whole test run:
+    ///
+    /// ```js
+    /// afterAll(() => expect(controller.coverageReport().untouched).toEqual([]));
+    /// ```
+    #[napi]
+    pub fn coverage_report(&self) -> CoverageReport {
+        self.inner.read().unwrap().coverage_report().into()
+    }
+
+    /// Reset the hit counts of `coverageReport()`.
+    #[napi]
+    pub fn reset_coverage(&self) {
+        self.history.coverage().clear();
+    }
+
+    /// Change the number of requests kept in the request history.
+    ///
+    /// Overrides the `history` setting. The oldest requests beyond the new
+    /// capacity are dropped.
+    ///
+    /// @param capacity - Number of kept requests, `null` restores the default of 1000 and 0 disables recording
+    #[napi]
+    pub fn set_history_capacity(&self, capacity: Option<u32>) {
+        self.history
+            .set_capacity(capacity.map_or(DEFAULT_HISTORY_CAPACITY, |c| c as usize));
+    }
+
+    /// Answer requests to a route with a JS handler instead of its static variant.
+    ///
+    /// `respond()` calls the handler synchronously with the request context
+    /// (`method`, `url`, `params`, `query`, `headers`, `payload`) whenever the route
+    /// matches, whichever preset and variant are active. The returned response
+    /// replaces the variant, keeping its ID; returning `undefined` falls back to
+    /// the variant. Registering again replaces the previous handler.
+    ///
+    /// @param routeId - Route ID
+    /// @param handler - Function receiving the request context and returning a `RouteResponse`
+    #[napi(ts_args_type = "routeId: string, handler: (request: any) => RouteResponse | undefined")]
+    pub fn on_route(&self, route_id: String, handler: RouteHandler) {
+        self.handlers
+            .borrow_mut()
+            .insert(route_id, Rc::new(handler));
+    }
+
+    /// Remove the handler of a route registered by `onRoute()`.
+    ///
+    /// @returns `false` if the route had no handler
+    #[napi]
+    pub fn off_route(&self, route_id: String) -> bool {
+        self.handlers.borrow_mut().remove(&route_id).is_some()
+    }
+
+    /// Subscribe to controller state changes.
+    ///
+    /// The callback is invoked asynchronously on the event loop after each change
+    /// (collection switches, route overrides, resets, reloads, ...), so it may call
+    /// controller methods itself. Subscriptions don't keep the process alive.
+    ///
+    /// @param callback - Function receiving `ControllerEvent`s
+    #[napi]
+    pub fn on_change(
+        &self,
+        callback: ThreadsafeFunction<ControllerEvent, (), ControllerEvent, Status, false, true>,
+    ) -> ChangeSubscription {
+        let mut controller = self.inner.write().unwrap();
+        let listener_id = controller.on_change(move |event| {
+            callback.call(
+                ControllerEvent::from(event),
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+        });
+        self.subscription(Listener::Change(listener_id))
+    }
+
+    /// Subscribe to an event.
+    ///
+    /// - `request`: a request looked up by `respond()` or `findRoute()` matched a route
+    /// - `unmatched`: a looked up request matched no route
+    /// - `collectionChanged`: another collection was activated
+    /// - `reloaded`: routes and collections were reloaded
+    ///
+    /// Like `onChange()`, callbacks are invoked asynchronously on the event loop
+    /// and subscriptions don't keep the process alive.
+    ///
+    /// @param event - Event name
+    /// @param callback - Function receiving a `RequestEvent` (`request`, `unmatched`) or `ControllerEvent`
+    #[napi(
+        ts_args_type = "event: ControllerEventName, callback: (event: RequestEvent | ControllerEvent) => void"
+    )]
+    pub fn on(&self, event: ControllerEventName, callback: EventCallback) -> ChangeSubscription {
+        let kind = match event {
+            ControllerEventName::Request | ControllerEventName::Unmatched => {
+                let mut listeners = self.request_listeners.write().unwrap();
+                let id = listeners.next_id;
+                listeners.next_id += 1;
+                let matched = event == ControllerEventName::Request;
+                listeners.entries.push((id, matched, callback));
+                return self.subscription(Listener::Request(id));
+            }
+            ControllerEventName::CollectionChanged => ControllerEventKind::CollectionChanged,
+            ControllerEventName::Reloaded => ControllerEventKind::Reloaded,
+        };
+        let mut controller = self.inner.write().unwrap();
+        let listener_id = controller.on_change(move |event| {
+            let event = ControllerEvent::from(event);
+            if event.kind == kind {
+                callback.call(Either::B(event), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        });
+        self.subscription(Listener::Change(listener_id))
+    }
+
+    /// Stream every request looked up from now on as a JSON live event.
+    ///
+    /// Meant for an admin WebSocket at `/__admin/live` feeding a dashboard with
+    /// the traffic hitting the mock: each frame holds the request, the matched
+    /// route, preset and variant, the response status and the serve time.
+    /// Events are streamed regardless of the history capacity, and frames are
+    /// dropped if the callback falls behind. Subscriptions don't keep the
+    /// process alive.
+    ///
+    /// ```js
+    /// controller.onLive((frame) => socket.send(frame));
+    /// ```
+    ///
+    /// @param callback - Function receiving JSON text frames
+    #[napi]
+    pub fn on_live(
+        &self,
+        callback: ThreadsafeFunction<String, (), String, Status, false, true>,
+    ) -> ChangeSubscription {
+        let mut events = self.history.tap().subscribe();
+        let task = napi::bindgen_prelude::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        callback.call(event.to_json(), ThreadsafeFunctionCallMode::NonBlocking);
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        self.subscription(Listener::Live(task.abort_handle()))
+    }
+
+    /// Label state changes applied from now on in the audit log.
+    ///
+    /// Set it to the name of the running test, so changes leaking into later
+    /// tests can be traced back to it.
+    ///
+    /// ```js
+    /// beforeEach(() => controller.setAuditLabel(expect.getState().currentTestName));
+    /// ```
+    ///
+    /// @param label - Label of later changes, `null` removes it
+    #[napi]
+    pub fn set_audit_label(&self, label: Option<String>) {
+        let controller = self.inner.read().unwrap();
+        controller.set_audit_label(label.as_deref());
+    }
+
+    /// Get the state changes applied so far, oldest first.
+    ///
+    /// Every change delivered to `onChange()` callbacks is recorded with its
+    /// time and the label set by `setAuditLabel()`. Handles of `share()` share
+    /// the log and it's kept on reload, forks start with an empty one.
+    #[napi]
+    pub fn get_audit_log(&self) -> Vec<AuditEntry> {
+        let controller = self.inner.read().unwrap();
         controller
-            .get_active_routes()
-            .iter()
-            .map(|a| ActiveRoute {
-                route: Route::from(&a.route),
-                preset: Preset::from(&a.preset),
-                variant: Variant::from(&a.variant),
-            })
+            .audit_log()
+            .into_iter()
+            .map(AuditEntry::from)
+            .collect()
+    }
+
+    /// Remove all state changes from the audit log, keeping the label.
+    #[napi]
+    pub fn clear_audit_log(&self) {
+        self.inner.read().unwrap().clear_audit_log();
+    }
+
+    /// Wait for the next request looked up by `respond()` or `findRoute()`.
+    ///
+    /// Lets tests await a request made by the code under test, e.g. the next call to
+    /// a route, instead of polling. Use `on('request')` to receive every request.
+    ///
+    /// @param routeId - Only resolve for a request matching this route (default: any request, matched or not)
+    /// @param timeoutMs - Reject if no request arrives in time (default: 5000)
+    /// @throws Error with code `TIMEOUT` if no request arrives in time
+    #[napi(ts_return_type = "Promise<RequestEvent>")]
+    pub fn next_request<'env>(
+        &self,
+        env: &'env Env,
+        route_id: Option<String>,
+        timeout_ms: Option<u32>,
+    ) -> Result<PromiseRaw<'env, RequestEvent>> {
+        let (sender, receiver) = oneshot::channel();
+        let mut listeners = self.request_listeners.write().unwrap();
+        // Drop waiters whose wait timed out, in case no request came since
+        listeners.waiters.retain(|(_, sender)| !sender.is_closed());
+        listeners.waiters.push((route_id.clone(), sender));
+        drop(listeners);
+
+        let timeout_ms = timeout_ms.unwrap_or(DEFAULT_NEXT_REQUEST_TIMEOUT_MS);
+        let timeout = Duration::from_millis(timeout_ms.into());
+        env.spawn_future_with_callback(
+            async move { Ok(tokio::time::timeout(timeout, receiver).await) },
+            move |env, result| match result {
+                Ok(Ok(event)) => Ok(event),
+                _ => {
+                    let message = match &route_id {
+                        Some(route_id) => format!(
+                            "No request to route '{}' within {} ms",
+                            route_id, timeout_ms
+                        ),
+                        None => format!("No request within {} ms", timeout_ms),
+                    };
+                    let error = MockitoError::new(ErrorCode::Timeout, message)
+                        .with("routeId", route_id)
+                        .with("timeoutMs", timeout_ms);
+                    Err(js_error(env, error))
+                }
+            },
+        )
+    }
+
+    /// Find all active routes matching a request, in priority order.
+    ///
+    /// The first route is the one returned by `findRoute`, the rest are shadowed by it.
+    /// Like `findRoute`, lookup doesn't lock the controller.
+    #[napi]
+    pub fn find_routes(&self, request: Request) -> Vec<ActiveRoute> {
+        let route_table = self.route_table.load();
+        route_table
+            .find_routes(&CoreRequest::from(request))
+            .into_iter()
+            .map(ActiveRoute::from)
             .collect()
     }
+
+    /// Report preset expressions that are invalid or fail to evaluate for a request.
+    ///
+    /// Routes with a failing expression never match, so this explains why `findRoute`
+    /// skipped them.
+    #[napi]
+    pub fn diagnose(&self, request: Request) -> Vec<ExpressionDiagnostic> {
+        let route_table = self.route_table.load();
+        route_table
+            .diagnose(&CoreRequest::from(request))
+            .into_iter()
+            .map(ExpressionDiagnostic::from)
+            .collect()
+    }
+
+    /// Explain how every active route matches a request.
+    ///
+    /// Reports the first condition each route fails, with expected and actual
+    /// values, in priority order. Routes failing at a later `MatchStep` are closer
+    /// matches, so test failures can point at the route a request almost hit.
+    #[napi]
+    pub fn explain(&self, request: Request) -> Vec<RouteExplanation> {
+        let route_table = self.route_table.load();
+        route_table
+            .explain(&CoreRequest::from(request))
+            .into_iter()
+            .map(RouteExplanation::from)
+            .collect()
+    }
+}
+
+impl MocksController {
+    fn from_core(controller: CoreMocksController) -> Self {
+        let fixture_limit = controller.settings().fixture_cache;
+        Self {
+            route_table: Arc::new(ArcSwap::new(controller.route_table())),
+            template: controller.settings().template.unwrap_or_default(),
+            history: controller.history(),
+            inner: Arc::new(RwLock::new(controller)),
+            handlers: RefCell::new(HashMap::new()),
+            request_listeners: Arc::default(),
+            fixtures: Arc::new(fixture_limit.map(FixtureCache::new).unwrap_or_default()),
+        }
+    }
+
+    /// Answer a matched request by the route handler or the rendered variant.
+    fn answer(
+        &self,
+        env: &Env,
+        route_table: &RouteTable,
+        active_route: &CoreActiveRoute,
+        context: RequestContext,
+    ) -> Result<Variant> {
+        // Cloned out, so the handler may register other handlers while running
+        let handler = self.handlers.borrow().get(&*active_route.route.id).cloned();
+        if let Some(handler) = handler {
+            let response = handler.borrow_back(env)?.call(context.value().clone())?;
+            if let Some(response) = response {
+                return Ok(response.into_variant(&active_route.variant().id));
+            }
+        }
+        let variant = route_table
+            .render(active_route, &context, self.template)
+            .map_err(|e| js_error(env, e))?;
+        let fixture = match variant.body_file() {
+            Some(path) => Some(self.fixtures.read(path).map_err(|e| js_error(env, e))?),
+            None => None,
+        };
+        let mut variant = Variant::from(variant);
+        if let Some(data) = fixture {
+            variant.body_buffer = Some(data.to_vec().into());
+        }
+        Ok(variant)
+    }
+
+    /// Notify request listeners about a looked up request.
+    ///
+    /// Concurrent lookups only share the listeners, they are locked exclusively
+    /// while `nextRequest()` calls are waiting.
+    fn emit_request(&self, request: &CoreRequest, active_route: Option<&CoreActiveRoute>) {
+        let listeners = self.request_listeners.read().unwrap();
+        listeners.notify(request, active_route);
+        if listeners.waiters.is_empty() {
+            return;
+        }
+        drop(listeners);
+        self.request_listeners
+            .write()
+            .unwrap()
+            .resolve_waiters(request, active_route);
+    }
+
+    /// Create a subscription removing a listener.
+    fn subscription(&self, listener: Listener) -> ChangeSubscription {
+        ChangeSubscription {
+            controller: Arc::clone(&self.inner),
+            request_listeners: Arc::clone(&self.request_listeners),
+            listener,
+        }
+    }
+
+    /// Apply a change to the controller and publish the resulting route table.
+    fn update<F>(&self, env: &Env, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut CoreMocksController) -> std::result::Result<(), ResolveError>,
+    {
+        let mut controller = self.inner.write().unwrap();
+        let result = f(&mut controller);
+        self.route_table.store(controller.route_table());
+        result.map_err(|e| js_error(env, e))
+    }
 }
@@ -1,16 +1,32 @@
 //! NAPI bindings for mocks operations.
 
-use crate::config::{Preset, Route, Variant};
-use mockito_core::config::parser;
+use crate::config::{Collection, Group, Preset, Route, Variant};
+use crate::error::{js_error, ErrorCode, MockitoError};
+use crate::watcher::{WatchEvent, WatchSubscription, DEFAULT_INTERVAL_MS};
+use mockito_core::config::export::ExportFormat as CoreExportFormat;
+use mockito_core::config::lint::{LintRule as CoreLintRule, LintWarning as CoreLintWarning};
+use mockito_core::config::reload::ProjectReloader;
+use mockito_core::config::watcher::{WatchEvent as CoreWatchEvent, Watcher};
+use mockito_core::config::{lint_with, parser, project};
 use mockito_core::mocks::manager::{
-    ActiveRoute as CoreActiveRoute, MocksManager as CoreMocksManager,
+    ActiveRoute as CoreActiveRoute, DefinitionKind, DuplicatePolicy as CoreDuplicatePolicy,
+    MocksManager as CoreMocksManager,
 };
+use mockito_core::mocks::validation::{
+    self, Severity as CoreSeverity, ValidationIssue as CoreValidationIssue,
+};
+use mockito_core::template::Engine as CoreTemplateEngine;
 use mockito_core::types::{
-    preset::Preset as CorePreset, route::Route as CoreRoute, variant::Variant as CoreVariant,
+    collection::Collection as CoreCollection, preset::Preset as CorePreset,
+    route::Route as CoreRoute, variant::Variant as CoreVariant,
 };
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[napi(object)]
 pub struct ActiveRoute {
@@ -19,40 +35,389 @@ pub struct ActiveRoute {
     pub variant: Variant,
 }
 
-impl From<CoreActiveRoute> for ActiveRoute {
-    fn from(a: CoreActiveRoute) -> Self {
+impl From<&CoreActiveRoute> for ActiveRoute {
+    fn from(a: &CoreActiveRoute) -> Self {
         Self {
-            route: Route::from(a.route),
-            preset: Preset::from(a.preset),
-            variant: Variant::from(a.variant),
+            route: Route::from(&*a.route),
+            preset: Preset::from(a.preset()),
+            variant: Variant::from(a.variant()),
         }
     }
 }
 
+impl From<CoreActiveRoute> for ActiveRoute {
+    fn from(a: CoreActiveRoute) -> Self {
+        Self::from(&a)
+    }
+}
+
 impl From<&ActiveRoute> for CoreActiveRoute {
     fn from(a: &ActiveRoute) -> Self {
-        Self {
-            route: CoreRoute::from(&a.route),
-            preset: CorePreset::from(&a.preset),
-            variant: CoreVariant::from(&a.variant),
-        }
+        Self::new(
+            CoreRoute::from(&a.route),
+            CorePreset::from(&a.preset),
+            CoreVariant::from(&a.variant),
+        )
     }
 }
 
 impl From<ActiveRoute> for CoreActiveRoute {
     fn from(a: ActiveRoute) -> Self {
+        Self::new(
+            CoreRoute::from(a.route),
+            CorePreset::from(a.preset),
+            CoreVariant::from(a.variant),
+        )
+    }
+}
+
+/// How definitions reusing an already loaded ID are handled
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Replace the loaded definition
+    Override,
+    /// Fail loading
+    Error,
+    /// Unite presets and variants of routes, replace other definitions
+    Merge,
+}
+
+impl From<DuplicatePolicy> for CoreDuplicatePolicy {
+    fn from(p: DuplicatePolicy) -> Self {
+        match p {
+            DuplicatePolicy::Override => CoreDuplicatePolicy::Override,
+            DuplicatePolicy::Error => CoreDuplicatePolicy::Error,
+            DuplicatePolicy::Merge => CoreDuplicatePolicy::Merge,
+        }
+    }
+}
+
+/// Load a project directory (`routes/`, `collections/`, `mockito.config.*`) or combined file.
+pub(crate) fn load_project(
+    dir: &str,
+    duplicate_policy: Option<DuplicatePolicy>,
+) -> std::result::Result<project::Project, MockitoError> {
+    let policy = duplicate_policy.map(Into::into).unwrap_or_default();
+    project::load_project_with(dir, CoreMocksManager::with_duplicate_policy(policy))
+        .map_err(|e| MockitoError::from(e).context("Failed to load project"))
+}
+
+/// Files a manager or controller is loaded from.
+#[derive(Clone)]
+pub(crate) enum LoadSource {
+    /// Separate collection, route and optional group files
+    Files {
+        collections_path: String,
+        routes_path: String,
+        groups_path: Option<String>,
+    },
+    /// Project directory or combined config file
+    Project {
+        path: String,
+        duplicate_policy: Option<DuplicatePolicy>,
+    },
+}
+
+impl LoadSource {
+    /// Load a core manager from the files.
+    pub(crate) fn load_manager(&self) -> std::result::Result<CoreMocksManager, MockitoError> {
+        match self {
+            LoadSource::Files {
+                collections_path,
+                routes_path,
+                groups_path,
+            } => load_manager(collections_path, routes_path, groups_path.as_deref()),
+            LoadSource::Project {
+                path,
+                duplicate_policy,
+            } => load_project(path, *duplicate_policy).map(|project| project.manager),
+        }
+    }
+
+    /// Load a core manager with the project settings it's validated with.
+    fn load_with_project(
+        &self,
+    ) -> std::result::Result<(CoreMocksManager, LoadedProject), MockitoError> {
+        match self {
+            LoadSource::Files { .. } => Ok((self.load_manager()?, LoadedProject::default())),
+            LoadSource::Project {
+                path,
+                duplicate_policy,
+            } => {
+                let project = load_project(path, *duplicate_policy)?;
+                let loaded = LoadedProject {
+                    sources: project.sources,
+                    template: project.settings.template.unwrap_or_default(),
+                    partials: project.settings.partials.unwrap_or_default(),
+                };
+                Ok((project.manager, loaded))
+            }
+        }
+    }
+
+    /// Core policy for duplicate IDs, only configurable for projects.
+    fn duplicate_policy(&self) -> CoreDuplicatePolicy {
+        match self {
+            LoadSource::Files { .. } => CoreDuplicatePolicy::default(),
+            LoadSource::Project {
+                duplicate_policy, ..
+            } => duplicate_policy.map(Into::into).unwrap_or_default(),
+        }
+    }
+
+    /// Glob patterns of the files, watched by `MocksManager.watch()`.
+    fn watch_patterns(&self) -> Vec<String> {
+        match self {
+            LoadSource::Files {
+                collections_path,
+                routes_path,
+                groups_path,
+            } => [
+                Some(collections_path),
+                Some(routes_path),
+                groups_path.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect(),
+            LoadSource::Project { path, .. } => vec![path.clone()],
+        }
+    }
+}
+
+/// Loading of a mocks manager on a background thread.
+pub struct LoadManagerTask(LoadSource);
+
+impl Task for LoadManagerTask {
+    type Output = std::result::Result<MocksManager, MockitoError>;
+    type JsValue = MocksManager;
+
+    // Failures are converted in `resolve`, since JS errors need an env
+    fn compute(&mut self) -> Result<Self::Output> {
+        Ok(MocksManager::load_from(self.0.clone()))
+    }
+
+    fn resolve(&mut self, env: Env, manager: Self::Output) -> Result<Self::JsValue> {
+        manager.map_err(|e| e.into_napi(&env))
+    }
+}
+
+/// Load routes, collections and optional route groups from files into a new core manager.
+pub(crate) fn load_manager(
+    collections_path: &str,
+    routes_path: &str,
+    groups_path: Option<&str>,
+) -> std::result::Result<CoreMocksManager, MockitoError> {
+    // Load routes and collections
+    let routes = parser::load_routes(routes_path)
+        .map_err(|e| MockitoError::from(e).context("Failed to load routes"))?;
+    let collections = parser::load_collections(collections_path)
+        .map_err(|e| MockitoError::from(e).context("Failed to load collections"))?;
+    let groups = match groups_path {
+        Some(path) => parser::load_groups(path)
+            .map_err(|e| MockitoError::from(e).context("Failed to load groups"))?,
+        None => Vec::new(),
+    };
+
+    // Create manager and add data
+    let mut manager = CoreMocksManager::new();
+    manager
+        .add_routes(routes)
+        .and_then(|_| manager.add_collections(collections))
+        .and_then(|_| manager.add_groups(groups))?;
+    Ok(manager)
+}
+
+/// Validation issue severity
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl From<CoreSeverity> for Severity {
+    fn from(s: CoreSeverity) -> Self {
+        match s {
+            CoreSeverity::Error => Severity::Error,
+            CoreSeverity::Warning => Severity::Warning,
+        }
+    }
+}
+
+/// Problem found during validation
+#[napi(object)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Location of the problem (e.g. `collections.base.routes[1]`)
+    pub path: String,
+    pub message: String,
+    /// File the definition was loaded from, if known
+    pub file: Option<String>,
+}
+
+impl From<CoreValidationIssue> for ValidationIssue {
+    fn from(i: CoreValidationIssue) -> Self {
+        Self {
+            severity: Severity::from(i.severity),
+            path: i.path,
+            message: i.message,
+            file: i.file,
+        }
+    }
+}
+
+/// Kind of likely mistake found by `MocksManager.lint()`
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintRule {
+    ShadowedPreset,
+    UnreferencedRoute,
+    RedirectWithoutLocation,
+    InvalidExpression,
+}
+
+impl From<CoreLintRule> for LintRule {
+    fn from(r: CoreLintRule) -> Self {
+        match r {
+            CoreLintRule::ShadowedPreset => LintRule::ShadowedPreset,
+            CoreLintRule::UnreferencedRoute => LintRule::UnreferencedRoute,
+            CoreLintRule::RedirectWithoutLocation => LintRule::RedirectWithoutLocation,
+            CoreLintRule::InvalidExpression => LintRule::InvalidExpression,
+        }
+    }
+}
+
+/// Likely mistake found by the linter
+#[napi(object)]
+pub struct LintWarning {
+    pub rule: LintRule,
+    /// Location of the problem (e.g. `routes.users.presets[0].variants[1]`)
+    pub path: String,
+    pub message: String,
+    /// File the definition was loaded from, if known
+    pub file: Option<String>,
+}
+
+impl From<CoreLintWarning> for LintWarning {
+    fn from(w: CoreLintWarning) -> Self {
         Self {
-            route: CoreRoute::from(a.route),
-            preset: CorePreset::from(a.preset),
-            variant: CoreVariant::from(a.variant),
+            rule: LintRule::from(w.rule),
+            path: w.path,
+            message: w.message,
+            file: w.file,
+        }
+    }
+}
+
+/// Format of exported config files
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Yaml,
+    Json,
+}
+
+impl From<ExportFormat> for CoreExportFormat {
+    fn from(f: ExportFormat) -> Self {
+        match f {
+            ExportFormat::Yaml => CoreExportFormat::Yaml,
+            ExportFormat::Json => CoreExportFormat::Json,
         }
     }
 }
 
+/// Project settings `validate()` and `lint()` check a manager with
+#[derive(Default)]
+struct LoadedProject {
+    /// Files definitions were loaded from, by kind and ID
+    sources: HashMap<(DefinitionKind, String), String>,
+    /// Template engine of variants without their own
+    template: CoreTemplateEngine,
+    /// Template partials
+    partials: HashMap<String, String>,
+}
+
+impl LoadedProject {
+    fn source(&self, kind: DefinitionKind, id: &str) -> Option<String> {
+        self.sources.get(&(kind, id.to_string())).cloned()
+    }
+}
+
+/// Change of the files a manager was loaded from, see `MocksManager.watch()`
+#[napi(object)]
+pub struct ManagerWatchEvent {
+    /// Changed files
+    pub changes: Vec<WatchEvent>,
+    /// Whether all files were reloaded, instead of replacing only the definitions
+    /// of changed files
+    pub reloaded: bool,
+    /// Error applying the changes, definitions of files that failed to load are kept
+    pub error: Option<String>,
+}
+
+/// Reloading of a watched manager on the watcher thread
+struct ManagerReloader {
+    source: LoadSource,
+    inner: Arc<Mutex<CoreMocksManager>>,
+    project: Arc<Mutex<LoadedProject>>,
+    /// Incremental reloader of project directories
+    reloader: Option<ProjectReloader>,
+}
+
+impl ManagerReloader {
+    /// Apply changed files to the manager.
+    ///
+    /// Returns whether all files were reloaded.
+    fn apply(&mut self, changes: &[CoreWatchEvent]) -> std::result::Result<bool, MockitoError> {
+        let Some(reloader) = &mut self.reloader else {
+            let (manager, project) = self.source.load_with_project()?;
+            *self.inner.lock().unwrap() = manager;
+            *self.project.lock().unwrap() = project;
+            return Ok(true);
+        };
+
+        let mut manager = self.inner.lock().unwrap();
+        let mut patched = true;
+        let mut error = None;
+        for change in changes {
+            match reloader.apply(&mut manager, change) {
+                Ok(applied) => patched &= applied,
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if patched {
+            self.project.lock().unwrap().sources = reloader.sources();
+            return error.map_or(Ok(false), |e| Err(e.into()));
+        }
+
+        // Settings changed, the reloader is only replaced if all files load
+        let mut next = reloader.clone();
+        let mut reloaded = CoreMocksManager::with_duplicate_policy(self.source.duplicate_policy());
+        let settings = next.load(&mut reloaded)?;
+        *manager = reloaded;
+        *self.project.lock().unwrap() = LoadedProject {
+            sources: next.sources(),
+            template: settings.template.unwrap_or_default(),
+            partials: settings.partials.unwrap_or_default(),
+        };
+        *reloader = next;
+        Ok(true)
+    }
+}
+
 /// Mocks Manager class
 #[napi]
 pub struct MocksManager {
     inner: Arc<Mutex<CoreMocksManager>>,
+    /// Settings of the loaded project, defaults for managers loaded from files
+    project: Arc<Mutex<LoadedProject>>,
+    /// Files the manager was loaded from, watched by `watch()`
+    source: Option<LoadSource>,
 }
 
 #[napi]
@@ -61,40 +426,315 @@ impl MocksManager {
     ///
     /// @param collectionsPath - Path or glob pattern to collections file(s)
     /// @param routesPath - Path or glob pattern to routes file(s)
-    /// @param basePath - Optional base directory for resolving relative paths
+    /// @param groupsPath - Optional path to route groups file
     #[napi(constructor)]
-    pub fn new(collections_path: String, routes_path: String) -> Result<Self> {
-        // Load routes and collections
-        let routes = parser::load_routes(&routes_path)
-            .map_err(|e| Error::from_reason(format!("Failed to load routes: {e}")))?;
-        let collections = parser::load_collections(&collections_path)
-            .map_err(|e| Error::from_reason(format!("Failed to load collections: {e}")))?;
-
-        // Create manager and add data
-        let mut manager = CoreMocksManager::new();
-        manager.add_routes(routes);
-        manager.add_collections(collections);
+    pub fn new(
+        env: Env,
+        collections_path: String,
+        routes_path: String,
+        groups_path: Option<String>,
+    ) -> Result<Self> {
+        let source = LoadSource::Files {
+            collections_path,
+            routes_path,
+            groups_path,
+        };
+        Self::load_from(source).map_err(|e| e.into_napi(&env))
+    }
 
-        Ok(Self {
-            inner: Arc::new(Mutex::new(manager)),
-        })
+    /// Create a mocks manager from a project directory or combined config file
+    ///
+    /// Discovers `routes/**`, `fixtures/**`, `collections/**` and `groups/**` files under a directory.
+    ///
+    /// @param path - Project root directory or combined config file
+    /// @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+    #[napi(factory)]
+    pub fn from_project(
+        env: Env,
+        path: String,
+        duplicate_policy: Option<DuplicatePolicy>,
+    ) -> Result<Self> {
+        let source = LoadSource::Project {
+            path,
+            duplicate_policy,
+        };
+        Self::load_from(source).map_err(|e| e.into_napi(&env))
+    }
+
+    /// Load a mocks manager on a background thread
+    ///
+    /// Same as the constructor, without blocking the event loop while files are parsed.
+    ///
+    /// @param collectionsPath - Path or glob pattern to collections file(s)
+    /// @param routesPath - Path or glob pattern to routes file(s)
+    /// @param groupsPath - Optional path to route groups file
+    #[napi(ts_return_type = "Promise<MocksManager>")]
+    pub fn load(
+        collections_path: String,
+        routes_path: String,
+        groups_path: Option<String>,
+    ) -> AsyncTask<LoadManagerTask> {
+        AsyncTask::new(LoadManagerTask(LoadSource::Files {
+            collections_path,
+            routes_path,
+            groups_path,
+        }))
+    }
+
+    /// Load a mocks manager from a project directory or combined config file on a background thread
+    ///
+    /// Same as `fromProject`, without blocking the event loop while files are parsed.
+    ///
+    /// @param path - Project root directory or combined config file
+    /// @param duplicatePolicy - Handling of IDs defined in several files (default: Override)
+    #[napi(ts_return_type = "Promise<MocksManager>")]
+    pub fn load_project(
+        path: String,
+        duplicate_policy: Option<DuplicatePolicy>,
+    ) -> AsyncTask<LoadManagerTask> {
+        AsyncTask::new(LoadManagerTask(LoadSource::Project {
+            path,
+            duplicate_policy,
+        }))
+    }
+
+    /// Create an empty mocks manager
+    ///
+    /// Routes and collections are added with `addRoute()` and `addCollection()`,
+    /// so mocks can be defined inline without config files.
+    ///
+    /// @param duplicatePolicy - Handling of IDs added several times (default: Override)
+    #[napi(factory)]
+    pub fn create(duplicate_policy: Option<DuplicatePolicy>) -> Self {
+        let policy = duplicate_policy.map(Into::into).unwrap_or_default();
+        Self::from_core(CoreMocksManager::with_duplicate_policy(policy))
+    }
+
+    /// Add a route
+    ///
+    /// An existing route with the same ID is handled by the duplicate policy.
+    /// Run `validate()` to check added routes like loaded ones.
+    ///
+    /// @throws Error if the ID is taken and the duplicate policy is `Error`
+    #[napi]
+    pub fn add_route(&self, env: Env, route: Route) -> Result<()> {
+        let mut manager = self.inner.lock().unwrap();
+        let id = route.id.clone();
+        manager
+            .add_route(CoreRoute::from(route))
+            .map_err(|e| js_error(&env, e))?;
+        self.forget_source(DefinitionKind::Route, id);
+        Ok(())
+    }
+
+    /// Add a collection
+    ///
+    /// An existing collection with the same ID is handled by the duplicate policy.
+    ///
+    /// @throws Error if the ID is taken and the duplicate policy is `Error`
+    #[napi]
+    pub fn add_collection(&self, env: Env, collection: Collection) -> Result<()> {
+        let mut manager = self.inner.lock().unwrap();
+        let id = collection.id.clone();
+        manager
+            .add_collection(CoreCollection::from(collection))
+            .map_err(|e| js_error(&env, e))?;
+        self.forget_source(DefinitionKind::Collection, id);
+        Ok(())
     }
 
     /// Resolve collection with inheritance and return active routes
     #[napi]
-    pub fn resolve_collection(&self, collection_id: String) -> Result<Vec<ActiveRoute>> {
+    pub fn resolve_collection(&self, env: Env, collection_id: String) -> Result<Vec<ActiveRoute>> {
         let manager = self.inner.lock().unwrap();
         let active_routes = manager
             .resolve_collection(&collection_id)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+            .map_err(|e| js_error(&env, e))?;
 
-        Ok(active_routes
+        Ok(active_routes.into_iter().map(ActiveRoute::from).collect())
+    }
+
+    /// List all collections, sorted by collection ID
+    #[napi]
+    pub fn list_collections(&self) -> Vec<Collection> {
+        let manager = self.inner.lock().unwrap();
+        manager
+            .list_collections()
             .into_iter()
-            .map(|a| ActiveRoute {
-                route: Route::from(&a.route),
-                preset: Preset::from(&a.preset),
-                variant: Variant::from(&a.variant),
-            })
-            .collect())
+            .map(Collection::from)
+            .collect()
+    }
+
+    /// List all routes, sorted by route ID
+    #[napi]
+    pub fn list_routes(&self) -> Vec<Route> {
+        let manager = self.inner.lock().unwrap();
+        manager.list_routes().into_iter().map(Route::from).collect()
+    }
+
+    /// Get a route by ID, or `null` if not found
+    #[napi]
+    pub fn get_route(&self, route_id: String) -> Option<Route> {
+        let manager = self.inner.lock().unwrap();
+        manager.get_route(&route_id).map(Route::from)
+    }
+
+    /// Get a route group by ID, or `null` if not found
+    #[napi]
+    pub fn get_group(&self, group_id: String) -> Option<Group> {
+        let manager = self.inner.lock().unwrap();
+        manager.get_group(&group_id).map(Group::from)
+    }
+
+    /// Get the inheritance chain of a collection.
+    ///
+    /// Returns collection IDs ordered from the root ancestor down to the given collection.
+    ///
+    /// @throws Error if a collection in the chain is not found or the chain is circular
+    #[napi]
+    pub fn collection_tree(&self, env: Env, collection_id: String) -> Result<Vec<String>> {
+        let manager = self.inner.lock().unwrap();
+        manager
+            .collection_tree(&collection_id)
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Write all routes, collections and groups to config files in a directory.
+    ///
+    /// Files are written as `routes/<id>`, `collections/<id>` and `groups/<id>`,
+    /// so the directory can be loaded back with `MocksManager.fromProject`.
+    ///
+    /// @param dir - Output directory
+    /// @param format - Format of written files
+    /// @returns Paths of written files
+    #[napi]
+    pub fn export(&self, env: Env, dir: String, format: ExportFormat) -> Result<Vec<String>> {
+        let manager = self.inner.lock().unwrap();
+        manager
+            .export(&dir, format.into())
+            .map_err(|e| js_error(&env, e))
+    }
+
+    /// Find likely mistakes in definitions that resolve fine.
+    ///
+    /// Reports shadowed presets, routes not referenced by any collection,
+    /// redirects without a `Location` header and expressions that don't compile.
+    /// Warnings of managers loaded from a project name the file of the definition.
+    #[napi]
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let manager = self.inner.lock().unwrap();
+        let project = self.project.lock().unwrap();
+        lint_with(&manager, |kind, id| project.source(kind, id))
+            .into_iter()
+            .map(LintWarning::from)
+            .collect()
+    }
+
+    /// Validate all collections and routes in one pass.
+    ///
+    /// Managers loaded from a project check templates with the engine and partials
+    /// of its settings, and issues name the file of the definition.
+    /// Returns an empty array if no issues were found.
+    #[napi]
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let manager = self.inner.lock().unwrap();
+        let project = self.project.lock().unwrap();
+        validation::validate_with(&manager, project.template, &project.partials, |kind, id| {
+            project.source(kind, id)
+        })
+        .into_iter()
+        .map(ValidationIssue::from)
+        .collect()
+    }
+
+    /// Watch the files the manager was loaded from and apply their changes.
+    ///
+    /// Changes of project route, collection and group files replace only the
+    /// definitions of the changed files, other changes reload all files. Files that
+    /// fail to load keep their previous definitions. The callback receives a
+    /// `ManagerWatchEvent` on the event loop after each batch of changes, e.g. to
+    /// reload a controller with `MocksController.fromManager()`. Subscriptions don't
+    /// keep the process alive.
+    ///
+    /// @param callback - Function receiving `ManagerWatchEvent`s
+    /// @param intervalMs - Polling interval in milliseconds (default: 500)
+    /// @throws Error if the manager wasn't loaded from files or they fail to load
+    #[napi]
+    pub fn watch(
+        &self,
+        env: Env,
+        callback: ThreadsafeFunction<ManagerWatchEvent, (), ManagerWatchEvent, Status, false, true>,
+        interval_ms: Option<u32>,
+    ) -> Result<WatchSubscription> {
+        let source = self.source.clone().ok_or_else(|| {
+            let error = MockitoError::new(
+                ErrorCode::NotWatchable,
+                "Manager wasn't loaded from files, there's nothing to watch",
+            );
+            js_error(&env, error)
+        })?;
+        let (reloader, patterns) = match &source {
+            LoadSource::Project { path, .. } if Path::new(path).is_dir() => {
+                // Populated on a scratch manager to know the file of each definition
+                let mut reloader = ProjectReloader::new(path);
+                reloader
+                    .load(&mut CoreMocksManager::with_duplicate_policy(
+                        source.duplicate_policy(),
+                    ))
+                    .map_err(|e| js_error(&env, e))?;
+                let patterns = reloader.watch_patterns();
+                (Some(reloader), patterns)
+            }
+            source => (None, source.watch_patterns()),
+        };
+        let watcher = Watcher::new(patterns).map_err(|e| js_error(&env, e))?;
+
+        let reloader = Mutex::new(ManagerReloader {
+            source,
+            inner: Arc::clone(&self.inner),
+            project: Arc::clone(&self.project),
+            reloader,
+        });
+        let interval = Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS).into());
+        let handle = watcher.spawn(interval, move |changes| {
+            let result = reloader.lock().unwrap().apply(&changes);
+            let event = ManagerWatchEvent {
+                changes: changes.into_iter().map(WatchEvent::from).collect(),
+                reloaded: result.as_ref().is_ok_and(|reloaded| *reloaded),
+                error: result.err().map(|e| e.to_string()),
+            };
+            callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+        });
+        Ok(WatchSubscription::new(handle))
+    }
+}
+
+impl MocksManager {
+    fn from_core(manager: CoreMocksManager) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(manager)),
+            project: Arc::default(),
+            source: None,
+        }
+    }
+
+    /// Load a manager from files, remembering them for `watch()`.
+    fn load_from(source: LoadSource) -> std::result::Result<Self, MockitoError> {
+        let (manager, project) = source.load_with_project()?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(manager)),
+            project: Arc::new(Mutex::new(project)),
+            source: Some(source),
+        })
+    }
+
+    /// Forget the file of a definition replaced by one added inline.
+    fn forget_source(&self, kind: DefinitionKind, id: String) {
+        self.project.lock().unwrap().sources.remove(&(kind, id));
+    }
+
+    /// Copy of the current core manager.
+    pub(crate) fn to_core(&self) -> CoreMocksManager {
+        self.inner.lock().unwrap().clone()
     }
 }
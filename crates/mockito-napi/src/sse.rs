@@ -0,0 +1,46 @@
+//! NAPI bindings for Server-Sent Events scripts.
+
+use crate::config::Variant;
+use crate::error::js_error;
+use mockito_core::mocks::sse::SseScript;
+use mockito_core::types::variant::Variant as CoreVariant;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Chunk of an event stream, see `sseFrames()`
+#[napi(object)]
+pub struct SseFrame {
+    /// Time to wait before sending the frame, in milliseconds
+    pub delay_ms: f64,
+    /// Frame in the `text/event-stream` format
+    pub text: String,
+}
+
+/// Get the frames streamed by a variant of an SSE route.
+///
+/// The variant body holds the event script (`retry` and `events`). Events up to
+/// the one with `lastEventId` are skipped, so reconnecting clients resume
+/// where they left off. Answer with the `text/event-stream` content type.
+///
+/// @param variant - Variant rendered by `MocksController.respond()`
+/// @param lastEventId - `Last-Event-ID` header of the request
+/// @throws Error if the variant body isn't an event script
+#[napi]
+pub fn sse_frames(
+    env: Env,
+    variant: Variant,
+    last_event_id: Option<String>,
+) -> Result<Vec<SseFrame>> {
+    let script =
+        SseScript::from_variant(&CoreVariant::from(variant)).map_err(|e| js_error(&env, e))?;
+    let frames = script
+        .resume(last_event_id.as_deref())
+        .frames()
+        .into_iter()
+        .map(|frame| SseFrame {
+            delay_ms: frame.delay.as_secs_f64() * 1000.0,
+            text: frame.text,
+        })
+        .collect();
+    Ok(frames)
+}
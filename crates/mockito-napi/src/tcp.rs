@@ -0,0 +1,53 @@
+//! NAPI bindings for TCP reply scripts.
+
+use crate::config::Variant;
+use crate::error::js_error;
+use mockito_core::mocks::tcp::TcpScript;
+use mockito_core::types::variant::Variant as CoreVariant;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Chunk of bytes sent on a TCP connection, see `tcpReplies()`
+#[napi(object)]
+pub struct TcpFrame {
+    /// Time to wait before sending the bytes, in milliseconds
+    pub delay_ms: f64,
+    pub data: Buffer,
+}
+
+/// Replies of a TCP route variant, see `tcpReplies()`
+#[napi(object)]
+pub struct TcpReplies {
+    pub frames: Vec<TcpFrame>,
+    /// Whether to close the connection after sending the frames
+    pub close: bool,
+}
+
+/// Get the replies sent by a variant of a TCP route.
+///
+/// The variant body holds the reply script (`replies` and `close`), or a single
+/// reply as a string. Replies holding a `data:` URI are decoded to bytes.
+///
+/// The core `TcpServer` answering TCP routes isn't exposed to Node: a `net`
+/// server passes messages to `MocksController.respond()` and writes these
+/// replies.
+///
+/// @param variant - Variant rendered by `MocksController.respond()`
+/// @throws Error if the variant body isn't a reply script
+#[napi]
+pub fn tcp_replies(env: Env, variant: Variant) -> Result<TcpReplies> {
+    let script =
+        TcpScript::from_variant(&CoreVariant::from(variant)).map_err(|e| js_error(&env, e))?;
+    let frames = script
+        .frames()
+        .into_iter()
+        .map(|frame| TcpFrame {
+            delay_ms: frame.delay.as_secs_f64() * 1000.0,
+            data: frame.data.into(),
+        })
+        .collect();
+    Ok(TcpReplies {
+        frames,
+        close: script.close,
+    })
+}
@@ -0,0 +1,343 @@
+//! NAPI bindings for tracing and logging.
+//!
+//! The core library reports matching, resolution, rendering and config loading
+//! with `tracing` spans and events. A global subscriber, installed on first use,
+//! forwards them to JS: `initTracing()` receives events with their enclosing
+//! spans, `setLogger()` receives flat log records. Each callback has its own
+//! filter, so levels can be set per module.
+
+use crate::error::{js_error, ErrorCode, MockitoError};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::{OnceLock, PoisonError, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+
+/// Filter of events forwarded by `initTracing()` if none is given
+const DEFAULT_TRACING_FILTER: &str = "mockito_core=debug";
+/// Filter of records forwarded by `setLogger()` if none is given
+const DEFAULT_LOGGER_FILTER: &str = "mockito_core=warn";
+
+/// Span enclosing a trace event
+#[napi(object)]
+pub struct TraceSpan {
+    /// Span name, e.g. `match_request`
+    pub name: String,
+    /// Fields recorded on the span, e.g. `url` and `routeId`
+    pub fields: Value,
+}
+
+/// Event reported by the core library, see `initTracing()`
+#[napi(object)]
+pub struct TraceEvent {
+    /// Time of the event, in milliseconds since the Unix epoch
+    pub timestamp: f64,
+    /// Level: `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`
+    pub level: String,
+    /// Module emitting the event
+    pub target: String,
+    pub message: Option<String>,
+    /// Fields of the event besides the message
+    pub fields: Value,
+    /// Enclosing spans, outermost first
+    pub spans: Vec<TraceSpan>,
+}
+
+/// Log record of the core library, see `setLogger()`
+#[napi(object)]
+pub struct LogRecord {
+    /// Time of the record, in milliseconds since the Unix epoch
+    pub timestamp: f64,
+    /// Level: `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`
+    pub level: String,
+    /// Module emitting the record, e.g. `mockito_core::config::project`
+    pub target: String,
+    pub message: Option<String>,
+    /// Fields of the record and its enclosing spans, e.g. `routeId` and `error`
+    pub fields: Value,
+}
+
+type TraceCallback = ThreadsafeFunction<TraceEvent, (), TraceEvent, Status, false, true>;
+type LogCallback = ThreadsafeFunction<LogRecord, (), LogRecord, Status, false, true>;
+
+/// Forward matching, resolution and rendering events of the core library to a callback.
+///
+/// Each event carries the fields of its enclosing spans, so events of a lookup
+/// hold the request and the matched route, preset and variant. The callback is
+/// invoked asynchronously on the event loop and doesn't keep the process alive.
+/// Calling it again replaces the callback.
+///
+/// @param callback - Function receiving `TraceEvent`s
+/// @param filter - Comma-separated `target=level` directives or a bare level (default: `mockito_core=debug`)
+/// @throws Error with code `INVALID_ARGUMENT` if the filter is invalid, or
+/// `TRACING_INSTALLED` if another subscriber is installed in the process
+#[napi]
+pub fn init_tracing(env: Env, callback: TraceCallback, filter: Option<String>) -> Result<()> {
+    let filter = parse_filter(&env, filter.as_deref().unwrap_or(DEFAULT_TRACING_FILTER))?;
+    update_sinks(&env, |sinks| sinks.tracing = Some((callback, filter)))
+}
+
+/// Forward log records of the core library to a JS logger.
+///
+/// By default only warnings and errors are forwarded, e.g. preset expressions
+/// that don't compile, validation issues of loaded projects and remote files
+/// served from the cache. The callback is invoked asynchronously on the event
+/// loop and doesn't keep the process alive.
+///
+/// ```js
+/// setLogger((record) => console[record.level.toLowerCase()]?.(record.message, record.fields));
+/// ```
+///
+/// @param logger - Function receiving `LogRecord`s, `null` stops forwarding
+/// @param filter - Comma-separated `target=level` directives or a bare level (default: `mockito_core=warn`)
+/// @throws Error with code `INVALID_ARGUMENT` if the filter is invalid, or
+/// `TRACING_INSTALLED` if another subscriber is installed in the process
+#[napi]
+pub fn set_logger(env: Env, logger: Option<LogCallback>, filter: Option<String>) -> Result<()> {
+    let filter = parse_filter(&env, filter.as_deref().unwrap_or(DEFAULT_LOGGER_FILTER))?;
+    update_sinks(&env, |sinks| {
+        sinks.logger = logger.map(|logger| (logger, filter));
+    })
+}
+
+/// Parse `target=level` directives.
+fn parse_filter(env: &Env, filter: &str) -> Result<Targets> {
+    filter.parse().map_err(|e| {
+        let error = MockitoError::new(
+            ErrorCode::InvalidArgument,
+            format!("Invalid tracing filter '{}': {}", filter, e),
+        )
+        .with("filter", filter);
+        js_error(env, error)
+    })
+}
+
+/// Callbacks receiving events, with their filters.
+struct Sinks {
+    tracing: Option<(TraceCallback, Targets)>,
+    logger: Option<(LogCallback, Targets)>,
+}
+
+impl Sinks {
+    /// Whether any callback receives events of a callsite.
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let enables = |filter: &Targets| filter.would_enable(metadata.target(), metadata.level());
+        self.tracing.as_ref().is_some_and(|(_, f)| enables(f))
+            || self.logger.as_ref().is_some_and(|(_, f)| enables(f))
+    }
+}
+
+static SINKS: RwLock<Sinks> = RwLock::new(Sinks {
+    tracing: None,
+    logger: None,
+});
+
+/// Outcome of installing the global subscriber
+static INSTALLED: OnceLock<std::result::Result<(), String>> = OnceLock::new();
+
+/// Change the callbacks, installing the global subscriber on first use.
+fn update_sinks(env: &Env, update: impl FnOnce(&mut Sinks)) -> Result<()> {
+    let installed = INSTALLED.get_or_init(|| {
+        let subscriber = tracing_subscriber::registry().with(CallbackLayer);
+        tracing::subscriber::set_global_default(subscriber).map_err(|e| e.to_string())
+    });
+    if let Err(message) = installed {
+        let error = MockitoError::new(ErrorCode::TracingInstalled, message.clone());
+        return Err(js_error(env, error));
+    }
+    update(&mut SINKS.write().unwrap_or_else(PoisonError::into_inner));
+    // Callsites cache whether they're enabled, recompute it for the new filters
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
+
+/// Layer calling the JS callbacks with every event they're interested in.
+struct CallbackLayer;
+
+/// Fields recorded on a span, kept in the span extensions.
+struct SpanFields(Map<String, Value>);
+
+impl<S> Layer<S> for CallbackLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let sinks = SINKS.read().unwrap_or_else(PoisonError::into_inner);
+        if sinks.enabled(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        let sinks = SINKS.read().unwrap_or_else(PoisonError::into_inner);
+        sinks.enabled(metadata)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = JsonVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(visitor.fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            let mut visitor = JsonVisitor {
+                fields: std::mem::take(fields),
+                message: None,
+            };
+            values.record(&mut visitor);
+            *fields = visitor.fields;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let sinks = SINKS.read().unwrap_or_else(PoisonError::into_inner);
+        let metadata = event.metadata();
+        let enabled = |filter: &Targets| filter.would_enable(metadata.target(), metadata.level());
+        let tracing = sinks.tracing.as_ref().filter(|(_, f)| enabled(f));
+        let logger = sinks.logger.as_ref().filter(|(_, f)| enabled(f));
+        if tracing.is_none() && logger.is_none() {
+            return;
+        }
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let spans: Vec<_> = ctx
+            .event_scope(event)
+            .map(|scope| scope.from_root().collect())
+            .unwrap_or_default();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+            * 1000.0;
+
+        if let Some((logger, _)) = logger {
+            let mut fields = Map::new();
+            for span in &spans {
+                fields.extend(span_fields(span));
+            }
+            fields.extend(visitor.fields.clone());
+            logger.call(
+                LogRecord {
+                    timestamp,
+                    level: metadata.level().to_string(),
+                    target: metadata.target().to_string(),
+                    message: visitor.message.clone(),
+                    fields: Value::Object(fields),
+                },
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+        }
+        if let Some((callback, _)) = tracing {
+            callback.call(
+                TraceEvent {
+                    timestamp,
+                    level: metadata.level().to_string(),
+                    target: metadata.target().to_string(),
+                    message: visitor.message,
+                    fields: Value::Object(visitor.fields),
+                    spans: spans
+                        .iter()
+                        .map(|span| TraceSpan {
+                            name: span.name().to_string(),
+                            fields: Value::Object(span_fields(span)),
+                        })
+                        .collect(),
+                },
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+        }
+    }
+}
+
+/// Get the fields recorded on a span.
+fn span_fields<S>(span: &SpanRef<'_, S>) -> Map<String, Value>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    span.extensions()
+        .get::<SpanFields>()
+        .map(|fields| fields.0.clone())
+        .unwrap_or_default()
+}
+
+/// Collects fields into a JSON object with camelCase keys, apart from the message.
+#[derive(Default)]
+struct JsonVisitor {
+    fields: Map<String, Value>,
+    message: Option<String>,
+}
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                Value::String(message) => message,
+                value => value.to_string(),
+            });
+        } else {
+            self.fields.insert(camel_case(field.name()), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+}
+
+/// Convert a snake_case field name to camelCase.
+fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            result.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
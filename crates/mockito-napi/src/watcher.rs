@@ -0,0 +1,105 @@
+//! NAPI bindings for config file watching.
+
+use crate::error::js_error;
+use mockito_core::config::watcher::{
+    ChangeKind as CoreChangeKind, WatchEvent as CoreWatchEvent, WatchHandle, Watcher,
+};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default polling interval in milliseconds
+pub(crate) const DEFAULT_INTERVAL_MS: u32 = 500;
+
+/// Kind of file change
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl From<CoreChangeKind> for ChangeKind {
+    fn from(k: CoreChangeKind) -> Self {
+        match k {
+            CoreChangeKind::Added => ChangeKind::Added,
+            CoreChangeKind::Modified => ChangeKind::Modified,
+            CoreChangeKind::Removed => ChangeKind::Removed,
+        }
+    }
+}
+
+/// Change of a watched config file
+#[napi(object)]
+pub struct WatchEvent {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Parse error of an added or modified file, not set if it parsed successfully
+    pub parse_error: Option<String>,
+}
+
+impl From<CoreWatchEvent> for WatchEvent {
+    fn from(e: CoreWatchEvent) -> Self {
+        Self {
+            path: e.path,
+            kind: e.kind.into(),
+            parse_error: e.parse_error,
+        }
+    }
+}
+
+/// Subscription to config file changes, see `watchFiles()` and `MocksManager.watch()`
+#[napi]
+pub struct WatchSubscription {
+    handle: Mutex<Option<WatchHandle>>,
+}
+
+impl WatchSubscription {
+    pub(crate) fn new(handle: WatchHandle) -> Self {
+        Self {
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+}
+
+#[napi]
+impl WatchSubscription {
+    /// Stop watching. Calling it more than once has no effect.
+    #[napi]
+    pub fn unsubscribe(&self) {
+        if let Some(mut handle) = self.handle.lock().unwrap().take() {
+            handle.stop();
+        }
+    }
+}
+
+/// Watch config files matched by glob patterns for changes.
+///
+/// The callback receives batches of changes (added/modified/removed files with
+/// parse results) on the event loop. Subscriptions don't keep the process alive.
+///
+/// @param patterns - Glob patterns of watched files, `!` prefixed ones exclude files
+/// @param callback - Function receiving arrays of `WatchEvent`s
+/// @param intervalMs - Polling interval in milliseconds (default: 500)
+/// @throws Error if any glob pattern is invalid
+#[napi]
+pub fn watch_files(
+    env: Env,
+    patterns: Vec<String>,
+    callback: ThreadsafeFunction<Vec<WatchEvent>, (), Vec<WatchEvent>, Status, false, true>,
+    interval_ms: Option<u32>,
+) -> Result<WatchSubscription> {
+    let watcher = Watcher::new(patterns).map_err(|e| js_error(&env, e))?;
+    let interval = Duration::from_millis(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS).into());
+    let handle = watcher.spawn(interval, move |events| {
+        callback.call(
+            events.into_iter().map(WatchEvent::from).collect(),
+            ThreadsafeFunctionCallMode::NonBlocking,
+        );
+    });
+
+    Ok(WatchSubscription::new(handle))
+}
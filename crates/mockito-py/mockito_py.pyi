@@ -0,0 +1,61 @@
+"""Type stubs of the Mockito core Python bindings."""
+
+from enum import Enum
+from typing import Any, Optional
+
+class MockitoError(Exception):
+    """Error loading, resolving or rendering mocks."""
+
+class DuplicatePolicy(Enum):
+    """How definitions reusing an already loaded ID are handled"""
+
+    Override = 0
+    Error = 1
+    Merge = 2
+
+class MocksManager:
+    """Manager of routes, collections and groups"""
+
+    def __init__(
+        self,
+        collections_path: Optional[str] = None,
+        routes_path: Optional[str] = None,
+        groups_path: Optional[str] = None,
+        duplicate_policy: Optional[DuplicatePolicy] = None,
+    ) -> None: ...
+    @staticmethod
+    def from_project(path: str, duplicate_policy: Optional[DuplicatePolicy] = None) -> MocksManager: ...
+    def add_routes(self, routes: list[dict[str, Any]]) -> None: ...
+    def add_collections(self, collections: list[dict[str, Any]]) -> None: ...
+    def add_groups(self, groups: list[dict[str, Any]]) -> None: ...
+    def list_routes(self) -> list[dict[str, Any]]: ...
+    def list_collections(self) -> list[dict[str, Any]]: ...
+    def resolve_collection(self, collection_id: str) -> list[dict[str, Any]]: ...
+    def validate(self) -> list[str]: ...
+
+class MocksController:
+    """Controller of active routes, matching requests like the mock server"""
+
+    def __init__(
+        self,
+        collections_path: str,
+        routes_path: str,
+        default_collection: Optional[str] = None,
+        groups_path: Optional[str] = None,
+    ) -> None: ...
+    @staticmethod
+    def from_project(path: str, duplicate_policy: Optional[DuplicatePolicy] = None) -> MocksController: ...
+    @staticmethod
+    def from_manager(manager: MocksManager, default_collection: Optional[str] = None) -> MocksController: ...
+    def use_collection(self, collection_id: str) -> None: ...
+    def use_routes(self, routes: list[str]) -> None: ...
+    def disable_routes(self, route_ids: list[str]) -> None: ...
+    def enable_routes(self, route_ids: list[str]) -> None: ...
+    def reset_routes(self) -> None: ...
+    def current_collection(self) -> Optional[str]: ...
+    def get_active_routes(self) -> list[dict[str, Any]]: ...
+    def find_route(self, request: dict[str, Any]) -> Optional[dict[str, Any]]: ...
+    def find_routes(self, request: dict[str, Any]) -> list[dict[str, Any]]: ...
+    def respond(self, request: dict[str, Any]) -> Optional[dict[str, Any]]: ...
+
+def version() -> str: ...
@@ -0,0 +1,97 @@
+//! Conversion of values between Python and core types.
+//!
+//! Definitions and requests are passed as plain dicts and lists, shaped like
+//! the JSON of config files.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use pyo3::IntoPyObjectExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Convert a Python value into a core type.
+pub(crate) fn from_py<T: DeserializeOwned>(object: &Bound<'_, PyAny>) -> PyResult<T> {
+    serde_json::from_value(py_to_value(object)?).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Convert a core value into plain Python dicts and lists.
+pub(crate) fn to_py<'py, T: Serialize + ?Sized>(
+    py: Python<'py>,
+    value: &T,
+) -> PyResult<Bound<'py, PyAny>> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    value_to_py(py, &value)
+}
+
+fn py_to_value(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if object.is_none() {
+        return Ok(Value::Null);
+    }
+    // `bool` is a subclass of `int`, so it's checked first
+    if let Ok(b) = object.cast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if object.is_instance_of::<PyInt>() {
+        return match object.extract::<i64>() {
+            Ok(n) => Ok(Value::from(n)),
+            Err(_) => object.extract::<u64>().map(Value::from),
+        };
+    }
+    if let Ok(f) = object.cast::<PyFloat>() {
+        return Number::from_f64(f.value())
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err("NaN and infinity can't be converted"));
+    }
+    if let Ok(s) = object.cast::<PyString>() {
+        return Ok(Value::String(s.to_cow()?.into_owned()));
+    }
+    if let Ok(dict) = object.cast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, value) in dict.iter() {
+            let key = key
+                .cast::<PyString>()
+                .map_err(|_| PyValueError::new_err("Dict keys must be strings"))?;
+            map.insert(key.to_cow()?.into_owned(), py_to_value(&value)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    if let Ok(list) = object.cast::<PyList>() {
+        return list.iter().map(|item| py_to_value(&item)).collect();
+    }
+    if let Ok(tuple) = object.cast::<PyTuple>() {
+        return tuple.iter().map(|item| py_to_value(&item)).collect();
+    }
+    Err(PyValueError::new_err(format!(
+        "Unsupported value of type {}",
+        object.get_type().name()?
+    )))
+}
+
+fn value_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::Bool(b) => b.into_bound_py_any(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_bound_py_any(py),
+            (None, Some(u)) => u.into_bound_py_any(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_bound_py_any(py),
+        },
+        Value::String(s) => s.into_bound_py_any(py),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| value_to_py(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_bound_py_any(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, value) in map {
+                dict.set_item(key, value_to_py(py, value)?)?;
+            }
+            dict.into_bound_py_any(py)
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Errors raised to Python.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::PyErr;
+use std::fmt::Display;
+
+create_exception!(
+    mockito_py,
+    MockitoError,
+    PyException,
+    "Error loading, resolving or rendering mocks."
+);
+
+/// Convert a core error into a raised `MockitoError`.
+pub(crate) fn py_error(error: impl Display) -> PyErr {
+    MockitoError::new_err(error.to_string())
+}
+
+/// Convert a core error into a raised `MockitoError`, prefixed with the failed operation.
+pub(crate) fn py_error_context(context: &str, error: impl Display) -> PyErr {
+    MockitoError::new_err(format!("{}: {}", context, error))
+}
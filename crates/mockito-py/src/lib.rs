@@ -0,0 +1,36 @@
+//! Python bindings for Mockito core library.
+//!
+//! Exposes mocks managers and controllers to Python, so test suites can load
+//! the mocks of a project and resolve requests without the Node.js package:
+//!
+//! ```python
+//! from mockito_py import MocksController
+//!
+//! controller = MocksController.from_project("./mocks")
+//! controller.use_routes(["get-user:admin:success"])
+//! controller.respond({"url": "/api/users/1", "method": "GET", "transport": "HTTP"})
+//! ```
+//!
+//! Built with `maturin develop -m crates/mockito-py/Cargo.toml`.
+
+use pyo3::prelude::*;
+
+mod convert;
+mod error;
+mod mocks;
+
+/// Library version
+#[pyfunction]
+fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+#[pymodule]
+fn mockito_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("MockitoError", m.py().get_type::<error::MockitoError>())?;
+    m.add_class::<mocks::DuplicatePolicy>()?;
+    m.add_class::<mocks::MocksManager>()?;
+    m.add_class::<mocks::MocksController>()?;
+    m.add_function(wrap_pyfunction!(version, m)?)?;
+    Ok(())
+}
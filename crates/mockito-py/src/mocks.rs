@@ -0,0 +1,359 @@
+//! Python bindings for mocks operations.
+
+use crate::convert::{from_py, to_py};
+use crate::error::{py_error, py_error_context};
+use mockito_core::config::{parser, project};
+use mockito_core::mocks::controller::{
+    MocksController as CoreMocksController, Request as CoreRequest,
+};
+use mockito_core::mocks::manager::{
+    ActiveRoute as CoreActiveRoute, DuplicatePolicy as CoreDuplicatePolicy,
+    MocksManager as CoreMocksManager,
+};
+use mockito_core::types::collection::Collection;
+use mockito_core::types::group::Group;
+use mockito_core::types::preset::Preset;
+use mockito_core::types::route::{HttpMethod, Route, Transport};
+use mockito_core::types::variant::Variant;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// How definitions reusing an already loaded ID are handled
+#[pyclass(eq, eq_int, frozen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Replace the loaded definition
+    Override,
+    /// Fail loading
+    Error,
+    /// Unite presets and variants of routes, replace other definitions
+    Merge,
+}
+
+impl From<DuplicatePolicy> for CoreDuplicatePolicy {
+    fn from(p: DuplicatePolicy) -> Self {
+        match p {
+            DuplicatePolicy::Override => CoreDuplicatePolicy::Override,
+            DuplicatePolicy::Error => CoreDuplicatePolicy::Error,
+            DuplicatePolicy::Merge => CoreDuplicatePolicy::Merge,
+        }
+    }
+}
+
+/// Request matched against active routes
+#[derive(Deserialize)]
+struct Request {
+    /// Request URL (path + query string)
+    url: String,
+    /// HTTP method (required for HTTP routes)
+    method: Option<HttpMethod>,
+    transport: Transport,
+    headers: Option<HashMap<String, String>>,
+    /// Query parameters (parsed from URL if not provided)
+    query: Option<HashMap<String, String>>,
+    payload: Option<Value>,
+}
+
+impl From<Request> for CoreRequest {
+    fn from(r: Request) -> Self {
+        Self {
+            url: r.url,
+            method: r.method,
+            transport: r.transport,
+            headers: r.headers,
+            query: r.query,
+            payload: r.payload,
+        }
+    }
+}
+
+/// Route with selected preset and variant, converted to `{"route", "preset", "variant"}`
+#[derive(Serialize)]
+struct ActiveRoute<'a> {
+    route: &'a Route,
+    preset: &'a Preset,
+    variant: &'a Variant,
+}
+
+impl<'a> From<&'a CoreActiveRoute> for ActiveRoute<'a> {
+    fn from(a: &'a CoreActiveRoute) -> Self {
+        Self {
+            route: &*a.route,
+            preset: a.preset(),
+            variant: a.variant(),
+        }
+    }
+}
+
+fn load_manager(
+    collections_path: &str,
+    routes_path: &str,
+    groups_path: Option<&str>,
+) -> PyResult<CoreMocksManager> {
+    let routes = parser::load_routes(routes_path)
+        .map_err(|e| py_error_context("Failed to load routes", e))?;
+    let collections = parser::load_collections(collections_path)
+        .map_err(|e| py_error_context("Failed to load collections", e))?;
+    let groups = match groups_path {
+        Some(path) => {
+            parser::load_groups(path).map_err(|e| py_error_context("Failed to load groups", e))?
+        }
+        None => Vec::new(),
+    };
+
+    let mut manager = CoreMocksManager::new();
+    manager
+        .add_routes(routes)
+        .and_then(|_| manager.add_collections(collections))
+        .and_then(|_| manager.add_groups(groups))
+        .map_err(py_error)?;
+    Ok(manager)
+}
+
+fn load_project(
+    path: &str,
+    duplicate_policy: Option<DuplicatePolicy>,
+) -> PyResult<project::Project> {
+    let policy = duplicate_policy.map(Into::into).unwrap_or_default();
+    project::load_project_with(path, CoreMocksManager::with_duplicate_policy(policy))
+        .map_err(|e| py_error_context("Failed to load project", e))
+}
+
+/// Manager of routes, collections and groups
+#[pyclass]
+pub struct MocksManager {
+    inner: CoreMocksManager,
+}
+
+#[pymethods]
+impl MocksManager {
+    /// Create an empty mocks manager, or load one from files
+    ///
+    /// Routes and collections are added with `add_routes()` and `add_collections()`
+    /// if no paths are passed.
+    #[new]
+    #[pyo3(signature = (collections_path=None, routes_path=None, groups_path=None, duplicate_policy=None))]
+    fn new(
+        collections_path: Option<&str>,
+        routes_path: Option<&str>,
+        groups_path: Option<&str>,
+        duplicate_policy: Option<DuplicatePolicy>,
+    ) -> PyResult<Self> {
+        let inner = match (collections_path, routes_path) {
+            (Some(collections_path), Some(routes_path)) => {
+                load_manager(collections_path, routes_path, groups_path)?
+            }
+            (None, None) => {
+                let policy = duplicate_policy.map(Into::into).unwrap_or_default();
+                CoreMocksManager::with_duplicate_policy(policy)
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "collections_path and routes_path must be passed together",
+                ))
+            }
+        };
+        Ok(Self { inner })
+    }
+
+    /// Load a mocks manager from a project directory or combined config file
+    #[staticmethod]
+    #[pyo3(signature = (path, duplicate_policy=None))]
+    fn from_project(path: &str, duplicate_policy: Option<DuplicatePolicy>) -> PyResult<Self> {
+        let project = load_project(path, duplicate_policy)?;
+        Ok(Self {
+            inner: project.manager,
+        })
+    }
+
+    /// Add routes, written like a routes file
+    fn add_routes(&mut self, routes: &Bound<'_, PyAny>) -> PyResult<()> {
+        let routes: Vec<Route> = from_py(routes)?;
+        self.inner.add_routes(routes).map_err(py_error)
+    }
+
+    /// Add collections, written like a collections file
+    fn add_collections(&mut self, collections: &Bound<'_, PyAny>) -> PyResult<()> {
+        let collections: Vec<Collection> = from_py(collections)?;
+        self.inner.add_collections(collections).map_err(py_error)
+    }
+
+    /// Add route groups, written like a groups file
+    fn add_groups(&mut self, groups: &Bound<'_, PyAny>) -> PyResult<()> {
+        let groups: Vec<Group> = from_py(groups)?;
+        self.inner.add_groups(groups).map_err(py_error)
+    }
+
+    /// Get all routes, sorted by ID
+    fn list_routes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.inner.list_routes())
+    }
+
+    /// Get all collections, sorted by ID
+    fn list_collections<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.inner.list_collections())
+    }
+
+    /// Resolve collection with inheritance and return active routes
+    fn resolve_collection<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let active_routes = self
+            .inner
+            .resolve_collection(collection_id)
+            .map_err(py_error)?;
+        to_py(
+            py,
+            &active_routes
+                .iter()
+                .map(ActiveRoute::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Validate routes and collections, returning messages of found problems
+    fn validate(&self) -> Vec<String> {
+        self.inner
+            .validate()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// Controller of active routes, matching requests like the mock server
+#[pyclass]
+pub struct MocksController {
+    inner: CoreMocksController,
+}
+
+#[pymethods]
+impl MocksController {
+    /// Load a controller from collection, route and optional group files
+    #[new]
+    #[pyo3(signature = (collections_path, routes_path, default_collection=None, groups_path=None))]
+    fn new(
+        collections_path: &str,
+        routes_path: &str,
+        default_collection: Option<&str>,
+        groups_path: Option<&str>,
+    ) -> PyResult<Self> {
+        let manager = load_manager(collections_path, routes_path, groups_path)?;
+        let mut inner = CoreMocksController::new(manager);
+        if let Some(collection_id) = default_collection {
+            inner.use_collection(collection_id).map_err(py_error)?;
+        }
+        Ok(Self { inner })
+    }
+
+    /// Load a controller from a project directory or combined config file
+    ///
+    /// Activates the default collection from the `mockito.config.*` settings file.
+    #[staticmethod]
+    #[pyo3(signature = (path, duplicate_policy=None))]
+    fn from_project(path: &str, duplicate_policy: Option<DuplicatePolicy>) -> PyResult<Self> {
+        let project = load_project(path, duplicate_policy)?;
+        let inner = CoreMocksController::from_project(project).map_err(py_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Create a controller serving the routes of a mocks manager
+    ///
+    /// The controller works on a copy, so later changes to the manager don't affect it.
+    #[staticmethod]
+    #[pyo3(signature = (manager, default_collection=None))]
+    fn from_manager(manager: &MocksManager, default_collection: Option<&str>) -> PyResult<Self> {
+        let mut inner = CoreMocksController::new(manager.inner.clone());
+        if let Some(collection_id) = default_collection {
+            inner.use_collection(collection_id).map_err(py_error)?;
+        }
+        Ok(Self { inner })
+    }
+
+    /// Switch to a collection
+    fn use_collection(&mut self, collection_id: &str) -> PyResult<()> {
+        self.inner.use_collection(collection_id).map_err(py_error)
+    }
+
+    /// Activate routes by `route:preset:variant` references on top of the collection
+    fn use_routes(&mut self, routes: Vec<String>) -> PyResult<()> {
+        self.inner.use_routes(&routes).map_err(py_error)
+    }
+
+    /// Disable routes, so requests fall through to other routes
+    fn disable_routes(&mut self, route_ids: Vec<String>) -> PyResult<()> {
+        self.inner.disable_routes(&route_ids).map_err(py_error)
+    }
+
+    /// Enable routes disabled by `disable_routes()`
+    fn enable_routes(&mut self, route_ids: Vec<String>) -> PyResult<()> {
+        self.inner.enable_routes(&route_ids).map_err(py_error)
+    }
+
+    /// Drop route overrides and go back to the routes of the collection
+    fn reset_routes(&mut self) -> PyResult<()> {
+        self.inner.reset_routes().map_err(py_error)
+    }
+
+    /// ID of the active collection
+    fn current_collection(&self) -> Option<String> {
+        self.inner.active_collection_id().map(str::to_string)
+    }
+
+    /// Get active routes
+    fn get_active_routes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let active_routes = self.inner.get_active_routes();
+        to_py(
+            py,
+            &active_routes
+                .iter()
+                .map(ActiveRoute::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Find the active route matching a request, or `None`
+    ///
+    /// The request is a dict of `url`, `method`, `transport` and optional
+    /// `headers`, `query` and `payload`.
+    fn find_route<'py>(
+        &self,
+        py: Python<'py>,
+        request: &Bound<'_, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let request = CoreRequest::from(from_py::<Request>(request)?);
+        to_py(py, &self.inner.find_route(&request).map(ActiveRoute::from))
+    }
+
+    /// Find all active routes matching a request, in priority order
+    fn find_routes<'py>(
+        &self,
+        py: Python<'py>,
+        request: &Bound<'_, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let request = CoreRequest::from(from_py::<Request>(request)?);
+        let found = self.inner.find_routes(&request);
+        to_py(
+            py,
+            &found.into_iter().map(ActiveRoute::from).collect::<Vec<_>>(),
+        )
+    }
+
+    /// Find the active route matching a request and render its variant, or `None`
+    ///
+    /// Templates in variant headers and body are rendered against the request,
+    /// with the project `template` engine if the variant has none.
+    fn respond<'py>(
+        &self,
+        py: Python<'py>,
+        request: &Bound<'_, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let request = CoreRequest::from(from_py::<Request>(request)?);
+        let variant = self.inner.respond(&request).map_err(py_error)?;
+        to_py(py, &variant)
+    }
+}
@@ -0,0 +1,21 @@
+//! WebAssembly bindings for config parsing.
+
+use crate::convert::{js_error, to_js};
+use mockito_core::config::parser;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Parse the contents of a config file.
+///
+/// The format is detected by the file extension like when loading files:
+/// JSON, JSONC, JSON5, YAML or TOML.
+///
+/// @param content - File contents
+/// @param fileName - File name, e.g. `routes.yaml`
+/// @returns Parsed value, e.g. routes to pass to `MocksManager.addRoutes()`
+/// @throws Error if the content is invalid or the extension unknown
+#[wasm_bindgen(js_name = parseConfig)]
+pub fn parse_config(content: &str, file_name: &str) -> Result<JsValue, JsError> {
+    let value: Value = parser::parse_config(content, file_name).map_err(js_error)?;
+    to_js(&value)
+}
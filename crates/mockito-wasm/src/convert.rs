@@ -0,0 +1,30 @@
+//! Conversion of values between JavaScript and core types.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Display;
+use wasm_bindgen::prelude::*;
+
+/// Deserialize a JS value into a core type.
+pub(crate) fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(js_error)
+}
+
+/// Serialize a core value into a plain JS value.
+///
+/// Maps become objects instead of `Map`, so results look like parsed JSON.
+pub(crate) fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(js_error)
+}
+
+/// Check if an optional argument was left out.
+pub(crate) fn is_missing(value: &JsValue) -> bool {
+    value.is_undefined() || value.is_null()
+}
+
+/// Convert an error into a thrown JS error.
+pub(crate) fn js_error(error: impl Display) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -0,0 +1,25 @@
+//! WebAssembly bindings for Mockito core library.
+//!
+//! Exposes route matching and resolution to browsers, so service-worker mocks
+//! and fixture editors match requests exactly like the server. There is no file
+//! system in the browser: definitions are passed as objects, or as file contents
+//! parsed with `parseConfig()`.
+//!
+//! Built with `wasm-pack build crates/mockito-wasm --target web`.
+
+use wasm_bindgen::prelude::*;
+
+mod config;
+mod convert;
+mod matching;
+mod mocks;
+
+pub use config::*;
+pub use matching::*;
+pub use mocks::*;
+
+/// Library version
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
@@ -0,0 +1,117 @@
+//! WebAssembly bindings for request matching utilities.
+//!
+//! Same as the matching functions of the Node.js binding: expected values are
+//! written like in route files, objects are matched by intersection and
+//! `${...}` strings are expressions.
+
+use crate::convert::{from_js, is_missing, js_error, to_js};
+use mockito_core::expression::EvaluationLimits;
+use mockito_core::matching;
+use mockito_core::types::preset::{HeadersOrExpression, PayloadOrExpression, QueryOrExpression};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Result of matching a URL against a route pattern
+#[derive(Serialize)]
+struct UrlMatch {
+    matched: bool,
+    /// Path parameters captured by `{name}` segments
+    params: HashMap<String, String>,
+}
+
+/// Match a URL against a route URL pattern.
+///
+/// Trailing slashes and the query string are ignored.
+///
+/// @param pattern - Route URL pattern like `/api/users/{id}`
+/// @param url - Request URL
+/// @returns `{ matched, params }`
+#[wasm_bindgen(js_name = urlMatches)]
+pub fn url_matches(pattern: &str, url: &str) -> Result<JsValue, JsError> {
+    let result = matching::url_matches(pattern, url);
+    to_js(&UrlMatch {
+        matched: result.matched,
+        params: result.params,
+    })
+}
+
+/// Match query parameters like a preset `query`.
+///
+/// @param expected - Query parameters or `${...}` expression
+/// @param query - Query parameters of the request
+/// @param request - Request context evaluated by expressions (default: `{ query }`)
+/// @throws Error if `expected` is malformed or the expression fails
+#[wasm_bindgen(js_name = queryMatches)]
+pub fn query_matches(expected: JsValue, query: JsValue, request: JsValue) -> Result<bool, JsError> {
+    let expected: QueryOrExpression = from_js(expected)?;
+    let query: HashMap<String, String> = from_js(query)?;
+    let context = context(request, || json!({ "query": query }))?;
+    matching::query_matches(
+        Some(&expected),
+        &query,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(js_error)
+}
+
+/// Match request headers like a preset `headers`, ignoring the case of names.
+///
+/// @param expected - Headers or `${...}` expression
+/// @param headers - Headers of the request
+/// @param request - Request context evaluated by expressions (default: `{ headers }`)
+/// @throws Error if `expected` is malformed or the expression fails
+#[wasm_bindgen(js_name = headersMatches)]
+pub fn headers_matches(
+    expected: JsValue,
+    headers: JsValue,
+    request: JsValue,
+) -> Result<bool, JsError> {
+    let expected: HeadersOrExpression = from_js(expected)?;
+    let headers: HashMap<String, String> = from_js(headers)?;
+    let context = context(request, || json!({ "headers": headers }))?;
+    matching::headers_matches(
+        Some(&expected),
+        &headers,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(js_error)
+}
+
+/// Match a request payload like a preset `payload`.
+///
+/// Objects match if the payload contains all expected fields, other values must be equal.
+///
+/// @param expected - Expected payload or `${...}` expression
+/// @param payload - Payload of the request
+/// @param request - Request context evaluated by expressions (default: `{ payload }`)
+/// @throws Error if the expression fails
+#[wasm_bindgen(js_name = payloadMatches)]
+pub fn payload_matches(
+    expected: JsValue,
+    payload: JsValue,
+    request: JsValue,
+) -> Result<bool, JsError> {
+    let expected: PayloadOrExpression = from_js(expected)?;
+    let payload: Value = from_js(payload)?;
+    let context = context(request, || json!({ "payload": payload }))?;
+    matching::payload_matches(
+        Some(&expected),
+        &payload,
+        &context,
+        &EvaluationLimits::default(),
+    )
+    .map_err(js_error)
+}
+
+/// Request context of expressions, built by `default` if none is passed.
+fn context(request: JsValue, default: impl FnOnce() -> Value) -> Result<Value, JsError> {
+    if is_missing(&request) {
+        Ok(default())
+    } else {
+        from_js(request)
+    }
+}
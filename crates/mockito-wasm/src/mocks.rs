@@ -0,0 +1,292 @@
+//! WebAssembly bindings for mocks operations.
+//!
+//! Definitions are added as objects shaped like config files, since there are
+//! no files to load in the browser.
+
+use crate::convert::{from_js, js_error, to_js};
+use mockito_core::mocks::controller::{
+    MocksController as CoreMocksController, Request as CoreRequest,
+};
+use mockito_core::mocks::manager::{
+    ActiveRoute as CoreActiveRoute, DuplicatePolicy as CoreDuplicatePolicy,
+    MocksManager as CoreMocksManager,
+};
+use mockito_core::types::collection::Collection;
+use mockito_core::types::group::Group;
+use mockito_core::types::preset::Preset;
+use mockito_core::types::route::{HttpMethod, Route, Transport};
+use mockito_core::types::variant::Variant;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// How definitions reusing an already added ID are handled
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Replace the added definition
+    Override,
+    /// Fail adding
+    Error,
+    /// Unite presets and variants of routes, replace other definitions
+    Merge,
+}
+
+impl From<DuplicatePolicy> for CoreDuplicatePolicy {
+    fn from(p: DuplicatePolicy) -> Self {
+        match p {
+            DuplicatePolicy::Override => CoreDuplicatePolicy::Override,
+            DuplicatePolicy::Error => CoreDuplicatePolicy::Error,
+            DuplicatePolicy::Merge => CoreDuplicatePolicy::Merge,
+        }
+    }
+}
+
+/// Request matched against active routes
+#[derive(Deserialize)]
+struct Request {
+    /// Request URL (path + query string)
+    url: String,
+    /// HTTP method (required for HTTP routes)
+    method: Option<HttpMethod>,
+    transport: Transport,
+    headers: Option<HashMap<String, String>>,
+    /// Query parameters (parsed from URL if not provided)
+    query: Option<HashMap<String, String>>,
+    payload: Option<Value>,
+}
+
+impl From<Request> for CoreRequest {
+    fn from(r: Request) -> Self {
+        Self {
+            url: r.url,
+            method: r.method,
+            transport: r.transport,
+            headers: r.headers,
+            query: r.query,
+            payload: r.payload,
+        }
+    }
+}
+
+/// Route with selected preset and variant, serialized as `{ route, preset, variant }`
+#[derive(Serialize)]
+struct ActiveRoute<'a> {
+    route: &'a Route,
+    preset: &'a Preset,
+    variant: &'a Variant,
+}
+
+impl<'a> From<&'a CoreActiveRoute> for ActiveRoute<'a> {
+    fn from(a: &'a CoreActiveRoute) -> Self {
+        Self {
+            route: &*a.route,
+            preset: a.preset(),
+            variant: a.variant(),
+        }
+    }
+}
+
+/// Manager of routes, collections and groups
+#[wasm_bindgen]
+pub struct MocksManager {
+    inner: CoreMocksManager,
+}
+
+#[wasm_bindgen]
+impl MocksManager {
+    /// Create an empty mocks manager
+    ///
+    /// @param duplicatePolicy - Handling of IDs added several times (default: Override)
+    #[wasm_bindgen(constructor)]
+    pub fn new(duplicate_policy: Option<DuplicatePolicy>) -> Self {
+        let policy = duplicate_policy.map(Into::into).unwrap_or_default();
+        Self {
+            inner: CoreMocksManager::with_duplicate_policy(policy),
+        }
+    }
+
+    /// Add routes, written like a routes file
+    ///
+    /// @throws Error if a route is malformed, or its ID is taken and the duplicate policy is `Error`
+    #[wasm_bindgen(js_name = addRoutes)]
+    pub fn add_routes(&mut self, routes: JsValue) -> Result<(), JsError> {
+        let routes: Vec<Route> = from_js(routes)?;
+        self.inner.add_routes(routes).map_err(js_error)
+    }
+
+    /// Add collections, written like a collections file
+    ///
+    /// @throws Error if a collection is malformed, or its ID is taken and the duplicate policy is `Error`
+    #[wasm_bindgen(js_name = addCollections)]
+    pub fn add_collections(&mut self, collections: JsValue) -> Result<(), JsError> {
+        let collections: Vec<Collection> = from_js(collections)?;
+        self.inner.add_collections(collections).map_err(js_error)
+    }
+
+    /// Add route groups, written like a groups file
+    ///
+    /// @throws Error if a group is malformed, or its ID is taken and the duplicate policy is `Error`
+    #[wasm_bindgen(js_name = addGroups)]
+    pub fn add_groups(&mut self, groups: JsValue) -> Result<(), JsError> {
+        let groups: Vec<Group> = from_js(groups)?;
+        self.inner.add_groups(groups).map_err(js_error)
+    }
+
+    /// Get all routes, sorted by ID
+    #[wasm_bindgen(js_name = listRoutes)]
+    pub fn list_routes(&self) -> Result<JsValue, JsError> {
+        to_js(&self.inner.list_routes())
+    }
+
+    /// Get all collections, sorted by ID
+    #[wasm_bindgen(js_name = listCollections)]
+    pub fn list_collections(&self) -> Result<JsValue, JsError> {
+        to_js(&self.inner.list_collections())
+    }
+
+    /// Resolve collection with inheritance and return active routes
+    ///
+    /// @throws Error if the collection or a referenced route doesn't exist
+    #[wasm_bindgen(js_name = resolveCollection)]
+    pub fn resolve_collection(&self, collection_id: &str) -> Result<JsValue, JsError> {
+        let active_routes = self
+            .inner
+            .resolve_collection(collection_id)
+            .map_err(js_error)?;
+        to_js(
+            &active_routes
+                .iter()
+                .map(ActiveRoute::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Validate routes and collections
+    ///
+    /// @returns Messages of found problems, e.g. `error: routes.users.presets[0]: ...`
+    pub fn validate(&self) -> Vec<String> {
+        self.inner
+            .validate()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// Controller of active routes, matching requests like the mock server
+#[wasm_bindgen]
+pub struct MocksController {
+    inner: CoreMocksController,
+}
+
+#[wasm_bindgen]
+impl MocksController {
+    /// Create a controller serving the routes of a mocks manager
+    ///
+    /// The controller works on a copy, so later changes to the manager don't affect it.
+    ///
+    /// @param manager - Mocks manager with routes and collections
+    /// @param defaultCollection - Optional default collection ID
+    /// @throws Error if the default collection can't be resolved
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        manager: &MocksManager,
+        default_collection: Option<String>,
+    ) -> Result<MocksController, JsError> {
+        let mut inner = CoreMocksController::new(manager.inner.clone());
+        if let Some(collection_id) = default_collection {
+            inner.use_collection(&collection_id).map_err(js_error)?;
+        }
+        Ok(Self { inner })
+    }
+
+    /// Switch to a collection
+    ///
+    /// @throws Error if the collection or a referenced route doesn't exist
+    #[wasm_bindgen(js_name = useCollection)]
+    pub fn use_collection(&mut self, collection_id: &str) -> Result<(), JsError> {
+        self.inner.use_collection(collection_id).map_err(js_error)
+    }
+
+    /// Activate routes by `route:preset:variant` references on top of the collection
+    ///
+    /// @throws Error if a reference can't be resolved
+    #[wasm_bindgen(js_name = useRoutes)]
+    pub fn use_routes(&mut self, routes: Vec<String>) -> Result<(), JsError> {
+        self.inner.use_routes(&routes).map_err(js_error)
+    }
+
+    /// Disable routes, so requests fall through to other routes
+    ///
+    /// @throws Error if a route doesn't exist
+    #[wasm_bindgen(js_name = disableRoutes)]
+    pub fn disable_routes(&mut self, route_ids: Vec<String>) -> Result<(), JsError> {
+        self.inner.disable_routes(&route_ids).map_err(js_error)
+    }
+
+    /// Enable routes disabled by `disableRoutes()`
+    ///
+    /// @throws Error if a route doesn't exist
+    #[wasm_bindgen(js_name = enableRoutes)]
+    pub fn enable_routes(&mut self, route_ids: Vec<String>) -> Result<(), JsError> {
+        self.inner.enable_routes(&route_ids).map_err(js_error)
+    }
+
+    /// Drop route overrides and go back to the routes of the collection
+    ///
+    /// @throws Error if the collection can't be resolved anymore
+    #[wasm_bindgen(js_name = resetRoutes)]
+    pub fn reset_routes(&mut self) -> Result<(), JsError> {
+        self.inner.reset_routes().map_err(js_error)
+    }
+
+    /// ID of the active collection
+    #[wasm_bindgen(js_name = currentCollection)]
+    pub fn current_collection(&self) -> Option<String> {
+        self.inner.active_collection_id().map(str::to_string)
+    }
+
+    /// Get active routes
+    #[wasm_bindgen(js_name = getActiveRoutes)]
+    pub fn get_active_routes(&self) -> Result<JsValue, JsError> {
+        let active_routes = self.inner.get_active_routes();
+        to_js(
+            &active_routes
+                .iter()
+                .map(ActiveRoute::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Find the active route matching a request
+    ///
+    /// @param request - `{ url, method, transport, headers?, query?, payload? }`
+    /// @returns Matching route or `null`
+    #[wasm_bindgen(js_name = findRoute)]
+    pub fn find_route(&self, request: JsValue) -> Result<JsValue, JsError> {
+        let request = CoreRequest::from(from_js::<Request>(request)?);
+        to_js(&self.inner.find_route(&request).map(ActiveRoute::from))
+    }
+
+    /// Find all active routes matching a request, in priority order
+    #[wasm_bindgen(js_name = findRoutes)]
+    pub fn find_routes(&self, request: JsValue) -> Result<JsValue, JsError> {
+        let request = CoreRequest::from(from_js::<Request>(request)?);
+        let found = self.inner.find_routes(&request);
+        to_js(&found.into_iter().map(ActiveRoute::from).collect::<Vec<_>>())
+    }
+
+    /// Find the active route matching a request and render its variant
+    ///
+    /// Templates in variant headers and body are rendered against the request.
+    ///
+    /// @returns Rendered variant or `null` if no route matches
+    /// @throws Error if a template fails to render
+    pub fn respond(&self, request: JsValue) -> Result<JsValue, JsError> {
+        let request = CoreRequest::from(from_js::<Request>(request)?);
+        let variant = self.inner.respond(&request).map_err(js_error)?;
+        to_js(&variant)
+    }
+}